@@ -0,0 +1,255 @@
+//! `awen`: run, validate, replay and inspect photonic IR and artifact bundles, and run
+//! calibration kernels, without writing Rust.
+
+use anyhow::{anyhow, Result};
+use awen_runtime::calibration::{
+    CalibrationExecutor, CalibrationKernel, CalibrationState, ReferenceCalibrationExecutor,
+};
+use awen_runtime::config::Config;
+use awen_runtime::ir::{self, Graph};
+use awen_runtime::namespace::Namespace;
+use awen_runtime::storage::{self, ArtifactBundle};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[clap(name = "awen", version, about = "Drive the AWEN photonic runtime")]
+struct Args {
+    /// Configuration file; defaults to `$AWEN_CONFIG`, else `./awen.toml` when present
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+    /// Override a configuration value, e.g. `--set engine.seed=7`; repeatable
+    #[clap(long = "set", global = true, value_name = "KEY=VALUE")]
+    overrides: Vec<String>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Run an IR graph on the reference engine and write its run bundle
+    Run {
+        /// Path to IR JSON file
+        ir: PathBuf,
+        /// RNG seed for deterministic replay; defaults to `engine.seed`
+        #[clap(long)]
+        seed: Option<u64>,
+        /// Directory the `awen_<id>` bundle is written under; defaults to
+        /// `storage.artifacts_dir`
+        #[clap(long)]
+        out_dir: Option<PathBuf>,
+        /// Namespace the run belongs to, by default `storage.namespace`; other than `default`,
+        /// its bundle goes under `<out-dir>/namespaces/<namespace>/`
+        #[clap(long)]
+        namespace: Option<String>,
+    },
+    /// Check an IR JSON file, or an artifact bundle and its IR; exits non-zero when invalid
+    Validate {
+        /// IR JSON file, bundle directory or `.tar.zst` archive
+        path: PathBuf,
+    },
+    /// Re-run an artifact bundle and compare against its stored results; exits non-zero on mismatch
+    Replay {
+        /// Artifact bundle directory or `.tar.zst` archive
+        bundle: PathBuf,
+        /// Print the JSON report instead of text
+        #[clap(long)]
+        json: bool,
+    },
+    /// Summarise an artifact bundle
+    Inspect {
+        /// Artifact bundle directory or `.tar.zst` archive
+        bundle: PathBuf,
+        /// Print the whole bundle as JSON instead of a summary
+        #[clap(long)]
+        json: bool,
+    },
+    /// Run a calibration kernel and write the resulting calibration state
+    Calibrate {
+        /// Calibration kernel JSON file
+        kernel: PathBuf,
+        /// Calibration state to start from; the result is its next version
+        #[clap(long)]
+        state: Option<PathBuf>,
+        /// Directory `calibration_state_v<version>.json` is written to
+        #[clap(long, default_value = ".")]
+        out_dir: PathBuf,
+    },
+    /// Print the resolved configuration as TOML; exits non-zero when it is invalid
+    Config,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = || Config::resolve(args.config.as_deref(), &args.overrides);
+    match args.command {
+        Command::Run {
+            ir,
+            seed,
+            out_dir,
+            namespace,
+        } => {
+            let config = config()?;
+            config.validate()?;
+            let graph = load_graph(&ir)?;
+            let namespace = match namespace {
+                Some(name) => Namespace::new(&name)?,
+                None => config.storage.namespace.clone(),
+            };
+            let out_dir = out_dir.unwrap_or_else(|| config.storage.artifacts_dir.clone());
+            let bundle = storage::run_artifact_on(
+                &config.engine()?,
+                &graph,
+                seed.or(config.engine.seed),
+                &namespace,
+                &namespace.dir(&out_dir),
+            )?;
+            if let Some(catalog) = &config.storage.catalog {
+                storage::index_in_catalog(catalog, &bundle)?;
+            }
+            println!("{}", bundle.display());
+        }
+        Command::Validate { path } => {
+            let what = validate(&path)?;
+            println!("{}: valid {}", path.display(), what);
+        }
+        Command::Replay { bundle, json } => {
+            let bundle = storage::import_bundle(&bundle)?;
+            let report = storage::verify_replay(&bundle)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report.to_text());
+            }
+            if !report.passed() {
+                std::process::exit(1);
+            }
+        }
+        Command::Inspect { bundle, json } => {
+            let bundle = storage::import_bundle(&bundle)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&bundle)?);
+            } else {
+                print!("{}", summarize(&bundle));
+            }
+        }
+        Command::Calibrate {
+            kernel,
+            state,
+            out_dir,
+        } => {
+            let kernel: CalibrationKernel = read_json(&kernel)?;
+            let previous: Option<CalibrationState> = state.as_deref().map(read_json).transpose()?;
+            let next = ReferenceCalibrationExecutor::new()
+                .execute_calibration(&kernel, previous.as_ref())?;
+            std::fs::create_dir_all(&out_dir)?;
+            let path = out_dir.join(format!("calibration_state_v{}.json", next.version));
+            std::fs::write(&path, serde_json::to_string_pretty(&next)?)?;
+            println!(
+                "{} v{} ({} nodes): {}",
+                next.calibration_id,
+                next.version,
+                next.node_calibrations.len(),
+                path.display()
+            );
+        }
+        Command::Config => {
+            let config = config()?;
+            print!("{}", toml::to_string_pretty(&config)?);
+            let problems = config.problems();
+            for problem in &problems {
+                eprintln!("error: {}", problem);
+            }
+            if !problems.is_empty() {
+                std::process::exit(1);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_graph(path: &Path) -> Result<Graph> {
+    let graph: Graph = read_json(path)?;
+    ir::validate_graph(&graph).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    Ok(graph)
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&data).map_err(|e| anyhow!("invalid {}: {}", path.display(), e))
+}
+
+/// Validate `path` as a bundle when it is a directory or archive, else as IR. Returns what it was.
+fn validate(path: &Path) -> Result<String> {
+    let is_archive = path.to_string_lossy().contains(".tar.zst");
+    if path.is_dir() || is_archive {
+        let bundle = storage::import_bundle(path)?;
+        storage::validate_bundle(&bundle)?;
+        ir::validate_graph(&bundle.ir_original).map_err(|e| anyhow!("bundle IR: {}", e))?;
+        Ok(format!("bundle {}", bundle.artifact_id))
+    } else {
+        let graph = load_graph(path)?;
+        Ok(format!(
+            "IR ({} nodes, {} edges)",
+            graph.nodes.len(),
+            graph.edges.len()
+        ))
+    }
+}
+
+fn summarize(bundle: &ArtifactBundle) -> String {
+    let present = |set: bool| if set { "yes" } else { "no" };
+    let mut out = String::new();
+    let mut line = |key: &str, value: String| out.push_str(&format!("{:<14}{}\n", key, value));
+    line("artifact", bundle.artifact_id.clone());
+    line("type", bundle.manifest.artifact_type.clone());
+    line("namespace", bundle.namespace().to_string());
+    line("created", bundle.manifest.created_at.clone());
+    line("runtime", bundle.manifest.awen_runtime_version.clone());
+    line("schema", bundle.manifest.schema_version.clone());
+    line(
+        "seed",
+        bundle
+            .seed
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "none".to_string()),
+    );
+    line(
+        "ir",
+        format!(
+            "{} nodes, {} edges",
+            bundle.ir_original.nodes.len(),
+            bundle.ir_original.edges.len()
+        ),
+    );
+    line("parameters", bundle.parameters_initial.len().to_string());
+    line(
+        "calibration",
+        format!(
+            "initial {}, final {}",
+            present(bundle.calibration_state_initial.is_some()),
+            present(bundle.calibration_state_final.is_some())
+        ),
+    );
+    line(
+        "files",
+        format!("{} checksummed", bundle.manifest.checksums.len()),
+    );
+    if let Some(obs) = &bundle.observability {
+        let kinds: Vec<&str> = [
+            ("traces", obs.traces.is_some()),
+            ("timeline", obs.timeline.is_some()),
+            ("metrics", obs.metrics.is_some()),
+            ("events", obs.events.is_some()),
+            ("states", obs.states.is_some()),
+            ("measurements", obs.measurements.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(kind, _)| kind)
+        .collect();
+        line("observability", kinds.join(", "));
+    }
+    out
+}
@@ -0,0 +1,253 @@
+//! End-to-end tests of the `awen` binary
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn awen(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_awen"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("awen should start")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "awen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn example_ir(dir: &Path) -> PathBuf {
+    let path = dir.join("ir.json");
+    let ir = serde_json::json!({
+        "nodes": [
+            {"id": "mzi_0", "type": "MZI", "params": {"phase": 0.1}},
+            {"id": "mzi_1", "type": "MZI", "params": {"phase": 0.2}}
+        ],
+        "edges": []
+    });
+    std::fs::write(&path, ir.to_string()).unwrap();
+    path
+}
+
+#[test]
+fn test_run_inspect_validate_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    example_ir(dir.path());
+    let validated = stdout(&awen(dir.path(), &["validate", "ir.json"]));
+    assert_eq!(validated.trim(), "ir.json: valid IR (2 nodes, 0 edges)");
+
+    let out = awen(
+        dir.path(),
+        &["run", "ir.json", "--seed", "42", "--out-dir", "artifacts"],
+    );
+    let bundle = stdout(&out).trim().to_string();
+    assert!(bundle.starts_with("artifacts/awen_"), "{}", bundle);
+    // Only the exported bundle is left behind
+    assert_eq!(
+        std::fs::read_dir(dir.path().join("artifacts"))
+            .unwrap()
+            .count(),
+        1
+    );
+
+    let summary = stdout(&awen(dir.path(), &["inspect", &bundle]));
+    assert!(summary.contains("type          run"), "{}", summary);
+    assert!(summary.contains("seed          42"), "{}", summary);
+    assert!(summary.contains("namespace     default"), "{}", summary);
+    assert!(
+        summary.contains("ir            2 nodes, 0 edges"),
+        "{}",
+        summary
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout(&awen(dir.path(), &["inspect", &bundle, "--json"]))).unwrap();
+    assert_eq!(json["parameters_initial"]["mzi_1:phase"], 0.2);
+
+    let validated = stdout(&awen(dir.path(), &["validate", &bundle]));
+    assert!(validated.contains("valid bundle awen_"), "{}", validated);
+    let replay = stdout(&awen(dir.path(), &["replay", &bundle]));
+    assert!(replay.contains(": PASS"), "{}", replay);
+
+    let out = awen(
+        dir.path(),
+        &[
+            "run",
+            "ir.json",
+            "--out-dir",
+            "artifacts",
+            "--namespace",
+            "optics",
+        ],
+    );
+    let bundle = stdout(&out).trim().to_string();
+    assert!(
+        bundle.starts_with("artifacts/namespaces/optics/awen_"),
+        "{}",
+        bundle
+    );
+    let summary = stdout(&awen(dir.path(), &["inspect", &bundle]));
+    assert!(summary.contains("namespace     optics"), "{}", summary);
+    assert!(
+        !awen(dir.path(), &["run", "ir.json", "--namespace", "../x"])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn test_invalid_inputs_exit_non_zero() {
+    let dir = tempfile::tempdir().unwrap();
+    let ir = serde_json::json!({
+        "nodes": [{
+            "id": "m0", "type": "DETECTOR", "params": {},
+            "conditional_branches": [{"outcome_index": 1, "then_nodes": ["ghost"]}]
+        }]
+    });
+    std::fs::write(dir.path().join("bad.json"), ir.to_string()).unwrap();
+    let out = awen(dir.path(), &["validate", "bad.json"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("non-existent node: ghost"), "{}", stderr);
+
+    assert!(!awen(dir.path(), &["run", "missing.json"]).status.success());
+    assert!(!awen(dir.path(), &["inspect", "nowhere"]).status.success());
+}
+
+#[test]
+fn test_calibrate_versions_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let kernel = serde_json::json!({
+        "id": "phase-trim",
+        "target_nodes": ["mzi_0"],
+        "parameters_to_tune": ["phase"],
+        "cost_function": {"Minimize": {"expression": "loss", "target_value": null}},
+        "measurement_sequence": [],
+        "optimizer_config": {
+            "algorithm": {"NelderMead": {"initial_simplex_size": 0.1}},
+            "max_iterations": 2,
+            "convergence_threshold": 0.0,
+            "initial_guess": null
+        },
+        "safety_constraints": {
+            "hard_limits": {},
+            "soft_limits": {},
+            "max_optical_power_dbm": 10.0,
+            "timeout_seconds": 60
+        },
+        "schedule": "Manual"
+    });
+    std::fs::write(dir.path().join("kernel.json"), kernel.to_string()).unwrap();
+
+    let first = stdout(&awen(dir.path(), &["calibrate", "kernel.json"]));
+    assert!(first.contains(" v1 (1 nodes): "), "{}", first);
+    let second = stdout(&awen(
+        dir.path(),
+        &[
+            "calibrate",
+            "kernel.json",
+            "--state",
+            "calibration_state_v1.json",
+            "--out-dir",
+            "states",
+        ],
+    ));
+    assert!(second.contains(" v2 (1 nodes): "), "{}", second);
+
+    let read = |path: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join(path)).unwrap()).unwrap()
+    };
+    let (v1, v2) = (
+        read("calibration_state_v1.json"),
+        read("states/calibration_state_v2.json"),
+    );
+    assert_eq!(
+        v2["provenance"]["parent_calibration_id"],
+        v1["calibration_id"]
+    );
+    assert_eq!(v2["provenance"]["calibration_kernel_id"], "phase-trim");
+}
+
+#[test]
+fn test_config_file_and_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    example_ir(dir.path());
+    std::fs::write(
+        dir.path().join("awen.toml"),
+        "[engine]\nseed = 9\n\n[storage]\nartifacts_dir = \"runs\"\nnamespace = \"lab-a\"\n",
+    )
+    .unwrap();
+
+    let bundle = stdout(&awen(dir.path(), &["run", "ir.json"]));
+    assert!(
+        bundle.trim().starts_with("runs/namespaces/lab-a/awen_"),
+        "{}",
+        bundle
+    );
+    let summary = stdout(&awen(dir.path(), &["inspect", bundle.trim()]));
+    assert!(summary.contains("seed          9"), "{}", summary);
+
+    let resolved = stdout(&awen(
+        dir.path(),
+        &[
+            "config",
+            "--set",
+            "engine.seed=3",
+            "--set",
+            "observability.log=debug",
+        ],
+    ));
+    assert!(resolved.contains("seed = 3"), "{}", resolved);
+    assert!(resolved.contains("log = \"debug\""), "{}", resolved);
+    assert!(
+        resolved.contains("artifacts_dir = \"runs\""),
+        "{}",
+        resolved
+    );
+
+    let invalid = awen(
+        dir.path(),
+        &[
+            "config",
+            "--set",
+            "safety.min_voltage=5",
+            "--set",
+            "safety.max_voltage=1",
+        ],
+    );
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("voltage"));
+    let rejected = awen(dir.path(), &["run", "ir.json", "--set", "engine.bogus=1"]);
+    assert!(!rejected.status.success());
+}
+
+#[test]
+fn test_run_indexes_into_configured_catalog() {
+    let dir = tempfile::tempdir().unwrap();
+    example_ir(dir.path());
+    let run = awen(
+        dir.path(),
+        &["run", "ir.json", "--set", "storage.catalog=runs.sqlite"],
+    );
+    #[cfg(not(feature = "catalog"))]
+    {
+        assert!(!run.status.success());
+        assert!(String::from_utf8_lossy(&run.stderr).contains("catalog"));
+    }
+    #[cfg(feature = "catalog")]
+    {
+        let bundle = dir.path().join(stdout(&run).trim());
+        let run_id = awen_runtime::storage::import_bundle(&bundle)
+            .unwrap()
+            .artifact_id;
+        let entry = awen_runtime::storage::RunCatalog::open(dir.path().join("runs.sqlite"))
+            .unwrap()
+            .get(&run_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.run_id, run_id);
+    }
+}
@@ -0,0 +1,49 @@
+/*
+ * C API of the AWEN runtime (crate awen-ffi, library libawen).
+ *
+ * Handles are opaque and owned by the caller until passed to their _free function.
+ * Returned strings are UTF-8, NUL terminated and borrowed from the handle they came from.
+ * A failing call returns NULL; awen_last_error() then gives the reason for this thread.
+ */
+#ifndef AWEN_H
+#define AWEN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct AwenGraph AwenGraph;
+typedef struct AwenRun AwenRun;
+
+/* Runtime version, e.g. "0.1.0"; static. */
+const char *awen_version(void);
+
+/* Reason the last failing call on this thread failed; empty when none has. Valid until the
+ * next failing call on this thread. */
+const char *awen_last_error(void);
+
+/* Parse and validate an IR graph from JSON. NULL when the JSON or the graph is invalid. */
+AwenGraph *awen_graph_load(const char *ir_json);
+
+/* Number of nodes of graph; 0 for NULL. */
+size_t awen_graph_node_count(const AwenGraph *graph);
+
+void awen_graph_free(AwenGraph *graph);
+
+/* Run graph on the reference engine. The same graph and seed give the same results.
+ * NULL when the run fails. */
+AwenRun *awen_run(const AwenGraph *graph, uint64_t seed);
+
+/* Results of run as JSON; valid until the run is freed. NULL for NULL. */
+const char *awen_run_results(const AwenRun *run);
+
+void awen_run_free(AwenRun *run);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AWEN_H */
@@ -0,0 +1,159 @@
+//! C API for embedding the AWEN runtime in LabVIEW, C++ or any other host that can call C.
+//!
+//! The API is declared in `include/awen.h`. A host loads an IR graph from JSON, runs it on the
+//! reference engine with a seed, and reads the run's results as JSON:
+//!
+//! ```c
+//! AwenGraph *graph = awen_graph_load(ir_json);
+//! AwenRun *run = graph ? awen_run(graph, 42) : NULL;
+//! if (!run) fprintf(stderr, "%s\n", awen_last_error());
+//! else puts(awen_run_results(run));
+//! awen_run_free(run);
+//! awen_graph_free(graph);
+//! ```
+//!
+//! Handles are opaque and owned by the caller until passed to their `_free` function. Strings
+//! returned by the API are UTF-8, NUL terminated and borrowed from the handle they came from.
+//! A failing call returns `NULL` and leaves its reason in [`awen_last_error`], per thread. No
+//! call unwinds into the host: a panic is reported as an error.
+
+use anyhow::{anyhow, Result};
+use awen_runtime::engine::Engine;
+use awen_runtime::ir::{self, Graph};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// An IR graph that passed validation
+pub struct AwenGraph {
+    graph: Graph,
+}
+
+/// The results of one run
+pub struct AwenRun {
+    results: CString,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Run `f`, turning an error or a panic into `NULL` and the thread's last error.
+fn guard<T>(f: impl FnOnce() -> Result<T>) -> *mut T {
+    let outcome = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let reason = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(anyhow!("panic: {}", reason))
+    });
+    let message = match outcome {
+        Ok(value) => return Box::into_raw(Box::new(value)),
+        Err(e) => format!("{:#}", e),
+    };
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    });
+    ptr::null_mut()
+}
+
+fn read_str<'a>(s: *const c_char, what: &str) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(anyhow!("{} is NULL", what));
+    }
+    // SAFETY: callers of the API guarantee `s` is a NUL-terminated string
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| anyhow!("{} is not UTF-8: {}", what, e))
+}
+
+/// Version of the runtime, e.g. `"0.1.0"`. The string is static.
+#[no_mangle]
+pub extern "C" fn awen_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Reason the last failing call on this thread failed; empty when none has. The string stays
+/// valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn awen_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Parse and validate an IR graph. Returns `NULL` when the JSON or the graph is invalid.
+///
+/// # Safety
+///
+/// `ir_json` must be `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn awen_graph_load(ir_json: *const c_char) -> *mut AwenGraph {
+    guard(|| {
+        let graph: Graph = serde_json::from_str(read_str(ir_json, "IR JSON")?)
+            .map_err(|e| anyhow!("invalid IR JSON: {}", e))?;
+        ir::validate_graph(&graph).map_err(|e| anyhow!("invalid IR: {}", e))?;
+        Ok(AwenGraph { graph })
+    })
+}
+
+/// Number of nodes of `graph`, or 0 for `NULL`.
+///
+/// # Safety
+///
+/// `graph` must be `NULL` or a live handle from [`awen_graph_load`].
+#[no_mangle]
+pub unsafe extern "C" fn awen_graph_node_count(graph: *const AwenGraph) -> usize {
+    graph.as_ref().map_or(0, |g| g.graph.nodes.len())
+}
+
+/// # Safety
+///
+/// `graph` must be `NULL` or a handle from [`awen_graph_load`] not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn awen_graph_free(graph: *mut AwenGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Run `graph` on the reference engine with `seed`. The same graph and seed give the same
+/// results. Returns `NULL` when the run fails.
+///
+/// # Safety
+///
+/// `graph` must be `NULL` or a live handle from [`awen_graph_load`].
+#[no_mangle]
+pub unsafe extern "C" fn awen_run(graph: *const AwenGraph, seed: u64) -> *mut AwenRun {
+    let graph = graph.as_ref();
+    guard(|| {
+        let graph = graph.ok_or_else(|| anyhow!("graph is NULL"))?;
+        let scratch = tempfile::tempdir()?;
+        let run_dir = Engine::new().run_graph_in(&graph.graph, Some(seed), scratch.path())?;
+        let results = std::fs::read_to_string(run_dir.join("results.json"))?;
+        Ok(AwenRun {
+            results: CString::new(results)?,
+        })
+    })
+}
+
+/// Results of `run` as JSON, or `NULL` for a `NULL` run. The string is valid until the run is
+/// freed.
+///
+/// # Safety
+///
+/// `run` must be `NULL` or a live handle from [`awen_run`].
+#[no_mangle]
+pub unsafe extern "C" fn awen_run_results(run: *const AwenRun) -> *const c_char {
+    run.as_ref().map_or(ptr::null(), |r| r.results.as_ptr())
+}
+
+/// # Safety
+///
+/// `run` must be `NULL` or a handle from [`awen_run`] not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn awen_run_free(run: *mut AwenRun) {
+    if !run.is_null() {
+        drop(Box::from_raw(run));
+    }
+}
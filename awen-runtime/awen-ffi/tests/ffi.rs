@@ -0,0 +1,77 @@
+//! The C API, called through the same symbols a C host links against
+
+use awen::*;
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+fn ir(json: &str) -> CString {
+    CString::new(json).unwrap()
+}
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(awen_last_error()) }
+        .to_string_lossy()
+        .to_string()
+}
+
+const GRAPH: &str = r#"{
+    "nodes": [
+        {"id": "mzi_0", "type": "MZI", "params": {"phase": 0.1}},
+        {"id": "mzi_1", "type": "MZI", "params": {"phase": 0.2}}
+    ],
+    "edges": []
+}"#;
+
+#[test]
+fn test_load_run_and_free() {
+    let version = unsafe { CStr::from_ptr(awen_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+
+    unsafe {
+        let graph = awen_graph_load(ir(GRAPH).as_ptr());
+        assert!(!graph.is_null(), "{}", last_error());
+        assert_eq!(awen_graph_node_count(graph), 2);
+
+        let results = |seed| {
+            let run = awen_run(graph, seed);
+            assert!(!run.is_null(), "{}", last_error());
+            let json: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(awen_run_results(run)).to_str().unwrap())
+                    .unwrap();
+            awen_run_free(run);
+            json
+        };
+        assert_eq!(results(7), results(7));
+
+        awen_graph_free(graph);
+    }
+}
+
+#[test]
+fn test_errors_return_null() {
+    unsafe {
+        assert!(awen_graph_load(ptr::null()).is_null());
+        assert_eq!(last_error(), "IR JSON is NULL");
+
+        assert!(awen_graph_load(ir("not json").as_ptr()).is_null());
+        assert!(
+            last_error().starts_with("invalid IR JSON"),
+            "{}",
+            last_error()
+        );
+
+        let dangling = r#"{"nodes": [{
+            "id": "m0", "type": "DETECTOR", "params": {},
+            "conditional_branches": [{"outcome_index": 1, "then_nodes": ["ghost"]}]
+        }]}"#;
+        assert!(awen_graph_load(ir(dangling).as_ptr()).is_null());
+        assert!(last_error().starts_with("invalid IR:"), "{}", last_error());
+
+        assert!(awen_run(ptr::null(), 1).is_null());
+        assert_eq!(last_error(), "graph is NULL");
+        assert!(awen_run_results(ptr::null()).is_null());
+        assert_eq!(awen_graph_node_count(ptr::null()), 0);
+        awen_graph_free(ptr::null_mut());
+        awen_run_free(ptr::null_mut());
+    }
+}
@@ -0,0 +1,152 @@
+//! Minimal HTTP/1.1: one request per connection, answered then closed.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+/// Longest request head accepted.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    /// Header names are lower-cased
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Token of an `Authorization: Bearer <token>` header
+    pub fn bearer_token(&self) -> Option<&str> {
+        self.headers
+            .get("authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim)
+    }
+
+    /// Read one request, answering malformed or oversized ones with an error response.
+    pub fn read(stream: &mut TcpStream, max_body_bytes: usize) -> Result<Self, Response> {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let head_end = loop {
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos;
+            }
+            if buf.len() > MAX_HEAD_BYTES {
+                return Err(Response::error(431, "request head too large"));
+            }
+            match stream.read(&mut chunk) {
+                Ok(0) | Err(_) => return Err(Response::error(400, "incomplete request")),
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+        let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+            return Err(Response::error(400, "malformed request line"));
+        };
+        let headers: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+            .collect();
+
+        let length = match headers.get("content-length") {
+            Some(v) => v
+                .parse::<usize>()
+                .map_err(|_| Response::error(400, "invalid content-length"))?,
+            None => 0,
+        };
+        if length > max_body_bytes {
+            return Err(Response::error(
+                413,
+                &format!("body of {} bytes exceeds {}", length, max_body_bytes),
+            ));
+        }
+        let mut body = buf[head_end + 4..].to_vec();
+        while body.len() < length {
+            match stream.read(&mut chunk) {
+                Ok(0) | Err(_) => return Err(Response::error(400, "incomplete body")),
+                Ok(n) => body.extend_from_slice(&chunk[..n]),
+            }
+        }
+        body.truncate(length);
+
+        Ok(Self {
+            method: method.to_string(),
+            path: target.split('?').next().unwrap_or_default().to_string(),
+            headers,
+            body,
+        })
+    }
+}
+
+pub(crate) struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, value: &impl Serialize) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec_pretty(value).unwrap_or_default(),
+        }
+    }
+
+    /// `{"error": message}`
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message }))
+    }
+
+    pub fn bytes(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    pub fn write_to(&self, stream: &mut TcpStream) -> std::io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            reason(self.status),
+            self.content_type,
+            self.body.len()
+        )?;
+        stream.write_all(&self.body)?;
+        stream.flush()
+    }
+}
+
+/// Start an open-ended `text/event-stream` response.
+pub(crate) fn start_event_stream(stream: &mut TcpStream) -> std::io::Result<()> {
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\n\
+          Content-Type: text/event-stream\r\n\
+          Cache-Control: no-cache\r\n\
+          Connection: close\r\n\r\n",
+    )?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,56 @@
+//! Jobs as clients see them: a summary of each [`QueuedJob`] without its graph or history.
+
+use awen_runtime::namespace::Namespace;
+use awen_runtime::queue::{JobPriority, JobStatus, QueuedJob};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub submitted_by: String,
+    pub namespace: Namespace,
+    pub priority: JobPriority,
+    pub seed: Option<u64>,
+    pub nodes: usize,
+    /// Device session the job is pinned to, if any
+    pub affinity: Option<String>,
+    /// Device session of the current or last attempt
+    pub device: Option<String>,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub submitted_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Error of the last failed attempt
+    pub error: Option<String>,
+    /// Id of the run's artifact bundle, once it succeeded
+    pub artifact_id: Option<String>,
+}
+
+impl From<&QueuedJob> for Job {
+    fn from(job: &QueuedJob) -> Self {
+        Self {
+            job_id: job.job_id.clone(),
+            status: job.status,
+            submitted_by: job.request.user.clone(),
+            namespace: job.request.namespace.clone(),
+            priority: job.request.priority,
+            seed: job.request.seed,
+            nodes: job.request.graph.nodes.len(),
+            affinity: job.request.device.clone(),
+            device: job.device.clone(),
+            attempts: job.attempts,
+            max_attempts: job.request.max_attempts,
+            submitted_at: job.submitted_at.clone(),
+            started_at: job.started_at.clone(),
+            finished_at: job.finished_at.clone(),
+            error: job.errors.last().cloned(),
+            artifact_id: job
+                .artifact
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string()),
+        }
+    }
+}
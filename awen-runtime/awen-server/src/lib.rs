@@ -0,0 +1,433 @@
+//! `awen-server`: the AWEN runtime as a shared lab service.
+//!
+//! [`JobServer`] accepts IR graphs over HTTP and keeps them in a persistent [`JobQueue`] at
+//! `<data_dir>/queue.json`, which survives restarts. One device session per configured device
+//! runs the queued jobs on the reference engine, one at a time, each becoming one replayable
+//! artifact bundle. Requests and responses are JSON; errors are `{"error": "<reason>"}`.
+//!
+//! | Request | Response |
+//! |---|---|
+//! | `GET /health` | `{"status": "ok", "jobs": <count>, "devices": [<session>]}` |
+//! | `POST /jobs` with `{"ir": <graph>, "seed"?, "priority"?, "device"?, "max_attempts"?, "namespace"?}` | `202` and the queued [`Job`] |
+//! | `GET /jobs` | Every [`Job`] the caller can see, oldest first |
+//! | `GET /jobs/<id>` | The [`Job`] |
+//! | `GET /jobs/<id>/events` | Server-Sent Events, one `status` event per [`JobEvent`](awen_runtime::queue::JobEvent), closed once the job has finished |
+//! | `GET /jobs/<id>/artifacts` | Paths of the files in the job's bundle; `409` until it succeeded |
+//! | `GET /jobs/<id>/artifacts/<path>` | One bundle file |
+//!
+//! With an [`AccessControl`] installed, requests carry `Authorization: Bearer <token>`.
+//! Submitting needs `run_simulation`; everything else except `/health` needs `read_artifacts`.
+//! A missing or unknown token gets `401`, a role below the one needed `403`. Without access
+//! control every caller is `anonymous`. The caller is the user jobs are shared out by.
+//!
+//! Every job belongs to a namespace, by default the caller token's own one or `default`. A
+//! namespaced token can neither submit to nor see jobs of other namespaces: they get `403` and
+//! `404` respectively. Bundles of a namespace are written under its directory of
+//! `<data_dir>/jobs`.
+
+mod http;
+mod jobs;
+
+pub use jobs::Job;
+
+use anyhow::{anyhow, Result};
+use awen_runtime::ir::Graph;
+use awen_runtime::namespace::Namespace;
+use awen_runtime::observability::EventSink;
+use awen_runtime::queue::{
+    EngineJobExecutor, JobPriority, JobQueue, JobRequest, QueueRunner, DEFAULT_MAX_ATTEMPTS,
+};
+use awen_runtime::{AccessControl, Identity, Role, RuntimeAction};
+use http::{Request, Response};
+use serde::Deserialize;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Poll interval of the accept loop.
+const ACCEPT_POLL: Duration = Duration::from_millis(10);
+
+/// Longest a client may take to send its request, or to accept a response chunk.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often an event stream checks for new job events.
+const EVENT_POLL: Duration = Duration::from_millis(200);
+
+pub struct ServerConfig {
+    /// Holds `queue.json`; bundles are written under `<data_dir>/jobs/<job_id>/`
+    pub data_dir: PathBuf,
+    /// Device sessions, each running one job at a time
+    pub devices: Vec<String>,
+    pub access: Option<AccessControl>,
+    /// Largest request body accepted
+    pub max_body_bytes: usize,
+    pub events: EventSink,
+    /// Run catalog every job bundle is indexed into; needs the `catalog` feature
+    pub catalog: Option<PathBuf>,
+}
+
+impl ServerConfig {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            devices: vec!["simulator-0".to_string(), "simulator-1".to_string()],
+            access: None,
+            max_body_bytes: 16 * 1024 * 1024,
+            events: EventSink::new(),
+            catalog: None,
+        }
+    }
+
+    pub fn with_devices(mut self, devices: &[&str]) -> Self {
+        self.devices = devices.iter().map(|d| d.to_string()).collect();
+        self
+    }
+
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Require API tokens, checked against `access`.
+    pub fn with_access_control(mut self, access: AccessControl) -> Self {
+        self.access = Some(access);
+        self
+    }
+
+    pub fn with_catalog(mut self, catalog: &Path) -> Self {
+        self.catalog = Some(catalog.to_path_buf());
+        self
+    }
+}
+
+/// Body of `POST /jobs`
+#[derive(Deserialize)]
+struct Submission {
+    ir: Graph,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    priority: JobPriority,
+    /// Device session the job must run on
+    #[serde(default)]
+    device: Option<String>,
+    #[serde(default)]
+    max_attempts: Option<u32>,
+    #[serde(default)]
+    namespace: Option<Namespace>,
+}
+
+struct Context {
+    queue: Arc<JobQueue>,
+    devices: Vec<String>,
+    access: Option<AccessControl>,
+    max_body_bytes: usize,
+    stop: AtomicBool,
+}
+
+/// HTTP front end to a [`JobQueue`]. Dropping it stops accepting connections, then the device
+/// sessions once their current jobs finish.
+pub struct JobServer {
+    local_addr: SocketAddr,
+    context: Arc<Context>,
+    accept_thread: Option<JoinHandle<()>>,
+    runner: QueueRunner,
+}
+
+impl JobServer {
+    /// Listen on `addr` (e.g. `"127.0.0.1:0"` for any free port).
+    pub fn bind(addr: &str, config: ServerConfig) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        if config.devices.is_empty() {
+            return Err(anyhow!("no device sessions configured"));
+        }
+        let queue = Arc::new(JobQueue::open(&config.data_dir.join("queue.json"))?);
+        let mut executor = EngineJobExecutor::new(&config.data_dir.join("jobs"));
+        if let Some(catalog) = &config.catalog {
+            executor = executor.with_catalog(catalog);
+        }
+        let runner = QueueRunner::start(
+            Arc::clone(&queue),
+            &config.devices,
+            Arc::new(executor),
+            config.events,
+        );
+        let context = Arc::new(Context {
+            queue,
+            devices: config.devices,
+            access: config.access,
+            max_body_bytes: config.max_body_bytes,
+            stop: AtomicBool::new(false),
+        });
+        let accept_thread = {
+            let context = Arc::clone(&context);
+            std::thread::spawn(move || {
+                while !context.stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let context = Arc::clone(&context);
+                            std::thread::spawn(move || serve(&context, stream));
+                        }
+                        Err(_) => std::thread::sleep(ACCEPT_POLL),
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            local_addr,
+            context,
+            accept_thread: Some(accept_thread),
+            runner,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn queue(&self) -> &Arc<JobQueue> {
+        &self.context.queue
+    }
+}
+
+impl Drop for JobServer {
+    fn drop(&mut self) {
+        self.context.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.accept_thread.take() {
+            let _ = thread.join();
+        }
+        self.runner.stop();
+    }
+}
+
+fn serve(context: &Context, mut stream: TcpStream) {
+    if stream.set_nonblocking(false).is_err()
+        || stream.set_read_timeout(Some(CLIENT_TIMEOUT)).is_err()
+        || stream.set_write_timeout(Some(CLIENT_TIMEOUT)).is_err()
+    {
+        return;
+    }
+    let response = match Request::read(&mut stream, context.max_body_bytes) {
+        Ok(request) => match route(context, &request) {
+            Route::Respond(response) => response,
+            Route::Events(job_id) => {
+                stream_events(context, &mut stream, &job_id);
+                return;
+            }
+        },
+        Err(response) => response,
+    };
+    let _ = response.write_to(&mut stream);
+}
+
+enum Route {
+    Respond(Response),
+    Events(String),
+}
+
+fn route(context: &Context, request: &Request) -> Route {
+    let segments: Vec<&str> = request.path.trim_matches('/').splitn(4, '/').collect();
+    let respond = Route::Respond;
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["health"]) => respond(Response::json(
+            200,
+            &serde_json::json!({"status": "ok", "jobs": context.queue.list().len(), "devices": context.devices}),
+        )),
+        ("POST", ["jobs"]) => respond(
+            match authorize(context, request, RuntimeAction::RunSimulation) {
+                Ok(caller) => submit(context, request, &caller),
+                Err(response) => response,
+            },
+        ),
+        ("GET", ["jobs", rest @ ..]) => {
+            let caller = match authorize(context, request, RuntimeAction::ReadArtifacts) {
+                Ok(caller) => caller,
+                Err(response) => return respond(response),
+            };
+            let visible = |job_id: &str| {
+                context
+                    .queue
+                    .get(job_id)
+                    .is_some_and(|job| caller.reaches(&job.request.namespace))
+            };
+            match rest {
+                [] => respond(Response::json(
+                    200,
+                    &context
+                        .queue
+                        .list()
+                        .iter()
+                        .filter(|job| caller.reaches(&job.request.namespace))
+                        .map(Job::from)
+                        .collect::<Vec<_>>(),
+                )),
+                [job_id, ..] if !visible(job_id) => {
+                    respond(Response::error(404, &format!("no job {}", job_id)))
+                }
+                [job_id] => respond(Response::json(
+                    200,
+                    &context.queue.get(job_id).as_ref().map(Job::from),
+                )),
+                [job_id, "events"] => Route::Events(job_id.to_string()),
+                [job_id, "artifacts"] => respond(list_artifacts(context, job_id)),
+                [job_id, "artifacts", path] => respond(download(context, job_id, path)),
+                _ => respond(Response::error(404, "not found")),
+            }
+        }
+        (_, ["health"]) | (_, ["jobs", ..]) => respond(Response::error(
+            405,
+            &format!("{} not allowed on {}", request.method, request.path),
+        )),
+        _ => respond(Response::error(404, "not found")),
+    }
+}
+
+/// Identity of the caller allowed to perform `action`
+fn authorize(
+    context: &Context,
+    request: &Request,
+    action: RuntimeAction,
+) -> Result<Identity, Response> {
+    let Some(access) = &context.access else {
+        return Ok(Identity {
+            name: "anonymous".to_string(),
+            role: Role::Admin,
+            namespace: None,
+        });
+    };
+    access
+        .authorize(request.bearer_token(), action)
+        .map_err(|e| {
+            let status = if e.is_unauthenticated() { 401 } else { 403 };
+            Response::error(status, &e.to_string())
+        })
+}
+
+fn submit(context: &Context, request: &Request, caller: &Identity) -> Response {
+    let submission: Submission = match serde_json::from_slice(&request.body) {
+        Ok(submission) => submission,
+        Err(e) => return Response::error(400, &format!("invalid job: {}", e)),
+    };
+    let namespace = submission
+        .namespace
+        .or_else(|| caller.namespace.clone())
+        .unwrap_or_default();
+    if !caller.reaches(&namespace) {
+        return Response::error(
+            403,
+            &format!("{} cannot submit to namespace {}", caller.name, namespace),
+        );
+    }
+    let mut job = JobRequest::new(submission.ir, &caller.name)
+        .in_namespace(&namespace)
+        .with_priority(submission.priority)
+        .with_max_attempts(submission.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS));
+    if let Some(seed) = submission.seed {
+        job = job.with_seed(seed);
+    }
+    if let Some(device) = &submission.device {
+        if !context.devices.contains(device) {
+            return Response::error(400, &format!("no device session {}", device));
+        }
+        job = job.on_device(device);
+    }
+    match context.queue.submit(job) {
+        Ok(job) => Response::json(202, &Job::from(&job)),
+        Err(e) => Response::error(400, &e.to_string()),
+    }
+}
+
+/// Bundle directory of a succeeded job
+fn bundle_dir(context: &Context, job_id: &str) -> Result<PathBuf, Response> {
+    let job = context
+        .queue
+        .get(job_id)
+        .ok_or_else(|| Response::error(404, &format!("no job {}", job_id)))?;
+    job.artifact.ok_or_else(|| {
+        Response::error(
+            409,
+            &format!("job {} is {}, no artifacts", job_id, job.status.as_str()),
+        )
+    })
+}
+
+fn list_artifacts(context: &Context, job_id: &str) -> Response {
+    let dir = match bundle_dir(context, job_id) {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    let mut files: Vec<String> = walkdir::WalkDir::new(&dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            e.path()
+                .strip_prefix(&dir)
+                .ok()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    files.sort();
+    Response::json(200, &files)
+}
+
+fn download(context: &Context, job_id: &str, path: &str) -> Response {
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Response::error(400, &format!("invalid artifact path {}", path));
+    }
+    let dir = match bundle_dir(context, job_id) {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    match std::fs::read(dir.join(relative)) {
+        Ok(body) => {
+            let content_type = match relative.extension().and_then(|e| e.to_str()) {
+                Some("json") => "application/json",
+                Some("jsonl") => "application/x-ndjson",
+                Some("txt") => "text/plain",
+                _ => "application/octet-stream",
+            };
+            Response::bytes(content_type, body)
+        }
+        Err(_) => Response::error(404, &format!("no artifact {} in job {}", path, job_id)),
+    }
+}
+
+/// Send every event of `job_id` as it happens, closing the stream once the job has finished.
+fn stream_events(context: &Context, stream: &mut TcpStream, job_id: &str) {
+    if http::start_event_stream(stream).is_err() {
+        return;
+    }
+    let mut next = 0;
+    while !context.stop.load(Ordering::Relaxed) {
+        let Some(events) = context.queue.events(job_id, next, EVENT_POLL) else {
+            return;
+        };
+        for event in &events {
+            let Ok(data) = serde_json::to_string(event) else {
+                return;
+            };
+            if std::io::Write::write_all(
+                stream,
+                format!("event: status\ndata: {}\n\n", data).as_bytes(),
+            )
+            .is_err()
+            {
+                return;
+            }
+            next = event.seq + 1;
+        }
+        if events.last().is_some_and(|e| e.status.is_finished()) {
+            return;
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! `awen-server`: serve the AWEN runtime over HTTP so a lab can share one instance.
+
+use anyhow::Result;
+use awen_runtime::AccessControl;
+use awen_server::{JobServer, ServerConfig};
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(
+    name = "awen-server",
+    version,
+    about = "Accept IR graphs over HTTP and run them as artifact bundles"
+)]
+struct Args {
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1:8080")]
+    bind: String,
+    /// Directory holding the job queue and the job bundles
+    #[clap(long, default_value = "awen-data")]
+    data_dir: PathBuf,
+    /// Device sessions, comma separated; each runs one job at a time
+    #[clap(long, value_delimiter = ',', default_value = "simulator-0,simulator-1")]
+    devices: Vec<String>,
+    /// Token file (`{"tokens": [{"name", "role", "token_sha256", "namespace"?}]}`); without one,
+    /// access is open
+    #[clap(long)]
+    tokens: Option<PathBuf>,
+    /// SQLite run catalog each job bundle is indexed into; needs the `catalog` feature
+    #[clap(long)]
+    catalog: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let mut config = ServerConfig::new(&args.data_dir);
+    config.devices = args.devices;
+    if let Some(tokens) = &args.tokens {
+        config = config.with_access_control(AccessControl::load(tokens)?);
+    }
+    if let Some(catalog) = &args.catalog {
+        config = config.with_catalog(catalog);
+    }
+    let server = JobServer::bind(&args.bind, config)?;
+    println!(
+        "awen-server listening on http://{} (data in {}, {} queued)",
+        server.local_addr(),
+        args.data_dir.display(),
+        server.queue().pending().len()
+    );
+    loop {
+        std::thread::park();
+    }
+}
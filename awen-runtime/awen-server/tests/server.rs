@@ -0,0 +1,270 @@
+//! End-to-end tests of the job server over real HTTP
+
+use awen_runtime::namespace::Namespace;
+use awen_runtime::{AccessControl, Role};
+use awen_server::{JobServer, ServerConfig};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+/// Send one request and return the status and body.
+fn request(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+    body: &str,
+) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(30)))
+        .unwrap();
+    let auth = token
+        .map(|t| format!("Authorization: Bearer {}\r\n", t))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: test\r\n{}Content-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        auth,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+fn get(addr: SocketAddr, path: &str) -> (u16, Value) {
+    let (status, body) = request(addr, "GET", path, None, "");
+    (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+}
+
+fn submission() -> String {
+    json!({
+        "ir": {
+            "nodes": [
+                {"id": "mzi_0", "type": "MZI", "params": {"phase": 0.1}},
+                {"id": "mzi_1", "type": "MZI", "params": {"phase": 0.2}}
+            ],
+            "edges": []
+        },
+        "seed": 7
+    })
+    .to_string()
+}
+
+fn wait_finished(addr: SocketAddr, job_id: &str) -> Value {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    loop {
+        let (_, job) = get(addr, &format!("/jobs/{}", job_id));
+        if job["status"] == "succeeded" || job["status"] == "failed" {
+            return job;
+        }
+        assert!(Instant::now() < deadline, "job never finished: {}", job);
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_submit_run_and_download() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = JobServer::bind("127.0.0.1:0", ServerConfig::new(dir.path())).unwrap();
+    let addr = server.local_addr();
+    assert_eq!(get(addr, "/health").1["status"], "ok");
+
+    let (status, body) = request(addr, "POST", "/jobs", None, &submission());
+    assert_eq!(status, 202, "{}", body);
+    let job: Value = serde_json::from_str(&body).unwrap();
+    let job_id = job["job_id"].as_str().unwrap().to_string();
+    assert_eq!(job["submitted_by"], "anonymous");
+    assert_eq!(job["nodes"], 2);
+
+    let job = wait_finished(addr, &job_id);
+    assert_eq!(job["status"], "succeeded", "{}", job);
+    assert!(job["artifact_id"].as_str().unwrap().starts_with("awen_"));
+    let (_, jobs) = get(addr, "/jobs");
+    assert_eq!(jobs.as_array().unwrap().len(), 1);
+
+    let (status, files) = get(addr, &format!("/jobs/{}/artifacts", job_id));
+    assert_eq!(status, 200);
+    let files: Vec<&str> = files
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f.as_str().unwrap())
+        .collect();
+    assert!(files.contains(&"manifest.json"), "{:?}", files);
+    let (status, manifest) = get(addr, &format!("/jobs/{}/artifacts/manifest.json", job_id));
+    assert_eq!(status, 200);
+    assert_eq!(manifest["artifact_id"], job["artifact_id"]);
+
+    // The job has finished, so the event stream replays its history and closes
+    let (status, stream) = request(addr, "GET", &format!("/jobs/{}/events", job_id), None, "");
+    assert_eq!(status, 200);
+    let statuses: Vec<String> = stream
+        .lines()
+        .filter_map(|l| l.strip_prefix("data: "))
+        .map(|d| serde_json::from_str::<Value>(d).unwrap()["status"].to_string())
+        .collect();
+    assert_eq!(statuses, ["\"pending\"", "\"running\"", "\"succeeded\""]);
+}
+
+#[test]
+fn test_rejects_bad_requests() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = JobServer::bind("127.0.0.1:0", ServerConfig::new(dir.path())).unwrap();
+    let addr = server.local_addr();
+
+    let (status, _) = request(addr, "POST", "/jobs", None, "not json");
+    assert_eq!(status, 400);
+    let bad = json!({"ir": {"nodes": [{
+        "id": "m0", "type": "DETECTOR", "params": {},
+        "conditional_branches": [{"outcome_index": 1, "then_nodes": ["ghost"]}]
+    }]}});
+    let (status, body) = request(addr, "POST", "/jobs", None, &bad.to_string());
+    assert_eq!(status, 400);
+    assert!(body.contains("invalid IR"), "{}", body);
+
+    assert_eq!(get(addr, "/jobs/job-missing").0, 404);
+    assert_eq!(get(addr, "/nowhere").0, 404);
+    assert_eq!(request(addr, "DELETE", "/jobs", None, "").0, 405);
+
+    let (_, body) = request(addr, "POST", "/jobs", None, &submission());
+    let job_id = serde_json::from_str::<Value>(&body).unwrap()["job_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    wait_finished(addr, &job_id);
+    let traversal = format!("/jobs/{}/artifacts/../../../etc/passwd", job_id);
+    assert_eq!(get(addr, &traversal).0, 400);
+    let missing = format!("/jobs/{}/artifacts/nothing.json", job_id);
+    assert_eq!(get(addr, &missing).0, 404);
+}
+
+#[test]
+fn test_tokens_gate_submission() {
+    let dir = tempfile::tempdir().unwrap();
+    let access = AccessControl::new().with_token("obs-token", "grafana", Role::Observer);
+    let config = ServerConfig::new(dir.path()).with_access_control(access);
+    let server = JobServer::bind("127.0.0.1:0", config).unwrap();
+    let addr = server.local_addr();
+
+    assert_eq!(request(addr, "GET", "/jobs", None, "").0, 401);
+    assert_eq!(request(addr, "GET", "/jobs", Some("wrong"), "").0, 401);
+    assert_eq!(get(addr, "/health").0, 200);
+
+    let (status, body) = request(addr, "POST", "/jobs", Some("obs-token"), &submission());
+    assert_eq!(status, 202, "{}", body);
+    assert_eq!(
+        serde_json::from_str::<Value>(&body).unwrap()["submitted_by"],
+        "grafana"
+    );
+    assert_eq!(request(addr, "GET", "/jobs", Some("obs-token"), "").0, 200);
+}
+
+#[test]
+fn test_namespaces_isolate_groups() {
+    let dir = tempfile::tempdir().unwrap();
+    let optics = Namespace::new("optics").unwrap();
+    let access = AccessControl::new()
+        .with_namespace_token("optics-token", "olga", Role::Observer, &optics)
+        .with_namespace_token(
+            "chem-token",
+            "carl",
+            Role::Observer,
+            &Namespace::new("chem").unwrap(),
+        )
+        .with_token("admin-token", "root", Role::Admin);
+    let config = ServerConfig::new(dir.path()).with_access_control(access);
+    let server = JobServer::bind("127.0.0.1:0", config).unwrap();
+    let addr = server.local_addr();
+    let list = |token| {
+        let (_, body) = request(addr, "GET", "/jobs", Some(token), "");
+        serde_json::from_str::<Value>(&body).unwrap()
+    };
+
+    let (status, body) = request(addr, "POST", "/jobs", Some("optics-token"), &submission());
+    assert_eq!(status, 202, "{}", body);
+    let job: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(job["namespace"], "optics");
+    let job_id = job["job_id"].as_str().unwrap().to_string();
+
+    let mut elsewhere: Value = serde_json::from_str(&submission()).unwrap();
+    elsewhere["namespace"] = json!("optics");
+    let (status, _) = request(
+        addr,
+        "POST",
+        "/jobs",
+        Some("chem-token"),
+        &elsewhere.to_string(),
+    );
+    assert_eq!(status, 403);
+
+    assert_eq!(list("optics-token").as_array().unwrap().len(), 1);
+    assert_eq!(list("chem-token"), json!([]));
+    assert_eq!(list("admin-token").as_array().unwrap().len(), 1);
+    let path = format!("/jobs/{}", job_id);
+    assert_eq!(request(addr, "GET", &path, Some("chem-token"), "").0, 404);
+    let artifacts = format!("/jobs/{}/artifacts", job_id);
+    assert_eq!(
+        request(addr, "GET", &artifacts, Some("chem-token"), "").0,
+        404
+    );
+
+    // The bundle is written under the namespace's directory
+    let deadline = Instant::now() + Duration::from_secs(60);
+    while server.queue().get(&job_id).unwrap().artifact.is_none() {
+        assert!(Instant::now() < deadline, "job never finished");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let bundle = server.queue().get(&job_id).unwrap().artifact.unwrap();
+    assert!(
+        bundle.starts_with(dir.path().join("jobs/namespaces/optics")),
+        "{}",
+        bundle.display()
+    );
+}
+
+#[test]
+fn test_queue_options_and_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = || ServerConfig::new(dir.path()).with_devices(&["chip-0"]);
+    let server = JobServer::bind("127.0.0.1:0", config()).unwrap();
+    let addr = server.local_addr();
+
+    let mut body: Value = serde_json::from_str(&submission()).unwrap();
+    body["device"] = json!("chip-9");
+    let (status, reply) = request(addr, "POST", "/jobs", None, &body.to_string());
+    assert_eq!(status, 400);
+    assert!(reply.contains("no device session chip-9"), "{}", reply);
+
+    body["device"] = json!("chip-0");
+    body["priority"] = json!("urgent");
+    body["max_attempts"] = json!(1);
+    let (status, reply) = request(addr, "POST", "/jobs", None, &body.to_string());
+    assert_eq!(status, 202, "{}", reply);
+    let job: Value = serde_json::from_str(&reply).unwrap();
+    assert_eq!(job["priority"], "urgent");
+    assert_eq!(job["affinity"], "chip-0");
+    let job_id = job["job_id"].as_str().unwrap().to_string();
+    let job = wait_finished(addr, &job_id);
+    assert_eq!(job["device"], "chip-0");
+    assert_eq!(job["attempts"], 1);
+    drop(server);
+
+    // The queue file keeps the job across restarts
+    let server = JobServer::bind("127.0.0.1:0", config()).unwrap();
+    let (status, job) = get(server.local_addr(), &format!("/jobs/{}", job_id));
+    assert_eq!(status, 200);
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(
+        get(server.local_addr(), "/health").1["devices"],
+        json!(["chip-0"])
+    );
+}
@@ -0,0 +1,42 @@
+//! The IR validator and reference simulator for the browser.
+//!
+//! Built for `wasm32-unknown-unknown` with `wasm-pack build awen-wasm --target web`, this
+//! crate backs a playground that checks graphs and previews simulated outcomes without a
+//! server. It uses `awen_runtime` without its `native` feature, so no filesystem, device or
+//! thread code is compiled in. Both exports take and return JSON strings:
+//!
+//! - `validate(ir)`: `{"valid": true, "nodes": <n>, "edges": <n>}`, or
+//!   `{"valid": false, "error": "<reason>"}`;
+//! - `simulate(ir, seed)`: the reference simulator's per-node results, or a thrown error
+//!   message for an invalid graph.
+
+use awen_runtime::ir::{self, Graph};
+use awen_runtime::plugins::run_reference_simulator;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+fn parse(ir_json: &str) -> Result<Graph, String> {
+    let graph: Graph =
+        serde_json::from_str(ir_json).map_err(|e| format!("invalid IR JSON: {}", e))?;
+    ir::validate_graph(&graph).map_err(|e| format!("invalid IR: {}", e))?;
+    Ok(graph)
+}
+
+/// Validate an IR graph; returns a JSON report.
+#[wasm_bindgen]
+pub fn validate(ir_json: &str) -> String {
+    let report = match parse(ir_json) {
+        Ok(graph) => json!({"valid": true, "nodes": graph.nodes.len(), "edges": graph.edges.len()}),
+        Err(e) => json!({"valid": false, "error": e}),
+    };
+    report.to_string()
+}
+
+/// Run an IR graph on the reference simulator; returns its results as JSON. The same graph and
+/// seed give the same results as the native runtime.
+#[wasm_bindgen]
+pub fn simulate(ir_json: &str, seed: u64) -> Result<String, String> {
+    let graph = parse(ir_json)?;
+    let results = run_reference_simulator(&graph, Some(seed)).map_err(|e| e.to_string())?;
+    serde_json::to_string(&results).map_err(|e| e.to_string())
+}
@@ -0,0 +1,59 @@
+//! The browser exports, called natively
+
+use awen_wasm::{simulate, validate};
+use serde_json::Value;
+
+const GRAPH: &str = r#"{
+    "nodes": [
+        {"id": "mzi_0", "type": "MZI", "params": {"phase": 0.3}},
+        {"id": "det_0", "type": "DETECTOR", "params": {"quantum": 1.0}}
+    ],
+    "edges": [{"src_node": "mzi_0", "src_port": null, "dst_node": "det_0", "dst_port": null, "delay": null}]
+}"#;
+
+fn report(json: &str) -> Value {
+    serde_json::from_str(&validate(json)).unwrap()
+}
+
+#[test]
+fn test_validate_reports() {
+    let ok = report(GRAPH);
+    assert_eq!(ok["valid"], true);
+    assert_eq!(ok["nodes"], 2);
+    assert_eq!(ok["edges"], 1);
+
+    let bad = report("{\"nodes\": 3}");
+    assert_eq!(bad["valid"], false);
+    assert!(bad["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid IR JSON"));
+
+    let dangling = r#"{"nodes": [{
+        "id": "m0", "type": "DETECTOR", "params": {},
+        "conditional_branches": [{"outcome_index": 1, "then_nodes": ["ghost"]}]
+    }]}"#;
+    assert!(report(dangling)["error"]
+        .as_str()
+        .unwrap()
+        .contains("non-existent node: ghost"));
+}
+
+#[test]
+fn test_simulate_matches_native_simulator() {
+    let results: Value = serde_json::from_str(&simulate(GRAPH, 11).unwrap()).unwrap();
+    assert_eq!(results["run_seed"], 11);
+    assert_eq!(results["node_results"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        results["node_results"][1]["measurement"]["detector_id"],
+        "det_0"
+    );
+
+    let graph = serde_json::from_str(GRAPH).unwrap();
+    let native = awen_runtime::plugins::run_reference_simulator(&graph, Some(11)).unwrap();
+    assert_eq!(
+        simulate(GRAPH, 11).unwrap(),
+        serde_json::to_string(&native).unwrap()
+    );
+    assert!(simulate("[]", 1).is_err());
+}
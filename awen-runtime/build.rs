@@ -0,0 +1,91 @@
+//! Build metadata recorded in environment snapshots: the compiler version and the SHA-256 of
+//! the Cargo.lock the runtime was built against. With the `grpc` feature, also generates the
+//! out-of-process plugin service stubs.
+
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // The lockfile sits next to the manifest, or at the root of an enclosing workspace
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    if let Some(lock) = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.is_file())
+    {
+        println!("cargo:rerun-if-changed={}", lock.display());
+        if let Ok(bytes) = std::fs::read(&lock) {
+            println!(
+                "cargo:rustc-env=AWEN_CARGO_LOCK_SHA256={:x}",
+                Sha256::digest(&bytes)
+            );
+        }
+    }
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    if let Ok(output) = Command::new(rustc).arg("--version").output() {
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !version.is_empty() {
+            println!("cargo:rustc-env=RUSTC_VERSION={}", version);
+        }
+    }
+
+    #[cfg(feature = "grpc")]
+    grpc::compile();
+}
+
+/// `PluginService` client and server, defined in Rust against the message types in
+/// `src/plugins/grpc.rs` so no `protoc` is needed. Keep in step with `proto/awen_plugin.proto`.
+#[cfg(feature = "grpc")]
+mod grpc {
+    const RPCS: &[(&str, &str, &str, &str)] = &[
+        ("describe", "Describe", "Empty", "DescribeResponse"),
+        ("prepare", "Prepare", "PrepareRequest", "StateResponse"),
+        ("evolve", "Evolve", "EvolveRequest", "EvolveResponse"),
+        ("measure", "Measure", "MeasureRequest", "MeasureResponse"),
+        ("snapshot", "Snapshot", "StateRequest", "JsonResponse"),
+        ("wigner", "Wigner", "WignerRequest", "JsonResponse"),
+        (
+            "fidelity",
+            "Fidelity",
+            "FidelityRequest",
+            "FidelityResponse",
+        ),
+        ("sample", "Sample", "SampleRequest", "JsonResponse"),
+        (
+            "release_state",
+            "ReleaseState",
+            "ReleaseStateRequest",
+            "Empty",
+        ),
+        ("schedule", "Schedule", "ScheduleRequest", "JsonResponse"),
+        (
+            "validate_plan",
+            "ValidatePlan",
+            "ValidatePlanRequest",
+            "Empty",
+        ),
+    ];
+
+    pub fn compile() {
+        println!("cargo:rerun-if-changed=proto/awen_plugin.proto");
+        let mut service = tonic_build::manual::Service::builder()
+            .name("PluginService")
+            .package("awen.plugin");
+        for (name, route, input, output) in RPCS {
+            service = service.method(
+                tonic_build::manual::Method::builder()
+                    .name(*name)
+                    .route_name(*route)
+                    .input_type(format!("crate::plugins::grpc::proto::{}", input))
+                    .output_type(format!("crate::plugins::grpc::proto::{}", output))
+                    .codec_path("tonic::codec::ProstCodec")
+                    .build(),
+            );
+        }
+        tonic_build::manual::Builder::new().compile(&[service.build()]);
+    }
+}
@@ -3,6 +3,8 @@ use awen_runtime::engine::Engine;
 use awen_runtime::gradients;
 use awen_runtime::gradients::{GradientOptions, NoiseModel};
 use awen_runtime::ir;
+use awen_runtime::observability;
+use awen_runtime::storage;
 use clap::Parser;
 use std::path::PathBuf;
 use uuid::Uuid;
@@ -37,6 +39,51 @@ enum Command {
         #[clap(long, default_value_t = 1u32)]
         samples: u32,
     },
+    /// Compare run bundle B against baseline A; exits non-zero on regressions
+    Compare {
+        /// Baseline run bundle directory
+        run_a: PathBuf,
+        /// Candidate run bundle directory
+        run_b: PathBuf,
+        /// Relative slowdown that counts as a timing regression
+        #[clap(long, default_value_t = 0.2)]
+        timing_threshold: f64,
+        /// Distribution distance that counts as a shift
+        #[clap(long, default_value_t = 0.1)]
+        distribution_threshold: f64,
+        /// Print the JSON report instead of text
+        #[clap(long)]
+        json: bool,
+    },
+    /// Print a run bundle's artifact sizes; exits non-zero when over the size budget
+    Budget {
+        /// Run bundle directory
+        run_dir: PathBuf,
+        /// Largest allowed total bundle size in bytes
+        #[clap(long)]
+        max_bytes: u64,
+    },
+    /// Re-run an artifact bundle and compare against its stored results; exits non-zero on mismatch
+    VerifyReplay {
+        /// Artifact bundle directory or `.tar.zst` archive
+        bundle: PathBuf,
+        /// Print the JSON report instead of text
+        #[clap(long)]
+        json: bool,
+    },
+    /// Walk the provenance graph of the bundles in an artifacts directory
+    Lineage {
+        /// Directory holding artifact bundles
+        artifacts_dir: PathBuf,
+        /// Artifact, calibration or sweep group id
+        id: String,
+        /// List what derives from `id` instead of what it derives from
+        #[clap(long)]
+        descendants: bool,
+        /// Output format: text, dot (Graphviz) or json
+        #[clap(long, default_value = "text")]
+        format: String,
+    },
 }
 
 fn main() -> Result<()> {
@@ -50,13 +97,100 @@ fn main() -> Result<()> {
             seed,
             samples,
         } => gradient_command(&ir, &params, &strategy, seed, samples)?,
+        Command::Compare {
+            run_a,
+            run_b,
+            timing_threshold,
+            distribution_threshold,
+            json,
+        } => {
+            let options = observability::CompareOptions {
+                timing_threshold,
+                distribution_threshold,
+                ..Default::default()
+            };
+            let diff = observability::compare_with(&run_a, &run_b, &options)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                print!("{}", diff.to_text());
+            }
+            if diff.has_regressions() {
+                std::process::exit(1);
+            }
+        }
+        Command::Budget { run_dir, max_bytes } => {
+            let sizes = observability::bundle_sizes(&run_dir)?;
+            for (name, bytes) in &sizes {
+                println!("{:>12}  {}", bytes, name);
+            }
+            let total: u64 = sizes.values().sum();
+            println!("{:>12}  total (budget {})", total, max_bytes);
+            if total > max_bytes {
+                eprintln!(
+                    "bundle {} exceeds its size budget by {} bytes",
+                    run_dir.display(),
+                    total - max_bytes
+                );
+                std::process::exit(1);
+            }
+        }
+        Command::VerifyReplay { bundle, json } => {
+            let bundle = storage::import_bundle(&bundle)?;
+            let report = storage::verify_replay(&bundle)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report.to_text());
+            }
+            if !report.passed() {
+                std::process::exit(1);
+            }
+        }
+        Command::Lineage {
+            artifacts_dir,
+            id,
+            descendants,
+            format,
+        } => {
+            let graph = storage::ProvenanceGraph::from_artifacts_dir(&artifacts_dir)?;
+            let lineage = graph.lineage(&id)?;
+            match format.as_str() {
+                "dot" if !descendants => print!("{}", lineage.to_dot()),
+                "json" if !descendants => println!("{}", serde_json::to_string_pretty(&lineage)?),
+                "text" => {
+                    let nodes = if descendants {
+                        graph.descendants(&id)
+                    } else {
+                        graph.ancestors(&id)
+                    };
+                    for node in nodes {
+                        println!(
+                            "{:<12} {} {}",
+                            node.kind.as_str(),
+                            node.id,
+                            node.location
+                                .as_ref()
+                                .map(|p| p.display().to_string())
+                                .unwrap_or_else(|| "(not in directory)".to_string())
+                        );
+                    }
+                }
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "unsupported lineage format {} (dot and json export ancestors only)",
+                        other
+                    ))
+                }
+            }
+        }
     }
     Ok(())
 }
 
 fn run_command(ir_path: &str, seed: Option<u64>) -> Result<()> {
     println!("awenctl: running IR {} (seed={:?})", ir_path, seed);
-    let graph = ir::load_from_json(ir_path).map_err(|e| anyhow::anyhow!(e))?;
+    let graph = ir::load_from_json(ir_path)?;
     let engine = Engine::new();
     let out_dir = engine.run_graph(&graph, seed)?;
     println!("Run complete. Artifacts written to: {}", out_dir.display());
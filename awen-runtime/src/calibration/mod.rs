@@ -1,9 +1,16 @@
 // AWEN Calibration Module
 // First-class calibration with drift detection and closed-loop optimization
 
+use crate::namespace::Namespace;
+use crate::observability::{ErrorCode, EventSink, SpanContext};
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Calibration Kernel Definition
@@ -73,9 +80,13 @@ pub struct OptimizerConfig {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum OptimizerAlgorithm {
+    /// Momentum descent on gradients from the named provider in
+    /// `gradients::GLOBAL_GRADIENT_REGISTRY` (e.g. `parameter_shift`, `finite_diff`)
     GradientDescent {
         learning_rate: f64,
         momentum: f64,
+        #[serde(default = "default_gradient_provider")]
+        gradient_provider: String,
     },
     NelderMead {
         initial_simplex_size: f64,
@@ -86,6 +97,10 @@ pub enum OptimizerAlgorithm {
     },
 }
 
+fn default_gradient_provider() -> String {
+    "finite_diff".to_string()
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SafetyConstraints {
     pub hard_limits: HashMap<String, (f64, f64)>,
@@ -118,6 +133,95 @@ pub enum CalibrationSchedule {
     Manual,
 }
 
+impl CalibrationKernel {
+    /// Template kernel characterising optical crosstalk between adjacent `waveguides`: each
+    /// waveguide is driven alone while its neighbours' output power is read, and the fitted
+    /// leakage fractions `crosstalk_<i>_<j>` (indices into `waveguides`) feed
+    /// `SimulatorNoiseConfig::crosstalk_from_parameters`.
+    pub fn crosstalk_characterization(waveguides: &[String]) -> Self {
+        let pairs: Vec<(usize, usize)> = (1..waveguides.len()).map(|j| (j - 1, j)).collect();
+        let mut measurement_sequence = Vec::new();
+        for (i, source) in waveguides.iter().enumerate() {
+            measurement_sequence.push(MeasurementStep {
+                step_id: format!("drive_{}", i),
+                action: MeasurementAction::SetParameter {
+                    node_id: source.clone(),
+                    param_name: "input_power".to_string(),
+                    value: 1.0,
+                },
+                expected_duration_ns: 1_000,
+            });
+            for j in [i.checked_sub(1), Some(i + 1)]
+                .into_iter()
+                .flatten()
+                .filter(|j| *j < waveguides.len())
+            {
+                measurement_sequence.push(MeasurementStep {
+                    step_id: format!("read_{}_{}", i, j),
+                    action: MeasurementAction::ReadSensor {
+                        sensor_id: format!("{}:output_power", waveguides[j]),
+                        integration_time_ns: 10_000,
+                    },
+                    expected_duration_ns: 10_000,
+                });
+                measurement_sequence.push(MeasurementStep {
+                    step_id: format!("leakage_{}_{}", i, j),
+                    action: MeasurementAction::Compute {
+                        metric_name: format!("leakage_{}_{}", i, j),
+                        expression: format!(
+                            "{}:output_power / {}:input_power",
+                            waveguides[j], source
+                        ),
+                    },
+                    expected_duration_ns: 0,
+                });
+            }
+            measurement_sequence.push(MeasurementStep {
+                step_id: format!("release_{}", i),
+                action: MeasurementAction::SetParameter {
+                    node_id: source.clone(),
+                    param_name: "input_power".to_string(),
+                    value: 0.0,
+                },
+                expected_duration_ns: 1_000,
+            });
+        }
+
+        let parameters_to_tune: Vec<String> = pairs
+            .iter()
+            .map(|(i, j)| format!("crosstalk_{}_{}", i, j))
+            .collect();
+        CalibrationKernel {
+            id: "crosstalk_characterization".to_string(),
+            target_nodes: waveguides.to_vec(),
+            parameters_to_tune: parameters_to_tune.clone(),
+            cost_function: CostFunction::Minimize {
+                expression:
+                    "sum((leakage_i_j - crosstalk_i_j)^2 + (leakage_j_i - crosstalk_i_j)^2)"
+                        .to_string(),
+                target_value: Some(0.0),
+            },
+            measurement_sequence,
+            optimizer_config: OptimizerConfig {
+                algorithm: OptimizerAlgorithm::NelderMead {
+                    initial_simplex_size: 0.01,
+                },
+                max_iterations: 200,
+                convergence_threshold: 1e-8,
+                initial_guess: None,
+            },
+            safety_constraints: SafetyConstraints {
+                hard_limits: parameters_to_tune
+                    .into_iter()
+                    .map(|p| (p, (0.0, 0.5)))
+                    .collect(),
+                ..SafetyConstraints::default()
+            },
+            schedule: CalibrationSchedule::PreRun,
+        }
+    }
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Calibration State & Versioning
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -328,21 +432,190 @@ pub trait CalibrationExecutor: Send + Sync {
     fn get_current_calibration(&self) -> Result<CalibrationState>;
 }
 
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Drift-Triggered Recalibration
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Check `measurements` for drift against `current` and, when the detector recommends it,
+/// recalibrate with `kernel` starting from `current`. Traced as a `drift_check` span under
+/// `span`, with the `calibration` it triggers nested beneath it. Returns the new state, or
+/// `None` if no recalibration was recommended.
+pub fn recalibrate_on_drift(
+    detector: &dyn DriftDetector,
+    executor: &dyn CalibrationExecutor,
+    kernel: &CalibrationKernel,
+    current: &CalibrationState,
+    measurements: &[Measurement],
+    span: &SpanContext,
+) -> Result<Option<CalibrationState>> {
+    let mut drift_span = span.start_span("drift_check");
+    drift_span.set_attribute("calibration_id", &current.calibration_id);
+    let report = detector.detect_drift(current, measurements)?;
+    drift_span.set_attribute("drift_detected", &report.drift_detected.to_string());
+    let recalibrated = match &report.recommended_action {
+        RecalibrationAction::Recalibrate { urgency, .. } => {
+            let mut calibration_span = drift_span.context().start_span("calibration");
+            calibration_span.set_attribute("kernel_id", &kernel.id);
+            calibration_span.set_attribute("urgency", &format!("{:?}", urgency));
+            let state = executor.execute_calibration(kernel, Some(current));
+            if let Ok(state) = &state {
+                calibration_span.set_attribute("calibration_id", &state.calibration_id);
+            }
+            calibration_span.end();
+            Some(state?)
+        }
+        _ => None,
+    };
+    drift_span.end();
+    Ok(recalibrated)
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Background Recalibration
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Background thread acting on published [`DriftReport`]s.
+///
+/// A `Recalibrate` recommendation runs `kernel` from the shared state and replaces it with the
+/// result, so drift checks see the new calibration from then on. An `Alert` recommendation is
+/// recorded as a warning event. Reports arrive on the channel returned by [`Self::sender`].
+pub struct BackgroundCalibrationRunner {
+    sender: Sender<DriftReport>,
+    recalibrations: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundCalibrationRunner {
+    pub fn start(
+        executor: Arc<dyn CalibrationExecutor>,
+        kernel: CalibrationKernel,
+        state: Arc<RwLock<CalibrationState>>,
+        events: EventSink,
+    ) -> Self {
+        let (sender, reports) = mpsc::channel::<DriftReport>();
+        let recalibrations = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (count, stop_flag) = (Arc::clone(&recalibrations), Arc::clone(&stop));
+
+        let handle = std::thread::spawn(move || {
+            while !stop_flag.load(Ordering::SeqCst) {
+                // Wake up in short slices so `stop` returns promptly while idle.
+                let report = match reports.recv_timeout(Duration::from_millis(10)) {
+                    Ok(report) => report,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                match report.recommended_action {
+                    RecalibrationAction::Recalibrate {
+                        urgency,
+                        target_nodes,
+                    } => {
+                        let current = state.read().unwrap().clone();
+                        let mut attrs = HashMap::from([
+                            ("kernel_id".to_string(), kernel.id.clone()),
+                            ("urgency".to_string(), format!("{:?}", urgency)),
+                            ("target_nodes".to_string(), target_nodes.join(",")),
+                            (
+                                "parent_calibration_id".to_string(),
+                                current.calibration_id.clone(),
+                            ),
+                        ]);
+                        match executor.execute_calibration(&kernel, Some(&current)) {
+                            Ok(next) => {
+                                attrs.insert(
+                                    "calibration_id".to_string(),
+                                    next.calibration_id.clone(),
+                                );
+                                attrs.insert("version".to_string(), next.version.to_string());
+                                *state.write().unwrap() = next;
+                                count.fetch_add(1, Ordering::SeqCst);
+                                events.info("calibration.background", "recalibrated", attrs);
+                            }
+                            Err(e) => events.error_with_code(
+                                ErrorCode::CalibrationFailure,
+                                "calibration.background",
+                                &format!("recalibration failed: {}", e),
+                                attrs,
+                            ),
+                        }
+                    }
+                    RecalibrationAction::Alert { message } => {
+                        events.warning("calibration.background", &message, HashMap::new())
+                    }
+                    RecalibrationAction::NoAction => {}
+                }
+            }
+        });
+
+        Self {
+            sender,
+            recalibrations,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Channel publishing reports to this runner
+    pub fn sender(&self) -> Sender<DriftReport> {
+        self.sender.clone()
+    }
+
+    /// Recalibrations completed so far
+    pub fn recalibrations(&self) -> usize {
+        self.recalibrations.load(Ordering::SeqCst)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|h| !h.is_finished())
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for BackgroundCalibrationRunner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Reference Calibration Executor (Nelder-Mead)
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
 pub struct ReferenceCalibrationExecutor {
     current_state: std::sync::Mutex<CalibrationState>,
+    hardware: Option<crate::hal::DeviceProvenance>,
+    graph: Option<crate::ir::Graph>,
 }
 
 impl ReferenceCalibrationExecutor {
     pub fn new() -> Self {
         ReferenceCalibrationExecutor {
             current_state: std::sync::Mutex::new(CalibrationState::default()),
+            hardware: None,
+            graph: None,
         }
     }
 
+    /// Record the calibrated device's identity in the provenance of every produced state.
+    pub fn with_hardware(mut self, hardware: crate::hal::DeviceProvenance) -> Self {
+        self.hardware = Some(hardware);
+        self
+    }
+
+    /// Model of the calibrated device. Gradient descent differentiates the cost of its gradient
+    /// provider on this graph, with `parameters_to_tune` named as `node:key` in it.
+    pub fn with_graph(mut self, graph: crate::ir::Graph) -> Self {
+        self.graph = Some(graph);
+        self
+    }
+
     fn evaluate_cost_function(
         &self,
         cost_function: &CostFunction,
@@ -415,6 +688,104 @@ impl ReferenceCalibrationExecutor {
 
         Ok((best_params, best_cost, iterations))
     }
+
+    /// Descend the cost of the kernel's gradient provider on the device graph, or ascend it for
+    /// a `Maximize` cost function. Each step is clamped to the kernel's hard limits. Stops when the largest gradient component falls
+    /// below the convergence threshold.
+    fn gradient_descent_optimize(
+        &self,
+        kernel: &CalibrationKernel,
+        initial_params: &HashMap<String, f64>,
+    ) -> Result<(HashMap<String, f64>, f64, usize)> {
+        use crate::gradients::{self, GradientOptions, NoiseModel, GLOBAL_GRADIENT_REGISTRY};
+
+        let OptimizerAlgorithm::GradientDescent {
+            learning_rate,
+            momentum,
+            gradient_provider,
+        } = &kernel.optimizer_config.algorithm
+        else {
+            return Err(anyhow!(
+                "kernel {} does not use gradient descent",
+                kernel.id
+            ));
+        };
+        let provider = GLOBAL_GRADIENT_REGISTRY
+            .get(gradient_provider)
+            .or_else(|| {
+                gradients::register_defaults_to_global();
+                GLOBAL_GRADIENT_REGISTRY.get(gradient_provider)
+            })
+            .ok_or_else(|| anyhow!("unknown gradient provider {}", gradient_provider))?;
+        let mut graph = self.graph.clone().ok_or_else(|| {
+            anyhow!(
+                "gradient provider {} needs a device graph; see ReferenceCalibrationExecutor::with_graph",
+                gradient_provider
+            )
+        })?;
+        let sign = match kernel.cost_function {
+            CostFunction::Maximize { .. } => -1.0,
+            _ => 1.0,
+        };
+
+        let mut params = initial_params.clone();
+        let names: Vec<String> = kernel.parameters_to_tune.clone();
+        let noise = NoiseModel {
+            shot_noise_std: None,
+            thermal_noise_std: None,
+            phase_noise_std: None,
+            loss_variation: None,
+            metadata: None,
+        };
+        let mut velocity: HashMap<String, f64> = HashMap::new();
+        let mut iterations = 0;
+        let write = |graph: &mut crate::ir::Graph, params: &HashMap<String, f64>| -> Result<()> {
+            for name in &names {
+                let (node, key) = gradients::locate_param(graph, name)
+                    .ok_or_else(|| anyhow!("parameter {} not found in device graph", name))?;
+                if let Some(value) = params.get(name) {
+                    graph.nodes[node].params.insert(key, *value);
+                }
+            }
+            Ok(())
+        };
+
+        for iter in 0..kernel.optimizer_config.max_iterations {
+            iterations = iter + 1;
+            write(&mut graph, &params)?;
+            let opts = GradientOptions {
+                strategy: gradient_provider.clone(),
+                seed: Some(42 + iter as u64),
+                samples: None,
+            };
+            let result = provider.compute_gradients(
+                &serde_json::to_string(&graph)?,
+                &names,
+                &noise,
+                &opts,
+            )?;
+
+            let mut largest = 0.0_f64;
+            for name in &names {
+                let gradient = sign * result.gradients.get(name).copied().unwrap_or(0.0);
+                largest = largest.max(gradient.abs());
+                let v = velocity.entry(name.clone()).or_insert(0.0);
+                *v = momentum * *v - learning_rate * gradient;
+                let value = params.entry(name.clone()).or_insert(0.0);
+                *value += *v;
+                if let Some(&(min, max)) = kernel.safety_constraints.hard_limits.get(name) {
+                    *value = value.clamp(min, max);
+                }
+            }
+            if largest < kernel.optimizer_config.convergence_threshold {
+                break;
+            }
+        }
+
+        write(&mut graph, &params)?;
+        let cost = provider.evaluate_cost(&serde_json::to_string(&graph)?)?;
+        Ok((params, cost, iterations))
+    }
 }
 
 impl CalibrationExecutor for ReferenceCalibrationExecutor {
@@ -429,9 +800,15 @@ impl CalibrationExecutor for ReferenceCalibrationExecutor {
         let initial_params = if let Some(guess) = &kernel.optimizer_config.initial_guess {
             guess.clone()
         } else {
+            // Start from the device graph's current settings when there is one
+            let current = |name: &str| {
+                let graph = self.graph.as_ref()?;
+                let (node, key) = crate::gradients::locate_param(graph, name)?;
+                graph.nodes[node].params.get(&key).copied()
+            };
             let mut params = HashMap::new();
             for param in &kernel.parameters_to_tune {
-                params.insert(param.clone(), 0.0);
+                params.insert(param.clone(), current(param).unwrap_or(0.0));
             }
             params
         };
@@ -442,8 +819,7 @@ impl CalibrationExecutor for ReferenceCalibrationExecutor {
                 self.nelder_mead_optimize(kernel, &initial_params)?
             }
             OptimizerAlgorithm::GradientDescent { .. } => {
-                // Simplified: use Nelder-Mead as fallback
-                self.nelder_mead_optimize(kernel, &initial_params)?
+                self.gradient_descent_optimize(kernel, &initial_params)?
             }
             OptimizerAlgorithm::BayesianOptimization { .. } => {
                 // Simplified: use Nelder-Mead as fallback
@@ -489,7 +865,11 @@ impl CalibrationExecutor for ReferenceCalibrationExecutor {
                 optimizer_algorithm: format!("{:?}", kernel.optimizer_config.algorithm),
                 measurement_count: iterations * kernel.measurement_sequence.len(),
                 parent_calibration_id: initial_state.map(|s| s.calibration_id.clone()),
-                hardware_revision: "v0.2".to_string(),
+                hardware_revision: self
+                    .hardware
+                    .as_ref()
+                    .map(|h| h.hardware_revision())
+                    .unwrap_or_else(|| "v0.2".to_string()),
                 temperature_c: Some(25.0), // Mock temperature
                 seed: Some(42),
             },
@@ -506,6 +886,8 @@ impl CalibrationExecutor for ReferenceCalibrationExecutor {
         state: &CalibrationState,
         safety: &SafetyConstraints,
     ) -> Result<()> {
+        crate::hal::interlock::Interlock::global().check()?;
+
         // Validate safety constraints
         for node_calib in state.node_calibrations.values() {
             for (param_name, &value) in &node_calib.parameters {
@@ -541,6 +923,78 @@ impl Default for ReferenceCalibrationExecutor {
     }
 }
 
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Calibration Store
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Calibration states of one namespace, one `<calibration_id>.json` file each under
+/// `<namespace dir>/calibrations`. Stores of different namespaces never share a directory.
+pub struct CalibrationStore {
+    namespace: Namespace,
+    dir: std::path::PathBuf,
+}
+
+impl CalibrationStore {
+    pub fn open(root: &Path, namespace: &Namespace) -> Result<Self> {
+        let dir = namespace.dir(root).join("calibrations");
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            namespace: namespace.clone(),
+            dir,
+        })
+    }
+
+    pub fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+
+    /// Write `state`, replacing an earlier copy with the same id; returns its path.
+    pub fn save(&self, state: &CalibrationState) -> Result<std::path::PathBuf> {
+        let path = self.path(&state.calibration_id)?;
+        fs::write(&path, serde_json::to_string_pretty(state)?)?;
+        Ok(path)
+    }
+
+    pub fn load(&self, calibration_id: &str) -> Result<Option<CalibrationState>> {
+        let path = self.path(calibration_id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    /// All states, oldest version first.
+    pub fn list(&self) -> Result<Vec<CalibrationState>> {
+        let mut states = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                states.push(serde_json::from_slice::<CalibrationState>(&fs::read(
+                    &path,
+                )?)?);
+            }
+        }
+        states.sort_by(|a, b| (a.version, &a.calibration_id).cmp(&(b.version, &b.calibration_id)));
+        Ok(states)
+    }
+
+    /// The state with the highest version.
+    pub fn latest(&self) -> Result<Option<CalibrationState>> {
+        Ok(self.list()?.pop())
+    }
+
+    fn path(&self, calibration_id: &str) -> Result<std::path::PathBuf> {
+        let safe = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.';
+        if calibration_id.is_empty()
+            || calibration_id.starts_with('.')
+            || !calibration_id.chars().all(safe)
+        {
+            return Err(anyhow!("invalid calibration id {:?}", calibration_id));
+        }
+        Ok(self.dir.join(format!("{}.json", calibration_id)))
+    }
+}
+
 // -----------------------------
 // Basic compatibility helpers
 // -----------------------------
@@ -622,6 +1076,30 @@ pub fn basic_apply_to_params(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_crosstalk_characterization_template() {
+        let waveguides: Vec<String> = (0..3).map(|i| format!("wg{}", i)).collect();
+        let kernel = CalibrationKernel::crosstalk_characterization(&waveguides);
+        assert_eq!(
+            kernel.parameters_to_tune,
+            vec!["crosstalk_0_1".to_string(), "crosstalk_1_2".to_string()]
+        );
+        // Drive and release each waveguide, reading and computing once per neighbour.
+        assert_eq!(kernel.measurement_sequence.len(), 3 * 2 + 4 * 2);
+        assert!(kernel.measurement_sequence.iter().any(|step| matches!(
+            &step.action,
+            MeasurementAction::ReadSensor { sensor_id, .. } if sensor_id == "wg2:output_power"
+        )));
+        assert_eq!(
+            kernel.safety_constraints.hard_limits["crosstalk_1_2"],
+            (0.0, 0.5)
+        );
+
+        let json = serde_json::to_string(&kernel).unwrap();
+        let parsed: CalibrationKernel = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.target_nodes, waveguides);
+    }
+
     #[test]
     fn test_calibration_kernel_serialization() {
         let kernel = CalibrationKernel {
@@ -650,6 +1128,38 @@ mod tests {
         assert_eq!(kernel.id, deserialized.id);
     }
 
+    #[test]
+    fn test_calibration_store_namespaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let optics =
+            CalibrationStore::open(dir.path(), &Namespace::new("optics").unwrap()).unwrap();
+        let shared = CalibrationStore::open(dir.path(), &Namespace::default()).unwrap();
+
+        let v1 = CalibrationState {
+            calibration_id: "calib-001".to_string(),
+            version: 1,
+            ..CalibrationState::default()
+        };
+        let v2 = CalibrationState {
+            calibration_id: "calib-002".to_string(),
+            version: 2,
+            ..CalibrationState::default()
+        };
+        optics.save(&v2).unwrap();
+        optics.save(&v1).unwrap();
+        assert_eq!(
+            optics.latest().unwrap().unwrap().calibration_id,
+            "calib-002"
+        );
+        assert_eq!(optics.list().unwrap().len(), 2);
+
+        assert!(shared.load("calib-001").unwrap().is_none());
+        assert!(shared.list().unwrap().is_empty());
+        shared.save(&v1).unwrap();
+        assert_eq!(optics.list().unwrap().len(), 2);
+        assert!(optics.load("../calibrations/calib-001").is_err());
+    }
+
     #[test]
     fn test_calibration_state_versioning() {
         let state_v1 = CalibrationState {
@@ -721,6 +1231,191 @@ mod tests {
         assert!(report.drift_metrics[0].threshold_exceeded);
     }
 
+    #[test]
+    fn test_recalibration_nests_under_drift_check() {
+        let calibration_state = CalibrationState {
+            calibration_id: "calib-001".to_string(),
+            version: 1,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            node_calibrations: HashMap::from([(
+                "mzi_0".to_string(),
+                NodeCalibration {
+                    node_id: "mzi_0".to_string(),
+                    parameters: HashMap::from([("phase".to_string(), 1.0)]),
+                    metadata: NodeCalibrationMetadata {
+                        cost_function_value: 0.01,
+                        convergence_iterations: 10,
+                        measurement_snr_db: 20.0,
+                        confidence: 0.95,
+                        calibration_duration_seconds: 1.0,
+                    },
+                },
+            )]),
+            provenance: CalibrationProvenance::default(),
+        };
+        let kernel = CalibrationKernel {
+            id: "k".to_string(),
+            target_nodes: vec!["mzi_0".to_string()],
+            parameters_to_tune: vec!["phase".to_string()],
+            cost_function: CostFunction::Minimize {
+                expression: "loss".to_string(),
+                target_value: None,
+            },
+            measurement_sequence: vec![],
+            optimizer_config: OptimizerConfig {
+                algorithm: OptimizerAlgorithm::NelderMead {
+                    initial_simplex_size: 0.1,
+                },
+                max_iterations: 2,
+                convergence_threshold: 0.0,
+                initial_guess: None,
+            },
+            safety_constraints: SafetyConstraints::default(),
+            schedule: CalibrationSchedule::Manual,
+        };
+        let reading = |value: f64| Measurement {
+            measurement_id: "meas-001".to_string(),
+            timestamp_ns: 1000,
+            sensor_id: "phase".to_string(),
+            value,
+            unit: "radians".to_string(),
+        };
+        let detector = ThresholdDriftDetector::new(0.1);
+        let executor = ReferenceCalibrationExecutor::new();
+        let tracer = crate::observability::TracerHandle::new();
+        let run = tracer.start_span("run");
+
+        let stable = recalibrate_on_drift(
+            &detector,
+            &executor,
+            &kernel,
+            &calibration_state,
+            &[reading(1.01)],
+            &run.context(),
+        )
+        .unwrap();
+        assert!(stable.is_none());
+        let drifted = recalibrate_on_drift(
+            &detector,
+            &executor,
+            &kernel,
+            &calibration_state,
+            &[reading(1.5)],
+            &run.context(),
+        )
+        .unwrap();
+        assert!(drifted.is_some());
+
+        let spans = tracer.spans();
+        let names: Vec<&str> = spans.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["run", "drift_check", "drift_check", "calibration"]);
+        assert_eq!(spans[1].parent.as_deref(), Some(spans[0].id.as_str()));
+        assert_eq!(spans[2].attributes["drift_detected"], "true");
+        assert_eq!(spans[3].parent.as_deref(), Some(spans[2].id.as_str()));
+    }
+
+    #[test]
+    fn test_background_runner_recalibrates_shared_state() {
+        let kernel = CalibrationKernel {
+            id: "k".to_string(),
+            target_nodes: vec!["mzi_0".to_string()],
+            parameters_to_tune: vec!["phase".to_string()],
+            cost_function: CostFunction::Minimize {
+                expression: "loss".to_string(),
+                target_value: None,
+            },
+            measurement_sequence: vec![],
+            optimizer_config: OptimizerConfig {
+                algorithm: OptimizerAlgorithm::NelderMead {
+                    initial_simplex_size: 0.1,
+                },
+                max_iterations: 2,
+                convergence_threshold: 0.0,
+                initial_guess: None,
+            },
+            safety_constraints: SafetyConstraints::default(),
+            schedule: CalibrationSchedule::Manual,
+        };
+        let state = Arc::new(RwLock::new(CalibrationState::default()));
+        let events = EventSink::with_filter(crate::observability::LogFilter::default());
+        let mut runner = BackgroundCalibrationRunner::start(
+            Arc::new(ReferenceCalibrationExecutor::new()),
+            kernel,
+            Arc::clone(&state),
+            events.clone(),
+        );
+        let report = |recommended_action| DriftReport {
+            drift_detected: true,
+            drift_metrics: vec![],
+            recommended_action,
+        };
+        let sender = runner.sender();
+        sender
+            .send(report(RecalibrationAction::Alert {
+                message: "laser aging".to_string(),
+            }))
+            .unwrap();
+        sender
+            .send(report(RecalibrationAction::Recalibrate {
+                urgency: Urgency::High,
+                target_nodes: vec!["mzi_0".to_string()],
+            }))
+            .unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while runner.recalibrations() == 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        runner.stop();
+        assert!(!runner.is_running());
+        assert_eq!(runner.recalibrations(), 1);
+
+        let state = state.read().unwrap();
+        assert_eq!(state.version, 1);
+        assert_eq!(
+            state.provenance.parent_calibration_id.as_deref(),
+            Some("default-calib")
+        );
+        let messages: Vec<String> = events.events().into_iter().map(|e| e.message).collect();
+        assert_eq!(messages, ["laser aging", "recalibrated"]);
+    }
+
+    #[test]
+    fn test_hardware_revision_from_device_provenance() {
+        let kernel = CalibrationKernel {
+            id: "k".to_string(),
+            target_nodes: vec!["mzi_0".to_string()],
+            parameters_to_tune: vec!["phase".to_string()],
+            cost_function: CostFunction::Minimize {
+                expression: "loss".to_string(),
+                target_value: None,
+            },
+            measurement_sequence: vec![],
+            optimizer_config: OptimizerConfig {
+                algorithm: OptimizerAlgorithm::NelderMead {
+                    initial_simplex_size: 0.1,
+                },
+                max_iterations: 2,
+                convergence_threshold: 0.0,
+                initial_guess: None,
+            },
+            safety_constraints: SafetyConstraints::default(),
+            schedule: CalibrationSchedule::Manual,
+        };
+        let hardware = crate::hal::DeviceProvenance {
+            device_id: "chip-a".to_string(),
+            firmware_version: Some("2.1.0".to_string()),
+            serial_number: Some("SN123".to_string()),
+            driver_version: None,
+        };
+        let executor = ReferenceCalibrationExecutor::new().with_hardware(hardware);
+        let state = executor.execute_calibration(&kernel, None).unwrap();
+        assert_eq!(
+            state.provenance.hardware_revision,
+            "chip-a sn=SN123 fw=2.1.0"
+        );
+    }
+
     #[test]
     fn test_safety_constraint_validation() {
         let state = CalibrationState {
@@ -762,4 +1457,72 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_gradient_descent_pulls_gradients_from_registry() {
+        // n₁ = 2·sin²θ photons leak into mode 1; descent should close the splitter
+        let graph: crate::ir::Graph = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                {"id": "d0", "type": "DISPLACEMENT", "params": {"mode_id": 0.0, "q": 2.0}},
+                {"id": "bs", "type": "BS", "params": {"mode1": 0.0, "mode2": 1.0, "theta": 0.6}}
+            ],
+            "metadata": {"cost_mode": "1"}
+        }))
+        .unwrap();
+        let kernel = |provider: &str| CalibrationKernel {
+            id: "leakage".to_string(),
+            target_nodes: vec!["bs".to_string()],
+            parameters_to_tune: vec!["bs:theta".to_string()],
+            cost_function: CostFunction::Minimize {
+                expression: "mode_1:photons".to_string(),
+                target_value: Some(0.0),
+            },
+            measurement_sequence: vec![],
+            optimizer_config: OptimizerConfig {
+                algorithm: OptimizerAlgorithm::GradientDescent {
+                    learning_rate: 0.1,
+                    momentum: 0.5,
+                    gradient_provider: provider.to_string(),
+                },
+                max_iterations: 200,
+                convergence_threshold: 1e-6,
+                initial_guess: None,
+            },
+            safety_constraints: SafetyConstraints {
+                hard_limits: HashMap::from([("bs:theta".to_string(), (-1.0, 1.0))]),
+                ..SafetyConstraints::default()
+            },
+            schedule: CalibrationSchedule::Manual,
+        };
+
+        let executor = ReferenceCalibrationExecutor::new().with_graph(graph);
+        let state = executor
+            .execute_calibration(&kernel("gaussian-adjoint"), None)
+            .unwrap();
+        let calibration = &state.node_calibrations["bs"];
+        assert!(calibration.parameters["bs:theta"].abs() < 1e-3);
+        assert!(calibration.metadata.cost_function_value < 1e-6);
+        assert!(calibration.metadata.convergence_iterations < 200);
+        assert!(state
+            .provenance
+            .optimizer_algorithm
+            .contains("gaussian-adjoint"));
+
+        let err = executor
+            .execute_calibration(&kernel("no-such-provider"), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("no-such-provider"));
+        let err = ReferenceCalibrationExecutor::new()
+            .execute_calibration(&kernel("gaussian-adjoint"), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("device graph"));
+
+        let parsed: OptimizerAlgorithm =
+            serde_json::from_str(r#"{"GradientDescent": {"learning_rate": 0.1, "momentum": 0.9}}"#)
+                .unwrap();
+        assert!(matches!(
+            parsed,
+            OptimizerAlgorithm::GradientDescent { gradient_provider, .. } if gradient_provider == "finite_diff"
+        ));
+    }
 }
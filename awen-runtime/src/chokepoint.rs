@@ -5,8 +5,10 @@
 
 use crate::calibration;
 use crate::ir::{Graph, Node};
+use crate::namespace::Namespace;
 use crate::observability;
-use crate::plugins::registry::PluginRegistry;
+use crate::plugins::permissions::{ExecutionTarget, PluginPermission};
+use crate::plugins::registry::{PluginManifest, PluginRegistry};
 use crate::plugins::PluginLoader;
 use crate::storage::{save_artifact, ArtifactType, BundleBuilder};
 use jsonschema::JSONSchema;
@@ -14,8 +16,22 @@ use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value as JsonValue;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
+use std::sync::RwLock;
+
+mod limits;
+mod policy;
+mod rbac;
+
+pub use limits::{
+    QuotaExceeded, QuotaResource, QuotaRule, QuotaScope, RateLimiter, TokenBucket, DURATION_PARAM,
+    OPTICAL_POWER_PARAM,
+};
+pub use policy::{
+    Policy, PolicyDecision, PolicyEffect, PolicyMode, PolicyRule, TimeWindow, ValueRange,
+};
+pub use rbac::{hash_token, AccessControl, AccessError, Identity, Role, RuntimeAction, TokenEntry};
 
 /// A representation of a photonic operation in the runtime IR. This is
 /// intentionally serializable so we can validate against the canonical
@@ -36,6 +52,17 @@ pub struct PhotonicOp {
 pub struct ExecContext {
     pub run_id: String,
     pub timestamp_ns: u64,
+    /// Identity the op runs on behalf of, matched by [`Policy`] rules. A gateway with
+    /// [`AccessControl`] replaces it with the identity of `api_token`.
+    #[serde(default)]
+    pub caller: Option<String>,
+    /// Namespace the op runs in; `None` is the default namespace, or the token's own one for a
+    /// namespaced token. A gateway denies ops outside the token's namespace.
+    #[serde(default)]
+    pub namespace: Option<Namespace>,
+    /// API token presented to a gateway with [`AccessControl`]; never serialized
+    #[serde(default, skip_serializing)]
+    pub api_token: Option<String>,
 }
 
 /// Result of an execution attempt.
@@ -49,15 +76,45 @@ pub struct ExecutionResult {
 /// non-bypassable runtime requirement.
 pub trait ExecutionChokepoint: Send + Sync {
     fn execute(&self, op: &PhotonicOp, ctx: &ExecContext) -> ExecutionResult;
+
+    /// What operations run against. Plugins need permissions only on hardware.
+    fn target(&self) -> ExecutionTarget {
+        ExecutionTarget::Simulation
+    }
+
+    /// Execute `op` on behalf of `plugin`, denying it unless the plugin's manifest declares the
+    /// permissions the op needs on this chokepoint's target.
+    fn execute_for_plugin(
+        &self,
+        plugin: &PluginManifest,
+        op: &PhotonicOp,
+        ctx: &ExecContext,
+    ) -> ExecutionResult {
+        if let Err(e) = plugin.authorize(op, self.target()) {
+            warn!("{}", e);
+            return ExecutionResult {
+                ok: false,
+                details: Some(format!("permission denied: {}", e)),
+            };
+        }
+        self.execute(op, ctx)
+    }
 }
 
 /// A simple in-memory gateway used as a reference implementation. It performs:
 /// - JSON Schema validation against the canonical IR schema
+/// - Role checks against API tokens, when access control is installed
+/// - Policy evaluation and rate limiting on hardware targets
 /// - Calibration injection stub
 /// - Telemetry hooks (logs)
 pub struct NonBypassableGateway {
     // Precompiled JSONSchema instance (None on compilation failure)
     compiled_schema: Option<JSONSchema>,
+    target: ExecutionTarget,
+    policy: RwLock<Policy>,
+    rate_limiter: RateLimiter,
+    access: Option<AccessControl>,
+    approved_calibrations: RwLock<BTreeSet<String>>,
 }
 
 impl NonBypassableGateway {
@@ -76,9 +133,113 @@ impl NonBypassableGateway {
 
         NonBypassableGateway {
             compiled_schema: compiled,
+            target: ExecutionTarget::Simulation,
+            policy: RwLock::new(Policy::permissive()),
+            rate_limiter: RateLimiter::new(),
+            access: None,
+            approved_calibrations: RwLock::new(BTreeSet::new()),
+        }
+    }
+
+    /// Gateway executing against `target`. Routed plugins must hold the matching permissions.
+    pub fn with_target(mut self, target: ExecutionTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Policy every op must pass before it runs against hardware. Simulation ignores it.
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = RwLock::new(policy);
+        self
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy.read().unwrap().clone()
+    }
+
+    /// Replace the policy, and with it the safety limits its value ranges enforce. Needs
+    /// [`RuntimeAction::ChangeSafetyLimits`] and a token not confined to a namespace, since
+    /// the policy applies to every namespace.
+    pub fn set_policy(&self, ctx: &ExecContext, policy: Policy) -> anyhow::Result<()> {
+        let identity = self.authorize_gateway_wide(ctx, RuntimeAction::ChangeSafetyLimits)?;
+        policy.validate()?;
+        info!("{} replaced the gateway policy", identity.name);
+        *self.policy.write().unwrap() = policy;
+        Ok(())
+    }
+
+    /// Quotas charged by every op the policy allows on a hardware target.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Require an API token for every op and admin action. Without access control, every
+    /// caller is trusted as an admin.
+    pub fn with_access_control(mut self, access: AccessControl) -> Self {
+        self.access = Some(access);
+        self
+    }
+
+    /// Identity `ctx` acts as, if it may perform `action`
+    pub fn authorize(
+        &self,
+        ctx: &ExecContext,
+        action: RuntimeAction,
+    ) -> Result<Identity, AccessError> {
+        match &self.access {
+            Some(access) => access.authorize(ctx.api_token.as_deref(), action),
+            None => Ok(Identity {
+                name: ctx
+                    .caller
+                    .clone()
+                    .unwrap_or_else(|| "anonymous".to_string()),
+                role: Role::Admin,
+                namespace: None,
+            }),
+        }
+    }
+
+    /// Like [`Self::authorize`], for actions that affect every namespace: a token confined to
+    /// one namespace is refused whatever its role.
+    fn authorize_gateway_wide(
+        &self,
+        ctx: &ExecContext,
+        action: RuntimeAction,
+    ) -> Result<Identity, AccessError> {
+        let identity = self.authorize(ctx, action)?;
+        match identity.namespace {
+            Some(namespace) => Err(AccessError::Confined {
+                name: identity.name,
+                namespace,
+                action,
+            }),
+            None => Ok(identity),
         }
     }
 
+    /// Approve the calibration `handle` for hardware ops. Needs
+    /// [`RuntimeAction::ApproveCalibration`] and a token not confined to a namespace, since
+    /// approvals apply to every namespace. With access control installed, a hardware op
+    /// naming an unapproved calibration is denied.
+    pub fn approve_calibration(&self, ctx: &ExecContext, handle: &str) -> anyhow::Result<()> {
+        let identity = self.authorize_gateway_wide(ctx, RuntimeAction::ApproveCalibration)?;
+        info!("{} approved calibration {}", identity.name, handle);
+        self.approved_calibrations
+            .write()
+            .unwrap()
+            .insert(handle.to_string());
+        Ok(())
+    }
+
+    pub fn is_calibration_approved(&self, handle: &str) -> bool {
+        self.approved_calibrations.read().unwrap().contains(handle)
+    }
+
     fn validate_op_against_schema(&self, op: &PhotonicOp) -> Result<(), String> {
         let schema = match &self.compiled_schema {
             Some(s) => s,
@@ -156,6 +317,10 @@ impl Default for NonBypassableGateway {
 }
 
 impl ExecutionChokepoint for NonBypassableGateway {
+    fn target(&self) -> ExecutionTarget {
+        self.target
+    }
+
     fn execute(&self, op: &PhotonicOp, ctx: &ExecContext) -> ExecutionResult {
         if op.op_id.is_empty() {
             return ExecutionResult {
@@ -175,9 +340,85 @@ impl ExecutionChokepoint for NonBypassableGateway {
             }
         }
 
+        let action = match self.target {
+            ExecutionTarget::Simulation => RuntimeAction::RunSimulation,
+            ExecutionTarget::Hardware => RuntimeAction::DriveHardware,
+        };
+        let identity = match self.authorize(ctx, action) {
+            Ok(identity) => identity,
+            Err(e) => {
+                warn!("refusing op {}: {}", op.op_id, e);
+                return ExecutionResult {
+                    ok: false,
+                    details: Some(e.to_string()),
+                };
+            }
+        };
+        // A namespaced token runs in its own namespace unless the context names another
+        let namespace = ctx
+            .namespace
+            .clone()
+            .or_else(|| identity.namespace.clone())
+            .unwrap_or_default();
+        if !identity.reaches(&namespace) {
+            warn!(
+                "refusing op {}: {} cannot run in namespace {}",
+                op.op_id, identity.name, namespace
+            );
+            return ExecutionResult {
+                ok: false,
+                details: Some(format!(
+                    "access denied: {} cannot run in namespace {}",
+                    identity.name, namespace
+                )),
+            };
+        }
+        // Policies and quotas see the authenticated identity, not the claimed caller
+        let authenticated;
+        let ctx = if self.access.is_some() {
+            authenticated = ExecContext {
+                caller: Some(identity.name),
+                namespace: Some(namespace.clone()),
+                ..ctx.clone()
+            };
+            &authenticated
+        } else {
+            ctx
+        };
+
+        if self.target == ExecutionTarget::Hardware {
+            if let Some(handle) = op.calibration_handle.as_deref() {
+                if self.access.is_some() && !self.is_calibration_approved(handle) {
+                    warn!(
+                        "refusing op {}: calibration {} not approved",
+                        op.op_id, handle
+                    );
+                    return ExecutionResult {
+                        ok: false,
+                        details: Some(format!("calibration {} is not approved", handle)),
+                    };
+                }
+            }
+            let decision = self.policy.read().unwrap().evaluate(op, ctx);
+            if !decision.allowed {
+                warn!("policy denied op {}: {}", op.op_id, decision.reason);
+                return ExecutionResult {
+                    ok: false,
+                    details: Some(format!("policy denied: {}", decision.reason)),
+                };
+            }
+            info!("policy allowed op {}: {}", op.op_id, decision.reason);
+            if let Err(exceeded) = self.rate_limiter.admit(op, ctx) {
+                warn!("rate limit refused op {}: {}", op.op_id, exceeded);
+                return ExecutionResult {
+                    ok: false,
+                    details: Some(format!("quota exceeded: {}", exceeded)),
+                };
+            }
+        }
+
         // Artifact directory must exist before calibration state is persisted or loaded.
-        let mut out_dir = std::env::temp_dir();
-        out_dir.push("awen_runtime_artifacts");
+        let mut out_dir = namespace.dir(&std::env::temp_dir().join("awen_runtime_artifacts"));
         out_dir.push(&ctx.run_id);
         out_dir.push(ctx.timestamp_ns.to_string());
 
@@ -251,7 +492,9 @@ impl ExecutionChokepoint for NonBypassableGateway {
                 m
             },
             measure_mode: None,
+            measurement: None,
             conditional_branches: None,
+            feed_forward: None,
         };
 
         let graph = Graph {
@@ -260,11 +503,28 @@ impl ExecutionChokepoint for NonBypassableGateway {
             metadata: HashMap::new(),
         };
 
+        // Plugin registry enforcement: discover signed JSON manifests and `awen-plugin.toml`
+        // manifests from the configured plugin dir
+        let plugin_dir = std::env::var("AWEN_PLUGIN_DIR").unwrap_or_else(|_| "plugins".to_string());
+        let mut registry =
+            match PluginRegistry::discover_from_dir(std::path::Path::new(&plugin_dir)) {
+                Ok(r) => r,
+                Err(e) => {
+                    warn!("plugin discovery failed: {}", e);
+                    PluginRegistry::new()
+                }
+            };
+        for (path, e) in registry.discover(&[&plugin_dir]) {
+            warn!("skipping plugin manifest {}: {}", path.display(), e);
+        }
+
         // Build artifact bundle
         let mut builder = BundleBuilder::new(graph, ArtifactType::Run)
             .with_initial_parameters(HashMap::new())
             .with_results(serde_json::json!({"status": "accepted", "op_id": op_clone.op_id}))
-            .with_seed(0);
+            .with_seed(0)
+            .with_namespace(&namespace)
+            .with_plugins(registry.provenance());
 
         if let Some(cal) = op_clone.calibration_handle.clone() {
             builder = builder.with_calibration_state(serde_json::json!({"handle": cal}), None);
@@ -285,23 +545,24 @@ impl ExecutionChokepoint for NonBypassableGateway {
             Err(e) => warn!("failed to build artifact bundle: {}", e),
         }
 
-        // Plugin registry enforcement: discover manifests from configured plugin dir
-        let plugin_dir = std::env::var("AWEN_PLUGIN_DIR").unwrap_or_else(|_| "plugins".to_string());
-        let registry = match PluginRegistry::discover_from_dir(std::path::Path::new(&plugin_dir)) {
-            Ok(r) => r,
-            Err(e) => {
-                warn!("plugin discovery failed: {}", e);
-                PluginRegistry::new()
-            }
-        };
-
         if let Some(p) = registry.find_by_capability("execute") {
             // Enforce manifest signature before routing
             match registry.verify_manifest(&p) {
                 Ok(true) => {
+                    if let Err(e) = p.authorize(&op_clone, self.target) {
+                        warn!("{}; refusing to route", e);
+                        return ExecutionResult {
+                            ok: false,
+                            details: Some(format!("permission denied: {}", e)),
+                        };
+                    }
                     if let Some(path) = p.path.clone() {
-                        // Prepare JSON payload for plugin: {"op": <op>, "ctx": <ctx>}
-                        let payload = serde_json::json!({"op": op_clone, "ctx": ctx});
+                        // Prepare JSON payload for plugin: {"op": <op>, "ctx": <ctx>}, plus the
+                        // artifact directory for plugins allowed filesystem access
+                        let mut payload = serde_json::json!({"op": op_clone, "ctx": ctx});
+                        if p.has_permission(PluginPermission::Filesystem) {
+                            payload["artifacts_dir"] = json!(out_dir);
+                        }
                         if let Ok(Some(stdout)) = PluginLoader::invoke(
                             path,
                             &serde_json::to_string(&payload).unwrap_or_default(),
@@ -0,0 +1,425 @@
+//! Rate limits and quotas on hardware-affecting operations.
+//!
+//! A [`RateLimiter`] holds [`QuotaRule`]s. Each rule allows `limit` units of one
+//! [`QuotaResource`] per `period_s`, counted separately for every device or every caller
+//! ([`QuotaScope`]). Each counter is a [`TokenBucket`] that holds up to `limit` tokens and
+//! refills at `limit / period_s`. Bursts up to the full quota are therefore allowed, and
+//! sustained use is capped at the quota's rate.
+//!
+//! An op is charged against every matching bucket before it runs:
+//!
+//! - parameter writes: one per parameter per target, or one per target for an op without
+//!   parameters; measurement ops write nothing;
+//! - measurements: one per target of a measurement op;
+//! - optical energy: `optical_power_mw · duration_ns`, in joules, charged to each target and
+//!   once to the caller.
+//!
+//! The op is admitted only if every bucket can pay, and then all of them are charged. A refusal
+//! charges nothing and records a `quota exceeded` warning event. Time is the op's
+//! `ExecContext::timestamp_ns`, so replaying a run replays its admissions.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::policy::glob_match;
+use super::{ExecContext, PhotonicOp};
+use crate::observability::EventSink;
+use crate::plugins::permissions::is_measurement_op;
+
+/// Op parameter holding the optical power, in mW, that an op launches
+pub const OPTICAL_POWER_PARAM: &str = "optical_power_mw";
+/// Op parameter holding how long the op runs, in ns
+pub const DURATION_PARAM: &str = "duration_ns";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaResource {
+    ParamWrites,
+    Measurements,
+    /// Joules
+    OpticalEnergy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaScope {
+    Device,
+    Caller,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaRule {
+    pub id: String,
+    pub resource: QuotaResource,
+    pub scope: QuotaScope,
+    /// Glob on the device or caller the rule applies to; unset applies to all
+    #[serde(default)]
+    pub pattern: Option<String>,
+    pub limit: f64,
+    pub period_s: f64,
+}
+
+impl QuotaRule {
+    pub fn new(
+        id: &str,
+        resource: QuotaResource,
+        scope: QuotaScope,
+        limit: f64,
+        period_s: f64,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            resource,
+            scope,
+            pattern: None,
+            limit,
+            period_s,
+        }
+    }
+
+    pub fn matching(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+}
+
+/// Bucket of `capacity` tokens refilled continuously at `refill_per_s`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_s: f64,
+    tokens: f64,
+    last_ns: Option<u64>,
+}
+
+impl TokenBucket {
+    /// A full bucket.
+    pub fn new(capacity: f64, refill_per_s: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_s,
+            tokens: capacity,
+            last_ns: None,
+        }
+    }
+
+    /// Tokens available at `now_ns`. Time going backwards refills nothing.
+    pub fn available(&mut self, now_ns: u64) -> f64 {
+        if let Some(last) = self.last_ns {
+            let elapsed_s = now_ns.saturating_sub(last) as f64 * 1e-9;
+            self.tokens = (self.tokens + elapsed_s * self.refill_per_s).min(self.capacity);
+        }
+        self.last_ns = Some(self.last_ns.map_or(now_ns, |last| last.max(now_ns)));
+        self.tokens
+    }
+
+    /// Take `amount` tokens at `now_ns` if that many are available.
+    pub fn try_take(&mut self, amount: f64, now_ns: u64) -> bool {
+        if self.available(now_ns) + 1e-9 < amount {
+            return false;
+        }
+        self.tokens -= amount;
+        true
+    }
+}
+
+/// An op refused by a [`RateLimiter`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaExceeded {
+    pub rule: String,
+    pub resource: QuotaResource,
+    pub scope: QuotaScope,
+    /// Device or caller whose quota ran out
+    pub key: String,
+    pub requested: f64,
+    pub available: f64,
+}
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "quota {} exceeded for {} {}: requested {} {:?}, {} available",
+            self.rule,
+            match self.scope {
+                QuotaScope::Device => "device",
+                QuotaScope::Caller => "caller",
+            },
+            self.key,
+            self.requested,
+            self.resource,
+            self.available
+        )
+    }
+}
+
+pub struct RateLimiter {
+    rules: Vec<QuotaRule>,
+    buckets: Mutex<HashMap<(usize, String), TokenBucket>>,
+    events: EventSink,
+}
+
+impl RateLimiter {
+    /// No quotas; every op is admitted.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            buckets: Mutex::new(HashMap::new()),
+            events: EventSink::new(),
+        }
+    }
+
+    pub fn with_quota(mut self, rule: QuotaRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Record `quota exceeded` events into `events`.
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn rules(&self) -> &[QuotaRule] {
+        &self.rules
+    }
+
+    pub fn events(&self) -> &EventSink {
+        &self.events
+    }
+
+    /// Admit `op` and charge its usage, or refuse it without charging anything.
+    pub fn admit(&self, op: &PhotonicOp, ctx: &ExecContext) -> Result<(), QuotaExceeded> {
+        let caller = ctx.caller.as_deref().unwrap_or("anonymous");
+        let mut charges = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            for (key, amount) in usage(op, caller, rule.resource, rule.scope) {
+                let applies = rule.pattern.as_deref().is_none_or(|p| glob_match(p, &key));
+                if applies && amount > 0.0 {
+                    charges.push((index, key, amount));
+                }
+            }
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+        for (index, key, amount) in &charges {
+            let rule = &self.rules[*index];
+            let bucket = buckets
+                .entry((*index, key.clone()))
+                .or_insert_with(|| TokenBucket::new(rule.limit, rule.limit / rule.period_s));
+            let available = bucket.available(ctx.timestamp_ns);
+            if available + 1e-9 < *amount {
+                let exceeded = QuotaExceeded {
+                    rule: rule.id.clone(),
+                    resource: rule.resource,
+                    scope: rule.scope,
+                    key: key.clone(),
+                    requested: *amount,
+                    available,
+                };
+                self.record(&exceeded, op, ctx, rule);
+                return Err(exceeded);
+            }
+        }
+        for (index, key, amount) in charges {
+            let taken = buckets
+                .get_mut(&(index, key))
+                .is_some_and(|bucket| bucket.try_take(amount, ctx.timestamp_ns));
+            debug_assert!(taken);
+        }
+        Ok(())
+    }
+
+    fn record(
+        &self,
+        exceeded: &QuotaExceeded,
+        op: &PhotonicOp,
+        ctx: &ExecContext,
+        rule: &QuotaRule,
+    ) {
+        let mut attrs = HashMap::new();
+        attrs.insert("rule".to_string(), exceeded.rule.clone());
+        attrs.insert("key".to_string(), exceeded.key.clone());
+        attrs.insert("op_id".to_string(), op.op_id.clone());
+        attrs.insert("run_id".to_string(), ctx.run_id.clone());
+        attrs.insert("requested".to_string(), exceeded.requested.to_string());
+        attrs.insert("available".to_string(), exceeded.available.to_string());
+        attrs.insert("limit".to_string(), rule.limit.to_string());
+        attrs.insert("period_s".to_string(), rule.period_s.to_string());
+        self.events.warning(
+            "chokepoint.quota",
+            &format!("quota exceeded: {}", exceeded),
+            attrs,
+        );
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Units of `resource` that `op` uses, keyed by device or caller
+fn usage(
+    op: &PhotonicOp,
+    caller: &str,
+    resource: QuotaResource,
+    scope: QuotaScope,
+) -> Vec<(String, f64)> {
+    let param = |name: &str| {
+        op.params
+            .as_ref()
+            .and_then(|p| p.get(name))
+            .and_then(|v| v.as_f64())
+    };
+    let per_target = match resource {
+        QuotaResource::ParamWrites if is_measurement_op(op) => 0.0,
+        QuotaResource::ParamWrites => op
+            .params
+            .as_ref()
+            .and_then(|p| p.as_object())
+            .map_or(1, |map| map.len().max(1)) as f64,
+        QuotaResource::Measurements if is_measurement_op(op) => 1.0,
+        QuotaResource::Measurements => 0.0,
+        QuotaResource::OpticalEnergy => match (param(OPTICAL_POWER_PARAM), param(DURATION_PARAM)) {
+            (Some(power_mw), Some(duration_ns)) => power_mw * 1e-3 * duration_ns * 1e-9,
+            _ => 0.0,
+        },
+    };
+    match (scope, resource) {
+        (QuotaScope::Device, _) => op
+            .targets
+            .iter()
+            .map(|target| (target.clone(), per_target))
+            .collect(),
+        (QuotaScope::Caller, QuotaResource::OpticalEnergy) => {
+            vec![(caller.to_string(), per_target)]
+        }
+        (QuotaScope::Caller, _) => vec![(caller.to_string(), per_target * op.targets.len() as f64)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::LogFilter;
+    use serde_json::json;
+
+    const S: u64 = 1_000_000_000;
+
+    fn op(op_type: &str, targets: &[&str], params: serde_json::Value) -> PhotonicOp {
+        PhotonicOp {
+            op_id: "op1".into(),
+            op_type: op_type.into(),
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            params: Some(params),
+            calibration_handle: None,
+        }
+    }
+
+    fn ctx(caller: &str, timestamp_ns: u64) -> ExecContext {
+        ExecContext {
+            run_id: "run1".into(),
+            timestamp_ns,
+            caller: Some(caller.into()),
+            namespace: None,
+            api_token: None,
+        }
+    }
+
+    #[test]
+    fn test_token_bucket_bursts_then_refills() {
+        let mut bucket = TokenBucket::new(3.0, 1.0);
+        assert!(bucket.try_take(3.0, 0));
+        assert!(!bucket.try_take(1.0, S / 2));
+        assert!(bucket.try_take(1.0, S));
+        // Out-of-order timestamps neither refill nor rewind the clock
+        assert!(!bucket.try_take(1.0, S / 2));
+        assert!((bucket.available(10 * S) - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_param_writes_limited_per_device() {
+        let limiter = RateLimiter::new()
+            .with_quota(QuotaRule::new(
+                "writes",
+                QuotaResource::ParamWrites,
+                QuotaScope::Device,
+                4.0,
+                1.0,
+            ))
+            .with_events(EventSink::with_filter(LogFilter::default()));
+        let write = op(
+            "classical:phase_shifter",
+            &["mzi_0"],
+            json!({"phase": 1.0, "theta": 0.2}),
+        );
+        assert!(limiter.admit(&write, &ctx("alice", 0)).is_ok());
+        assert!(limiter.admit(&write, &ctx("alice", 0)).is_ok());
+        let err = limiter.admit(&write, &ctx("bob", 0)).unwrap_err();
+        assert_eq!(err.key, "mzi_0");
+        assert_eq!(err.requested, 2.0);
+
+        // Other devices have their own bucket, and the first refills within a second
+        let other = op("classical:phase_shifter", &["mzi_1"], json!({"phase": 1.0}));
+        assert!(limiter.admit(&other, &ctx("bob", 0)).is_ok());
+        assert!(limiter.admit(&write, &ctx("bob", S / 2)).is_ok());
+        // Measurements are not writes
+        let measure = op("measurement", &["mzi_0"], json!({}));
+        assert!(limiter.admit(&measure, &ctx("bob", S / 2)).is_ok());
+
+        let events = limiter.events().events();
+        assert_eq!(events.len(), 1);
+        assert!(events[0].message.starts_with("quota exceeded"));
+        assert_eq!(events[0].attributes["rule"], "writes");
+    }
+
+    #[test]
+    fn test_caller_quotas_are_all_or_nothing() {
+        let limiter = RateLimiter::new()
+            .with_quota(
+                QuotaRule::new(
+                    "measure-hourly",
+                    QuotaResource::Measurements,
+                    QuotaScope::Caller,
+                    3.0,
+                    3600.0,
+                )
+                .matching("guest-*"),
+            )
+            .with_quota(QuotaRule::new(
+                "energy-daily",
+                QuotaResource::OpticalEnergy,
+                QuotaScope::Caller,
+                1e-6,
+                86_400.0,
+            ));
+        let measure = op(
+            "measurement",
+            &["det_0", "det_1"],
+            json!({"optical_power_mw": 1.0, "duration_ns": 4e5}),
+        );
+        assert!(limiter.admit(&measure, &ctx("guest-1", 0)).is_ok());
+        // Two more measurements would exceed the hourly three
+        let err = limiter
+            .admit(&measure, &ctx("guest-1", 60 * S))
+            .unwrap_err();
+        assert_eq!(err.rule, "measure-hourly");
+        // Staff are not under the measurement quota, but share the energy budget per caller
+        assert!(limiter.admit(&measure, &ctx("staff", 0)).is_ok());
+        assert!(limiter.admit(&measure, &ctx("staff", 0)).is_ok());
+        let err = limiter.admit(&measure, &ctx("staff", 0)).unwrap_err();
+        assert_eq!(err.resource, QuotaResource::OpticalEnergy);
+        assert!((err.requested - 4e-7).abs() < 1e-15);
+
+        // The refused guest op charged no energy: guest-1 still has 0.6 µJ after one op
+        let small = op(
+            "classical:laser",
+            &["src_0"],
+            json!({"optical_power_mw": 1.0, "duration_ns": 6e5}),
+        );
+        assert!(limiter.admit(&small, &ctx("guest-1", 60 * S)).is_ok());
+    }
+}
@@ -0,0 +1,554 @@
+//! Declarative allow/deny policy for hardware-affecting operations.
+//!
+//! A [`Policy`] is a list of [`PolicyRule`]s and a [`PolicyMode`]. Before a gateway
+//! runs an op against hardware, the op is split into one action per target and parameter. An
+//! op with no parameters, such as a measurement, gives one action per target. A rule matches an
+//! action when every condition it sets holds:
+//!
+//! - `devices`: a glob (`*`, `?`) matching the target;
+//! - `params`: a glob matching the parameter name;
+//! - `value_range`: bounds containing the parameter's numeric value;
+//! - `time_window`: a daily UTC window containing the op's timestamp;
+//! - `callers`: a glob matching the caller identity of the [`ExecContext`];
+//! - `namespaces`: a glob matching its namespace, `default` when it names none.
+//!
+//! An unset condition matches anything. A deny rule that matches any action denies the op. An
+//! action no allow rule matches is allowed in [`PolicyMode::Permissive`] and denied in
+//! [`PolicyMode::Production`], so a production policy allows only what its rules list.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::path::Path;
+
+use super::{ExecContext, PhotonicOp};
+use crate::namespace::DEFAULT_NAMESPACE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyEffect {
+    Allow,
+    Deny,
+}
+
+/// What happens to an action no rule matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyMode {
+    /// Allowed, for development benches
+    #[default]
+    Permissive,
+    /// Denied
+    Production,
+}
+
+/// Inclusive bounds on a parameter value; an unset bound is open.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ValueRange {
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+impl ValueRange {
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// Daily window in UTC, `HH:MM` to `HH:MM`. A window whose end is before its start wraps past
+/// midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start_utc: String,
+    pub end_utc: String,
+}
+
+impl TimeWindow {
+    pub fn new(start_utc: &str, end_utc: &str) -> Self {
+        Self {
+            start_utc: start_utc.to_string(),
+            end_utc: end_utc.to_string(),
+        }
+    }
+
+    /// Whether `timestamp_ns`, nanoseconds since the Unix epoch, falls inside the window
+    pub fn contains(&self, timestamp_ns: u64) -> Result<bool> {
+        let (start, end) = (
+            minute_of_day(&self.start_utc)?,
+            minute_of_day(&self.end_utc)?,
+        );
+        let minute = (timestamp_ns / 60_000_000_000 % (24 * 60)) as u32;
+        Ok(if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        })
+    }
+}
+
+fn minute_of_day(hhmm: &str) -> Result<u32> {
+    let parsed = hhmm.split_once(':').and_then(|(h, m)| {
+        let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+        (h < 24 && m < 60).then_some(h * 60 + m)
+    });
+    parsed.ok_or_else(|| anyhow!("invalid time of day {:?}, expected HH:MM", hhmm))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub id: String,
+    pub effect: PolicyEffect,
+    #[serde(default)]
+    pub devices: Option<String>,
+    #[serde(default)]
+    pub params: Option<String>,
+    #[serde(default)]
+    pub value_range: Option<ValueRange>,
+    #[serde(default)]
+    pub time_window: Option<TimeWindow>,
+    #[serde(default)]
+    pub callers: Option<String>,
+    #[serde(default)]
+    pub namespaces: Option<String>,
+}
+
+impl PolicyRule {
+    pub fn allow(id: &str) -> Self {
+        Self::new(id, PolicyEffect::Allow)
+    }
+
+    pub fn deny(id: &str) -> Self {
+        Self::new(id, PolicyEffect::Deny)
+    }
+
+    fn new(id: &str, effect: PolicyEffect) -> Self {
+        Self {
+            id: id.to_string(),
+            effect,
+            devices: None,
+            params: None,
+            value_range: None,
+            time_window: None,
+            callers: None,
+            namespaces: None,
+        }
+    }
+
+    pub fn on_devices(mut self, pattern: &str) -> Self {
+        self.devices = Some(pattern.to_string());
+        self
+    }
+
+    pub fn on_params(mut self, pattern: &str) -> Self {
+        self.params = Some(pattern.to_string());
+        self
+    }
+
+    pub fn within(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.value_range = Some(ValueRange { min, max });
+        self
+    }
+
+    pub fn during(mut self, window: TimeWindow) -> Self {
+        self.time_window = Some(window);
+        self
+    }
+
+    pub fn for_callers(mut self, pattern: &str) -> Self {
+        self.callers = Some(pattern.to_string());
+        self
+    }
+
+    pub fn in_namespaces(mut self, pattern: &str) -> Self {
+        self.namespaces = Some(pattern.to_string());
+        self
+    }
+
+    fn matches(&self, action: &Action, ctx: &ExecContext) -> Result<bool> {
+        let field = |pattern: &Option<String>, value: Option<&str>| match (pattern, value) {
+            (None, _) => true,
+            (Some(pattern), Some(value)) => glob_match(pattern, value),
+            (Some(_), None) => false,
+        };
+        let in_range = match (&self.value_range, action.value) {
+            (None, _) => true,
+            (Some(range), Some(value)) => range.contains(value),
+            (Some(_), None) => false,
+        };
+        let in_window = match &self.time_window {
+            None => true,
+            Some(window) => window.contains(ctx.timestamp_ns)?,
+        };
+        let namespace = ctx
+            .namespace
+            .as_ref()
+            .map_or(DEFAULT_NAMESPACE, |ns| ns.as_str());
+        Ok(field(&self.devices, Some(action.device))
+            && field(&self.params, action.param)
+            && field(&self.callers, ctx.caller.as_deref())
+            && field(&self.namespaces, Some(namespace))
+            && in_range
+            && in_window)
+    }
+}
+
+/// `*` matches any run of characters, `?` any single character
+pub(super) fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ti = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// One target and parameter of an op
+struct Action<'a> {
+    device: &'a str,
+    param: Option<&'a str>,
+    value: Option<f64>,
+}
+
+impl Action<'_> {
+    fn describe(&self) -> String {
+        match (self.param, self.value) {
+            (Some(param), Some(value)) => format!("{} {}={}", self.device, param, value),
+            (Some(param), None) => format!("{} {}", self.device, param),
+            (None, _) => self.device.to_string(),
+        }
+    }
+}
+
+fn actions(op: &PhotonicOp) -> Vec<Action<'_>> {
+    let params: Vec<(&str, Option<f64>)> = match &op.params {
+        Some(JsonValue::Object(map)) => map.iter().map(|(k, v)| (k.as_str(), v.as_f64())).collect(),
+        _ => Vec::new(),
+    };
+    op.targets
+        .iter()
+        .flat_map(|device| {
+            if params.is_empty() {
+                vec![Action {
+                    device,
+                    param: None,
+                    value: None,
+                }]
+            } else {
+                params
+                    .iter()
+                    .map(|&(param, value)| Action {
+                        device,
+                        param: Some(param),
+                        value,
+                    })
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Outcome of evaluating a [`Policy`] against one op
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    pub allowed: bool,
+    /// Rule that decided, or `None` when the mode's default did
+    pub rule: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Policy {
+    #[serde(default)]
+    pub mode: PolicyMode,
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+impl Policy {
+    /// No rules; everything is allowed.
+    pub fn permissive() -> Self {
+        Self::default()
+    }
+
+    /// Default-deny; only what later rules allow runs.
+    pub fn production() -> Self {
+        Self {
+            mode: PolicyMode::Production,
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn with_rule(mut self, rule: PolicyRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Load and validate a policy from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let policy: Policy = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("invalid policy {}: {}", path.display(), e))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Reject malformed time windows and inverted value ranges.
+    pub fn validate(&self) -> Result<()> {
+        for rule in &self.rules {
+            if let Some(window) = &rule.time_window {
+                window
+                    .contains(0)
+                    .map_err(|e| anyhow!("policy rule {}: {}", rule.id, e))?;
+            }
+            if let Some(ValueRange {
+                min: Some(min),
+                max: Some(max),
+            }) = rule.value_range
+            {
+                if min > max {
+                    return Err(anyhow!(
+                        "policy rule {}: value range [{}, {}] is empty",
+                        rule.id,
+                        min,
+                        max
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decide whether `op` may run. Deny rules take precedence over allow rules; actions no
+    /// rule matches fall back to the mode. A rule that cannot be evaluated denies.
+    pub fn evaluate(&self, op: &PhotonicOp, ctx: &ExecContext) -> PolicyDecision {
+        let caller = ctx.caller.as_deref().unwrap_or("anonymous");
+        let actions = actions(op);
+        if actions.is_empty() && self.mode == PolicyMode::Production {
+            return PolicyDecision {
+                allowed: false,
+                rule: None,
+                reason: format!("op {} names no target", op.op_id),
+            };
+        }
+        let mut allowed_by = None;
+        for action in actions {
+            let mut allow = None;
+            for rule in &self.rules {
+                match rule.matches(&action, ctx) {
+                    Ok(false) => {}
+                    Ok(true) if rule.effect == PolicyEffect::Allow => {
+                        allow.get_or_insert(rule.id.as_str());
+                    }
+                    Ok(true) => {
+                        return PolicyDecision {
+                            allowed: false,
+                            rule: Some(rule.id.clone()),
+                            reason: format!(
+                                "rule {} denies {} for {} on {}",
+                                rule.id,
+                                op.op_id,
+                                caller,
+                                action.describe()
+                            ),
+                        }
+                    }
+                    Err(e) => {
+                        return PolicyDecision {
+                            allowed: false,
+                            rule: Some(rule.id.clone()),
+                            reason: format!("rule {} cannot be evaluated: {}", rule.id, e),
+                        }
+                    }
+                }
+            }
+            match (allow, self.mode) {
+                (Some(rule), _) => {
+                    allowed_by.get_or_insert(rule.to_string());
+                }
+                (None, PolicyMode::Permissive) => {}
+                (None, PolicyMode::Production) => {
+                    return PolicyDecision {
+                        allowed: false,
+                        rule: None,
+                        reason: format!(
+                            "no rule allows {} for {} on {}",
+                            op.op_id,
+                            caller,
+                            action.describe()
+                        ),
+                    }
+                }
+            }
+        }
+        PolicyDecision {
+            allowed: true,
+            reason: match &allowed_by {
+                Some(rule) => format!("rule {} allows {}", rule, op.op_id),
+                None => format!("no rule matches {}; permissive mode allows it", op.op_id),
+            },
+            rule: allowed_by,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn op(targets: &[&str], params: JsonValue) -> PhotonicOp {
+        PhotonicOp {
+            op_id: "op1".into(),
+            op_type: "classical:phase_shifter".into(),
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            params: Some(params),
+            calibration_handle: None,
+        }
+    }
+
+    /// 2024-01-01 at `hh:mm` UTC
+    fn ctx(caller: Option<&str>, hh: u64, mm: u64) -> ExecContext {
+        ExecContext {
+            run_id: "run1".into(),
+            timestamp_ns: (1_704_067_200 + hh * 3600 + mm * 60) * 1_000_000_000,
+            caller: caller.map(str::to_string),
+            namespace: None,
+            api_token: None,
+        }
+    }
+
+    fn lab_policy() -> Policy {
+        Policy::production()
+            .with_rule(
+                PolicyRule::allow("phases")
+                    .on_devices("mzi_*")
+                    .on_params("phase")
+                    .within(Some(0.0), Some(6.3)),
+            )
+            .with_rule(
+                PolicyRule::allow("heaters-office-hours")
+                    .on_devices("heater_?")
+                    .on_params("power")
+                    .within(None, Some(50.0))
+                    .during(TimeWindow::new("08:00", "18:00"))
+                    .for_callers("lab-*"),
+            )
+            .with_rule(PolicyRule::deny("mzi-9-broken").on_devices("mzi_9"))
+    }
+
+    #[test]
+    fn test_production_mode_denies_by_default() {
+        let policy = lab_policy();
+        let at_noon = ctx(Some("lab-alice"), 12, 0);
+        assert!(
+            policy
+                .evaluate(&op(&["mzi_0", "mzi_1"], json!({"phase": 1.2})), &at_noon)
+                .allowed
+        );
+
+        let out_of_range = policy.evaluate(&op(&["mzi_0"], json!({"phase": 7.0})), &at_noon);
+        assert!(!out_of_range.allowed);
+        assert_eq!(out_of_range.rule, None);
+        assert!(
+            out_of_range.reason.contains("mzi_0 phase=7"),
+            "{}",
+            out_of_range.reason
+        );
+
+        // One unlisted parameter denies the whole op
+        let extra = op(&["mzi_0"], json!({"phase": 1.0, "coupling": 0.5}));
+        assert!(!policy.evaluate(&extra, &at_noon).allowed);
+        let unknown = op(&["ring_0"], json!({"phase": 1.0}));
+        assert!(!policy.evaluate(&unknown, &at_noon).allowed);
+        assert!(Policy::permissive().evaluate(&unknown, &at_noon).allowed);
+    }
+
+    #[test]
+    fn test_deny_overrides_allow() {
+        let decision = lab_policy().evaluate(
+            &op(&["mzi_0", "mzi_9"], json!({"phase": 1.0})),
+            &ctx(None, 12, 0),
+        );
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule.as_deref(), Some("mzi-9-broken"));
+    }
+
+    #[test]
+    fn test_caller_and_time_window_conditions() {
+        let policy = lab_policy();
+        let heater = op(&["heater_0"], json!({"power": 20.0}));
+        assert!(
+            policy
+                .evaluate(&heater, &ctx(Some("lab-bob"), 9, 30))
+                .allowed
+        );
+        assert!(
+            !policy
+                .evaluate(&heater, &ctx(Some("lab-bob"), 18, 0))
+                .allowed
+        );
+        assert!(!policy.evaluate(&heater, &ctx(Some("guest"), 9, 30)).allowed);
+        assert!(!policy.evaluate(&heater, &ctx(None, 9, 30)).allowed);
+        let hot = op(&["heater_0"], json!({"power": 80.0}));
+        assert!(!policy.evaluate(&hot, &ctx(Some("lab-bob"), 9, 30)).allowed);
+
+        let night = TimeWindow::new("22:00", "06:00");
+        assert!(night.contains(ctx(None, 23, 0).timestamp_ns).unwrap());
+        assert!(night.contains(ctx(None, 2, 0).timestamp_ns).unwrap());
+        assert!(!night.contains(ctx(None, 12, 0).timestamp_ns).unwrap());
+    }
+
+    #[test]
+    fn test_namespace_condition() {
+        let policy = Policy::production()
+            .with_rule(PolicyRule::allow("optics-bench").in_namespaces("optics*"))
+            .with_rule(PolicyRule::deny("shared-bench").in_namespaces("default"));
+        let phase = op(&["mzi_0"], json!({"phase": 1.0}));
+        let in_ns = |name: &str| ExecContext {
+            namespace: Some(crate::namespace::Namespace::new(name).unwrap()),
+            ..ctx(None, 12, 0)
+        };
+        assert!(policy.evaluate(&phase, &in_ns("optics-2")).allowed);
+        assert!(!policy.evaluate(&phase, &in_ns("chem")).allowed);
+        let shared = policy.evaluate(&phase, &ctx(None, 12, 0));
+        assert_eq!(shared.rule.as_deref(), Some("shared-bench"));
+    }
+
+    #[test]
+    fn test_policy_round_trips_and_validates() {
+        let policy = lab_policy();
+        let parsed: Policy =
+            serde_json::from_value(serde_json::to_value(&policy).unwrap()).unwrap();
+        assert_eq!(parsed, policy);
+        parsed.validate().unwrap();
+
+        let bad = Policy::production()
+            .with_rule(PolicyRule::allow("bad").during(TimeWindow::new("25:00", "26:00")));
+        assert!(bad.validate().is_err());
+        assert!(
+            !bad.evaluate(&op(&["mzi_0"], json!({"phase": 1.0})), &ctx(None, 0, 0))
+                .allowed
+        );
+        let empty =
+            Policy::permissive().with_rule(PolicyRule::deny("empty").within(Some(2.0), Some(1.0)));
+        assert!(empty.validate().is_err());
+
+        assert!(glob_match("mzi_*:phase", "mzi_12:phase"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("mzi_?", "mzi_12"));
+    }
+}
@@ -0,0 +1,320 @@
+//! API tokens, roles and the runtime actions each role may perform.
+//!
+//! [`AccessControl`] maps API tokens to an [`Identity`], a name plus a [`Role`]. Only SHA-256
+//! hashes of the tokens are kept, so a leaked token file does not leak credentials. Roles are
+//! cumulative:
+//!
+//! - `observer` runs simulations and reads artifacts;
+//! - `operator` also drives hardware, within the gateway's policy and quotas;
+//! - `admin` also changes safety limits and approves calibrations.
+//!
+//! A token may be confined to one [`Namespace`]: its holder only runs ops in, and sees data
+//! of, that namespace. A token without one is unrestricted. Changing safety limits and
+//! approving calibrations affect every namespace, so a confined admin may do neither.
+
+use crate::namespace::Namespace;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Why a caller was refused
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AccessError {
+    #[error("access denied: no API token")]
+    MissingToken,
+    #[error("access denied: unknown API token")]
+    UnknownToken,
+    /// The caller is known but their role is below what the action needs
+    #[error("access denied: {name} is {role} but {action} needs {}", action.minimum_role())]
+    Forbidden {
+        name: String,
+        role: Role,
+        action: RuntimeAction,
+    },
+    /// The action affects every namespace, but the caller is confined to one
+    #[error("access denied: {name} is confined to namespace {namespace}, but {action} affects every namespace")]
+    Confined {
+        name: String,
+        namespace: Namespace,
+        action: RuntimeAction,
+    },
+}
+
+impl AccessError {
+    /// Whether the caller failed to authenticate at all, rather than lacking permission
+    pub fn is_unauthenticated(&self) -> bool {
+        matches!(self, AccessError::MissingToken | AccessError::UnknownToken)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Observer,
+    Operator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Observer => "observer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Whether this role may perform `action`
+    pub fn allows(&self, action: RuntimeAction) -> bool {
+        *self >= action.minimum_role()
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeAction {
+    RunSimulation,
+    ReadArtifacts,
+    DriveHardware,
+    ChangeSafetyLimits,
+    ApproveCalibration,
+}
+
+impl RuntimeAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuntimeAction::RunSimulation => "run_simulation",
+            RuntimeAction::ReadArtifacts => "read_artifacts",
+            RuntimeAction::DriveHardware => "drive_hardware",
+            RuntimeAction::ChangeSafetyLimits => "change_safety_limits",
+            RuntimeAction::ApproveCalibration => "approve_calibration",
+        }
+    }
+
+    pub fn minimum_role(&self) -> Role {
+        match self {
+            RuntimeAction::RunSimulation | RuntimeAction::ReadArtifacts => Role::Observer,
+            RuntimeAction::DriveHardware => Role::Operator,
+            RuntimeAction::ChangeSafetyLimits | RuntimeAction::ApproveCalibration => Role::Admin,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    pub name: String,
+    pub role: Role,
+    /// Namespace the identity is confined to; `None` reaches every namespace
+    #[serde(default)]
+    pub namespace: Option<Namespace>,
+}
+
+impl Identity {
+    /// Whether the identity may reach data and ops of `namespace`
+    pub fn reaches(&self, namespace: &Namespace) -> bool {
+        self.namespace.as_ref().is_none_or(|own| own == namespace)
+    }
+}
+
+/// One entry of a token file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenEntry {
+    pub name: String,
+    pub role: Role,
+    /// Hex SHA-256 of the API token
+    pub token_sha256: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<Namespace>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct TokenFile {
+    tokens: Vec<TokenEntry>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AccessControl {
+    identities: HashMap<String, Identity>,
+}
+
+impl AccessControl {
+    /// No tokens; every request is denied.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grant `role` to whoever presents `token`.
+    pub fn with_token(self, token: &str, name: &str, role: Role) -> Self {
+        self.with_token_hash(&hash_token(token), name, role)
+    }
+
+    /// Grant `role` to whoever presents a token whose hex SHA-256 is `token_sha256`.
+    pub fn with_token_hash(self, token_sha256: &str, name: &str, role: Role) -> Self {
+        self.with_identity(token_sha256, name, role, None)
+    }
+
+    /// Grant `role` within `namespace` only to whoever presents `token`.
+    pub fn with_namespace_token(
+        self,
+        token: &str,
+        name: &str,
+        role: Role,
+        namespace: &Namespace,
+    ) -> Self {
+        self.with_identity(&hash_token(token), name, role, Some(namespace.clone()))
+    }
+
+    fn with_identity(
+        mut self,
+        token_sha256: &str,
+        name: &str,
+        role: Role,
+        namespace: Option<Namespace>,
+    ) -> Self {
+        self.identities.insert(
+            token_sha256.to_ascii_lowercase(),
+            Identity {
+                name: name.to_string(),
+                role,
+                namespace,
+            },
+        );
+        self
+    }
+
+    /// Load `{"tokens": [{"name", "role", "token_sha256", "namespace"?}]}` from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file: TokenFile = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("invalid token file {}: {}", path.display(), e))?;
+        Ok(file.tokens.into_iter().fold(Self::new(), |access, entry| {
+            access.with_identity(
+                &entry.token_sha256,
+                &entry.name,
+                entry.role,
+                entry.namespace,
+            )
+        }))
+    }
+
+    /// Identity holding `token`
+    pub fn authenticate(&self, token: Option<&str>) -> Result<Identity, AccessError> {
+        let token = token.ok_or(AccessError::MissingToken)?;
+        self.identities
+            .get(&hash_token(token))
+            .cloned()
+            .ok_or(AccessError::UnknownToken)
+    }
+
+    /// Identity holding `token`, if its role allows `action`
+    pub fn authorize(
+        &self,
+        token: Option<&str>,
+        action: RuntimeAction,
+    ) -> Result<Identity, AccessError> {
+        let identity = self.authenticate(token)?;
+        if !identity.role.allows(action) {
+            return Err(AccessError::Forbidden {
+                name: identity.name,
+                role: identity.role,
+                action,
+            });
+        }
+        Ok(identity)
+    }
+}
+
+/// Hex SHA-256 of an API token, as stored in token files
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_are_cumulative() {
+        use RuntimeAction::*;
+        let all = [
+            RunSimulation,
+            ReadArtifacts,
+            DriveHardware,
+            ChangeSafetyLimits,
+            ApproveCalibration,
+        ];
+        let allowed = |role: Role| all.iter().filter(|a| role.allows(**a)).count();
+        assert_eq!(allowed(Role::Observer), 2);
+        assert_eq!(allowed(Role::Operator), 3);
+        assert_eq!(allowed(Role::Admin), 5);
+        assert!(!Role::Operator.allows(ApproveCalibration));
+    }
+
+    #[test]
+    fn test_tokens_authenticate_by_hash() {
+        let access = AccessControl::new()
+            .with_token("obs-token", "grafana", Role::Observer)
+            .with_token_hash(
+                &hash_token("op-token").to_uppercase(),
+                "alice",
+                Role::Operator,
+            );
+
+        let alice = access
+            .authorize(Some("op-token"), RuntimeAction::DriveHardware)
+            .unwrap();
+        assert_eq!(alice.name, "alice");
+        let err = access
+            .authorize(Some("obs-token"), RuntimeAction::DriveHardware)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "access denied: grafana is observer but drive_hardware needs operator"
+        );
+        assert!(!err.is_unauthenticated());
+        assert_eq!(
+            access.authenticate(Some("guess")),
+            Err(AccessError::UnknownToken)
+        );
+        assert_eq!(access.authenticate(None), Err(AccessError::MissingToken));
+    }
+
+    #[test]
+    fn test_load_token_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens.json");
+        let file = serde_json::json!({"tokens": [
+            {"name": "root", "role": "admin", "token_sha256": hash_token("s3cret")},
+            {"name": "bob", "role": "operator", "token_sha256": hash_token("bob"),
+             "namespace": "optics"}
+        ]});
+        std::fs::write(&path, file.to_string()).unwrap();
+        let access = AccessControl::load(&path).unwrap();
+        let root = access
+            .authorize(Some("s3cret"), RuntimeAction::ChangeSafetyLimits)
+            .unwrap();
+        assert_eq!(root.role, Role::Admin);
+        let optics = Namespace::new("optics").unwrap();
+        assert!(root.reaches(&optics) && root.reaches(&Namespace::default()));
+        let bob = access.authenticate(Some("bob")).unwrap();
+        assert_eq!(bob.namespace, Some(optics.clone()));
+        assert!(bob.reaches(&optics) && !bob.reaches(&Namespace::default()));
+
+        std::fs::write(&path, r#"{"tokens": [{"name": "x", "role": "root"}]}"#).unwrap();
+        assert!(AccessControl::load(&path).is_err());
+    }
+}
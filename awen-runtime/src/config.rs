@@ -0,0 +1,532 @@
+//! Layered runtime configuration.
+//!
+//! A [`Config`] covers the engine, the HAL driver registry, observability, storage and safety
+//! settings. It is built in layers, each overriding the one before:
+//!
+//! 1. defaults;
+//! 2. a TOML file: the one given, else the one named by `AWEN_CONFIG`, else `awen.toml` in
+//!    the working directory when present;
+//! 3. environment variables `AWEN_<SECTION>_<KEY>`, e.g. `AWEN_STORAGE_ARTIFACTS_DIR`, plus
+//!    `AWEN_LOG` and `AWEN_PLUGIN_DIR`, which predate the file;
+//! 4. `key=value` overrides from the command line, with dotted keys such as `engine.seed=7`.
+//!
+//! Values from the environment and the command line are read as JSON scalars when they parse
+//! as one of the right type, else as strings; list values are comma separated. Relative paths
+//! are relative to the working directory. Each layer only checks that values have the right
+//! type. [`Config::validate`] then reports every malformed or conflicting value at once.
+
+use crate::chokepoint::{AccessControl, Policy, PolicyMode};
+use crate::engine::Engine;
+use crate::hal::driver::DriverRegistry;
+use crate::hal::SafetyLimits;
+use crate::namespace::Namespace;
+use crate::observability::{
+    EventSink, EventStreamer, ExporterConfig, LogFilter, SamplingConfig, LOG_ENV_VAR,
+};
+use crate::simulator::NoiseProfileRegistry;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Configuration file read from the working directory
+pub const CONFIG_FILE: &str = "awen.toml";
+
+/// Environment variable naming the configuration file
+pub const CONFIG_ENV_VAR: &str = "AWEN_CONFIG";
+
+/// Prefix of configuration environment variables
+pub const ENV_PREFIX: &str = "AWEN_";
+
+/// Environment variable the plugin directory was read from before the configuration file
+pub const PLUGIN_DIR_ENV_VAR: &str = "AWEN_PLUGIN_DIR";
+
+const SECTIONS: [&str; 5] = ["engine", "hal", "observability", "storage", "safety"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub engine: EngineSettings,
+    pub hal: HalSettings,
+    pub observability: ObservabilitySettings,
+    pub storage: StorageSettings,
+    pub safety: SafetySettings,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineSettings {
+    /// Seed of runs that name none
+    pub seed: Option<u64>,
+    /// Registered noise profile of graphs that name none
+    pub noise_profile: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HalSettings {
+    /// Directories searched for driver libraries
+    pub driver_dirs: Vec<PathBuf>,
+    /// Directory searched for plugin manifests
+    pub plugin_dir: PathBuf,
+    /// Devices to open, by name
+    pub devices: BTreeMap<String, DeviceSettings>,
+}
+
+impl Default for HalSettings {
+    fn default() -> Self {
+        Self {
+            driver_dirs: Vec::new(),
+            plugin_dir: PathBuf::from("plugins"),
+            devices: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceSettings {
+    pub driver: String,
+    /// Passed to the driver's factory
+    #[serde(default)]
+    pub options: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObservabilitySettings {
+    /// Event filter in `AWEN_LOG` syntax; empty keeps every level
+    pub log: String,
+    /// Address to serve live run events on, e.g. `127.0.0.1:8765`
+    pub event_stream: Option<String>,
+    pub exporters: ExporterConfig,
+    pub timeline_sampling: SamplingConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageSettings {
+    /// Directory bundles are written under
+    pub artifacts_dir: PathBuf,
+    /// Namespace runs belong to
+    pub namespace: Namespace,
+    /// SQLite run catalog `awen run` indexes each bundle into; needs the `catalog` feature
+    pub catalog: Option<PathBuf>,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            artifacts_dir: PathBuf::from("."),
+            namespace: Namespace::default(),
+            catalog: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SafetySettings {
+    /// Chokepoint policy file
+    pub policy: Option<PathBuf>,
+    /// Mode of the policy, overriding the file's
+    pub policy_mode: Option<PolicyMode>,
+    /// Token file for access control
+    pub tokens: Option<PathBuf>,
+    /// Refuse to start without a token file
+    pub require_tokens: bool,
+    pub min_voltage: Option<f64>,
+    pub max_voltage: Option<f64>,
+    pub max_temperature: Option<f64>,
+}
+
+impl Config {
+    /// Defaults overridden by the TOML file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("cannot read {}: {}", path.display(), e))?;
+        toml::from_str(&text)
+            .map_err(|e| anyhow!("invalid configuration {}: {}", path.display(), e))
+    }
+
+    /// All layers: defaults, the configuration file (`file`, else the usual places), the
+    /// process environment, then `overrides` (`key=value`). Not validated.
+    pub fn resolve(file: Option<&Path>, overrides: &[String]) -> Result<Self> {
+        let file = file
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_ENV_VAR).map(PathBuf::from))
+            .or_else(|| Some(PathBuf::from(CONFIG_FILE)).filter(|p| p.is_file()));
+        let mut config = match &file {
+            Some(path) => Self::load(path)?,
+            None => Self::default(),
+        };
+        config.apply_env(std::env::vars())?;
+        for set in overrides {
+            let (key, value) = set
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expected key=value, got {:?}", set))?;
+            config.set(key.trim(), value.trim())?;
+        }
+        Ok(config)
+    }
+
+    /// Apply the `AWEN_<SECTION>_<KEY>` variables of `vars`; other variables are ignored.
+    pub fn apply_env(&mut self, vars: impl IntoIterator<Item = (String, String)>) -> Result<()> {
+        for (name, value) in vars {
+            let key = match name.as_str() {
+                LOG_ENV_VAR => "observability.log".to_string(),
+                PLUGIN_DIR_ENV_VAR => "hal.plugin_dir".to_string(),
+                _ => {
+                    let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+                        continue;
+                    };
+                    let rest = rest.to_ascii_lowercase();
+                    let Some((section, key)) = SECTIONS.iter().find_map(|section| {
+                        let key = rest.strip_prefix(section)?.strip_prefix('_')?;
+                        Some((section, key))
+                    }) else {
+                        continue;
+                    };
+                    format!("{}.{}", section, key)
+                }
+            };
+            self.set(&key, &value)
+                .map_err(|e| anyhow!("{}: {}", name, e))?;
+        }
+        Ok(())
+    }
+
+    /// Set the value at dotted `key`, e.g. `storage.artifacts_dir`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let mut tree = serde_json::to_value(&*self)?;
+        let is_list = match slot(&mut tree, key) {
+            Some(slot) => slot.is_array(),
+            None => return Err(anyhow!("unknown configuration key {}", key)),
+        };
+        let candidates: Vec<Value> = if is_list {
+            vec![Value::Array(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(|v| Value::String(v.to_string()))
+                    .collect(),
+            )]
+        } else {
+            serde_json::from_str(value)
+                .ok()
+                .into_iter()
+                .chain([Value::String(value.to_string())])
+                .collect()
+        };
+        let mut error = None;
+        for candidate in candidates {
+            if let Some(slot) = slot(&mut tree, key) {
+                *slot = candidate;
+            }
+            match serde_json::from_value(tree.clone()) {
+                Ok(config) => {
+                    *self = config;
+                    return Ok(());
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(anyhow!(
+            "invalid value {:?} for {}: {}",
+            value,
+            key,
+            error.map(|e| e.to_string()).unwrap_or_default()
+        ))
+    }
+
+    /// Every malformed or conflicting value, one message each; empty when the configuration
+    /// is usable.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(id) = &self.engine.noise_profile {
+            if let Err(e) = NoiseProfileRegistry::new().get(id) {
+                problems.push(format!("engine.noise_profile: {}", e));
+            }
+        }
+        for dir in &self.hal.driver_dirs {
+            if !dir.is_dir() {
+                problems.push(format!(
+                    "hal.driver_dirs: {} is not a directory",
+                    dir.display()
+                ));
+            }
+        }
+        for (name, device) in &self.hal.devices {
+            if device.driver.trim().is_empty() {
+                problems.push(format!("hal.devices.{}: no driver", name));
+            }
+        }
+        if let Err(e) = LogFilter::parse(&self.observability.log) {
+            problems.push(format!("observability.log: {}", e));
+        }
+        if let Some(addr) = &self.observability.event_stream {
+            if addr.parse::<SocketAddr>().is_err() {
+                problems.push(format!(
+                    "observability.event_stream: {:?} is not a socket address",
+                    addr
+                ));
+            }
+        }
+        if self.storage.catalog.is_some() && !cfg!(feature = "catalog") {
+            problems.push("storage.catalog: built without the catalog feature".to_string());
+        }
+        let safety = &self.safety;
+        if let (Some(min), Some(max)) = (safety.min_voltage, safety.max_voltage) {
+            if min > max {
+                problems.push(format!(
+                    "safety.min_voltage {} is above safety.max_voltage {}",
+                    min, max
+                ));
+            }
+        }
+        if let Some(path) = &safety.policy {
+            if let Err(e) = Policy::load(path) {
+                problems.push(format!("safety.policy: {}", e));
+            }
+        } else if safety.policy_mode == Some(PolicyMode::Production) {
+            problems.push(
+                "safety.policy_mode is production but no safety.policy lists what may run"
+                    .to_string(),
+            );
+        }
+        match &safety.tokens {
+            Some(path) => {
+                if let Err(e) = AccessControl::load(path) {
+                    problems.push(format!("safety.tokens: {}", e));
+                }
+            }
+            None if safety.require_tokens => {
+                problems.push("safety.require_tokens is set but safety.tokens is not".to_string())
+            }
+            None => {}
+        }
+        if !self.hal.devices.is_empty() && safety.require_tokens && safety.policy.is_none() {
+            problems.push(
+                "hal.devices drive hardware under safety.require_tokens but no safety.policy \
+                 limits them"
+                    .to_string(),
+            );
+        }
+        problems
+    }
+
+    /// Fail with every problem [`Config::problems`] finds.
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!("invalid configuration: {}", problems.join("; ")))
+    }
+
+    /// Engine with the configured noise profile, timeline sampling and event stream.
+    pub fn engine(&self) -> Result<Engine> {
+        let mut engine =
+            Engine::new().with_timeline_sampling(self.observability.timeline_sampling.clone());
+        if let Some(id) = &self.engine.noise_profile {
+            engine = engine.with_noise_profile(id)?;
+        }
+        if let Some(addr) = &self.observability.event_stream {
+            engine = engine.with_event_stream(Arc::new(EventStreamer::bind(addr)?));
+        }
+        Ok(engine)
+    }
+
+    /// Event sink keeping what `observability.log` lets through.
+    pub fn events(&self) -> Result<EventSink> {
+        Ok(EventSink::with_filter(LogFilter::parse(
+            &self.observability.log,
+        )?))
+    }
+
+    /// Driver registry with the libraries of `hal.driver_dirs`; a library that fails to load
+    /// is an error.
+    pub fn driver_registry(&self) -> Result<DriverRegistry> {
+        let mut registry = DriverRegistry::new();
+        for dir in &self.hal.driver_dirs {
+            let (_, failures) = registry.discover(dir);
+            if let Some((path, e)) = failures.first() {
+                return Err(anyhow!("driver {}: {}", path.display(), e));
+            }
+        }
+        Ok(registry)
+    }
+
+    /// The policy file with `safety.policy_mode` applied; permissive without one.
+    pub fn policy(&self) -> Result<Policy> {
+        let mut policy = match &self.safety.policy {
+            Some(path) => Policy::load(path)?,
+            None => Policy::permissive(),
+        };
+        if let Some(mode) = self.safety.policy_mode {
+            policy.mode = mode;
+        }
+        Ok(policy)
+    }
+
+    /// Access control from `safety.tokens`; `None` leaves access open.
+    pub fn access_control(&self) -> Result<Option<AccessControl>> {
+        self.safety
+            .tokens
+            .as_deref()
+            .map(AccessControl::load)
+            .transpose()
+    }
+
+    pub fn safety_limits(&self) -> SafetyLimits {
+        SafetyLimits {
+            max_voltage: self.safety.max_voltage,
+            min_voltage: self.safety.min_voltage,
+            max_temperature: self.safety.max_temperature,
+            notes: None,
+        }
+    }
+}
+
+/// The value at dotted `key` of `tree`
+fn slot<'a>(tree: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    key.split('.')
+        .try_fold(tree, |node, part| node.as_object_mut()?.get_mut(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(
+            &path,
+            r#"
+            [engine]
+            seed = 7
+
+            [storage]
+            artifacts_dir = "from-file"
+            namespace = "optics"
+
+            [hal.devices.chip0]
+            driver = "scpi"
+            options = { address = "TCPIP::10.0.0.5" }
+            "#,
+        )
+        .unwrap();
+        let mut config = Config::load(&path).unwrap();
+        assert_eq!(config.engine.seed, Some(7));
+        assert_eq!(config.hal.plugin_dir, Path::new("plugins"));
+        assert_eq!(
+            config.hal.devices["chip0"].options["address"],
+            "TCPIP::10.0.0.5"
+        );
+
+        config
+            .apply_env(vars(&[
+                ("AWEN_STORAGE_ARTIFACTS_DIR", "from-env"),
+                ("AWEN_HAL_DRIVER_DIRS", "/opt/a, /opt/b"),
+                ("AWEN_LOG", "warn,hal=debug"),
+                ("AWEN_BUNDLE_KEY", "not configuration"),
+                ("PATH", "/usr/bin"),
+            ]))
+            .unwrap();
+        assert_eq!(config.storage.artifacts_dir, Path::new("from-env"));
+        assert_eq!(config.hal.driver_dirs.len(), 2);
+        assert_eq!(config.observability.log, "warn,hal=debug");
+
+        config.set("storage.artifacts_dir", "from-flag").unwrap();
+        config.set("engine.seed", "9").unwrap();
+        config.set("safety.max_voltage", "4.5").unwrap();
+        assert_eq!(config.storage.artifacts_dir, Path::new("from-flag"));
+        assert_eq!(config.engine.seed, Some(9));
+        assert_eq!(config.safety.max_voltage, Some(4.5));
+        assert_eq!(config.storage.namespace.as_str(), "optics");
+
+        assert!(config.set("engine.speed", "1").is_err());
+        assert!(config.set("engine.seed", "soon").is_err());
+        assert!(config.set("storage.namespace", "../x").is_err());
+        let err = Config::default()
+            .apply_env(vars(&[("AWEN_SAFETY_MAX_VOLTS", "3")]))
+            .unwrap_err();
+        assert!(
+            err.to_string().starts_with("AWEN_SAFETY_MAX_VOLTS"),
+            "{}",
+            err
+        );
+        std::fs::write(&path, "[engine]\nsede = 1\n").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_validate_reports_every_conflict() {
+        assert!(Config::default().validate().is_ok());
+
+        let mut config = Config::default();
+        for (key, value) in [
+            ("engine.noise_profile", "no-such-profile"),
+            ("observability.log", "loud"),
+            ("observability.event_stream", "localhost"),
+            ("safety.min_voltage", "5"),
+            ("safety.max_voltage", "1"),
+            ("safety.policy_mode", "production"),
+            ("safety.require_tokens", "true"),
+        ] {
+            config.set(key, value).unwrap();
+        }
+        let problems = config.problems();
+        assert_eq!(problems.len(), 6, "{:#?}", problems);
+        for key in [
+            "engine.noise_profile",
+            "observability.log",
+            "observability.event_stream",
+            "safety.min_voltage",
+            "safety.policy_mode",
+            "safety.require_tokens",
+        ] {
+            assert!(problems.iter().any(|p| p.starts_with(key)), "{}", key);
+        }
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_builds_runtime_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = dir.path().join("policy.json");
+        std::fs::write(&policy, r#"{"rules": [{"id": "r", "effect": "deny"}]}"#).unwrap();
+        let mut config = Config::default();
+        config
+            .set("safety.policy", policy.to_str().unwrap())
+            .unwrap();
+        config.set("safety.policy_mode", "production").unwrap();
+        config.set("safety.min_voltage", "-1").unwrap();
+        config.set("observability.log", "error").unwrap();
+        config.validate().unwrap();
+
+        let policy = config.policy().unwrap();
+        assert_eq!(policy.mode, PolicyMode::Production);
+        assert_eq!(policy.rules.len(), 1);
+        assert!(config.access_control().unwrap().is_none());
+        assert_eq!(config.safety_limits().min_voltage, Some(-1.0));
+        let events = config.events().unwrap();
+        events.warning("test", "dropped", Default::default());
+        assert!(events.events().is_empty());
+        config.engine().unwrap();
+        assert!(config.driver_registry().unwrap().list_drivers().is_empty());
+    }
+}
@@ -0,0 +1,427 @@
+//! Periodic drift tracking against the active calibration.
+//!
+//! [`DriftTracker`] reads a set of HAL sensors, turns the readings into [`Measurement`]s and asks
+//! a [`DriftDetector`] whether they still agree with the shared [`CalibrationState`]. Each
+//! [`DriftReport`] is published as an event, `warning` when drift was detected and `debug`
+//! otherwise, along with the metrics [`DRIFT_DELTA`] and [`DRIFT_DETECTED`]. Reports that
+//! recommend an action are also sent to a background calibration runner, typically
+//! [`BackgroundCalibrationRunner::sender`].
+//!
+//! [`DriftTracker::start`] runs the tracker on its own thread every `interval`. A sensor that
+//! fails to read is skipped for that round with a warning. The rest are still checked.
+//!
+//! [`BackgroundCalibrationRunner::sender`]: crate::calibration::BackgroundCalibrationRunner::sender
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::calibration::{
+    CalibrationState, DriftDetector, DriftReport, Measurement, RecalibrationAction,
+};
+use crate::hal::Device;
+use crate::observability::{ErrorCode, EventSink, MetricsCollector};
+
+/// Metric names recorded by [`DriftTracker`].
+pub const DRIFT_DELTA: &str = "drift_delta";
+pub const DRIFT_DETECTED: &str = "drift_detected";
+
+const SOURCE: &str = "control.drift";
+
+/// A HAL sensor checked for drift.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriftSensor {
+    /// Name passed to [`Device::read_sensor`], e.g. `mzi_0:phase_monitor`
+    pub name: String,
+    /// Calibration parameter the reading is compared against, e.g. `phase`
+    pub sensor_id: String,
+    pub unit: String,
+}
+
+pub struct DriftTracker {
+    device: Arc<dyn Device + Send + Sync>,
+    detector: Arc<dyn DriftDetector>,
+    state: Arc<RwLock<CalibrationState>>,
+    sensors: Vec<DriftSensor>,
+    interval: Duration,
+    runner: Option<Sender<DriftReport>>,
+    metrics: MetricsCollector,
+    events: EventSink,
+    rounds: u64,
+}
+
+impl DriftTracker {
+    /// Check `device` against `state` with `detector` every `interval`.
+    pub fn new(
+        device: Arc<dyn Device + Send + Sync>,
+        detector: Arc<dyn DriftDetector>,
+        state: Arc<RwLock<CalibrationState>>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            device,
+            detector,
+            state,
+            sensors: Vec::new(),
+            interval,
+            runner: None,
+            metrics: MetricsCollector::new(),
+            events: EventSink::new(),
+            rounds: 0,
+        }
+    }
+
+    /// Read sensor `name` each round and compare it against calibration parameter `sensor_id`.
+    pub fn with_sensor(mut self, name: &str, sensor_id: &str, unit: &str) -> Self {
+        self.sensors.push(DriftSensor {
+            name: name.to_string(),
+            sensor_id: sensor_id.to_string(),
+            unit: unit.to_string(),
+        });
+        self
+    }
+
+    /// Send reports recommending an action to a background calibration runner.
+    pub fn publish_to(mut self, runner: Sender<DriftReport>) -> Self {
+        self.runner = Some(runner);
+        self
+    }
+
+    /// Record drift telemetry into `metrics` instead of a private collector.
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Publish reports and sensor failures into `events`.
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn sensors(&self) -> &[DriftSensor] {
+        &self.sensors
+    }
+
+    pub fn metrics(&self) -> &MetricsCollector {
+        &self.metrics
+    }
+
+    pub fn events(&self) -> &EventSink {
+        &self.events
+    }
+
+    /// Run one round: read every sensor, detect drift and publish the report.
+    pub fn sample(&mut self) -> Result<DriftReport> {
+        self.rounds += 1;
+        let device_id = self.device.id();
+        let mut measurements = Vec::with_capacity(self.sensors.len());
+        for sensor in &self.sensors {
+            match self.device.read_sensor(&sensor.name) {
+                Ok(value) => measurements.push(Measurement {
+                    measurement_id: format!("{}-{}", sensor.name, self.rounds),
+                    timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+                    sensor_id: sensor.sensor_id.clone(),
+                    value,
+                    unit: sensor.unit.clone(),
+                }),
+                Err(e) => self.events.warning(
+                    SOURCE,
+                    &format!("drift sensor {} unreadable: {}", sensor.name, e),
+                    HashMap::from([
+                        ("device_id".to_string(), device_id.clone()),
+                        ("sensor".to_string(), sensor.name.clone()),
+                    ]),
+                ),
+            }
+        }
+
+        let (report, calibration_id) = {
+            let state = self.state.read().unwrap();
+            let report = self.detector.detect_drift(&state, &measurements)?;
+            (report, state.calibration_id.clone())
+        };
+        self.publish(&report, &calibration_id, &device_id, measurements.len());
+
+        if report.recommended_action != RecalibrationAction::NoAction {
+            if let Some(runner) = &self.runner {
+                if runner.send(report.clone()).is_err() {
+                    self.events.warning(
+                        SOURCE,
+                        "background calibration runner has stopped",
+                        HashMap::from([("calibration_id".to_string(), calibration_id)]),
+                    );
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    fn publish(&self, report: &DriftReport, calibration_id: &str, device_id: &str, read: usize) {
+        for metric in &report.drift_metrics {
+            let attrs = HashMap::from([("metric_id".to_string(), metric.metric_id.clone())]);
+            self.metrics
+                .gauge(DRIFT_DELTA, metric.delta, "ratio", attrs);
+        }
+        let mut attrs = HashMap::from([
+            ("device_id".to_string(), device_id.to_string()),
+            ("calibration_id".to_string(), calibration_id.to_string()),
+            ("measurements".to_string(), read.to_string()),
+            (
+                "recommended_action".to_string(),
+                format!("{:?}", report.recommended_action),
+            ),
+        ]);
+        if report.drift_detected {
+            self.metrics
+                .counter(DRIFT_DETECTED, 1.0, "count", attrs.clone());
+            let drifted: Vec<&str> = report
+                .drift_metrics
+                .iter()
+                .filter(|m| m.threshold_exceeded)
+                .map(|m| m.metric_id.as_str())
+                .collect();
+            attrs.insert("drifted".to_string(), drifted.join(","));
+            self.events.warning(SOURCE, "drift detected", attrs);
+        } else {
+            self.events.debug(SOURCE, "no drift", attrs);
+        }
+    }
+
+    /// Sample every `interval` on a background thread until the returned monitor is stopped.
+    pub fn start(mut self) -> DriftMonitor {
+        let stop = Arc::new(AtomicBool::new(false));
+        let rounds = Arc::new(AtomicU64::new(0));
+        let (stop_flag, count) = (Arc::clone(&stop), Arc::clone(&rounds));
+        let interval = self.interval.max(Duration::from_millis(1));
+
+        let handle = std::thread::spawn(move || {
+            while !stop_flag.load(Ordering::SeqCst) {
+                if let Err(e) = self.sample() {
+                    self.events.error_with_code(
+                        ErrorCode::CalibrationFailure,
+                        SOURCE,
+                        &format!("drift check failed: {}", e),
+                        HashMap::from([("device_id".to_string(), self.device.id())]),
+                    );
+                }
+                count.fetch_add(1, Ordering::SeqCst);
+                // Sleep in short slices so `stop` returns promptly even for long intervals.
+                let mut waited = Duration::ZERO;
+                while waited < interval && !stop_flag.load(Ordering::SeqCst) {
+                    let slice = (interval - waited).min(Duration::from_millis(10));
+                    std::thread::sleep(slice);
+                    waited += slice;
+                }
+            }
+        });
+
+        DriftMonitor {
+            stop,
+            rounds,
+            handle: Some(handle),
+        }
+    }
+}
+
+/// Handle to a [`DriftTracker`] running in the background. Dropping it stops the tracker.
+pub struct DriftMonitor {
+    stop: Arc<AtomicBool>,
+    rounds: Arc<AtomicU64>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DriftMonitor {
+    /// Rounds completed so far
+    pub fn rounds(&self) -> u64 {
+        self.rounds.load(Ordering::SeqCst)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|h| !h.is_finished())
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DriftMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calibration::{
+        BackgroundCalibrationRunner, CalibrationKernel, CalibrationProvenance, CalibrationSchedule,
+        CostFunction, NodeCalibration, NodeCalibrationMetadata, OptimizerAlgorithm,
+        OptimizerConfig, ReferenceCalibrationExecutor, SafetyConstraints, ThresholdDriftDetector,
+    };
+    use crate::hal::{Capability, HalError};
+    use crate::observability::LogFilter;
+    use std::sync::{mpsc, Mutex};
+
+    struct PhaseMonitor {
+        phase: Mutex<f64>,
+    }
+
+    impl Device for PhaseMonitor {
+        fn id(&self) -> String {
+            "chip_0".to_string()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+        fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+            match name {
+                "mzi_0:phase_monitor" => Ok(*self.phase.lock().unwrap()),
+                _ => Err(format!("no sensor {}", name).into()),
+            }
+        }
+    }
+
+    fn calibrated(phase: f64) -> Arc<RwLock<CalibrationState>> {
+        Arc::new(RwLock::new(CalibrationState {
+            calibration_id: "calib-001".to_string(),
+            version: 1,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            node_calibrations: HashMap::from([(
+                "mzi_0".to_string(),
+                NodeCalibration {
+                    node_id: "mzi_0".to_string(),
+                    parameters: HashMap::from([("phase".to_string(), phase)]),
+                    metadata: NodeCalibrationMetadata {
+                        cost_function_value: 0.01,
+                        convergence_iterations: 10,
+                        measurement_snr_db: 20.0,
+                        confidence: 0.95,
+                        calibration_duration_seconds: 1.0,
+                    },
+                },
+            )]),
+            provenance: CalibrationProvenance::default(),
+        }))
+    }
+
+    fn tracker(device: Arc<PhaseMonitor>, state: Arc<RwLock<CalibrationState>>) -> DriftTracker {
+        DriftTracker::new(
+            device,
+            Arc::new(ThresholdDriftDetector::new(0.1)),
+            state,
+            Duration::from_millis(5),
+        )
+        .with_sensor("mzi_0:phase_monitor", "phase", "rad")
+        .with_events(EventSink::with_filter(LogFilter::parse("trace").unwrap()))
+    }
+
+    #[test]
+    fn test_sample_publishes_reports() {
+        let device = Arc::new(PhaseMonitor {
+            phase: Mutex::new(1.02),
+        });
+        let (sender, reports) = mpsc::channel();
+        let mut tracker = tracker(Arc::clone(&device), calibrated(1.0))
+            .with_sensor("mzi_1:phase_monitor", "phase", "rad")
+            .publish_to(sender);
+
+        let stable = tracker.sample().unwrap();
+        assert!(!stable.drift_detected);
+        assert!(reports.try_recv().is_err());
+
+        *device.phase.lock().unwrap() = 1.5;
+        let drifted = tracker.sample().unwrap();
+        assert!(drifted.drift_detected);
+        let published = reports.try_recv().unwrap();
+        assert!(matches!(
+            published.recommended_action,
+            RecalibrationAction::Recalibrate { .. }
+        ));
+
+        let events = tracker.events().events();
+        let messages: Vec<&str> = events.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "drift sensor mzi_1:phase_monitor unreadable: no sensor mzi_1:phase_monitor",
+                "no drift",
+                "drift sensor mzi_1:phase_monitor unreadable: no sensor mzi_1:phase_monitor",
+                "drift detected",
+            ]
+        );
+        assert_eq!(events[3].attributes["drifted"], "mzi_0.phase");
+        assert_eq!(events[3].attributes["measurements"], "1");
+        let recorded = tracker.metrics().metrics();
+        let deltas: Vec<f64> = recorded
+            .iter()
+            .filter(|m| m.name == DRIFT_DELTA)
+            .map(|m| m.value)
+            .collect();
+        assert_eq!(deltas.len(), 2);
+        assert!((deltas[1] - 0.5).abs() < 1e-9);
+        assert_eq!(
+            recorded.iter().filter(|m| m.name == DRIFT_DETECTED).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_background_tracking_triggers_recalibration() {
+        let device = Arc::new(PhaseMonitor {
+            phase: Mutex::new(1.5),
+        });
+        let state = calibrated(1.0);
+        let kernel = CalibrationKernel {
+            id: "k".to_string(),
+            target_nodes: vec!["mzi_0".to_string()],
+            parameters_to_tune: vec!["phase".to_string()],
+            cost_function: CostFunction::Minimize {
+                expression: "loss".to_string(),
+                target_value: None,
+            },
+            measurement_sequence: vec![],
+            optimizer_config: OptimizerConfig {
+                algorithm: OptimizerAlgorithm::NelderMead {
+                    initial_simplex_size: 0.1,
+                },
+                max_iterations: 2,
+                convergence_threshold: 0.0,
+                initial_guess: Some(HashMap::from([("phase".to_string(), 1.5)])),
+            },
+            safety_constraints: SafetyConstraints::default(),
+            schedule: CalibrationSchedule::Manual,
+        };
+        let mut runner = BackgroundCalibrationRunner::start(
+            Arc::new(ReferenceCalibrationExecutor::new()),
+            kernel,
+            Arc::clone(&state),
+            EventSink::with_filter(LogFilter::default()),
+        );
+        let mut monitor = tracker(device, Arc::clone(&state))
+            .publish_to(runner.sender())
+            .start();
+        assert!(monitor.is_running());
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while runner.recalibrations() == 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        monitor.stop();
+        runner.stop();
+        assert!(!monitor.is_running());
+        assert!(monitor.rounds() >= 1);
+        assert!(runner.recalibrations() >= 1);
+        let state = state.read().unwrap();
+        assert!(state.version >= 2);
+        assert!(state.provenance.parent_calibration_id.is_some());
+    }
+}
@@ -0,0 +1,371 @@
+//! Runtime execution of scheduled feedback loops.
+//!
+//! The scheduler only checks that a [`FeedbackLoop`]'s measurement and control nodes are planned
+//! within `deadline_ns` of each other. [`FeedbackExecutor`] runs such a loop for real. It binds
+//! the loop to a measurement, a decision and an actuation closure, and times every iteration
+//! from the end of the measurement to the end of the actuation. An iteration that takes longer
+//! than `deadline_ns` is a deadline miss, and the loop's declared [`ViolationAction`] applies:
+//!
+//! - `Abort` ends the run with an error.
+//! - `Alert` records a warning event and keeps going.
+//! - `Degrade` records a warning and switches to the fallback decision for the rest of the run.
+//!   Without a fallback, the last actuated value is held and the decision is skipped.
+//!
+//! Each iteration records the histogram [`FEEDBACK_LATENCY_NS`]. Each miss records the counter
+//! [`FEEDBACK_DEADLINE_MISS`].
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::observability::{ErrorCode, EventSink, MetricsCollector, SpanContext};
+use crate::scheduler::{FeedbackLoop, ViolationAction};
+
+/// Metric names recorded by [`FeedbackExecutor`].
+pub const FEEDBACK_LATENCY_NS: &str = "feedback_latency_ns";
+pub const FEEDBACK_DEADLINE_MISS: &str = "feedback_deadline_miss";
+
+type Measure<'a> = Box<dyn FnMut() -> Result<f64> + 'a>;
+type Decide<'a> = Box<dyn FnMut(f64) -> f64 + 'a>;
+type Actuate<'a> = Box<dyn FnMut(f64) -> Result<()> + 'a>;
+
+/// Summary of a [`FeedbackExecutor`] run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeedbackReport {
+    pub loop_id: String,
+    pub iterations: usize,
+    pub deadline_misses: usize,
+    pub worst_latency_ns: u64,
+    /// Whether a `Degrade` violation switched the loop away from its primary decision
+    pub degraded: bool,
+    pub last_output: Option<f64>,
+}
+
+pub struct FeedbackExecutor<'a> {
+    feedback: FeedbackLoop,
+    measure: Measure<'a>,
+    decide: Decide<'a>,
+    actuate: Actuate<'a>,
+    fallback: Option<Decide<'a>>,
+    metrics: MetricsCollector,
+    events: EventSink,
+    span: Option<SpanContext>,
+}
+
+impl<'a> FeedbackExecutor<'a> {
+    /// Bind `feedback` to the closures reading its measurement node, turning a measurement into
+    /// a control value, and applying that value to its control node.
+    pub fn new(
+        feedback: FeedbackLoop,
+        measure: impl FnMut() -> Result<f64> + 'a,
+        decide: impl FnMut(f64) -> f64 + 'a,
+        actuate: impl FnMut(f64) -> Result<()> + 'a,
+    ) -> Self {
+        Self {
+            feedback,
+            measure: Box::new(measure),
+            decide: Box::new(decide),
+            actuate: Box::new(actuate),
+            fallback: None,
+            metrics: MetricsCollector::new(),
+            events: EventSink::new(),
+            span: None,
+        }
+    }
+
+    /// Cheaper decision used once a `Degrade` violation has occurred.
+    pub fn with_fallback(mut self, decide: impl FnMut(f64) -> f64 + 'a) -> Self {
+        self.fallback = Some(Box::new(decide));
+        self
+    }
+
+    /// Record loop telemetry into `metrics` instead of a private collector.
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Record deadline warnings and aborts into `events`.
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Trace each run as a `feedback_loop` span under `context`.
+    pub fn with_span(mut self, context: SpanContext) -> Self {
+        self.span = Some(context);
+        self
+    }
+
+    pub fn feedback_loop(&self) -> &FeedbackLoop {
+        &self.feedback
+    }
+
+    pub fn metrics(&self) -> &MetricsCollector {
+        &self.metrics
+    }
+
+    pub fn events(&self) -> &EventSink {
+        &self.events
+    }
+
+    /// Run exactly `iterations` measurement–decision–actuation cycles.
+    pub fn run(&mut self, iterations: usize) -> Result<FeedbackReport> {
+        self.run_while(|i| i < iterations)
+    }
+
+    /// Run until `stop` is set, checked before every cycle.
+    pub fn run_until(&mut self, stop: &AtomicBool) -> Result<FeedbackReport> {
+        self.run_while(|_| !stop.load(Ordering::Relaxed))
+    }
+
+    fn run_while(&mut self, mut keep_going: impl FnMut(usize) -> bool) -> Result<FeedbackReport> {
+        let mut span = self.span.as_ref().map(|c| c.start_span("feedback_loop"));
+        let mut report = FeedbackReport {
+            loop_id: self.feedback.id.clone(),
+            iterations: 0,
+            deadline_misses: 0,
+            worst_latency_ns: 0,
+            degraded: false,
+            last_output: None,
+        };
+
+        let result = loop {
+            if !keep_going(report.iterations) {
+                break Ok(());
+            }
+            let latency_ns = match self.iterate(&mut report) {
+                Ok(latency_ns) => latency_ns,
+                Err(e) => break Err(e),
+            };
+            report.iterations += 1;
+            report.worst_latency_ns = report.worst_latency_ns.max(latency_ns);
+            self.metrics.histogram(
+                FEEDBACK_LATENCY_NS,
+                latency_ns as f64,
+                "ns",
+                self.attributes(),
+            );
+            if latency_ns > self.feedback.deadline_ns {
+                report.deadline_misses += 1;
+                if let Err(e) = self.violate(&mut report, latency_ns) {
+                    break Err(e);
+                }
+            }
+        };
+
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("loop_id", &self.feedback.id);
+            span.set_attribute("iterations", &report.iterations.to_string());
+            span.set_attribute("deadline_misses", &report.deadline_misses.to_string());
+            span.set_attribute("worst_latency_ns", &report.worst_latency_ns.to_string());
+            if let Err(e) = &result {
+                span.set_attribute("error", &e.to_string());
+            }
+            span.end();
+        }
+        result.map(|_| report)
+    }
+
+    /// One cycle; returns the latency from the end of the measurement to the end of the
+    /// actuation.
+    fn iterate(&mut self, report: &mut FeedbackReport) -> Result<u64> {
+        let measurement = (self.measure)()
+            .map_err(|e| anyhow!("feedback {} measure: {}", self.feedback.id, e))?;
+        let started = Instant::now();
+        let output = match (report.degraded, self.fallback.as_mut(), report.last_output) {
+            (false, _, _) => (self.decide)(measurement),
+            (true, Some(fallback), _) => fallback(measurement),
+            (true, None, Some(held)) => held,
+            (true, None, None) => (self.decide)(measurement),
+        };
+        (self.actuate)(output)
+            .map_err(|e| anyhow!("feedback {} actuate: {}", self.feedback.id, e))?;
+        report.last_output = Some(output);
+        Ok(started.elapsed().as_nanos() as u64)
+    }
+
+    fn violate(&mut self, report: &mut FeedbackReport, latency_ns: u64) -> Result<()> {
+        let mut attrs = self.attributes();
+        attrs.insert("latency_ns".to_string(), latency_ns.to_string());
+        attrs.insert(
+            "deadline_ns".to_string(),
+            self.feedback.deadline_ns.to_string(),
+        );
+        attrs.insert("iteration".to_string(), (report.iterations - 1).to_string());
+        self.metrics
+            .counter(FEEDBACK_DEADLINE_MISS, 1.0, "1", attrs.clone());
+
+        let message = format!(
+            "Feedback loop {} latency {}ns exceeds deadline {}ns",
+            self.feedback.id, latency_ns, self.feedback.deadline_ns
+        );
+        match self.feedback.violation_action {
+            ViolationAction::Abort => {
+                self.events.error_with_code(
+                    ErrorCode::SchedulingFailure,
+                    "control",
+                    &message,
+                    attrs,
+                );
+                Err(anyhow!(message))
+            }
+            ViolationAction::Alert => {
+                self.events.warning("control", &message, attrs);
+                Ok(())
+            }
+            ViolationAction::Degrade => {
+                if !report.degraded {
+                    report.degraded = true;
+                    self.events
+                        .warning("control", &format!("{}; degrading", message), attrs);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn attributes(&self) -> HashMap<String, String> {
+        let mut attrs = HashMap::new();
+        attrs.insert("loop_id".to_string(), self.feedback.id.clone());
+        attrs.insert(
+            "measurement_node".to_string(),
+            self.feedback.measurement_node.clone(),
+        );
+        attrs.insert(
+            "control_node".to_string(),
+            self.feedback.control_node.clone(),
+        );
+        attrs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::LogFilter;
+    use crate::scheduler::Priority;
+    use std::cell::{Cell, RefCell};
+    use std::time::Duration;
+
+    /// Iterations listed in `slow` sleep well past the 20 ms deadline inside the actuation.
+    fn feedback(action: ViolationAction) -> FeedbackLoop {
+        FeedbackLoop {
+            id: "fb_0".to_string(),
+            measurement_node: "detector".to_string(),
+            control_node: "phase_0".to_string(),
+            deadline_ns: 20_000_000,
+            priority: Priority::High,
+            violation_action: action,
+        }
+    }
+
+    fn slow_actuation<'a>(
+        slow: &'a [usize],
+        applied: &'a RefCell<Vec<f64>>,
+    ) -> impl FnMut(f64) -> Result<()> + 'a {
+        move |value| {
+            let mut applied = applied.borrow_mut();
+            if slow.contains(&applied.len()) {
+                std::thread::sleep(Duration::from_millis(60));
+            }
+            applied.push(value);
+            Ok(())
+        }
+    }
+
+    fn counter() -> impl FnMut() -> Result<f64> {
+        let mut n = 0.0;
+        move || {
+            n += 1.0;
+            Ok(n)
+        }
+    }
+
+    fn sink() -> EventSink {
+        EventSink::with_filter(LogFilter::parse("trace").unwrap())
+    }
+
+    #[test]
+    fn test_abort_stops_on_first_miss() {
+        let applied = RefCell::new(Vec::new());
+        let mut exec = FeedbackExecutor::new(
+            feedback(ViolationAction::Abort),
+            counter(),
+            |m| 2.0 * m,
+            slow_actuation(&[2], &applied),
+        )
+        .with_events(sink());
+        let err = exec.run(5).unwrap_err();
+        assert!(err.to_string().contains("fb_0"), "{}", err);
+        assert_eq!(*applied.borrow(), vec![2.0, 4.0, 6.0]);
+        assert_eq!(
+            exec.events().events()[0].code,
+            Some(ErrorCode::SchedulingFailure)
+        );
+        assert_eq!(
+            exec.metrics().histogram_values(FEEDBACK_LATENCY_NS).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_alert_records_and_continues() {
+        let applied = RefCell::new(Vec::new());
+        let mut exec = FeedbackExecutor::new(
+            feedback(ViolationAction::Alert),
+            counter(),
+            |m| m,
+            slow_actuation(&[1, 3], &applied),
+        )
+        .with_events(sink());
+        let report = exec.run(5).unwrap();
+        assert_eq!(report.iterations, 5);
+        assert_eq!(report.deadline_misses, 2);
+        assert!(!report.degraded);
+        assert!(report.worst_latency_ns > 20_000_000);
+        assert_eq!(exec.events().events().len(), 2);
+        let misses = exec
+            .metrics()
+            .metrics()
+            .iter()
+            .filter(|m| m.name == FEEDBACK_DEADLINE_MISS)
+            .count();
+        assert_eq!(misses, 2);
+    }
+
+    #[test]
+    fn test_degrade_switches_to_fallback_or_holds() {
+        let applied = RefCell::new(Vec::new());
+        let report = FeedbackExecutor::new(
+            feedback(ViolationAction::Degrade),
+            counter(),
+            |m| 10.0 * m,
+            slow_actuation(&[1], &applied),
+        )
+        .with_fallback(|m| -m)
+        .run(4)
+        .unwrap();
+        assert!(report.degraded);
+        assert_eq!(*applied.borrow(), vec![10.0, 20.0, -3.0, -4.0]);
+
+        let applied = RefCell::new(Vec::new());
+        let decisions = Cell::new(0);
+        let report = FeedbackExecutor::new(
+            feedback(ViolationAction::Degrade),
+            counter(),
+            |m| {
+                decisions.set(decisions.get() + 1);
+                10.0 * m
+            },
+            slow_actuation(&[1], &applied),
+        )
+        .run(4)
+        .unwrap();
+        assert_eq!(report.last_output, Some(20.0));
+        assert_eq!(*applied.borrow(), vec![10.0, 20.0, 20.0, 20.0]);
+        assert_eq!(decisions.get(), 2);
+    }
+}
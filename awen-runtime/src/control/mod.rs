@@ -1,11 +1,60 @@
-// Calibration & control stubs
+// Calibration & control
 
-pub fn calibrate_mzi_chain() {
-    // TODO: Implement calibration routine
+pub mod drift;
+pub mod feedback;
+pub mod mzi_chain;
+pub mod phase_lock;
+pub mod pid;
+pub mod setpoint;
+
+pub use drift::{DriftMonitor, DriftSensor, DriftTracker};
+pub use feedback::{FeedbackExecutor, FeedbackReport};
+pub use mzi_chain::{
+    FringeFit, MziChainCalibration, MziChainConfig, MziChainReport, MziNodeReport,
+};
+pub use phase_lock::{
+    FringeSlope, PhaseLockConfig, PhaseLockLoop, PhaseLockReport, PhaseLockUpdate,
+};
+pub use pid::{PidController, PidGains, PidLoop, PidLoopConfig, PidLoopReport, PidStep};
+pub use setpoint::{Interpolation, SetpointRamp, SetpointRampReport, SetpointSchedule, Waypoint};
+
+use std::time::{Duration, Instant};
+
+/// Fixed-rate schedule for control loops.
+pub(crate) struct Pacer {
+    period: Duration,
+    next: Instant,
+}
+
+impl Pacer {
+    pub(crate) fn new(period: Duration) -> Self {
+        Self {
+            period,
+            next: Instant::now(),
+        }
+    }
+
+    /// Sleep until the next period starts.
+    pub(crate) fn wait(&mut self) {
+        self.next += self.period;
+        let now = Instant::now();
+        if self.next > now {
+            std::thread::sleep(self.next - now);
+        } else {
+            // Fell behind: restart the schedule rather than bursting to catch up
+            self.next = now;
+        }
+    }
 }
 
-pub fn track_drift() {
-    // TODO: Implement drift tracking
+/// Sweep and fit every MZI in `config` and derive the next version of `previous`. See
+/// [`MziChainCalibration`] for telemetry and tracing.
+pub fn calibrate_mzi_chain(
+    device: &dyn crate::hal::Device,
+    config: MziChainConfig,
+    previous: Option<&crate::calibration::CalibrationState>,
+) -> anyhow::Result<MziChainReport> {
+    MziChainCalibration::new(device, config).run(previous)
 }
 
 /// High-level calibration interface: accepts a target kernel/node id and a cost function spec.
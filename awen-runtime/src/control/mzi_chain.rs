@@ -0,0 +1,663 @@
+//! Phase calibration of a chain of thermally tuned MZIs.
+//!
+//! A heater's phase shift grows with its dissipated power, so with `V` the heater voltage the
+//! transmission of an MZI follows the fringe
+//!
+//! `T(V) = offset + amplitude · cos(phase_offset + rad_per_v2 · V²)`
+//!
+//! [`MziChainCalibration`] sweeps each MZI's heater from 0 to `v_max`, one MZI at a time, and
+//! reads its tap monitor at every step. A [`FringeFit`] is found by scanning `rad_per_v2` up to
+//! the sweep's Nyquist limit and solving the linear least-squares problem for the other terms.
+//! The fit is the MZI's phase-voltage map: [`FringeFit::voltage_for`] gives the heater voltage
+//! for a phase, with phase 0 at maximum transmission.
+//!
+//! Each run produces a new version of the [`CalibrationState`], with one [`NodeCalibration`] per
+//! MZI, and an [`MziChainReport`] that can be written next to it with [`MziChainReport::write`].
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::calibration::{
+    CalibrationProvenance, CalibrationState, NodeCalibration, NodeCalibrationMetadata,
+};
+use crate::hal::Device;
+use crate::observability::{EventSink, MetricsCollector, SpanContext};
+
+/// Metric names recorded by [`MziChainCalibration`].
+pub const MZI_FRINGE_VISIBILITY: &str = "mzi_fringe_visibility";
+pub const MZI_FIT_RESIDUAL: &str = "mzi_fit_residual";
+
+/// Kernel id recorded in the provenance of states written by [`MziChainCalibration`].
+pub const MZI_CHAIN_KERNEL_ID: &str = "mzi_chain_fringe_sweep";
+
+/// Frequencies scanned before the golden-section refinement.
+const SCAN_STEPS: usize = 2000;
+const REFINE_ITERATIONS: usize = 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MziChainConfig {
+    /// MZI node ids, calibrated in order
+    pub mzis: Vec<String>,
+    /// Heater parameter of each MZI, written as `<mzi>:<heater_param>`
+    pub heater_param: String,
+    /// Tap monitor of each MZI, read as `<mzi>:<monitor_sensor>`
+    pub monitor_sensor: String,
+    /// Highest heater voltage swept; the sweep must cover at least one whole fringe
+    pub v_max: f64,
+    /// Sweep points per MZI, from 0 V to `v_max` inclusive
+    pub points: usize,
+    /// Wait after each heater write before reading the monitor
+    #[serde(default)]
+    pub settle: Duration,
+}
+
+impl MziChainConfig {
+    pub fn new(mzis: &[&str], v_max: f64, points: usize) -> Self {
+        Self {
+            mzis: mzis.iter().map(|m| m.to_string()).collect(),
+            heater_param: "heater_voltage".to_string(),
+            monitor_sensor: "transmission".to_string(),
+            v_max,
+            points,
+            settle: Duration::ZERO,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.mzis.is_empty() {
+            return Err(anyhow!("MZI chain calibration needs at least one MZI"));
+        }
+        if !(self.v_max.is_finite() && self.v_max > 0.0) {
+            return Err(anyhow!("v_max must be positive, got {}", self.v_max));
+        }
+        if self.points < 8 {
+            return Err(anyhow!(
+                "a fringe sweep needs at least 8 points, got {}",
+                self.points
+            ));
+        }
+        Ok(())
+    }
+
+    fn voltages(&self) -> Vec<f64> {
+        let step = self.v_max / (self.points - 1) as f64;
+        (0..self.points).map(|i| i as f64 * step).collect()
+    }
+}
+
+/// Fitted transmission fringe of one MZI.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FringeFit {
+    pub offset: f64,
+    pub amplitude: f64,
+    /// Phase at 0 V, in `[0, 2π)`
+    pub phase_offset_rad: f64,
+    pub rad_per_v2: f64,
+    /// RMS of the fit residuals, in monitor units
+    pub residual_rms: f64,
+    /// Coefficient of determination of the fit
+    pub r_squared: f64,
+}
+
+impl FringeFit {
+    /// Fit `T(V) = offset + amplitude · cos(phase_offset + rad_per_v2 · V²)` to a sweep.
+    pub fn fit(voltages: &[f64], transmission: &[f64]) -> Result<Self> {
+        if voltages.len() != transmission.len() || voltages.len() < 4 {
+            return Err(anyhow!(
+                "fringe fit needs matching sweeps of at least 4 points"
+            ));
+        }
+        let powers: Vec<f64> = voltages.iter().map(|v| v * v).collect();
+        let span = powers.iter().cloned().fold(0.0, f64::max)
+            - powers.iter().cloned().fold(f64::INFINITY, f64::min);
+        let widest_step = powers
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f64::max);
+        if !(span > 0.0 && widest_step > 0.0) {
+            return Err(anyhow!("fringe sweep does not vary the heater"));
+        }
+
+        // Scan from half a fringe over the sweep up to the Nyquist limit of its widest step.
+        let (lo, hi) = (PI / span, PI / widest_step);
+        if lo >= hi {
+            return Err(anyhow!(
+                "fringe sweep has too few points to resolve a fringe"
+            ));
+        }
+        let cost = |omega: f64| solve_at(omega, &powers, transmission).map(|(_, ssr)| ssr);
+        let step = (hi - lo) / SCAN_STEPS as f64;
+        let mut best = (lo, f64::INFINITY);
+        for i in 0..=SCAN_STEPS {
+            let omega = lo + i as f64 * step;
+            if let Some(ssr) = cost(omega) {
+                if ssr < best.1 {
+                    best = (omega, ssr);
+                }
+            }
+        }
+        let omega = golden_section(
+            |omega| cost(omega).unwrap_or(f64::INFINITY),
+            (best.0 - step).max(lo),
+            (best.0 + step).min(hi),
+        );
+        let ([offset, a, b], ssr) = solve_at(omega, &powers, transmission)
+            .ok_or_else(|| anyhow!("fringe fit is singular"))?;
+
+        let amplitude = a.hypot(b);
+        let mean = transmission.iter().sum::<f64>() / transmission.len() as f64;
+        let total: f64 = transmission.iter().map(|t| (t - mean).powi(2)).sum();
+        let fit = FringeFit {
+            offset,
+            amplitude,
+            // a·cos(ωP) + b·sin(ωP) = amplitude · cos(ωP + θ) with θ = atan2(-b, a)
+            phase_offset_rad: (-b).atan2(a).rem_euclid(TAU),
+            rad_per_v2: omega,
+            residual_rms: (ssr / transmission.len() as f64).sqrt(),
+            r_squared: if total > 0.0 { 1.0 - ssr / total } else { 0.0 },
+        };
+        if fit.amplitude <= 3.0 * fit.residual_rms {
+            return Err(anyhow!(
+                "no fringe: amplitude {:.3e} is within the fit noise {:.3e}",
+                fit.amplitude,
+                fit.residual_rms
+            ));
+        }
+        if fit.rad_per_v2 * span < TAU {
+            return Err(anyhow!(
+                "fringe sweep covers {:.2} rad, less than one fringe",
+                fit.rad_per_v2 * span
+            ));
+        }
+        Ok(fit)
+    }
+
+    /// Phase at heater voltage `v`
+    pub fn phase_at(&self, v: f64) -> f64 {
+        self.phase_offset_rad + self.rad_per_v2 * v * v
+    }
+
+    /// Lowest heater voltage setting `phase`, modulo 2π
+    pub fn voltage_for(&self, phase: f64) -> f64 {
+        ((phase - self.phase_offset_rad).rem_euclid(TAU) / self.rad_per_v2).sqrt()
+    }
+
+    /// Predicted monitor reading at heater voltage `v`
+    pub fn transmission_at(&self, v: f64) -> f64 {
+        self.offset + self.amplitude * self.phase_at(v).cos()
+    }
+
+    /// Heater voltage for a π phase shift from 0 V
+    pub fn v_pi(&self) -> f64 {
+        (PI / self.rad_per_v2).sqrt()
+    }
+
+    /// `(T_max - T_min) / (T_max + T_min)`
+    pub fn visibility(&self) -> f64 {
+        self.amplitude / self.offset
+    }
+
+    pub fn extinction_db(&self) -> f64 {
+        10.0 * ((self.offset + self.amplitude) / (self.offset - self.amplitude).max(1e-12)).log10()
+    }
+
+    fn parameters(&self) -> HashMap<String, f64> {
+        HashMap::from([
+            ("phase_offset_rad".to_string(), self.phase_offset_rad),
+            ("rad_per_v2".to_string(), self.rad_per_v2),
+            ("v_pi".to_string(), self.v_pi()),
+            ("transmission_offset".to_string(), self.offset),
+            ("transmission_amplitude".to_string(), self.amplitude),
+            ("visibility".to_string(), self.visibility()),
+            ("extinction_db".to_string(), self.extinction_db()),
+        ])
+    }
+}
+
+/// Least-squares `offset + a·cos(ωP) + b·sin(ωP)` at a fixed `ω`, with its residual sum of squares.
+fn solve_at(omega: f64, powers: &[f64], transmission: &[f64]) -> Option<([f64; 3], f64)> {
+    let basis = |p: f64| [1.0, (omega * p).cos(), (omega * p).sin()];
+    let mut normal = [[0.0; 4]; 3];
+    for (&p, &t) in powers.iter().zip(transmission) {
+        let row = basis(p);
+        for i in 0..3 {
+            for j in 0..3 {
+                normal[i][j] += row[i] * row[j];
+            }
+            normal[i][3] += row[i] * t;
+        }
+    }
+    // Gaussian elimination with partial pivoting
+    for col in 0..3 {
+        let pivot =
+            (col..3).max_by(|&a, &b| normal[a][col].abs().total_cmp(&normal[b][col].abs()))?;
+        if normal[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        normal.swap(col, pivot);
+        let pivot_row = normal[col];
+        for row in normal.iter_mut().skip(col + 1) {
+            let factor = row[col] / pivot_row[col];
+            for (x, p) in row.iter_mut().zip(pivot_row).skip(col) {
+                *x -= factor * p;
+            }
+        }
+    }
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        let known: f64 = (row + 1..3).map(|k| normal[row][k] * x[k]).sum();
+        x[row] = (normal[row][3] - known) / normal[row][row];
+    }
+    let ssr = powers
+        .iter()
+        .zip(transmission)
+        .map(|(&p, &t)| {
+            let row = basis(p);
+            (t - (x[0] * row[0] + x[1] * row[1] + x[2] * row[2])).powi(2)
+        })
+        .sum();
+    Some((x, ssr))
+}
+
+fn golden_section(f: impl Fn(f64) -> f64, mut lo: f64, mut hi: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut x1 = hi - ratio * (hi - lo);
+    let mut x2 = lo + ratio * (hi - lo);
+    let (mut f1, mut f2) = (f(x1), f(x2));
+    for _ in 0..REFINE_ITERATIONS {
+        if f1 < f2 {
+            hi = x2;
+            (x2, f2) = (x1, f1);
+            x1 = hi - ratio * (hi - lo);
+            f1 = f(x1);
+        } else {
+            lo = x1;
+            (x1, f1) = (x2, f2);
+            x2 = lo + ratio * (hi - lo);
+            f2 = f(x2);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Sweep and fit of one MZI.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MziNodeReport {
+    pub node_id: String,
+    pub fit: FringeFit,
+    pub v_pi: f64,
+    pub visibility: f64,
+    pub extinction_db: f64,
+    pub voltages: Vec<f64>,
+    pub transmission: Vec<f64>,
+    pub duration_s: f64,
+}
+
+/// Result of a [`MziChainCalibration`] run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MziChainReport {
+    pub calibration_id: String,
+    pub version: u64,
+    pub parent_calibration_id: Option<String>,
+    pub timestamp: String,
+    pub nodes: Vec<MziNodeReport>,
+    /// The new calibration, including any nodes of the parent state this run did not touch
+    #[serde(skip)]
+    pub state: CalibrationState,
+}
+
+impl MziChainReport {
+    /// Write `calibration_state_v<version>.json` and `calibration_report_v<version>.json` to
+    /// `dir`, returning their paths.
+    pub fn write(&self, dir: &Path) -> Result<(PathBuf, PathBuf)> {
+        std::fs::create_dir_all(dir)?;
+        let state = dir.join(format!("calibration_state_v{}.json", self.version));
+        let report = dir.join(format!("calibration_report_v{}.json", self.version));
+        std::fs::write(&state, serde_json::to_string_pretty(&self.state)?)?;
+        std::fs::write(&report, serde_json::to_string_pretty(self)?)?;
+        Ok((state, report))
+    }
+}
+
+pub struct MziChainCalibration<'a> {
+    device: &'a dyn Device,
+    config: MziChainConfig,
+    metrics: MetricsCollector,
+    events: EventSink,
+    span: Option<SpanContext>,
+}
+
+impl<'a> MziChainCalibration<'a> {
+    pub fn new(device: &'a dyn Device, config: MziChainConfig) -> Self {
+        Self {
+            device,
+            config,
+            metrics: MetricsCollector::new(),
+            events: EventSink::new(),
+            span: None,
+        }
+    }
+
+    /// Record fit quality into `metrics` instead of a private collector.
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Record one event per calibrated MZI into `events`.
+    pub fn with_events(mut self, events: EventSink) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Trace each run as an `mzi_chain_calibration` span under `context`.
+    pub fn with_span(mut self, context: SpanContext) -> Self {
+        self.span = Some(context);
+        self
+    }
+
+    pub fn config(&self) -> &MziChainConfig {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> &MetricsCollector {
+        &self.metrics
+    }
+
+    pub fn events(&self) -> &EventSink {
+        &self.events
+    }
+
+    /// Calibrate every MZI and derive the next version of `previous`. Each heater is left at
+    /// 0 V after its sweep.
+    pub fn run(&mut self, previous: Option<&CalibrationState>) -> Result<MziChainReport> {
+        self.config.validate()?;
+        let mut span = self
+            .span
+            .as_ref()
+            .map(|c| c.start_span("mzi_chain_calibration"));
+        let result = self.calibrate(previous);
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("device_id", &self.device.id());
+            span.set_attribute("mzis", &self.config.mzis.len().to_string());
+            match &result {
+                Ok(report) => {
+                    span.set_attribute("calibration_id", &report.calibration_id);
+                    span.set_attribute("version", &report.version.to_string());
+                }
+                Err(e) => span.set_attribute("error", &e.to_string()),
+            }
+            span.end();
+        }
+        result
+    }
+
+    fn calibrate(&mut self, previous: Option<&CalibrationState>) -> Result<MziChainReport> {
+        let mut node_calibrations = previous
+            .map(|p| p.node_calibrations.clone())
+            .unwrap_or_default();
+        let mut nodes = Vec::with_capacity(self.config.mzis.len());
+        for mzi in self.config.mzis.clone() {
+            let node = self.calibrate_mzi(&mzi)?;
+            node_calibrations.insert(
+                mzi.clone(),
+                NodeCalibration {
+                    node_id: mzi.clone(),
+                    parameters: node.fit.parameters(),
+                    metadata: NodeCalibrationMetadata {
+                        cost_function_value: node.fit.residual_rms,
+                        convergence_iterations: SCAN_STEPS + REFINE_ITERATIONS,
+                        measurement_snr_db: 20.0
+                            * (node.fit.amplitude / node.fit.residual_rms.max(1e-12)).log10(),
+                        confidence: node.fit.r_squared.clamp(0.0, 1.0),
+                        calibration_duration_seconds: node.duration_s,
+                    },
+                },
+            );
+            nodes.push(node);
+        }
+
+        let state = CalibrationState {
+            calibration_id: format!("calib-{}", uuid::Uuid::new_v4()),
+            version: previous.map_or(1, |p| p.version + 1),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            node_calibrations,
+            provenance: CalibrationProvenance {
+                calibration_kernel_id: MZI_CHAIN_KERNEL_ID.to_string(),
+                optimizer_algorithm: "fringe_fit".to_string(),
+                measurement_count: nodes.len() * self.config.points,
+                parent_calibration_id: previous.map(|p| p.calibration_id.clone()),
+                ..CalibrationProvenance::default()
+            },
+        };
+        Ok(MziChainReport {
+            calibration_id: state.calibration_id.clone(),
+            version: state.version,
+            parent_calibration_id: state.provenance.parent_calibration_id.clone(),
+            timestamp: state.timestamp.clone(),
+            nodes,
+            state,
+        })
+    }
+
+    fn calibrate_mzi(&self, mzi: &str) -> Result<MziNodeReport> {
+        let started = Instant::now();
+        let heater = format!("{}:{}", mzi, self.config.heater_param);
+        let monitor = format!("{}:{}", mzi, self.config.monitor_sensor);
+        let voltages = self.config.voltages();
+        let sweep = voltages
+            .iter()
+            .map(|&v| {
+                self.device
+                    .set_param(&heater, v)
+                    .map_err(|e| anyhow!("heater write {}: {}", heater, e))?;
+                if !self.config.settle.is_zero() {
+                    std::thread::sleep(self.config.settle);
+                }
+                self.device
+                    .read_sensor(&monitor)
+                    .map_err(|e| anyhow!("monitor read {}: {}", monitor, e))
+            })
+            .collect::<Result<Vec<f64>>>();
+        let parked = self.device.set_param(&heater, 0.0);
+        let transmission = sweep?;
+        parked.map_err(|e| anyhow!("heater write {}: {}", heater, e))?;
+
+        let attrs = HashMap::from([
+            ("device_id".to_string(), self.device.id()),
+            ("node_id".to_string(), mzi.to_string()),
+        ]);
+        let fit = match FringeFit::fit(&voltages, &transmission) {
+            Ok(fit) => fit,
+            Err(e) => {
+                let e = anyhow!("calibration of {} failed: {}", mzi, e);
+                self.events
+                    .error("control.mzi_chain", &e.to_string(), attrs);
+                return Err(e);
+            }
+        };
+        self.metrics
+            .gauge(MZI_FRINGE_VISIBILITY, fit.visibility(), "1", attrs.clone());
+        self.metrics
+            .gauge(MZI_FIT_RESIDUAL, fit.residual_rms, "1", attrs.clone());
+        let mut attrs = attrs;
+        attrs.insert("v_pi".to_string(), format!("{:.6}", fit.v_pi()));
+        attrs.insert(
+            "phase_offset_rad".to_string(),
+            format!("{:.6}", fit.phase_offset_rad),
+        );
+        attrs.insert("visibility".to_string(), format!("{:.4}", fit.visibility()));
+        self.events
+            .info("control.mzi_chain", &format!("calibrated {}", mzi), attrs);
+
+        Ok(MziNodeReport {
+            node_id: mzi.to_string(),
+            v_pi: fit.v_pi(),
+            visibility: fit.visibility(),
+            extinction_db: fit.extinction_db(),
+            fit,
+            voltages,
+            transmission,
+            duration_s: started.elapsed().as_secs_f64(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::{Capability, HalError};
+    use crate::observability::LogFilter;
+    use std::sync::Mutex;
+
+    /// Chain of MZIs each with its own heater and tap monitor, as `(offset, rad_per_v2)`.
+    struct Chain {
+        mzis: HashMap<String, (f64, f64)>,
+        heaters: Mutex<HashMap<String, f64>>,
+        reads: Mutex<usize>,
+    }
+
+    impl Chain {
+        fn new(mzis: &[(&str, f64, f64)]) -> Self {
+            Self {
+                mzis: mzis
+                    .iter()
+                    .map(|(id, offset, k)| (id.to_string(), (*offset, *k)))
+                    .collect(),
+                heaters: Mutex::new(HashMap::new()),
+                reads: Mutex::new(0),
+            }
+        }
+    }
+
+    impl Device for Chain {
+        fn id(&self) -> String {
+            "chain".to_string()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+        fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+            self.heaters.lock().unwrap().insert(name.to_string(), value);
+            Ok(())
+        }
+        fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+            let (mzi, _) = name.split_once(':').ok_or("bad sensor")?;
+            let (offset, k) = self.mzis.get(mzi).ok_or("no such MZI")?;
+            let heaters = self.heaters.lock().unwrap();
+            let v = heaters
+                .get(&format!("{}:heater_voltage", mzi))
+                .copied()
+                .unwrap_or(0.0);
+            let mut reads = self.reads.lock().unwrap();
+            *reads += 1;
+            // Small deterministic ripple standing in for detector noise
+            let noise = 0.002 * ((*reads as f64) * 12.9898).sin();
+            Ok(0.5 + 0.45 * (offset + k * v * v).cos() + noise)
+        }
+    }
+
+    #[test]
+    fn test_fit_recovers_phase_voltage_map() {
+        let chain = Chain::new(&[("mzi_0", 0.7, 0.35), ("mzi_1", 4.0, 0.2)]);
+        let events = EventSink::with_filter(LogFilter::default());
+        let previous = CalibrationState::default();
+        let mut calibration =
+            MziChainCalibration::new(&chain, MziChainConfig::new(&["mzi_0", "mzi_1"], 8.0, 201))
+                .with_events(events.clone());
+        let report = calibration.run(Some(&previous)).unwrap();
+
+        for (node, (offset, k)) in report.nodes.iter().zip([(0.7, 0.35), (4.0, 0.2)]) {
+            assert!((node.fit.rad_per_v2 - k).abs() < 1e-3, "{:?}", node.fit);
+            assert!(
+                (node.fit.phase_offset_rad - offset).abs() < 1e-2,
+                "{:?}",
+                node.fit
+            );
+            assert!((node.visibility - 0.9).abs() < 1e-2);
+            // The map drives the true MZI to quadrature
+            let v = node.fit.voltage_for(PI / 2.0);
+            let error = (offset + k * v * v - PI / 2.0).rem_euclid(TAU);
+            assert!(error.min(TAU - error) < 1e-2);
+            assert!((node.v_pi - (PI / k).sqrt()).abs() < 1e-2);
+        }
+        assert_eq!(report.version, 1);
+        assert_eq!(
+            report.parent_calibration_id.as_deref(),
+            Some("default-calib")
+        );
+        assert_eq!(report.state.node_calibrations.len(), 2);
+        assert_eq!(
+            report.state.provenance.calibration_kernel_id,
+            MZI_CHAIN_KERNEL_ID
+        );
+        assert!(report.state.node_calibrations["mzi_0"].metadata.confidence > 0.99);
+        let heaters = chain.heaters.lock().unwrap();
+        assert_eq!(heaters["mzi_0:heater_voltage"], 0.0);
+        assert_eq!(heaters["mzi_1:heater_voltage"], 0.0);
+
+        let messages: Vec<String> = events.events().into_iter().map(|e| e.message).collect();
+        assert_eq!(messages, ["calibrated mzi_0", "calibrated mzi_1"]);
+        assert_eq!(calibration.metrics().metrics().len(), 4);
+    }
+
+    #[test]
+    fn test_report_versions_and_writes_state() {
+        let chain = Chain::new(&[("mzi_0", 1.0, 0.3), ("mzi_1", 2.0, 0.3)]);
+        let config = MziChainConfig::new(&["mzi_0"], 8.0, 161);
+        let first = MziChainCalibration::new(&chain, config).run(None).unwrap();
+        assert_eq!(first.version, 1);
+        assert!(first.parent_calibration_id.is_none());
+
+        let config = MziChainConfig::new(&["mzi_1"], 8.0, 161);
+        let second = MziChainCalibration::new(&chain, config)
+            .run(Some(&first.state))
+            .unwrap();
+        assert_eq!(second.version, 2);
+        assert_eq!(
+            second.parent_calibration_id.as_deref(),
+            Some(first.calibration_id.as_str())
+        );
+        // Nodes the run did not touch are carried over from the parent
+        assert_eq!(second.state.node_calibrations.len(), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let (state, report) = second.write(dir.path()).unwrap();
+        assert!(state.ends_with("calibration_state_v2.json"));
+        assert!(report.ends_with("calibration_report_v2.json"));
+        let saved: CalibrationState =
+            serde_json::from_str(&std::fs::read_to_string(state).unwrap()).unwrap();
+        assert_eq!(saved.calibration_id, second.calibration_id);
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(report).unwrap()).unwrap();
+        assert_eq!(saved["nodes"][0]["node_id"], "mzi_1");
+    }
+
+    #[test]
+    fn test_flat_or_short_sweeps_fail() {
+        let voltages: Vec<f64> = (0..50).map(|i| i as f64 * 0.1).collect();
+        let flat = vec![0.5; 50];
+        let err = FringeFit::fit(&voltages, &flat).unwrap_err();
+        assert!(err.to_string().starts_with("no fringe"), "{}", err);
+
+        // Barely a quarter of a fringe over the sweep
+        let partial: Vec<f64> = voltages
+            .iter()
+            .map(|v| 0.5 + 0.4 * (0.06 * v * v).cos())
+            .collect();
+        assert!(FringeFit::fit(&voltages, &partial).is_err());
+
+        let chain = Chain::new(&[("mzi_0", 1.0, 0.3)]);
+        let events = EventSink::with_filter(LogFilter::default());
+        let err = MziChainCalibration::new(&chain, MziChainConfig::new(&["mzi_9"], 8.0, 100))
+            .with_events(events.clone())
+            .run(None)
+            .unwrap_err();
+        assert!(err.to_string().contains("no such MZI"), "{}", err);
+        assert!(MziChainConfig::new(&["mzi_0"], 8.0, 4).validate().is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! Lock-in phase locking of an interferometer at quadrature.
+//!
+//! [`PhaseLockLoop`] adds a small sinusoidal dither `A·sin θ_k` (with `θ_k = 2πk/N`) to a fast
+//! phase shifter and reads the detector once per sample. Over a window of whole dither periods
+//! it demodulates the detected fringe `T(φ)` at the dither frequency and at twice it:
+//!
+//! - `Y₁ = (2/M)·Σ y_k·sin θ_k ≈ A·T'(φ)`, the fringe slope;
+//! - `Y₂ = (2/M)·Σ y_k·cos 2θ_k ≈ −(A²/4)·T''(φ)`, the fringe curvature.
+//!
+//! Quadrature is where the curvature vanishes and the slope is steepest. The error signal
+//! `e = ±4·Y₂/A² = ∓T''` therefore crosses zero there. A PI [`PidController`] servos the slow
+//! heater to drive it to zero. The sign selects the [`FringeSlope`] to lock to and assumes more
+//! heater power means more phase. The loop reports itself locked when `|e|` is below the lock
+//! threshold and the measured slope has the requested sign.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use super::{Pacer, PidController};
+use crate::hal::Device;
+use crate::observability::{MetricsCollector, SpanContext};
+
+/// Metric names recorded by [`PhaseLockLoop`], one gauge each per servo update.
+pub const PHASE_LOCK_ERROR: &str = "phase_lock_error";
+pub const PHASE_LOCK_SLOPE: &str = "phase_lock_slope";
+pub const PHASE_LOCK_HEATER: &str = "phase_lock_heater";
+pub const PHASE_LOCK_LOCKED: &str = "phase_lock_locked";
+
+/// Side of the fringe to hold, as seen while the phase increases.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FringeSlope {
+    /// Transmission falling with phase, e.g. `cos²(φ/2)` at `φ = π/2`
+    Falling,
+    /// Transmission rising with phase, e.g. `cos²(φ/2)` at `φ = 3π/2`
+    Rising,
+}
+
+impl FringeSlope {
+    fn sign(self) -> f64 {
+        match self {
+            FringeSlope::Falling => 1.0,
+            FringeSlope::Rising => -1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PhaseLockConfig {
+    /// Fast phase shifter carrying the dither, e.g. `mzi_0:dither`
+    pub dither_param: String,
+    /// Detector read once per dither sample, e.g. `mzi_0:transmission`
+    pub detector: String,
+    /// Slow actuator servoed to quadrature, e.g. `heater_0:power`
+    pub heater: String,
+    /// Dither amplitude `A` in radians
+    pub dither_amplitude: f64,
+    /// Samples per dither period `N`; at least 4 to resolve the second harmonic
+    pub samples_per_period: usize,
+    /// Dither periods demodulated per servo update
+    pub periods_per_update: usize,
+    pub sample_rate_hz: f64,
+    pub slope: FringeSlope,
+    /// `|e|` below which the loop counts as locked
+    pub lock_threshold: f64,
+}
+
+impl PhaseLockConfig {
+    fn validate(&self) -> Result<Duration> {
+        if self.samples_per_period < 4 {
+            return Err(anyhow!(
+                "dither needs at least 4 samples per period, got {}",
+                self.samples_per_period
+            ));
+        }
+        if self.periods_per_update == 0 {
+            return Err(anyhow!("demodulation window must span at least one period"));
+        }
+        if !(self.dither_amplitude.is_finite() && self.dither_amplitude > 0.0) {
+            return Err(anyhow!(
+                "dither amplitude must be positive, got {}",
+                self.dither_amplitude
+            ));
+        }
+        if !(self.sample_rate_hz.is_finite() && self.sample_rate_hz > 0.0) {
+            return Err(anyhow!(
+                "sample rate must be positive, got {} Hz",
+                self.sample_rate_hz
+            ));
+        }
+        Ok(Duration::from_secs_f64(1.0 / self.sample_rate_hz))
+    }
+}
+
+/// Demodulated window and the servo's response to it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PhaseLockUpdate {
+    /// Error signal `e`, zero at quadrature
+    pub error: f64,
+    /// Fringe slope `T'` estimated from the first harmonic
+    pub slope: f64,
+    /// Mean detector reading over the window
+    pub mean: f64,
+    pub heater: f64,
+    pub locked: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PhaseLockReport {
+    pub updates: usize,
+    pub last: Option<PhaseLockUpdate>,
+    /// Updates at the end of the run that were all locked
+    pub locked_streak: usize,
+}
+
+pub struct PhaseLockLoop<'a> {
+    device: &'a dyn Device,
+    config: PhaseLockConfig,
+    servo: PidController,
+    metrics: MetricsCollector,
+    span: Option<SpanContext>,
+}
+
+impl<'a> PhaseLockLoop<'a> {
+    /// `servo` drives the heater with the error signal as its input; its setpoint is ignored.
+    pub fn new(device: &'a dyn Device, config: PhaseLockConfig, servo: PidController) -> Self {
+        Self {
+            device,
+            config,
+            servo,
+            metrics: MetricsCollector::new(),
+            span: None,
+        }
+    }
+
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Trace each run as a `phase_lock` span under `context`.
+    pub fn with_span(mut self, context: SpanContext) -> Self {
+        self.span = Some(context);
+        self
+    }
+
+    pub fn metrics(&self) -> &MetricsCollector {
+        &self.metrics
+    }
+
+    pub fn servo(&self) -> &PidController {
+        &self.servo
+    }
+
+    /// Run `updates` demodulation windows, servoing the heater after each. The dither is
+    /// returned to zero afterwards, even on error.
+    pub fn run(&mut self, updates: usize) -> Result<PhaseLockReport> {
+        let sample_period = self.config.validate()?;
+        self.servo.setpoint = 0.0;
+        let mut span = self.span.as_ref().map(|c| c.start_span("phase_lock"));
+        let mut report = PhaseLockReport {
+            updates: 0,
+            last: None,
+            locked_streak: 0,
+        };
+
+        let mut pacer = Pacer::new(sample_period);
+        let mut result = Ok(());
+        for _ in 0..updates {
+            match self.update(&mut pacer) {
+                Ok(update) => {
+                    report.updates += 1;
+                    report.locked_streak = if update.locked {
+                        report.locked_streak + 1
+                    } else {
+                        0
+                    };
+                    report.last = Some(update);
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        let reset = self
+            .device
+            .set_param(&self.config.dither_param, 0.0)
+            .map_err(|e| anyhow!("phase lock write {}: {}", self.config.dither_param, e));
+        let result = result.and(reset);
+
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("detector", &self.config.detector);
+            span.set_attribute("heater", &self.config.heater);
+            span.set_attribute("updates", &report.updates.to_string());
+            span.set_attribute("locked", &(report.locked_streak > 0).to_string());
+            if let Err(e) = &result {
+                span.set_attribute("error", &e.to_string());
+            }
+            span.end();
+        }
+        result.map(|_| report)
+    }
+
+    fn update(&mut self, pacer: &mut Pacer) -> Result<PhaseLockUpdate> {
+        let n = self.config.samples_per_period;
+        let total = n * self.config.periods_per_update;
+        let amplitude = self.config.dither_amplitude;
+        let (mut sum, mut first, mut second) = (0.0, 0.0, 0.0);
+        for k in 0..total {
+            let theta = 2.0 * PI * (k % n) as f64 / n as f64;
+            self.device
+                .set_param(&self.config.dither_param, amplitude * theta.sin())
+                .map_err(|e| anyhow!("phase lock write {}: {}", self.config.dither_param, e))?;
+            let y = self
+                .device
+                .read_sensor(&self.config.detector)
+                .map_err(|e| anyhow!("phase lock read {}: {}", self.config.detector, e))?;
+            sum += y;
+            first += y * theta.sin();
+            second += y * (2.0 * theta).cos();
+            pacer.wait();
+        }
+        let m = total as f64;
+        let slope = 2.0 * first / m / amplitude;
+        let error = self.config.slope.sign() * 4.0 * (2.0 * second / m) / amplitude.powi(2);
+
+        let window_s = m / self.config.sample_rate_hz;
+        let step = self.servo.update(-error, window_s);
+        self.device
+            .set_param(&self.config.heater, step.output)
+            .map_err(|e| anyhow!("phase lock write {}: {}", self.config.heater, e))?;
+
+        let locked =
+            error.abs() < self.config.lock_threshold && slope * self.config.slope.sign() < 0.0;
+        let update = PhaseLockUpdate {
+            error,
+            slope,
+            mean: sum / m,
+            heater: step.output,
+            locked,
+        };
+        let mut attrs = HashMap::new();
+        attrs.insert("device_id".to_string(), self.device.id());
+        attrs.insert("detector".to_string(), self.config.detector.clone());
+        self.metrics
+            .gauge(PHASE_LOCK_ERROR, update.error, "1", attrs.clone());
+        self.metrics
+            .gauge(PHASE_LOCK_SLOPE, update.slope, "1/rad", attrs.clone());
+        self.metrics
+            .gauge(PHASE_LOCK_HEATER, update.heater, "1", attrs.clone());
+        self.metrics
+            .gauge(PHASE_LOCK_LOCKED, f64::from(u8::from(locked)), "1", attrs);
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::PidGains;
+    use crate::hal::{Capability, HalError, SafetyLimits};
+    use std::sync::Mutex;
+
+    /// MZI whose phase is `offset + rad_per_mw·heater + dither`, with the offset drifting by
+    /// `drift` per detector read.
+    struct DriftingMzi {
+        state: Mutex<(f64, f64, f64)>, // (offset, heater, dither)
+        rad_per_mw: f64,
+        drift: f64,
+    }
+
+    impl DriftingMzi {
+        fn new(offset: f64, drift: f64) -> Self {
+            Self {
+                state: Mutex::new((offset, 0.0, 0.0)),
+                rad_per_mw: 0.1,
+                drift,
+            }
+        }
+
+        fn phase(&self) -> f64 {
+            let s = self.state.lock().unwrap();
+            s.0 + self.rad_per_mw * s.1
+        }
+    }
+
+    impl Device for DriftingMzi {
+        fn id(&self) -> String {
+            "mzi".to_string()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+        fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+            let mut s = self.state.lock().unwrap();
+            match name {
+                "heater_0:power" => s.1 = value,
+                "mzi_0:dither" => s.2 = value,
+                _ => return Err(HalError::UnknownChannel(name.to_string())),
+            }
+            Ok(())
+        }
+        fn read_sensor(&self, _name: &str) -> Result<f64, HalError> {
+            let mut s = self.state.lock().unwrap();
+            s.0 += self.drift;
+            let phase = s.0 + self.rad_per_mw * s.1 + s.2;
+            Ok((phase / 2.0).cos().powi(2))
+        }
+    }
+
+    fn config(slope: FringeSlope) -> PhaseLockConfig {
+        PhaseLockConfig {
+            dither_param: "mzi_0:dither".to_string(),
+            detector: "mzi_0:transmission".to_string(),
+            heater: "heater_0:power".to_string(),
+            dither_amplitude: 0.1,
+            samples_per_period: 8,
+            periods_per_update: 2,
+            sample_rate_hz: 1e6,
+            slope,
+            lock_threshold: 0.01,
+        }
+    }
+
+    fn servo() -> PidController {
+        PidController::new(PidGains::new(0.0, 2e5, 0.0), 0.0).with_limits(SafetyLimits {
+            max_voltage: Some(80.0),
+            min_voltage: Some(0.0),
+            max_temperature: None,
+            notes: None,
+        })
+    }
+
+    fn wrapped(phase: f64) -> f64 {
+        phase.rem_euclid(2.0 * PI)
+    }
+
+    #[test]
+    fn test_locks_to_requested_quadrature_despite_drift() {
+        // The heater only adds phase, so each lock starts inside its capture range
+        for (slope, offset, target) in [
+            (FringeSlope::Falling, 0.3, PI / 2.0),
+            (FringeSlope::Rising, 2.0, 3.0 * PI / 2.0),
+        ] {
+            let mzi = DriftingMzi::new(offset, -2e-5);
+            let mut lock = PhaseLockLoop::new(&mzi, config(slope), servo());
+            let report = lock.run(150).unwrap();
+            assert!(report.locked_streak > 10, "{:?}: {:?}", slope, report);
+            assert!(
+                (wrapped(mzi.phase()) - target).abs() < 0.02,
+                "{:?}: phase {}",
+                slope,
+                wrapped(mzi.phase())
+            );
+            let last = report.last.unwrap();
+            assert!((last.mean - 0.5).abs() < 0.02);
+            assert_eq!(mzi.state.lock().unwrap().2, 0.0);
+            let locked: Vec<f64> = lock
+                .metrics()
+                .metrics()
+                .iter()
+                .filter(|m| m.name == PHASE_LOCK_LOCKED)
+                .map(|m| m.value)
+                .collect();
+            assert_eq!(locked.len(), 150);
+            assert_eq!(locked.last(), Some(&1.0));
+        }
+    }
+
+    #[test]
+    fn test_rejects_unresolvable_dither() {
+        let mzi = DriftingMzi::new(0.0, 0.0);
+        let mut bad = config(FringeSlope::Falling);
+        bad.samples_per_period = 3;
+        assert!(PhaseLockLoop::new(&mzi, bad, servo()).run(1).is_err());
+
+        let mut bad = config(FringeSlope::Falling);
+        bad.heater = "heater_7:power".to_string();
+        let err = PhaseLockLoop::new(&mzi, bad, servo()).run(1).unwrap_err();
+        assert!(err.to_string().contains("heater_7:power"));
+        assert_eq!(mzi.state.lock().unwrap().2, 0.0);
+    }
+}
@@ -0,0 +1,445 @@
+//! PID control of one HAL actuator from one HAL sensor.
+//!
+//! [`PidController`] is the discrete controller. It computes
+//! `u = kp·e + ki·∫e dt − kd·dy/dt`, where `e = setpoint − y`. The derivative acts on the
+//! measurement `y`, so setpoint changes do not kick the output. The output is clamped to
+//! the `min_voltage`/`max_voltage` of its [`SafetyLimits`]. The integrator stops accumulating
+//! while the output is saturated and the error pushes it further into the limit, which prevents
+//! windup.
+//!
+//! [`PidLoop`] runs the controller against a [`Device`]. Each period it reads the sensor,
+//! updates the controller and writes the actuator parameter. To honour the safety interlock,
+//! wrap the device in an `InterlockedDevice`. Every iteration is recorded into a
+//! [`MetricsCollector`]:
+//!
+//! - Gauges [`PID_ERROR`], [`PID_OUTPUT`] and [`PID_MEASUREMENT`].
+//! - Histogram [`PID_ITERATION_NS`], the measured work per iteration.
+//! - Counters [`PID_SATURATED`] and [`PID_OVERRUN`], recorded when the output hit a limit or
+//!   the iteration took longer than the period.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::Pacer;
+use crate::hal::{Device, SafetyLimits};
+use crate::observability::{MetricsCollector, SpanContext};
+
+/// Metric names recorded by [`PidLoop`].
+pub const PID_ERROR: &str = "pid_error";
+pub const PID_OUTPUT: &str = "pid_output";
+pub const PID_MEASUREMENT: &str = "pid_measurement";
+pub const PID_ITERATION_NS: &str = "pid_iteration_ns";
+pub const PID_SATURATED: &str = "pid_saturated";
+pub const PID_OVERRUN: &str = "pid_overrun";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PidGains {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+}
+
+impl PidGains {
+    pub fn new(kp: f64, ki: f64, kd: f64) -> Self {
+        Self { kp, ki, kd }
+    }
+}
+
+/// Output of one [`PidController::update`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PidStep {
+    pub error: f64,
+    pub output: f64,
+    /// The unclamped output was outside the safety limits.
+    pub saturated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PidController {
+    pub gains: PidGains,
+    pub setpoint: f64,
+    limits: SafetyLimits,
+    integral: f64,
+    last_measurement: Option<f64>,
+}
+
+impl PidController {
+    pub fn new(gains: PidGains, setpoint: f64) -> Self {
+        Self {
+            gains,
+            setpoint,
+            limits: SafetyLimits {
+                max_voltage: None,
+                min_voltage: None,
+                max_temperature: None,
+                notes: None,
+            },
+            integral: 0.0,
+            last_measurement: None,
+        }
+    }
+
+    /// Clamp the output to `limits.min_voltage..=limits.max_voltage`.
+    pub fn with_limits(mut self, limits: SafetyLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &SafetyLimits {
+        &self.limits
+    }
+
+    /// Accumulated `∫e dt`.
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Clear the integrator and derivative history, e.g. before re-engaging the loop.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_measurement = None;
+    }
+
+    /// Advance the controller by `dt_s` seconds with a new `measurement`.
+    pub fn update(&mut self, measurement: f64, dt_s: f64) -> PidStep {
+        let error = self.setpoint - measurement;
+        let derivative = match self.last_measurement {
+            Some(last) if dt_s > 0.0 => (measurement - last) / dt_s,
+            _ => 0.0,
+        };
+        self.last_measurement = Some(measurement);
+
+        let integral = self.integral + error * dt_s;
+        let raw = self.gains.kp * error + self.gains.ki * integral - self.gains.kd * derivative;
+        let output = self.clamp(raw);
+        let saturated = output != raw;
+        // Conditional integration: hold the integrator while the error drives further into the
+        // limit
+        if !saturated || (raw > output) != (error > 0.0) {
+            self.integral = integral;
+        }
+        PidStep {
+            error,
+            output,
+            saturated,
+        }
+    }
+
+    fn clamp(&self, value: f64) -> f64 {
+        let value = match self.limits.max_voltage {
+            Some(max) => value.min(max),
+            None => value,
+        };
+        match self.limits.min_voltage {
+            Some(min) => value.max(min),
+            None => value,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PidLoopConfig {
+    /// Sensor read each iteration, e.g. `heater_0:temperature`
+    pub sensor: String,
+    /// Parameter written each iteration, e.g. `heater_0:power`
+    pub actuator: String,
+    pub rate_hz: f64,
+}
+
+impl PidLoopConfig {
+    pub fn period(&self) -> Result<Duration> {
+        if !(self.rate_hz.is_finite() && self.rate_hz > 0.0) {
+            return Err(anyhow!(
+                "loop rate must be positive, got {} Hz",
+                self.rate_hz
+            ));
+        }
+        Ok(Duration::from_secs_f64(1.0 / self.rate_hz))
+    }
+}
+
+/// Summary of a [`PidLoop`] run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PidLoopReport {
+    pub iterations: usize,
+    pub final_measurement: f64,
+    pub final_output: f64,
+    pub final_error: f64,
+    pub saturated_iterations: usize,
+    pub overruns: usize,
+}
+
+pub struct PidLoop<'a> {
+    device: &'a dyn Device,
+    config: PidLoopConfig,
+    controller: PidController,
+    metrics: MetricsCollector,
+    span: Option<SpanContext>,
+}
+
+impl<'a> PidLoop<'a> {
+    pub fn new(device: &'a dyn Device, config: PidLoopConfig, controller: PidController) -> Self {
+        Self {
+            device,
+            config,
+            controller,
+            metrics: MetricsCollector::new(),
+            span: None,
+        }
+    }
+
+    /// Record loop telemetry into `metrics` instead of a private collector.
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Trace each run as a `pid_loop` span under `context`.
+    pub fn with_span(mut self, context: SpanContext) -> Self {
+        self.span = Some(context);
+        self
+    }
+
+    pub fn controller(&self) -> &PidController {
+        &self.controller
+    }
+
+    pub fn controller_mut(&mut self) -> &mut PidController {
+        &mut self.controller
+    }
+
+    pub fn metrics(&self) -> &MetricsCollector {
+        &self.metrics
+    }
+
+    /// Run exactly `iterations` periods.
+    pub fn run(&mut self, iterations: usize) -> Result<PidLoopReport> {
+        self.run_while(|i| i < iterations)
+    }
+
+    /// Run until `stop` is set, checked before every period.
+    pub fn run_until(&mut self, stop: &AtomicBool) -> Result<PidLoopReport> {
+        self.run_while(|_| !stop.load(Ordering::Relaxed))
+    }
+
+    fn run_while(&mut self, mut keep_going: impl FnMut(usize) -> bool) -> Result<PidLoopReport> {
+        let period = self.config.period()?;
+        let dt_s = period.as_secs_f64();
+        let mut span = self.span.as_ref().map(|c| c.start_span("pid_loop"));
+        let mut report = PidLoopReport {
+            iterations: 0,
+            final_measurement: 0.0,
+            final_output: 0.0,
+            final_error: 0.0,
+            saturated_iterations: 0,
+            overruns: 0,
+        };
+
+        let mut pacer = Pacer::new(period);
+        let result = loop {
+            if !keep_going(report.iterations) {
+                break Ok(());
+            }
+            let started = Instant::now();
+            let step = match self.iterate(dt_s) {
+                Ok(step) => step,
+                Err(e) => break Err(e),
+            };
+            let elapsed = started.elapsed();
+
+            report.iterations += 1;
+            report.final_measurement = step.0;
+            report.final_output = step.1.output;
+            report.final_error = step.1.error;
+            let attrs = self.attributes();
+            self.metrics.histogram(
+                PID_ITERATION_NS,
+                elapsed.as_nanos() as f64,
+                "ns",
+                attrs.clone(),
+            );
+            if step.1.saturated {
+                report.saturated_iterations += 1;
+                self.metrics.counter(PID_SATURATED, 1.0, "1", attrs.clone());
+            }
+            if elapsed > period {
+                report.overruns += 1;
+                self.metrics.counter(PID_OVERRUN, 1.0, "1", attrs);
+            }
+
+            pacer.wait();
+        };
+
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("sensor", &self.config.sensor);
+            span.set_attribute("actuator", &self.config.actuator);
+            span.set_attribute("iterations", &report.iterations.to_string());
+            span.set_attribute("overruns", &report.overruns.to_string());
+            if let Err(e) = &result {
+                span.set_attribute("error", &e.to_string());
+            }
+            span.end();
+        }
+        result.map(|_| report)
+    }
+
+    /// One read–update–write cycle; returns the measurement and the controller step.
+    fn iterate(&mut self, dt_s: f64) -> Result<(f64, PidStep)> {
+        let measurement = self
+            .device
+            .read_sensor(&self.config.sensor)
+            .map_err(|e| anyhow!("pid read {}: {}", self.config.sensor, e))?;
+        let step = self.controller.update(measurement, dt_s);
+        self.device
+            .set_param(&self.config.actuator, step.output)
+            .map_err(|e| anyhow!("pid write {}: {}", self.config.actuator, e))?;
+
+        let attrs = self.attributes();
+        self.metrics
+            .gauge(PID_MEASUREMENT, measurement, "1", attrs.clone());
+        self.metrics
+            .gauge(PID_ERROR, step.error, "1", attrs.clone());
+        self.metrics.gauge(PID_OUTPUT, step.output, "1", attrs);
+        Ok((measurement, step))
+    }
+
+    fn attributes(&self) -> HashMap<String, String> {
+        let mut attrs = HashMap::new();
+        attrs.insert("device_id".to_string(), self.device.id());
+        attrs.insert("sensor".to_string(), self.config.sensor.clone());
+        attrs.insert("actuator".to_string(), self.config.actuator.clone());
+        attrs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::{Capability, HalError};
+    use std::sync::Mutex;
+
+    /// First-order plant: each read relaxes the temperature toward `gain · drive` with time
+    /// constant `tau` iterations.
+    struct Plant {
+        drive: Mutex<f64>,
+        temperature: Mutex<f64>,
+        gain: f64,
+        tau: f64,
+    }
+
+    impl Plant {
+        fn new() -> Self {
+            Self {
+                drive: Mutex::new(0.0),
+                temperature: Mutex::new(0.0),
+                gain: 2.0,
+                tau: 5.0,
+            }
+        }
+    }
+
+    impl Device for Plant {
+        fn id(&self) -> String {
+            "plant".to_string()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+        fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+            if name != "heater_0:power" {
+                return Err(HalError::UnknownChannel(name.to_string()));
+            }
+            *self.drive.lock().unwrap() = value;
+            Ok(())
+        }
+        fn read_sensor(&self, _name: &str) -> Result<f64, HalError> {
+            let target = self.gain * *self.drive.lock().unwrap();
+            let mut t = self.temperature.lock().unwrap();
+            *t += (target - *t) / self.tau;
+            Ok(*t)
+        }
+    }
+
+    fn limits(min: f64, max: f64) -> SafetyLimits {
+        SafetyLimits {
+            max_voltage: Some(max),
+            min_voltage: Some(min),
+            max_temperature: None,
+            notes: None,
+        }
+    }
+
+    fn config() -> PidLoopConfig {
+        PidLoopConfig {
+            sensor: "heater_0:temperature".to_string(),
+            actuator: "heater_0:power".to_string(),
+            rate_hz: 100_000.0,
+        }
+    }
+
+    #[test]
+    fn test_loop_reaches_setpoint_and_records_telemetry() {
+        let plant = Plant::new();
+        let controller =
+            PidController::new(PidGains::new(0.3, 2000.0, 0.0), 1.5).with_limits(limits(0.0, 5.0));
+        let tracer = crate::observability::TracerHandle::new();
+        let mut pid = PidLoop::new(&plant, config(), controller).with_span(tracer.context());
+        let report = pid.run(300).unwrap();
+
+        assert_eq!(report.iterations, 300);
+        assert!(report.final_error.abs() < 1e-3, "{:?}", report);
+        assert!((report.final_output - 0.75).abs() < 1e-2);
+        let metrics = pid.metrics().metrics();
+        let outputs = metrics.iter().filter(|m| m.name == PID_OUTPUT).count();
+        assert_eq!(outputs, 300);
+        assert_eq!(pid.metrics().histogram_values(PID_ITERATION_NS).len(), 300);
+        let spans = tracer.spans();
+        assert_eq!(spans[0].name, "pid_loop");
+        assert_eq!(spans[0].attributes["iterations"], "300");
+    }
+
+    #[test]
+    fn test_output_clamped_without_windup() {
+        // Setpoint out of reach: the output pins at the limit and the integrator holds
+        let mut controller =
+            PidController::new(PidGains::new(1.0, 10.0, 0.0), 100.0).with_limits(limits(-1.0, 1.0));
+        for _ in 0..1000 {
+            let step = controller.update(0.0, 0.01);
+            assert_eq!(step.output, 1.0);
+            assert!(step.saturated);
+        }
+        assert!(controller.integral() < 1.0, "{}", controller.integral());
+
+        // Once the setpoint is reachable the output leaves the limit straight away
+        controller.setpoint = 0.0;
+        let step = controller.update(0.5, 0.01);
+        assert!(step.output < 0.0 && !step.saturated, "{:?}", step);
+    }
+
+    #[test]
+    fn test_derivative_acts_on_measurement() {
+        let mut controller = PidController::new(PidGains::new(0.0, 0.0, 1.0), 0.0);
+        assert_eq!(controller.update(1.0, 0.1).output, 0.0);
+        controller.setpoint = 10.0;
+        assert_eq!(controller.update(1.0, 0.1).output, 0.0);
+        assert!((controller.update(1.5, 0.1).output + 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_device_errors_stop_the_loop() {
+        let plant = Plant::new();
+        let mut bad = config();
+        bad.actuator = "heater_9:power".to_string();
+        let controller = PidController::new(PidGains::new(1.0, 0.0, 0.0), 1.0);
+        let err = PidLoop::new(&plant, bad, controller).run(5).unwrap_err();
+        assert!(err.to_string().contains("heater_9:power"));
+
+        let mut zero_rate = config();
+        zero_rate.rate_hz = 0.0;
+        let controller = PidController::new(PidGains::new(1.0, 0.0, 0.0), 1.0);
+        assert!(PidLoop::new(&plant, zero_rate, controller).run(1).is_err());
+    }
+}
@@ -0,0 +1,497 @@
+//! Setpoint trajectories for slow actuators such as heater temperature or bias voltage.
+//!
+//! A [`SetpointSchedule`] lists waypoints `(t, value)` for one HAL parameter and interpolates
+//! between them. [`Interpolation::Linear`] gives straight ramps. [`Interpolation::Spline`] is a
+//! monotone cubic (Fritsch–Carlson) through the same waypoints. It is smooth but never
+//! overshoots a waypoint, so it stays inside any range the waypoints respect.
+//!
+//! [`SetpointRamp`] plays a schedule into a [`Device`] at a fixed rate. Each write moves at most
+//! `max_slew_per_s · dt` from the previous one, so the commanded value can lag the trajectory.
+//! The ramp keeps running past the last waypoint until it reaches the final value. Every
+//! segment becomes one [`TimelineEvent`] on the `Control` lane.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::Pacer;
+use crate::hal::Device;
+use crate::observability::timeline::lanes;
+use crate::observability::{MetricsCollector, SpanContext, TimelineEvent};
+
+/// Metric names recorded by [`SetpointRamp`].
+pub const SETPOINT_TARGET: &str = "setpoint_target";
+pub const SETPOINT_COMMAND: &str = "setpoint_command";
+pub const SETPOINT_SLEW_LIMITED: &str = "setpoint_slew_limited";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    Linear,
+    Spline,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Waypoint {
+    /// Seconds from the start of the schedule
+    pub t_s: f64,
+    pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetpointSchedule {
+    /// HAL parameter written by the ramp, e.g. `heater_0:power`
+    pub param: String,
+    pub waypoints: Vec<Waypoint>,
+    pub interpolation: Interpolation,
+    /// Largest change per second written to the device; `None` for no limit
+    #[serde(default)]
+    pub max_slew_per_s: Option<f64>,
+}
+
+impl SetpointSchedule {
+    pub fn new(param: &str, interpolation: Interpolation) -> Self {
+        Self {
+            param: param.to_string(),
+            waypoints: Vec::new(),
+            interpolation,
+            max_slew_per_s: None,
+        }
+    }
+
+    pub fn with_waypoint(mut self, t_s: f64, value: f64) -> Self {
+        self.waypoints.push(Waypoint { t_s, value });
+        self
+    }
+
+    pub fn with_slew_limit(mut self, max_slew_per_s: f64) -> Self {
+        self.max_slew_per_s = Some(max_slew_per_s);
+        self
+    }
+
+    /// Waypoints must be finite, start at `t = 0` and strictly increase in time.
+    pub fn validate(&self) -> Result<()> {
+        let first = self
+            .waypoints
+            .first()
+            .ok_or_else(|| anyhow!("setpoint schedule for {} has no waypoints", self.param))?;
+        if first.t_s != 0.0 {
+            return Err(anyhow!(
+                "setpoint schedule for {} starts at t = {} s, not 0",
+                self.param,
+                first.t_s
+            ));
+        }
+        if let Some(w) = self
+            .waypoints
+            .iter()
+            .find(|w| !(w.t_s.is_finite() && w.value.is_finite()))
+        {
+            return Err(anyhow!(
+                "setpoint schedule for {} has a non-finite waypoint {:?}",
+                self.param,
+                w
+            ));
+        }
+        if let Some(pair) = self.waypoints.windows(2).find(|p| p[1].t_s <= p[0].t_s) {
+            return Err(anyhow!(
+                "setpoint schedule for {} goes back in time at t = {} s",
+                self.param,
+                pair[1].t_s
+            ));
+        }
+        if let Some(rate) = self.max_slew_per_s {
+            if !(rate.is_finite() && rate > 0.0) {
+                return Err(anyhow!("slew limit must be positive, got {}", rate));
+            }
+        }
+        Ok(())
+    }
+
+    /// Time of the last waypoint.
+    pub fn duration_s(&self) -> f64 {
+        self.waypoints.last().map(|w| w.t_s).unwrap_or(0.0)
+    }
+
+    /// Index of the segment `[w_i, w_i+1]` containing `t_s`, clamped to the first and last.
+    pub fn segment_at(&self, t_s: f64) -> usize {
+        let last = self.waypoints.len().saturating_sub(2);
+        self.waypoints[1..]
+            .iter()
+            .position(|w| t_s < w.t_s)
+            .unwrap_or(last)
+            .min(last)
+    }
+
+    /// Trajectory value at `t_s`, holding the end values outside the schedule.
+    pub fn value_at(&self, t_s: f64) -> f64 {
+        let w = &self.waypoints;
+        match w.len() {
+            0 => return 0.0,
+            1 => return w[0].value,
+            _ => {}
+        }
+        if t_s <= w[0].t_s {
+            return w[0].value;
+        }
+        if t_s >= self.duration_s() {
+            return w[w.len() - 1].value;
+        }
+        let i = self.segment_at(t_s);
+        let (a, b) = (w[i], w[i + 1]);
+        let h = b.t_s - a.t_s;
+        let s = (t_s - a.t_s) / h;
+        match self.interpolation {
+            Interpolation::Linear => a.value + s * (b.value - a.value),
+            Interpolation::Spline => {
+                let (ma, mb) = (self.tangent(i), self.tangent(i + 1));
+                let (s2, s3) = (s * s, s * s * s);
+                (2.0 * s3 - 3.0 * s2 + 1.0) * a.value
+                    + (s3 - 2.0 * s2 + s) * h * ma
+                    + (-2.0 * s3 + 3.0 * s2) * b.value
+                    + (s3 - s2) * h * mb
+            }
+        }
+    }
+
+    /// Fritsch–Carlson tangent at waypoint `i`: the secant at either end, zero at interior
+    /// extrema, otherwise a weighted harmonic mean of the neighbouring secants.
+    fn tangent(&self, i: usize) -> f64 {
+        let w = &self.waypoints;
+        let secant = |j: usize| (w[j + 1].value - w[j].value) / (w[j + 1].t_s - w[j].t_s);
+        match i {
+            0 => secant(0),
+            i if i == w.len() - 1 => secant(i - 1),
+            i => {
+                let (d0, d1) = (secant(i - 1), secant(i));
+                if d0 * d1 <= 0.0 {
+                    0.0
+                } else {
+                    let (h0, h1) = (w[i].t_s - w[i - 1].t_s, w[i + 1].t_s - w[i].t_s);
+                    let (w0, w1) = (2.0 * h1 + h0, h1 + 2.0 * h0);
+                    (w0 + w1) / (w0 / d0 + w1 / d1)
+                }
+            }
+        }
+    }
+}
+
+/// Summary of a [`SetpointRamp`] run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetpointRampReport {
+    pub steps: usize,
+    pub final_value: f64,
+    /// Steps where the slew limit held the command back from the trajectory
+    pub slew_limited_steps: usize,
+    /// One event per segment, on the `Control` lane
+    pub events: Vec<TimelineEvent>,
+}
+
+pub struct SetpointRamp<'a> {
+    device: &'a dyn Device,
+    schedule: SetpointSchedule,
+    rate_hz: f64,
+    metrics: MetricsCollector,
+    span: Option<SpanContext>,
+}
+
+impl<'a> SetpointRamp<'a> {
+    pub fn new(device: &'a dyn Device, schedule: SetpointSchedule, rate_hz: f64) -> Self {
+        Self {
+            device,
+            schedule,
+            rate_hz,
+            metrics: MetricsCollector::new(),
+            span: None,
+        }
+    }
+
+    /// Record ramp telemetry into `metrics` instead of a private collector.
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Trace each run as a `setpoint_ramp` span under `context`.
+    pub fn with_span(mut self, context: SpanContext) -> Self {
+        self.span = Some(context);
+        self
+    }
+
+    pub fn schedule(&self) -> &SetpointSchedule {
+        &self.schedule
+    }
+
+    pub fn metrics(&self) -> &MetricsCollector {
+        &self.metrics
+    }
+
+    /// Play the whole schedule, starting from its first waypoint.
+    pub fn run(&mut self) -> Result<SetpointRampReport> {
+        self.schedule.validate()?;
+        if !(self.rate_hz.is_finite() && self.rate_hz > 0.0) {
+            return Err(anyhow!(
+                "ramp rate must be positive, got {} Hz",
+                self.rate_hz
+            ));
+        }
+        let dt_s = 1.0 / self.rate_hz;
+        let mut span = self.span.as_ref().map(|c| c.start_span("setpoint_ramp"));
+        let result = self.play(dt_s);
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("param", &self.schedule.param);
+            span.set_attribute("segments", &self.segments().to_string());
+            match &result {
+                Ok(report) => span.set_attribute("steps", &report.steps.to_string()),
+                Err(e) => span.set_attribute("error", &e.to_string()),
+            }
+            span.end();
+        }
+        result
+    }
+
+    fn segments(&self) -> usize {
+        self.schedule.waypoints.len().saturating_sub(1).max(1)
+    }
+
+    fn play(&mut self, dt_s: f64) -> Result<SetpointRampReport> {
+        let param = self.schedule.param.clone();
+        let final_target = self.schedule.value_at(self.schedule.duration_s());
+        let max_step = self.schedule.max_slew_per_s.map(|rate| rate * dt_s);
+        let mut report = SetpointRampReport {
+            steps: 0,
+            final_value: self.schedule.waypoints[0].value,
+            slew_limited_steps: 0,
+            events: Vec::new(),
+        };
+
+        let mut pacer = Pacer::new(Duration::from_secs_f64(dt_s));
+        let mut segment = Segment::start(0, 0.0);
+        let mut command = report.final_value;
+        loop {
+            let t_s = report.steps as f64 * dt_s;
+            let target = self.schedule.value_at(t_s);
+            let mut limited = false;
+            if report.steps > 0 {
+                let delta = target - command;
+                match max_step {
+                    Some(max) if delta.abs() > max => {
+                        command += max.copysign(delta);
+                        limited = true;
+                    }
+                    _ => command = target,
+                }
+            }
+            let index = self.schedule.segment_at(t_s);
+            if index != segment.index {
+                report
+                    .events
+                    .push(self.segment_event(&segment, t_s, command));
+                segment = Segment::start(index, t_s);
+            }
+
+            self.device
+                .set_param(&param, command)
+                .map_err(|e| anyhow!("setpoint write {}: {}", param, e))?;
+            let attrs = self.attributes(index);
+            self.metrics
+                .gauge(SETPOINT_TARGET, target, "1", attrs.clone());
+            self.metrics
+                .gauge(SETPOINT_COMMAND, command, "1", attrs.clone());
+            if limited {
+                report.slew_limited_steps += 1;
+                segment.slew_limited += 1;
+                self.metrics.counter(SETPOINT_SLEW_LIMITED, 1.0, "1", attrs);
+            }
+            report.steps += 1;
+            report.final_value = command;
+
+            if t_s >= self.schedule.duration_s() && command == final_target {
+                report
+                    .events
+                    .push(self.segment_event(&segment, t_s, command));
+                return Ok(report);
+            }
+            pacer.wait();
+        }
+    }
+
+    fn segment_event(&self, segment: &Segment, end_t_s: f64, reached: f64) -> TimelineEvent {
+        let index = segment.index.min(self.schedule.waypoints.len() - 1);
+        let from = self.schedule.waypoints[index];
+        let to = self
+            .schedule
+            .waypoints
+            .get(index + 1)
+            .copied()
+            .unwrap_or(from);
+        let mut attributes = self.attributes(segment.index);
+        attributes.insert("from".to_string(), from.value.to_string());
+        attributes.insert("to".to_string(), to.value.to_string());
+        attributes.insert("reached".to_string(), reached.to_string());
+        attributes.insert("start_s".to_string(), segment.start_t_s.to_string());
+        attributes.insert("end_s".to_string(), end_t_s.to_string());
+        attributes.insert(
+            "slew_limited_steps".to_string(),
+            segment.slew_limited.to_string(),
+        );
+        let now_ms = Utc::now().timestamp_millis() as u128;
+        let elapsed_ms = ((end_t_s - segment.start_t_s) * 1000.0) as u128;
+        TimelineEvent {
+            lane: lanes::CONTROL.to_string(),
+            name: format!("setpoint:{}:{}", self.schedule.param, segment.index),
+            start_ms: now_ms.saturating_sub(elapsed_ms),
+            end_ms: now_ms,
+            attributes,
+        }
+    }
+
+    fn attributes(&self, segment: usize) -> HashMap<String, String> {
+        let mut attrs = HashMap::new();
+        attrs.insert("device_id".to_string(), self.device.id());
+        attrs.insert("param".to_string(), self.schedule.param.clone());
+        attrs.insert("segment".to_string(), segment.to_string());
+        attrs
+    }
+}
+
+struct Segment {
+    index: usize,
+    start_t_s: f64,
+    slew_limited: usize,
+}
+
+impl Segment {
+    fn start(index: usize, start_t_s: f64) -> Self {
+        Self {
+            index,
+            start_t_s,
+            slew_limited: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::{Capability, HalError};
+    use std::sync::Mutex;
+
+    struct Recorder {
+        writes: Mutex<Vec<(String, f64)>>,
+    }
+
+    impl Device for Recorder {
+        fn id(&self) -> String {
+            "recorder".to_string()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+        fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+            self.writes.lock().unwrap().push((name.to_string(), value));
+            Ok(())
+        }
+        fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+            Err(HalError::UnknownChannel(name.to_string()))
+        }
+    }
+
+    fn ramp_up_hold_down(interpolation: Interpolation) -> SetpointSchedule {
+        SetpointSchedule::new("heater_0:power", interpolation)
+            .with_waypoint(0.0, 0.0)
+            .with_waypoint(0.01, 10.0)
+            .with_waypoint(0.02, 10.0)
+            .with_waypoint(0.03, 4.0)
+    }
+
+    #[test]
+    fn test_interpolation_hits_waypoints_without_overshoot() {
+        for interpolation in [Interpolation::Linear, Interpolation::Spline] {
+            let schedule = ramp_up_hold_down(interpolation);
+            schedule.validate().unwrap();
+            for w in &schedule.waypoints {
+                assert!((schedule.value_at(w.t_s) - w.value).abs() < 1e-12);
+            }
+            for k in 0..=300 {
+                let v = schedule.value_at(k as f64 * 1e-4);
+                assert!(
+                    (-1e-12..=10.0 + 1e-12).contains(&v),
+                    "{:?}: {}",
+                    interpolation,
+                    v
+                );
+            }
+            assert_eq!(schedule.value_at(-1.0), 0.0);
+            assert_eq!(schedule.value_at(1.0), 4.0);
+        }
+        let linear = ramp_up_hold_down(Interpolation::Linear);
+        assert!((linear.value_at(0.0025) - 2.5).abs() < 1e-9);
+        // The spline bends into the flat hold instead of following the straight ramp
+        let spline = ramp_up_hold_down(Interpolation::Spline);
+        assert!((spline.value_at(0.015) - 10.0).abs() < 1e-12);
+        assert!(spline.value_at(0.0025) != linear.value_at(0.0025));
+    }
+
+    #[test]
+    fn test_ramp_respects_slew_limit_and_emits_segment_events() {
+        let device = Recorder {
+            writes: Mutex::new(Vec::new()),
+        };
+        // 10 units in 10 ms needs 1000/s; the limit allows half that
+        let schedule = ramp_up_hold_down(Interpolation::Linear).with_slew_limit(500.0);
+        let mut ramp = SetpointRamp::new(&device, schedule, 10_000.0);
+        let report = ramp.run().unwrap();
+
+        let writes = device.writes.lock().unwrap();
+        assert_eq!(writes.len(), report.steps);
+        assert!(writes.iter().all(|(name, _)| name == "heater_0:power"));
+        for pair in writes.windows(2) {
+            assert!((pair[1].1 - pair[0].1).abs() <= 500.0 / 10_000.0 + 1e-9);
+        }
+        assert_eq!(writes.last().unwrap().1, 4.0);
+        assert_eq!(report.final_value, 4.0);
+        assert!(report.slew_limited_steps > 0);
+
+        assert_eq!(report.events.len(), 3);
+        for (i, event) in report.events.iter().enumerate() {
+            assert_eq!(event.lane, lanes::CONTROL);
+            assert_eq!(event.name, format!("setpoint:heater_0:power:{}", i));
+        }
+        // The command lags the ramp, so the first segment ends short of its target
+        let reached: f64 = report.events[0].attributes["reached"].parse().unwrap();
+        assert!(reached < 10.0);
+        assert_ne!(report.events[0].attributes["slew_limited_steps"], "0");
+    }
+
+    #[test]
+    fn test_rejects_invalid_schedules() {
+        let device = Recorder {
+            writes: Mutex::new(Vec::new()),
+        };
+        let backwards = SetpointSchedule::new("bias_0:voltage", Interpolation::Linear)
+            .with_waypoint(0.0, 1.0)
+            .with_waypoint(0.5, 2.0)
+            .with_waypoint(0.5, 3.0);
+        assert!(backwards.validate().is_err());
+        let late =
+            SetpointSchedule::new("bias_0:voltage", Interpolation::Linear).with_waypoint(0.1, 1.0);
+        assert!(late.validate().is_err());
+        let empty = SetpointSchedule::new("bias_0:voltage", Interpolation::Spline);
+        assert!(SetpointRamp::new(&device, empty, 100.0).run().is_err());
+        assert!(device.writes.lock().unwrap().is_empty());
+
+        // A single waypoint is a hold
+        let hold =
+            SetpointSchedule::new("bias_0:voltage", Interpolation::Spline).with_waypoint(0.0, 1.5);
+        let report = SetpointRamp::new(&device, hold, 100.0).run().unwrap();
+        assert_eq!(report.steps, 1);
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(
+            *device.writes.lock().unwrap(),
+            vec![("bias_0:voltage".to_string(), 1.5)]
+        );
+    }
+}
@@ -1,32 +1,192 @@
 // Engine skeleton
 
-use crate::hal::{self, LabDevice};
+use crate::hal::latency::{LatencyStats, TimedDevice};
+use crate::hal::telemetry::{HalTelemetry, TelemetryDevice};
+use crate::hal::{self, Device, LabDevice};
 use crate::ir::Graph;
-use crate::observability;
+use crate::observability::{
+    self, ArtifactWriter, EventStreamer, Histogram, MetricsSink, SamplingConfig, SpanContext,
+    SpanHandle, StreamMessage, TimelineBuilder, TracerHandle, DEFAULT_WRITER_CAPACITY,
+};
 use crate::plugins::run_reference_simulator;
+use crate::quantum::tomography::{self, TomographyPlan};
+use crate::quantum::wigner::PhaseSpaceGrid;
+use crate::quantum::{self, MeasurementBasis, QuantumBackend};
+use crate::scheduler::{ResourceLimits, Scheduler, SchedulingConstraints, StaticScheduler};
+use crate::simulator::{
+    HomodyneSimulator, NoiseDiagnostics, NoiseProfile, NoiseProfileRegistry, SimulatorNoiseConfig,
+    TrajectorySimulator, NOISE_PROFILE_METADATA_KEY,
+};
 use crate::state::{
-    CoherenceManager, QuantumMode, QuantumState, ReferenceCoherenceManager, ReferenceStateEvolver,
-    StateEvolver,
+    CoherenceManager, MeasurementKind, QuantumMode, QuantumState, ReferenceCoherenceManager,
+    ReferenceStateEvolver, StateEvolver,
 };
 use anyhow::Result;
 use chrono::Utc;
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 use uuid::Uuid;
 
-pub struct Engine {}
+pub struct Engine {
+    hal_telemetry: HalTelemetry,
+    hal_latency: observability::MetricsCollector,
+    wigner_modes: Vec<String>,
+    wigner_grid: PhaseSpaceGrid,
+    homodyne_noise: Option<HomodyneSimulator>,
+    noise_profiles: NoiseProfileRegistry,
+    noise_profile: Option<NoiseProfile>,
+    trajectory: Option<TrajectorySimulator>,
+    event_stream: Option<Arc<EventStreamer>>,
+    timeline_sampling: SamplingConfig,
+}
 
 impl Engine {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            hal_telemetry: HalTelemetry::new(),
+            hal_latency: observability::MetricsCollector::new(),
+            wigner_modes: Vec::new(),
+            wigner_grid: PhaseSpaceGrid::default(),
+            homodyne_noise: None,
+            noise_profiles: NoiseProfileRegistry::new(),
+            noise_profile: None,
+            trajectory: None,
+            event_stream: None,
+            timeline_sampling: SamplingConfig::new(),
+        }
+    }
+
+    /// Write `wigner_<mode>.json` for each of `modes` (final state of the run) into run bundles,
+    /// sampled over `grid`.
+    pub fn with_wigner_modes(mut self, modes: Vec<String>, grid: PhaseSpaceGrid) -> Self {
+        self.wigner_modes = modes;
+        self.wigner_grid = grid;
+        self
+    }
+
+    /// Apply `noise` to homodyne/heterodyne DETECTOR readouts (ideal, shot-noise-limited
+    /// detection otherwise).
+    pub fn with_homodyne_noise(mut self, noise: HomodyneSimulator) -> Self {
+        self.homodyne_noise = Some(noise);
+        self
+    }
+
+    /// Apply `config`'s evolution noise (inter-mode crosstalk, see
+    /// `SimulatorNoiseConfig::apply_crosstalk`) after every gate, recorded as the `custom`
+    /// noise profile; modes are addressed by their index in the graph.
+    pub fn with_noise_config(mut self, config: SimulatorNoiseConfig) -> Self {
+        self.noise_profile = Some(NoiseProfile {
+            id: "custom".to_string(),
+            config,
+        });
+        self
+    }
+
+    /// Profiles that `with_noise_profile` and the graphs' `noise_profile` metadata resolve
+    /// against (built-in `ideal` and `default` otherwise).
+    pub fn with_noise_profiles(mut self, profiles: NoiseProfileRegistry) -> Self {
+        self.noise_profiles = profiles;
+        self
+    }
+
+    /// Run with the registered noise profile `id` unless a graph names its own.
+    pub fn with_noise_profile(mut self, id: &str) -> Result<Self> {
+        self.noise_profile = Some(self.noise_profiles.get(id)?);
+        Ok(self)
+    }
+
+    /// Run in trajectory mode: after every gate, draw one loss/dephasing trajectory step from
+    /// an RNG seeded with the run seed, so each run is one shot and runs vary shot to shot.
+    pub fn with_trajectories(mut self, trajectory: TrajectorySimulator) -> Self {
+        self.trajectory = Some(trajectory);
+        self
+    }
+
+    /// Push measurement outcomes to `stream` as they happen, and the run's spans, timeline
+    /// events and metrics once they are written.
+    pub fn with_event_stream(mut self, stream: Arc<EventStreamer>) -> Self {
+        self.event_stream = Some(stream);
+        self
+    }
+
+    /// Thin timeline lanes by `sampling` before they are written; dropped events are counted
+    /// as `timeline.dropped.<lane>` counters in metrics.json.
+    pub fn with_timeline_sampling(mut self, sampling: SamplingConfig) -> Self {
+        self.timeline_sampling = sampling;
+        self
+    }
+
+    /// Apply the profile's evolution noise (inter-mode crosstalk, see
+    /// `SimulatorNoiseConfig::apply_crosstalk`) and, in trajectory mode, a trajectory step
+    /// after a gate.
+    fn apply_evolution_noise(
+        &self,
+        profile: Option<&NoiseProfile>,
+        rng: &mut StdRng,
+        state: QuantumState,
+    ) -> Result<QuantumState> {
+        let state = match profile {
+            Some(profile) if !profile.config.crosstalk.is_empty() => {
+                profile.config.apply_crosstalk(&state)?
+            }
+            _ => state,
+        };
+        match &self.trajectory {
+            Some(trajectory) => trajectory.step(&state, rng),
+            None => Ok(state),
+        }
+    }
+
+    /// Noise profile for `graph`: its `noise_profile` metadata, else the engine's.
+    fn resolve_noise_profile(&self, graph: &Graph) -> Result<Option<NoiseProfile>> {
+        match graph.metadata.get(NOISE_PROFILE_METADATA_KEY) {
+            Some(id) => self.noise_profiles.get(id).map(Some),
+            None => Ok(self.noise_profile.clone()),
+        }
+    }
+
+    /// HAL telemetry buffer. Devices wrapped in `hal::telemetry::TelemetryDevice` with this buffer
+    /// have their operations included on `HAL.*` lanes of the next run's timeline.
+    pub fn hal_telemetry(&self) -> &HalTelemetry {
+        &self.hal_telemetry
+    }
+
+    /// Per-operation HAL latency histograms (`hal::latency`). Summaries are written to each run's
+    /// `metrics.json` as `hal.<operation>.{count,p50,p99,max}` gauges.
+    pub fn hal_latency(&self) -> &observability::MetricsCollector {
+        &self.hal_latency
     }
 
     /// Run the provided IR graph, optionally with a seed for deterministic replay.
     pub fn run_graph(&self, graph: &Graph, seed: Option<u64>) -> Result<PathBuf> {
-        // Validate IR: check conditional branches reference valid nodes
-        crate::ir::validate_graph(graph).map_err(|e| anyhow::anyhow!(e))?;
+        self.run_graph_in(graph, seed, &std::env::current_dir()?)
+    }
 
+    /// Run `graph`, writing the `awen_run_<id>` bundle under `artifacts_dir`.
+    pub fn run_graph_in(
+        &self,
+        graph: &Graph,
+        seed: Option<u64>,
+        artifacts_dir: &Path,
+    ) -> Result<PathBuf> {
         let run_seed = seed.unwrap_or(42);
+        let run_id = Uuid::new_v4().to_string();
+        let tracer = TracerHandle::new();
+        let (ctx, mut run_span) = RunContext::start(&run_id, run_seed, &tracer);
+
+        // Validate IR: check conditional branches reference valid nodes
+        let mut validate_span = ctx.span.start_span("ir_validate");
+        crate::ir::validate_graph(graph)?;
+        validate_span.end();
+
+        let noise_profile = self.resolve_noise_profile(graph)?;
+        let mut trajectory_rng = StdRng::seed_from_u64(run_seed);
 
         // Initialize coherence window and quantum state evolver for quantum-capable graphs
         let coherence_mgr = ReferenceCoherenceManager;
@@ -36,6 +196,10 @@ impl Engine {
         // Assume graph execution takes ~1 microsecond per node (realistic for photonic systems)
         let _execution_duration_ns = (graph.nodes.len() as u64) * 1_000; // 1µs per node
         let coherence_window = coherence_mgr.create_window(0, 10_000_000, "gaussian")?; // 10ms coherence
+        let mut coherence_span = ctx.span.start_span("coherence_window");
+        coherence_span.set_attribute("coherence_start_ns", &coherence_window.start_ns.to_string());
+        coherence_span.set_attribute("coherence_end_ns", &coherence_window.end_ns.to_string());
+        coherence_span.end();
 
         // Initialize quantum state: one mode per node (simplified; real systems track physical modes)
         let initial_modes: Vec<QuantumMode> = graph
@@ -46,8 +210,11 @@ impl Engine {
                 mode_id: format!("mode_{}", i),
                 mode_type: "quantum_fock".to_string(),
                 photon_numbers: Some(vec![0, 1, 2]),
-                amplitudes: Some(vec![1.0, 0.0, 0.0]), // |0⟩ state
-                phases: Some(vec![0.0, 0.0, 0.0]),
+                amplitudes: Some(vec![
+                    Complex64::new(1.0, 0.0), // |0⟩ state
+                    Complex64::new(0.0, 0.0),
+                    Complex64::new(0.0, 0.0),
+                ]),
             })
             .collect();
 
@@ -59,6 +226,9 @@ impl Engine {
             provenance: {
                 let mut p = HashMap::new();
                 p.insert("origin".to_string(), "engine.run_graph".to_string());
+                if let Some(profile) = &noise_profile {
+                    p.insert("noise_model_id".to_string(), profile.id.clone());
+                }
                 p
             },
         };
@@ -71,11 +241,45 @@ impl Engine {
         // Run reference simulator for classical simulation
         let sim = run_reference_simulator(graph, Some(run_seed))?;
 
-        // Simulate quantum gate operations on each node (demonstration)
-        // Build a set of nodes to execute, starting with root nodes
-        let mut nodes_to_execute: Vec<String> = graph.nodes.iter().map(|n| n.id.clone()).collect();
+        // Simulate quantum gate operations on each node (demonstration), in dependency order so
+        // feed-forward consumers and branch targets run after the detectors they read
+        let mut nodes_to_execute: Vec<String> = crate::ir::execution_order(graph)?
+            .iter()
+            .map(|n| n.id.clone())
+            .collect();
         let mut executed_nodes = std::collections::HashSet::new();
         let mut idx = 0usize;
+        // Wall-clock node execution time, and measurement-to-correction time of feed-forward
+        let mut node_latency = Histogram::latency_ns();
+        let mut feedback_latency = Histogram::latency_ns();
+        let mut measured_at: HashMap<String, Instant> = HashMap::new();
+        // Coherence budget and fidelity estimate at each node boundary
+        let mut coherence_samples: Vec<CoherenceSample> = Vec::new();
+        let mut noise_samples: Vec<(u64, String, NoiseDiagnostics)> = Vec::new();
+        // Branch decisions, and the correlation id of the measurement that queued each node
+        let mut branch_events: Vec<observability::TimelineEvent> = Vec::new();
+        let mut triggered_by: HashMap<String, String> = HashMap::new();
+        let mut scheduling_span = ctx.span.start_span("scheduling");
+        scheduling_span.set_attribute("nodes", &nodes_to_execute.len().to_string());
+        // Resource allocations of the static plan, on per-device resource lanes. Graphs the
+        // scheduler rejects (e.g. cyclic) still run; they just get no allocation lanes.
+        let plan_events = match StaticScheduler::new().schedule_traced(
+            graph,
+            &SchedulingConstraints::unconstrained(ResourceLimits {
+                max_wavelengths: 2,
+                max_memory_slots: 2,
+                max_concurrent_operations: graph.nodes.len().max(1),
+            }),
+            run_seed,
+            &scheduling_span.context(),
+        ) {
+            Ok(plan) => plan.timeline_events(
+                &hal::SimulatedDevice::new().id(),
+                Utc::now().timestamp_millis() as u128,
+            ),
+            Err(_) => Vec::new(),
+        };
+        scheduling_span.end();
 
         while idx < nodes_to_execute.len() {
             let node_id = &nodes_to_execute[idx];
@@ -92,13 +296,68 @@ impl Engine {
                 .find(|n| &n.id == node_id)
                 .ok_or_else(|| anyhow::anyhow!("node {} not found", node_id))?;
 
+            // Node span; HAL calls made while the node executes nest under it
+            let node_started = Instant::now();
+            let mut node_span = ctx.span.start_span(&format!("exec:{}", node.id));
+            node_span.set_attribute("node_id", &node.id);
+            node_span.set_attribute("node_type", &node.node_type);
+            if let Some(nr) = sim.node_results.iter().find(|nr| nr.node_id == node.id) {
+                node_span.set_attribute("phase_noise", &nr.phase_noise.to_string());
+            }
+            if let Some(correlation_id) = triggered_by.get(&node.id) {
+                node_span.set_attribute("correlation_id", correlation_id);
+            }
+            let node_ctx = ctx.within(&node_span);
+            self.hal_telemetry
+                .set_span_context(Some(node_ctx.span.clone()));
+
             // Validate coherence before processing this node
             let current_time_ns = (idx as u64) * 1_000; // increment time by 1µs per node
             coherence_mgr.validate_coherence(&quantum_state, current_time_ns)?;
+            coherence_samples.push(CoherenceSample::at(
+                &quantum_state.coherence_window,
+                current_time_ns,
+                &node.id,
+            )?);
+
+            // Noise the profile exposes this node to, attributed on its span
+            if let Some(profile) = &noise_profile {
+                let detector = node.node_type == "DETECTOR";
+                let mode_id = match node.measure_mode.as_deref() {
+                    Some(mode_id) if detector => mode_id,
+                    _ => "mode_0",
+                };
+                let diagnostics = profile.config.diagnostics(
+                    node.params.get("length_cm").copied().unwrap_or(1.0),
+                    mean_photon_number(&quantum_state, mode_id),
+                    detector,
+                );
+                for (name, value) in diagnostics.entries() {
+                    node_span.set_attribute(&format!("noise.{}", name), &value.to_string());
+                }
+                noise_samples.push((current_time_ns, node.id.clone(), diagnostics));
+            }
+
+            // Resolve feed-forward parameters from earlier measurements
+            let resolved = resolve_feed_forward(node, &measurement_outcomes)?;
+            if let Some(feed_forward) = &node.feed_forward {
+                for ff in feed_forward {
+                    if let Some(measured) = measured_at.get(&ff.source_node) {
+                        feedback_latency.record(measured.elapsed().as_nanos() as f64);
+                    }
+                }
+                let applied: Vec<String> = feed_forward
+                    .iter()
+                    .map(|ff| format!("{}={}", ff.param, resolved[&ff.param]))
+                    .collect();
+                quantum_state
+                    .provenance
+                    .insert(format!("feed_forward:{}", node.id), applied.join(","));
+            }
 
             // Apply gate evolution based on node type
-            if !node.params.is_empty() {
-                let params = &node.params;
+            if !resolved.is_empty() {
+                let params = &resolved;
                 match node.node_type.as_str() {
                     "MZI" => {
                         // MZI acts as a beam splitter; couple modes
@@ -112,6 +371,11 @@ impl Engine {
 
                         quantum_state =
                             state_evolver.evolve_state(&quantum_state, "BS", &gate_params)?;
+                        quantum_state = self.apply_evolution_noise(
+                            noise_profile.as_ref(),
+                            &mut trajectory_rng,
+                            quantum_state,
+                        )?;
                         state_history.push(quantum_state.clone());
                     }
                     "PS" => {
@@ -123,38 +387,135 @@ impl Engine {
                         }
                         quantum_state =
                             state_evolver.evolve_state(&quantum_state, "PS", &gate_params)?;
+                        quantum_state = self.apply_evolution_noise(
+                            noise_profile.as_ref(),
+                            &mut trajectory_rng,
+                            quantum_state,
+                        )?;
+                        state_history.push(quantum_state.clone());
+                    }
+                    "DISPLACEMENT" => {
+                        // Displacement by (q, p) on mode_id (default mode_0)
+                        let mut gate_params = params.clone();
+                        gate_params.entry("mode_id".to_string()).or_insert(0.0);
+                        quantum_state = state_evolver.evolve_state(
+                            &quantum_state,
+                            "DISPLACEMENT",
+                            &gate_params,
+                        )?;
+                        quantum_state = self.apply_evolution_noise(
+                            noise_profile.as_ref(),
+                            &mut trajectory_rng,
+                            quantum_state,
+                        )?;
+                        state_history.push(quantum_state.clone());
+                    }
+                    "PDC" => {
+                        // Down-conversion into signal/idler modes (default mode_0 / mode_1)
+                        let mut gate_params = params.clone();
+                        gate_params.entry("signal_id".to_string()).or_insert(0.0);
+                        gate_params.entry("idler_id".to_string()).or_insert(1.0);
+                        quantum_state =
+                            state_evolver.evolve_state(&quantum_state, "PDC", &gate_params)?;
+                        quantum_state = self.apply_evolution_noise(
+                            noise_profile.as_ref(),
+                            &mut trajectory_rng,
+                            quantum_state,
+                        )?;
+                        state_history.push(quantum_state.clone());
+                    }
+                    "HERALD" | "PHOTON_SUBTRACT" => {
+                        // CV → DV conversion on mode_id (default mode_0)
+                        let mut gate_params = params.clone();
+                        gate_params.entry("mode_id".to_string()).or_insert(0.0);
+                        quantum_state = state_evolver.evolve_state(
+                            &quantum_state,
+                            node.node_type.as_str(),
+                            &gate_params,
+                        )?;
+                        quantum_state = self.apply_evolution_noise(
+                            noise_profile.as_ref(),
+                            &mut trajectory_rng,
+                            quantum_state,
+                        )?;
                         state_history.push(quantum_state.clone());
                     }
                     "DETECTOR" => {
                         // Measurement: destructive measurement on mode specified in measure_mode or default to mode_0
                         let measure_mode = node.measure_mode.as_deref().unwrap_or("mode_0");
-                        let outcome = state_evolver.measure(
-                            &quantum_state,
-                            measure_mode,
-                            Some(run_seed + idx as u64),
-                        )?;
+                        let kind =
+                            MeasurementKind::from_node(node.measurement.as_deref(), &node.params)?;
+                        let mut outcome = match kind {
+                            MeasurementKind::PhotonCount => state_evolver.measure(
+                                &quantum_state,
+                                measure_mode,
+                                Some(run_seed + idx as u64),
+                            )?,
+                            _ => state_evolver.measure_quadrature(
+                                &quantum_state,
+                                measure_mode,
+                                kind,
+                                self.homodyne_noise.as_ref(),
+                                Some(run_seed + idx as u64),
+                            )?,
+                        };
+                        let correlation_id = format!("{}:{}", run_id, node.id);
+                        outcome.correlation_id = Some(correlation_id.clone());
+                        node_span.set_attribute("correlation_id", &correlation_id);
                         measurement_outcomes.insert(node_id.clone(), outcome.clone());
+                        measured_at.insert(node_id.clone(), Instant::now());
+                        if let Some(stream) = &self.event_stream {
+                            let summary = crate::state::MeasurementOutcome {
+                                collapsed_state: None,
+                                ..outcome.clone()
+                            };
+                            stream.publish(&StreamMessage::Measurement {
+                                run_id: run_id.clone(),
+                                node_id: node_id.clone(),
+                                outcome: serde_json::to_value(&summary)?,
+                            });
+                        }
                         quantum_state = outcome
                             .collapsed_state
                             .ok_or_else(|| anyhow::anyhow!("measurement failed"))?;
                         state_history.push(quantum_state.clone());
 
-                        // Handle measurement-conditioned branches
+                        // Handle measurement-conditioned branches; each decision and the
+                        // nodes it runs carry the outcome's correlation id
                         if let Some(branches) = &node.conditional_branches {
                             for branch in branches {
-                                if outcome.outcome_index == branch.outcome_index {
-                                    // Execute then_nodes
-                                    for then_id in &branch.then_nodes {
-                                        if !executed_nodes.contains(then_id) {
-                                            nodes_to_execute.push(then_id.clone());
-                                        }
-                                    }
-                                } else if let Some(else_nodes) = &branch.else_nodes {
-                                    // Execute else_nodes
-                                    for else_id in else_nodes {
-                                        if !executed_nodes.contains(else_id) {
-                                            nodes_to_execute.push(else_id.clone());
-                                        }
+                                let (taken, chosen) =
+                                    if outcome.outcome_index == branch.outcome_index {
+                                        ("then", branch.then_nodes.as_slice())
+                                    } else {
+                                        ("else", branch.else_nodes.as_deref().unwrap_or_default())
+                                    };
+                                let now_ms = Utc::now().timestamp_millis() as u128;
+                                branch_events.push(observability::TimelineEvent {
+                                    lane: observability::timeline::lanes::CONTROL.to_string(),
+                                    name: format!("branch:{}", node.id),
+                                    start_ms: now_ms,
+                                    end_ms: now_ms,
+                                    attributes: HashMap::from([
+                                        ("correlation_id".to_string(), correlation_id.clone()),
+                                        ("detector".to_string(), node.id.clone()),
+                                        (
+                                            "outcome_index".to_string(),
+                                            outcome.outcome_index.to_string(),
+                                        ),
+                                        (
+                                            "branch_outcome_index".to_string(),
+                                            branch.outcome_index.to_string(),
+                                        ),
+                                        ("taken".to_string(), taken.to_string()),
+                                        ("nodes".to_string(), chosen.join(",")),
+                                    ]),
+                                });
+                                for next_id in chosen {
+                                    if !executed_nodes.contains(next_id) {
+                                        triggered_by
+                                            .insert(next_id.clone(), correlation_id.clone());
+                                        nodes_to_execute.push(next_id.clone());
                                     }
                                 }
                             }
@@ -165,102 +526,70 @@ impl Engine {
                     }
                 }
             }
+            self.hal_telemetry.set_span_context(None);
+            node_span.end();
+            node_latency.record(node_started.elapsed().as_nanos() as f64);
         }
 
         // Create artifact bundle directory
-        let run_id = Uuid::new_v4().to_string();
-        let out_dir = std::env::current_dir()?.join(format!("awen_run_{}", run_id));
+        let out_dir = artifacts_dir.join(format!("awen_run_{}", run_id));
         std::fs::create_dir_all(&out_dir)?;
+        // Bundle files are written in the background; traces, timeline and metrics are
+        // flushed once by `writer.finish()`
+        let writer = ArtifactWriter::spawn(&out_dir, DEFAULT_WRITER_CAPACITY);
 
         // Save IR
-        let ir_path = out_dir.join("ir.json");
-        let ir_data = serde_json::to_string_pretty(graph)?;
-        std::fs::write(&ir_path, ir_data)?;
+        writer.json("ir.json", graph)?;
 
         // Save simulation results
-        let results_path = out_dir.join("results.json");
-        let results_data = serde_json::to_string_pretty(&sim)?;
-        std::fs::write(&results_path, results_data)?;
+        writer.json("results.json", &sim)?;
 
         // Save quantum state history (new artifact)
-        let state_history_path = out_dir.join("quantum_states.json");
-        let state_history_data = serde_json::to_string_pretty(&state_history)?;
-        std::fs::write(&state_history_path, state_history_data)?;
+        writer.json("quantum_states.json", &state_history)?;
 
         // Save measurement outcomes (new artifact)
-        let measurements_path = out_dir.join("measurements.json");
-        let measurements_data = serde_json::to_string_pretty(&measurement_outcomes)?;
-        std::fs::write(&measurements_path, measurements_data)?;
+        writer.json("measurements.json", &measurement_outcomes)?;
+
+        // Save the active noise profile so replays resolve the exact model
+        if let Some(profile) = &noise_profile {
+            writer.json("noise_model.json", profile)?;
+        }
+
+        // Save phase-space views of selected modes (optional artifact); modes are independent,
+        // so their grids are sampled in parallel
+        let wigners = if self.wigner_modes.is_empty() {
+            Vec::new()
+        } else {
+            let backend_state = quantum_state
+                .select_modes(&self.wigner_modes)?
+                .to_backend_state()?;
+            self.wigner_modes
+                .par_iter()
+                .map(|mode_id| backend_state.wigner(mode_id, &self.wigner_grid))
+                .collect::<Result<Vec<_>>>()?
+        };
+        for (mode_id, wigner) in self.wigner_modes.iter().zip(&wigners) {
+            writer.json(&format!("wigner_{}.json", mode_id), &wigner)?;
+        }
 
         // Save a simple trace (reuse results for now)
-        let trace_path = out_dir.join("trace.json");
-        std::fs::write(&trace_path, serde_json::to_string_pretty(&sim)?)?;
+        writer.json("trace.json", &sim)?;
 
-        // Build and write basic observability artifacts (traces.jsonl, timeline.json, metrics.json)
+        // Build basic observability artifacts (traces.jsonl, timeline.json, metrics.json)
         // Create simple node id list
         let node_ids: Vec<String> = graph.nodes.iter().map(|n| n.id.clone()).collect();
-        let (spans, events, metrics) =
+        let (mut spans, events, mut metrics) =
             observability::build_basic_observability(&run_id, &node_ids, Some(run_seed));
-        observability::write_traces(&out_dir, &spans)?;
-        observability::write_timeline(&out_dir, &events)?;
-        observability::write_metrics(&out_dir, &metrics)?;
+        for span in &mut spans {
+            span.parent = Some(run_span.id());
+        }
 
-        // More structured spans/events: IR validate, scheduling, per-node execution and measurement
-        let mut extra_spans: Vec<observability::Span> = Vec::new();
+        // Per-node timeline events
         let mut extra_events: Vec<observability::TimelineEvent> = Vec::new();
-
-        // IR validate span
-        extra_spans.push(observability::Span {
-            id: format!("{}-ir-validate", run_id),
-            parent: None,
-            name: "ir_validate".to_string(),
-            start_iso: Utc::now().to_rfc3339(),
-            end_iso: Utc::now().to_rfc3339(),
-            attributes: HashMap::new(),
-        });
-        // scheduling span
-        extra_spans.push(observability::Span {
-            id: format!("{}-schedule", run_id),
-            parent: None,
-            name: "scheduling".to_string(),
-            start_iso: Utc::now().to_rfc3339(),
-            end_iso: Utc::now().to_rfc3339(),
-            attributes: HashMap::new(),
-        });
-
-        // coherence window span
-        let mut coh_attrs = HashMap::new();
-        coh_attrs.insert(
-            "coherence_start_ns".to_string(),
-            coherence_window.start_ns.to_string(),
-        );
-        coh_attrs.insert(
-            "coherence_end_ns".to_string(),
-            coherence_window.end_ns.to_string(),
-        );
-        extra_spans.push(observability::Span {
-            id: format!("{}-coherence", run_id),
-            parent: None,
-            name: "coherence_window".to_string(),
-            start_iso: Utc::now().to_rfc3339(),
-            end_iso: Utc::now().to_rfc3339(),
-            attributes: coh_attrs,
-        });
-
         for nr in &sim.node_results {
             let mut attrs = HashMap::new();
             attrs.insert("node_id".to_string(), nr.node_id.clone());
             attrs.insert("phase_noise".to_string(), format!("{}", nr.phase_noise));
-            let span = observability::Span {
-                id: format!("{}-node-{}", run_id, nr.node_id),
-                parent: None,
-                name: format!("exec:{}", nr.node_id),
-                start_iso: Utc::now().to_rfc3339(),
-                end_iso: Utc::now().to_rfc3339(),
-                attributes: attrs.clone(),
-            };
-            extra_spans.push(span);
-
             let ev = observability::TimelineEvent {
                 lane: "kernel".to_string(),
                 name: format!("exec:{}", nr.node_id),
@@ -272,15 +601,77 @@ impl Engine {
         }
 
         // merge previous and extra
+        run_span.end();
         let mut all_spans = spans.clone();
-        all_spans.extend(extra_spans);
+        all_spans.extend(tracer.spans());
         let mut all_events = events.clone();
         all_events.extend(extra_events);
+        all_events.extend(plan_events);
+        all_events.extend(branch_events);
+        all_events.extend(
+            coherence_samples
+                .iter()
+                .map(CoherenceSample::timeline_event),
+        );
+        all_events.extend(self.hal_telemetry.drain());
+        let (all_events, dropped) = self.timeline_sampling.apply(all_events);
+        for (lane, count) in dropped {
+            metrics
+                .counters
+                .insert(format!("timeline.dropped.{}", lane), count as f64);
+        }
+        for stats in LatencyStats::all(&self.hal_latency) {
+            let prefix = format!("hal.{}", stats.operation);
+            metrics
+                .gauges
+                .insert(format!("{}.count", prefix), stats.count as f64);
+            metrics
+                .gauges
+                .insert(format!("{}.p50", prefix), stats.p50_ns);
+            metrics
+                .gauges
+                .insert(format!("{}.p99", prefix), stats.p99_ns);
+            metrics
+                .gauges
+                .insert(format!("{}.max", prefix), stats.max_ns);
+            let histogram = self
+                .hal_latency
+                .to_histogram(&stats.operation, Histogram::latency_ns().bounds())?;
+            metrics.record_histogram(&prefix, &histogram);
+        }
+        for sample in &coherence_samples {
+            sample.record(&mut metrics);
+        }
+        for (t_ns, node_id, diagnostics) in &noise_samples {
+            for (name, value) in diagnostics.entries() {
+                metrics.record_sample(&format!("noise.{}", name), *t_ns, value, Some(node_id));
+            }
+        }
+        metrics.record_histogram("node_latency_ns", &node_latency);
+        metrics.record_histogram("feedback_latency_ns", &feedback_latency);
+
+        // Observability artifacts including detailed spans/events
+        writer.spans(all_spans.clone());
+        writer.events(all_events.clone());
+        writer.metrics(metrics.clone());
+        writer.finish()?;
+        observability::export_chrome_trace(&out_dir)?;
+        observability::render_report(&out_dir)?;
 
-        // rewrite observability artifacts including detailed spans/events
-        observability::write_traces(&out_dir, &all_spans)?;
-        observability::write_timeline(&out_dir, &all_events)?;
-        observability::write_metrics(&out_dir, &metrics)?;
+        if let Some(stream) = &self.event_stream {
+            for span in all_spans {
+                stream.publish(&StreamMessage::Span(span));
+            }
+            for event in all_events {
+                stream.add_event(event);
+            }
+            for (key, value) in &metrics.counters {
+                stream.record_counter(key, *value);
+            }
+            for (key, value) in &metrics.gauges {
+                stream.record_gauge(key, *value);
+            }
+        }
 
         // TODO: Phase 2.6.2 - Build and persist ArtifactBundle with full provenance
         // save_artifact(&bundle, &artifacts_dir)?;
@@ -288,21 +679,236 @@ impl Engine {
         Ok(out_dir)
     }
 
+    /// State tomography of `target` on `backend`: measures every setting of `plan` on fresh
+    /// copies of the target, reconstructs the state by maximum likelihood and writes
+    /// `tomography.json` (shots, reconstruction, fidelity vs. target) into a new run bundle.
+    pub fn run_tomography(
+        &self,
+        backend: &mut dyn QuantumBackend,
+        target: &quantum::QuantumState,
+        plan: &TomographyPlan,
+        seed: Option<u64>,
+    ) -> Result<PathBuf> {
+        self.run_tomography_in(backend, target, plan, seed, &std::env::current_dir()?)
+    }
+
+    /// `run_tomography`, writing the `awen_run_<id>` bundle under `artifacts_dir`.
+    pub fn run_tomography_in(
+        &self,
+        backend: &mut dyn QuantumBackend,
+        target: &quantum::QuantumState,
+        plan: &TomographyPlan,
+        seed: Option<u64>,
+        artifacts_dir: &Path,
+    ) -> Result<PathBuf> {
+        let artifact = tomography::run(backend, target, plan, seed.unwrap_or(42))?;
+        let out_dir = artifacts_dir.join(format!("awen_run_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&out_dir)?;
+        std::fs::write(
+            out_dir.join("tomography.json"),
+            serde_json::to_string_pretty(&artifact)?,
+        )?;
+        Ok(out_dir)
+    }
+
+    /// `shots` repetitions of measuring `basis` on `state` through `QuantumBackend::sample`,
+    /// written as `shots.json` into a new run bundle.
+    pub fn run_shots(
+        &self,
+        backend: &dyn QuantumBackend,
+        state: &quantum::QuantumState,
+        basis: &MeasurementBasis,
+        shots: usize,
+        seed: Option<u64>,
+    ) -> Result<PathBuf> {
+        self.run_shots_in(
+            backend,
+            state,
+            basis,
+            shots,
+            seed,
+            &std::env::current_dir()?,
+        )
+    }
+
+    /// `run_shots`, writing the `awen_run_<id>` bundle under `artifacts_dir`.
+    pub fn run_shots_in(
+        &self,
+        backend: &dyn QuantumBackend,
+        state: &quantum::QuantumState,
+        basis: &MeasurementBasis,
+        shots: usize,
+        seed: Option<u64>,
+        artifacts_dir: &Path,
+    ) -> Result<PathBuf> {
+        let record = backend.sample(state, basis, shots, seed.unwrap_or(42))?;
+        let out_dir = artifacts_dir.join(format!("awen_run_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&out_dir)?;
+        std::fs::write(
+            out_dir.join("shots.json"),
+            serde_json::to_string_pretty(&record)?,
+        )?;
+        Ok(out_dir)
+    }
+
     /// Apply a calibration mapping through the HAL, enforcing safety limits if provided.
+    /// Fails without touching the device while the global safety interlock is tripped.
     pub fn apply_calibration(
         &self,
         mapping: &HashMap<String, f64>,
         safety: Option<&hal::SafetyLimits>,
     ) -> Result<hal::CalibrationResult> {
+        hal::interlock::Interlock::global().check()?;
         // In a realistic runtime this would select a real device from a registry. For now use the simulated device.
-        let dev = hal::SimulatedDevice::new();
-        let res = dev
-            .apply_calibration(mapping, safety)
-            .map_err(|e: String| anyhow::anyhow!(e))?;
+        let dev = TimedDevice::new(
+            TelemetryDevice::new(hal::SimulatedDevice::new(), self.hal_telemetry.clone()),
+            self.hal_latency.clone(),
+        );
+        let res = dev.apply_calibration(mapping, safety)?;
         Ok(res)
     }
 }
 
+/// Per-run state threaded through execution: the run's identity and seed, and the span context
+/// that subsystems (scheduler, HAL, calibration) start their spans in.
+#[derive(Clone)]
+pub struct RunContext {
+    pub run_id: String,
+    pub seed: u64,
+    pub span: SpanContext,
+}
+
+impl RunContext {
+    /// Context of a new run, whose root `run` span is opened on `tracer`; the caller ends it.
+    pub fn start(run_id: &str, seed: u64, tracer: &TracerHandle) -> (Self, SpanHandle) {
+        let mut run_span = tracer.start_span("run");
+        run_span.set_attribute("run_id", run_id);
+        run_span.set_attribute("seed", &seed.to_string());
+        let ctx = Self {
+            run_id: run_id.to_string(),
+            seed,
+            span: run_span.context(),
+        };
+        (ctx, run_span)
+    }
+
+    /// The same run, with new spans nested under `span`.
+    pub fn within(&self, span: &SpanHandle) -> Self {
+        Self {
+            span: span.context(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Coherence budget left in the run's window, and the window's fidelity estimate, when node
+/// `node_id` starts at run time `t_ns`.
+struct CoherenceSample {
+    window_id: String,
+    node_id: String,
+    t_ns: u64,
+    remaining_ns: u64,
+    fidelity: f64,
+    wall_ms: u128,
+}
+
+impl CoherenceSample {
+    fn at(window: &crate::state::CoherenceWindow, t_ns: u64, node_id: &str) -> Result<Self> {
+        let elapsed_ns = t_ns.saturating_sub(window.start_ns);
+        Ok(Self {
+            window_id: window.id.clone(),
+            node_id: node_id.to_string(),
+            t_ns,
+            remaining_ns: window.end_ns.saturating_sub(t_ns.max(window.start_ns)),
+            fidelity: window.fidelity_after(elapsed_ns as f64)?,
+            wall_ms: Utc::now().timestamp_millis() as u128,
+        })
+    }
+
+    fn record(&self, metrics: &mut observability::Metrics) {
+        let node = Some(self.node_id.as_str());
+        metrics.record_sample(
+            "coherence_budget_remaining_ns",
+            self.t_ns,
+            self.remaining_ns as f64,
+            node,
+        );
+        metrics.record_sample(
+            &format!("coherence_fidelity.{}", self.window_id),
+            self.t_ns,
+            self.fidelity,
+            node,
+        );
+    }
+
+    fn timeline_event(&self) -> observability::TimelineEvent {
+        let attributes = HashMap::from([
+            ("node_id".to_string(), self.node_id.clone()),
+            ("window_id".to_string(), self.window_id.clone()),
+            ("t_ns".to_string(), self.t_ns.to_string()),
+            (
+                "coherence_budget_remaining_ns".to_string(),
+                self.remaining_ns.to_string(),
+            ),
+            ("fidelity".to_string(), self.fidelity.to_string()),
+        ]);
+        observability::TimelineEvent {
+            lane: observability::timeline::lanes::COHERENCE.to_string(),
+            name: format!("coherence:{}", self.node_id),
+            start_ms: self.wall_ms,
+            end_ms: self.wall_ms,
+            attributes,
+        }
+    }
+}
+
+/// Mean photon number `⟨n⟩` of `mode_id` in `state`; 0 for unknown or classical modes.
+fn mean_photon_number(state: &QuantumState, mode_id: &str) -> f64 {
+    state
+        .modes
+        .iter()
+        .find(|m| m.mode_id == mode_id)
+        .and_then(|mode| crate::state::fock_coefficients(mode).ok())
+        .map_or(0.0, |coefficients| {
+            coefficients
+                .iter()
+                .map(|(n, c)| *n as f64 * c.norm_sqr())
+                .sum()
+        })
+}
+
+/// A node's parameters with its feed-forward entries filled in from already-recorded
+/// measurements (`offset + gain · value`).
+fn resolve_feed_forward(
+    node: &crate::ir::Node,
+    outcomes: &HashMap<String, crate::state::MeasurementOutcome>,
+) -> Result<HashMap<String, f64>> {
+    let mut params = node.params.clone();
+    for ff in node.feed_forward.iter().flatten() {
+        let outcome = outcomes.get(&ff.source_node).ok_or_else(|| {
+            anyhow::anyhow!(
+                "feed-forward on {} depends on {}, which has not been measured",
+                node.id,
+                ff.source_node
+            )
+        })?;
+        let value = match &outcome.quadratures {
+            Some(values) => *values.get(ff.quadrature).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "feed-forward on {} reads quadrature {} of {}, which recorded {}",
+                    node.id,
+                    ff.quadrature,
+                    ff.source_node,
+                    values.len()
+                )
+            })?,
+            None => outcome.photon_count as f64,
+        };
+        params.insert(ff.param.clone(), ff.offset + ff.gain * value);
+    }
+    Ok(params)
+}
+
 // Ensure gradient providers and other pluggable subsystems are registered during runtime initialization.
 impl Default for Engine {
     fn default() -> Self {
@@ -316,14 +922,17 @@ impl Default for Engine {
 mod tests {
     use super::*;
     use crate::ir;
+    use crate::quantum::wigner::WignerGrid;
+    use crate::quantum::StatevectorSimulator;
 
     #[test]
     fn integration_run_example_ir() {
+        let dir = tempfile::tempdir().unwrap();
         // Load example IR shipped with the crate
         let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
         let engine = Engine::new();
         let out = engine
-            .run_graph(&graph, Some(42))
+            .run_graph_in(&graph, Some(42), dir.path())
             .expect("engine run failed");
         assert!(out.exists(), "output directory does not exist");
         assert!(out.join("results.json").exists(), "results.json missing");
@@ -336,10 +945,11 @@ mod tests {
 
     #[test]
     fn test_timeline_contains_kernel_events() {
+        let dir = tempfile::tempdir().unwrap();
         let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
         let engine = Engine::new();
         let out = engine
-            .run_graph(&graph, Some(42))
+            .run_graph_in(&graph, Some(42), dir.path())
             .expect("engine run failed");
         let timeline_path = out.join("timeline.json");
         let data = std::fs::read_to_string(&timeline_path).expect("read timeline");
@@ -351,6 +961,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_chrome_trace_exported() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+        let data = std::fs::read_to_string(out.join("chrome_trace.json")).expect("read trace");
+        let trace: serde_json::Value = serde_json::from_str(&data).expect("parse trace");
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert!(events
+            .iter()
+            .any(|e| e["ph"] == "M" && e["args"]["name"] == "kernel"));
+        assert!(events
+            .iter()
+            .any(|e| e["ph"] == "X" && e["cat"] == "span" && e["name"] == "scheduling"));
+    }
+
+    #[test]
+    fn test_spans_nest_under_run_span() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+        let spans: Vec<observability::Span> = std::fs::read_to_string(out.join("traces.jsonl"))
+            .expect("read traces")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("parse span"))
+            .collect();
+
+        let roots: Vec<&observability::Span> =
+            spans.iter().filter(|s| s.parent.is_none()).collect();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].name, "run");
+        for node in &graph.nodes {
+            let exec = spans
+                .iter()
+                .find(|s| s.name == format!("exec:{}", node.id))
+                .expect("node span");
+            assert_eq!(exec.parent.as_deref(), Some(roots[0].id.as_str()));
+            assert_eq!(exec.attributes["node_type"], node.node_type);
+        }
+        let ids: std::collections::HashSet<&str> = spans.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids.len(), spans.len());
+        assert!(spans
+            .iter()
+            .filter_map(|s| s.parent.as_deref())
+            .all(|p| ids.contains(p)));
+    }
+
+    #[test]
+    fn test_html_report_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+        let html = std::fs::read_to_string(out.join("report.html")).expect("read report");
+        assert!(html.contains("<svg class=\"gantt\""));
+        assert!(html.contains("nodes_executed"));
+    }
+
     #[test]
     fn test_apply_calibration_enforces_safety() {
         let engine = Engine::new();
@@ -376,12 +1049,180 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hal_telemetry_included_in_timeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let engine = Engine::new();
+        let mut mapping = HashMap::new();
+        mapping.insert("heater_1:power".to_string(), 2.0_f64);
+        engine
+            .apply_calibration(&mapping, None)
+            .expect("apply calibration");
+
+        let out = engine
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+        let data = std::fs::read_to_string(out.join("timeline.json")).expect("read timeline");
+        let events: Vec<observability::TimelineEvent> =
+            serde_json::from_str(&data).expect("parse timeline");
+        assert!(events
+            .iter()
+            .any(|e| e.lane == "HAL.Channel.1" && e.name == "apply_calibration"));
+        assert!(engine.hal_telemetry().events().is_empty());
+    }
+
+    #[test]
+    fn test_hal_latency_summarised_in_metrics() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let engine = Engine::new();
+        engine
+            .apply_calibration(&HashMap::new(), None)
+            .expect("apply calibration");
+        assert_eq!(
+            engine
+                .hal_latency()
+                .histogram_values(crate::hal::latency::APPLY_CALIBRATION_NS)
+                .len(),
+            1
+        );
+
+        let out = engine
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+        let data = std::fs::read_to_string(out.join("metrics.json")).expect("read metrics");
+        let metrics: observability::Metrics = serde_json::from_str(&data).expect("parse metrics");
+        assert_eq!(
+            metrics.gauges.get("hal.apply_calibration_ns.count"),
+            Some(&1.0)
+        );
+        assert!(metrics.gauges.contains_key("hal.apply_calibration_ns.p99"));
+        assert_eq!(metrics.histograms["hal.apply_calibration_ns"].count, 1);
+        let nodes = &metrics.histograms["node_latency_ns"];
+        assert_eq!(nodes.count, graph.nodes.len() as u64);
+        assert!(nodes.p50 <= nodes.p95 && nodes.p95 <= nodes.p99 && nodes.p99 <= nodes.max);
+        // No feed-forward in this graph, so no feedback-loop distribution.
+        assert!(!metrics.histograms.contains_key("feedback_latency_ns"));
+    }
+
+    #[test]
+    fn test_timeline_sampling_caps_hal_lanes() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let engine = Engine::new().with_timeline_sampling(SamplingConfig::new().with_lane(
+            "HAL.Channel.*",
+            observability::SamplingPolicy::Head { limit: 3 },
+        ));
+        let mapping = HashMap::from([("heater_1:power".to_string(), 2.0_f64)]);
+        for _ in 0..10 {
+            engine
+                .apply_calibration(&mapping, None)
+                .expect("apply calibration");
+        }
+        let out = engine
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+
+        let timeline: Vec<observability::TimelineEvent> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("timeline.json")).expect("read timeline"),
+        )
+        .expect("parse timeline");
+        let hal: Vec<_> = timeline
+            .iter()
+            .filter(|e| e.lane.starts_with("HAL.Channel."))
+            .collect();
+        assert_eq!(hal.len(), 3);
+        assert!(timeline.iter().any(|e| e.lane == "kernel"));
+        let metrics: observability::Metrics = serde_json::from_str(
+            &std::fs::read_to_string(out.join("metrics.json")).expect("read metrics"),
+        )
+        .expect("parse metrics");
+        assert_eq!(
+            metrics.counters[&format!("timeline.dropped.{}", hal[0].lane)],
+            7.0
+        );
+    }
+
+    #[test]
+    fn test_plan_allocations_populate_device_lanes() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+
+        let timeline: Vec<observability::TimelineEvent> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("timeline.json")).expect("read timeline"),
+        )
+        .expect("parse timeline");
+        let allocations: Vec<_> = timeline
+            .iter()
+            .filter(|e| e.lane.starts_with("HAL.Device.simulated."))
+            .collect();
+        assert!(allocations
+            .iter()
+            .any(|e| e.lane == "HAL.Device.simulated.wavelength.1550nm"));
+        for node in &graph.nodes {
+            assert!(allocations
+                .iter()
+                .any(|e| e.attributes["node_id"] == node.id));
+        }
+
+        let traces: Vec<observability::Span> = std::fs::read_to_string(out.join("traces.jsonl"))
+            .expect("read traces")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("parse span"))
+            .collect();
+        let scheduling = traces.iter().find(|s| s.name == "scheduling").unwrap();
+        let schedule = traces.iter().find(|s| s.name == "schedule").unwrap();
+        assert_eq!(schedule.parent.as_deref(), Some(scheduling.id.as_str()));
+    }
+
+    #[test]
+    fn test_coherence_budget_sampled_per_node() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+
+        let metrics: observability::Metrics = serde_json::from_str(
+            &std::fs::read_to_string(out.join("metrics.json")).expect("read metrics"),
+        )
+        .expect("parse metrics");
+        let budget = &metrics.series["coherence_budget_remaining_ns"];
+        assert_eq!(budget.len(), graph.nodes.len());
+        assert!(budget.windows(2).all(|w| w[0].t_ns < w[1].t_ns));
+        assert!(budget.windows(2).all(|w| w[0].value > w[1].value));
+        assert!(budget.iter().all(|s| s.node_id.is_some()));
+        assert_eq!(
+            metrics.gauges["coherence_budget_remaining_ns"],
+            budget.last().unwrap().value
+        );
+        let (key, fidelity) = metrics
+            .series
+            .iter()
+            .find(|(k, _)| k.starts_with("coherence_fidelity."))
+            .expect("fidelity series");
+        assert!(fidelity.windows(2).all(|w| w[0].value >= w[1].value));
+        assert!(metrics.gauges.contains_key(key));
+
+        let timeline: Vec<observability::TimelineEvent> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("timeline.json")).expect("read timeline"),
+        )
+        .expect("parse timeline");
+        let coherence = timeline.iter().filter(|e| e.lane == "Coherence").count();
+        assert_eq!(coherence, graph.nodes.len());
+    }
+
     #[test]
     fn test_quantum_state_artifact_created() {
+        let dir = tempfile::tempdir().unwrap();
         let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
         let engine = Engine::new();
         let out = engine
-            .run_graph(&graph, Some(42))
+            .run_graph_in(&graph, Some(42), dir.path())
             .expect("engine run failed");
         // Verify quantum state artifact is created
         assert!(
@@ -403,12 +1244,564 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wigner_artifacts_for_selected_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+        assert!(!out.join("wigner_mode_0.json").exists());
+
+        let engine = Engine::new()
+            .with_wigner_modes(vec!["mode_0".to_string()], PhaseSpaceGrid::square(5.0, 41));
+        let out = engine
+            .run_graph_in(&graph, Some(42), dir.path())
+            .expect("engine run failed");
+        let data = std::fs::read_to_string(out.join("wigner_mode_0.json")).expect("read wigner");
+        let wigner: WignerGrid = serde_json::from_str(&data).expect("parse wigner");
+        assert_eq!(wigner.mode, "mode_0");
+        assert_eq!(wigner.values.len(), 41);
+        assert!((wigner.integral() - 1.0).abs() < 1e-3);
+
+        let engine =
+            Engine::new().with_wigner_modes(vec!["missing".to_string()], PhaseSpaceGrid::default());
+        assert!(engine.run_graph_in(&graph, Some(42), dir.path()).is_err());
+    }
+
+    fn feed_forward_graph(detector_first: bool) -> ir::Graph {
+        let detector = ir::Node {
+            id: "m0".to_string(),
+            node_type: "DETECTOR".to_string(),
+            params: [("angle".to_string(), 0.0)].into_iter().collect(),
+            measure_mode: Some("mode_0".to_string()),
+            measurement: Some("homodyne".to_string()),
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let displacement = ir::Node {
+            id: "d1".to_string(),
+            node_type: "DISPLACEMENT".to_string(),
+            params: [("mode_id".to_string(), 1.0)].into_iter().collect(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: Some(vec![ir::FeedForward {
+                param: "q".to_string(),
+                source_node: "m0".to_string(),
+                quadrature: 0,
+                gain: -0.5,
+                offset: 0.0,
+            }]),
+        };
+        let nodes = if detector_first {
+            vec![detector, displacement]
+        } else {
+            vec![displacement, detector]
+        };
+        ir::Graph {
+            nodes,
+            edges: vec![],
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_feed_forward_displacement_from_homodyne() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = feed_forward_graph(true);
+        ir::validate_graph(&graph).expect("valid feed-forward");
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(5), dir.path())
+            .expect("engine run failed");
+
+        let measures: HashMap<String, crate::state::MeasurementOutcome> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("measurements.json")).expect("read measurements"),
+        )
+        .expect("parse measurements");
+        let x = measures["m0"].quadratures.as_ref().expect("homodyne value")[0];
+
+        let states: Vec<QuantumState> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("quantum_states.json")).expect("read states"),
+        )
+        .expect("parse states");
+        let last = states.last().unwrap();
+        assert_eq!(
+            last.provenance["feed_forward:d1"],
+            format!("q={}", -0.5 * x)
+        );
+        // The displaced mode leaves vacuum unless the homodyne value was ~0.
+        let amps = last.modes[1].amplitudes.as_ref().unwrap();
+        assert!(x.abs() < 1e-2 || amps[0].norm() < 1.0 - 1e-6);
+
+        // One measure-to-correction loop closed.
+        let metrics: observability::Metrics = serde_json::from_str(
+            &std::fs::read_to_string(out.join("metrics.json")).expect("read metrics"),
+        )
+        .expect("parse metrics");
+        assert_eq!(metrics.histograms["feedback_latency_ns"].count, 1);
+
+        // Listing the correction before its detector changes nothing: the detector runs first.
+        let reversed = Engine::new()
+            .run_graph_in(&feed_forward_graph(false), Some(5), dir.path())
+            .expect("engine run failed");
+        let reversed_states: Vec<QuantumState> = serde_json::from_str(
+            &std::fs::read_to_string(reversed.join("quantum_states.json")).expect("read states"),
+        )
+        .expect("parse states");
+        assert_eq!(
+            reversed_states.last().unwrap().provenance["feed_forward:d1"],
+            last.provenance["feed_forward:d1"]
+        );
+    }
+
+    #[test]
+    fn test_pdc_heralds_signal_photon_number() {
+        let dir = tempfile::tempdir().unwrap();
+        let node =
+            |id: &str, node_type: &str, params: Vec<(&str, f64)>, mode: Option<&str>| ir::Node {
+                id: id.to_string(),
+                node_type: node_type.to_string(),
+                params: params
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect(),
+                measure_mode: mode.map(str::to_string),
+                measurement: None,
+                conditional_branches: None,
+                feed_forward: None,
+            };
+        let graph = ir::Graph {
+            nodes: vec![
+                node("pdc", "PDC", vec![("gain", 0.8)], None),
+                node(
+                    "herald",
+                    "DETECTOR",
+                    vec![("efficiency", 1.0)],
+                    Some("mode_1"),
+                ),
+                node(
+                    "signal",
+                    "DETECTOR",
+                    vec![("efficiency", 1.0)],
+                    Some("mode_0"),
+                ),
+            ],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+
+        let mut heralded = 0;
+        for seed in 0..8 {
+            let out = Engine::new()
+                .run_graph_in(&graph, Some(seed), dir.path())
+                .expect("engine run failed");
+            let measures: HashMap<String, crate::state::MeasurementOutcome> = serde_json::from_str(
+                &std::fs::read_to_string(out.join("measurements.json")).expect("read measurements"),
+            )
+            .expect("parse measurements");
+            let herald = measures["herald"].photon_count;
+            assert_eq!(measures["signal"].photon_count, herald);
+            assert_eq!(measures["signal"].probability, 1.0);
+            heralded += (herald > 0) as u32;
+        }
+        assert!(heralded > 0);
+    }
+
+    #[test]
+    fn test_hybrid_conversion_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let node = |id: &str, node_type: &str, params: Vec<(&str, f64)>| ir::Node {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            params: params
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let graph = ir::Graph {
+            nodes: vec![
+                node("pdc", "PDC", vec![("gain", 0.8)]),
+                node("herald", "HERALD", vec![("mode_id", 1.0), ("count", 1.0)]),
+                node("d2", "DISPLACEMENT", vec![("mode_id", 2.0), ("q", 1.0)]),
+                node("sub", "PHOTON_SUBTRACT", vec![("mode_id", 2.0)]),
+            ],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(1), dir.path())
+            .expect("engine run failed");
+        let states: Vec<QuantumState> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("quantum_states.json")).expect("read states"),
+        )
+        .expect("parse states");
+        let last = states.last().unwrap();
+
+        // The heralded signal is a DV single photon; the displaced, subtracted mode stays CV.
+        assert!(last.is_hybrid());
+        assert_eq!(last.cv_modes(), vec!["mode_2"]);
+        let signal = &last.modes[0];
+        assert_eq!(signal.mode_type, crate::state::DV_MODE);
+        assert!((signal.amplitudes.as_ref().unwrap()[1].norm_sqr() - 1.0).abs() < 1e-12);
+        assert!(last.provenance.contains_key("herald_probability:mode_1"));
+        assert!(last.provenance.contains_key("herald_probability:mode_2"));
+    }
+
+    #[test]
+    fn test_event_stream_receives_measurements() {
+        let dir = tempfile::tempdir().unwrap();
+        use std::io::{BufRead, BufReader, Write};
+
+        let stream = Arc::new(EventStreamer::bind("127.0.0.1:0").expect("bind stream"));
+        let mut client = std::net::TcpStream::connect(stream.local_addr()).expect("connect");
+        client.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        client
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        while stream.client_count() == 0 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let graph = ir::Graph {
+            nodes: vec![ir::Node {
+                id: "det0".to_string(),
+                node_type: "DETECTOR".to_string(),
+                params: HashMap::from([("efficiency".to_string(), 1.0)]),
+                measure_mode: Some("mode_0".to_string()),
+                measurement: None,
+                conditional_branches: None,
+                feed_forward: None,
+            }],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        Engine::new()
+            .with_event_stream(Arc::clone(&stream))
+            .run_graph_in(&graph, Some(7), dir.path())
+            .expect("engine run failed");
+
+        // The measurement arrives before the run's spans and metrics.
+        let mut events = Vec::new();
+        for line in BufReader::new(client).lines() {
+            let line = line.expect("read stream");
+            if let Some(event) = line.strip_prefix("event: ") {
+                events.push(event.to_string());
+                if event == "metric" {
+                    break;
+                }
+            }
+        }
+        assert_eq!(events[0], "measurement");
+        assert!(events.iter().any(|e| e == "span"));
+        assert!(events.iter().any(|e| e == "timeline"));
+    }
+
+    #[test]
+    fn test_crosstalk_applied_after_gates() {
+        let dir = tempfile::tempdir().unwrap();
+        let node = |id: &str, node_type: &str, params: Vec<(&str, f64)>| ir::Node {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            params: params
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let graph = ir::Graph {
+            nodes: vec![
+                node("d0", "DISPLACEMENT", vec![("q", 1.0)]),
+                node("ps", "PS", vec![("phase", 0.1)]),
+            ],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        let mode_1_vacuum = |engine: Engine| {
+            let out = engine
+                .run_graph_in(&graph, Some(3), dir.path())
+                .expect("engine run failed");
+            let states: Vec<QuantumState> = serde_json::from_str(
+                &std::fs::read_to_string(out.join("quantum_states.json")).expect("read states"),
+            )
+            .expect("parse states");
+            states.last().unwrap().modes[1].amplitudes.as_ref().unwrap()[0].norm_sqr()
+        };
+
+        assert!((mode_1_vacuum(Engine::new()) - 1.0).abs() < 1e-12);
+        let noisy = Engine::new().with_noise_config(SimulatorNoiseConfig {
+            crosstalk: SimulatorNoiseConfig::adjacent_crosstalk(2, 0.05),
+            ..Default::default()
+        });
+        assert!(mode_1_vacuum(noisy) < 1.0 - 1e-3);
+    }
+
+    #[test]
+    fn test_trajectory_mode_varies_shot_to_shot() {
+        let dir = tempfile::tempdir().unwrap();
+        let node = |id: &str, node_type: &str, params: Vec<(&str, f64)>| ir::Node {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            params: params
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let graph = ir::Graph {
+            nodes: vec![
+                node("d0", "DISPLACEMENT", vec![("q", 1.5)]),
+                node("ps", "PS", vec![("phase", 0.1)]),
+            ],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        let engine = Engine::new().with_trajectories(TrajectorySimulator::new(0.3, 0.0));
+        let photons_lost = |seed: u64| {
+            let out = engine
+                .run_graph_in(&graph, Some(seed), dir.path())
+                .expect("engine run failed");
+            let states: Vec<QuantumState> = serde_json::from_str(
+                &std::fs::read_to_string(out.join("quantum_states.json")).expect("read states"),
+            )
+            .expect("parse states");
+            states
+                .last()
+                .unwrap()
+                .provenance
+                .get("trajectory_loss:mode_0")
+                .map_or(0, |n| n.parse::<u32>().unwrap())
+        };
+
+        let lost: Vec<u32> = (0..12).map(photons_lost).collect();
+        assert!(
+            lost.iter().any(|&k| k != lost[0]),
+            "no variation: {:?}",
+            lost
+        );
+        assert_eq!(photons_lost(5), lost[5]);
+    }
+
+    #[test]
+    fn test_noise_profile_selected_by_graph_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut profiles = NoiseProfileRegistry::new();
+        profiles
+            .register(
+                "lab_chip_A_2026Q1",
+                SimulatorNoiseConfig {
+                    crosstalk: SimulatorNoiseConfig::adjacent_crosstalk(2, 0.05),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let engine = Engine::new()
+            .with_noise_profiles(profiles)
+            .with_noise_profile("ideal")
+            .unwrap();
+        let mut graph = ir::Graph {
+            nodes: vec![
+                ir::Node {
+                    id: "d0".to_string(),
+                    node_type: "DISPLACEMENT".to_string(),
+                    params: HashMap::from([("q".to_string(), 1.0)]),
+                    measure_mode: None,
+                    measurement: None,
+                    conditional_branches: None,
+                    feed_forward: None,
+                },
+                ir::Node {
+                    id: "ps".to_string(),
+                    node_type: "PS".to_string(),
+                    params: HashMap::from([("phase".to_string(), 0.1)]),
+                    measure_mode: None,
+                    measurement: None,
+                    conditional_branches: None,
+                    feed_forward: None,
+                },
+            ],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        let run = |graph: &ir::Graph| {
+            let out = engine
+                .run_graph_in(graph, Some(3), dir.path())
+                .expect("engine run failed");
+            let states: Vec<QuantumState> = serde_json::from_str(
+                &std::fs::read_to_string(out.join("quantum_states.json")).expect("read states"),
+            )
+            .expect("parse states");
+            let recorded: NoiseProfile = serde_json::from_str(
+                &std::fs::read_to_string(out.join("noise_model.json")).expect("read noise model"),
+            )
+            .expect("parse noise model");
+            (states.last().unwrap().clone(), recorded)
+        };
+
+        // Without metadata the engine's profile applies.
+        let (state, recorded) = run(&graph);
+        assert_eq!(state.provenance["noise_model_id"], "ideal");
+        assert_eq!(recorded.id, "ideal");
+        assert!((state.modes[1].amplitudes.as_ref().unwrap()[0].norm_sqr() - 1.0).abs() < 1e-12);
+
+        // The graph's metadata overrides it, and the recorded model is the one that ran.
+        graph.metadata.insert(
+            NOISE_PROFILE_METADATA_KEY.to_string(),
+            "lab_chip_A_2026Q1".to_string(),
+        );
+        let (state, recorded) = run(&graph);
+        assert_eq!(state.provenance["noise_model_id"], "lab_chip_A_2026Q1");
+        assert_eq!(recorded.config.crosstalk[0][1], 0.05);
+        assert!(state.modes[1].amplitudes.as_ref().unwrap()[0].norm_sqr() < 1.0 - 1e-3);
+
+        graph.metadata.insert(
+            NOISE_PROFILE_METADATA_KEY.to_string(),
+            "lab_chip_B".to_string(),
+        );
+        assert!(engine.run_graph_in(&graph, Some(3), dir.path()).is_err());
+        assert!(Engine::new().with_noise_profile("lab_chip_B").is_err());
+    }
+
+    #[test]
+    fn test_noise_diagnostics_on_node_spans() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = Engine::new().with_noise_profile("default").unwrap();
+        let node = |id: &str, node_type: &str, params: &[(&str, f64)]| ir::Node {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            params: params.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let graph = ir::Graph {
+            nodes: vec![
+                node("d0", "DISPLACEMENT", &[("q", 1.0), ("length_cm", 3.0)]),
+                node("det", "DETECTOR", &[]),
+            ],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        let out = engine
+            .run_graph_in(&graph, Some(5), dir.path())
+            .expect("engine run failed");
+
+        let spans: Vec<observability::Span> = std::fs::read_to_string(out.join("traces.jsonl"))
+            .expect("read traces")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("parse span"))
+            .collect();
+        let span = |name: &str| spans.iter().find(|s| s.name == name).unwrap();
+        let attr = |name: &str, key: &str| -> f64 { span(name).attributes[key].parse().unwrap() };
+        assert!(
+            (attr("exec:d0", "noise.loss_probability") - (1.0 - (-0.03_f64).exp())).abs() < 1e-12
+        );
+        assert_eq!(attr("exec:d0", "noise.dark_counts"), 0.0);
+        assert!(attr("exec:det", "noise.dark_counts") > 0.0);
+        // The detector sees the displaced mode's photons.
+        assert!(attr("exec:det", "noise.kerr_phase_shift") > 0.0);
+
+        let metrics: observability::Metrics = serde_json::from_str(
+            &std::fs::read_to_string(out.join("metrics.json")).expect("read metrics"),
+        )
+        .expect("parse metrics");
+        let dark = &metrics.series["noise.dark_counts"];
+        assert_eq!(dark.len(), 2);
+        assert_eq!(dark[1].node_id.as_deref(), Some("det"));
+
+        // Without a noise profile there is nothing to attribute.
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(5), dir.path())
+            .expect("engine run failed");
+        let traces = std::fs::read_to_string(out.join("traces.jsonl")).expect("read traces");
+        assert!(!traces.contains("noise.loss_probability"));
+    }
+
+    #[test]
+    fn test_ir_validation_rejects_feed_forward_from_non_detector() {
+        let mut graph = feed_forward_graph(true);
+        graph.nodes[0].node_type = "MZI".to_string();
+        assert!(ir::validate_graph(&graph).is_err());
+        graph.nodes[1].feed_forward.as_mut().unwrap()[0].source_node = "missing".to_string();
+        assert!(ir::validate_graph(&graph).is_err());
+    }
+
+    #[test]
+    fn test_shot_record_artifact_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut backend = StatevectorSimulator::new();
+        let labels = vec!["a".to_string(), "b".to_string()];
+        let state = backend
+            .prepare(
+                labels.clone(),
+                &quantum::PreparationKind::BellState {
+                    entanglement_type: quantum::BellType::PhiPlus,
+                },
+                1,
+            )
+            .unwrap();
+        let basis = MeasurementBasis {
+            basis_type: quantum::BasisType::Computational,
+            mode_labels: labels,
+        };
+        let out = Engine::new()
+            .run_shots_in(&backend, &state, &basis, 64, Some(3), dir.path())
+            .expect("shot run failed");
+        let data = std::fs::read_to_string(out.join("shots.json")).expect("read shots");
+        let record: quantum::ShotRecord = serde_json::from_str(&data).expect("parse shots");
+        assert_eq!(record.num_shots(), 64);
+        assert_eq!(record.seed, 3);
+        let counts = record.counts().unwrap();
+        assert_eq!(
+            counts.get("0,0").unwrap_or(&0) + counts.get("1,1").unwrap_or(&0),
+            64
+        );
+    }
+
+    #[test]
+    fn test_tomography_artifact_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut backend = StatevectorSimulator::new();
+        let labels = vec!["q0".to_string()];
+        let target = backend
+            .prepare(
+                labels.clone(),
+                &quantum::PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 1.0],
+                },
+                3,
+            )
+            .expect("prepare");
+        let plan = TomographyPlan::pauli(labels, 200).expect("plan");
+        let out = Engine::new()
+            .run_tomography_in(&mut backend, &target, &plan, Some(5), dir.path())
+            .expect("tomography run failed");
+        let data = std::fs::read_to_string(out.join("tomography.json")).expect("read tomography");
+        let artifact: tomography::TomographyArtifact =
+            serde_json::from_str(&data).expect("parse tomography");
+        assert_eq!(artifact.records.len(), 3);
+        assert_eq!(artifact.target_state_id, target.state_id);
+        assert!(artifact.fidelity > 0.97, "fidelity {}", artifact.fidelity);
+    }
+
     #[test]
     fn test_measurements_artifact_created() {
+        let dir = tempfile::tempdir().unwrap();
         let graph = ir::load_from_json("example_ir.json").expect("failed to load example_ir.json");
         let engine = Engine::new();
         let out = engine
-            .run_graph(&graph, Some(42))
+            .run_graph_in(&graph, Some(42), dir.path())
             .expect("engine run failed");
         // Verify measurements artifact is created
         assert!(
@@ -422,6 +1815,124 @@ mod tests {
         // Measurements may be empty if no DETECTOR nodes in graph, which is fine
     }
 
+    #[test]
+    fn test_branch_decisions_share_measurement_correlation_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let node = |id: &str, node_type: &str| ir::Node {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            params: HashMap::from([("phase".to_string(), 0.1)]),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let mut detector = node("m0", "DETECTOR");
+        detector.conditional_branches = Some(vec![ir::ConditionalBranch {
+            outcome_index: 0,
+            then_nodes: vec!["then_ps".to_string()],
+            else_nodes: Some(vec!["else_ps".to_string()]),
+        }]);
+        let graph = ir::Graph {
+            nodes: vec![detector, node("then_ps", "PS"), node("else_ps", "PS")],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(9), dir.path())
+            .expect("engine run failed");
+
+        let measurements: HashMap<String, crate::state::MeasurementOutcome> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("measurements.json")).expect("read measurements"),
+        )
+        .expect("parse measurements");
+        let outcome = &measurements["m0"];
+        let correlation_id = outcome.correlation_id.clone().expect("correlation id");
+
+        let timeline: Vec<observability::TimelineEvent> = serde_json::from_str(
+            &std::fs::read_to_string(out.join("timeline.json")).expect("read timeline"),
+        )
+        .expect("parse timeline");
+        let decision = timeline
+            .iter()
+            .find(|e| e.name == "branch:m0")
+            .expect("branch decision event");
+        assert_eq!(decision.lane, "Control");
+        assert_eq!(decision.attributes["correlation_id"], correlation_id);
+        let (taken, other) = if outcome.outcome_index == 0 {
+            ("then_ps", "else_ps")
+        } else {
+            ("else_ps", "then_ps")
+        };
+        assert_eq!(decision.attributes["nodes"], taken);
+
+        let spans: Vec<observability::Span> = std::fs::read_to_string(out.join("traces.jsonl"))
+            .expect("read traces")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("parse span"))
+            .collect();
+        let span = |name: &str| spans.iter().find(|s| s.name == name).unwrap();
+        assert_eq!(span("exec:m0").attributes["correlation_id"], correlation_id);
+        assert_eq!(
+            span(&format!("exec:{}", taken)).attributes["correlation_id"],
+            correlation_id
+        );
+        assert!(!span(&format!("exec:{}", other))
+            .attributes
+            .contains_key("correlation_id"));
+    }
+
+    #[test]
+    fn test_consumers_listed_before_their_detector_run_after_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let node = |id: &str, node_type: &str| ir::Node {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            params: HashMap::from([("phase".to_string(), 0.1)]),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let mut corrected = node("fix_ps", "PS");
+        corrected.feed_forward = Some(vec![ir::FeedForward {
+            param: "phase".to_string(),
+            source_node: "m0".to_string(),
+            quadrature: 0,
+            gain: 0.5,
+            offset: 0.0,
+        }]);
+        let mut detector = node("m0", "DETECTOR");
+        detector.conditional_branches = Some(vec![ir::ConditionalBranch {
+            outcome_index: 0,
+            then_nodes: vec!["then_ps".to_string()],
+            else_nodes: None,
+        }]);
+        let graph = ir::Graph {
+            nodes: vec![corrected, node("then_ps", "PS"), detector],
+            edges: vec![],
+            metadata: Default::default(),
+        };
+        let out = Engine::new()
+            .run_graph_in(&graph, Some(9), dir.path())
+            .expect("engine run failed");
+
+        let spans: Vec<observability::Span> = std::fs::read_to_string(out.join("traces.jsonl"))
+            .expect("read traces")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("parse span"))
+            .collect();
+        // Node spans are written as each node finishes, so line order is execution order
+        let position = |name: &str| {
+            spans
+                .iter()
+                .position(|s| s.name == name)
+                .unwrap_or_else(|| panic!("no span {}", name))
+        };
+        assert!(position("exec:m0") < position("exec:fix_ps"));
+        assert!(position("exec:m0") < position("exec:then_ps"));
+    }
+
     #[test]
     fn test_ir_validation_fails_on_invalid_branches() {
         let graph = ir::Graph {
@@ -430,11 +1941,13 @@ mod tests {
                 node_type: "DETECTOR".to_string(),
                 params: Default::default(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: Some(vec![ir::ConditionalBranch {
                     outcome_index: 0,
                     then_nodes: vec!["nonexistent".to_string()], // references non-existent node
                     else_nodes: None,
                 }]),
+                feed_forward: None,
             }],
             edges: vec![],
             metadata: Default::default(),
@@ -442,8 +1955,9 @@ mod tests {
 
         let result = ir::validate_graph(&graph);
         assert!(
-            result.is_err(),
-            "validation should reject invalid branch references"
+            matches!(result, Err(ir::IrError::UnknownBranchNode { ref node, .. }) if node == "nonexistent"),
+            "validation should reject invalid branch references: {:?}",
+            result
         );
     }
 
@@ -456,18 +1970,22 @@ mod tests {
                     node_type: "DETECTOR".to_string(),
                     params: Default::default(),
                     measure_mode: None,
+                    measurement: None,
                     conditional_branches: Some(vec![ir::ConditionalBranch {
                         outcome_index: 0,
                         then_nodes: vec!["mzi1".to_string()],
                         else_nodes: None,
                     }]),
+                    feed_forward: None,
                 },
                 ir::Node {
                     id: "mzi1".to_string(),
                     node_type: "MZI".to_string(),
                     params: Default::default(),
                     measure_mode: None,
+                    measurement: None,
                     conditional_branches: None,
+                    feed_forward: None,
                 },
             ],
             edges: vec![],
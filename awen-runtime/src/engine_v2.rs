@@ -1,3 +1,4 @@
+use crate::observability::ErrorCode;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 /// Engine Execution Core - Phase 2, Section 2.1
@@ -126,6 +127,9 @@ pub struct NodeExecutionLog {
     pub duration_ns: u64,
     pub success: bool,
     pub error: Option<String>,
+    /// Failure class of `error`.
+    #[serde(default)]
+    pub error_code: Option<ErrorCode>,
 }
 
 // ============================================================================
@@ -241,6 +245,7 @@ impl Engine {
                             duration_ns,
                             success: true,
                             error: None,
+                            error_code: None,
                         });
 
                         // Update coherence budget
@@ -250,12 +255,11 @@ impl Engine {
                     }
                     Err(e) => {
                         let error_msg = e.to_string();
-
-                        if error_msg.contains("coherence") {
-                            coherence_violations += 1;
-                        }
-                        if error_msg.contains("safety") {
-                            safety_violations += 1;
+                        let error_code = ErrorCode::classify(&error_msg);
+                        match error_code {
+                            Some(ErrorCode::CoherenceViolation) => coherence_violations += 1,
+                            Some(ErrorCode::SafetyClamp) => safety_violations += 1,
+                            _ => {}
                         }
 
                         execution_log.push(NodeExecutionLog {
@@ -266,6 +270,7 @@ impl Engine {
                             duration_ns,
                             success: false,
                             error: Some(error_msg),
+                            error_code,
                         });
 
                         // Handle violation based on strategy
@@ -0,0 +1,132 @@
+//! Typed errors library consumers can match on.
+//!
+//! Each subsystem has its own error enum: [`IrError`](crate::ir::IrError) for loading and
+//! validating graphs, `hal::HalError` for device calls (with `hal::interlock::InterlockError`
+//! for the safety interlock) and `chokepoint::AccessError` for API tokens and roles.
+//! [`AwenError`] wraps them all.
+//!
+//! Runtime APIs that return `anyhow::Result` keep these as the underlying error, including
+//! under added context, so `AwenError::from` recovers the typed variant:
+//!
+//! ```
+//! use awen_runtime::error::AwenError;
+//! use awen_runtime::ir::{self, Graph, IrError};
+//!
+//! let graph: Graph = serde_json::from_str(
+//!     r#"{"nodes": [{"id": "m0", "type": "DETECTOR",
+//!         "conditional_branches": [{"outcome_index": 1, "then_nodes": ["ghost"]}]}]}"#,
+//! )
+//! .unwrap();
+//! let err: anyhow::Error = ir::validate_graph(&graph).unwrap_err().into();
+//! match AwenError::from(err) {
+//!     AwenError::Ir(IrError::UnknownBranchNode { node, .. }) => assert_eq!(node, "ghost"),
+//!     other => panic!("unexpected error: {}", other),
+//! }
+//! ```
+
+use crate::ir::IrError;
+
+#[cfg(feature = "native")]
+use crate::chokepoint::AccessError;
+#[cfg(feature = "simulator")]
+use crate::hal::{interlock::InterlockError, HalError};
+
+/// Any error the runtime returns
+#[derive(Debug, thiserror::Error)]
+pub enum AwenError {
+    #[error(transparent)]
+    Ir(#[from] IrError),
+    #[cfg(feature = "simulator")]
+    #[error(transparent)]
+    Hal(#[from] HalError),
+    #[cfg(feature = "native")]
+    #[error(transparent)]
+    Access(#[from] AccessError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// An error with no typed variant; its message and context chain are kept
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+/// `Result` with [`AwenError`] as the default error
+pub type Result<T, E = AwenError> = std::result::Result<T, E>;
+
+#[cfg(feature = "simulator")]
+impl From<InterlockError> for AwenError {
+    fn from(e: InterlockError) -> Self {
+        AwenError::Hal(e.into())
+    }
+}
+
+impl From<anyhow::Error> for AwenError {
+    /// The typed error `e` was raised from, if it has one; otherwise [`AwenError::Other`]
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<IrError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        #[cfg(feature = "simulator")]
+        let e = match e.downcast::<HalError>() {
+            Ok(e) => return e.into(),
+            Err(e) => match e.downcast::<InterlockError>() {
+                Ok(e) => return e.into(),
+                Err(e) => e,
+            },
+        };
+        #[cfg(feature = "native")]
+        let e = match e.downcast::<AccessError>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<std::io::Error>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        match e.downcast::<serde_json::Error>() {
+            Ok(e) => e.into(),
+            Err(e) => AwenError::Other(e),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::chokepoint::{AccessControl, Role, RuntimeAction};
+    use crate::hal::interlock::Interlock;
+    use anyhow::Context;
+
+    #[test]
+    fn test_typed_errors_survive_anyhow() {
+        let access = AccessControl::new().with_token("t", "grafana", Role::Observer);
+        let denied = || -> anyhow::Result<()> {
+            access
+                .authorize(Some("t"), RuntimeAction::DriveHardware)
+                .context("submitting job 7")?;
+            Ok(())
+        };
+        let err = AwenError::from(denied().unwrap_err());
+        assert!(matches!(
+            err,
+            AwenError::Access(AccessError::Forbidden {
+                role: Role::Observer,
+                ..
+            })
+        ));
+
+        let interlock = Interlock::new();
+        interlock.trip("operator", "door open");
+        let err = AwenError::from(anyhow::Error::from(interlock.check().unwrap_err()));
+        assert!(matches!(
+            err,
+            AwenError::Hal(HalError::Interlock(InterlockError::Tripped { .. }))
+        ));
+
+        let err = AwenError::from(anyhow::anyhow!("queue full"));
+        assert!(matches!(err, AwenError::Other(_)));
+        assert_eq!(err.to_string(), "queue full");
+    }
+}
@@ -13,6 +13,18 @@ use crate::ir;
 use crate::plugins::run_reference_simulator;
 use std::f64::consts::PI;
 
+mod adaptive_fd;
+mod check;
+mod gaussian_adjoint;
+mod parameter_shift;
+pub(crate) use adaptive_fd::locate_param;
+pub use adaptive_fd::{
+    AdaptiveFiniteDifferenceProvider, DeviceObjective, Objective, SimulatorObjective,
+};
+pub use check::{check, CostFiniteDifference, GradientCheck, GradientDiscrepancy};
+pub use gaussian_adjoint::GaussianAdjointProvider;
+pub use parameter_shift::{ParameterShiftProvider, SHIFTABLE_PARAMS};
+
 /// Describes noise model parameters for gradient estimation.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NoiseModel {
@@ -55,6 +67,12 @@ pub trait GradientProvider: Send + Sync + 'static {
     fn supports_adjoint(&self) -> bool {
         false
     }
+
+    /// Value of the scalar cost the gradients belong to. Defaults to the output power of the last
+    /// node in the reference simulator, the cost of the reference providers.
+    fn evaluate_cost(&self, ir_json: &str) -> Result<f64> {
+        SimulatorObjective::new(serde_json::from_str(ir_json)?).evaluate(&HashMap::new(), 0)
+    }
 }
 
 /// Registry: runtime holds a registry of gradient providers keyed by backend name. Providers are stored as Arc.
@@ -78,6 +96,12 @@ impl GradientRegistry {
         let r = self.providers.read().unwrap();
         r.get(name).cloned()
     }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.providers.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
 }
 
 impl Default for GradientRegistry {
@@ -453,9 +477,16 @@ impl GradientProvider for ReferenceAdjointProvider {
 // Update register_defaults_to_global to add adjoint provider as well
 pub fn register_default_providers(registry: &GradientRegistry) {
     let provider = Arc::new(ReferenceGradientProvider::new());
-    registry.register("reference-fd", provider);
+    registry.register("reference-fd", provider.clone());
+    registry.register("finite_diff", provider);
     let adj = Arc::new(ReferenceAdjointProvider::new());
     registry.register("reference-adjoint", adj);
+    registry.register(
+        "adaptive-fd",
+        Arc::new(AdaptiveFiniteDifferenceProvider::new()),
+    );
+    registry.register("gaussian-adjoint", Arc::new(GaussianAdjointProvider::new()));
+    registry.register("parameter_shift", Arc::new(ParameterShiftProvider::new()));
 }
 
 #[cfg(test)]
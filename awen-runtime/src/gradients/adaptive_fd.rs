@@ -0,0 +1,366 @@
+//! Finite differences with automatic step-size selection.
+//!
+//! The step for each parameter balances truncation error against the noise of the objective.
+//!
+//! 1. The noise level `σ` is measured from repeated evaluations at the base point. A
+//!    `NoiseModel::shot_noise_std` is used as a floor.
+//! 2. A noiseless objective uses `h = ∛ε · max(1, |x|)`.
+//! 3. Otherwise the third derivative `f'''` is estimated with a five-point stencil at the
+//!    largest allowed step, and the step that minimises the central-difference error
+//!    `h = ∛(3σ / |f'''|)` is taken. When `f'''` is lost in the noise, the largest step is used.
+//! 4. The gradient is the Richardson extrapolation `(4·D(h/2) − D(h)) / 3` of the central
+//!    differences `D(h) = (f(x+h) − f(x−h)) / 2h`. This cancels the `O(h²)` truncation term.
+//!
+//! Every evaluation gets a fresh seed, as repeated reads on hardware would. The same code runs
+//! against the reference simulator ([`SimulatorObjective`]) and against a HAL device
+//! ([`DeviceObjective`]) through the [`Objective`] trait.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+use super::{GradientOptions, GradientProvider, GradientResult, NoiseModel};
+use crate::hal::Device;
+use crate::ir;
+use crate::plugins::run_reference_simulator;
+
+/// Scalar cost at given parameter values, the quantity finite differences probe
+pub trait Objective {
+    fn evaluate(&self, params: &HashMap<String, f64>, seed: u64) -> Result<f64>;
+}
+
+/// Output power of the last node of the reference simulator. Parameters are named `node:key`,
+/// or `key` for the first node that has it.
+pub struct SimulatorObjective {
+    graph: ir::Graph,
+}
+
+impl SimulatorObjective {
+    pub fn new(graph: ir::Graph) -> Self {
+        Self { graph }
+    }
+
+    /// Current values of `params` in the graph, or an error naming an unknown parameter
+    pub fn point(&self, params: &[String]) -> Result<HashMap<String, f64>> {
+        params
+            .iter()
+            .map(|name| {
+                let (node, key) = locate_param(&self.graph, name)
+                    .ok_or_else(|| anyhow!("parameter {} not found in graph", name))?;
+                let value = self.graph.nodes[node].params.get(&key).copied();
+                Ok((name.clone(), value.unwrap_or(0.0)))
+            })
+            .collect()
+    }
+}
+
+impl Objective for SimulatorObjective {
+    fn evaluate(&self, params: &HashMap<String, f64>, seed: u64) -> Result<f64> {
+        let mut graph = self.graph.clone();
+        for (name, value) in params {
+            let (node, key) = locate_param(&graph, name)
+                .ok_or_else(|| anyhow!("parameter {} not found in graph", name))?;
+            graph.nodes[node].params.insert(key, *value);
+        }
+        let sim = run_reference_simulator(&graph, Some(seed))?;
+        Ok(sim
+            .node_results
+            .last()
+            .map(|r| r.out_amplitude.0.powi(2) + r.out_amplitude.1.powi(2))
+            .unwrap_or(0.0))
+    }
+}
+
+/// `sensor` read back after writing the parameters to `device`. The seed is unused; each read
+/// carries the instrument's own noise.
+pub struct DeviceObjective<'a> {
+    pub device: &'a dyn Device,
+    pub sensor: String,
+}
+
+impl Objective for DeviceObjective<'_> {
+    fn evaluate(&self, params: &HashMap<String, f64>, _seed: u64) -> Result<f64> {
+        for (name, value) in params {
+            self.device
+                .set_param(name, *value)
+                .map_err(|e| anyhow!(e))?;
+        }
+        self.device
+            .read_sensor(&self.sensor)
+            .map_err(|e| anyhow!(e))
+    }
+}
+
+/// Node index and key of `name` (`node:key`, or `key` in the first node that has it)
+pub(crate) fn locate_param(graph: &ir::Graph, name: &str) -> Option<(usize, String)> {
+    match name.split_once(':') {
+        Some((node, key)) => graph
+            .nodes
+            .iter()
+            .position(|n| n.id == node)
+            .map(|i| (i, key.to_string())),
+        None => graph
+            .nodes
+            .iter()
+            .position(|n| n.params.contains_key(name))
+            .map(|i| (i, name.to_string())),
+    }
+}
+
+/// Finite-difference [`GradientProvider`] with noise-adapted steps, registered as
+/// `adaptive-fd`
+#[derive(Debug, Clone)]
+pub struct AdaptiveFiniteDifferenceProvider {
+    /// Smallest step, relative to `max(1, |x|)`
+    pub min_step: f64,
+    /// Largest step, relative to `max(1, |x|)`; also the pilot step for `f'''`
+    pub max_step: f64,
+    /// Evaluations at the base point used to measure noise when `GradientOptions::samples`
+    /// does not ask for more
+    pub noise_samples: u32,
+    /// Apply Richardson extrapolation to the central differences
+    pub richardson: bool,
+}
+
+impl AdaptiveFiniteDifferenceProvider {
+    pub fn new() -> Self {
+        Self {
+            min_step: 1e-8,
+            max_step: 0.1,
+            noise_samples: 5,
+            richardson: true,
+        }
+    }
+
+    /// Gradient of `objective` at `point` with respect to `params`, e.g. against a
+    /// [`DeviceObjective`]
+    pub fn gradient(
+        &self,
+        objective: &dyn Objective,
+        point: &HashMap<String, f64>,
+        params: &[String],
+        noise: &NoiseModel,
+        opts: &GradientOptions,
+    ) -> Result<GradientResult> {
+        let mut seed = opts.seed.unwrap_or(0x1234_5678);
+        let mut eval = |values: &HashMap<String, f64>| {
+            seed = seed.wrapping_add(1);
+            objective.evaluate(values, seed)
+        };
+
+        let samples = opts.samples.unwrap_or(0).max(self.noise_samples).max(2);
+        let base: Vec<f64> = (0..samples).map(|_| eval(point)).collect::<Result<_>>()?;
+        let sigma = sample_std(&base).max(noise.shot_noise_std.unwrap_or(0.0));
+
+        let mut gradients = HashMap::new();
+        let mut stds = HashMap::new();
+        let mut provenance = HashMap::new();
+        provenance.insert("provider".to_string(), "adaptive-fd".to_string());
+        provenance.insert("noise_std".to_string(), format!("{:e}", sigma));
+
+        for name in params {
+            let x = *point
+                .get(name)
+                .ok_or_else(|| anyhow!("no value for parameter {}", name))?;
+            let scale = x.abs().max(1.0);
+            let mut at = |offset: f64| {
+                let mut values = point.clone();
+                values.insert(name.clone(), x + offset);
+                eval(&values)
+            };
+
+            let h = if sigma == 0.0 {
+                f64::EPSILON.cbrt() * scale
+            } else {
+                let pilot = self.max_step * scale;
+                let third = (at(2.0 * pilot)? - 2.0 * at(pilot)? + 2.0 * at(-pilot)?
+                    - at(-2.0 * pilot)?)
+                    / (2.0 * pilot.powi(3));
+                // Standard deviation of the stencil from noise alone
+                let third_noise = sigma * 10f64.sqrt() / (2.0 * pilot.powi(3));
+                if third.abs() <= third_noise {
+                    pilot
+                } else {
+                    (3.0 * sigma / third.abs()).cbrt()
+                }
+            }
+            .clamp(self.min_step * scale, self.max_step * scale);
+
+            let mut central =
+                |step: f64| -> Result<f64> { Ok((at(step)? - at(-step)?) / (2.0 * step)) };
+            let (gradient, std) = if self.richardson {
+                let coarse = central(h)?;
+                let fine = central(h / 2.0)?;
+                // Var = (16·2σ²/h² + σ²/2h²) / 9
+                (
+                    (4.0 * fine - coarse) / 3.0,
+                    sigma / h * 32.5f64.sqrt() / 3.0,
+                )
+            } else {
+                (central(h)?, sigma / (h * 2f64.sqrt()))
+            };
+
+            gradients.insert(name.clone(), gradient);
+            stds.insert(name.clone(), std);
+            provenance.insert(format!("step:{}", name), format!("{:e}", h));
+        }
+
+        Ok(GradientResult {
+            gradients,
+            gradient_std: Some(stds),
+            provenance,
+        })
+    }
+}
+
+impl Default for AdaptiveFiniteDifferenceProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GradientProvider for AdaptiveFiniteDifferenceProvider {
+    fn compute_gradients(
+        &self,
+        ir_json: &str,
+        params: &[String],
+        noise: &NoiseModel,
+        opts: &GradientOptions,
+    ) -> Result<GradientResult> {
+        let objective = SimulatorObjective::new(serde_json::from_str(ir_json)?);
+        let point = objective.point(params)?;
+        self.gradient(&objective, &point, params, noise, opts)
+    }
+}
+
+fn sample_std(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    var.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradients::ReferenceAdjointProvider;
+    use crate::hal::{Capability, HalError};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::cell::{Cell, RefCell};
+
+    fn noise_model(shot_noise_std: Option<f64>) -> NoiseModel {
+        NoiseModel {
+            shot_noise_std,
+            thermal_noise_std: None,
+            phase_noise_std: None,
+            loss_variation: None,
+            metadata: None,
+        }
+    }
+
+    fn opts() -> GradientOptions {
+        GradientOptions {
+            strategy: "finite_difference".to_string(),
+            seed: Some(7),
+            samples: None,
+        }
+    }
+
+    /// Detector reading `sin(phase) + 0.3·phase³` with Gaussian read noise
+    struct NoisyDetector {
+        phase: Cell<f64>,
+        noise_std: f64,
+        rng: RefCell<StdRng>,
+    }
+
+    impl Device for NoisyDetector {
+        fn id(&self) -> String {
+            "noisy".to_string()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+        fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+            if name != "mzi_0:phase" {
+                return Err(HalError::UnknownChannel(name.to_string()));
+            }
+            self.phase.set(value);
+            Ok(())
+        }
+        fn read_sensor(&self, _name: &str) -> Result<f64, HalError> {
+            let x = self.phase.get();
+            // Box–Muller
+            let mut rng = self.rng.borrow_mut();
+            let (u1, u2): (f64, f64) = (rng.gen_range(1e-12..1.0), rng.gen());
+            let n = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+            Ok(x.sin() + 0.3 * x.powi(3) + self.noise_std * n)
+        }
+    }
+
+    #[test]
+    fn test_step_adapts_to_measured_noise() {
+        let params = vec!["mzi_0:phase".to_string()];
+        let point = HashMap::from([(params[0].clone(), 0.4)]);
+        let exact = 0.4f64.cos() + 0.9 * 0.4f64.powi(2);
+        let provider = AdaptiveFiniteDifferenceProvider::new();
+
+        let mut steps = Vec::new();
+        for noise_std in [0.0, 1e-4] {
+            let device = NoisyDetector {
+                phase: Cell::new(0.0),
+                noise_std,
+                rng: RefCell::new(StdRng::seed_from_u64(3)),
+            };
+            let objective = DeviceObjective {
+                device: &device,
+                sensor: "det_0:power".to_string(),
+            };
+            let res = provider
+                .gradient(&objective, &point, &params, &noise_model(None), &opts())
+                .unwrap();
+            let g = res.gradients[&params[0]];
+            let std = res.gradient_std.as_ref().unwrap()[&params[0]];
+            assert!(
+                (g - exact).abs() <= 4.0 * std + 1e-6,
+                "noise {}: g={} exact={} std={}",
+                noise_std,
+                g,
+                exact,
+                std
+            );
+            steps.push(
+                res.provenance[&format!("step:{}", params[0])]
+                    .parse::<f64>()
+                    .unwrap(),
+            );
+        }
+        // Noise pushes the step well above the noiseless ∛ε
+        assert!(steps[1] > 100.0 * steps[0], "steps {:?}", steps);
+    }
+
+    #[test]
+    fn test_matches_adjoint_on_reference_graph() {
+        let ir = std::fs::read_to_string("example_ir.json").unwrap();
+        let params = vec!["mzi_0:phase".to_string(), "mzi_1:phase".to_string()];
+        let adaptive = AdaptiveFiniteDifferenceProvider::new()
+            .compute_gradients(&ir, &params, &noise_model(None), &opts())
+            .unwrap();
+        let adjoint = ReferenceAdjointProvider::new()
+            .compute_gradients(&ir, &params, &noise_model(None), &opts())
+            .unwrap();
+        for p in &params {
+            assert!((adaptive.gradients[p] - adjoint.gradients[p]).abs() < 1e-6);
+        }
+
+        let err = AdaptiveFiniteDifferenceProvider::new()
+            .compute_gradients(
+                &ir,
+                &["missing:phase".to_string()],
+                &noise_model(None),
+                &opts(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("missing:phase"));
+    }
+}
@@ -0,0 +1,156 @@
+//! Cross-checking gradient providers against each other.
+//!
+//! [`check`] runs two providers on the same graph and lists every parameter where they
+//! disagree. [`CostFiniteDifference`] is the usual second opinion. It takes central differences
+//! of a provider's own [`GradientProvider::evaluate_cost`], so any provider can be checked
+//! against the cost it claims to differentiate.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{locate_param, GradientOptions, GradientProvider, GradientResult, NoiseModel};
+use crate::ir;
+
+/// A parameter whose gradients differ by more than the tolerance. A gradient a provider did
+/// not return is `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradientDiscrepancy {
+    pub param: String,
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+    pub difference: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradientCheck {
+    pub gradients_a: HashMap<String, f64>,
+    pub gradients_b: HashMap<String, f64>,
+    pub tolerance: f64,
+    pub discrepancies: Vec<GradientDiscrepancy>,
+}
+
+impl GradientCheck {
+    pub fn passed(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Compare the gradients of `provider_a` and `provider_b` on `graph` element-wise. Two values
+/// agree when `|a − b| ≤ tol · max(1, |a|, |b|)`, so `tol` is absolute for small gradients and
+/// relative for large ones. Both providers run noiselessly with the same seed.
+pub fn check(
+    provider_a: &dyn GradientProvider,
+    provider_b: &dyn GradientProvider,
+    graph: &ir::Graph,
+    params: &[String],
+    tol: f64,
+) -> Result<GradientCheck> {
+    let ir_json = serde_json::to_string(graph)?;
+    let (noise, opts) = check_settings();
+    let a = provider_a.compute_gradients(&ir_json, params, &noise, &opts)?;
+    let b = provider_b.compute_gradients(&ir_json, params, &noise, &opts)?;
+
+    let discrepancies = params
+        .iter()
+        .filter_map(|param| {
+            let (ga, gb) = (
+                a.gradients.get(param).copied(),
+                b.gradients.get(param).copied(),
+            );
+            let difference = match (ga, gb) {
+                (Some(x), Some(y)) if (x - y).abs() <= tol * 1f64.max(x.abs()).max(y.abs()) => {
+                    return None
+                }
+                (Some(x), Some(y)) => (x - y).abs(),
+                _ => f64::INFINITY,
+            };
+            Some(GradientDiscrepancy {
+                param: param.clone(),
+                a: ga,
+                b: gb,
+                difference,
+            })
+        })
+        .collect();
+
+    Ok(GradientCheck {
+        gradients_a: a.gradients,
+        gradients_b: b.gradients,
+        tolerance: tol,
+        discrepancies,
+    })
+}
+
+fn check_settings() -> (NoiseModel, GradientOptions) {
+    let noise = NoiseModel {
+        shot_noise_std: None,
+        thermal_noise_std: None,
+        phase_noise_std: None,
+        loss_variation: None,
+        metadata: None,
+    };
+    let opts = GradientOptions {
+        strategy: "check".to_string(),
+        seed: Some(0),
+        samples: None,
+    };
+    (noise, opts)
+}
+
+/// Central finite differences of another provider's [`GradientProvider::evaluate_cost`]
+pub struct CostFiniteDifference {
+    inner: Arc<dyn GradientProvider>,
+    step: f64,
+}
+
+impl CostFiniteDifference {
+    pub fn new(inner: Arc<dyn GradientProvider>) -> Self {
+        Self { inner, step: 1e-5 }
+    }
+
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+}
+
+impl GradientProvider for CostFiniteDifference {
+    fn compute_gradients(
+        &self,
+        ir_json: &str,
+        params: &[String],
+        _noise: &NoiseModel,
+        _opts: &GradientOptions,
+    ) -> Result<GradientResult> {
+        let graph: ir::Graph = serde_json::from_str(ir_json)?;
+        let mut gradients = HashMap::new();
+        for name in params {
+            let Some((node, key)) = locate_param(&graph, name) else {
+                continue;
+            };
+            let x = graph.nodes[node].params.get(&key).copied().unwrap_or(0.0);
+            let at = |value: f64| {
+                let mut shifted = graph.clone();
+                shifted.nodes[node].params.insert(key.clone(), value);
+                self.inner.evaluate_cost(&serde_json::to_string(&shifted)?)
+            };
+            let gradient = (at(x + self.step)? - at(x - self.step)?) / (2.0 * self.step);
+            gradients.insert(name.clone(), gradient);
+        }
+
+        let mut provenance = HashMap::new();
+        provenance.insert("provider".to_string(), "cost-fd".to_string());
+        provenance.insert("step".to_string(), format!("{:e}", self.step));
+        Ok(GradientResult {
+            gradients,
+            gradient_std: None,
+            provenance,
+        })
+    }
+
+    fn evaluate_cost(&self, ir_json: &str) -> Result<f64> {
+        self.inner.evaluate_cost(ir_json)
+    }
+}
@@ -0,0 +1,563 @@
+//! Adjoint gradients for continuous-variable circuits on the Gaussian backend.
+//!
+//! Each gate is an affine symplectic map `μ → Sμ + d`, `V → S V Sᵀ`. One forward pass stores
+//! the state entering every gate. One backward pass carries the cost adjoints `(∂C/∂μ, ∂C/∂V)`
+//! back through the gates with `∂C/∂μ → Sᵀ·∂C/∂μ` and `∂C/∂V → Sᵀ·∂C/∂V·S`. Along the way every
+//! gate parameter `θ` gets
+//!
+//! `∂C/∂θ = ∂C/∂μ · (∂S/∂θ·μ + ∂d/∂θ) + 2·tr(∂C/∂V · ∂S/∂θ · V · Sᵀ)`.
+//!
+//! A full mesh therefore costs two passes whatever the number of parameters.
+//!
+//! Circuits use the node types the engine executes on CV states:
+//!
+//! | Node type | Parameters | Modes |
+//! |---|---|---|
+//! | `DISPLACEMENT` | `q`, `p` | `mode_id` |
+//! | `PS` | `phase` | `mode_id` |
+//! | `SQUEEZING` | `r`, `angle` | `mode_id` |
+//! | `BS`, `MZI` | `theta` (`phase` on an `MZI`), `phi` | `mode1`, `mode2` |
+//!
+//! Other nodes are ignored. The circuit starts in vacuum on `metadata["num_modes"]` modes,
+//! or as many as the gates touch. The cost is the mean photon number of
+//! `metadata["cost_mode"]`, mode 0 by default.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+use super::{GradientOptions, GradientProvider, GradientResult, NoiseModel};
+use crate::ir;
+use crate::quantum::gaussian::GaussianState;
+use crate::quantum::linalg::matmul;
+
+#[derive(Debug, Clone)]
+enum GateKind {
+    Displace { q: f64, p: f64 },
+    PhaseShift { phi: f64 },
+    Squeeze { r: f64, angle: f64 },
+    BeamSplitter { theta: f64, phi: f64 },
+}
+
+#[derive(Debug, Clone)]
+struct Gate {
+    node: usize,
+    kind: GateKind,
+    modes: Vec<usize>,
+    /// Parameter key holding the beam-splitter angle (`phase` on an `MZI`)
+    theta_key: &'static str,
+}
+
+impl Gate {
+    fn from_node(index: usize, node: &ir::Node) -> Option<Gate> {
+        let param = |key: &str, default: f64| node.params.get(key).copied().unwrap_or(default);
+        let mode = |key: &str, default: f64| param(key, default).max(0.0) as usize;
+        let (kind, modes, theta_key) = match node.node_type.to_ascii_uppercase().as_str() {
+            "DISPLACEMENT" => (
+                GateKind::Displace {
+                    q: param("q", 0.0),
+                    p: param("p", 0.0),
+                },
+                vec![mode("mode_id", 0.0)],
+                "theta",
+            ),
+            "PS" => (
+                GateKind::PhaseShift {
+                    phi: param("phase", 0.0),
+                },
+                vec![mode("mode_id", 0.0)],
+                "theta",
+            ),
+            "SQUEEZING" => (
+                GateKind::Squeeze {
+                    r: param("r", 0.0),
+                    angle: param("angle", 0.0),
+                },
+                vec![mode("mode_id", 0.0)],
+                "theta",
+            ),
+            kind @ ("BS" | "MZI") => {
+                let theta_key = if kind == "MZI" { "phase" } else { "theta" };
+                (
+                    GateKind::BeamSplitter {
+                        theta: param(theta_key, std::f64::consts::FRAC_PI_4),
+                        phi: param("phi", 0.0),
+                    },
+                    vec![mode("mode1", 0.0), mode("mode2", 1.0)],
+                    theta_key,
+                )
+            }
+            _ => return None,
+        };
+        Some(Gate {
+            node: index,
+            kind,
+            modes,
+            theta_key,
+        })
+    }
+
+    fn apply(&self, state: &mut GaussianState) -> Result<()> {
+        match self.kind {
+            GateKind::Displace { q, p } => state.displace(self.modes[0], q, p),
+            GateKind::PhaseShift { phi } => state.phase_shift(self.modes[0], phi),
+            GateKind::Squeeze { r, angle } => state.squeeze(self.modes[0], r, angle),
+            GateKind::BeamSplitter { theta, phi } => {
+                state.beam_splitter(self.modes[0], self.modes[1], theta, phi)
+            }
+        }
+    }
+
+    /// Local symplectic matrix on `modes`, matching `GaussianState`'s gate conventions
+    fn local_matrix(&self) -> Vec<Vec<f64>> {
+        match self.kind {
+            GateKind::Displace { .. } => identity(2),
+            GateKind::PhaseShift { phi } => rotation(phi),
+            GateKind::Squeeze { r, angle } => squeeze_matrix(r, angle, false),
+            GateKind::BeamSplitter { theta, phi } => {
+                let outer = mode_b_phase(rotation(phi));
+                let inner = mode_b_phase(rotation(-phi));
+                matmul(&matmul(&outer, &beam_splitter_matrix(theta, false)), &inner)
+            }
+        }
+    }
+
+    /// `(∂S/∂θ, ∂d/∂θ)` on `modes` for parameter `key`, or `None` if `key` is not a gate
+    /// parameter
+    fn local_derivative(&self, key: &str) -> Option<(Vec<Vec<f64>>, Vec<f64>)> {
+        match (&self.kind, key) {
+            (GateKind::Displace { .. }, "q") => Some((zeros(2), vec![1.0, 0.0])),
+            (GateKind::Displace { .. }, "p") => Some((zeros(2), vec![0.0, 1.0])),
+            (GateKind::PhaseShift { phi }, "phase") => {
+                Some((rotation_derivative(*phi), vec![0.0; 2]))
+            }
+            (GateKind::Squeeze { r, angle }, "r") => {
+                Some((squeeze_matrix(*r, *angle, true), vec![0.0; 2]))
+            }
+            (GateKind::Squeeze { r, angle }, "angle") => {
+                // S = R(a/2)·D·R(−a/2)
+                let d = vec![vec![(-r).exp(), 0.0], vec![0.0, r.exp()]];
+                let left = matmul(
+                    &matmul(&rotation_derivative(angle / 2.0), &d),
+                    &rotation(-angle / 2.0),
+                );
+                let right = matmul(
+                    &matmul(&rotation(angle / 2.0), &d),
+                    &rotation_derivative(-angle / 2.0),
+                );
+                Some((sub_scaled(&left, &right, 0.5), vec![0.0; 2]))
+            }
+            (GateKind::BeamSplitter { theta, phi }, key) if key == self.theta_key => {
+                let outer = mode_b_phase(rotation(*phi));
+                let inner = mode_b_phase(rotation(-phi));
+                let ds = matmul(&matmul(&outer, &beam_splitter_matrix(*theta, true)), &inner);
+                Some((ds, vec![0.0; 4]))
+            }
+            (GateKind::BeamSplitter { theta, phi }, "phi") => {
+                // S = P(φ)·B·P(−φ), with P the phase on the second mode
+                let bs = beam_splitter_matrix(*theta, false);
+                let left = matmul(
+                    &matmul(&mode_b_block(rotation_derivative(*phi), 0.0), &bs),
+                    &mode_b_phase(rotation(-phi)),
+                );
+                let right = matmul(
+                    &matmul(&mode_b_phase(rotation(*phi)), &bs),
+                    &mode_b_block(rotation_derivative(-phi), 0.0),
+                );
+                Some((sub_scaled(&left, &right, 1.0), vec![0.0; 4]))
+            }
+            _ => None,
+        }
+    }
+
+    fn quadratures(&self) -> Vec<usize> {
+        self.modes
+            .iter()
+            .flat_map(|&m| [2 * m, 2 * m + 1])
+            .collect()
+    }
+}
+
+/// Analytic gradient provider for Gaussian CV circuits, registered as `gaussian-adjoint`
+pub struct GaussianAdjointProvider {}
+
+impl GaussianAdjointProvider {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Mean photon number of the cost mode at the end of `graph`
+    pub fn cost(&self, graph: &ir::Graph) -> Result<f64> {
+        let (gates, num_modes, cost_mode) = circuit(graph)?;
+        let mut state = GaussianState::vacuum(num_modes);
+        for gate in &gates {
+            gate.apply(&mut state)?;
+        }
+        state.mean_photon_number(cost_mode)
+    }
+}
+
+impl Default for GaussianAdjointProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GradientProvider for GaussianAdjointProvider {
+    fn compute_gradients(
+        &self,
+        ir_json: &str,
+        params: &[String],
+        _noise: &NoiseModel,
+        _opts: &GradientOptions,
+    ) -> Result<GradientResult> {
+        let graph: ir::Graph = serde_json::from_str(ir_json)?;
+        let (gates, num_modes, cost_mode) = circuit(&graph)?;
+
+        // Requested parameters grouped by the gate they belong to
+        let mut wanted: HashMap<usize, Vec<(String, String)>> = HashMap::new();
+        for name in params {
+            let (node, key) = match name.split_once(':') {
+                Some((node, key)) => (graph.nodes.iter().position(|n| n.id == node), key),
+                None => (
+                    graph.nodes.iter().position(|n| n.params.contains_key(name)),
+                    name.as_str(),
+                ),
+            };
+            let gate = node
+                .and_then(|node| gates.iter().position(|g| g.node == node))
+                .filter(|&g| gates[g].local_derivative(key).is_some())
+                .ok_or_else(|| anyhow!("{} is not a Gaussian gate parameter", name))?;
+            wanted
+                .entry(gate)
+                .or_default()
+                .push((name.clone(), key.to_string()));
+        }
+
+        // Forward: the state entering each gate
+        let mut inputs = Vec::with_capacity(gates.len());
+        let mut state = GaussianState::vacuum(num_modes);
+        for gate in &gates {
+            inputs.push(state.clone());
+            gate.apply(&mut state)?;
+        }
+
+        // Cost adjoints for n = (V_qq + V_pp + μ_q² + μ_p²)/2 − ½
+        let dim = 2 * num_modes;
+        let (q, p) = (2 * cost_mode, 2 * cost_mode + 1);
+        let mut g_mu = vec![0.0; dim];
+        g_mu[q] = state.means[q];
+        g_mu[p] = state.means[p];
+        let mut g_v = zeros(dim);
+        g_v[q][q] = 0.5;
+        g_v[p][p] = 0.5;
+
+        // Backward
+        let mut gradients = HashMap::new();
+        for (index, gate) in gates.iter().enumerate().rev() {
+            let s = embed(&gate.local_matrix(), &gate.quadratures(), dim, true);
+            let input = &inputs[index];
+            for (name, key) in wanted.get(&index).into_iter().flatten() {
+                let (local_ds, local_dd) = gate
+                    .local_derivative(key)
+                    .expect("parameters were checked above");
+                let idx = gate.quadratures();
+                let ds = embed(&local_ds, &idx, dim, false);
+                let mut dd = vec![0.0; dim];
+                for (a, &i) in idx.iter().enumerate() {
+                    dd[i] = local_dd[a];
+                }
+
+                let d_mu: f64 = (0..dim)
+                    .map(|i| {
+                        let shift: f64 = (0..dim).map(|j| ds[i][j] * input.means[j]).sum();
+                        g_mu[i] * (shift + dd[i])
+                    })
+                    .sum();
+                let d_v = 2.0
+                    * trace_product(
+                        &matmul(&g_v, &ds),
+                        &matmul(&input.covariance, &transpose(&s)),
+                    );
+                gradients.insert(name.clone(), d_mu + d_v);
+            }
+
+            let s_t = transpose(&s);
+            g_mu = (0..dim)
+                .map(|i| (0..dim).map(|j| s_t[i][j] * g_mu[j]).sum())
+                .collect();
+            g_v = matmul(&matmul(&s_t, &g_v), &s);
+        }
+
+        let stds = params.iter().map(|p| (p.clone(), 0.0)).collect();
+        let mut provenance = HashMap::new();
+        provenance.insert("provider".to_string(), "gaussian-adjoint".to_string());
+        provenance.insert(
+            "cost".to_string(),
+            format!("mean_photon_number:mode_{}", cost_mode),
+        );
+        Ok(GradientResult {
+            gradients,
+            gradient_std: Some(stds),
+            provenance,
+        })
+    }
+
+    fn supports_adjoint(&self) -> bool {
+        true
+    }
+
+    fn evaluate_cost(&self, ir_json: &str) -> Result<f64> {
+        self.cost(&serde_json::from_str(ir_json)?)
+    }
+}
+
+/// Gates of `graph` in node order, the mode count and the cost mode
+fn circuit(graph: &ir::Graph) -> Result<(Vec<Gate>, usize, usize)> {
+    let gates: Vec<Gate> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, node)| Gate::from_node(i, node))
+        .collect();
+    let metadata = |key: &str| -> Result<Option<usize>> {
+        graph
+            .metadata
+            .get(key)
+            .map(|v| {
+                v.parse::<usize>()
+                    .map_err(|e| anyhow!("metadata {}: {}", key, e))
+            })
+            .transpose()
+    };
+    let cost_mode = metadata("cost_mode")?.unwrap_or(0);
+    let touched = gates
+        .iter()
+        .flat_map(|g| g.modes.iter().copied())
+        .chain([cost_mode])
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let num_modes = metadata("num_modes")?.unwrap_or(touched);
+    if num_modes < touched {
+        return Err(anyhow!(
+            "circuit touches mode {} but num_modes is {}",
+            touched - 1,
+            num_modes
+        ));
+    }
+    if let Some(gate) = gates
+        .iter()
+        .find(|g| g.modes.len() == 2 && g.modes[0] == g.modes[1])
+    {
+        return Err(anyhow!(
+            "node {} couples mode {} to itself",
+            graph.nodes[gate.node].id,
+            gate.modes[0]
+        ));
+    }
+    Ok((gates, num_modes, cost_mode))
+}
+
+/// `local` on quadratures `idx` of a `dim`-dimensional matrix, identity elsewhere when
+/// `with_identity`
+fn embed(local: &[Vec<f64>], idx: &[usize], dim: usize, with_identity: bool) -> Vec<Vec<f64>> {
+    let mut full = if with_identity {
+        identity(dim)
+    } else {
+        zeros(dim)
+    };
+    for (a, &i) in idx.iter().enumerate() {
+        for (b, &j) in idx.iter().enumerate() {
+            full[i][j] = local[a][b];
+        }
+    }
+    full
+}
+
+fn trace_product(a: &[Vec<f64>], b: &[Vec<f64>]) -> f64 {
+    (0..a.len())
+        .map(|i| (0..b.len()).map(|k| a[i][k] * b[k][i]).sum::<f64>())
+        .sum()
+}
+
+fn transpose(m: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    (0..m[0].len())
+        .map(|j| m.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+fn identity(dim: usize) -> Vec<Vec<f64>> {
+    let mut m = zeros(dim);
+    for (i, row) in m.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    m
+}
+
+fn zeros(dim: usize) -> Vec<Vec<f64>> {
+    vec![vec![0.0; dim]; dim]
+}
+
+/// `scale·(left − right)`
+fn sub_scaled(left: &[Vec<f64>], right: &[Vec<f64>], scale: f64) -> Vec<Vec<f64>> {
+    left.iter()
+        .zip(right)
+        .map(|(l, r)| l.iter().zip(r).map(|(a, b)| scale * (a - b)).collect())
+        .collect()
+}
+
+fn rotation(phi: f64) -> Vec<Vec<f64>> {
+    let (c, s) = (phi.cos(), phi.sin());
+    vec![vec![c, -s], vec![s, c]]
+}
+
+fn rotation_derivative(phi: f64) -> Vec<Vec<f64>> {
+    let (c, s) = (phi.cos(), phi.sin());
+    vec![vec![-s, -c], vec![c, -s]]
+}
+
+/// `R(a/2)·diag(e^{−r}, e^{r})·R(−a/2)`, or its derivative in `r`
+fn squeeze_matrix(r: f64, angle: f64, d_r: bool) -> Vec<Vec<f64>> {
+    let sign = if d_r { -1.0 } else { 1.0 };
+    let d = vec![vec![sign * (-r).exp(), 0.0], vec![0.0, r.exp()]];
+    matmul(&matmul(&rotation(angle / 2.0), &d), &rotation(-angle / 2.0))
+}
+
+/// Real beam splitter on two modes, or its derivative in `theta`
+fn beam_splitter_matrix(theta: f64, d_theta: bool) -> Vec<Vec<f64>> {
+    let (c, s) = if d_theta {
+        (-theta.sin(), theta.cos())
+    } else {
+        (theta.cos(), theta.sin())
+    };
+    vec![
+        vec![c, 0.0, -s, 0.0],
+        vec![0.0, c, 0.0, -s],
+        vec![s, 0.0, c, 0.0],
+        vec![0.0, s, 0.0, c],
+    ]
+}
+
+/// Two-mode matrix acting as `block` on the second mode only
+fn mode_b_phase(block: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    mode_b_block(block, 1.0)
+}
+
+/// `diag(first·I, block)` on two modes
+fn mode_b_block(block: Vec<Vec<f64>>, first: f64) -> Vec<Vec<f64>> {
+    let mut m = identity(4);
+    m[0][0] = first;
+    m[1][1] = first;
+    for a in 0..2 {
+        for b in 0..2 {
+            m[2 + a][2 + b] = block[a][b];
+        }
+    }
+    m
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn opts() -> GradientOptions {
+        GradientOptions {
+            strategy: "adjoint".to_string(),
+            seed: None,
+            samples: None,
+        }
+    }
+
+    fn noise() -> NoiseModel {
+        NoiseModel {
+            shot_noise_std: None,
+            thermal_noise_std: None,
+            phase_noise_std: None,
+            loss_variation: None,
+            metadata: None,
+        }
+    }
+
+    /// Squeezed and displaced inputs into a three-mode mesh
+    fn mesh() -> ir::Graph {
+        serde_json::from_value(json!({
+            "nodes": [
+                {"id": "sq0", "type": "SQUEEZING", "params": {"mode_id": 0.0, "r": 0.4, "angle": 0.3}},
+                {"id": "d1", "type": "DISPLACEMENT", "params": {"mode_id": 1.0, "q": 0.7, "p": -0.2}},
+                {"id": "sq2", "type": "SQUEEZING", "params": {"mode_id": 2.0, "r": 0.25, "angle": -0.5}},
+                {"id": "bs01", "type": "BS", "params": {"mode1": 0.0, "mode2": 1.0, "theta": 0.6, "phi": 0.4}},
+                {"id": "ps1", "type": "PS", "params": {"mode_id": 1.0, "phase": 0.9}},
+                {"id": "mzi12", "type": "MZI", "params": {"mode1": 1.0, "mode2": 2.0, "phase": 0.35, "phi": -0.7}},
+                {"id": "bs01b", "type": "BS", "params": {"mode1": 0.0, "mode2": 1.0, "theta": 1.1, "phi": 0.2}},
+                {"id": "det", "type": "DETECTOR", "params": {}}
+            ],
+            "metadata": {"cost_mode": "1"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_adjoint_matches_finite_differences() {
+        let graph = mesh();
+        let params: Vec<String> = [
+            "sq0:r",
+            "sq0:angle",
+            "d1:q",
+            "d1:p",
+            "sq2:r",
+            "sq2:angle",
+            "bs01:theta",
+            "bs01:phi",
+            "ps1:phase",
+            "mzi12:phase",
+            "mzi12:phi",
+            "bs01b:theta",
+            "bs01b:phi",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let provider = GaussianAdjointProvider::new();
+        let res = provider
+            .compute_gradients(
+                &serde_json::to_string(&graph).unwrap(),
+                &params,
+                &noise(),
+                &opts(),
+            )
+            .unwrap();
+        assert_eq!(res.provenance["provider"], "gaussian-adjoint");
+
+        let h = 1e-6;
+        for name in &params {
+            let (node, key) = name.split_once(':').unwrap();
+            let i = graph.nodes.iter().position(|n| n.id == node).unwrap();
+            let mut shifted = graph.clone();
+            *shifted.nodes[i].params.get_mut(key).unwrap() += h;
+            let plus = provider.cost(&shifted).unwrap();
+            *shifted.nodes[i].params.get_mut(key).unwrap() -= 2.0 * h;
+            let minus = provider.cost(&shifted).unwrap();
+            let fd = (plus - minus) / (2.0 * h);
+            assert!(
+                (res.gradients[name] - fd).abs() < 1e-6,
+                "{}: adjoint {} vs fd {}",
+                name,
+                res.gradients[name],
+                fd
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_non_gate_parameters() {
+        let ir = serde_json::to_string(&mesh()).unwrap();
+        let provider = GaussianAdjointProvider::new();
+        for name in ["det:gain", "ps1:mode_id", "nowhere:phase"] {
+            let err = provider
+                .compute_gradients(&ir, &[name.to_string()], &noise(), &opts())
+                .unwrap_err();
+            assert!(err.to_string().contains(name), "{}", err);
+        }
+    }
+}
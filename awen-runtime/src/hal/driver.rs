@@ -0,0 +1,272 @@
+//! Vendor driver plugin interface.
+//!
+//! Instrument vendors ship a driver as a `cdylib` exporting two symbols, normally generated with
+//! [`declare_driver!`](crate::declare_driver):
+//!
+//! - `AWEN_DRIVER_ABI_VERSION: u32` — must equal [`DRIVER_ABI_VERSION`]; checked before anything
+//!   else in the library is touched.
+//! - `AWEN_DRIVER_DECLARATION: DriverDeclaration` — driver name, version and a factory returning
+//!   a boxed [`Device`].
+//!
+//! The declaration uses Rust types, so drivers must be built with the same toolchain and
+//! awen-runtime ABI version as the host. [`DriverRegistry`] loads such libraries (individually or
+//! by scanning a directory) alongside factories registered in-process, and creates devices by
+//! driver name. Loading libraries needs the `hal-drivers` feature; in-process factories do not.
+
+use super::{Device, HalError};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+#[cfg(feature = "hal-drivers")]
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Version of the driver ABI implemented by this runtime. Bump on any change to
+/// [`DriverDeclaration`], [`DeviceFactory`] or the [`Device`] trait's method set.
+pub const DRIVER_ABI_VERSION: u32 = 2;
+
+#[cfg(feature = "hal-drivers")]
+const ABI_VERSION_SYMBOL: &[u8] = b"AWEN_DRIVER_ABI_VERSION\0";
+#[cfg(feature = "hal-drivers")]
+const DECLARATION_SYMBOL: &[u8] = b"AWEN_DRIVER_DECLARATION\0";
+
+/// Device handle produced by a driver factory.
+pub type DynDevice = Box<dyn Device + Send + Sync>;
+
+/// Builds a device from driver-specific string configuration (address, serial port, ...).
+pub type DeviceFactory = fn(&HashMap<String, String>) -> Result<DynDevice, HalError>;
+
+/// Static description exported by a driver library.
+pub struct DriverDeclaration {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub factory: DeviceFactory,
+}
+
+/// Export the driver symbols from a vendor `cdylib`.
+///
+/// ```ignore
+/// awen_runtime::declare_driver!("acme_dac", "1.2.0", make_acme_dac);
+/// ```
+#[macro_export]
+macro_rules! declare_driver {
+    ($name:expr, $version:expr, $factory:path) => {
+        #[no_mangle]
+        pub static AWEN_DRIVER_ABI_VERSION: u32 = $crate::hal::driver::DRIVER_ABI_VERSION;
+
+        #[no_mangle]
+        pub static AWEN_DRIVER_DECLARATION: $crate::hal::driver::DriverDeclaration =
+            $crate::hal::driver::DriverDeclaration {
+                name: $name,
+                version: $version,
+                factory: $factory,
+            };
+    };
+}
+
+/// Where a registered driver came from, for provenance and diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriverSource {
+    Builtin,
+    Library(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct DriverInfo {
+    pub name: String,
+    pub version: String,
+    pub source: DriverSource,
+}
+
+struct RegisteredDriver {
+    info: DriverInfo,
+    factory: DeviceFactory,
+}
+
+/// Registry of device drivers, keyed by driver name.
+#[derive(Default)]
+pub struct DriverRegistry {
+    drivers: HashMap<String, RegisteredDriver>,
+    // Factories loaded from libraries point into these; they must outlive every entry above.
+    #[cfg(feature = "hal-drivers")]
+    libraries: Vec<libloading::Library>,
+}
+
+impl DriverRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an in-process driver factory.
+    pub fn register(&mut self, name: &str, version: &str, factory: DeviceFactory) -> Result<()> {
+        self.insert(
+            DriverInfo {
+                name: name.to_string(),
+                version: version.to_string(),
+                source: DriverSource::Builtin,
+            },
+            factory,
+        )
+    }
+
+    fn insert(&mut self, info: DriverInfo, factory: DeviceFactory) -> Result<()> {
+        if self.drivers.contains_key(&info.name) {
+            return Err(anyhow!("Driver {} already registered", info.name));
+        }
+        self.drivers
+            .insert(info.name.clone(), RegisteredDriver { info, factory });
+        Ok(())
+    }
+
+    /// Load a driver `cdylib`, verifying its ABI version before reading the declaration.
+    #[cfg(feature = "hal-drivers")]
+    pub fn load_library(&mut self, path: &Path) -> Result<DriverInfo> {
+        // SAFETY: loading a library runs its initializers; driver libraries are trusted code
+        // installed by the lab operator. Symbols are only read after the ABI version matches.
+        let library = unsafe { libloading::Library::new(path) }
+            .map_err(|e| anyhow!("failed to load driver {}: {}", path.display(), e))?;
+
+        let abi = unsafe { library.get::<*const u32>(ABI_VERSION_SYMBOL) }
+            .map_err(|e| anyhow!("{} is not an AWEN driver: {}", path.display(), e))?;
+        // SAFETY: the symbol is a `u32` static exported by `declare_driver!`.
+        let abi_version = unsafe { **abi };
+        check_abi_version(abi_version)?;
+
+        let decl = unsafe { library.get::<*const DriverDeclaration>(DECLARATION_SYMBOL) }
+            .map_err(|e| anyhow!("{} has no driver declaration: {}", path.display(), e))?;
+        // SAFETY: ABI version matched, so the static has the layout of our `DriverDeclaration`.
+        let decl: &DriverDeclaration = unsafe { &**decl };
+
+        let info = DriverInfo {
+            name: decl.name.to_string(),
+            version: decl.version.to_string(),
+            source: DriverSource::Library(path.to_path_buf()),
+        };
+        let factory = decl.factory;
+        self.insert(info.clone(), factory)?;
+        self.libraries.push(library);
+        Ok(info)
+    }
+
+    /// Load every shared library in `dir`. Libraries that fail to load or are not AWEN drivers
+    /// are reported in the returned error list rather than aborting discovery.
+    #[cfg(feature = "hal-drivers")]
+    pub fn discover(&mut self, dir: &Path) -> (Vec<DriverInfo>, Vec<(PathBuf, String)>) {
+        let mut loaded = Vec::new();
+        let mut failed = Vec::new();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return (loaded, failed),
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| is_shared_library(p))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match self.load_library(&path) {
+                Ok(info) => loaded.push(info),
+                Err(e) => failed.push((path, e.to_string())),
+            }
+        }
+        (loaded, failed)
+    }
+
+    /// Instantiate a device from the named driver.
+    pub fn create(&self, name: &str, config: &HashMap<String, String>) -> Result<DynDevice> {
+        let driver = self
+            .drivers
+            .get(name)
+            .ok_or_else(|| anyhow!("Driver {} not found", name))?;
+        (driver.factory)(config).map_err(|e| anyhow!("driver {}: {}", name, e))
+    }
+
+    pub fn list_drivers(&self) -> Vec<DriverInfo> {
+        let mut infos: Vec<DriverInfo> = self.drivers.values().map(|d| d.info.clone()).collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+}
+
+#[cfg(feature = "hal-drivers")]
+fn check_abi_version(found: u32) -> Result<()> {
+    if found != DRIVER_ABI_VERSION {
+        return Err(anyhow!(
+            "driver ABI version {} does not match runtime ABI version {}",
+            found,
+            DRIVER_ABI_VERSION
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "hal-drivers")]
+fn is_shared_library(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("so") | Some("dylib") | Some("dll")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::Capability;
+
+    struct NullDevice(String);
+
+    impl Device for NullDevice {
+        fn id(&self) -> String {
+            self.0.clone()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+    }
+
+    fn make_null(config: &HashMap<String, String>) -> Result<DynDevice, HalError> {
+        let addr = config
+            .get("address")
+            .ok_or_else(|| "missing address".to_string())?;
+        Ok(Box::new(NullDevice(format!("null@{}", addr))))
+    }
+
+    #[test]
+    fn test_register_and_create_builtin_driver() {
+        let mut reg = DriverRegistry::new();
+        reg.register("null", "0.1.0", make_null).unwrap();
+        assert!(reg.register("null", "0.2.0", make_null).is_err());
+
+        let mut config = HashMap::new();
+        config.insert("address".to_string(), "10.0.0.5".to_string());
+        let dev = reg.create("null", &config).unwrap();
+        assert_eq!(dev.id(), "null@10.0.0.5");
+
+        assert!(reg.create("null", &HashMap::new()).is_err());
+        assert!(reg.create("missing", &config).is_err());
+        assert_eq!(reg.list_drivers()[0].source, DriverSource::Builtin);
+    }
+
+    #[cfg(feature = "hal-drivers")]
+    #[test]
+    fn test_abi_version_mismatch_rejected() {
+        assert!(check_abi_version(DRIVER_ABI_VERSION).is_ok());
+        assert!(check_abi_version(DRIVER_ABI_VERSION + 1).is_err());
+    }
+
+    #[cfg(feature = "hal-drivers")]
+    #[test]
+    fn test_discover_reports_invalid_libraries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("not_a_driver.so"), b"garbage").unwrap();
+        std::fs::write(dir.path().join("readme.txt"), b"ignored").unwrap();
+
+        let mut reg = DriverRegistry::new();
+        let (loaded, failed) = reg.discover(dir.path());
+        assert!(loaded.is_empty());
+        assert_eq!(failed.len(), 1);
+        assert!(reg.list_drivers().is_empty());
+
+        let (loaded, failed) = reg.discover(&dir.path().join("missing"));
+        assert!(loaded.is_empty() && failed.is_empty());
+    }
+}
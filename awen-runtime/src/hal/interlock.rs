@@ -0,0 +1,362 @@
+//! Safety interlock and emergency stop.
+//!
+//! Any component (drift detector, health monitor, operator CLI) can trip an [`Interlock`]. While
+//! tripped, every parameter write and calibration apply routed through an [`InterlockedDevice`]
+//! (or `Engine::apply_calibration`, which consults [`Interlock::global`]) is rejected. Devices
+//! armed on the interlock ramp their outputs to configured safe values as soon as it trips.
+//! Clearing the interlock requires an explicit reset naming the operator and a reason; trips and
+//! resets are kept in an audit log.
+
+use super::{
+    CalibrationResult, Capability, Device, HalError, LabDevice, SafetyLimits, SequenceReport,
+    Waveform,
+};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+static GLOBAL_INTERLOCK: Lazy<Interlock> = Lazy::new(Interlock::new);
+
+/// Why an interlock refused an action
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InterlockError {
+    /// Writes are blocked until an operator resets the interlock
+    #[error("interlock tripped by {actor}: {reason}")]
+    Tripped { actor: String, reason: String },
+    #[error("interlock reset requires an operator and a reason")]
+    ResetUnaudited,
+    #[error("interlock is not tripped")]
+    NotTripped,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InterlockAction {
+    Trip,
+    Reset,
+}
+
+/// One trip or reset, as recorded in the audit log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InterlockAuditEntry {
+    pub timestamp: String,
+    pub action: InterlockAction,
+    /// Component that tripped the interlock, or operator that reset it.
+    pub actor: String,
+    pub reason: String,
+}
+
+type TripHandler = Box<dyn Fn(&InterlockAuditEntry) + Send + Sync>;
+
+pub struct Interlock {
+    tripped: AtomicBool,
+    audit: Mutex<Vec<InterlockAuditEntry>>,
+    handlers: Mutex<Vec<TripHandler>>,
+}
+
+impl Interlock {
+    pub fn new() -> Self {
+        Self {
+            tripped: AtomicBool::new(false),
+            audit: Mutex::new(Vec::new()),
+            handlers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Process-wide interlock consulted by runtime entry points.
+    pub fn global() -> &'static Interlock {
+        &GLOBAL_INTERLOCK
+    }
+
+    /// Trip the interlock. Trip handlers run synchronously before this returns; tripping an
+    /// already-tripped interlock is recorded but does not re-run them.
+    pub fn trip(&self, source: &str, reason: &str) {
+        let entry = InterlockAuditEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            action: InterlockAction::Trip,
+            actor: source.to_string(),
+            reason: reason.to_string(),
+        };
+        let was_tripped = self.tripped.swap(true, Ordering::SeqCst);
+        if let Ok(mut audit) = self.audit.lock() {
+            audit.push(entry.clone());
+        }
+        if !was_tripped {
+            log::warn!("interlock tripped by {}: {}", source, reason);
+            if let Ok(handlers) = self.handlers.lock() {
+                for handler in handlers.iter() {
+                    handler(&entry);
+                }
+            }
+        }
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::SeqCst)
+    }
+
+    /// Ok while clear; otherwise an error naming the most recent trip.
+    pub fn check(&self) -> Result<(), InterlockError> {
+        if !self.is_tripped() {
+            return Ok(());
+        }
+        let last = self
+            .audit_log()
+            .into_iter()
+            .rev()
+            .find(|e| e.action == InterlockAction::Trip);
+        let (actor, reason) = last
+            .map(|e| (e.actor, e.reason))
+            .unwrap_or_else(|| ("unknown".to_string(), "no audit entry".to_string()));
+        Err(InterlockError::Tripped { actor, reason })
+    }
+
+    /// Clear the interlock. Both the operator and the reason are mandatory audit fields.
+    pub fn reset(&self, operator: &str, reason: &str) -> Result<(), InterlockError> {
+        if operator.trim().is_empty() || reason.trim().is_empty() {
+            return Err(InterlockError::ResetUnaudited);
+        }
+        if !self.is_tripped() {
+            return Err(InterlockError::NotTripped);
+        }
+        if let Ok(mut audit) = self.audit.lock() {
+            audit.push(InterlockAuditEntry {
+                timestamp: Utc::now().to_rfc3339(),
+                action: InterlockAction::Reset,
+                actor: operator.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+        self.tripped.store(false, Ordering::SeqCst);
+        log::info!("interlock reset by {}: {}", operator, reason);
+        Ok(())
+    }
+
+    /// Register a callback run when the interlock trips.
+    pub fn on_trip(&self, handler: impl Fn(&InterlockAuditEntry) + Send + Sync + 'static) {
+        if let Ok(mut handlers) = self.handlers.lock() {
+            handlers.push(Box::new(handler));
+        }
+    }
+
+    pub fn audit_log(&self) -> Vec<InterlockAuditEntry> {
+        self.audit.lock().map(|a| a.clone()).unwrap_or_default()
+    }
+}
+
+impl Default for Interlock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Device wrapper that enforces an interlock and knows how to bring outputs to a safe state.
+pub struct InterlockedDevice<D: Device> {
+    inner: D,
+    interlock: Arc<Interlock>,
+    /// Parameter -> value considered safe (e.g. heater power 0 mW).
+    safe_values: HashMap<String, f64>,
+    /// Number of linear steps used when ramping from the last commanded value to the safe value.
+    ramp_steps: usize,
+    last_commanded: Mutex<HashMap<String, f64>>,
+}
+
+impl<D: Device> InterlockedDevice<D> {
+    pub fn new(inner: D, interlock: Arc<Interlock>, safe_values: HashMap<String, f64>) -> Self {
+        Self {
+            inner,
+            interlock,
+            safe_values,
+            ramp_steps: 10,
+            last_commanded: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_ramp_steps(mut self, steps: usize) -> Self {
+        self.ramp_steps = steps.max(1);
+        self
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Ramp every parameter with a configured safe value from its last commanded value to the safe
+    /// value. Writes go straight to the wrapped device, bypassing the interlock check.
+    pub fn ramp_to_safe(&self) -> Result<(), HalError> {
+        let last = self.last_commanded.lock()?.clone();
+        let mut names: Vec<&String> = self.safe_values.keys().collect();
+        names.sort();
+        for name in names {
+            let target = self.safe_values[name];
+            let start = last.get(name).copied().unwrap_or(target);
+            for step in 1..=self.ramp_steps {
+                let frac = step as f64 / self.ramp_steps as f64;
+                self.inner
+                    .set_param(name, start + (target - start) * frac)?;
+            }
+        }
+        if let Ok(mut l) = self.last_commanded.lock() {
+            for (name, value) in &self.safe_values {
+                l.insert(name.clone(), *value);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<D: Device + Send + Sync + 'static> InterlockedDevice<D> {
+    /// Register this device on its interlock so outputs ramp to safe values as soon as it trips.
+    pub fn arm(self: &Arc<Self>) {
+        let weak: Weak<Self> = Arc::downgrade(self);
+        self.interlock.on_trip(move |entry| {
+            if let Some(dev) = weak.upgrade() {
+                if let Err(e) = dev.ramp_to_safe() {
+                    log::error!("safe ramp after trip by {} failed: {}", entry.actor, e);
+                }
+            }
+        });
+    }
+}
+
+impl<D: Device> Device for InterlockedDevice<D> {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn capabilities(&self) -> Vec<Capability> {
+        self.inner.capabilities()
+    }
+
+    fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+        self.interlock.check()?;
+        self.inner.set_param(name, value)?;
+        if let Ok(mut l) = self.last_commanded.lock() {
+            l.insert(name.to_string(), value);
+        }
+        Ok(())
+    }
+
+    fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+        self.inner.read_sensor(name)
+    }
+
+    fn upload_waveform(&self, channel: &str, waveform: Waveform) -> Result<(), HalError> {
+        self.interlock.check()?;
+        self.inner.upload_waveform(channel, waveform)
+    }
+
+    fn trigger_sequence(&self, id: &str) -> Result<SequenceReport, HalError> {
+        self.interlock.check()?;
+        self.inner.trigger_sequence(id)
+    }
+}
+
+impl<D: LabDevice> LabDevice for InterlockedDevice<D> {
+    fn apply_calibration(
+        &self,
+        mapping: &HashMap<String, f64>,
+        safety: Option<&SafetyLimits>,
+    ) -> Result<CalibrationResult, HalError> {
+        self.interlock.check()?;
+        self.inner.apply_calibration(mapping, safety)
+    }
+
+    fn health_report(&self) -> HashMap<String, String> {
+        let mut report = self.inner.health_report();
+        report.insert(
+            "interlock".to_string(),
+            if self.interlock.is_tripped() {
+                "tripped"
+            } else {
+                "clear"
+            }
+            .to_string(),
+        );
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::SimulatedDevice;
+
+    fn heater_safe_values() -> HashMap<String, f64> {
+        let mut safe = HashMap::new();
+        safe.insert("heater_0:power".to_string(), 0.0);
+        safe
+    }
+
+    #[test]
+    fn test_trip_blocks_writes_and_calibration() {
+        let interlock = Arc::new(Interlock::new());
+        let dev = InterlockedDevice::new(
+            SimulatedDevice::new(),
+            Arc::clone(&interlock),
+            heater_safe_values(),
+        );
+        dev.set_param("heater_0:power", 5.0).unwrap();
+
+        interlock.trip("drift_detector", "phase runaway");
+        let err = dev.set_param("heater_0:power", 6.0).unwrap_err();
+        assert_eq!(
+            err,
+            HalError::Interlock(InterlockError::Tripped {
+                actor: "drift_detector".to_string(),
+                reason: "phase runaway".to_string(),
+            })
+        );
+        assert!(dev.apply_calibration(&HashMap::new(), None).is_err());
+        assert_eq!(dev.health_report().get("interlock").unwrap(), "tripped");
+        // Sensors stay readable so operators can inspect the device.
+        assert!(dev.read_sensor("heater_0:power").is_ok());
+    }
+
+    #[test]
+    fn test_armed_device_ramps_to_safe_on_trip() {
+        let interlock = Arc::new(Interlock::new());
+        let dev = Arc::new(
+            InterlockedDevice::new(
+                crate::hal::replay::RecordingDevice::new(SimulatedDevice::new()),
+                Arc::clone(&interlock),
+                heater_safe_values(),
+            )
+            .with_ramp_steps(4),
+        );
+        dev.arm();
+        dev.set_param("heater_0:power", 20.0).unwrap();
+
+        interlock.trip("operator", "e-stop button");
+        assert_eq!(dev.read_sensor("heater_0:power").unwrap(), 0.0);
+        // One commanded write, four ramp steps, then the verification read.
+        let calls = dev.inner().script().calls;
+        assert_eq!(calls.len(), 6);
+    }
+
+    #[test]
+    fn test_reset_requires_operator_and_is_audited() {
+        let interlock = Interlock::new();
+        interlock.trip("health_monitor", "device faulty");
+        assert!(interlock.reset("", "fixed").is_err());
+        assert!(interlock.reset("alice", " ").is_err());
+        assert!(interlock.is_tripped());
+
+        interlock.reset("alice", "replaced fiber").unwrap();
+        assert!(!interlock.is_tripped());
+        assert!(interlock.check().is_ok());
+        assert_eq!(
+            interlock.reset("alice", "again"),
+            Err(InterlockError::NotTripped)
+        );
+
+        let audit = interlock.audit_log();
+        assert_eq!(audit.len(), 2);
+        assert_eq!(audit[0].action, InterlockAction::Trip);
+        assert_eq!(audit[1].action, InterlockAction::Reset);
+        assert_eq!(audit[1].actor, "alice");
+    }
+}
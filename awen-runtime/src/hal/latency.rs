@@ -0,0 +1,423 @@
+//! Measured HAL call latencies.
+//!
+//! [`TimedDevice`] (for [`Device`]/[`LabDevice`]) and [`TimedBackend`] (for
+//! `hal_v0::PhotonicBackend`) time every call to the wrapped device and record the wall-clock
+//! duration in nanoseconds as a [`MetricsCollector`] histogram named after the operation
+//! (`set_param_ns`, `read_sensor_ns`, `measure_homodyne_ns`, ...). [`LatencyStats`] summarises
+//! those histograms so feedback-loop feasibility checks such as
+//! `CoherenceWindow::check_can_schedule_feedback` can use measured rather than assumed latencies.
+
+use super::{
+    CalibrationResult, Capability, Device, HalError, LabDevice, SafetyLimits, SequenceReport,
+    Waveform,
+};
+use crate::hal_v0::{
+    DeviceCalibrationState, DeviceCapabilities, DeviceMetrics, DeviceType, DirectDetectionConfig,
+    DirectDetectionResult, FaultDetectionThresholds, HealthStatus, HeterodyneConfig,
+    HeterodyneResult, HomodyneConfig, HomodyneResult, PhotonicBackend,
+};
+use crate::observability::MetricsCollector;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Histogram names, one per instrumented operation.
+pub const SET_PARAM_NS: &str = "set_param_ns";
+pub const READ_SENSOR_NS: &str = "read_sensor_ns";
+pub const UPLOAD_WAVEFORM_NS: &str = "upload_waveform_ns";
+pub const TRIGGER_SEQUENCE_NS: &str = "trigger_sequence_ns";
+pub const APPLY_CALIBRATION_NS: &str = "apply_calibration_ns";
+pub const SET_PHASE_SHIFTER_NS: &str = "set_phase_shifter_ns";
+pub const SET_COUPLER_SPLIT_NS: &str = "set_coupler_split_ns";
+pub const MEASURE_HOMODYNE_NS: &str = "measure_homodyne_ns";
+pub const MEASURE_HETERODYNE_NS: &str = "measure_heterodyne_ns";
+pub const MEASURE_DIRECT_NS: &str = "measure_direct_ns";
+
+/// Every histogram written by the timed wrappers.
+pub const ALL_OPERATIONS: &[&str] = &[
+    SET_PARAM_NS,
+    READ_SENSOR_NS,
+    UPLOAD_WAVEFORM_NS,
+    TRIGGER_SEQUENCE_NS,
+    APPLY_CALIBRATION_NS,
+    SET_PHASE_SHIFTER_NS,
+    SET_COUPLER_SPLIT_NS,
+    MEASURE_HOMODYNE_NS,
+    MEASURE_HETERODYNE_NS,
+    MEASURE_DIRECT_NS,
+];
+
+fn record(metrics: &MetricsCollector, name: &str, device_id: String, start: Instant, ok: bool) {
+    let mut attrs = HashMap::new();
+    attrs.insert("device_id".to_string(), device_id);
+    attrs.insert("ok".to_string(), ok.to_string());
+    metrics.histogram(name, start.elapsed().as_nanos() as f64, "ns", attrs);
+}
+
+/// Summary of one latency histogram.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LatencyStats {
+    pub operation: String,
+    pub count: usize,
+    pub min_ns: f64,
+    pub mean_ns: f64,
+    pub p50_ns: f64,
+    pub p99_ns: f64,
+    pub max_ns: f64,
+}
+
+impl LatencyStats {
+    /// Summarise histogram `operation`; `None` if nothing has been recorded for it.
+    pub fn from_metrics(metrics: &MetricsCollector, operation: &str) -> Option<Self> {
+        let mut samples = metrics.histogram_values(operation);
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let count = samples.len();
+        Some(Self {
+            operation: operation.to_string(),
+            count,
+            min_ns: samples[0],
+            mean_ns: samples.iter().sum::<f64>() / count as f64,
+            p50_ns: quantile_sorted(&samples, 0.5),
+            p99_ns: quantile_sorted(&samples, 0.99),
+            max_ns: samples[count - 1],
+        })
+    }
+
+    /// Summaries for every operation in [`ALL_OPERATIONS`] that has samples.
+    pub fn all(metrics: &MetricsCollector) -> Vec<Self> {
+        ALL_OPERATIONS
+            .iter()
+            .filter_map(|op| Self::from_metrics(metrics, op))
+            .collect()
+    }
+
+    /// Latency at quantile `q` (0..=1) of histogram `operation`.
+    pub fn quantile(metrics: &MetricsCollector, operation: &str, q: f64) -> Option<f64> {
+        let mut samples = metrics.histogram_values(operation);
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        Some(quantile_sorted(&samples, q))
+    }
+}
+
+/// Nearest-rank quantile of an ascending, non-empty slice.
+fn quantile_sorted(sorted: &[f64], q: f64) -> f64 {
+    let rank = (q.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Measured latency of one feedback iteration: the `q` quantile of each listed operation, summed
+/// (e.g. `[MEASURE_HOMODYNE_NS, SET_PHASE_SHIFTER_NS]` for measure-then-correct). Returns `None`
+/// if any operation has no samples yet, so callers never mistake "unmeasured" for "free".
+pub fn feedback_latency_ns(metrics: &MetricsCollector, operations: &[&str], q: f64) -> Option<u64> {
+    operations
+        .iter()
+        .map(|op| LatencyStats::quantile(metrics, op, q))
+        .sum::<Option<f64>>()
+        .map(|ns| ns.ceil() as u64)
+}
+
+/// [`Device`] wrapper recording the duration of every call into a [`MetricsCollector`].
+pub struct TimedDevice<D> {
+    inner: D,
+    metrics: MetricsCollector,
+}
+
+impl<D> TimedDevice<D> {
+    pub fn new(inner: D, metrics: MetricsCollector) -> Self {
+        Self { inner, metrics }
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: Device> Device for TimedDevice<D> {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn capabilities(&self) -> Vec<Capability> {
+        self.inner.capabilities()
+    }
+
+    fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+        let start = Instant::now();
+        let result = self.inner.set_param(name, value);
+        record(
+            &self.metrics,
+            SET_PARAM_NS,
+            self.id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+        let start = Instant::now();
+        let result = self.inner.read_sensor(name);
+        record(
+            &self.metrics,
+            READ_SENSOR_NS,
+            self.id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn upload_waveform(&self, channel: &str, waveform: Waveform) -> Result<(), HalError> {
+        let start = Instant::now();
+        let result = self.inner.upload_waveform(channel, waveform);
+        record(
+            &self.metrics,
+            UPLOAD_WAVEFORM_NS,
+            self.id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn trigger_sequence(&self, id: &str) -> Result<SequenceReport, HalError> {
+        let start = Instant::now();
+        let result = self.inner.trigger_sequence(id);
+        record(
+            &self.metrics,
+            TRIGGER_SEQUENCE_NS,
+            self.id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+}
+
+impl<D: LabDevice> LabDevice for TimedDevice<D> {
+    fn apply_calibration(
+        &self,
+        mapping: &HashMap<String, f64>,
+        safety: Option<&SafetyLimits>,
+    ) -> Result<CalibrationResult, HalError> {
+        let start = Instant::now();
+        let result = self.inner.apply_calibration(mapping, safety);
+        record(
+            &self.metrics,
+            APPLY_CALIBRATION_NS,
+            self.id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn health_report(&self) -> HashMap<String, String> {
+        self.inner.health_report()
+    }
+}
+
+/// [`PhotonicBackend`] wrapper recording the duration of every control and measurement call.
+pub struct TimedBackend<B> {
+    inner: B,
+    metrics: MetricsCollector,
+}
+
+impl<B> TimedBackend<B> {
+    pub fn new(inner: B, metrics: MetricsCollector) -> Self {
+        Self { inner, metrics }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<B: PhotonicBackend> PhotonicBackend for TimedBackend<B> {
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        self.inner.device_type()
+    }
+
+    fn device_id(&self) -> String {
+        self.inner.device_id()
+    }
+
+    fn set_phase_shifter(&mut self, index: usize, phase_radians: f64) -> anyhow::Result<()> {
+        let start = Instant::now();
+        let result = self.inner.set_phase_shifter(index, phase_radians);
+        record(
+            &self.metrics,
+            SET_PHASE_SHIFTER_NS,
+            self.device_id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn set_coupler_split(&mut self, index: usize, ratio: f64) -> anyhow::Result<()> {
+        let start = Instant::now();
+        let result = self.inner.set_coupler_split(index, ratio);
+        record(
+            &self.metrics,
+            SET_COUPLER_SPLIT_NS,
+            self.device_id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn measure_homodyne(&mut self, config: &HomodyneConfig) -> anyhow::Result<HomodyneResult> {
+        let start = Instant::now();
+        let result = self.inner.measure_homodyne(config);
+        record(
+            &self.metrics,
+            MEASURE_HOMODYNE_NS,
+            self.device_id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn measure_heterodyne(
+        &mut self,
+        config: &HeterodyneConfig,
+    ) -> anyhow::Result<HeterodyneResult> {
+        let start = Instant::now();
+        let result = self.inner.measure_heterodyne(config);
+        record(
+            &self.metrics,
+            MEASURE_HETERODYNE_NS,
+            self.device_id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn measure_direct(
+        &mut self,
+        config: &DirectDetectionConfig,
+    ) -> anyhow::Result<DirectDetectionResult> {
+        let start = Instant::now();
+        let result = self.inner.measure_direct(config);
+        record(
+            &self.metrics,
+            MEASURE_DIRECT_NS,
+            self.device_id(),
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn load_calibration(&mut self, state: DeviceCalibrationState) -> anyhow::Result<()> {
+        self.inner.load_calibration(state)
+    }
+
+    fn get_calibration(&self) -> anyhow::Result<DeviceCalibrationState> {
+        self.inner.get_calibration()
+    }
+
+    fn get_metrics(&self) -> DeviceMetrics {
+        self.inner.get_metrics()
+    }
+
+    fn fault_detection_thresholds(&self) -> FaultDetectionThresholds {
+        self.inner.fault_detection_thresholds()
+    }
+
+    fn initialize(&mut self) -> anyhow::Result<()> {
+        self.inner.initialize()
+    }
+
+    fn shutdown(&mut self) -> anyhow::Result<()> {
+        self.inner.shutdown()
+    }
+
+    fn health_check(&mut self) -> anyhow::Result<HealthStatus> {
+        self.inner.health_check()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::SimulatedDevice;
+    use crate::hal_v0::SimulatorBackend;
+    use crate::observability::MetricType;
+
+    #[test]
+    fn test_device_calls_recorded_as_histograms() {
+        let metrics = MetricsCollector::new();
+        let dev = TimedDevice::new(SimulatedDevice::new(), metrics.clone());
+        dev.set_param("heater_0:power", 1.0).unwrap();
+        assert!(dev.set_param("heater_0:power", -1.0).is_err());
+        dev.read_sensors(&["mzi_0:phase", "mzi_1:phase"]).unwrap();
+
+        assert_eq!(metrics.histogram_values(SET_PARAM_NS).len(), 2);
+        assert_eq!(metrics.histogram_values(READ_SENSOR_NS).len(), 2);
+        let records = metrics.metrics();
+        assert!(records
+            .iter()
+            .all(|m| m.metric_type == MetricType::Histogram && m.unit == "ns"));
+        assert_eq!(records[1].attributes.get("ok").unwrap(), "false");
+        assert_eq!(records[0].attributes.get("device_id").unwrap(), "simulated");
+    }
+
+    #[test]
+    fn test_backend_measurements_recorded() {
+        let metrics = MetricsCollector::new();
+        let mut backend = TimedBackend::new(SimulatorBackend::new(), metrics.clone());
+        backend.set_phase_shifter(0, 0.3).unwrap();
+        backend
+            .measure_homodyne(&HomodyneConfig {
+                lo_phase: 0.0,
+                lo_power_mw: 1.0,
+                vna_frequency_ghz: 0.0,
+                integration_time_us: 1.0,
+                bandwidth_mhz: 10.0,
+            })
+            .unwrap();
+
+        let stats = LatencyStats::from_metrics(&metrics, MEASURE_HOMODYNE_NS).unwrap();
+        assert_eq!(stats.count, 1);
+        assert!(stats.min_ns <= stats.p50_ns && stats.p99_ns <= stats.max_ns);
+        assert_eq!(LatencyStats::all(&metrics).len(), 2);
+    }
+
+    #[test]
+    fn test_quantiles_and_feedback_latency() {
+        let metrics = MetricsCollector::new();
+        for ns in 1..=100 {
+            metrics.histogram(MEASURE_HOMODYNE_NS, ns as f64, "ns", HashMap::new());
+        }
+        metrics.histogram(SET_PARAM_NS, 40.0, "ns", HashMap::new());
+
+        let stats = LatencyStats::from_metrics(&metrics, MEASURE_HOMODYNE_NS).unwrap();
+        assert_eq!(stats.p50_ns, 50.0);
+        assert_eq!(stats.p99_ns, 99.0);
+        assert_eq!(stats.mean_ns, 50.5);
+
+        assert_eq!(
+            feedback_latency_ns(&metrics, &[MEASURE_HOMODYNE_NS, SET_PARAM_NS], 0.99),
+            Some(139)
+        );
+        assert_eq!(
+            feedback_latency_ns(&metrics, &[MEASURE_HOMODYNE_NS, SET_PHASE_SHIFTER_NS], 0.99),
+            None
+        );
+    }
+}
@@ -1,6 +1,78 @@
 // Hardware Abstraction Layer (v0.1)
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::ir::{Graph, Node};
+use crate::plugins::reference_sim::run_reference_simulator;
+
+pub mod driver;
+pub mod interlock;
+pub mod latency;
+pub mod replay;
+pub mod telemetry;
+pub mod thermal;
+
+use thermal::ThermalModel;
+
+/// Why a device call failed. Drivers with nothing more specific to say return
+/// [`HalError::Device`]; a `String` or `&str` converts into it.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum HalError {
+    /// The device has no parameter, sensor or channel by this name
+    #[error("{0}: no such channel on this device")]
+    UnknownChannel(String),
+    /// The value is outside what the channel accepts
+    #[error("{channel}: {reason}")]
+    InvalidValue { channel: String, reason: String },
+    /// The waveform cannot be played back at all
+    #[error("waveform '{sequence_id}': {reason}")]
+    InvalidWaveform { sequence_id: String, reason: String },
+    /// The device does not implement `operation` for `target`
+    #[error("{target}: device '{device}' does not support {operation}")]
+    Unsupported {
+        device: String,
+        operation: &'static str,
+        target: String,
+    },
+    /// No waveform was uploaded for the sequence
+    #[error("sequence '{0}': no waveforms uploaded")]
+    UnknownSequence(String),
+    #[error(transparent)]
+    Interlock(#[from] interlock::InterlockError),
+    /// A replayed call does not match the recording
+    #[error("replay divergence at call {call}: expected {expected}, got {got}")]
+    ReplayDivergence {
+        call: usize,
+        expected: String,
+        got: String,
+    },
+    #[error("replay exhausted after {0} calls")]
+    ReplayExhausted(usize),
+    /// A thread panicked while holding the device's state
+    #[error("device state poisoned: {0}")]
+    Poisoned(String),
+    #[error("{0}")]
+    Device(String),
+}
+
+impl From<String> for HalError {
+    fn from(message: String) -> Self {
+        HalError::Device(message)
+    }
+}
+
+impl From<&str> for HalError {
+    fn from(message: &str) -> Self {
+        HalError::Device(message.to_string())
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for HalError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        HalError::Poisoned(e.to_string())
+    }
+}
 
 /// Device capability categories. Backends declare which capabilities they provide.
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -17,6 +89,63 @@ pub struct Capability {
     pub metadata: Option<HashMap<String, String>>,
 }
 
+/// Capability/health metadata keys describing the exact hardware behind a device.
+pub const META_FIRMWARE_VERSION: &str = "firmware_version";
+pub const META_SERIAL_NUMBER: &str = "serial_number";
+pub const META_DRIVER_VERSION: &str = "driver_version";
+
+/// Hardware identity of a device, recorded into calibration and run provenance so results can be
+/// traced back to the exact instrument, firmware and driver that produced them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceProvenance {
+    pub device_id: String,
+    pub firmware_version: Option<String>,
+    pub serial_number: Option<String>,
+    pub driver_version: Option<String>,
+}
+
+impl DeviceProvenance {
+    /// Build provenance from capability or health-report metadata using the `META_*` keys.
+    pub fn from_metadata(device_id: &str, metadata: &HashMap<String, String>) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            firmware_version: metadata.get(META_FIRMWARE_VERSION).cloned(),
+            serial_number: metadata.get(META_SERIAL_NUMBER).cloned(),
+            driver_version: metadata.get(META_DRIVER_VERSION).cloned(),
+        }
+    }
+
+    /// Write the known fields into a metadata map using the `META_*` keys.
+    pub fn insert_into(&self, metadata: &mut HashMap<String, String>) {
+        let fields = [
+            (META_FIRMWARE_VERSION, &self.firmware_version),
+            (META_SERIAL_NUMBER, &self.serial_number),
+            (META_DRIVER_VERSION, &self.driver_version),
+        ];
+        for (key, value) in fields {
+            if let Some(v) = value {
+                metadata.insert(key.to_string(), v.clone());
+            }
+        }
+    }
+
+    /// Compact single-string form used for `CalibrationProvenance.hardware_revision`,
+    /// e.g. `simulated sn=SIM-0000 fw=sim-1.0 drv=0.1.0`.
+    pub fn hardware_revision(&self) -> String {
+        let mut parts = vec![self.device_id.clone()];
+        if let Some(sn) = &self.serial_number {
+            parts.push(format!("sn={}", sn));
+        }
+        if let Some(fw) = &self.firmware_version {
+            parts.push(format!("fw={}", fw));
+        }
+        if let Some(drv) = &self.driver_version {
+            parts.push(format!("drv={}", drv));
+        }
+        parts.join(" ")
+    }
+}
+
 /// Minimal device abstraction used by the runtime. Implementations may represent lab instruments
 /// or simulated devices. Device implementations must expose stable capability descriptors and a
 /// set of control primitives (set_param / read_sensor) used by calibration and control loops.
@@ -24,17 +153,67 @@ pub trait Device {
     fn id(&self) -> String;
     fn capabilities(&self) -> Vec<Capability>;
 
-    /// Set a named parameter on the device (e.g., `mzi_3:phase`) to a value. Returns an error string on failure.
-    fn set_param(&self, name: &str, value: f64) -> Result<(), String> {
+    /// Set a named parameter on the device (e.g., `mzi_3:phase`) to a value.
+    fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
         let _ = (name, value);
         Ok(())
     }
 
     /// Read a named sensor or observable (e.g., `detector_1:power`). Returns value or error.
-    fn read_sensor(&self, name: &str) -> Result<f64, String> {
+    fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
         let _ = name;
         Ok(0.0)
     }
+
+    /// Hardware identity of this device. The default implementation collects the `META_*` keys
+    /// from capability metadata; drivers that query the instrument directly should override it.
+    fn provenance(&self) -> DeviceProvenance {
+        let mut merged = HashMap::new();
+        for cap in self.capabilities() {
+            if let Some(meta) = cap.metadata {
+                merged.extend(meta);
+            }
+        }
+        DeviceProvenance::from_metadata(&self.id(), &merged)
+    }
+
+    /// Set several parameters in one call. The default implementation loops over `set_param` and
+    /// stops at the first failure; drivers for instruments with a native batch write (multi-channel
+    /// DACs, SCPI command lists) should override it.
+    fn set_params(&self, params: &[(&str, f64)]) -> Result<(), HalError> {
+        for (name, value) in params {
+            self.set_param(name, *value)?;
+        }
+        Ok(())
+    }
+
+    /// Read several sensors in one call, returning values in the order of `names`. The default
+    /// implementation loops over `read_sensor`.
+    fn read_sensors(&self, names: &[&str]) -> Result<Vec<f64>, HalError> {
+        names.iter().map(|name| self.read_sensor(name)).collect()
+    }
+
+    /// Program a sampled waveform onto a channel (e.g. `heater_2:power`) for later playback with
+    /// `trigger_sequence`. Uploading to a channel replaces any waveform previously stored there.
+    /// The default implementation reports that the device has no waveform memory.
+    fn upload_waveform(&self, channel: &str, waveform: Waveform) -> Result<(), HalError> {
+        let _ = waveform;
+        Err(HalError::Unsupported {
+            device: self.id(),
+            operation: "waveform upload",
+            target: channel.to_string(),
+        })
+    }
+
+    /// Play back every uploaded waveform whose `sequence_id` equals `id`, all channels starting
+    /// on the same trigger. The default implementation reports that sequences are unsupported.
+    fn trigger_sequence(&self, id: &str) -> Result<SequenceReport, HalError> {
+        Err(HalError::Unsupported {
+            device: self.id(),
+            operation: "triggered sequences",
+            target: format!("sequence '{}'", id),
+        })
+    }
 }
 
 /// Lab-specific device trait exposing safety-constrained calibration primitives.
@@ -44,7 +223,7 @@ pub trait LabDevice: Device {
         &self,
         mapping: &HashMap<String, f64>,
         safety: Option<&SafetyLimits>,
-    ) -> Result<CalibrationResult, String>;
+    ) -> Result<CalibrationResult, HalError>;
 
     /// Query device health and status metadata for observability and reproducibility.
     fn health_report(&self) -> HashMap<String, String>;
@@ -67,17 +246,126 @@ pub struct CalibrationResult {
     pub warnings: Vec<String>,
 }
 
+/// Uniformly sampled waveform for AWG and pulse-generator channels. Waveforms sharing a
+/// `sequence_id` form one pulse sequence and are started together by `trigger_sequence`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Waveform {
+    pub sequence_id: String,
+    pub sample_rate_hz: f64,
+    pub samples: Vec<f64>,
+}
+
+impl Waveform {
+    pub fn new(sequence_id: &str, sample_rate_hz: f64, samples: Vec<f64>) -> Self {
+        Self {
+            sequence_id: sequence_id.to_string(),
+            sample_rate_hz,
+            samples,
+        }
+    }
+
+    /// Piecewise-constant waveform built from `(value, sample_count)` segments.
+    pub fn from_segments(
+        sequence_id: &str,
+        sample_rate_hz: f64,
+        segments: &[(f64, usize)],
+    ) -> Self {
+        let samples = segments
+            .iter()
+            .flat_map(|(value, count)| std::iter::repeat_n(*value, *count))
+            .collect();
+        Self::new(sequence_id, sample_rate_hz, samples)
+    }
+
+    /// Playback duration in seconds.
+    pub fn duration_s(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate_hz
+    }
+
+    /// Value at sample `index`; the last sample is held once the waveform has finished.
+    pub fn sample(&self, index: usize) -> Option<f64> {
+        self.samples
+            .get(index)
+            .or_else(|| self.samples.last())
+            .copied()
+    }
+
+    /// Check the waveform can be played back at all.
+    pub fn validate(&self) -> Result<(), HalError> {
+        let invalid = |reason: String| HalError::InvalidWaveform {
+            sequence_id: self.sequence_id.clone(),
+            reason,
+        };
+        if !(self.sample_rate_hz.is_finite() && self.sample_rate_hz > 0.0) {
+            return Err(invalid(format!(
+                "sample rate must be positive, got {}",
+                self.sample_rate_hz
+            )));
+        }
+        if self.samples.is_empty() {
+            return Err(invalid("no samples".to_string()));
+        }
+        if let Some(bad) = self.samples.iter().find(|v| !v.is_finite()) {
+            return Err(invalid(format!("contains non-finite sample {}", bad)));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a triggered pulse sequence.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SequenceReport {
+    pub sequence_id: String,
+    /// Channels that took part in the sequence, sorted by name.
+    pub channels: Vec<String>,
+    /// Number of sample periods played (the length of the longest waveform).
+    pub samples_played: usize,
+    /// Per-sample acquisition, if the device captures one during playback; empty otherwise.
+    pub readout: Vec<f64>,
+}
+
 /// Default simulated device implementation used by the reference HAL.
 ///
+/// The device models a row of heater-tuned MZIs: `heater_<i>:power` (mW) is writable, and
+/// `heater_<i>:temperature`, `mzi_<i>:phase` and `mzi_<i>:transmission` are readable sensors
+/// derived from the [`ThermalModel`], including nearest-neighbour thermal crosstalk.
+///
+/// Waveforms may be uploaded to `heater_<i>:power` and `mzi_<i>:phase` (an additional phase
+/// offset on top of the thermal one). On trigger, each sample period updates the heaters and
+/// runs the MZI row through the reference simulator; the readout is the detected power after
+/// the chain.
+///
 /// Note: `SimulatedDevice` is intentionally crate-private to prevent external code from
 /// constructing it directly and bypassing runtime safety chokepoints. External users must
 /// go through runtime APIs (e.g., `Engine::apply_calibration`).
-pub(crate) struct SimulatedDevice;
+pub(crate) struct SimulatedDevice {
+    thermal: ThermalModel,
+    heater_mw: Mutex<Vec<f64>>,
+    waveforms: Mutex<HashMap<String, Waveform>>,
+}
+
+fn negative_heater_power(channel: &str) -> HalError {
+    HalError::InvalidValue {
+        channel: channel.to_string(),
+        reason: "heater power must be non-negative".to_string(),
+    }
+}
+
+/// Number of heater channels on the default simulated device.
+const SIMULATED_HEATER_CHANNELS: usize = 8;
+
 impl Device for SimulatedDevice {
     fn id(&self) -> String {
         "simulated".into()
     }
     fn capabilities(&self) -> Vec<Capability> {
+        let mut heater_meta = HashMap::new();
+        heater_meta.insert("channels".to_string(), self.channels().to_string());
+        heater_meta.insert(
+            "crosstalk_fraction".to_string(),
+            self.thermal.crosstalk_fraction.to_string(),
+        );
+        simulated_provenance().insert_into(&mut heater_meta);
         vec![
             Capability {
                 name: "mzi".into(),
@@ -94,17 +382,201 @@ impl Device for SimulatedDevice {
                 channel: ChannelType::Optical,
                 metadata: None,
             },
+            Capability {
+                name: "heater".into(),
+                channel: ChannelType::Thermal,
+                metadata: Some(heater_meta),
+            },
         ]
     }
+
+    fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+        match parse_channel_param(name) {
+            Some(("heater", idx, "power")) => {
+                if value < 0.0 {
+                    return Err(negative_heater_power(name));
+                }
+                let mut heaters = self.heater_mw.lock()?;
+                let slot = heaters
+                    .get_mut(idx)
+                    .ok_or_else(|| HalError::UnknownChannel(name.to_string()))?;
+                *slot = value;
+                Ok(())
+            }
+            // Other parameters are accepted and ignored, as before thermal modeling existed.
+            _ => Ok(()),
+        }
+    }
+
+    fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+        let (kind, idx, quantity) = match parse_channel_param(name) {
+            Some(parsed) => parsed,
+            None => return Ok(0.0),
+        };
+        let heaters = self.heater_mw.lock()?;
+        if idx >= heaters.len() {
+            return Err(HalError::UnknownChannel(name.to_string()));
+        }
+        match (kind, quantity) {
+            ("heater", "power") => Ok(heaters[idx]),
+            ("heater", "temperature") => Ok(self.thermal.temperatures(&heaters)[idx]),
+            ("mzi", "phase") => Ok(self.thermal.phase_shifts(&heaters)[idx]),
+            ("mzi", "transmission") => {
+                let phase = self.thermal.phase_shifts(&heaters)[idx];
+                Ok((phase / 2.0).cos().powi(2))
+            }
+            _ => Ok(0.0),
+        }
+    }
+
+    fn upload_waveform(&self, channel: &str, waveform: Waveform) -> Result<(), HalError> {
+        waveform.validate()?;
+        match parse_channel_param(channel) {
+            Some(("heater", idx, "power")) | Some(("mzi", idx, "phase"))
+                if idx < self.channels() => {}
+            _ => {
+                return Err(HalError::Unsupported {
+                    device: self.id(),
+                    operation: "waveform upload",
+                    target: channel.to_string(),
+                })
+            }
+        }
+        if channel.starts_with("heater") && waveform.samples.iter().any(|v| *v < 0.0) {
+            return Err(negative_heater_power(channel));
+        }
+        self.waveforms.lock()?.insert(channel.to_string(), waveform);
+        Ok(())
+    }
+
+    fn trigger_sequence(&self, id: &str) -> Result<SequenceReport, HalError> {
+        let mut waveforms: Vec<(String, Waveform)> = self
+            .waveforms
+            .lock()?
+            .iter()
+            .filter(|(_, w)| w.sequence_id == id)
+            .map(|(c, w)| (c.clone(), w.clone()))
+            .collect();
+        if waveforms.is_empty() {
+            return Err(HalError::UnknownSequence(id.to_string()));
+        }
+        waveforms.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let samples_played = waveforms
+            .iter()
+            .map(|(_, w)| w.samples.len())
+            .max()
+            .unwrap_or(0);
+        let mut readout = Vec::with_capacity(samples_played);
+        for step in 0..samples_played {
+            let mut phase_offsets = vec![0.0; self.channels()];
+            for (channel, waveform) in &waveforms {
+                let value = waveform.sample(step).unwrap_or(0.0);
+                match parse_channel_param(channel) {
+                    Some(("mzi", idx, "phase")) => phase_offsets[idx] = value,
+                    _ => self.set_param(channel, value)?,
+                }
+            }
+            let phases: Vec<f64> = {
+                let heaters = self.heater_mw.lock()?;
+                self.thermal
+                    .phase_shifts(&heaters)
+                    .into_iter()
+                    .zip(phase_offsets)
+                    .map(|(thermal, offset)| thermal + offset)
+                    .collect()
+            };
+            let sim = run_reference_simulator(&mzi_chain_graph(&phases), Some(step as u64))
+                .map_err(|e| format!("sequence '{}': {}", id, e))?;
+            let detected = sim
+                .node_results
+                .iter()
+                .rev()
+                .filter_map(|r| r.measurement.as_ref())
+                .find_map(|m| m.analog_value)
+                .unwrap_or(0.0);
+            readout.push(detected);
+        }
+
+        Ok(SequenceReport {
+            sequence_id: id.to_string(),
+            channels: waveforms.into_iter().map(|(c, _)| c).collect(),
+            samples_played,
+            readout,
+        })
+    }
 }
 
 impl SimulatedDevice {
     /// Convenience constructor (crate-private)
     pub(crate) fn new() -> Self {
-        Self {}
+        Self::with_thermal_model(ThermalModel::default())
+    }
+
+    pub(crate) fn with_thermal_model(thermal: ThermalModel) -> Self {
+        Self {
+            thermal,
+            heater_mw: Mutex::new(vec![0.0; SIMULATED_HEATER_CHANNELS]),
+            waveforms: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn channels(&self) -> usize {
+        self.heater_mw.lock().map(|h| h.len()).unwrap_or(0)
+    }
+}
+
+/// Fixed identity reported by the simulated device.
+fn simulated_provenance() -> DeviceProvenance {
+    DeviceProvenance {
+        device_id: "simulated".to_string(),
+        firmware_version: Some("sim-1.0".to_string()),
+        serial_number: Some("SIM-0000".to_string()),
+        driver_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+    }
+}
+
+/// Reference-simulator graph for a row of MZIs followed by a detector. Each MZI's bar-port
+/// transmission `cos²(φ/2)` is expressed as a LOSS node so the detected power matches the
+/// `mzi_<i>:transmission` sensors multiplied along the chain.
+fn mzi_chain_graph(phases: &[f64]) -> Graph {
+    let node = |id: String, node_type: &str, params: Vec<(&str, f64)>| Node {
+        id,
+        node_type: node_type.to_string(),
+        params: params
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+        measure_mode: None,
+        measurement: None,
+        conditional_branches: None,
+        feed_forward: None,
+    };
+    let mut nodes = Vec::with_capacity(phases.len() * 2 + 1);
+    for (i, phase) in phases.iter().enumerate() {
+        nodes.push(node(format!("mzi_{}", i), "MZI", vec![("phase", *phase)]));
+        let amplitude = (phase / 2.0).cos().abs();
+        nodes.push(node(
+            format!("mzi_{}_bar", i),
+            "LOSS",
+            vec![("loss", 1.0 - amplitude)],
+        ));
+    }
+    nodes.push(node("detector".to_string(), "DETECTOR", Vec::new()));
+    Graph {
+        nodes,
+        edges: Vec::new(),
+        metadata: HashMap::new(),
     }
 }
 
+/// Split a `kind_<index>:quantity` name (e.g. `heater_3:power`) into its parts.
+fn parse_channel_param(name: &str) -> Option<(&str, usize, &str)> {
+    let (channel, quantity) = name.split_once(':')?;
+    let (kind, idx) = channel.rsplit_once('_')?;
+    Some((kind, idx.parse().ok()?, quantity))
+}
+
 /// Examples and enforcement: external attempts to construct `SimulatedDevice` should fail.
 ///
 /// ```compile_fail
@@ -116,7 +588,7 @@ impl LabDevice for SimulatedDevice {
         &self,
         mapping: &HashMap<String, f64>,
         _safety: Option<&SafetyLimits>,
-    ) -> Result<CalibrationResult, String> {
+    ) -> Result<CalibrationResult, HalError> {
         // In simulation we apply safety limits if provided and echo back applied values.
         let mut applied = mapping.clone();
         let mut warnings: Vec<String> = Vec::new();
@@ -148,6 +620,200 @@ impl LabDevice for SimulatedDevice {
     fn health_report(&self) -> HashMap<String, String> {
         let mut m = HashMap::new();
         m.insert("status".into(), "simulated-ok".into());
+        self.provenance().insert_into(&mut m);
+        if let Ok(heaters) = self.heater_mw.lock() {
+            let peak = self
+                .thermal
+                .temperatures(&heaters)
+                .into_iter()
+                .fold(self.thermal.ambient_celsius, f64::max);
+            m.insert("peak_temperature_celsius".into(), format!("{:.3}", peak));
+        }
         m
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Device that records every single-parameter call so batch defaults can be observed.
+    struct CountingDevice {
+        writes: RefCell<Vec<(String, f64)>>,
+        reads: RefCell<Vec<String>>,
+    }
+
+    impl Device for CountingDevice {
+        fn id(&self) -> String {
+            "counting".into()
+        }
+        fn capabilities(&self) -> Vec<Capability> {
+            Vec::new()
+        }
+        fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+            if name == "locked" {
+                return Err("parameter locked".into());
+            }
+            self.writes.borrow_mut().push((name.to_string(), value));
+            Ok(())
+        }
+        fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+            self.reads.borrow_mut().push(name.to_string());
+            Ok(name.len() as f64)
+        }
+    }
+
+    fn counting() -> CountingDevice {
+        CountingDevice {
+            writes: RefCell::new(Vec::new()),
+            reads: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn test_set_params_default_loops_in_order() {
+        let dev = counting();
+        dev.set_params(&[("mzi_0:phase", 0.1), ("mzi_1:phase", 0.2)])
+            .unwrap();
+        let writes = dev.writes.borrow();
+        assert_eq!(writes.len(), 2);
+        assert_eq!(writes[0], ("mzi_0:phase".to_string(), 0.1));
+        assert_eq!(writes[1], ("mzi_1:phase".to_string(), 0.2));
+    }
+
+    #[test]
+    fn test_set_params_stops_at_first_error() {
+        let dev = counting();
+        let res = dev.set_params(&[("a", 1.0), ("locked", 2.0), ("b", 3.0)]);
+        assert!(res.is_err());
+        assert_eq!(dev.writes.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_simulated_device_exposes_thermal_channel() {
+        let dev = SimulatedDevice::new();
+        assert!(dev
+            .capabilities()
+            .iter()
+            .any(|c| matches!(c.channel, ChannelType::Thermal)));
+    }
+
+    #[test]
+    fn test_simulated_heater_shifts_phase_with_crosstalk() {
+        let dev = SimulatedDevice::new();
+        let power = dev.thermal.power_for_phase(std::f64::consts::PI);
+        dev.set_param("heater_2:power", power).unwrap();
+
+        let phase = dev.read_sensor("mzi_2:phase").unwrap();
+        assert!((phase - std::f64::consts::PI).abs() < 1e-9);
+        assert!(dev.read_sensor("mzi_2:transmission").unwrap() < 1e-9);
+
+        let neighbour = dev.read_sensor("mzi_3:phase").unwrap();
+        let far = dev.read_sensor("mzi_5:phase").unwrap();
+        assert!(neighbour > 0.0 && neighbour < phase);
+        assert_eq!(far, 0.0);
+        assert!(
+            dev.read_sensor("heater_2:temperature").unwrap()
+                > dev.read_sensor("heater_3:temperature").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_simulated_heater_rejects_invalid_writes() {
+        let dev = SimulatedDevice::new();
+        assert!(dev.set_param("heater_0:power", -1.0).is_err());
+        assert!(dev.set_param("heater_99:power", 1.0).is_err());
+        assert!(dev.set_param("mzi_0:phase", 1.0).is_ok());
+    }
+
+    #[test]
+    fn test_simulated_device_provenance_in_capabilities_and_health() {
+        let dev = SimulatedDevice::new();
+        let prov = dev.provenance();
+        assert_eq!(prov.device_id, "simulated");
+        assert_eq!(prov.serial_number.as_deref(), Some("SIM-0000"));
+        assert!(prov.driver_version.is_some());
+
+        let from_health = DeviceProvenance::from_metadata(&dev.id(), &dev.health_report());
+        assert_eq!(from_health, prov);
+        assert!(prov.hardware_revision().contains("sn=SIM-0000"));
+    }
+
+    #[test]
+    fn test_waveform_segments_and_hold() {
+        let wf = Waveform::from_segments("pulse", 1e6, &[(0.0, 2), (1.5, 3)]);
+        assert_eq!(wf.samples, vec![0.0, 0.0, 1.5, 1.5, 1.5]);
+        assert!((wf.duration_s() - 5e-6).abs() < 1e-15);
+        assert_eq!(wf.sample(10), Some(1.5));
+        assert!(Waveform::new("empty", 1e6, Vec::new()).validate().is_err());
+        assert!(Waveform::new("rate", 0.0, vec![1.0]).validate().is_err());
+    }
+
+    #[test]
+    fn test_waveform_upload_unsupported_by_default() {
+        let dev = counting();
+        let wf = Waveform::new("seq", 1e3, vec![1.0]);
+        assert!(dev.upload_waveform("mzi_0:phase", wf).is_err());
+        assert!(dev.trigger_sequence("seq").is_err());
+        assert!(dev.writes.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_simulated_sequence_feeds_reference_simulator() {
+        let dev = SimulatedDevice::new();
+        let pi = std::f64::consts::PI;
+        let half = dev.thermal.power_for_phase(pi / 2.0);
+        let full = dev.thermal.power_for_phase(pi);
+        dev.upload_waveform(
+            "heater_0:power",
+            Waveform::new("sweep", 1e3, vec![0.0, half, full]),
+        )
+        .unwrap();
+        dev.upload_waveform("mzi_7:phase", Waveform::new("other", 1e3, vec![pi]))
+            .unwrap();
+
+        let report = dev.trigger_sequence("sweep").unwrap();
+        assert_eq!(report.channels, vec!["heater_0:power".to_string()]);
+        assert_eq!(report.samples_played, 3);
+        // Dark port at 0 mW, half transmission at pi/2 (times the neighbour's crosstalk),
+        // and the MZI fully switched at pi.
+        assert!((report.readout[0] - 1.0).abs() < 1e-9);
+        assert!(report.readout[1] < 0.6 && report.readout[1] > 0.4);
+        assert!(report.readout[2] < 1e-9);
+        // The last sample is held on the heater after playback.
+        assert!((dev.read_sensor("heater_0:power").unwrap() - full).abs() < 1e-12);
+
+        // The phase-offset waveform on the other sequence switches the last MZI off.
+        dev.set_param("heater_0:power", 0.0).unwrap();
+        assert!(dev.trigger_sequence("other").unwrap().readout[0] < 1e-9);
+        assert!(dev.trigger_sequence("missing").is_err());
+    }
+
+    #[test]
+    fn test_simulated_waveform_rejects_invalid_channels() {
+        let dev = SimulatedDevice::new();
+        let wf = Waveform::new("seq", 1e3, vec![1.0]);
+        assert!(dev.upload_waveform("heater_99:power", wf.clone()).is_err());
+        assert!(dev.upload_waveform("mzi_0:transmission", wf).is_err());
+        assert!(dev
+            .upload_waveform("heater_0:power", Waveform::new("seq", 1e3, vec![-1.0]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_default_provenance_without_metadata() {
+        let prov = counting().provenance();
+        assert_eq!(prov.device_id, "counting");
+        assert_eq!(prov.firmware_version, None);
+        assert_eq!(prov.hardware_revision(), "counting");
+    }
+
+    #[test]
+    fn test_read_sensors_preserves_order() {
+        let dev = counting();
+        let values = dev.read_sensors(&["d0", "detector_1"]).unwrap();
+        assert_eq!(values, vec![2.0, 10.0]);
+        assert_eq!(*dev.reads.borrow(), vec!["d0", "detector_1"]);
+    }
+}
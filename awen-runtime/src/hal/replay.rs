@@ -0,0 +1,408 @@
+//! Record/replay devices for hardware-free regression tests.
+//!
+//! [`RecordingDevice`] wraps any [`Device`] and logs every `set_param`/`read_sensor` call (and
+//! waveform upload/trigger) with a timestamp. The resulting [`DeviceScript`] can be saved as JSON and later played back by a
+//! [`ReplayDevice`], which returns the recorded sensor values and rejects calls that diverge from
+//! the recording. This lets full calibration loops captured on the bench be re-run in CI.
+//! Recorded errors are kept as messages and replay as [`HalError::Device`].
+
+use super::{Capability, Device, HalError, SequenceReport, Waveform};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Absolute tolerance when comparing replayed parameter values against the recording.
+const VALUE_TOLERANCE: f64 = 1e-9;
+
+/// One recorded device interaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum DeviceCall {
+    SetParam {
+        name: String,
+        value: f64,
+        error: Option<String>,
+    },
+    ReadSensor {
+        name: String,
+        value: Option<f64>,
+        error: Option<String>,
+    },
+    UploadWaveform {
+        channel: String,
+        waveform: Waveform,
+        error: Option<String>,
+    },
+    TriggerSequence {
+        id: String,
+        report: Option<SequenceReport>,
+        error: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedCall {
+    pub timestamp_ns: u64,
+    #[serde(flatten)]
+    pub call: DeviceCall,
+}
+
+/// A complete recording of a device session.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeviceScript {
+    pub device_id: String,
+    pub capabilities: Vec<Capability>,
+    pub calls: Vec<RecordedCall>,
+}
+
+impl DeviceScript {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
+
+/// Device wrapper recording every call made through it.
+///
+/// Batch operations use the trait's default loops, so each element of a `set_params` or
+/// `read_sensors` call is recorded individually.
+pub struct RecordingDevice<D: Device> {
+    inner: D,
+    calls: Mutex<Vec<RecordedCall>>,
+}
+
+impl<D: Device> RecordingDevice<D> {
+    pub fn new(inner: D) -> Self {
+        Self {
+            inner,
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Snapshot of everything recorded so far.
+    pub fn script(&self) -> DeviceScript {
+        DeviceScript {
+            device_id: self.inner.id(),
+            capabilities: self.inner.capabilities(),
+            calls: self.calls.lock().map(|c| c.clone()).unwrap_or_default(),
+        }
+    }
+
+    fn record(&self, call: DeviceCall) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(RecordedCall {
+                timestamp_ns: now_ns(),
+                call,
+            });
+        }
+    }
+}
+
+impl<D: Device> Device for RecordingDevice<D> {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn capabilities(&self) -> Vec<Capability> {
+        self.inner.capabilities()
+    }
+
+    fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+        let result = self.inner.set_param(name, value);
+        self.record(DeviceCall::SetParam {
+            name: name.to_string(),
+            value,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    }
+
+    fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+        let result = self.inner.read_sensor(name);
+        self.record(DeviceCall::ReadSensor {
+            name: name.to_string(),
+            value: result.as_ref().ok().copied(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    }
+
+    fn upload_waveform(&self, channel: &str, waveform: Waveform) -> Result<(), HalError> {
+        let result = self.inner.upload_waveform(channel, waveform.clone());
+        self.record(DeviceCall::UploadWaveform {
+            channel: channel.to_string(),
+            waveform,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    }
+
+    fn trigger_sequence(&self, id: &str) -> Result<SequenceReport, HalError> {
+        let result = self.inner.trigger_sequence(id);
+        self.record(DeviceCall::TriggerSequence {
+            id: id.to_string(),
+            report: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    }
+}
+
+/// Device that plays back a [`DeviceScript`] call by call.
+///
+/// Calls must arrive in the recorded order with the recorded names (and, for writes, values);
+/// any divergence returns an error describing the mismatch instead of silently continuing.
+pub struct ReplayDevice {
+    script: DeviceScript,
+    cursor: Mutex<usize>,
+}
+
+impl ReplayDevice {
+    pub fn new(script: DeviceScript) -> Self {
+        Self {
+            script,
+            cursor: Mutex::new(0),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self::new(DeviceScript::load(path)?))
+    }
+
+    /// Number of recorded calls not yet replayed.
+    pub fn remaining(&self) -> usize {
+        let cursor = self.cursor.lock().map(|c| *c).unwrap_or(0);
+        self.script.calls.len().saturating_sub(cursor)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    fn next_call(&self) -> Result<(usize, &DeviceCall), HalError> {
+        let mut cursor = self.cursor.lock()?;
+        let idx = *cursor;
+        let recorded = self
+            .script
+            .calls
+            .get(idx)
+            .ok_or(HalError::ReplayExhausted(idx))?;
+        *cursor += 1;
+        Ok((idx, &recorded.call))
+    }
+}
+
+impl Device for ReplayDevice {
+    fn id(&self) -> String {
+        self.script.device_id.clone()
+    }
+
+    fn capabilities(&self) -> Vec<Capability> {
+        self.script.capabilities.clone()
+    }
+
+    fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+        match self.next_call()? {
+            (
+                _,
+                DeviceCall::SetParam {
+                    name: rec_name,
+                    value: rec_value,
+                    error,
+                },
+            ) if rec_name == name && (rec_value - value).abs() <= VALUE_TOLERANCE => match error {
+                Some(e) => Err(HalError::Device(e.clone())),
+                None => Ok(()),
+            },
+            (idx, other) => Err(divergence(
+                idx,
+                other,
+                format!("set_param({}, {})", name, value),
+            )),
+        }
+    }
+
+    fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+        match self.next_call()? {
+            (
+                idx,
+                DeviceCall::ReadSensor {
+                    name: rec_name,
+                    value,
+                    error,
+                },
+            ) if rec_name == name => match (value, error) {
+                (_, Some(e)) => Err(HalError::Device(e.clone())),
+                (Some(v), None) => Ok(*v),
+                (None, None) => Err(format!("recorded call {} has no value", idx).into()),
+            },
+            (idx, other) => Err(divergence(idx, other, format!("read_sensor({})", name))),
+        }
+    }
+
+    fn upload_waveform(&self, channel: &str, waveform: Waveform) -> Result<(), HalError> {
+        match self.next_call()? {
+            (
+                _,
+                DeviceCall::UploadWaveform {
+                    channel: rec_channel,
+                    waveform: rec_waveform,
+                    error,
+                },
+            ) if rec_channel == channel && waveforms_match(rec_waveform, &waveform) => {
+                match error {
+                    Some(e) => Err(HalError::Device(e.clone())),
+                    None => Ok(()),
+                }
+            }
+            (idx, other) => Err(divergence(
+                idx,
+                other,
+                format!(
+                    "upload_waveform({}, {} samples)",
+                    channel,
+                    waveform.samples.len()
+                ),
+            )),
+        }
+    }
+
+    fn trigger_sequence(&self, id: &str) -> Result<SequenceReport, HalError> {
+        match self.next_call()? {
+            (
+                idx,
+                DeviceCall::TriggerSequence {
+                    id: rec_id,
+                    report,
+                    error,
+                },
+            ) if rec_id == id => match (report, error) {
+                (_, Some(e)) => Err(HalError::Device(e.clone())),
+                (Some(r), None) => Ok(r.clone()),
+                (None, None) => Err(format!("recorded call {} has no report", idx).into()),
+            },
+            (idx, other) => Err(divergence(idx, other, format!("trigger_sequence({})", id))),
+        }
+    }
+}
+
+fn divergence(call: usize, expected: &DeviceCall, got: String) -> HalError {
+    HalError::ReplayDivergence {
+        call,
+        expected: format!("{:?}", expected),
+        got,
+    }
+}
+
+fn waveforms_match(recorded: &Waveform, actual: &Waveform) -> bool {
+    recorded.sequence_id == actual.sequence_id
+        && recorded.sample_rate_hz == actual.sample_rate_hz
+        && recorded.samples.len() == actual.samples.len()
+        && recorded
+            .samples
+            .iter()
+            .zip(&actual.samples)
+            .all(|(a, b)| (a - b).abs() <= VALUE_TOLERANCE)
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::SimulatedDevice;
+
+    /// A tiny calibration loop: sweep one heater and keep the lowest-transmission setting.
+    fn null_mzi<D: Device>(dev: &D) -> Result<f64, HalError> {
+        let mut best = (0.0, f64::MAX);
+        for step in 0..8 {
+            let power = step as f64 * 5.0;
+            dev.set_param("heater_0:power", power)?;
+            let t = dev.read_sensor("mzi_0:transmission")?;
+            if t < best.1 {
+                best = (power, t);
+            }
+        }
+        Ok(best.0)
+    }
+
+    #[test]
+    fn test_record_then_replay_reproduces_loop() {
+        let recorder = RecordingDevice::new(SimulatedDevice::new());
+        let recorded = null_mzi(&recorder).unwrap();
+        let script = recorder.script();
+        assert_eq!(script.calls.len(), 16);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.json");
+        script.save(&path).unwrap();
+
+        let replay = ReplayDevice::from_file(&path).unwrap();
+        assert_eq!(replay.id(), "simulated");
+        assert_eq!(null_mzi(&replay).unwrap(), recorded);
+        assert!(replay.is_exhausted());
+    }
+
+    #[test]
+    fn test_replay_rejects_divergent_calls() {
+        let recorder = RecordingDevice::new(SimulatedDevice::new());
+        recorder.set_param("heater_1:power", 3.0).unwrap();
+        recorder.read_sensor("mzi_1:phase").unwrap();
+
+        let replay = ReplayDevice::new(recorder.script());
+        let err = replay.set_param("heater_1:power", 4.0).unwrap_err();
+        assert!(
+            matches!(err, HalError::ReplayDivergence { call: 0, .. }),
+            "{}",
+            err
+        );
+
+        let replay = ReplayDevice::new(recorder.script());
+        replay.set_param("heater_1:power", 3.0).unwrap();
+        assert!(replay.read_sensor("mzi_2:phase").is_err());
+        assert_eq!(
+            replay.read_sensor("mzi_1:phase"),
+            Err(HalError::ReplayExhausted(2))
+        );
+    }
+
+    #[test]
+    fn test_recorded_errors_are_replayed() {
+        let recorder = RecordingDevice::new(SimulatedDevice::new());
+        assert!(recorder.set_param("heater_0:power", -1.0).is_err());
+
+        let replay = ReplayDevice::new(recorder.script());
+        assert!(replay.set_param("heater_0:power", -1.0).is_err());
+    }
+
+    #[test]
+    fn test_waveform_sequence_replays_report() {
+        let recorder = RecordingDevice::new(SimulatedDevice::new());
+        let waveform = Waveform::new("sweep", 1e3, vec![0.0, 2.0, 4.0]);
+        recorder
+            .upload_waveform("heater_0:power", waveform.clone())
+            .unwrap();
+        let report = recorder.trigger_sequence("sweep").unwrap();
+
+        let replay = ReplayDevice::new(recorder.script());
+        replay.upload_waveform("heater_0:power", waveform).unwrap();
+        let replayed = replay.trigger_sequence("sweep").unwrap();
+        assert_eq!(replayed.readout, report.readout);
+        assert!(replay.is_exhausted());
+    }
+}
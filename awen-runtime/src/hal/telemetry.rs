@@ -0,0 +1,495 @@
+//! Device telemetry streamed onto timeline lanes.
+//!
+//! [`TelemetryDevice`] (for [`Device`]/[`LabDevice`]) and [`TelemetryBackend`] (for
+//! `hal_v0::PhotonicBackend`) forward every call to the wrapped device and push a
+//! [`TimelineEvent`] into a shared [`HalTelemetry`] buffer. Parameter writes and sensor reads land
+//! on the `HAL.Channel.<i>` lane of the channel named in the parameter (`heater_3:power` ->
+//! channel 3); device-wide operations use a `HAL.Device.<id>` lane. Measurements are recorded as
+//! windows spanning their integration time. While a span context is set (the engine sets the
+//! executing node's), every call also records a `hal:<operation>` child span, and its timeline
+//! event carries the span's `span_id`.
+
+use super::{
+    parse_channel_param, CalibrationResult, Capability, Device, HalError, LabDevice, SafetyLimits,
+    SequenceReport, Waveform,
+};
+use crate::hal_v0::{
+    DeviceCalibrationState, DeviceCapabilities, DeviceMetrics, DeviceType, DirectDetectionConfig,
+    DirectDetectionResult, FaultDetectionThresholds, HealthStatus, HeterodyneConfig,
+    HeterodyneResult, HomodyneConfig, HomodyneResult, PhotonicBackend,
+};
+use crate::observability::{timeline::lanes, SpanContext, TimelineEvent};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Shared buffer of HAL timeline events.
+#[derive(Clone, Default)]
+pub struct HalTelemetry {
+    events: Arc<Mutex<Vec<TimelineEvent>>>,
+    span_context: Arc<Mutex<Option<SpanContext>>>,
+}
+
+impl HalTelemetry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, event: TimelineEvent) {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(event);
+        }
+    }
+
+    pub fn events(&self) -> Vec<TimelineEvent> {
+        self.events.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    /// Nest spans of subsequent HAL calls under `context`; `None` stops recording spans.
+    pub fn set_span_context(&self, context: Option<SpanContext>) {
+        if let Ok(mut current) = self.span_context.lock() {
+            *current = context;
+        }
+    }
+
+    /// Take all buffered events, leaving the buffer empty.
+    pub fn drain(&self) -> Vec<TimelineEvent> {
+        self.events
+            .lock()
+            .map(|mut e| std::mem::take(&mut *e))
+            .unwrap_or_default()
+    }
+
+    fn record(
+        &self,
+        device_id: &str,
+        lane: String,
+        name: &str,
+        start_ms: u128,
+        end_ms: u128,
+        mut attributes: HashMap<String, String>,
+    ) {
+        attributes.insert("device_id".to_string(), device_id.to_string());
+        let context = self.span_context.lock().ok().and_then(|c| c.clone());
+        if let Some(context) = context {
+            let mut span = context.start_span(&format!("hal:{}", name));
+            span.set_attribute("lane", &lane);
+            for (key, value) in &attributes {
+                span.set_attribute(key, value);
+            }
+            span.end();
+            attributes.insert("span_id".to_string(), span.id());
+        }
+        self.push(TimelineEvent {
+            lane,
+            name: name.to_string(),
+            start_ms,
+            end_ms,
+            attributes,
+        });
+    }
+}
+
+/// Lane for a named parameter or sensor: its channel lane if the name carries an index.
+pub fn lane_for(device_id: &str, name: &str) -> String {
+    match parse_channel_param(name) {
+        Some((_, idx, _)) => lanes::hal_channel(idx as u32),
+        None => device_lane(device_id),
+    }
+}
+
+/// Lane for device-wide operations.
+pub fn device_lane(device_id: &str) -> String {
+    lanes::hal_device(device_id)
+}
+
+fn now_ms() -> u128 {
+    Utc::now().timestamp_millis() as u128
+}
+
+fn error_attrs<T>(result: &Result<T, impl ToString>) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    if let Err(e) = result {
+        attrs.insert("error".to_string(), e.to_string());
+    }
+    attrs
+}
+
+/// [`Device`] wrapper emitting a timeline event for every parameter write and sensor read.
+pub struct TelemetryDevice<D> {
+    inner: D,
+    telemetry: HalTelemetry,
+}
+
+impl<D> TelemetryDevice<D> {
+    pub fn new(inner: D, telemetry: HalTelemetry) -> Self {
+        Self { inner, telemetry }
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: Device> Device for TelemetryDevice<D> {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn capabilities(&self) -> Vec<Capability> {
+        self.inner.capabilities()
+    }
+
+    fn set_param(&self, name: &str, value: f64) -> Result<(), HalError> {
+        let start = now_ms();
+        let result = self.inner.set_param(name, value);
+        let mut attrs = error_attrs(&result);
+        attrs.insert("param".to_string(), name.to_string());
+        attrs.insert("value".to_string(), value.to_string());
+        let id = self.inner.id();
+        self.telemetry.record(
+            &id,
+            lane_for(&id, name),
+            "set_param",
+            start,
+            now_ms(),
+            attrs,
+        );
+        result
+    }
+
+    fn read_sensor(&self, name: &str) -> Result<f64, HalError> {
+        let start = now_ms();
+        let result = self.inner.read_sensor(name);
+        let mut attrs = error_attrs(&result);
+        attrs.insert("sensor".to_string(), name.to_string());
+        if let Ok(v) = &result {
+            attrs.insert("value".to_string(), v.to_string());
+        }
+        let id = self.inner.id();
+        self.telemetry.record(
+            &id,
+            lane_for(&id, name),
+            "read_sensor",
+            start,
+            now_ms(),
+            attrs,
+        );
+        result
+    }
+
+    fn upload_waveform(&self, channel: &str, waveform: Waveform) -> Result<(), HalError> {
+        let start = now_ms();
+        let mut attrs = HashMap::new();
+        attrs.insert("sequence_id".to_string(), waveform.sequence_id.clone());
+        attrs.insert("samples".to_string(), waveform.samples.len().to_string());
+        let result = self.inner.upload_waveform(channel, waveform);
+        attrs.extend(error_attrs(&result));
+        let id = self.inner.id();
+        self.telemetry.record(
+            &id,
+            lane_for(&id, channel),
+            "upload_waveform",
+            start,
+            now_ms(),
+            attrs,
+        );
+        result
+    }
+
+    fn trigger_sequence(&self, id: &str) -> Result<SequenceReport, HalError> {
+        let start = now_ms();
+        let result = self.inner.trigger_sequence(id);
+        let mut attrs = error_attrs(&result);
+        attrs.insert("sequence_id".to_string(), id.to_string());
+        if let Ok(report) = &result {
+            attrs.insert("channels".to_string(), report.channels.join(","));
+            attrs.insert(
+                "samples_played".to_string(),
+                report.samples_played.to_string(),
+            );
+        }
+        let device_id = self.inner.id();
+        self.telemetry.record(
+            &device_id,
+            device_lane(&device_id),
+            "trigger_sequence",
+            start,
+            now_ms(),
+            attrs,
+        );
+        result
+    }
+}
+
+impl<D: LabDevice> LabDevice for TelemetryDevice<D> {
+    fn apply_calibration(
+        &self,
+        mapping: &HashMap<String, f64>,
+        safety: Option<&SafetyLimits>,
+    ) -> Result<CalibrationResult, HalError> {
+        let start = now_ms();
+        let result = self.inner.apply_calibration(mapping, safety);
+        let end = now_ms();
+        let id = self.inner.id();
+        if let Ok(res) = &result {
+            let mut applied: Vec<(&String, &f64)> = res.applied.iter().collect();
+            applied.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, value) in applied {
+                let mut attrs = HashMap::new();
+                attrs.insert("param".to_string(), name.clone());
+                attrs.insert("value".to_string(), value.to_string());
+                self.telemetry.record(
+                    &id,
+                    lane_for(&id, name),
+                    "apply_calibration",
+                    start,
+                    end,
+                    attrs,
+                );
+            }
+        } else {
+            self.telemetry.record(
+                &id,
+                device_lane(&id),
+                "apply_calibration",
+                start,
+                end,
+                error_attrs(&result),
+            );
+        }
+        result
+    }
+
+    fn health_report(&self) -> HashMap<String, String> {
+        self.inner.health_report()
+    }
+}
+
+/// [`PhotonicBackend`] wrapper emitting phase-shifter writes on their channel lanes and
+/// measurements as windows on the device lane.
+pub struct TelemetryBackend<B> {
+    inner: B,
+    telemetry: HalTelemetry,
+}
+
+impl<B: PhotonicBackend> TelemetryBackend<B> {
+    pub fn new(inner: B, telemetry: HalTelemetry) -> Self {
+        Self { inner, telemetry }
+    }
+
+    fn record_write(&self, kind: &str, index: usize, value: f64, start: u128, ok: bool) {
+        let mut attrs = HashMap::new();
+        attrs.insert("index".to_string(), index.to_string());
+        attrs.insert("value".to_string(), value.to_string());
+        if !ok {
+            attrs.insert("error".to_string(), "write failed".to_string());
+        }
+        self.telemetry.record(
+            &self.inner.device_id(),
+            lanes::hal_channel(index as u32),
+            kind,
+            start,
+            now_ms(),
+            attrs,
+        );
+    }
+
+    /// Record a measurement window of at least `integration_time_us`.
+    fn record_measurement(&self, kind: &str, start: u128, integration_time_us: f64, ok: bool) {
+        let integration_ms = (integration_time_us / 1000.0).ceil().max(0.0) as u128;
+        let end = now_ms().max(start + integration_ms);
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "integration_time_us".to_string(),
+            integration_time_us.to_string(),
+        );
+        if !ok {
+            attrs.insert("error".to_string(), "measurement failed".to_string());
+        }
+        let id = self.inner.device_id();
+        self.telemetry
+            .record(&id, device_lane(&id), kind, start, end, attrs);
+    }
+}
+
+impl<B: PhotonicBackend> PhotonicBackend for TelemetryBackend<B> {
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        self.inner.device_type()
+    }
+
+    fn device_id(&self) -> String {
+        self.inner.device_id()
+    }
+
+    fn set_phase_shifter(&mut self, index: usize, phase_radians: f64) -> anyhow::Result<()> {
+        let start = now_ms();
+        let result = self.inner.set_phase_shifter(index, phase_radians);
+        self.record_write(
+            "set_phase_shifter",
+            index,
+            phase_radians,
+            start,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn set_coupler_split(&mut self, index: usize, ratio: f64) -> anyhow::Result<()> {
+        let start = now_ms();
+        let result = self.inner.set_coupler_split(index, ratio);
+        self.record_write("set_coupler_split", index, ratio, start, result.is_ok());
+        result
+    }
+
+    fn measure_homodyne(&mut self, config: &HomodyneConfig) -> anyhow::Result<HomodyneResult> {
+        let start = now_ms();
+        let result = self.inner.measure_homodyne(config);
+        self.record_measurement(
+            "measure_homodyne",
+            start,
+            config.integration_time_us,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn measure_heterodyne(
+        &mut self,
+        config: &HeterodyneConfig,
+    ) -> anyhow::Result<HeterodyneResult> {
+        let start = now_ms();
+        let result = self.inner.measure_heterodyne(config);
+        self.record_measurement(
+            "measure_heterodyne",
+            start,
+            config.integration_time_us,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn measure_direct(
+        &mut self,
+        config: &DirectDetectionConfig,
+    ) -> anyhow::Result<DirectDetectionResult> {
+        let start = now_ms();
+        let result = self.inner.measure_direct(config);
+        self.record_measurement(
+            "measure_direct",
+            start,
+            config.integration_time_us,
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn load_calibration(&mut self, state: DeviceCalibrationState) -> anyhow::Result<()> {
+        self.inner.load_calibration(state)
+    }
+
+    fn get_calibration(&self) -> anyhow::Result<DeviceCalibrationState> {
+        self.inner.get_calibration()
+    }
+
+    fn get_metrics(&self) -> DeviceMetrics {
+        self.inner.get_metrics()
+    }
+
+    fn fault_detection_thresholds(&self) -> FaultDetectionThresholds {
+        self.inner.fault_detection_thresholds()
+    }
+
+    fn initialize(&mut self) -> anyhow::Result<()> {
+        self.inner.initialize()
+    }
+
+    fn shutdown(&mut self) -> anyhow::Result<()> {
+        self.inner.shutdown()
+    }
+
+    fn health_check(&mut self) -> anyhow::Result<HealthStatus> {
+        self.inner.health_check()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::SimulatedDevice;
+    use crate::hal_v0::SimulatorBackend;
+
+    #[test]
+    fn test_device_calls_land_on_channel_lanes() {
+        let telemetry = HalTelemetry::new();
+        let dev = TelemetryDevice::new(SimulatedDevice::new(), telemetry.clone());
+        dev.set_param("heater_2:power", 4.0).unwrap();
+        dev.read_sensor("mzi_5:phase").unwrap();
+        dev.read_sensor("ambient").unwrap();
+
+        let events = telemetry.events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].lane, "HAL.Channel.2");
+        assert_eq!(events[0].name, "set_param");
+        assert_eq!(events[0].attributes.get("value").unwrap(), "4");
+        assert_eq!(events[1].lane, "HAL.Channel.5");
+        assert_eq!(events[2].lane, "HAL.Device.simulated");
+    }
+
+    #[test]
+    fn test_calls_nest_under_span_context() {
+        let telemetry = HalTelemetry::new();
+        let dev = TelemetryDevice::new(SimulatedDevice::new(), telemetry.clone());
+        dev.set_param("heater_1:power", 1.0).unwrap();
+
+        let tracer = crate::observability::TracerHandle::new();
+        let node = tracer.start_span("exec:mzi_0");
+        telemetry.set_span_context(Some(node.context()));
+        dev.set_param("heater_1:power", 2.0).unwrap();
+        telemetry.set_span_context(None);
+        dev.set_param("heater_1:power", 3.0).unwrap();
+
+        // Only the call made under the node span is traced, as its child.
+        let spans = tracer.spans();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].name, "hal:set_param");
+        assert_eq!(spans[1].parent.as_deref(), Some(node.id().as_str()));
+        assert_eq!(spans[1].attributes["lane"], "HAL.Channel.1");
+        let events = telemetry.events();
+        assert!(!events[0].attributes.contains_key("span_id"));
+        assert_eq!(events[1].attributes["span_id"], spans[1].id);
+        assert!(!events[2].attributes.contains_key("span_id"));
+    }
+
+    #[test]
+    fn test_failed_write_is_recorded_with_error() {
+        let telemetry = HalTelemetry::new();
+        let dev = TelemetryDevice::new(SimulatedDevice::new(), telemetry.clone());
+        assert!(dev.set_param("heater_0:power", -2.0).is_err());
+        assert!(telemetry.events()[0].attributes.contains_key("error"));
+    }
+
+    #[test]
+    fn test_backend_measurement_window_covers_integration_time() {
+        let telemetry = HalTelemetry::new();
+        let mut backend = TelemetryBackend::new(SimulatorBackend::new(), telemetry.clone());
+        backend.set_phase_shifter(3, 0.5).unwrap();
+        backend
+            .measure_direct(&DirectDetectionConfig {
+                wavelength_nm: 1550.0,
+                integration_time_us: 5000.0,
+                dark_count_threshold: 10,
+            })
+            .unwrap();
+
+        let events = telemetry.drain();
+        assert_eq!(events[0].lane, "HAL.Channel.3");
+        assert_eq!(events[1].lane, "HAL.Device.simulator_v0.2");
+        assert!(events[1].end_ms - events[1].start_ms >= 5);
+        assert!(telemetry.events().is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+//! Steady-state thermal model for heater-driven phase shifters.
+//!
+//! Each channel has a resistive heater next to a waveguide. Heater power raises the local
+//! temperature, which shifts the optical phase through the thermo-optic effect. A fraction of the
+//! heat leaks into the nearest-neighbour channels, which is the dominant source of phase crosstalk
+//! on dense thermally-tuned meshes.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThermalModel {
+    /// Substrate temperature with all heaters off.
+    pub ambient_celsius: f64,
+    /// Temperature rise of a channel per mW dissipated in its own heater.
+    pub thermal_resistance_k_per_mw: f64,
+    /// Fraction of a heater's temperature rise seen by each adjacent channel.
+    pub crosstalk_fraction: f64,
+    /// Thermo-optic phase shift per kelvin of temperature rise.
+    pub phase_per_kelvin: f64,
+}
+
+impl Default for ThermalModel {
+    fn default() -> Self {
+        // Silicon waveguide with a TiN heater: ~0.25 K/mW and ~pi phase shift at ~25 mW.
+        Self {
+            ambient_celsius: 25.0,
+            thermal_resistance_k_per_mw: 0.25,
+            crosstalk_fraction: 0.05,
+            phase_per_kelvin: std::f64::consts::PI / 6.25,
+        }
+    }
+}
+
+impl ThermalModel {
+    /// Temperature rise of every channel, including nearest-neighbour crosstalk.
+    pub fn temperature_rise(&self, heater_mw: &[f64]) -> Vec<f64> {
+        (0..heater_mw.len())
+            .map(|i| {
+                let own = heater_mw[i];
+                let left = if i > 0 { heater_mw[i - 1] } else { 0.0 };
+                let right = heater_mw.get(i + 1).copied().unwrap_or(0.0);
+                self.thermal_resistance_k_per_mw * (own + self.crosstalk_fraction * (left + right))
+            })
+            .collect()
+    }
+
+    /// Absolute temperature of every channel.
+    pub fn temperatures(&self, heater_mw: &[f64]) -> Vec<f64> {
+        self.temperature_rise(heater_mw)
+            .into_iter()
+            .map(|dt| self.ambient_celsius + dt)
+            .collect()
+    }
+
+    /// Thermo-optic phase shift of every channel.
+    pub fn phase_shifts(&self, heater_mw: &[f64]) -> Vec<f64> {
+        self.temperature_rise(heater_mw)
+            .into_iter()
+            .map(|dt| self.phase_per_kelvin * dt)
+            .collect()
+    }
+
+    /// Heater power needed on an isolated channel to reach `phase` radians.
+    pub fn power_for_phase(&self, phase: f64) -> f64 {
+        phase / (self.phase_per_kelvin * self.thermal_resistance_k_per_mw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heater_raises_own_temperature() {
+        let model = ThermalModel::default();
+        let temps = model.temperatures(&[10.0, 0.0, 0.0]);
+        assert!((temps[0] - 27.5).abs() < 1e-9);
+        assert!(temps[0] > temps[1]);
+        assert!((temps[2] - model.ambient_celsius).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_crosstalk_only_reaches_nearest_neighbours() {
+        let model = ThermalModel::default();
+        let rise = model.temperature_rise(&[0.0, 0.0, 20.0, 0.0, 0.0]);
+        assert_eq!(rise[0], 0.0);
+        assert!((rise[1] - 0.25).abs() < 1e-9);
+        assert!((rise[3] - 0.25).abs() < 1e-9);
+        assert_eq!(rise[4], 0.0);
+    }
+
+    #[test]
+    fn test_power_for_phase_inverts_phase_shift() {
+        let model = ThermalModel::default();
+        let p = model.power_for_phase(std::f64::consts::PI);
+        let phases = model.phase_shifts(&[p]);
+        assert!((phases[0] - std::f64::consts::PI).abs() < 1e-9);
+    }
+}
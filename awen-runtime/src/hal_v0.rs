@@ -1,3 +1,5 @@
+use crate::observability::EventSink;
+use crate::scheduler::ResourceState;
 use anyhow::{anyhow, Result};
 /// Hardware Abstraction Layer (HAL) v0.2 - Device Backend Management
 ///
@@ -10,7 +12,10 @@ use anyhow::{anyhow, Result};
 /// - Resource allocation and preemption
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // ============================================================================
 // Device Types & Capabilities
@@ -543,6 +548,8 @@ pub struct HalManager {
     pub config: HalConfig,
     pub registry: BackendRegistry,
     pub metrics: DeviceMetrics,
+    /// Last health status observed per device by `sample_health`.
+    pub device_health: HashMap<String, HealthStatus>,
 }
 
 impl HalManager {
@@ -560,6 +567,7 @@ impl HalManager {
                 peak_temperature_celsius: 25.0,
                 average_power_consumption_mw: 10.0,
             },
+            device_health: HashMap::new(),
         }
     }
 
@@ -607,6 +615,68 @@ impl HalManager {
 
         Ok(true)
     }
+
+    /// Run one health check on every registered backend.
+    ///
+    /// Status transitions are reported to `events` (Warning for Degraded, Error for Faulty or a
+    /// failed check, Info on recovery) and mirrored into `resources.device_availability`, where
+    /// only Healthy devices are considered available to the scheduler.
+    pub fn sample_health(
+        &mut self,
+        events: &EventSink,
+        resources: &mut ResourceState,
+    ) -> Vec<HealthSample> {
+        let mut ids = self.registry.list_backends();
+        ids.sort();
+
+        let mut samples = Vec::with_capacity(ids.len());
+        for id in ids {
+            let checked = match self.registry.get(&id) {
+                Ok(device) => device.health_check(),
+                Err(e) => Err(e),
+            };
+            let (status, error) = match checked {
+                Ok(status) => (status, None),
+                Err(e) => (HealthStatus::Faulty, Some(e.to_string())),
+            };
+
+            let previous = self.device_health.insert(id.clone(), status);
+            if previous != Some(status) {
+                let mut attrs = HashMap::new();
+                attrs.insert("device_id".to_string(), id.clone());
+                attrs.insert("status".to_string(), format!("{:?}", status));
+                if let Some(prev) = previous {
+                    attrs.insert("previous_status".to_string(), format!("{:?}", prev));
+                }
+                if let Some(err) = &error {
+                    attrs.insert("error".to_string(), err.clone());
+                }
+                match status {
+                    HealthStatus::Degraded => {
+                        events.warning("hal.health", "device degraded", attrs)
+                    }
+                    HealthStatus::Faulty => events.error("hal.health", "device faulty", attrs),
+                    HealthStatus::Healthy if previous.is_some() => {
+                        events.info("hal.health", "device recovered", attrs)
+                    }
+                    HealthStatus::Healthy => {}
+                }
+            }
+            resources
+                .device_availability
+                .insert(id.clone(), status == HealthStatus::Healthy);
+
+            samples.push(HealthSample {
+                device_id: id,
+                status,
+                timestamp_ns: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos() as u64,
+            });
+        }
+        samples
+    }
 }
 
 impl Default for HalManager {
@@ -617,6 +687,80 @@ impl Default for HalManager {
     }
 }
 
+// ============================================================================
+// Health Monitoring
+// ============================================================================
+
+/// Result of a single health check on one device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthSample {
+    pub device_id: String,
+    pub status: HealthStatus,
+    pub timestamp_ns: u64,
+}
+
+/// Background loop calling `HalManager::sample_health` every `health_check_interval_ms`.
+///
+/// The monitor stops when `stop` is called or the handle is dropped.
+pub struct HealthMonitor {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HealthMonitor {
+    pub fn start(
+        hal: Arc<Mutex<HalManager>>,
+        events: EventSink,
+        resources: Arc<Mutex<ResourceState>>,
+    ) -> Self {
+        let interval_ms = hal
+            .lock()
+            .map(|h| h.config.health_check_interval_ms)
+            .unwrap_or(1000)
+            .max(1);
+        let interval = Duration::from_millis(interval_ms as u64);
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+
+        let handle = std::thread::spawn(move || {
+            while !stop_flag.load(Ordering::SeqCst) {
+                if let (Ok(mut hal), Ok(mut resources)) = (hal.lock(), resources.lock()) {
+                    hal.sample_health(&events, &mut resources);
+                }
+                // Sleep in short slices so `stop` returns promptly even for long intervals.
+                let mut waited = Duration::ZERO;
+                while waited < interval && !stop_flag.load(Ordering::SeqCst) {
+                    let slice = (interval - waited).min(Duration::from_millis(10));
+                    std::thread::sleep(slice);
+                    waited += slice;
+                }
+            }
+        });
+
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|h| !h.is_finished())
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for HealthMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
 // ============================================================================
 // Unit Tests
 // ============================================================================
@@ -777,6 +921,146 @@ mod tests {
         assert_eq!(backend.metrics.measurements_taken, count_before + 1);
     }
 
+    /// Simulator wrapper whose reported health can be changed from the test.
+    struct FlakyBackend {
+        inner: SimulatorBackend,
+        status: Arc<Mutex<HealthStatus>>,
+    }
+
+    impl PhotonicBackend for FlakyBackend {
+        fn capabilities(&self) -> DeviceCapabilities {
+            self.inner.capabilities()
+        }
+        fn device_type(&self) -> DeviceType {
+            self.inner.device_type()
+        }
+        fn device_id(&self) -> String {
+            "flaky".to_string()
+        }
+        fn set_phase_shifter(&mut self, index: usize, phase_radians: f64) -> Result<()> {
+            self.inner.set_phase_shifter(index, phase_radians)
+        }
+        fn set_coupler_split(&mut self, index: usize, ratio: f64) -> Result<()> {
+            self.inner.set_coupler_split(index, ratio)
+        }
+        fn measure_homodyne(&mut self, config: &HomodyneConfig) -> Result<HomodyneResult> {
+            self.inner.measure_homodyne(config)
+        }
+        fn measure_heterodyne(&mut self, config: &HeterodyneConfig) -> Result<HeterodyneResult> {
+            self.inner.measure_heterodyne(config)
+        }
+        fn measure_direct(
+            &mut self,
+            config: &DirectDetectionConfig,
+        ) -> Result<DirectDetectionResult> {
+            self.inner.measure_direct(config)
+        }
+        fn load_calibration(&mut self, state: DeviceCalibrationState) -> Result<()> {
+            self.inner.load_calibration(state)
+        }
+        fn get_calibration(&self) -> Result<DeviceCalibrationState> {
+            self.inner.get_calibration()
+        }
+        fn initialize(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn health_check(&mut self) -> Result<HealthStatus> {
+            Ok(*self.status.lock().unwrap())
+        }
+    }
+
+    fn empty_resources() -> ResourceState {
+        ResourceState {
+            available_wavelengths: vec![],
+            available_memory_slots: vec![],
+            device_availability: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_sample_health_reports_transitions() {
+        let status = Arc::new(Mutex::new(HealthStatus::Healthy));
+        let mut manager = HalManager::new(HalConfig::default());
+        manager
+            .registry
+            .register(
+                "flaky".to_string(),
+                Box::new(FlakyBackend {
+                    inner: SimulatorBackend::new(),
+                    status: Arc::clone(&status),
+                }),
+            )
+            .unwrap();
+        let events = EventSink::new();
+        let mut resources = empty_resources();
+
+        manager.sample_health(&events, &mut resources);
+        assert!(events.events().is_empty());
+        assert_eq!(resources.device_availability.get("flaky"), Some(&true));
+
+        *status.lock().unwrap() = HealthStatus::Degraded;
+        manager.sample_health(&events, &mut resources);
+        manager.sample_health(&events, &mut resources);
+        let evs = events.events();
+        assert_eq!(evs.len(), 1, "only the transition is reported");
+        assert_eq!(evs[0].level, crate::observability::Level::Warning);
+        assert_eq!(resources.device_availability.get("flaky"), Some(&false));
+
+        *status.lock().unwrap() = HealthStatus::Faulty;
+        manager.sample_health(&events, &mut resources);
+        assert_eq!(
+            events.events().last().unwrap().level,
+            crate::observability::Level::Error
+        );
+
+        *status.lock().unwrap() = HealthStatus::Healthy;
+        manager.sample_health(&events, &mut resources);
+        assert_eq!(
+            events.events().last().unwrap().level,
+            crate::observability::Level::Info
+        );
+        assert_eq!(resources.device_availability.get("flaky"), Some(&true));
+    }
+
+    #[test]
+    fn test_health_monitor_samples_in_background() {
+        let config = HalConfig {
+            health_check_interval_ms: 5,
+            ..HalConfig::default()
+        };
+        let mut manager = HalManager::new(config);
+        manager.register_simulator().unwrap();
+        let hal = Arc::new(Mutex::new(manager));
+        let resources = Arc::new(Mutex::new(empty_resources()));
+
+        let mut monitor =
+            HealthMonitor::start(Arc::clone(&hal), EventSink::new(), Arc::clone(&resources));
+        assert!(monitor.is_running());
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        while resources.lock().unwrap().device_availability.is_empty()
+            && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        monitor.stop();
+        assert!(!monitor.is_running());
+        assert_eq!(
+            resources
+                .lock()
+                .unwrap()
+                .device_availability
+                .get("simulator"),
+            Some(&true)
+        );
+        assert_eq!(
+            hal.lock().unwrap().device_health.get("simulator"),
+            Some(&HealthStatus::Healthy)
+        );
+    }
+
     #[test]
     fn test_fault_detection_thresholds_default() {
         let thresholds = FaultDetectionThresholds::default();
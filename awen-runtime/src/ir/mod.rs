@@ -13,9 +13,16 @@ pub struct Node {
     /// Optional: for measurement nodes, specify which mode to measure
     #[serde(default)]
     pub measure_mode: Option<String>,
+    /// Optional: for measurement nodes, the observable (`photon_count` (default), `homodyne`,
+    /// `heterodyne`); homodyne takes its LO angle from `params["angle"]`
+    #[serde(default)]
+    pub measurement: Option<String>,
     /// Optional: conditional branches based on measurement outcome
     #[serde(default)]
     pub conditional_branches: Option<Vec<ConditionalBranch>>,
+    /// Optional: parameters set at runtime from earlier measurement results
+    #[serde(default)]
+    pub feed_forward: Option<Vec<FeedForward>>,
 }
 
 /// A measurement-conditioned feedback branch: if outcome matches condition, execute subgraph
@@ -29,6 +36,25 @@ pub struct ConditionalBranch {
     pub else_nodes: Option<Vec<String>>,
 }
 
+/// A feed-forward parameter: `params[param] = offset + gain · value`, where `value` is the
+/// measurement recorded by `source_node` (a DETECTOR) — its `quadrature`-th continuous outcome
+/// for homodyne/heterodyne detection, otherwise its photon count
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedForward {
+    pub param: String,
+    pub source_node: String,
+    #[serde(default)]
+    pub quadrature: usize,
+    #[serde(default = "default_gain")]
+    pub gain: f64,
+    #[serde(default)]
+    pub offset: f64,
+}
+
+fn default_gain() -> f64 {
+    1.0
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Edge {
     pub src_node: String,
@@ -47,40 +73,189 @@ pub struct Graph {
     pub metadata: HashMap<String, String>,
 }
 
-pub fn load_from_json(path: &str) -> Result<Graph, String> {
-    let data = std::fs::read_to_string(path).map_err(|e| format!("read error: {}", e))?;
-    serde_json::from_str::<Graph>(&data).map_err(|e| format!("parse error: {}", e))
+/// Why an IR graph failed to load or validate
+#[derive(Debug, thiserror::Error)]
+pub enum IrError {
+    #[error("read error: {0}")]
+    Read(#[from] std::io::Error),
+    #[error("parse error: {0}")]
+    Parse(#[from] serde_json::Error),
+    /// A conditional branch names a node the graph does not have
+    #[error("{branch} branch references non-existent node: {node}")]
+    UnknownBranchNode { branch: &'static str, node: String },
+    /// A feed-forward reads a node the graph does not have
+    #[error("feed-forward on {node} references non-existent node: {source_node}")]
+    UnknownFeedForwardSource { node: String, source_node: String },
+    /// A feed-forward reads itself or a node that is not a DETECTOR
+    #[error("feed-forward on {node} must read an earlier DETECTOR, not {source_node}")]
+    FeedForwardNotDetector { node: String, source_node: String },
+    /// Edges, conditional branches and feed-forward reads loop back to this node
+    #[error("graph has a dependency cycle through node {node}")]
+    DependencyCycle { node: String },
+}
+
+#[cfg(feature = "simulator")]
+pub fn load_from_json(path: &str) -> Result<Graph, IrError> {
+    let data = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str::<Graph>(&data)?)
 }
 
 /// Validate IR: check that conditional branches reference existing nodes
-pub fn validate_graph(graph: &Graph) -> Result<(), String> {
+pub fn validate_graph(graph: &Graph) -> Result<(), IrError> {
     let node_ids: std::collections::HashSet<&str> =
         graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    let unknown = |branch, node: &String| IrError::UnknownBranchNode {
+        branch,
+        node: node.clone(),
+    };
 
     for node in &graph.nodes {
         if let Some(branches) = &node.conditional_branches {
             for branch in branches {
                 for then_id in &branch.then_nodes {
                     if !node_ids.contains(then_id.as_str()) {
-                        return Err(format!(
-                            "conditional branch references non-existent node: {}",
-                            then_id
-                        ));
+                        return Err(unknown("conditional", then_id));
                     }
                 }
                 if let Some(else_nodes) = &branch.else_nodes {
                     for else_id in else_nodes {
                         if !node_ids.contains(else_id.as_str()) {
-                            return Err(format!(
-                                "else branch references non-existent node: {}",
-                                else_id
-                            ));
+                            return Err(unknown("else", else_id));
                         }
                     }
                 }
             }
         }
+        if let Some(feed_forward) = &node.feed_forward {
+            for ff in feed_forward {
+                let source = graph
+                    .nodes
+                    .iter()
+                    .find(|n| n.id == ff.source_node)
+                    .ok_or_else(|| IrError::UnknownFeedForwardSource {
+                        node: node.id.clone(),
+                        source_node: ff.source_node.clone(),
+                    })?;
+                if source.id == node.id || source.node_type != "DETECTOR" {
+                    return Err(IrError::FeedForwardNotDetector {
+                        node: node.id.clone(),
+                        source_node: ff.source_node.clone(),
+                    });
+                }
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Detectors each node waits for: the one whose conditional branch selects it and the
+/// feed-forward sources it reads
+pub fn detector_dependencies(graph: &Graph) -> HashMap<&str, Vec<&str>> {
+    let mut detectors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for node in &graph.nodes {
+        for branch in node.conditional_branches.iter().flatten() {
+            for target in branch
+                .then_nodes
+                .iter()
+                .chain(branch.else_nodes.iter().flatten())
+            {
+                detectors.entry(target).or_default().push(&node.id);
+            }
+        }
+        for ff in node.feed_forward.iter().flatten() {
+            detectors.entry(&node.id).or_default().push(&ff.source_node);
+        }
+    }
+    detectors
+}
+
+/// Nodes ordered after their edge inputs and [`detector_dependencies`], otherwise in list order
+pub fn execution_order(graph: &Graph) -> Result<Vec<&Node>, IrError> {
+    let index: HashMap<&str, usize> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.as_str(), i))
+        .collect();
+    let mut successors = vec![Vec::new(); graph.nodes.len()];
+    let mut pending = vec![0usize; graph.nodes.len()];
+    let detectors = detector_dependencies(graph);
+    let dependencies = graph
+        .edges
+        .iter()
+        .map(|e| (e.src_node.as_str(), e.dst_node.as_str()))
+        .chain(
+            detectors
+                .iter()
+                .flat_map(|(node, sources)| sources.iter().map(move |source| (*source, *node))),
+        );
+    for (src, dst) in dependencies {
+        if let (Some(&src), Some(&dst)) = (index.get(src), index.get(dst)) {
+            successors[src].push(dst);
+            pending[dst] += 1;
+        }
+    }
+
+    let mut ready: std::collections::BTreeSet<usize> = (0..graph.nodes.len())
+        .filter(|&i| pending[i] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(graph.nodes.len());
+    while let Some(i) = ready.pop_first() {
+        order.push(&graph.nodes[i]);
+        for &next in &successors[i] {
+            pending[next] -= 1;
+            if pending[next] == 0 {
+                ready.insert(next);
+            }
+        }
+    }
+    match pending.iter().position(|&p| p > 0) {
+        Some(stuck) => Err(IrError::DependencyCycle {
+            node: graph.nodes[stuck].id.clone(),
+        }),
+        None => Ok(order),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(json: &str) -> Graph {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_execution_order_follows_dependencies() {
+        // The detector is listed last but gates both other nodes
+        let g = graph(
+            r#"{"nodes": [
+                {"id": "fix", "type": "MZI", "params": {}},
+                {"id": "tuned", "type": "MZI", "params": {},
+                 "feed_forward": [{"param": "phase", "source_node": "det"}]},
+                {"id": "det", "type": "DETECTOR", "params": {},
+                 "conditional_branches": [{"outcome_index": 1, "then_nodes": ["fix"]}]}
+            ]}"#,
+        );
+        let order: Vec<&str> = execution_order(&g)
+            .unwrap()
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect();
+        assert_eq!(order, ["det", "fix", "tuned"]);
+
+        let mut looped = g.clone();
+        looped.edges.push(Edge {
+            src_node: "fix".to_string(),
+            src_port: None,
+            dst_node: "det".to_string(),
+            dst_port: None,
+            delay: None,
+        });
+        assert!(matches!(
+            execution_order(&looped),
+            Err(IrError::DependencyCycle { .. })
+        ));
+    }
+}
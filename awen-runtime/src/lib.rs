@@ -1,20 +1,48 @@
 // AWEN Runtime crate root
+//
+// Without any feature only `ir`, `namespace`, `error` and `plugins::reference_sim` are built: no
+// filesystem, devices or threads, so they compile to wasm32. The `simulator` feature adds the
+// engine and what it runs on; the default `native` feature adds everything else.
+#[cfg(feature = "native")]
 pub mod calibration;
+#[cfg(feature = "native")]
 pub mod chokepoint;
+#[cfg(feature = "native")]
+pub mod config;
+#[cfg(feature = "native")]
 pub mod control;
+#[cfg(feature = "simulator")]
 pub mod engine;
+#[cfg(feature = "simulator")]
 pub mod engine_v2;
+pub mod error;
+#[cfg(feature = "simulator")]
 pub mod gradients;
+#[cfg(feature = "simulator")]
 pub mod hal;
+#[cfg(feature = "simulator")]
 pub mod hal_v0;
 pub mod ir;
+pub mod namespace;
+#[cfg(feature = "simulator")]
 pub mod observability;
 pub mod plugins;
+#[cfg(feature = "simulator")]
 pub mod quantum;
+#[cfg(feature = "native")]
+pub mod queue;
+#[cfg(feature = "simulator")]
 pub mod scheduler;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "simulator")]
 pub mod state;
+#[cfg(feature = "native")]
 pub mod storage;
+#[cfg(feature = "simulator")]
+pub mod testing;
 
+#[cfg(feature = "native")]
 pub use chokepoint::*;
 
 // TODO: Implement core engine types: Engine, ExecutionPlan, StateStore, RunContext
@@ -0,0 +1,125 @@
+//! Namespaces: research groups sharing one runtime deployment.
+//!
+//! Every run, calibration and job belongs to one [`Namespace`]. Bundles record theirs as a
+//! `namespace:<name>` tag. Data of a namespace lives under [`Namespace::dir`], so groups do
+//! not see each other's artifacts. The `default` namespace keeps the layout of deployments
+//! without namespaces: its directory is the root itself.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Namespace of data and callers that name none
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Tag prefix recording a bundle's namespace
+pub const NAMESPACE_TAG_PREFIX: &str = "namespace:";
+
+/// Directory under a root holding the non-default namespaces
+pub const NAMESPACES_DIR: &str = "namespaces";
+
+/// Name of a namespace: 1 to 63 lower-case letters, digits, `-` or `_`, starting with a letter
+/// or digit, so it is safe as a path component.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Namespace(String);
+
+impl Namespace {
+    pub fn new(name: &str) -> Result<Self> {
+        let valid_char =
+            |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_';
+        let starts_well = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+        if name.len() > 63 || !starts_well || !name.chars().all(valid_char) {
+            return Err(anyhow!(
+                "invalid namespace {:?}: use 1-63 lower-case letters, digits, '-' or '_'",
+                name
+            ));
+        }
+        Ok(Self(name.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_NAMESPACE
+    }
+
+    /// Directory of this namespace under `root`: `root` itself for the default namespace,
+    /// else `<root>/namespaces/<name>`.
+    pub fn dir(&self, root: &Path) -> PathBuf {
+        if self.is_default() {
+            root.to_path_buf()
+        } else {
+            root.join(NAMESPACES_DIR).join(&self.0)
+        }
+    }
+
+    /// The `namespace:<name>` bundle tag
+    pub fn tag(&self) -> String {
+        format!("{}{}", NAMESPACE_TAG_PREFIX, self.0)
+    }
+
+    /// Namespace recorded in `tags`; the default namespace when none is.
+    pub fn from_tags(tags: &[String]) -> Self {
+        tags.iter()
+            .filter_map(|t| t.strip_prefix(NAMESPACE_TAG_PREFIX))
+            .find_map(|name| Self::new(name).ok())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for Namespace {
+    fn default() -> Self {
+        Self(DEFAULT_NAMESPACE.to_string())
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for Namespace {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> Result<Self> {
+        Self::new(&name)
+    }
+}
+
+impl From<Namespace> for String {
+    fn from(namespace: Namespace) -> Self {
+        namespace.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace_names_and_dirs() {
+        for bad in ["", "Optics", "../x", "a/b", "-lab", &"x".repeat(64)] {
+            assert!(Namespace::new(bad).is_err(), "{:?}", bad);
+        }
+        let lab = Namespace::new("quantum-optics_2").unwrap();
+        let root = Path::new("/data");
+        assert_eq!(
+            lab.dir(root),
+            Path::new("/data/namespaces/quantum-optics_2")
+        );
+        assert_eq!(Namespace::default().dir(root), root);
+
+        let tags = vec!["sweep:a".to_string(), lab.tag()];
+        assert_eq!(Namespace::from_tags(&tags), lab);
+        assert!(Namespace::from_tags(&[]).is_default());
+        assert!(serde_json::from_str::<Namespace>("\"../etc\"").is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! Chrome trace-event export of a run bundle's spans and timeline.
+//!
+//! `export_chrome_trace` reads `traces.jsonl` and `timeline.json` and writes
+//! `chrome_trace.json` in the Chrome trace-event format, which Perfetto (ui.perfetto.dev) and
+//! `chrome://tracing` open directly. Spans go to the "Traces" process, with one thread lane
+//! per root span so children nest under their parents. Timeline events go to the "Timeline"
+//! process, with one thread lane per timeline lane (`kernel`, `HAL.Channel.1`, ...).
+//! Timestamps are in microseconds.
+
+use super::{Span, TimelineEvent};
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Process id of the span lanes.
+const TRACES_PID: u32 = 1;
+
+/// Process id of the timeline lanes.
+const TIMELINE_PID: u32 = 2;
+
+/// One entry of `traceEvents`.
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: String,
+    ph: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<i64>,
+    pid: u32,
+    tid: u32,
+    args: Value,
+}
+
+impl TraceEvent {
+    /// Metadata event naming a process (`tid` 0) or thread lane.
+    fn metadata(kind: &str, pid: u32, tid: u32, name: &str) -> Self {
+        Self {
+            name: kind.to_string(),
+            cat: "__metadata".to_string(),
+            ph: "M",
+            ts: None,
+            dur: None,
+            pid,
+            tid,
+            args: json!({ "name": name }),
+        }
+    }
+}
+
+fn micros(iso: &str) -> Result<i64> {
+    Ok(DateTime::parse_from_rfc3339(iso)
+        .map_err(|e| anyhow!("invalid span timestamp {}: {}", iso, e))?
+        .timestamp_micros())
+}
+
+/// Id of the span's outermost ancestor present in `spans`.
+fn root_of<'a>(span: &'a Span, by_id: &HashMap<&str, &'a Span>) -> &'a str {
+    let mut current = span;
+    // A parent cycle can't nest; bound the walk by the number of spans.
+    for _ in 0..by_id.len() {
+        match current.parent.as_deref().and_then(|p| by_id.get(p)) {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+    &current.id
+}
+
+/// Chrome trace events for `spans` and `timeline`.
+pub fn chrome_trace_events(spans: &[Span], timeline: &[TimelineEvent]) -> Result<Value> {
+    let mut events = vec![
+        TraceEvent::metadata("process_name", TRACES_PID, 0, "Traces"),
+        TraceEvent::metadata("process_name", TIMELINE_PID, 0, "Timeline"),
+    ];
+
+    let by_id: HashMap<&str, &Span> = spans.iter().map(|s| (s.id.as_str(), s)).collect();
+    let mut span_lanes: BTreeMap<&str, u32> = BTreeMap::new();
+    for span in spans {
+        let root = root_of(span, &by_id);
+        let next = span_lanes.len() as u32 + 1;
+        let tid = *span_lanes.entry(root).or_insert_with(|| {
+            events.push(TraceEvent::metadata(
+                "thread_name",
+                TRACES_PID,
+                next,
+                &by_id[root].name,
+            ));
+            next
+        });
+        let start = micros(&span.start_iso)?;
+        let end = micros(&span.end_iso)?;
+        let mut args: BTreeMap<&str, &str> = span
+            .attributes
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        args.insert("span_id", &span.id);
+        if let Some(parent) = &span.parent {
+            args.insert("parent", parent);
+        }
+        events.push(TraceEvent {
+            name: span.name.clone(),
+            cat: "span".to_string(),
+            ph: "X",
+            ts: Some(start),
+            dur: Some((end - start).max(0)),
+            pid: TRACES_PID,
+            tid,
+            args: json!(args),
+        });
+    }
+
+    let mut timeline_lanes: BTreeMap<&str, u32> = BTreeMap::new();
+    for event in timeline {
+        let next = timeline_lanes.len() as u32 + 1;
+        let tid = *timeline_lanes.entry(&event.lane).or_insert_with(|| {
+            events.push(TraceEvent::metadata(
+                "thread_name",
+                TIMELINE_PID,
+                next,
+                &event.lane,
+            ));
+            next
+        });
+        let start = event.start_ms as i64 * 1000;
+        let end = event.end_ms as i64 * 1000;
+        let args: BTreeMap<&str, &str> = event
+            .attributes
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        events.push(TraceEvent {
+            name: event.name.clone(),
+            cat: event.lane.clone(),
+            ph: "X",
+            ts: Some(start),
+            dur: Some((end - start).max(0)),
+            pid: TIMELINE_PID,
+            tid,
+            args: json!(args),
+        });
+    }
+
+    Ok(json!({
+        "traceEvents": events,
+        "displayTimeUnit": "ms",
+    }))
+}
+
+/// Convert `out_dir`'s `traces.jsonl` and `timeline.json` (either may be missing) into
+/// `chrome_trace.json`, returning its path.
+pub fn export_chrome_trace(out_dir: &Path) -> Result<PathBuf> {
+    let traces_path = out_dir.join("traces.jsonl");
+    let spans: Vec<Span> = if traces_path.exists() {
+        std::fs::read_to_string(&traces_path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?
+    } else {
+        Vec::new()
+    };
+    let timeline_path = out_dir.join("timeline.json");
+    let timeline: Vec<TimelineEvent> = if timeline_path.exists() {
+        serde_json::from_str(&std::fs::read_to_string(&timeline_path)?)?
+    } else {
+        Vec::new()
+    };
+
+    let path = out_dir.join("chrome_trace.json");
+    std::fs::write(
+        &path,
+        serde_json::to_string(&chrome_trace_events(&spans, &timeline)?)?,
+    )?;
+    Ok(path)
+}
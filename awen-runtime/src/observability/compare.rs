@@ -0,0 +1,412 @@
+//! Comparison of two run bundles.
+//!
+//! `compare(run_a, run_b)` diffs the observability artifacts of a baseline run `a` against a
+//! candidate `b`:
+//! - metric deltas over `metrics.json` counters and gauges,
+//! - spans (`traces.jsonl`, matched by name) present in only one run,
+//! - timing regressions: spans whose total duration, or latency histograms whose p99, grew by
+//!   more than `CompareOptions::timing_threshold` (and by at least `min_timing_delta_ms`),
+//! - measurement distribution shifts: total variation distance between photon-count and
+//!   discrete shot distributions, standardized mean shift for continuous shots.
+//!
+//! `RunDiff::has_regressions` is the gate for milestone runs; the diff renders as text
+//! (`to_text`) or JSON, and `write_report` stores both next to each other.
+
+use super::{Metrics, Span};
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Thresholds of a comparison.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompareOptions {
+    /// Relative growth of a duration that counts as a regression (0.2 = 20 % slower).
+    pub timing_threshold: f64,
+    /// Absolute growth (ms) a regression must also exceed, so sub-millisecond jitter is ignored.
+    pub min_timing_delta_ms: f64,
+    /// Total variation distance, or standardized mean shift, that counts as a shift.
+    pub distribution_threshold: f64,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            timing_threshold: 0.2,
+            min_timing_delta_ms: 1.0,
+            distribution_threshold: 0.1,
+        }
+    }
+}
+
+/// A counter or gauge that differs between the runs (`None`: absent from that run).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MetricDelta {
+    pub key: String,
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+    pub delta: Option<f64>,
+}
+
+/// A span or latency histogram that got slower beyond the thresholds.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimingRegression {
+    /// Span name, or `histogram:<key>.p99`.
+    pub name: String,
+    pub a_ms: f64,
+    pub b_ms: f64,
+    pub ratio: f64,
+}
+
+/// Distance between one measurement distribution of the two runs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DistributionShift {
+    /// `measurements.photon_count`, or `shots.<label>`.
+    pub distribution: String,
+    /// `total_variation` or `mean_shift_sigma`.
+    pub statistic: String,
+    pub value: f64,
+    pub shifted: bool,
+}
+
+/// Differences of run `b` relative to baseline run `a`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunDiff {
+    pub run_a: PathBuf,
+    pub run_b: PathBuf,
+    pub options: CompareOptions,
+    pub metric_deltas: Vec<MetricDelta>,
+    pub added_spans: Vec<String>,
+    pub removed_spans: Vec<String>,
+    pub timing_regressions: Vec<TimingRegression>,
+    pub distribution_shifts: Vec<DistributionShift>,
+}
+
+/// Compare `run_b` against baseline `run_a` with default thresholds.
+pub fn compare(run_a: &Path, run_b: &Path) -> Result<RunDiff> {
+    compare_with(run_a, run_b, &CompareOptions::default())
+}
+
+/// Compare `run_b` against baseline `run_a`.
+pub fn compare_with(run_a: &Path, run_b: &Path, options: &CompareOptions) -> Result<RunDiff> {
+    let (a, b) = (RunArtifacts::load(run_a)?, RunArtifacts::load(run_b)?);
+
+    let mut metric_deltas = Vec::new();
+    for (a_values, b_values) in [
+        (&a.metrics.counters, &b.metrics.counters),
+        (&a.metrics.gauges, &b.metrics.gauges),
+    ] {
+        let keys: BTreeSet<&String> = a_values.keys().chain(b_values.keys()).collect();
+        for key in keys {
+            let (va, vb) = (a_values.get(key).copied(), b_values.get(key).copied());
+            if va != vb {
+                metric_deltas.push(MetricDelta {
+                    key: key.clone(),
+                    a: va,
+                    b: vb,
+                    delta: va.zip(vb).map(|(va, vb)| vb - va),
+                });
+            }
+        }
+    }
+
+    let (a_spans, b_spans) = (span_durations_ms(&a.spans)?, span_durations_ms(&b.spans)?);
+    let added_spans = b_spans
+        .keys()
+        .filter(|name| !a_spans.contains_key(*name))
+        .cloned()
+        .collect();
+    let removed_spans = a_spans
+        .keys()
+        .filter(|name| !b_spans.contains_key(*name))
+        .cloned()
+        .collect();
+
+    let mut timings: Vec<(String, f64, f64)> = a_spans
+        .iter()
+        .filter_map(|(name, a_ms)| b_spans.get(name).map(|b_ms| (name.clone(), *a_ms, *b_ms)))
+        .collect();
+    // Latency histograms are recorded in nanoseconds.
+    let a_histograms: BTreeMap<_, _> = a.metrics.histograms.iter().collect();
+    for (key, ha) in a_histograms {
+        if let Some(hb) = b.metrics.histograms.get(key) {
+            timings.push((format!("histogram:{}.p99", key), ha.p99 / 1e6, hb.p99 / 1e6));
+        }
+    }
+    let timing_regressions = timings
+        .into_iter()
+        .filter(|(_, a_ms, b_ms)| {
+            b_ms - a_ms > options.min_timing_delta_ms
+                && *b_ms > a_ms * (1.0 + options.timing_threshold)
+        })
+        .map(|(name, a_ms, b_ms)| TimingRegression {
+            name,
+            a_ms,
+            b_ms,
+            ratio: b_ms / a_ms.max(f64::EPSILON),
+        })
+        .collect();
+
+    let mut distribution_shifts = Vec::new();
+    if !a.photon_counts.is_empty() && !b.photon_counts.is_empty() {
+        let value = total_variation(&a.photon_counts, &b.photon_counts);
+        distribution_shifts.push(DistributionShift {
+            distribution: "measurements.photon_count".to_string(),
+            statistic: "total_variation".to_string(),
+            value,
+            shifted: value > options.distribution_threshold,
+        });
+    }
+    for (label, shots_a) in &a.shots {
+        let Some(shots_b) = b.shots.get(label) else {
+            continue;
+        };
+        let (statistic, value) = match (shots_a, shots_b) {
+            (ShotValues::Discrete(da), ShotValues::Discrete(db)) => {
+                ("total_variation", total_variation(da, db))
+            }
+            (ShotValues::Continuous(ca), ShotValues::Continuous(cb)) => {
+                ("mean_shift_sigma", mean_shift_sigma(ca, cb))
+            }
+            _ => {
+                return Err(anyhow!(
+                    "shots label {} is discrete in one run and continuous in the other",
+                    label
+                ))
+            }
+        };
+        distribution_shifts.push(DistributionShift {
+            distribution: format!("shots.{}", label),
+            statistic: statistic.to_string(),
+            value,
+            shifted: value > options.distribution_threshold,
+        });
+    }
+
+    Ok(RunDiff {
+        run_a: run_a.to_path_buf(),
+        run_b: run_b.to_path_buf(),
+        options: options.clone(),
+        metric_deltas,
+        added_spans,
+        removed_spans,
+        timing_regressions,
+        distribution_shifts,
+    })
+}
+
+impl RunDiff {
+    /// Whether `b` regressed: any timing regression or shifted distribution.
+    pub fn has_regressions(&self) -> bool {
+        !self.timing_regressions.is_empty() || self.distribution_shifts.iter().any(|d| d.shifted)
+    }
+
+    /// Human-readable report.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "run comparison: {} -> {}",
+            self.run_a.display(),
+            self.run_b.display()
+        );
+        let _ = writeln!(
+            out,
+            "verdict: {}",
+            if self.has_regressions() {
+                "REGRESSION"
+            } else {
+                "ok"
+            }
+        );
+        let _ = writeln!(
+            out,
+            "\ntiming regressions ({}):",
+            self.timing_regressions.len()
+        );
+        for t in &self.timing_regressions {
+            let _ = writeln!(
+                out,
+                "  {}: {:.3} ms -> {:.3} ms (x{:.2})",
+                t.name, t.a_ms, t.b_ms, t.ratio
+            );
+        }
+        let _ = writeln!(
+            out,
+            "\ndistribution shifts ({}):",
+            self.distribution_shifts.len()
+        );
+        for d in &self.distribution_shifts {
+            let _ = writeln!(
+                out,
+                "  {}: {} = {:.4}{}",
+                d.distribution,
+                d.statistic,
+                d.value,
+                if d.shifted { "  SHIFTED" } else { "" }
+            );
+        }
+        let _ = writeln!(
+            out,
+            "\nspans: {} added, {} removed",
+            self.added_spans.len(),
+            self.removed_spans.len()
+        );
+        for name in &self.added_spans {
+            let _ = writeln!(out, "  + {}", name);
+        }
+        for name in &self.removed_spans {
+            let _ = writeln!(out, "  - {}", name);
+        }
+        let _ = writeln!(out, "\nmetric deltas ({}):", self.metric_deltas.len());
+        let show = |v: Option<f64>| v.map_or("-".to_string(), |v| v.to_string());
+        for m in &self.metric_deltas {
+            let _ = writeln!(out, "  {}: {} -> {}", m.key, show(m.a), show(m.b));
+        }
+        out
+    }
+
+    /// Write `run_diff.json` and `run_diff.txt` into `out_dir`, returning their paths.
+    pub fn write_report(&self, out_dir: &Path) -> Result<(PathBuf, PathBuf)> {
+        let json = out_dir.join("run_diff.json");
+        let text = out_dir.join("run_diff.txt");
+        std::fs::write(&json, serde_json::to_string_pretty(self)?)?;
+        std::fs::write(&text, self.to_text())?;
+        Ok((json, text))
+    }
+}
+
+enum ShotValues {
+    Discrete(BTreeMap<u64, usize>),
+    Continuous(Vec<f64>),
+}
+
+/// The artifacts a comparison reads; each is empty when the bundle lacks it.
+struct RunArtifacts {
+    metrics: Metrics,
+    spans: Vec<Span>,
+    photon_counts: BTreeMap<u64, usize>,
+    shots: BTreeMap<String, ShotValues>,
+}
+
+impl RunArtifacts {
+    fn load(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            return Err(anyhow!("run bundle {} not found", dir.display()));
+        }
+        let read = |name: &str| -> Result<Option<String>> {
+            let path = dir.join(name);
+            Ok(if path.exists() {
+                Some(std::fs::read_to_string(path)?)
+            } else {
+                None
+            })
+        };
+        let metrics = match read("metrics.json")? {
+            Some(data) => serde_json::from_str(&data)?,
+            None => Metrics::default(),
+        };
+        let spans = match read("traces.jsonl")? {
+            Some(data) => data
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<std::result::Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let mut photon_counts = BTreeMap::new();
+        if let Some(data) = read("measurements.json")? {
+            let outcomes: BTreeMap<String, Value> = serde_json::from_str(&data)?;
+            for outcome in outcomes.values() {
+                if outcome["quadratures"].is_null() {
+                    let n = outcome["photon_count"].as_u64().unwrap_or(0);
+                    *photon_counts.entry(n).or_default() += 1;
+                }
+            }
+        }
+        let mut shots = BTreeMap::new();
+        if let Some(data) = read("shots.json")? {
+            let record: Value = serde_json::from_str(&data)?;
+            let records = record["shots"].as_array().cloned().unwrap_or_default();
+            for (i, label) in record["labels"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                let values: Vec<&Value> = records.iter().filter_map(|s| s.get(i)).collect();
+                let discrete: Vec<u64> = values
+                    .iter()
+                    .filter_map(|v| v["DiscreteOutcome"].as_u64())
+                    .collect();
+                let label = label.as_str().unwrap_or_default().to_string();
+                if discrete.is_empty() {
+                    let continuous = values
+                        .iter()
+                        .filter_map(|v| v["ContinuousValue"].as_f64())
+                        .collect();
+                    shots.insert(label, ShotValues::Continuous(continuous));
+                } else {
+                    let mut counts = BTreeMap::new();
+                    for n in discrete {
+                        *counts.entry(n).or_default() += 1;
+                    }
+                    shots.insert(label, ShotValues::Discrete(counts));
+                }
+            }
+        }
+        Ok(Self {
+            metrics,
+            spans,
+            photon_counts,
+            shots,
+        })
+    }
+}
+
+/// Total duration (ms) of the spans of each name.
+fn span_durations_ms(spans: &[Span]) -> Result<BTreeMap<String, f64>> {
+    let mut durations = BTreeMap::new();
+    for span in spans {
+        let parse = |iso: &str| {
+            DateTime::parse_from_rfc3339(iso)
+                .map_err(|e| anyhow!("invalid span timestamp {}: {}", iso, e))
+        };
+        let elapsed = parse(&span.end_iso)? - parse(&span.start_iso)?;
+        let ms = elapsed.num_microseconds().unwrap_or(0).max(0) as f64 / 1e3;
+        *durations.entry(span.name.clone()).or_insert(0.0) += ms;
+    }
+    Ok(durations)
+}
+
+/// Total variation distance between two count histograms (0 = identical, 1 = disjoint).
+fn total_variation(a: &BTreeMap<u64, usize>, b: &BTreeMap<u64, usize>) -> f64 {
+    let (na, nb) = (
+        a.values().sum::<usize>() as f64,
+        b.values().sum::<usize>() as f64,
+    );
+    let keys: BTreeSet<&u64> = a.keys().chain(b.keys()).collect();
+    0.5 * keys
+        .into_iter()
+        .map(|k| {
+            let pa = a.get(k).copied().unwrap_or(0) as f64 / na;
+            let pb = b.get(k).copied().unwrap_or(0) as f64 / nb;
+            (pa - pb).abs()
+        })
+        .sum::<f64>()
+}
+
+/// `|μ_a − μ_b|` in units of the pooled standard deviation.
+fn mean_shift_sigma(a: &[f64], b: &[f64]) -> f64 {
+    let moments = |x: &[f64]| {
+        let n = x.len().max(1) as f64;
+        let mean = x.iter().sum::<f64>() / n;
+        (mean, x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n)
+    };
+    let ((ma, va), (mb, vb)) = (moments(a), moments(b));
+    let pooled = ((va + vb) / 2.0).sqrt();
+    (ma - mb).abs() / pooled.max(f64::EPSILON)
+}
@@ -0,0 +1,147 @@
+//! Stable codes for classes of runtime failure.
+//!
+//! Error and fatal [`EventRecord`](super::EventRecord)s and failed node execution logs carry an
+//! [`ErrorCode`] such as `AWEN-COH-002`, so tooling can alert on a failure class without
+//! matching message text. Codes are serialized as their string form and are never renumbered
+//! or reused; new classes get the next number.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Event attribute / log field name holding the code.
+pub const ERROR_CODE_ATTRIBUTE: &str = "error_code";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// The IR graph failed validation (undefined nodes, bad edges, malformed fields).
+    #[serde(rename = "AWEN-IR-001")]
+    IrValidation,
+    /// A node ran outside its coherence window or exhausted the coherence budget.
+    #[serde(rename = "AWEN-COH-002")]
+    CoherenceViolation,
+    /// A parameter hit a safety limit and was clamped or rejected.
+    #[serde(rename = "AWEN-SAF-003")]
+    SafetyClamp,
+    /// No execution plan satisfies the scheduling constraints.
+    #[serde(rename = "AWEN-SCH-004")]
+    SchedulingFailure,
+    /// A calibration run failed or did not converge.
+    #[serde(rename = "AWEN-CAL-005")]
+    CalibrationFailure,
+    /// A HAL device reported a fault or rejected an operation.
+    #[serde(rename = "AWEN-HAL-006")]
+    DeviceFault,
+    /// A measurement could not be taken or recorded.
+    #[serde(rename = "AWEN-MEA-007")]
+    MeasurementFailure,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 7] = [
+        ErrorCode::IrValidation,
+        ErrorCode::CoherenceViolation,
+        ErrorCode::SafetyClamp,
+        ErrorCode::SchedulingFailure,
+        ErrorCode::CalibrationFailure,
+        ErrorCode::DeviceFault,
+        ErrorCode::MeasurementFailure,
+    ];
+
+    /// The stable code, e.g. `AWEN-IR-001`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorCode::IrValidation => "AWEN-IR-001",
+            ErrorCode::CoherenceViolation => "AWEN-COH-002",
+            ErrorCode::SafetyClamp => "AWEN-SAF-003",
+            ErrorCode::SchedulingFailure => "AWEN-SCH-004",
+            ErrorCode::CalibrationFailure => "AWEN-CAL-005",
+            ErrorCode::DeviceFault => "AWEN-HAL-006",
+            ErrorCode::MeasurementFailure => "AWEN-MEA-007",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ErrorCode::IrValidation => "IR validation failure",
+            ErrorCode::CoherenceViolation => "coherence violation",
+            ErrorCode::SafetyClamp => "safety clamp",
+            ErrorCode::SchedulingFailure => "scheduling failure",
+            ErrorCode::CalibrationFailure => "calibration failure",
+            ErrorCode::DeviceFault => "device fault",
+            ErrorCode::MeasurementFailure => "measurement failure",
+        }
+    }
+
+    /// Code from its string form.
+    pub fn parse(code: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.code().eq_ignore_ascii_case(code.trim()))
+            .ok_or_else(|| anyhow!("unknown error code {:?}", code))
+    }
+
+    /// Best-effort class of an error message, for errors raised without a code. Checked in
+    /// order: coherence, safety, IR validation, scheduling, calibration, device, measurement.
+    pub fn classify(message: &str) -> Option<Self> {
+        let message = message.to_ascii_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| message.contains(n));
+        if has(&["coherence", "decoher"]) {
+            Some(ErrorCode::CoherenceViolation)
+        } else if has(&["safety", "clamp", "interlock"]) {
+            Some(ErrorCode::SafetyClamp)
+        } else if has(&["validation", "undefined node", "not defined", "invalid ir"]) {
+            Some(ErrorCode::IrValidation)
+        } else if has(&["schedul", "deadline"]) {
+            Some(ErrorCode::SchedulingFailure)
+        } else if has(&["calibrat"]) {
+            Some(ErrorCode::CalibrationFailure)
+        } else if has(&["device", "hal:"]) {
+            Some(ErrorCode::DeviceFault)
+        } else if has(&["measurement", "detector"]) {
+            Some(ErrorCode::MeasurementFailure)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_round_trip_and_classify() {
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::parse(code.code()).unwrap(), code);
+            let json = serde_json::to_string(&code).unwrap();
+            assert_eq!(json, format!("\"{}\"", code.code()));
+            assert_eq!(serde_json::from_str::<ErrorCode>(&json).unwrap(), code);
+        }
+        assert!(ErrorCode::parse("AWEN-XXX-999").is_err());
+
+        assert_eq!(
+            ErrorCode::classify("Coherence budget exceeded: need 5 ns, have 1 ns"),
+            Some(ErrorCode::CoherenceViolation)
+        );
+        assert_eq!(
+            ErrorCode::classify("Safety: parameter power exceeds limit (150)"),
+            Some(ErrorCode::SafetyClamp)
+        );
+        assert_eq!(
+            ErrorCode::classify("Edge references undefined node: n9"),
+            Some(ErrorCode::IrValidation)
+        );
+        assert_eq!(
+            ErrorCode::classify("device faulty"),
+            Some(ErrorCode::DeviceFault)
+        );
+        assert_eq!(ErrorCode::classify("something odd"), None);
+    }
+}
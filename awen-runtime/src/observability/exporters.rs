@@ -0,0 +1,282 @@
+//! Registry of named observability exporters.
+//!
+//! Plugins register factories for additional [`Tracer`], [`MetricsSink`] and
+//! [`TimelineBuilder`] implementations (a Kafka producer, an InfluxDB writer) under a name; an
+//! [`ExporterConfig`] then selects them by name with per-exporter string options, so
+//! site-specific sinks are added without touching this module. The built-in `file` exporter
+//! ([`FileExporter`], option `out_dir`) is always available in [`GLOBAL_EXPORTER_REGISTRY`].
+
+use super::{FileExporter, MetricsSink, TimelineBuilder, TimelineEvent, Tracer};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Per-exporter options, e.g. `brokers` or `out_dir`.
+pub type ExporterOptions = BTreeMap<String, String>;
+
+pub type TracerFactory = Arc<dyn Fn(&ExporterOptions) -> Result<Arc<dyn Tracer>> + Send + Sync>;
+pub type MetricsSinkFactory =
+    Arc<dyn Fn(&ExporterOptions) -> Result<Arc<dyn MetricsSink>> + Send + Sync>;
+pub type TimelineBuilderFactory =
+    Arc<dyn Fn(&ExporterOptions) -> Result<Arc<dyn TimelineBuilder>> + Send + Sync>;
+
+/// One exporter selected by name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ExporterSelection {
+    pub name: String,
+    #[serde(default)]
+    pub options: ExporterOptions,
+}
+
+impl ExporterSelection {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            options: ExporterOptions::new(),
+        }
+    }
+
+    pub fn with_option(mut self, key: &str, value: &str) -> Self {
+        self.options.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Exporters to build for a run, per kind.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ExporterConfig {
+    #[serde(default)]
+    pub tracers: Vec<ExporterSelection>,
+    #[serde(default)]
+    pub metrics: Vec<ExporterSelection>,
+    #[serde(default)]
+    pub timelines: Vec<ExporterSelection>,
+}
+
+/// Exporters built from an [`ExporterConfig`]. Metrics and timeline events fan out to every
+/// selected sink.
+#[derive(Debug, Default, Clone)]
+pub struct Exporters {
+    pub tracers: Vec<Arc<dyn Tracer>>,
+    pub metrics: Vec<Arc<dyn MetricsSink>>,
+    pub timelines: Vec<Arc<dyn TimelineBuilder>>,
+}
+
+impl MetricsSink for Exporters {
+    fn record_counter(&self, key: &str, value: f64) {
+        for sink in &self.metrics {
+            sink.record_counter(key, value);
+        }
+    }
+
+    fn record_gauge(&self, key: &str, value: f64) {
+        for sink in &self.metrics {
+            sink.record_gauge(key, value);
+        }
+    }
+}
+
+impl TimelineBuilder for Exporters {
+    fn add_event(&self, ev: TimelineEvent) {
+        for builder in &self.timelines {
+            builder.add_event(ev.clone());
+        }
+    }
+}
+
+/// Exporter factories keyed by name, one namespace per kind.
+#[derive(Default)]
+pub struct ExporterRegistry {
+    tracers: RwLock<HashMap<String, TracerFactory>>,
+    metrics: RwLock<HashMap<String, MetricsSinkFactory>>,
+    timelines: RwLock<HashMap<String, TimelineBuilderFactory>>,
+}
+
+impl fmt::Debug for ExporterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExporterRegistry")
+            .field("tracers", &self.tracer_names())
+            .field("metrics", &self.metrics_sink_names())
+            .field("timelines", &self.timeline_builder_names())
+            .finish()
+    }
+}
+
+fn insert<F>(map: &RwLock<HashMap<String, F>>, kind: &str, name: &str, factory: F) -> Result<()> {
+    let mut w = map.write().unwrap();
+    if w.contains_key(name) {
+        return Err(anyhow!("{} exporter {} already registered", kind, name));
+    }
+    w.insert(name.to_string(), factory);
+    Ok(())
+}
+
+fn names<F>(map: &RwLock<HashMap<String, F>>) -> Vec<String> {
+    let mut names: Vec<String> = map.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+fn build<F: Clone, T>(
+    map: &RwLock<HashMap<String, F>>,
+    kind: &str,
+    selections: &[ExporterSelection],
+    make: impl Fn(&F, &ExporterOptions) -> Result<T>,
+) -> Result<Vec<T>> {
+    selections
+        .iter()
+        .map(|sel| {
+            let factory = map
+                .read()
+                .unwrap()
+                .get(&sel.name)
+                .cloned()
+                .ok_or_else(|| anyhow!("unknown {} exporter {}", kind, sel.name))?;
+            make(&factory, &sel.options)
+                .map_err(|e| anyhow!("failed to build {} exporter {}: {}", kind, sel.name, e))
+        })
+        .collect()
+}
+
+impl ExporterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_tracer(&self, name: &str, factory: TracerFactory) -> Result<()> {
+        insert(&self.tracers, "tracer", name, factory)
+    }
+
+    pub fn register_metrics_sink(&self, name: &str, factory: MetricsSinkFactory) -> Result<()> {
+        insert(&self.metrics, "metrics", name, factory)
+    }
+
+    pub fn register_timeline_builder(
+        &self,
+        name: &str,
+        factory: TimelineBuilderFactory,
+    ) -> Result<()> {
+        insert(&self.timelines, "timeline", name, factory)
+    }
+
+    pub fn tracer_names(&self) -> Vec<String> {
+        names(&self.tracers)
+    }
+
+    pub fn metrics_sink_names(&self) -> Vec<String> {
+        names(&self.metrics)
+    }
+
+    pub fn timeline_builder_names(&self) -> Vec<String> {
+        names(&self.timelines)
+    }
+
+    /// Build every exporter `config` selects; fails on the first unknown name or factory error.
+    pub fn build(&self, config: &ExporterConfig) -> Result<Exporters> {
+        Ok(Exporters {
+            tracers: build(&self.tracers, "tracer", &config.tracers, |f, o| f(o))?,
+            metrics: build(&self.metrics, "metrics", &config.metrics, |f, o| f(o))?,
+            timelines: build(&self.timelines, "timeline", &config.timelines, |f, o| f(o))?,
+        })
+    }
+}
+
+fn file_exporter(options: &ExporterOptions) -> Result<Arc<FileExporter>> {
+    let out_dir = options
+        .get("out_dir")
+        .ok_or_else(|| anyhow!("file exporter requires option out_dir"))?;
+    Ok(Arc::new(FileExporter::new(out_dir)))
+}
+
+/// Register the built-in `file` exporter for every kind.
+pub fn register_default_exporters(registry: &ExporterRegistry) -> Result<()> {
+    registry.register_tracer(
+        "file",
+        Arc::new(|o| Ok(file_exporter(o)? as Arc<dyn Tracer>)),
+    )?;
+    registry.register_metrics_sink(
+        "file",
+        Arc::new(|o| Ok(file_exporter(o)? as Arc<dyn MetricsSink>)),
+    )?;
+    registry.register_timeline_builder(
+        "file",
+        Arc::new(|o| Ok(file_exporter(o)? as Arc<dyn TimelineBuilder>)),
+    )
+}
+
+/// Global registry plugins register into. Initialized lazily with the default exporters.
+pub static GLOBAL_EXPORTER_REGISTRY: Lazy<Arc<ExporterRegistry>> = Lazy::new(|| {
+    let registry = ExporterRegistry::new();
+    register_default_exporters(&registry).expect("default exporters register once");
+    Arc::new(registry)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Collecting {
+        gauges: Mutex<Vec<(String, f64)>>,
+    }
+
+    impl MetricsSink for Collecting {
+        fn record_counter(&self, _key: &str, _value: f64) {}
+        fn record_gauge(&self, key: &str, value: f64) {
+            self.gauges.lock().unwrap().push((key.to_string(), value));
+        }
+    }
+
+    #[test]
+    fn test_registered_exporters_selected_by_config() {
+        let registry = ExporterRegistry::new();
+        register_default_exporters(&registry).unwrap();
+        let sink = Arc::new(Collecting::default());
+        let shared = sink.clone();
+        registry
+            .register_metrics_sink(
+                "influx",
+                Arc::new(move |o| {
+                    assert_eq!(o["bucket"], "lab");
+                    Ok(shared.clone() as Arc<dyn MetricsSink>)
+                }),
+            )
+            .unwrap();
+        assert!(registry
+            .register_metrics_sink("influx", Arc::new(|_| Err(anyhow!("dup"))))
+            .is_err());
+        assert_eq!(registry.metrics_sink_names(), vec!["file", "influx"]);
+
+        let config: ExporterConfig = serde_json::from_str(
+            r#"{"tracers": [{"name": "file", "options": {"out_dir": "/tmp"}}],
+                "metrics": [{"name": "influx", "options": {"bucket": "lab"}}]}"#,
+        )
+        .unwrap();
+        let exporters = registry.build(&config).unwrap();
+        assert_eq!(exporters.tracers.len(), 1);
+        assert!(exporters.timelines.is_empty());
+        exporters.record_gauge("power", 1.5);
+        assert_eq!(
+            *sink.gauges.lock().unwrap(),
+            vec![("power".to_string(), 1.5)]
+        );
+
+        let unknown = ExporterConfig {
+            timelines: vec![ExporterSelection::new("kafka")],
+            ..ExporterConfig::default()
+        };
+        assert!(registry.build(&unknown).is_err());
+        let missing_option = ExporterConfig {
+            tracers: vec![ExporterSelection::new("file")],
+            ..ExporterConfig::default()
+        };
+        assert!(registry.build(&missing_option).is_err());
+        assert!(GLOBAL_EXPORTER_REGISTRY
+            .tracer_names()
+            .contains(&"file".to_string()));
+    }
+}
@@ -0,0 +1,194 @@
+//! Bucketed histograms with percentile aggregation.
+//!
+//! A [`Histogram`] counts observations into fixed buckets (upper bounds, plus an implicit
+//! overflow bucket) and tracks count, sum, min and max, so memory stays constant however many
+//! points are recorded. Percentiles are interpolated linearly within the bucket holding the
+//! requested rank, clamped to the observed min/max. [`HistogramSummary`] is the form exported
+//! under `histograms` in metrics.json.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Bucketed distribution of `f64` observations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+/// One bucket of a [`HistogramSummary`]: observations `<= le` and above the previous bound
+/// (`le: None` is the overflow bucket).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistogramBucket {
+    pub le: Option<f64>,
+    pub count: u64,
+}
+
+/// Exported view of a histogram: totals, percentiles and per-bucket counts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistogramSummary {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+impl Histogram {
+    /// Histogram with the given bucket upper bounds, which must be finite and strictly
+    /// increasing.
+    pub fn new(bounds: Vec<f64>) -> Result<Self> {
+        if bounds.is_empty() {
+            return Err(anyhow!("histogram needs at least one bucket bound"));
+        }
+        if bounds.iter().any(|b| !b.is_finite()) || bounds.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(anyhow!(
+                "histogram bounds must be finite and strictly increasing, got {:?}",
+                bounds
+            ));
+        }
+        let counts = vec![0; bounds.len() + 1];
+        Ok(Self {
+            bounds,
+            counts,
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        })
+    }
+
+    /// `count` bounds `start, start·factor, start·factor², ...`.
+    pub fn exponential(start: f64, factor: f64, count: usize) -> Result<Self> {
+        if start <= 0.0 || factor <= 1.0 {
+            return Err(anyhow!(
+                "exponential buckets need start > 0 and factor > 1, got {} and {}",
+                start,
+                factor
+            ));
+        }
+        Self::new((0..count).map(|i| start * factor.powi(i as i32)).collect())
+    }
+
+    /// Buckets for latencies in nanoseconds: 1 µs to ~1 s in ×2 steps.
+    pub fn latency_ns() -> Self {
+        Self::exponential(1_000.0, 2.0, 21).expect("valid latency buckets")
+    }
+
+    /// Record one observation; non-finite values are ignored.
+    pub fn record(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let bucket = self.bounds.partition_point(|b| *b < value);
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn bounds(&self) -> &[f64] {
+        &self.bounds
+    }
+
+    /// Value at quantile `q` (0..=1); `None` while empty.
+    pub fn percentile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = q.clamp(0.0, 1.0) * self.count as f64;
+        if rank <= 0.0 {
+            return Some(self.min);
+        }
+        let mut below = 0u64;
+        for (i, &in_bucket) in self.counts.iter().enumerate() {
+            if in_bucket == 0 || ((below + in_bucket) as f64) < rank {
+                below += in_bucket;
+                continue;
+            }
+            let lower = if i == 0 {
+                self.min
+            } else {
+                self.bounds[i - 1].max(self.min)
+            };
+            let upper = self
+                .bounds
+                .get(i)
+                .copied()
+                .unwrap_or(self.max)
+                .min(self.max);
+            let fraction = (rank - below as f64) / in_bucket as f64;
+            return Some(lower + (upper - lower) * fraction);
+        }
+        Some(self.max)
+    }
+
+    /// Totals, p50/p95/p99 and bucket counts; `None` while empty.
+    pub fn summary(&self) -> Option<HistogramSummary> {
+        Some(HistogramSummary {
+            count: self.count,
+            sum: self.sum,
+            min: self.min,
+            max: self.max,
+            p50: self.percentile(0.5)?,
+            p95: self.percentile(0.95)?,
+            p99: self.percentile(0.99)?,
+            buckets: self
+                .counts
+                .iter()
+                .enumerate()
+                .map(|(i, count)| HistogramBucket {
+                    le: self.bounds.get(i).copied(),
+                    count: *count,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_interpolate_within_buckets() {
+        let mut histogram = Histogram::new(vec![10.0, 20.0, 30.0, 40.0]).unwrap();
+        for v in 1..=40 {
+            histogram.record(v as f64);
+        }
+        assert_eq!(histogram.count(), 40);
+        let summary = histogram.summary().unwrap();
+        assert!((summary.p50 - 20.0).abs() < 1e-9);
+        assert!((summary.p95 - 38.0).abs() < 1e-9);
+        assert!(summary.p99 <= summary.max);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(
+            summary.buckets.iter().map(|b| b.count).collect::<Vec<_>>(),
+            [10, 10, 10, 10, 0]
+        );
+        assert_eq!(summary.buckets[4].le, None);
+
+        // Overflow observations are bounded by the largest value seen.
+        histogram.record(1_000.0);
+        assert_eq!(histogram.percentile(1.0), Some(1_000.0));
+        assert!(Histogram::new(vec![2.0, 1.0]).is_err());
+        assert!(Histogram::new(vec![]).is_err());
+        assert!(Histogram::latency_ns().summary().is_none());
+    }
+}
@@ -0,0 +1,102 @@
+//! Level filtering of `EventSink` records.
+//!
+//! A [`LogFilter`] holds a minimum [`Level`] plus per-source overrides, written in env_logger
+//! syntax: comma-separated directives, each either a bare level (the default) or
+//! `source=level`. A source directive covers that source and its children (`hal` covers
+//! `hal.health`); the longest matching source wins. `EventSink::new` reads the filter from
+//! `AWEN_LOG`, e.g. `AWEN_LOG=info,hal=warn,hal.interlock=trace`; unset, every level is kept.
+
+use super::Level;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Environment variable holding the event filter spec.
+pub const LOG_ENV_VAR: &str = "AWEN_LOG";
+
+/// Minimum event levels, by source.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    pub default: Level,
+    pub sources: BTreeMap<String, Level>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self::new(Level::Trace)
+    }
+}
+
+impl Level {
+    /// Parse a level name (`trace`, `debug`, `info`, `warn`/`warning`, `error`, `fatal`).
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "trace" => Ok(Level::Trace),
+            "debug" => Ok(Level::Debug),
+            "info" => Ok(Level::Info),
+            "warn" | "warning" => Ok(Level::Warning),
+            "error" => Ok(Level::Error),
+            "fatal" => Ok(Level::Fatal),
+            other => Err(anyhow!(
+                "unknown log level {:?} (expected trace, debug, info, warn, error or fatal)",
+                other
+            )),
+        }
+    }
+}
+
+impl LogFilter {
+    /// Keep `default` and above from every source.
+    pub fn new(default: Level) -> Self {
+        Self {
+            default,
+            sources: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_source(mut self, source: &str, level: Level) -> Self {
+        self.sources.insert(source.to_string(), level);
+        self
+    }
+
+    /// Parse an env_logger-style spec such as `warn,hal=debug`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut filter = Self::default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((source, level)) => {
+                    filter
+                        .sources
+                        .insert(source.trim().to_string(), Level::parse(level)?);
+                }
+                None => filter.default = Level::parse(directive)?,
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Filter from `AWEN_LOG`; keeps everything when it is unset or malformed.
+    pub fn from_env() -> Self {
+        std::env::var(LOG_ENV_VAR)
+            .ok()
+            .and_then(|spec| Self::parse(&spec).ok())
+            .unwrap_or_default()
+    }
+
+    /// Minimum level kept for `source`.
+    pub fn level_for(&self, source: &str) -> Level {
+        self.sources
+            .iter()
+            .filter(|(prefix, _)| {
+                source
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', ':']))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, level: Level, source: &str) -> bool {
+        level >= self.level_for(source)
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::Result;
 use chrono::Utc;
+use retention::SpanStore;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::fs;
 use std::io::Write;
@@ -9,6 +10,36 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+mod chrome;
+mod compare;
+mod error_code;
+mod exporters;
+mod histogram;
+mod log_filter;
+mod report;
+mod retention;
+mod sampling;
+mod stream;
+mod writer;
+pub use chrome::{chrome_trace_events, export_chrome_trace};
+pub use compare::{
+    compare, compare_with, CompareOptions, DistributionShift, MetricDelta, RunDiff,
+    TimingRegression,
+};
+pub use error_code::{ErrorCode, ERROR_CODE_ATTRIBUTE};
+pub use exporters::{
+    register_default_exporters, ExporterConfig, ExporterOptions, ExporterRegistry,
+    ExporterSelection, Exporters, MetricsSinkFactory, TimelineBuilderFactory, TracerFactory,
+    GLOBAL_EXPORTER_REGISTRY,
+};
+pub use histogram::{Histogram, HistogramBucket, HistogramSummary};
+pub use log_filter::{LogFilter, LOG_ENV_VAR};
+pub use report::render_report;
+pub use retention::RetentionPolicy;
+pub use sampling::{SamplingConfig, SamplingPolicy, TimelineSampler};
+pub use stream::{EventStreamer, StreamMessage};
+pub use writer::{bundle_sizes, ArtifactWriter, DEFAULT_WRITER_CAPACITY};
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Span {
     pub id: String,
@@ -32,6 +63,43 @@ pub struct TimelineEvent {
 pub struct Metrics {
     pub counters: HashMap<String, f64>,
     pub gauges: HashMap<String, f64>,
+    #[serde(default)]
+    pub histograms: HashMap<String, HistogramSummary>,
+    /// Gauges sampled over run time, oldest first.
+    #[serde(default)]
+    pub series: HashMap<String, Vec<GaugeSample>>,
+}
+
+/// One point of a gauge time series: the value at run time `t_ns`, taken at the boundary of
+/// node `node_id` when there is one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GaugeSample {
+    pub t_ns: u64,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+}
+
+impl Metrics {
+    /// Export `histogram` under `key`; empty histograms are skipped.
+    pub fn record_histogram(&mut self, key: &str, histogram: &Histogram) {
+        if let Some(summary) = histogram.summary() {
+            self.histograms.insert(key.to_string(), summary);
+        }
+    }
+
+    /// Append a sample to the `key` series; the gauge itself holds the latest value.
+    pub fn record_sample(&mut self, key: &str, t_ns: u64, value: f64, node_id: Option<&str>) {
+        self.series
+            .entry(key.to_string())
+            .or_default()
+            .push(GaugeSample {
+                t_ns,
+                value,
+                node_id: node_id.map(str::to_string),
+            });
+        self.gauges.insert(key.to_string(), value);
+    }
 }
 
 /// Core runtime-facing traits for observability. Implementations (exporters) must provide these
@@ -213,7 +281,7 @@ pub fn emit_metric(_name: &str, _value: f64) {
 }
 
 // Compatibility layer: a higher-level ObservabilityContext used by older tests
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum Level {
     Trace,
@@ -246,6 +314,9 @@ pub struct EventRecord {
     pub source: String,
     pub message: String,
     pub attributes: HashMap<String, String>,
+    /// Failure class of error and fatal events, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -263,48 +334,92 @@ pub mod timeline {
         pub const SCHEDULER: &str = "Scheduler";
         pub const CONTROL: &str = "Control";
         pub const STORAGE: &str = "Storage";
+        pub const COHERENCE: &str = "Coherence";
 
         pub fn hal_channel(i: u32) -> String {
             format!("HAL.Channel.{}", i)
         }
+
+        /// Device-wide HAL operations of `device_id`.
+        pub fn hal_device(device_id: &str) -> String {
+            format!("HAL.Device.{}", device_id)
+        }
+
+        /// A scheduled resource of `device_id`, e.g. `HAL.Device.simulated.wavelength.1550nm`.
+        pub fn hal_resource(device_id: &str, resource_type: &str, resource_id: &str) -> String {
+            format!("HAL.Device.{}.{}.{}", device_id, resource_type, resource_id)
+        }
     }
 }
 
 /// Simple thread-safe tracer compatible with tests
 #[derive(Clone)]
 pub struct TracerHandle {
-    inner: Arc<Mutex<Vec<Span>>>,
+    inner: Arc<Mutex<SpanStore>>,
 }
 
 pub struct SpanHandle {
-    inner: Arc<Mutex<Vec<Span>>>,
-    idx: usize,
+    inner: Arc<Mutex<SpanStore>>,
+    seq: u64,
+    id: String,
 }
 
 impl TracerHandle {
     pub fn new() -> Self {
+        Self::with_retention(RetentionPolicy::default())
+    }
+    /// Tracer whose memory is bounded by `retention` (see [`RetentionPolicy`]).
+    pub fn with_retention(retention: RetentionPolicy) -> Self {
         Self {
-            inner: Arc::new(Mutex::new(Vec::new())),
+            inner: Arc::new(Mutex::new(SpanStore::with_retention(retention))),
         }
     }
     pub fn start_span(&self, name: &str) -> SpanHandle {
+        self.start_span_under(name, None)
+    }
+    fn start_span_under(&self, name: &str, parent: Option<&str>) -> SpanHandle {
         let mut guard = self.inner.lock().unwrap();
-        let sp = Span {
-            id: format!("span-{}", name),
-            parent: None,
-            name: name.to_string(),
-            start_iso: Utc::now().to_rfc3339(),
-            end_iso: Utc::now().to_rfc3339(),
-            attributes: HashMap::new(),
-        };
-        guard.push(sp);
+        let mut id = String::new();
+        let seq = guard.push(|seq| {
+            id = format!("span-{}-{}", name, seq);
+            Span {
+                id: id.clone(),
+                parent: parent.map(str::to_string),
+                name: name.to_string(),
+                start_iso: Utc::now().to_rfc3339(),
+                end_iso: Utc::now().to_rfc3339(),
+                attributes: HashMap::new(),
+            }
+        });
         SpanHandle {
             inner: Arc::clone(&self.inner),
-            idx: guard.len() - 1,
+            seq,
+            id,
         }
     }
+    /// Spans still held in memory, in start order.
     pub fn spans(&self) -> Vec<Span> {
-        self.inner.lock().unwrap().clone()
+        self.inner.lock().unwrap().spans()
+    }
+    /// Spans evicted by the retention policy so far.
+    pub fn evicted(&self) -> u64 {
+        self.inner.lock().unwrap().evicted()
+    }
+    /// Segment files evicted spans were flushed to, oldest first.
+    pub fn segments(&self) -> Vec<PathBuf> {
+        self.inner.lock().unwrap().segments()
+    }
+    /// Move every ended span to the segment files; errors without a segment directory or
+    /// when a segment write failed.
+    pub fn flush(&self) -> Result<()> {
+        self.inner.lock().unwrap().flush()
+    }
+    /// Context starting root spans on this tracer.
+    pub fn context(&self) -> SpanContext {
+        SpanContext {
+            tracer: self.clone(),
+            parent: None,
+        }
     }
 }
 
@@ -314,17 +429,60 @@ impl Default for TracerHandle {
     }
 }
 
+/// Handle to the active span, threaded through subsystems so the spans they start nest under
+/// their caller's (run → node → HAL call, drift check → calibration).
+#[derive(Clone)]
+pub struct SpanContext {
+    tracer: TracerHandle,
+    parent: Option<String>,
+}
+
+impl SpanContext {
+    /// Id of the span new spans nest under; `None` for a root context.
+    pub fn parent_id(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+    /// Start a span as a child of this context's span.
+    pub fn start_span(&self, name: &str) -> SpanHandle {
+        self.tracer.start_span_under(name, self.parent.as_deref())
+    }
+    pub fn tracer(&self) -> &TracerHandle {
+        &self.tracer
+    }
+}
+
 impl SpanHandle {
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+    /// Context whose spans nest under this one.
+    pub fn context(&self) -> SpanContext {
+        SpanContext {
+            tracer: TracerHandle {
+                inner: Arc::clone(&self.inner),
+            },
+            parent: Some(self.id()),
+        }
+    }
+    /// Stamp the span's end time; an ended span becomes eligible for eviction.
+    pub fn end(&mut self) {
+        if let Ok(mut guard) = self.inner.lock() {
+            if let Some(sp) = guard.get_mut(self.seq) {
+                sp.end_iso = Utc::now().to_rfc3339();
+            }
+            guard.mark_ended(self.seq);
+        }
+    }
     pub fn set_attribute(&mut self, key: &str, value: &str) {
         if let Ok(mut guard) = self.inner.lock() {
-            if let Some(sp) = guard.get_mut(self.idx) {
+            if let Some(sp) = guard.get_mut(self.seq) {
                 sp.attributes.insert(key.to_string(), value.to_string());
             }
         }
     }
     pub fn add_event(&mut self, _name: &str, _attrs: HashMap<String, String>) {
         if let Ok(mut guard) = self.inner.lock() {
-            if let Some(sp) = guard.get_mut(self.idx) {
+            if let Some(sp) = guard.get_mut(self.seq) {
                 // record the event name and attrs into span attributes for test visibility
                 let key = format!("event::{}", _name);
                 if let Ok(s) = serde_json::to_string(&_attrs) {
@@ -381,6 +539,24 @@ impl MetricsCollector {
     pub fn metrics(&self) -> Vec<MetricRecord> {
         self.inner.lock().unwrap().clone()
     }
+    /// Histogram `name`'s raw points counted into buckets with upper `bounds`.
+    pub fn to_histogram(&self, name: &str, bounds: &[f64]) -> Result<Histogram> {
+        let mut histogram = Histogram::new(bounds.to_vec())?;
+        for value in self.histogram_values(name) {
+            histogram.record(value);
+        }
+        Ok(histogram)
+    }
+    /// All observations recorded for histogram `name`, in recording order.
+    pub fn histogram_values(&self, name: &str) -> Vec<f64> {
+        self.inner
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|m| m.name == name && matches!(m.metric_type, MetricType::Histogram))
+            .map(|m| m.value)
+            .collect()
+    }
 }
 
 impl Default for MetricsCollector {
@@ -392,21 +568,53 @@ impl Default for MetricsCollector {
 #[derive(Clone)]
 pub struct EventSink {
     inner: Arc<Mutex<Vec<EventRecord>>>,
+    filter: Arc<LogFilter>,
 }
 
 impl EventSink {
+    /// Sink filtered by `AWEN_LOG` (see [`LogFilter`]).
     pub fn new() -> Self {
+        Self::with_filter(LogFilter::from_env())
+    }
+    /// Sink keeping only events `filter` enables; others are dropped when recorded.
+    pub fn with_filter(filter: LogFilter) -> Self {
         Self {
             inner: Arc::new(Mutex::new(Vec::new())),
+            filter: Arc::new(filter),
         }
     }
+    pub fn filter(&self) -> &LogFilter {
+        &self.filter
+    }
     fn push(&self, level: Level, source: &str, message: &str, attrs: HashMap<String, String>) {
+        let code = if level >= Level::Error {
+            ErrorCode::classify(message)
+        } else {
+            None
+        };
+        self.push_coded(level, code, source, message, attrs)
+    }
+    fn push_coded(
+        &self,
+        level: Level,
+        code: Option<ErrorCode>,
+        source: &str,
+        message: &str,
+        mut attrs: HashMap<String, String>,
+    ) {
+        if !self.filter.enabled(level, source) {
+            return;
+        }
+        if let Some(code) = code {
+            attrs.insert(ERROR_CODE_ATTRIBUTE.to_string(), code.code().to_string());
+        }
         let mut guard = self.inner.lock().unwrap();
         guard.push(EventRecord {
             level,
             source: source.to_string(),
             message: message.to_string(),
             attributes: attrs,
+            code,
         });
     }
     pub fn trace(&self, source: &str, message: &str, attrs: HashMap<String, String>) {
@@ -421,12 +629,32 @@ impl EventSink {
     pub fn warning(&self, source: &str, message: &str, attrs: HashMap<String, String>) {
         self.push(Level::Warning, source, message, attrs)
     }
+    /// Error event, coded by [`ErrorCode::classify`] when the message is recognised.
     pub fn error(&self, source: &str, message: &str, attrs: HashMap<String, String>) {
         self.push(Level::Error, source, message, attrs)
     }
     pub fn fatal(&self, source: &str, message: &str, attrs: HashMap<String, String>) {
         self.push(Level::Fatal, source, message, attrs)
     }
+    /// Error event of a known failure class.
+    pub fn error_with_code(
+        &self,
+        code: ErrorCode,
+        source: &str,
+        message: &str,
+        attrs: HashMap<String, String>,
+    ) {
+        self.push_coded(Level::Error, Some(code), source, message, attrs)
+    }
+    pub fn fatal_with_code(
+        &self,
+        code: ErrorCode,
+        source: &str,
+        message: &str,
+        attrs: HashMap<String, String>,
+    ) {
+        self.push_coded(Level::Fatal, Some(code), source, message, attrs)
+    }
     pub fn events(&self) -> Vec<EventRecord> {
         self.inner.lock().unwrap().clone()
     }
@@ -440,13 +668,17 @@ impl Default for EventSink {
 
 #[derive(Clone)]
 pub struct TimelineBuilderCompat {
-    inner: Arc<Mutex<Vec<TimelineEntry>>>,
+    inner: Arc<Mutex<TimelineSampler<TimelineEntry>>>,
 }
 
 impl TimelineBuilderCompat {
     pub fn new() -> Self {
+        Self::with_sampling(SamplingConfig::new())
+    }
+    /// Builder whose lanes are thinned by `sampling` as entries arrive.
+    pub fn with_sampling(sampling: SamplingConfig) -> Self {
         Self {
-            inner: Arc::new(Mutex::new(Vec::new())),
+            inner: Arc::new(Mutex::new(TimelineSampler::new(sampling))),
         }
     }
     pub fn add_entry(
@@ -458,19 +690,34 @@ impl TimelineBuilderCompat {
         attrs: HashMap<String, String>,
     ) {
         let mut guard = self.inner.lock().unwrap();
-        guard.push(TimelineEntry {
-            lane: lane.to_string(),
-            name: name.to_string(),
+        guard.offer(
+            lane,
             start_ms,
-            end_ms,
-            attributes: attrs,
-        });
+            TimelineEntry {
+                lane: lane.to_string(),
+                name: name.to_string(),
+                start_ms,
+                end_ms,
+                attributes: attrs,
+            },
+        );
     }
     pub fn build(&self) -> Timeline {
         Timeline {
-            entries: self.inner.lock().unwrap().clone(),
+            entries: self
+                .inner
+                .lock()
+                .unwrap()
+                .kept()
+                .into_iter()
+                .cloned()
+                .collect(),
         }
     }
+    /// Entries dropped by sampling, per lane.
+    pub fn dropped(&self) -> BTreeMap<String, u64> {
+        self.inner.lock().unwrap().dropped()
+    }
 }
 
 impl Default for TimelineBuilderCompat {
@@ -517,6 +764,18 @@ impl ObservabilityContext {
         }
     }
 
+    /// Context whose event sink keeps only what `filter` enables.
+    pub fn with_log_filter(mut self, filter: LogFilter) -> Self {
+        self.events = EventSink::with_filter(filter);
+        self
+    }
+
+    /// Context whose timeline lanes are sampled by `sampling`.
+    pub fn with_sampling(mut self, sampling: SamplingConfig) -> Self {
+        self.timeline = TimelineBuilderCompat::with_sampling(sampling);
+        self
+    }
+
     pub fn export(&self, out_dir: &std::path::Path) -> Result<ObservabilityArtifacts> {
         // write traces
         let traces = out_dir.join("traces.jsonl");
@@ -531,9 +790,17 @@ impl ObservabilityContext {
         let tl = self.timeline.build();
         std::fs::write(&timeline, serde_json::to_string_pretty(&tl.entries)?)?;
 
-        // metrics
+        // metrics, with the timeline entries sampling dropped
         let metrics = out_dir.join("metrics.json");
-        let m = self.metrics.metrics();
+        let dropped = self.timeline.dropped();
+        let mut m = self.metrics.metrics();
+        m.extend(dropped.iter().map(|(lane, count)| MetricRecord {
+            name: "timeline.dropped_events".to_string(),
+            metric_type: MetricType::Counter,
+            value: *count as f64,
+            unit: "events".to_string(),
+            attributes: HashMap::from([("lane".to_string(), lane.clone())]),
+        }));
         std::fs::write(&metrics, serde_json::to_string_pretty(&m)?)?;
 
         // events
@@ -546,8 +813,11 @@ impl ObservabilityContext {
 
         // metadata
         let metadata = out_dir.join("observability_metadata.json");
-        let meta =
-            serde_json::json!({ "schema": "observability.v0.1", "conformance_level": "basic" });
+        let meta = serde_json::json!({
+            "schema": "observability.v0.1",
+            "conformance_level": "basic",
+            "dropped_events": dropped,
+        });
         std::fs::write(&metadata, serde_json::to_string_pretty(&meta)?)?;
 
         Ok(ObservabilityArtifacts {
@@ -0,0 +1,402 @@
+//! Self-contained HTML run report.
+//!
+//! `render_report` reads a run bundle's artifacts and writes `report.html` into it: a single
+//! file without external assets, so it can be attached to a ticket or mailed as-is. Sections
+//! are rendered only for artifacts the bundle holds:
+//! - timeline (`timeline.json`): SVG Gantt chart, one row per lane; hover a bar for its
+//!   details, click a lane name to hide or show it,
+//! - metrics (`metrics.json`): counter, gauge and histogram tables,
+//! - measurements (`measurements.json`, `shots.json`): per-node outcomes and histograms,
+//! - calibration: calibration-related timeline events, `hal.*` metrics and the calibration
+//!   id / hardware revision recorded in `quantum_states.json`.
+
+use super::{HistogramSummary, Metrics, TimelineEvent};
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const GANTT_WIDTH: f64 = 960.0;
+const GANTT_LABEL_WIDTH: f64 = 160.0;
+const GANTT_ROW_HEIGHT: f64 = 22.0;
+
+/// Bins of continuous shot histograms.
+const CONTINUOUS_BINS: usize = 20;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+h1{font-size:1.4em}h2{font-size:1.15em;margin-top:2em;border-bottom:1px solid #ccc}\
+table{border-collapse:collapse;margin:.5em 0}td,th{border:1px solid #ddd;padding:3px 8px;\
+text-align:left;font-size:.9em}th{background:#f4f4f4}.bar{background:#4a7bd0;height:14px}\
+.lane-label{cursor:pointer}.lane-hidden{opacity:.15}.empty{color:#888}";
+
+const SCRIPT: &str = "document.querySelectorAll('.lane-label').forEach(function(label){\
+label.addEventListener('click',function(){document.querySelectorAll('[data-lane=\"'+\
+label.dataset.lane+'\"]').forEach(function(el){el.classList.toggle('lane-hidden')});});});";
+
+/// Render `run_dir`'s artifacts into `run_dir/report.html`, returning its path.
+pub fn render_report(run_dir: &Path) -> Result<PathBuf> {
+    let read_json = |name: &str| -> Result<Option<Value>> {
+        let path = run_dir.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    };
+    let timeline: Vec<TimelineEvent> = match read_json("timeline.json")? {
+        Some(value) => serde_json::from_value(value)?,
+        None => Vec::new(),
+    };
+    let metrics: Option<Metrics> = read_json("metrics.json")?
+        .map(serde_json::from_value)
+        .transpose()?;
+    let measurements = read_json("measurements.json")?;
+    let shots = read_json("shots.json")?;
+    let states = read_json("quantum_states.json")?;
+
+    let title = run_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "run".to_string());
+    let mut html = String::new();
+    write!(
+        html,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>AWEN run report: {title}</title>\
+         <style>{STYLE}</style></head><body><h1>AWEN run report: {title}</h1>",
+        title = escape(&title)
+    )?;
+
+    html.push_str("<h2>Timeline</h2>");
+    html.push_str(&gantt(&timeline));
+    html.push_str("<h2>Metrics</h2>");
+    match &metrics {
+        Some(metrics) => {
+            html.push_str("<h3>Counters</h3>");
+            html.push_str(&metric_table(&metrics.counters));
+            html.push_str("<h3>Gauges</h3>");
+            html.push_str(&metric_table(&metrics.gauges));
+            if !metrics.histograms.is_empty() {
+                html.push_str("<h3>Histograms</h3>");
+                let sorted: BTreeMap<&String, &HistogramSummary> =
+                    metrics.histograms.iter().collect();
+                let rows: Vec<Vec<String>> = sorted
+                    .into_iter()
+                    .map(|(k, h)| {
+                        let mut row = vec![k.clone(), h.count.to_string()];
+                        row.extend([h.min, h.p50, h.p95, h.p99, h.max].map(|v| v.to_string()));
+                        row
+                    })
+                    .collect();
+                html.push_str(&table(
+                    &["name", "count", "min", "p50", "p95", "p99", "max"],
+                    &rows,
+                ));
+            }
+        }
+        None => html.push_str("<p class=\"empty\">No metrics.json in this bundle.</p>"),
+    }
+    html.push_str("<h2>Measurements</h2>");
+    html.push_str(&measurement_section(measurements.as_ref(), shots.as_ref()));
+    html.push_str("<h2>Calibration</h2>");
+    html.push_str(&calibration_section(
+        &timeline,
+        metrics.as_ref(),
+        states.as_ref(),
+    ));
+    write!(html, "<script>{SCRIPT}</script></body></html>")?;
+
+    let path = run_dir.join("report.html");
+    std::fs::write(&path, html)?;
+    Ok(path)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table><tr>");
+    for header in headers {
+        let _ = write!(html, "<th>{}</th>", escape(header));
+    }
+    html.push_str("</tr>");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            let _ = write!(html, "<td>{}</td>", escape(cell));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn metric_table(values: &std::collections::HashMap<String, f64>) -> String {
+    if values.is_empty() {
+        return "<p class=\"empty\">None recorded.</p>".to_string();
+    }
+    let sorted: BTreeMap<&String, &f64> = values.iter().collect();
+    let rows: Vec<Vec<String>> = sorted
+        .into_iter()
+        .map(|(k, v)| vec![k.clone(), v.to_string()])
+        .collect();
+    table(&["name", "value"], &rows)
+}
+
+/// Bar chart of `(label, count)` rows, bars scaled to the largest count.
+fn histogram(rows: &[(String, usize)]) -> String {
+    let max = rows.iter().map(|(_, c)| *c).max().unwrap_or(0).max(1);
+    let mut html = String::from("<table class=\"histogram\">");
+    for (label, count) in rows {
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td style=\"width:320px\"><div class=\"bar\" style=\"width:{:.1}%\">\
+             </div></td><td>{}</td></tr>",
+            escape(label),
+            100.0 * *count as f64 / max as f64,
+            count
+        );
+    }
+    html.push_str("</table>");
+    html
+}
+
+/// SVG Gantt chart of the timeline, one row per lane in order of first appearance.
+fn gantt(events: &[TimelineEvent]) -> String {
+    if events.is_empty() {
+        return "<p class=\"empty\">No timeline events.</p>".to_string();
+    }
+    let mut lanes: Vec<&str> = Vec::new();
+    for event in events {
+        if !lanes.contains(&event.lane.as_str()) {
+            lanes.push(&event.lane);
+        }
+    }
+    let t0 = events.iter().map(|e| e.start_ms).min().unwrap_or(0);
+    let t1 = events
+        .iter()
+        .map(|e| e.end_ms.max(e.start_ms))
+        .max()
+        .unwrap_or(t0);
+    let span_ms = (t1 - t0).max(1) as f64;
+    let plot_width = GANTT_WIDTH - GANTT_LABEL_WIDTH;
+    let height = GANTT_ROW_HEIGHT * (lanes.len() as f64 + 1.0);
+
+    let mut svg = format!(
+        "<svg class=\"gantt\" width=\"{GANTT_WIDTH}\" height=\"{height}\" \
+         xmlns=\"http://www.w3.org/2000/svg\" font-size=\"12\">"
+    );
+    for (row, lane) in lanes.iter().enumerate() {
+        let y = GANTT_ROW_HEIGHT * row as f64;
+        let _ = write!(
+            svg,
+            "<text class=\"lane-label\" data-lane=\"{lane}\" x=\"4\" y=\"{:.1}\">{lane}</text>",
+            y + 15.0,
+            lane = escape(lane)
+        );
+    }
+    for event in events {
+        let row = lanes.iter().position(|l| *l == event.lane).unwrap_or(0);
+        let x = GANTT_LABEL_WIDTH + plot_width * (event.start_ms - t0) as f64 / span_ms;
+        let width =
+            (plot_width * event.end_ms.saturating_sub(event.start_ms) as f64 / span_ms).max(2.0);
+        let attributes: BTreeMap<&String, &String> = event.attributes.iter().collect();
+        let details: String = attributes
+            .iter()
+            .map(|(k, v)| format!("\n{}={}", k, v))
+            .collect();
+        let _ = write!(
+            svg,
+            "<rect data-lane=\"{lane}\" x=\"{x:.1}\" y=\"{y:.1}\" width=\"{width:.1}\" \
+             height=\"{h:.1}\" fill=\"#4a7bd0\"><title>{name} [{lane}] {start}–{end} ms{details}\
+             </title></rect>",
+            lane = escape(&event.lane),
+            y = GANTT_ROW_HEIGHT * row as f64 + 3.0,
+            h = GANTT_ROW_HEIGHT - 6.0,
+            name = escape(&event.name),
+            start = event.start_ms - t0,
+            end = event.end_ms.saturating_sub(t0),
+            details = escape(&details),
+        );
+    }
+    let _ = write!(
+        svg,
+        "<text x=\"{GANTT_LABEL_WIDTH}\" y=\"{:.1}\">0 ms</text>\
+         <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{} ms</text></svg>",
+        height - 6.0,
+        GANTT_WIDTH - 4.0,
+        height - 6.0,
+        t1 - t0
+    );
+    svg
+}
+
+fn measurement_section(measurements: Option<&Value>, shots: Option<&Value>) -> String {
+    let mut html = String::new();
+    match measurements.and_then(Value::as_object) {
+        Some(outcomes) if !outcomes.is_empty() => {
+            let sorted: BTreeMap<&String, &Value> = outcomes.iter().collect();
+            let mut counts: BTreeMap<u64, usize> = BTreeMap::new();
+            let rows: Vec<Vec<String>> = sorted
+                .iter()
+                .map(|(node, outcome)| {
+                    let photons = outcome["photon_count"].as_u64().unwrap_or(0);
+                    let kind = match &outcome["kind"] {
+                        Value::String(kind) => kind.clone(),
+                        Value::Object(kind) => kind.keys().cloned().collect::<Vec<_>>().join(","),
+                        _ => "PhotonCount".to_string(),
+                    };
+                    if kind == "PhotonCount" {
+                        *counts.entry(photons).or_default() += 1;
+                    }
+                    vec![
+                        node.to_string(),
+                        kind,
+                        photons.to_string(),
+                        outcome["probability"].to_string(),
+                        match &outcome["quadratures"] {
+                            Value::Null => String::new(),
+                            q => q.to_string(),
+                        },
+                    ]
+                })
+                .collect();
+            html.push_str(&table(
+                &["node", "kind", "photon count", "probability", "quadratures"],
+                &rows,
+            ));
+            if !counts.is_empty() {
+                html.push_str("<h3>Photon counts across detectors</h3>");
+                let bars: Vec<(String, usize)> = counts
+                    .into_iter()
+                    .map(|(n, c)| (n.to_string(), c))
+                    .collect();
+                html.push_str(&histogram(&bars));
+            }
+        }
+        _ => html.push_str("<p class=\"empty\">No measurements recorded.</p>"),
+    }
+
+    if let Some(shots) = shots {
+        let labels: Vec<String> = shots["labels"]
+            .as_array()
+            .map(|l| {
+                l.iter()
+                    .map(|v| v.as_str().unwrap_or_default().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let records = shots["shots"].as_array().cloned().unwrap_or_default();
+        let _ = write!(html, "<h3>Shots ({})</h3>", records.len());
+        for (i, label) in labels.iter().enumerate() {
+            let values: Vec<&Value> = records.iter().filter_map(|shot| shot.get(i)).collect();
+            let _ = write!(html, "<h4>{}</h4>", escape(label));
+            html.push_str(&histogram(&shot_histogram(&values)));
+        }
+    }
+    html
+}
+
+/// Histogram rows of one label's shot results: one bar per discrete outcome, or
+/// `CONTINUOUS_BINS` equal bins over the range of continuous values.
+fn shot_histogram(values: &[&Value]) -> Vec<(String, usize)> {
+    let discrete: Vec<u64> = values
+        .iter()
+        .filter_map(|v| v["DiscreteOutcome"].as_u64())
+        .collect();
+    if !discrete.is_empty() {
+        let mut counts: BTreeMap<u64, usize> = BTreeMap::new();
+        for n in discrete {
+            *counts.entry(n).or_default() += 1;
+        }
+        return counts
+            .into_iter()
+            .map(|(n, c)| (n.to_string(), c))
+            .collect();
+    }
+    let continuous: Vec<f64> = values
+        .iter()
+        .filter_map(|v| v["ContinuousValue"].as_f64())
+        .collect();
+    if continuous.is_empty() {
+        return Vec::new();
+    }
+    let lo = continuous.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = continuous.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = ((hi - lo) / CONTINUOUS_BINS as f64).max(f64::EPSILON);
+    let mut bins = vec![0usize; CONTINUOUS_BINS];
+    for x in continuous {
+        bins[(((x - lo) / width) as usize).min(CONTINUOUS_BINS - 1)] += 1;
+    }
+    bins.into_iter()
+        .enumerate()
+        .map(|(i, c)| (format!("{:.3}", lo + width * (i as f64 + 0.5)), c))
+        .collect()
+}
+
+fn calibration_section(
+    timeline: &[TimelineEvent],
+    metrics: Option<&Metrics>,
+    states: Option<&Value>,
+) -> String {
+    let mut html = String::new();
+    let provenance = states
+        .and_then(Value::as_array)
+        .and_then(|s| s.last())
+        .map(|s| &s["provenance"]);
+    let recorded: Vec<Vec<String>> = ["calibration_id", "hardware_revision", "noise_model_id"]
+        .iter()
+        .filter_map(|key| {
+            provenance
+                .and_then(|p| p[key].as_str())
+                .map(|v| vec![key.to_string(), v.to_string()])
+        })
+        .collect();
+    if !recorded.is_empty() {
+        html.push_str(&table(&["provenance", "value"], &recorded));
+    }
+
+    let events: Vec<Vec<String>> = timeline
+        .iter()
+        .filter(|e| e.name.contains("calibrat"))
+        .map(|e| {
+            let attributes: BTreeMap<&String, &String> = e.attributes.iter().collect();
+            vec![
+                e.lane.clone(),
+                e.name.clone(),
+                e.start_ms.to_string(),
+                attributes
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]
+        })
+        .collect();
+    if !events.is_empty() {
+        html.push_str(&table(
+            &["lane", "event", "start (ms)", "attributes"],
+            &events,
+        ));
+    }
+
+    if let Some(metrics) = metrics {
+        let hal: std::collections::HashMap<String, f64> = metrics
+            .gauges
+            .iter()
+            .filter(|(k, _)| k.starts_with("hal."))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        if !hal.is_empty() {
+            html.push_str("<h3>HAL latency</h3>");
+            html.push_str(&metric_table(&hal));
+        }
+    }
+    if html.is_empty() {
+        html.push_str("<p class=\"empty\">No calibration activity recorded.</p>");
+    }
+    html
+}
@@ -0,0 +1,208 @@
+//! Span retention for long-lived tracers.
+//!
+//! A `TracerHandle` used by a background calibration or monitoring loop would otherwise keep
+//! every span it ever started. A [`RetentionPolicy`] bounds it: once more than `max_spans`
+//! spans are held, or spans are older than `max_age`, the oldest *ended* spans are evicted
+//! (open spans are never evicted, so long-running parents keep their children's parent ids
+//! valid). With a segment directory set, evicted spans are appended to rotating
+//! `traces-NNNN.jsonl` segments instead of being discarded.
+
+use super::Span;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Bounds on the spans a tracer keeps in memory. The default keeps everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// Most spans held in memory.
+    pub max_spans: Option<usize>,
+    /// Ended spans older than this (by start time) are evicted.
+    pub max_age: Option<Duration>,
+    /// Directory receiving evicted spans; `None` discards them.
+    pub segment_dir: Option<PathBuf>,
+    /// Spans per segment file before rotating to the next.
+    pub segment_spans: usize,
+}
+
+impl RetentionPolicy {
+    pub fn with_max_spans(mut self, max_spans: usize) -> Self {
+        self.max_spans = Some(max_spans);
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Flush evicted spans to `dir`, `spans_per_segment` per file.
+    pub fn with_segments(mut self, dir: impl Into<PathBuf>, spans_per_segment: usize) -> Self {
+        self.segment_dir = Some(dir.into());
+        self.segment_spans = spans_per_segment.max(1);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct StoredSpan {
+    span: Span,
+    started: Instant,
+    ended: bool,
+}
+
+/// Spans of a tracer keyed by start sequence, so handles stay valid across evictions.
+#[derive(Debug, Default)]
+pub(super) struct SpanStore {
+    spans: BTreeMap<u64, StoredSpan>,
+    next_seq: u64,
+    retention: RetentionPolicy,
+    evicted: u64,
+    segments: Vec<PathBuf>,
+    segment_len: usize,
+    segment_error: Option<String>,
+}
+
+impl SpanStore {
+    pub(super) fn with_retention(retention: RetentionPolicy) -> Self {
+        Self {
+            retention,
+            ..Self::default()
+        }
+    }
+
+    /// Add a span built from its sequence number; returns that sequence number.
+    pub(super) fn push(&mut self, build: impl FnOnce(u64) -> Span) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.spans.insert(
+            seq,
+            StoredSpan {
+                span: build(seq),
+                started: Instant::now(),
+                ended: false,
+            },
+        );
+        self.enforce();
+        seq
+    }
+
+    pub(super) fn get_mut(&mut self, seq: u64) -> Option<&mut Span> {
+        self.spans.get_mut(&seq).map(|stored| &mut stored.span)
+    }
+
+    pub(super) fn mark_ended(&mut self, seq: u64) {
+        if let Some(stored) = self.spans.get_mut(&seq) {
+            stored.ended = true;
+        }
+        self.enforce();
+    }
+
+    pub(super) fn spans(&self) -> Vec<Span> {
+        self.spans
+            .values()
+            .map(|stored| stored.span.clone())
+            .collect()
+    }
+
+    pub(super) fn evicted(&self) -> u64 {
+        self.evicted
+    }
+
+    pub(super) fn segments(&self) -> Vec<PathBuf> {
+        self.segments.clone()
+    }
+
+    /// Evict every ended span to the segments; reports the first segment write error.
+    pub(super) fn flush(&mut self) -> Result<()> {
+        if self.retention.segment_dir.is_none() {
+            return Err(anyhow!("tracer has no segment directory to flush to"));
+        }
+        let ended: Vec<u64> = self
+            .spans
+            .iter()
+            .filter(|(_, stored)| stored.ended)
+            .map(|(seq, _)| *seq)
+            .collect();
+        self.evict(&ended);
+        match self.segment_error.take() {
+            Some(e) => Err(anyhow!(e)),
+            None => Ok(()),
+        }
+    }
+
+    fn enforce(&mut self) {
+        let mut victims = Vec::new();
+        if let Some(max_age) = self.retention.max_age {
+            victims.extend(
+                self.spans
+                    .iter()
+                    .filter(|(_, stored)| stored.ended && stored.started.elapsed() > max_age)
+                    .map(|(seq, _)| *seq),
+            );
+        }
+        if let Some(max_spans) = self.retention.max_spans {
+            let excess = (self.spans.len() - victims.len()).saturating_sub(max_spans);
+            let oldest: Vec<u64> = self
+                .spans
+                .iter()
+                .filter(|(seq, stored)| stored.ended && !victims.contains(seq))
+                .map(|(seq, _)| *seq)
+                .take(excess)
+                .collect();
+            victims.extend(oldest);
+        }
+        if !victims.is_empty() {
+            victims.sort_unstable();
+            self.evict(&victims);
+        }
+    }
+
+    fn evict(&mut self, seqs: &[u64]) {
+        let spans: Vec<Span> = seqs
+            .iter()
+            .filter_map(|seq| self.spans.remove(seq))
+            .map(|stored| stored.span)
+            .collect();
+        self.evicted += spans.len() as u64;
+        if let Err(e) = self.write_segments(&spans) {
+            self.segment_error.get_or_insert(e.to_string());
+        }
+    }
+
+    fn write_segments(&mut self, spans: &[Span]) -> Result<()> {
+        let Some(dir) = self.retention.segment_dir.clone() else {
+            return Ok(());
+        };
+        let mut file: Option<std::io::BufWriter<std::fs::File>> = None;
+        for span in spans {
+            if self.segments.is_empty() || self.segment_len >= self.retention.segment_spans.max(1) {
+                if let Some(mut out) = file.take() {
+                    out.flush()?;
+                }
+                std::fs::create_dir_all(&dir)?;
+                self.segments
+                    .push(dir.join(format!("traces-{:04}.jsonl", self.segments.len())));
+                self.segment_len = 0;
+            }
+            if file.is_none() {
+                let path = self.segments.last().expect("current segment");
+                file = Some(std::io::BufWriter::new(
+                    std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)?,
+                ));
+            }
+            let out = file.as_mut().expect("open segment");
+            writeln!(out, "{}", serde_json::to_string(span)?)?;
+            self.segment_len += 1;
+        }
+        if let Some(mut out) = file {
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+//! Per-lane sampling of high-frequency timeline events.
+//!
+//! A fast feedback loop can emit millions of events on a few lanes. [`SamplingConfig`] maps lane
+//! patterns to a [`SamplingPolicy`] (keep the first N, a uniform reservoir, or a cap per time
+//! window), and [`TimelineSampler`] applies it as events arrive, so memory stays bounded by
+//! the policy rather than by the run length. Every dropped event is counted per lane.
+
+use super::TimelineEvent;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// How events of one lane are thinned.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum SamplingPolicy {
+    KeepAll,
+    /// Keep the first `limit` events.
+    Head {
+        limit: usize,
+    },
+    /// Keep a uniform random sample of `size` events over the whole run.
+    Reservoir {
+        size: usize,
+        seed: u64,
+    },
+    /// Keep at most `max_events` per `window_ms` of event start time.
+    RateCap {
+        max_events: usize,
+        window_ms: u128,
+    },
+}
+
+/// Lane pattern → policy rules. A pattern matches its lane exactly or, ending in `*`, by
+/// prefix (`HAL.Channel.*`); the longest matching pattern wins and unmatched lanes keep all.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SamplingConfig {
+    pub lanes: BTreeMap<String, SamplingPolicy>,
+}
+
+impl SamplingConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_lane(mut self, pattern: &str, policy: SamplingPolicy) -> Self {
+        self.lanes.insert(pattern.to_string(), policy);
+        self
+    }
+
+    /// Policy governing `lane`.
+    pub fn policy_for(&self, lane: &str) -> &SamplingPolicy {
+        const KEEP_ALL: SamplingPolicy = SamplingPolicy::KeepAll;
+        self.lanes
+            .iter()
+            .filter(|(pattern, _)| match pattern.strip_suffix('*') {
+                Some(prefix) => lane.starts_with(prefix),
+                None => pattern.as_str() == lane,
+            })
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, policy)| policy)
+            .unwrap_or(&KEEP_ALL)
+    }
+
+    /// Sample a complete event list, returning the kept events (in their original order) and
+    /// the dropped count of each lane that lost events.
+    pub fn apply(&self, events: Vec<TimelineEvent>) -> (Vec<TimelineEvent>, BTreeMap<String, u64>) {
+        let mut sampler = TimelineSampler::new(self.clone());
+        for event in events {
+            let (lane, start_ms) = (event.lane.clone(), event.start_ms);
+            sampler.offer(&lane, start_ms, event);
+        }
+        let dropped = sampler.dropped();
+        (sampler.into_kept(), dropped)
+    }
+}
+
+#[derive(Debug)]
+struct LaneState<T> {
+    seen: u64,
+    dropped: u64,
+    kept: Vec<(u64, T)>,
+    rng: Option<StdRng>,
+    window: u128,
+    in_window: usize,
+}
+
+/// Streaming sampler holding the kept items of every lane.
+#[derive(Debug)]
+pub struct TimelineSampler<T> {
+    config: SamplingConfig,
+    lanes: HashMap<String, LaneState<T>>,
+    sequence: u64,
+}
+
+impl<T> TimelineSampler<T> {
+    pub fn new(config: SamplingConfig) -> Self {
+        Self {
+            config,
+            lanes: HashMap::new(),
+            sequence: 0,
+        }
+    }
+
+    pub fn config(&self) -> &SamplingConfig {
+        &self.config
+    }
+
+    /// Offer one event of `lane` starting at `start_ms`; the lane's policy keeps or drops it.
+    pub fn offer(&mut self, lane: &str, start_ms: u128, item: T) {
+        let policy = self.config.policy_for(lane).clone();
+        let sequence = self.sequence;
+        self.sequence += 1;
+        let state = self
+            .lanes
+            .entry(lane.to_string())
+            .or_insert_with(|| LaneState {
+                seen: 0,
+                dropped: 0,
+                kept: Vec::new(),
+                rng: match policy {
+                    SamplingPolicy::Reservoir { seed, .. } => Some(StdRng::seed_from_u64(seed)),
+                    _ => None,
+                },
+                window: 0,
+                in_window: 0,
+            });
+        state.seen += 1;
+        match policy {
+            SamplingPolicy::KeepAll => state.kept.push((sequence, item)),
+            SamplingPolicy::Head { limit } => {
+                if state.kept.len() < limit {
+                    state.kept.push((sequence, item));
+                } else {
+                    state.dropped += 1;
+                }
+            }
+            SamplingPolicy::Reservoir { size, .. } => {
+                if state.kept.len() < size {
+                    state.kept.push((sequence, item));
+                } else {
+                    // Algorithm R: the n-th event replaces a random slot with probability size/n.
+                    state.dropped += 1;
+                    let slot = state
+                        .rng
+                        .as_mut()
+                        .map(|rng| rng.gen_range(0..state.seen))
+                        .unwrap_or(u64::MAX);
+                    if (slot as usize) < size {
+                        state.kept[slot as usize] = (sequence, item);
+                    }
+                }
+            }
+            SamplingPolicy::RateCap {
+                max_events,
+                window_ms,
+            } => {
+                let window = start_ms / window_ms.max(1);
+                if window != state.window || state.seen == 1 {
+                    state.window = window;
+                    state.in_window = 0;
+                }
+                if state.in_window < max_events {
+                    state.in_window += 1;
+                    state.kept.push((sequence, item));
+                } else {
+                    state.dropped += 1;
+                }
+            }
+        }
+    }
+
+    /// Dropped count of each lane that lost events.
+    pub fn dropped(&self) -> BTreeMap<String, u64> {
+        self.lanes
+            .iter()
+            .filter(|(_, state)| state.dropped > 0)
+            .map(|(lane, state)| (lane.clone(), state.dropped))
+            .collect()
+    }
+
+    /// Kept items of all lanes, in arrival order.
+    pub fn kept(&self) -> Vec<&T> {
+        let mut kept: Vec<&(u64, T)> = self.lanes.values().flat_map(|s| &s.kept).collect();
+        kept.sort_by_key(|(sequence, _)| *sequence);
+        kept.into_iter().map(|(_, item)| item).collect()
+    }
+
+    pub fn into_kept(self) -> Vec<T> {
+        let mut kept: Vec<(u64, T)> = self.lanes.into_values().flat_map(|s| s.kept).collect();
+        kept.sort_by_key(|(sequence, _)| *sequence);
+        kept.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer_all(config: SamplingConfig, lane: &str, n: u128) -> TimelineSampler<u128> {
+        let mut sampler = TimelineSampler::new(config);
+        for t in 0..n {
+            sampler.offer(lane, t, t);
+        }
+        sampler
+    }
+
+    #[test]
+    fn test_policies_bound_kept_events() {
+        let head = offer_all(
+            SamplingConfig::new().with_lane("fast", SamplingPolicy::Head { limit: 5 }),
+            "fast",
+            1000,
+        );
+        assert_eq!(head.into_kept(), [0, 1, 2, 3, 4]);
+
+        let reservoir = offer_all(
+            SamplingConfig::new()
+                .with_lane("fast", SamplingPolicy::Reservoir { size: 50, seed: 3 }),
+            "fast",
+            10_000,
+        );
+        assert_eq!(reservoir.dropped()["fast"], 9_950);
+        let kept = reservoir.into_kept();
+        assert_eq!(kept.len(), 50);
+        assert!(kept.windows(2).all(|w| w[0] < w[1]));
+        // Uniform over the run, not just its start.
+        assert!(kept.iter().any(|t| *t > 5_000));
+
+        let capped = offer_all(
+            SamplingConfig::new().with_lane(
+                "HAL.*",
+                SamplingPolicy::RateCap {
+                    max_events: 2,
+                    window_ms: 10,
+                },
+            ),
+            "HAL.Channel.1",
+            100,
+        );
+        assert_eq!(capped.dropped()["HAL.Channel.1"], 80);
+        assert_eq!(&capped.into_kept()[..4], [0, 1, 10, 11]);
+
+        let untouched = offer_all(SamplingConfig::new(), "kernel", 100);
+        assert!(untouched.dropped().is_empty());
+        assert_eq!(untouched.kept().len(), 100);
+    }
+
+    #[test]
+    fn test_longest_pattern_wins() {
+        let config = SamplingConfig::new()
+            .with_lane("HAL.*", SamplingPolicy::Head { limit: 1 })
+            .with_lane("HAL.Channel.*", SamplingPolicy::KeepAll);
+        assert_eq!(config.policy_for("HAL.Channel.3"), &SamplingPolicy::KeepAll);
+        assert_eq!(
+            config.policy_for("HAL.Device.x"),
+            &SamplingPolicy::Head { limit: 1 }
+        );
+        assert_eq!(config.policy_for("kernel"), &SamplingPolicy::KeepAll);
+    }
+}
@@ -0,0 +1,184 @@
+//! Live event streaming over Server-Sent Events.
+//!
+//! `EventStreamer` listens on a TCP address and answers every HTTP request with a
+//! `text/event-stream` response that stays open; each published [`StreamMessage`] is pushed
+//! to all connected clients as an SSE event named after its kind (`span`, `timeline`,
+//! `metric`, `measurement`). A browser dashboard subscribes with
+//! `new EventSource("http://host:port/")`. Clients that disconnect, or stall for longer than
+//! the write timeout, are dropped on the next publish; publishing with no clients is a no-op.
+
+use super::{MetricsSink, Span, TimelineBuilder, TimelineEvent};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Poll interval of the accept loop.
+const ACCEPT_POLL: Duration = Duration::from_millis(10);
+
+/// Longest a client may take to send its request head, or to accept an event.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// An update pushed to stream clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamMessage {
+    Span(Span),
+    Timeline(TimelineEvent),
+    Metric {
+        key: String,
+        kind: String,
+        value: f64,
+    },
+    Measurement {
+        run_id: String,
+        node_id: String,
+        outcome: serde_json::Value,
+    },
+}
+
+impl StreamMessage {
+    /// SSE event name.
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            StreamMessage::Span(_) => "span",
+            StreamMessage::Timeline(_) => "timeline",
+            StreamMessage::Metric { .. } => "metric",
+            StreamMessage::Measurement { .. } => "measurement",
+        }
+    }
+
+    /// Wire form: `event: <name>\ndata: <json>\n\n`.
+    pub fn to_sse(&self) -> Result<String> {
+        Ok(format!(
+            "event: {}\ndata: {}\n\n",
+            self.event_name(),
+            serde_json::to_string(self)?
+        ))
+    }
+}
+
+/// SSE server broadcasting [`StreamMessage`]s to connected clients.
+#[derive(Debug)]
+pub struct EventStreamer {
+    local_addr: SocketAddr,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    stop: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl EventStreamer {
+    /// Listen on `addr` (e.g. `"127.0.0.1:0"` for any free port).
+    pub fn bind(addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let accept_thread = {
+            let (clients, stop) = (Arc::clone(&clients), Arc::clone(&stop));
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            if let Ok(stream) = open_event_stream(stream) {
+                                clients.lock().unwrap().push(stream);
+                            }
+                        }
+                        Err(_) => std::thread::sleep(ACCEPT_POLL),
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            local_addr,
+            clients,
+            stop,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Number of connected clients.
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Push `message` to every client, dropping those that have disconnected.
+    pub fn publish(&self, message: &StreamMessage) {
+        let Ok(frame) = message.to_sse() else {
+            return;
+        };
+        self.clients.lock().unwrap().retain_mut(|client| {
+            client
+                .write_all(frame.as_bytes())
+                .and_then(|_| client.flush())
+                .is_ok()
+        });
+    }
+}
+
+impl Drop for EventStreamer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.accept_thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl MetricsSink for EventStreamer {
+    fn record_counter(&self, key: &str, value: f64) {
+        self.publish(&StreamMessage::Metric {
+            key: key.to_string(),
+            kind: "counter".to_string(),
+            value,
+        });
+    }
+
+    fn record_gauge(&self, key: &str, value: f64) {
+        self.publish(&StreamMessage::Metric {
+            key: key.to_string(),
+            kind: "gauge".to_string(),
+            value,
+        });
+    }
+}
+
+impl TimelineBuilder for EventStreamer {
+    fn add_event(&self, ev: TimelineEvent) {
+        self.publish(&StreamMessage::Timeline(ev));
+    }
+}
+
+/// Read the client's request head and answer with the open-ended event-stream header.
+fn open_event_stream(mut stream: TcpStream) -> std::io::Result<TcpStream> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut head = Vec::new();
+    let mut buf = [0u8; 512];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\n\
+          Content-Type: text/event-stream\r\n\
+          Cache-Control: no-cache\r\n\
+          Connection: keep-alive\r\n\
+          Access-Control-Allow-Origin: *\r\n\r\n",
+    )?;
+    stream.flush()?;
+    Ok(stream)
+}
@@ -0,0 +1,269 @@
+//! Background writer for run bundle artifacts.
+//!
+//! [`ArtifactWriter`] owns a thread fed through a bounded channel. Bundle files are written as
+//! they arrive; spans, timeline events and metrics are batched in memory and flushed once, to
+//! `traces.jsonl`, `timeline.json` and `metrics.json`, when the writer is finished. Callers only
+//! serialize and enqueue, so file I/O stays off the execution path; a full channel applies
+//! backpressure rather than growing without bound. I/O errors are reported by
+//! [`ArtifactWriter::finish`].
+//!
+//! The writer also measures the bundle it emits: bytes and serialization time per artifact and
+//! the span/event counts go into metrics.json as `artifact.*` gauges (metrics.json itself is
+//! not counted). [`bundle_sizes`] measures a finished bundle on disk, for size budgets.
+
+use super::{write_metrics, Metrics, Span, TimelineEvent};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// Queued writes before senders block.
+pub const DEFAULT_WRITER_CAPACITY: usize = 64;
+
+enum WriteOp {
+    Spans(Vec<Span>),
+    Events(Vec<TimelineEvent>),
+    Metrics(Metrics),
+    /// File name, contents and the time taken to serialize them
+    File(String, Vec<u8>, u64),
+}
+
+/// Bytes and serialization time of each artifact written.
+#[derive(Debug, Default)]
+struct ArtifactStats {
+    files: BTreeMap<String, (u64, u64)>,
+}
+
+impl ArtifactStats {
+    fn write(&mut self, dir: &Path, name: &str, bytes: &[u8], serialize_ns: u64) -> Result<()> {
+        std::fs::write(dir.join(name), bytes)
+            .map_err(|e| anyhow!("failed to write {}: {}", name, e))?;
+        self.files
+            .insert(name.to_string(), (bytes.len() as u64, serialize_ns));
+        Ok(())
+    }
+
+    /// `artifact.<file>.bytes` / `.serialize_ns`, `artifact.total_bytes`, `artifact.spans` and
+    /// `artifact.events` gauges.
+    fn record(&self, metrics: &mut Metrics, spans: usize, events: usize) {
+        for (name, (bytes, serialize_ns)) in &self.files {
+            let prefix = format!("artifact.{}", name);
+            metrics
+                .gauges
+                .insert(format!("{}.bytes", prefix), *bytes as f64);
+            metrics
+                .gauges
+                .insert(format!("{}.serialize_ns", prefix), *serialize_ns as f64);
+        }
+        let total: u64 = self.files.values().map(|(bytes, _)| bytes).sum();
+        metrics
+            .gauges
+            .insert("artifact.total_bytes".to_string(), total as f64);
+        metrics
+            .gauges
+            .insert("artifact.spans".to_string(), spans as f64);
+        metrics
+            .gauges
+            .insert("artifact.events".to_string(), events as f64);
+    }
+}
+
+/// Serialize with `f`, returning the bytes and the nanoseconds it took.
+fn timed(f: impl FnOnce() -> Result<String>) -> Result<(Vec<u8>, u64)> {
+    let started = Instant::now();
+    let contents = f()?;
+    Ok((contents.into_bytes(), started.elapsed().as_nanos() as u64))
+}
+
+/// Size in bytes of every file in the bundle at `run_dir`.
+pub fn bundle_sizes(run_dir: &Path) -> Result<BTreeMap<String, u64>> {
+    let mut sizes = BTreeMap::new();
+    for entry in std::fs::read_dir(run_dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_file() {
+            sizes.insert(entry.file_name().to_string_lossy().into_owned(), meta.len());
+        }
+    }
+    Ok(sizes)
+}
+
+pub struct ArtifactWriter {
+    out_dir: PathBuf,
+    tx: Option<SyncSender<WriteOp>>,
+    handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl ArtifactWriter {
+    /// Start a writer for the bundle at `out_dir` holding at most `capacity` queued writes.
+    pub fn spawn(out_dir: &Path, capacity: usize) -> Self {
+        let (tx, rx) = sync_channel::<WriteOp>(capacity.max(1));
+        let dir = out_dir.to_path_buf();
+        let handle = std::thread::spawn(move || -> Result<()> {
+            let mut spans = Vec::new();
+            let mut events = Vec::new();
+            let mut metrics = None;
+            let mut stats = ArtifactStats::default();
+            let mut first_error = None;
+            // Keep draining after an error so senders never block on a dead writer.
+            for op in rx {
+                match op {
+                    WriteOp::Spans(batch) => spans.extend(batch),
+                    WriteOp::Events(batch) => events.extend(batch),
+                    WriteOp::Metrics(m) => metrics = Some(m),
+                    WriteOp::File(name, bytes, serialize_ns) => {
+                        if let Err(e) = stats.write(&dir, &name, &bytes, serialize_ns) {
+                            first_error.get_or_insert(e);
+                        }
+                    }
+                }
+            }
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+            let (traces, ns) = timed(|| {
+                let mut out = String::new();
+                for span in &spans {
+                    out.push_str(&serde_json::to_string(span)?);
+                    out.push('\n');
+                }
+                Ok(out)
+            })?;
+            stats.write(&dir, "traces.jsonl", &traces, ns)?;
+            let (timeline, ns) = timed(|| Ok(serde_json::to_string_pretty(&events)?))?;
+            stats.write(&dir, "timeline.json", &timeline, ns)?;
+            let mut metrics = metrics.unwrap_or_default();
+            stats.record(&mut metrics, spans.len(), events.len());
+            write_metrics(&dir, &metrics)?;
+            Ok(())
+        });
+        Self {
+            out_dir: out_dir.to_path_buf(),
+            tx: Some(tx),
+            handle: Some(handle),
+        }
+    }
+
+    pub fn out_dir(&self) -> &Path {
+        &self.out_dir
+    }
+
+    fn send(&self, op: WriteOp) {
+        // A send only fails once the thread has exited; `finish` reports why.
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(op);
+        }
+    }
+
+    /// Append spans to `traces.jsonl`.
+    pub fn spans(&self, batch: Vec<Span>) {
+        self.send(WriteOp::Spans(batch));
+    }
+
+    /// Append events to `timeline.json`.
+    pub fn events(&self, batch: Vec<TimelineEvent>) {
+        self.send(WriteOp::Events(batch));
+    }
+
+    /// Set the contents of `metrics.json` (the last call wins).
+    pub fn metrics(&self, metrics: Metrics) {
+        self.send(WriteOp::Metrics(metrics));
+    }
+
+    /// Write `name` under the bundle directory.
+    pub fn file(&self, name: &str, contents: impl Into<Vec<u8>>) {
+        self.send(WriteOp::File(name.to_string(), contents.into(), 0));
+    }
+
+    /// `value` as pretty JSON into `name`; serialization happens on the caller's thread.
+    pub fn json<T: serde::Serialize>(&self, name: &str, value: &T) -> Result<()> {
+        let (bytes, serialize_ns) = timed(|| Ok(serde_json::to_string_pretty(value)?))?;
+        self.send(WriteOp::File(name.to_string(), bytes, serialize_ns));
+        Ok(())
+    }
+
+    /// Flush the batched observability artifacts and wait for every queued write.
+    pub fn finish(mut self) -> Result<()> {
+        self.tx.take();
+        match self.handle.take() {
+            Some(handle) => handle
+                .join()
+                .map_err(|_| anyhow!("artifact writer thread panicked"))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for ArtifactWriter {
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn event(name: &str) -> TimelineEvent {
+        TimelineEvent {
+            lane: "kernel".to_string(),
+            name: name.to_string(),
+            start_ms: 0,
+            end_ms: 1,
+            attributes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_batches_flush_once_on_finish() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Capacity 1: senders wait on the writer instead of queueing without bound.
+        let writer = ArtifactWriter::spawn(dir.path(), 1);
+        for i in 0..100 {
+            writer.events(vec![event(&format!("e{}", i))]);
+        }
+        writer
+            .json("ir.json", &serde_json::json!({"nodes": []}))
+            .unwrap();
+        let mut metrics = Metrics::default();
+        metrics.counters.insert("nodes".to_string(), 3.0);
+        writer.metrics(metrics);
+        assert!(!dir.path().join("timeline.json").exists());
+        writer.finish().unwrap();
+
+        let events: Vec<TimelineEvent> = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("timeline.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(events.len(), 100);
+        assert_eq!(events[99].name, "e99");
+        assert!(dir.path().join("ir.json").exists());
+        assert!(dir.path().join("traces.jsonl").exists());
+        let metrics: Metrics = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("metrics.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(metrics.counters["nodes"], 3.0);
+        assert_eq!(metrics.gauges["artifact.events"], 100.0);
+        let sizes = bundle_sizes(dir.path()).unwrap();
+        assert_eq!(
+            metrics.gauges["artifact.timeline.json.bytes"],
+            sizes["timeline.json"] as f64
+        );
+        assert_eq!(
+            metrics.gauges["artifact.total_bytes"],
+            (sizes["timeline.json"] + sizes["traces.jsonl"] + sizes["ir.json"]) as f64
+        );
+        assert!(metrics.gauges.contains_key("artifact.ir.json.serialize_ns"));
+
+        let missing = ArtifactWriter::spawn(&dir.path().join("missing"), 4);
+        missing.file("x.json", "{}");
+        assert!(missing.finish().is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! C ABI for compiled plugin libraries.
+//!
+//! A plugin is a `cdylib` exporting two `extern "C"` functions, normally generated with
+//! [`declare_plugin!`](crate::declare_plugin):
+//!
+//! - `awen_plugin_abi_version() -> u32`: must return [`PLUGIN_ABI_VERSION`]. The host calls it
+//!   before anything else in the library is touched.
+//! - `awen_plugin_entry() -> *const AwenPlugin`: a static table with the plugin's name,
+//!   version, and one function pointer per capability it provides.
+//!
+//! Every capability has the signature of [`PluginCall`]. It takes a NUL-terminated JSON
+//! request and writes a NUL-terminated JSON response allocated by the plugin. It returns
+//! [`PLUGIN_OK`] or, on failure, [`PLUGIN_ERROR`] with an error message as the response. The
+//! host copies the response and releases it with the plugin's `free_string`. Requests and
+//! responses use the serde forms of the types below, so plugins need not share the host's
+//! toolchain or be written in Rust. Capabilities may be called from several threads at once.
+//!
+//! | Capability | Request | Response |
+//! |---|---|---|
+//! | `simulate` | [`SimulateRequest`] | simulator-defined JSON |
+//! | `schedule` | [`ScheduleRequest`] | [`ExecutionPlan`] |
+//! | `validate_plan` | [`ValidatePlanRequest`] | `null` |
+//! | `compute_gradients` | [`GradientRequest`] | [`GradientResult`](crate::gradients::GradientResult) |
+
+use crate::gradients::{GradientOptions, NoiseModel};
+use crate::ir::Graph;
+use crate::scheduler::{ExecutionPlan, ResourceState, SchedulingConstraints};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::ffi::{c_char, CStr, CString};
+
+/// Version of the plugin ABI implemented by this runtime. Bump on any change to [`AwenPlugin`],
+/// [`PluginCall`] or the request and response types.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+pub const ABI_VERSION_SYMBOL: &[u8] = b"awen_plugin_abi_version\0";
+pub const ENTRY_SYMBOL: &[u8] = b"awen_plugin_entry\0";
+
+/// Capability call succeeded; the response is the result
+pub const PLUGIN_OK: i32 = 0;
+/// Capability call failed; the response is an error message
+pub const PLUGIN_ERROR: i32 = 1;
+
+/// `(request_json, &mut response) -> status`
+pub type PluginCall = extern "C" fn(*const c_char, *mut *mut c_char) -> i32;
+
+/// Entry table exported by a plugin. Strings are NUL-terminated and static; a `None`
+/// capability is not provided.
+#[repr(C)]
+pub struct AwenPlugin {
+    pub name: *const c_char,
+    pub version: *const c_char,
+    pub simulate: Option<PluginCall>,
+    pub schedule: Option<PluginCall>,
+    pub validate_plan: Option<PluginCall>,
+    pub compute_gradients: Option<PluginCall>,
+    /// Release a response string allocated by the plugin
+    pub free_string: extern "C" fn(*mut c_char),
+}
+
+// SAFETY: the table only holds pointers to static strings and functions.
+unsafe impl Sync for AwenPlugin {}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimulateRequest {
+    pub graph: Graph,
+    pub seed: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleRequest {
+    pub graph: Graph,
+    pub constraints: SchedulingConstraints,
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ValidatePlanRequest {
+    pub plan: ExecutionPlan,
+    pub state: ResourceState,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GradientRequest {
+    pub ir_json: String,
+    pub params: Vec<String>,
+    pub noise: NoiseModel,
+    pub opts: GradientOptions,
+}
+
+/// Plugin-side shim behind each generated capability: decode the request, run `handler`, and
+/// encode its result or error as the response. Panics are reported as errors.
+///
+/// # Safety
+///
+/// `request` must be null or a NUL-terminated string, and `response` null or valid for writes.
+pub unsafe fn dispatch<Req, Resp>(
+    handler: fn(Req) -> anyhow::Result<Resp>,
+    request: *const c_char,
+    response: *mut *mut c_char,
+) -> i32
+where
+    Req: DeserializeOwned,
+    Resp: Serialize,
+{
+    let run = || -> Result<String, String> {
+        if request.is_null() {
+            return Err("null request".to_string());
+        }
+        // SAFETY: the host passes a NUL-terminated string that outlives the call.
+        let request = unsafe { CStr::from_ptr(request) }
+            .to_str()
+            .map_err(|e| format!("request is not UTF-8: {}", e))?;
+        let request: Req =
+            serde_json::from_str(request).map_err(|e| format!("invalid request: {}", e))?;
+        let result = handler(request).map_err(|e| e.to_string())?;
+        serde_json::to_string(&result).map_err(|e| e.to_string())
+    };
+    let (status, body) = match std::panic::catch_unwind(run) {
+        Ok(Ok(json)) => (PLUGIN_OK, json),
+        Ok(Err(message)) => (PLUGIN_ERROR, message),
+        Err(_) => (PLUGIN_ERROR, "plugin panicked".to_string()),
+    };
+    if !response.is_null() {
+        let body = CString::new(body.replace('\0', "")).unwrap_or_default();
+        // SAFETY: the host passes a valid pointer to receive the response.
+        unsafe { *response = body.into_raw() };
+    }
+    status
+}
+
+/// Plugin-side `free_string`: release a response allocated by [`dispatch`]
+///
+/// # Safety
+///
+/// `s` must be null or a response from [`dispatch`] that has not been freed yet.
+pub unsafe fn free_string(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: `s` came from `CString::into_raw` in `dispatch`, in this same library.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Export the plugin symbols from a `cdylib`. Each capability handler is a
+/// `fn(Request) -> anyhow::Result<Response>`; list only those the plugin provides.
+///
+/// ```ignore
+/// awen_runtime::declare_plugin!("acme_sim", "0.3.0", {
+///     simulate: simulate,
+///     compute_gradients: gradients,
+/// });
+/// ```
+#[macro_export]
+macro_rules! declare_plugin {
+    ($name:expr, $version:expr, { $($capability:ident : $handler:path),* $(,)? }) => {
+        #[no_mangle]
+        pub extern "C" fn awen_plugin_abi_version() -> u32 {
+            $crate::plugins::abi::PLUGIN_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn awen_plugin_entry() -> *const $crate::plugins::abi::AwenPlugin {
+            extern "C" fn __awen_free_string(s: *mut ::std::ffi::c_char) {
+                // SAFETY: the host only frees responses written by `dispatch`.
+                unsafe { $crate::plugins::abi::free_string(s) }
+            }
+            static PLUGIN: $crate::plugins::abi::AwenPlugin = {
+                #[allow(unused_mut)]
+                let mut plugin = $crate::plugins::abi::AwenPlugin {
+                    name: concat!($name, "\0").as_ptr() as *const ::std::ffi::c_char,
+                    version: concat!($version, "\0").as_ptr() as *const ::std::ffi::c_char,
+                    simulate: None,
+                    schedule: None,
+                    validate_plan: None,
+                    compute_gradients: None,
+                    free_string: __awen_free_string,
+                };
+                $(
+                    plugin.$capability = {
+                        extern "C" fn call(
+                            request: *const ::std::ffi::c_char,
+                            response: *mut *mut ::std::ffi::c_char,
+                        ) -> i32 {
+                            // SAFETY: the host passes a request string and a response slot.
+                            unsafe { $crate::plugins::abi::dispatch($handler, request, response) }
+                        }
+                        Some(call)
+                    };
+                )*
+                plugin
+            };
+            &PLUGIN
+        }
+    };
+}
@@ -0,0 +1,399 @@
+//! Plugin discovery from `awen-plugin.toml` manifests.
+//!
+//! [`PluginRegistry::discover`] scans directories for manifests like:
+//!
+//! ```toml
+//! name = "acme_sim"
+//! version = "0.3.0"
+//! kind = "cdylib"             # cdylib | executable | grpc
+//! entry = "libacme_sim.so"    # relative to the manifest; a URL for grpc
+//! capabilities = ["simulate", "compute_gradients"]
+//! permissions = ["filesystem"]
+//! ```
+//!
+//! Valid manifests are registered without loading anything. A plugin is loaded by
+//! [`PluginRegistry::load`] the first time it is used, and the result is cached. The discovered
+//! set is recorded in run provenance through [`PluginRegistry::provenance`].
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::loader::{LoadedPlugin, PluginLoader, PluginTargets};
+use super::permissions::PluginPermission;
+use super::registry::{PluginManifest, PluginRegistry};
+use crate::storage::PluginRecord;
+
+#[cfg(feature = "grpc")]
+use super::grpc::RemotePlugin;
+
+/// File name of plugin manifests
+pub const MANIFEST_FILE: &str = "awen-plugin.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginKind {
+    /// Shared library implementing the C ABI in [`abi`](super::abi)
+    Cdylib,
+    /// Executable speaking JSON over stdin/stdout ([`PluginLoader::invoke`])
+    Executable,
+    /// Out-of-process plugin serving the gRPC protocol (`grpc` feature)
+    Grpc,
+}
+
+impl fmt::Display for PluginKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PluginKind::Cdylib => "cdylib",
+            PluginKind::Executable => "executable",
+            PluginKind::Grpc => "grpc",
+        })
+    }
+}
+
+/// Contents of an `awen-plugin.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginDescriptor {
+    pub name: String,
+    pub version: String,
+    pub kind: PluginKind,
+    /// Library or executable path relative to the manifest, or the gRPC endpoint
+    pub entry: String,
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<PluginPermission>,
+}
+
+impl PluginDescriptor {
+    /// Parse and validate the manifest at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let descriptor: Self = toml::from_str(&text)?;
+        descriptor.validate(path.parent().unwrap_or(Path::new(".")))?;
+        Ok(descriptor)
+    }
+
+    fn validate(&self, dir: &Path) -> Result<()> {
+        let valid_name = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        if self.name.is_empty() || !self.name.chars().all(valid_name) {
+            return Err(anyhow!(
+                "invalid plugin name {:?}: use letters, digits, '_' and '-'",
+                self.name
+            ));
+        }
+        if self.version.trim().is_empty() {
+            return Err(anyhow!("plugin {} has no version", self.name));
+        }
+        if self.capabilities.is_empty() {
+            return Err(anyhow!("plugin {} declares no capabilities", self.name));
+        }
+        match self.kind {
+            PluginKind::Cdylib | PluginKind::Executable => {
+                let entry = dir.join(&self.entry);
+                if !entry.is_file() {
+                    return Err(anyhow!(
+                        "plugin {} entry {} not found",
+                        self.name,
+                        entry.display()
+                    ));
+                }
+            }
+            PluginKind::Grpc => {
+                if !self.entry.starts_with("http://") && !self.entry.starts_with("https://") {
+                    return Err(anyhow!(
+                        "plugin {} entry {} is not an http(s) endpoint",
+                        self.name,
+                        self.entry
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A loaded plugin
+#[derive(Debug, Clone)]
+pub enum PluginHandle {
+    Library(Arc<LoadedPlugin>),
+    /// Executables are started per call with [`PluginLoader::invoke`]
+    Executable(PathBuf),
+    #[cfg(feature = "grpc")]
+    Remote(Arc<RemotePlugin>),
+}
+
+impl PluginHandle {
+    /// Register the plugin's simulators, schedulers and gradient providers into `targets`
+    pub fn register(&self, targets: &PluginTargets) {
+        match self {
+            PluginHandle::Library(plugin) => PluginLoader::register(plugin, targets),
+            PluginHandle::Executable(_) => {}
+            #[cfg(feature = "grpc")]
+            PluginHandle::Remote(plugin) => plugin.register(targets),
+        }
+    }
+}
+
+/// A plugin found by [`PluginRegistry::discover`], loaded on first use
+#[derive(Debug)]
+pub struct DiscoveredPlugin {
+    pub descriptor: PluginDescriptor,
+    /// The `awen-plugin.toml` it was read from
+    pub manifest_path: PathBuf,
+    handle: OnceCell<std::result::Result<PluginHandle, String>>,
+}
+
+impl DiscoveredPlugin {
+    /// Library or executable path, or the gRPC endpoint
+    pub fn entry(&self) -> String {
+        match self.descriptor.kind {
+            PluginKind::Grpc => self.descriptor.entry.clone(),
+            _ => self.entry_path().display().to_string(),
+        }
+    }
+
+    fn entry_path(&self) -> PathBuf {
+        self.manifest_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(&self.descriptor.entry)
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        matches!(self.handle.get(), Some(Ok(_)))
+    }
+
+    fn load(&self) -> Result<PluginHandle> {
+        self.handle
+            .get_or_init(|| self.open().map_err(|e| e.to_string()))
+            .clone()
+            .map_err(|e| anyhow!("plugin {}: {}", self.descriptor.name, e))
+    }
+
+    fn open(&self) -> Result<PluginHandle> {
+        match self.descriptor.kind {
+            PluginKind::Cdylib => {
+                let plugin = PluginLoader::load_library(&self.entry_path())?;
+                if plugin.name != self.descriptor.name {
+                    return Err(anyhow!(
+                        "library declares plugin {}, manifest says {}",
+                        plugin.name,
+                        self.descriptor.name
+                    ));
+                }
+                Ok(PluginHandle::Library(plugin))
+            }
+            PluginKind::Executable => Ok(PluginHandle::Executable(self.entry_path())),
+            #[cfg(feature = "grpc")]
+            PluginKind::Grpc => Ok(PluginHandle::Remote(RemotePlugin::connect(
+                &self.descriptor.entry,
+            )?)),
+            #[cfg(not(feature = "grpc"))]
+            PluginKind::Grpc => Err(anyhow!("runtime built without the grpc feature")),
+        }
+    }
+
+    fn manifest(&self) -> PluginManifest {
+        let d = &self.descriptor;
+        let (path, endpoint) = match d.kind {
+            PluginKind::Grpc => (None, Some(d.entry.clone())),
+            _ => (Some(self.entry_path()), None),
+        };
+        PluginManifest {
+            id: d.name.clone(),
+            version: d.version.clone(),
+            capabilities: d.capabilities.clone(),
+            signature: None,
+            public_key: None,
+            path,
+            endpoint,
+            permissions: d.permissions.clone(),
+        }
+    }
+}
+
+/// `awen-plugin.toml` files under `path`: the file itself, or the manifest in the directory
+/// and in each of its immediate subdirectories
+fn manifest_files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.to_path_buf()];
+    }
+    let mut files = Vec::new();
+    if path.join(MANIFEST_FILE).is_file() {
+        files.push(path.join(MANIFEST_FILE));
+    }
+    if let Ok(entries) = std::fs::read_dir(path) {
+        let mut subdirs: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.join(MANIFEST_FILE).is_file())
+            .map(|p| p.join(MANIFEST_FILE))
+            .collect();
+        subdirs.sort();
+        files.extend(subdirs);
+    }
+    files
+}
+
+impl PluginRegistry {
+    /// Scan `paths` for `awen-plugin.toml` manifests and register the valid ones without loading
+    /// them. Invalid manifests and duplicate names are reported in the returned error list
+    /// rather than aborting discovery.
+    pub fn discover<P: AsRef<Path>>(&mut self, paths: &[P]) -> Vec<(PathBuf, String)> {
+        let mut failed = Vec::new();
+        for file in paths.iter().flat_map(|p| manifest_files(p.as_ref())) {
+            let descriptor = match PluginDescriptor::load(&file) {
+                Ok(descriptor) => descriptor,
+                Err(e) => {
+                    failed.push((file, e.to_string()));
+                    continue;
+                }
+            };
+            if self.discovered(&descriptor.name).is_some() {
+                let message = format!("plugin {} already discovered", descriptor.name);
+                failed.push((file, message));
+                continue;
+            }
+            let plugin = DiscoveredPlugin {
+                descriptor,
+                manifest_path: file,
+                handle: OnceCell::new(),
+            };
+            self.register(plugin.manifest());
+            self.discovered.push(plugin);
+        }
+        failed
+    }
+
+    /// A plugin found by [`discover`](Self::discover)
+    pub fn discovered(&self, name: &str) -> Option<&DiscoveredPlugin> {
+        self.discovered.iter().find(|p| p.descriptor.name == name)
+    }
+
+    /// Load a discovered plugin, or return it if already loaded. Failures are cached too.
+    pub fn load(&self, name: &str) -> Result<PluginHandle> {
+        self.discovered(name)
+            .ok_or_else(|| anyhow!("plugin {} not discovered", name))?
+            .load()
+    }
+
+    /// The discovered plugins, for [`BundleBuilder::with_plugins`](crate::storage::BundleBuilder::with_plugins)
+    pub fn provenance(&self) -> Vec<PluginRecord> {
+        self.discovered
+            .iter()
+            .map(|p| PluginRecord {
+                name: p.descriptor.name.clone(),
+                version: p.descriptor.version.clone(),
+                kind: p.descriptor.kind.to_string(),
+                entry: p.entry(),
+                capabilities: p.descriptor.capabilities.clone(),
+                loaded: p.is_loaded(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_manifest(dir: &Path, body: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), body).unwrap();
+    }
+
+    #[test]
+    fn test_discover_validates_and_loads_lazily() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = root.path().join("tool");
+        write_manifest(
+            &tool,
+            r#"
+                name = "tool"
+                version = "1.0.0"
+                kind = "executable"
+                entry = "run.sh"
+                capabilities = ["execute"]
+                permissions = ["filesystem"]
+            "#,
+        );
+        std::fs::write(tool.join("run.sh"), "#!/bin/sh\ncat\n").unwrap();
+        write_manifest(
+            &root.path().join("broken_lib"),
+            r#"
+                name = "broken_lib"
+                version = "0.1.0"
+                kind = "cdylib"
+                entry = "libbroken.so"
+                capabilities = ["simulate"]
+            "#,
+        );
+        std::fs::write(root.path().join("broken_lib/libbroken.so"), b"garbage").unwrap();
+        write_manifest(
+            &root.path().join("missing_entry"),
+            r#"
+                name = "missing_entry"
+                version = "0.1.0"
+                kind = "executable"
+                entry = "nope"
+                capabilities = ["execute"]
+            "#,
+        );
+        write_manifest(
+            &root.path().join("bad_name"),
+            "name = \"bad name\"\nversion = \"1\"\nkind = \"grpc\"\nentry = \"http://h:1\"\ncapabilities = [\"scheduler\"]\n",
+        );
+        write_manifest(
+            &root.path().join("unknown_field"),
+            "name = \"x\"\nversion = \"1\"\nkind = \"grpc\"\nentry = \"http://h:1\"\ncapabilities = [\"scheduler\"]\nsudo = true\n",
+        );
+
+        let mut registry = PluginRegistry::new();
+        let failed = registry.discover(&[root.path()]);
+        let failed: Vec<String> = failed
+            .iter()
+            .map(|(path, _)| {
+                path.parent()
+                    .unwrap()
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(failed, ["bad_name", "missing_entry", "unknown_field"]);
+
+        let record = |registry: &PluginRegistry, name: &str| {
+            registry
+                .provenance()
+                .into_iter()
+                .find(|r| r.name == name)
+                .unwrap()
+        };
+        assert!(!record(&registry, "tool").loaded);
+        assert_eq!(
+            registry.find_by_capability("execute").unwrap().permissions,
+            [PluginPermission::Filesystem]
+        );
+
+        match registry.load("tool").unwrap() {
+            PluginHandle::Executable(path) => assert_eq!(path, tool.join("run.sh")),
+            other => panic!("unexpected handle {:?}", other),
+        }
+        assert!(record(&registry, "tool").loaded);
+
+        // A library that fails to load stays discovered but unloaded, and the error is cached
+        assert!(registry.load("broken_lib").is_err());
+        assert!(registry.load("broken_lib").is_err());
+        assert!(!record(&registry, "broken_lib").loaded);
+        assert!(registry.load("missing_entry").is_err());
+
+        // Rediscovery reports duplicates instead of replacing loaded plugins
+        let failed = registry.discover(&[tool.join(MANIFEST_FILE)]);
+        assert_eq!(failed.len(), 1);
+        assert!(registry.discovered("tool").unwrap().is_loaded());
+    }
+}
@@ -0,0 +1,921 @@
+//! Out-of-process plugins over gRPC (`grpc` feature).
+//!
+//! Backends written in Python or C++ run as their own process and serve the `PluginService`
+//! defined in `proto/awen_plugin.proto`. [`RemotePlugin`] connects to one and exposes it as a
+//! [`QuantumBackend`] ([`RemoteBackend`]) and a [`Scheduler`] ([`RemoteScheduler`]);
+//! [`PluginRegistry::register_remote`](super::PluginRegistry::register_remote) records it
+//! alongside local plugins. [`PluginServer`] is the other side, serving any Rust backend and
+//! scheduler, which is also how the protocol is tested.
+//!
+//! Runtime types travel as JSON strings inside the protobuf messages, so plugins only need the
+//! serde forms already used in artifact bundles. The client blocks on its own Tokio runtime;
+//! call it from synchronous code, not from inside another Tokio runtime.
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tonic::transport::Channel;
+use tonic::{Request, Response, Status};
+
+use super::loader::PluginTargets;
+use super::registry::PluginManifest;
+use crate::ir::Graph;
+use crate::quantum::wigner::{PhaseSpaceGrid, WignerGrid};
+use crate::quantum::{
+    BasisType, EvolutionTrace, Hamiltonian, MeasurementBasis, MeasurementLatency,
+    MeasurementOutcome, NoiseChannel, PreparationKind, QuantumBackend, QuantumState, ShotRecord,
+    StateSnapshot, StateType,
+};
+use crate::scheduler::{ExecutionPlan, ResourceState, Scheduler, SchedulingConstraints};
+
+use proto::plugin_service_client::PluginServiceClient;
+use proto::plugin_service_server::{PluginService, PluginServiceServer};
+
+/// Capability of a plugin implementing the `QuantumBackend` RPCs
+pub const CAPABILITY_QUANTUM_BACKEND: &str = "quantum_backend";
+/// Capability of a plugin implementing the `Scheduler` RPCs
+pub const CAPABILITY_SCHEDULER: &str = "scheduler";
+
+/// Protobuf messages of `proto/awen_plugin.proto` and the generated client and server
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Empty {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DescribeResponse {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub version: String,
+        #[prost(string, repeated, tag = "3")]
+        pub capabilities: Vec<String>,
+        #[prost(string, tag = "4")]
+        pub backend_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PrepareRequest {
+        #[prost(string, repeated, tag = "1")]
+        pub modes: Vec<String>,
+        #[prost(string, tag = "2")]
+        pub preparation_json: String,
+        #[prost(uint64, tag = "3")]
+        pub seed: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StateResponse {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EvolveRequest {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+        #[prost(string, tag = "2")]
+        pub hamiltonian_json: String,
+        #[prost(string, tag = "3")]
+        pub noise_channels_json: String,
+        #[prost(uint64, tag = "4")]
+        pub duration_ns: u64,
+        #[prost(uint64, tag = "5")]
+        pub seed: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EvolveResponse {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+        #[prost(string, tag = "2")]
+        pub trace_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MeasureRequest {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+        #[prost(string, tag = "2")]
+        pub basis_json: String,
+        #[prost(uint64, tag = "3")]
+        pub seed: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MeasureResponse {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+        #[prost(string, tag = "2")]
+        pub outcome_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StateRequest {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct WignerRequest {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+        #[prost(string, tag = "2")]
+        pub mode: String,
+        #[prost(string, tag = "3")]
+        pub grid_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FidelityRequest {
+        #[prost(string, tag = "1")]
+        pub state1_json: String,
+        #[prost(string, tag = "2")]
+        pub state2_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FidelityResponse {
+        #[prost(double, tag = "1")]
+        pub fidelity: f64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SampleRequest {
+        #[prost(string, tag = "1")]
+        pub state_json: String,
+        #[prost(string, tag = "2")]
+        pub basis_json: String,
+        #[prost(uint64, tag = "3")]
+        pub shots: u64,
+        #[prost(uint64, tag = "4")]
+        pub seed: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ReleaseStateRequest {
+        #[prost(string, tag = "1")]
+        pub state_id: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ScheduleRequest {
+        #[prost(string, tag = "1")]
+        pub graph_json: String,
+        #[prost(string, tag = "2")]
+        pub constraints_json: String,
+        #[prost(uint64, tag = "3")]
+        pub seed: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ValidatePlanRequest {
+        #[prost(string, tag = "1")]
+        pub plan_json: String,
+        #[prost(string, tag = "2")]
+        pub state_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct JsonResponse {
+        #[prost(string, tag = "1")]
+        pub json: String,
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/awen.plugin.PluginService.rs"));
+}
+
+/// Static properties of a remote backend, sent once in `Describe`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackendDescriptor {
+    pub name: String,
+    pub state_type: StateType,
+    pub supported_bases: Vec<BasisType>,
+    pub max_modes: usize,
+    pub coherence_time_ns: u64,
+    pub measurement_latency: MeasurementLatency,
+}
+
+impl BackendDescriptor {
+    pub fn of(backend: &dyn QuantumBackend) -> Self {
+        Self {
+            name: backend.name().to_string(),
+            state_type: backend.state_type(),
+            supported_bases: backend.supported_bases(),
+            max_modes: backend.max_modes(),
+            coherence_time_ns: backend.coherence_time_ns(),
+            measurement_latency: backend.measurement_latency(),
+        }
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Client
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Connection to a plugin process
+pub struct RemotePlugin {
+    pub name: String,
+    pub version: String,
+    pub endpoint: String,
+    pub capabilities: Vec<String>,
+    backend: Option<BackendDescriptor>,
+    client: PluginServiceClient<Channel>,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl std::fmt::Debug for RemotePlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemotePlugin")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("endpoint", &self.endpoint)
+            .field("capabilities", &self.capabilities)
+            .finish()
+    }
+}
+
+impl RemotePlugin {
+    /// Connect to the plugin serving at `endpoint` (e.g. `http://127.0.0.1:50051`) and fetch its
+    /// description.
+    pub fn connect(endpoint: &str) -> Result<Arc<Self>> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+        let mut client = runtime
+            .block_on(PluginServiceClient::connect(endpoint.to_string()))
+            .map_err(|e| anyhow!("failed to connect to plugin at {}: {}", endpoint, e))?;
+        let describe = runtime
+            .block_on(client.describe(proto::Empty {}))
+            .map_err(|s| anyhow!("plugin at {} describe: {}", endpoint, s.message()))?
+            .into_inner();
+
+        let backend = if describe.backend_json.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::from_str(&describe.backend_json)
+                    .map_err(|e| anyhow!("plugin {} backend: {}", describe.name, e))?,
+            )
+        };
+        if backend.is_none()
+            && describe
+                .capabilities
+                .iter()
+                .any(|c| c == CAPABILITY_QUANTUM_BACKEND)
+        {
+            return Err(anyhow!(
+                "plugin {} declares {} without a backend description",
+                describe.name,
+                CAPABILITY_QUANTUM_BACKEND
+            ));
+        }
+
+        Ok(Arc::new(Self {
+            name: describe.name,
+            version: describe.version,
+            endpoint: endpoint.to_string(),
+            capabilities: describe.capabilities,
+            backend,
+            client,
+            runtime,
+        }))
+    }
+
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// Manifest recorded in the [`PluginRegistry`](super::PluginRegistry). Remote plugins are
+    /// trusted by endpoint and carry no signature. They are granted no permissions, so the
+    /// chokepoint never lets them drive hardware.
+    pub fn manifest(&self) -> PluginManifest {
+        PluginManifest {
+            id: self.name.clone(),
+            version: self.version.clone(),
+            capabilities: self.capabilities.clone(),
+            signature: None,
+            public_key: None,
+            path: None,
+            endpoint: Some(self.endpoint.clone()),
+            permissions: Vec::new(),
+        }
+    }
+
+    /// The plugin as a [`QuantumBackend`]
+    pub fn backend(self: &Arc<Self>) -> Result<RemoteBackend> {
+        let descriptor = self.backend.clone().ok_or_else(|| {
+            anyhow!(
+                "plugin {} does not provide {}",
+                self.name,
+                CAPABILITY_QUANTUM_BACKEND
+            )
+        })?;
+        Ok(RemoteBackend {
+            plugin: self.clone(),
+            descriptor,
+        })
+    }
+
+    /// The plugin as a [`Scheduler`]
+    pub fn scheduler(self: &Arc<Self>) -> Result<RemoteScheduler> {
+        if !self.has_capability(CAPABILITY_SCHEDULER) {
+            return Err(anyhow!(
+                "plugin {} does not provide {}",
+                self.name,
+                CAPABILITY_SCHEDULER
+            ));
+        }
+        Ok(RemoteScheduler(self.clone()))
+    }
+
+    /// Register the plugin's scheduler, if it has one, under the plugin name
+    pub fn register(self: &Arc<Self>, targets: &PluginTargets) {
+        if let Ok(scheduler) = self.scheduler() {
+            targets.schedulers.register(&self.name, Arc::new(scheduler));
+        }
+    }
+
+    fn call<Resp, F, Fut>(&self, rpc: &str, f: F) -> Result<Resp>
+    where
+        F: FnOnce(PluginServiceClient<Channel>) -> Fut,
+        Fut: Future<Output = std::result::Result<Response<Resp>, Status>>,
+    {
+        self.runtime
+            .block_on(f(self.client.clone()))
+            .map(Response::into_inner)
+            .map_err(|s| anyhow!("remote plugin {} {}: {}", self.name, rpc, s.message()))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, rpc: &str, json: &str) -> Result<T> {
+        serde_json::from_str(json).map_err(|e| {
+            anyhow!(
+                "remote plugin {} {}: invalid response: {}",
+                self.name,
+                rpc,
+                e
+            )
+        })
+    }
+}
+
+/// [`QuantumBackend`] proxy onto a remote plugin
+pub struct RemoteBackend {
+    plugin: Arc<RemotePlugin>,
+    descriptor: BackendDescriptor,
+}
+
+impl QuantumBackend for RemoteBackend {
+    fn name(&self) -> &str {
+        &self.descriptor.name
+    }
+
+    fn state_type(&self) -> StateType {
+        self.descriptor.state_type
+    }
+
+    fn supported_bases(&self) -> Vec<BasisType> {
+        self.descriptor.supported_bases.clone()
+    }
+
+    fn max_modes(&self) -> usize {
+        self.descriptor.max_modes
+    }
+
+    fn coherence_time_ns(&self) -> u64 {
+        self.descriptor.coherence_time_ns
+    }
+
+    fn measurement_latency(&self) -> MeasurementLatency {
+        self.descriptor.measurement_latency.clone()
+    }
+
+    fn prepare(
+        &mut self,
+        modes: Vec<String>,
+        preparation: &PreparationKind,
+        seed: u64,
+    ) -> Result<QuantumState> {
+        let request = proto::PrepareRequest {
+            modes,
+            preparation_json: serde_json::to_string(preparation)?,
+            seed,
+        };
+        let response = self
+            .plugin
+            .call("prepare", |mut c| async move { c.prepare(request).await })?;
+        self.plugin.decode("prepare", &response.state_json)
+    }
+
+    fn evolve(
+        &mut self,
+        state: &mut QuantumState,
+        hamiltonian: &Hamiltonian,
+        noise_channels: &[NoiseChannel],
+        duration_ns: u64,
+        seed: u64,
+    ) -> Result<EvolutionTrace> {
+        let request = proto::EvolveRequest {
+            state_json: serde_json::to_string(state)?,
+            hamiltonian_json: serde_json::to_string(hamiltonian)?,
+            noise_channels_json: serde_json::to_string(noise_channels)?,
+            duration_ns,
+            seed,
+        };
+        let response = self
+            .plugin
+            .call("evolve", |mut c| async move { c.evolve(request).await })?;
+        let trace = self.plugin.decode("evolve", &response.trace_json)?;
+        *state = self.plugin.decode("evolve", &response.state_json)?;
+        Ok(trace)
+    }
+
+    fn measure(
+        &mut self,
+        state: &mut QuantumState,
+        basis: &MeasurementBasis,
+        seed: u64,
+    ) -> Result<MeasurementOutcome> {
+        let request = proto::MeasureRequest {
+            state_json: serde_json::to_string(state)?,
+            basis_json: serde_json::to_string(basis)?,
+            seed,
+        };
+        let response = self
+            .plugin
+            .call("measure", |mut c| async move { c.measure(request).await })?;
+        let outcome = self.plugin.decode("measure", &response.outcome_json)?;
+        *state = self.plugin.decode("measure", &response.state_json)?;
+        Ok(outcome)
+    }
+
+    fn snapshot(&self, state: &QuantumState) -> Result<StateSnapshot> {
+        let request = proto::StateRequest {
+            state_json: serde_json::to_string(state)?,
+        };
+        let response = self
+            .plugin
+            .call("snapshot", |mut c| async move { c.snapshot(request).await })?;
+        self.plugin.decode("snapshot", &response.json)
+    }
+
+    fn wigner(
+        &self,
+        state: &QuantumState,
+        mode: &str,
+        grid: &PhaseSpaceGrid,
+    ) -> Result<WignerGrid> {
+        let request = proto::WignerRequest {
+            state_json: serde_json::to_string(state)?,
+            mode: mode.to_string(),
+            grid_json: serde_json::to_string(grid)?,
+        };
+        let response = self
+            .plugin
+            .call("wigner", |mut c| async move { c.wigner(request).await })?;
+        self.plugin.decode("wigner", &response.json)
+    }
+
+    fn fidelity(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64> {
+        let request = proto::FidelityRequest {
+            state1_json: serde_json::to_string(state1)?,
+            state2_json: serde_json::to_string(state2)?,
+        };
+        let response = self
+            .plugin
+            .call("fidelity", |mut c| async move { c.fidelity(request).await })?;
+        Ok(response.fidelity)
+    }
+
+    fn sample(
+        &self,
+        state: &QuantumState,
+        basis: &MeasurementBasis,
+        shots: usize,
+        seed: u64,
+    ) -> Result<ShotRecord> {
+        let request = proto::SampleRequest {
+            state_json: serde_json::to_string(state)?,
+            basis_json: serde_json::to_string(basis)?,
+            shots: shots as u64,
+            seed,
+        };
+        let response = self
+            .plugin
+            .call("sample", |mut c| async move { c.sample(request).await })?;
+        self.plugin.decode("sample", &response.json)
+    }
+
+    fn release_state(&mut self, state_id: &str) -> Result<()> {
+        let request = proto::ReleaseStateRequest {
+            state_id: state_id.to_string(),
+        };
+        self.plugin.call("release_state", |mut c| async move {
+            c.release_state(request).await
+        })?;
+        Ok(())
+    }
+}
+
+/// [`Scheduler`] proxy onto a remote plugin
+pub struct RemoteScheduler(Arc<RemotePlugin>);
+
+impl Scheduler for RemoteScheduler {
+    fn schedule(
+        &self,
+        graph: &Graph,
+        constraints: &SchedulingConstraints,
+        seed: u64,
+    ) -> Result<ExecutionPlan> {
+        let request = proto::ScheduleRequest {
+            graph_json: serde_json::to_string(graph)?,
+            constraints_json: serde_json::to_string(constraints)?,
+            seed,
+        };
+        let response = self
+            .0
+            .call("schedule", |mut c| async move { c.schedule(request).await })?;
+        self.0.decode("schedule", &response.json)
+    }
+
+    fn validate_plan(&self, plan: &ExecutionPlan, current_state: &ResourceState) -> Result<()> {
+        let request = proto::ValidatePlanRequest {
+            plan_json: serde_json::to_string(plan)?,
+            state_json: serde_json::to_string(current_state)?,
+        };
+        self.0.call("validate_plan", |mut c| async move {
+            c.validate_plan(request).await
+        })?;
+        Ok(())
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Server
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//
+// The helpers below return `tonic::Status` errors, which the service trait dictates even though
+// clippy finds the type large.
+
+/// Serves a Rust backend and/or scheduler as a `PluginService`
+pub struct PluginServer {
+    name: String,
+    version: String,
+    backend: Option<Mutex<Box<dyn QuantumBackend>>>,
+    scheduler: Option<Arc<dyn Scheduler>>,
+}
+
+impl PluginServer {
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            backend: None,
+            scheduler: None,
+        }
+    }
+
+    pub fn with_backend(mut self, backend: Box<dyn QuantumBackend>) -> Self {
+        self.backend = Some(Mutex::new(backend));
+        self
+    }
+
+    pub fn with_scheduler(mut self, scheduler: Arc<dyn Scheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    pub fn into_service(self) -> PluginServiceServer<Self> {
+        PluginServiceServer::new(self)
+    }
+
+    /// Serve on `listener` until the task is dropped
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> Result<()> {
+        let incoming = tonic::transport::server::TcpIncoming::from_listener(listener, true, None)
+            .map_err(|e| anyhow!("{}", e))?;
+        tonic::transport::Server::builder()
+            .add_service(self.into_service())
+            .serve_with_incoming(incoming)
+            .await?;
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn backend(&self) -> std::result::Result<MutexGuard<'_, Box<dyn QuantumBackend>>, Status> {
+        let backend = self.backend.as_ref().ok_or_else(|| {
+            Status::unimplemented(format!("{} has no quantum backend", self.name))
+        })?;
+        backend
+            .lock()
+            .map_err(|_| Status::internal("backend lock poisoned"))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn scheduler(&self) -> std::result::Result<&Arc<dyn Scheduler>, Status> {
+        self.scheduler
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented(format!("{} has no scheduler", self.name)))
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn decode<T: DeserializeOwned>(field: &str, json: &str) -> std::result::Result<T, Status> {
+    serde_json::from_str(json).map_err(|e| Status::invalid_argument(format!("{}: {}", field, e)))
+}
+
+#[allow(clippy::result_large_err)]
+fn encode<T: Serialize>(value: &T) -> std::result::Result<String, Status> {
+    serde_json::to_string(value).map_err(|e| Status::internal(e.to_string()))
+}
+
+fn failed(e: anyhow::Error) -> Status {
+    Status::internal(e.to_string())
+}
+
+#[allow(clippy::result_large_err)]
+fn json_response<T: Serialize>(
+    value: &T,
+) -> std::result::Result<Response<proto::JsonResponse>, Status> {
+    Ok(Response::new(proto::JsonResponse {
+        json: encode(value)?,
+    }))
+}
+
+#[tonic::async_trait]
+impl PluginService for PluginServer {
+    async fn describe(
+        &self,
+        _request: Request<proto::Empty>,
+    ) -> std::result::Result<Response<proto::DescribeResponse>, Status> {
+        let mut capabilities = Vec::new();
+        let mut backend_json = String::new();
+        if self.backend.is_some() {
+            capabilities.push(CAPABILITY_QUANTUM_BACKEND.to_string());
+            backend_json = encode(&BackendDescriptor::of(self.backend()?.as_ref()))?;
+        }
+        if self.scheduler.is_some() {
+            capabilities.push(CAPABILITY_SCHEDULER.to_string());
+        }
+        Ok(Response::new(proto::DescribeResponse {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            capabilities,
+            backend_json,
+        }))
+    }
+
+    async fn prepare(
+        &self,
+        request: Request<proto::PrepareRequest>,
+    ) -> std::result::Result<Response<proto::StateResponse>, Status> {
+        let request = request.into_inner();
+        let preparation: PreparationKind = decode("preparation", &request.preparation_json)?;
+        let state = self
+            .backend()?
+            .prepare(request.modes, &preparation, request.seed)
+            .map_err(failed)?;
+        Ok(Response::new(proto::StateResponse {
+            state_json: encode(&state)?,
+        }))
+    }
+
+    async fn evolve(
+        &self,
+        request: Request<proto::EvolveRequest>,
+    ) -> std::result::Result<Response<proto::EvolveResponse>, Status> {
+        let request = request.into_inner();
+        let mut state: QuantumState = decode("state", &request.state_json)?;
+        let hamiltonian: Hamiltonian = decode("hamiltonian", &request.hamiltonian_json)?;
+        let noise: Vec<NoiseChannel> = decode("noise_channels", &request.noise_channels_json)?;
+        let trace = self
+            .backend()?
+            .evolve(
+                &mut state,
+                &hamiltonian,
+                &noise,
+                request.duration_ns,
+                request.seed,
+            )
+            .map_err(failed)?;
+        Ok(Response::new(proto::EvolveResponse {
+            state_json: encode(&state)?,
+            trace_json: encode(&trace)?,
+        }))
+    }
+
+    async fn measure(
+        &self,
+        request: Request<proto::MeasureRequest>,
+    ) -> std::result::Result<Response<proto::MeasureResponse>, Status> {
+        let request = request.into_inner();
+        let mut state: QuantumState = decode("state", &request.state_json)?;
+        let basis: MeasurementBasis = decode("basis", &request.basis_json)?;
+        let outcome = self
+            .backend()?
+            .measure(&mut state, &basis, request.seed)
+            .map_err(failed)?;
+        Ok(Response::new(proto::MeasureResponse {
+            state_json: encode(&state)?,
+            outcome_json: encode(&outcome)?,
+        }))
+    }
+
+    async fn snapshot(
+        &self,
+        request: Request<proto::StateRequest>,
+    ) -> std::result::Result<Response<proto::JsonResponse>, Status> {
+        let state: QuantumState = decode("state", &request.into_inner().state_json)?;
+        json_response(&self.backend()?.snapshot(&state).map_err(failed)?)
+    }
+
+    async fn wigner(
+        &self,
+        request: Request<proto::WignerRequest>,
+    ) -> std::result::Result<Response<proto::JsonResponse>, Status> {
+        let request = request.into_inner();
+        let state: QuantumState = decode("state", &request.state_json)?;
+        let grid: PhaseSpaceGrid = decode("grid", &request.grid_json)?;
+        json_response(
+            &self
+                .backend()?
+                .wigner(&state, &request.mode, &grid)
+                .map_err(failed)?,
+        )
+    }
+
+    async fn fidelity(
+        &self,
+        request: Request<proto::FidelityRequest>,
+    ) -> std::result::Result<Response<proto::FidelityResponse>, Status> {
+        let request = request.into_inner();
+        let state1: QuantumState = decode("state1", &request.state1_json)?;
+        let state2: QuantumState = decode("state2", &request.state2_json)?;
+        let fidelity = self.backend()?.fidelity(&state1, &state2).map_err(failed)?;
+        Ok(Response::new(proto::FidelityResponse { fidelity }))
+    }
+
+    async fn sample(
+        &self,
+        request: Request<proto::SampleRequest>,
+    ) -> std::result::Result<Response<proto::JsonResponse>, Status> {
+        let request = request.into_inner();
+        let state: QuantumState = decode("state", &request.state_json)?;
+        let basis: MeasurementBasis = decode("basis", &request.basis_json)?;
+        json_response(
+            &self
+                .backend()?
+                .sample(&state, &basis, request.shots as usize, request.seed)
+                .map_err(failed)?,
+        )
+    }
+
+    async fn release_state(
+        &self,
+        request: Request<proto::ReleaseStateRequest>,
+    ) -> std::result::Result<Response<proto::Empty>, Status> {
+        self.backend()?
+            .release_state(&request.into_inner().state_id)
+            .map_err(failed)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+
+    async fn schedule(
+        &self,
+        request: Request<proto::ScheduleRequest>,
+    ) -> std::result::Result<Response<proto::JsonResponse>, Status> {
+        let request = request.into_inner();
+        let graph: Graph = decode("graph", &request.graph_json)?;
+        let constraints: SchedulingConstraints = decode("constraints", &request.constraints_json)?;
+        json_response(
+            &self
+                .scheduler()?
+                .schedule(&graph, &constraints, request.seed)
+                .map_err(failed)?,
+        )
+    }
+
+    async fn validate_plan(
+        &self,
+        request: Request<proto::ValidatePlanRequest>,
+    ) -> std::result::Result<Response<proto::Empty>, Status> {
+        let request = request.into_inner();
+        let plan: ExecutionPlan = decode("plan", &request.plan_json)?;
+        let state: ResourceState = decode("state", &request.state_json)?;
+        self.scheduler()?
+            .validate_plan(&plan, &state)
+            .map_err(failed)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::PluginRegistry;
+    use crate::quantum::{GaussianSimulator, HomodyneAxis};
+    use crate::scheduler::{ResourceLimits, StaticScheduler};
+
+    /// Serve a Gaussian simulator and the static scheduler on a loopback port
+    fn spawn_server(runtime: &tokio::runtime::Runtime) -> String {
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = PluginServer::new("remote_gaussian", "0.2.0")
+            .with_backend(Box::new(GaussianSimulator::new()))
+            .with_scheduler(Arc::new(StaticScheduler::new()));
+        runtime.spawn(server.serve(listener));
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_remote_plugin_round_trip() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let endpoint = spawn_server(&runtime);
+
+        let mut registry = PluginRegistry::new();
+        let plugin = registry.register_remote(&endpoint).unwrap();
+        assert_eq!(plugin.version, "0.2.0");
+        let manifest = registry.find_by_capability(CAPABILITY_SCHEDULER).unwrap();
+        assert_eq!(manifest.id, "remote_gaussian");
+        assert_eq!(manifest.endpoint.as_deref(), Some(endpoint.as_str()));
+        assert!(registry.remote("remote_gaussian").is_some());
+
+        let mut remote = plugin.backend().unwrap();
+        let mut local = GaussianSimulator::new();
+        assert_eq!(remote.name(), local.name());
+        assert_eq!(remote.max_modes(), local.max_modes());
+
+        let preparation = PreparationKind::DisplacedSqueezed {
+            displacement_q: 1.0,
+            displacement_p: 0.0,
+            squeezing_db: 3.0,
+            squeezing_angle: 0.0,
+        };
+        let modes = vec!["m0".to_string()];
+        let mut remote_state = remote.prepare(modes.clone(), &preparation, 7).unwrap();
+        let mut local_state = local.prepare(modes.clone(), &preparation, 7).unwrap();
+        assert!((remote.fidelity(&remote_state, &local_state).unwrap() - 1.0).abs() < 1e-9);
+
+        let basis = MeasurementBasis {
+            basis_type: BasisType::Homodyne {
+                axis: HomodyneAxis::Q,
+            },
+            mode_labels: modes,
+        };
+        let remote_outcome = remote.measure(&mut remote_state, &basis, 11).unwrap();
+        let local_outcome = local.measure(&mut local_state, &basis, 11).unwrap();
+        assert_eq!(
+            serde_json::to_value(&remote_outcome.classical_results).unwrap(),
+            serde_json::to_value(&local_outcome.classical_results).unwrap()
+        );
+
+        let too_many: Vec<String> = (0..=local.max_modes()).map(|i| format!("m{}", i)).collect();
+        let err = remote.prepare(too_many, &preparation, 1).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("remote plugin remote_gaussian prepare"));
+
+        let targets = PluginTargets {
+            simulators: Arc::new(crate::plugins::SimulatorRegistry::new()),
+            schedulers: Arc::new(crate::scheduler::SchedulerRegistry::new()),
+            gradients: Arc::new(crate::gradients::GradientRegistry::new()),
+        };
+        plugin.register(&targets);
+        let scheduler = targets.schedulers.get("remote_gaussian").unwrap();
+        let graph = Graph {
+            nodes: vec![crate::ir::Node {
+                id: "node_0".to_string(),
+                node_type: "MZI".to_string(),
+                params: Default::default(),
+                measure_mode: None,
+                measurement: None,
+                conditional_branches: None,
+                feed_forward: None,
+            }],
+            edges: Vec::new(),
+            metadata: Default::default(),
+        };
+        let constraints = SchedulingConstraints::unconstrained(ResourceLimits {
+            max_wavelengths: 4,
+            max_memory_slots: 4,
+            max_concurrent_operations: 10,
+        });
+        let plan = scheduler.schedule(&graph, &constraints, 42).unwrap();
+        assert_eq!(plan.id, "exec-plan-42");
+    }
+
+    #[test]
+    fn test_connect_to_missing_plugin_fails() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let mut registry = PluginRegistry::new();
+        assert!(registry
+            .register_remote(&format!("http://{}", addr))
+            .is_err());
+        assert!(registry.plugins.is_empty());
+    }
+}
@@ -1,13 +1,232 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ffi::{c_char, CStr, CString};
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::Arc;
 
-/// Simple plugin loader that launches plugin binaries as subprocesses and
-/// communicates JSON over stdin/stdout. This avoids unsafe dynamic linking and
-/// keeps the runtime model simple and auditable.
+use super::abi::{
+    AwenPlugin, GradientRequest, PluginCall, ScheduleRequest, SimulateRequest, ValidatePlanRequest,
+    ABI_VERSION_SYMBOL, ENTRY_SYMBOL, PLUGIN_ABI_VERSION, PLUGIN_OK,
+};
+use super::simulator::{Simulator, SimulatorRegistry, GLOBAL_SIMULATOR_REGISTRY};
+use crate::gradients::{
+    GradientOptions, GradientProvider, GradientRegistry, GradientResult, NoiseModel,
+    GLOBAL_GRADIENT_REGISTRY,
+};
+use crate::ir::Graph;
+use crate::scheduler::{
+    ExecutionPlan, ResourceState, Scheduler, SchedulerRegistry, SchedulingConstraints,
+    GLOBAL_SCHEDULER_REGISTRY,
+};
+
+/// Plugin loader. Plugins are either executables invoked as subprocesses with JSON over
+/// stdin/stdout ([`invoke`](Self::invoke)), or `cdylib` libraries implementing the C ABI in
+/// [`abi`](super::abi) and loaded in-process ([`load_library`](Self::load_library)).
 pub struct PluginLoader;
 
+/// A plugin library loaded in-process
+pub struct LoadedPlugin {
+    pub name: String,
+    pub version: String,
+    /// `None` for plugins linked into the host
+    pub path: Option<PathBuf>,
+    table: &'static AwenPlugin,
+    // The table points into this library; it must be dropped last.
+    _library: Option<libloading::Library>,
+}
+
+// SAFETY: `AwenPlugin` is `Sync` and the ABI requires capabilities to be callable from any
+// thread.
+unsafe impl Send for LoadedPlugin {}
+unsafe impl Sync for LoadedPlugin {}
+
+impl std::fmt::Debug for LoadedPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadedPlugin")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("path", &self.path)
+            .field("capabilities", &self.capabilities())
+            .finish()
+    }
+}
+
+impl LoadedPlugin {
+    /// Wrap an entry table that is linked into the host, such as one generated by
+    /// [`declare_plugin!`](crate::declare_plugin) in the same binary.
+    pub fn from_static(table: &'static AwenPlugin) -> Result<Self> {
+        Self::new(table, None, None)
+    }
+
+    fn new(
+        table: &'static AwenPlugin,
+        path: Option<PathBuf>,
+        library: Option<libloading::Library>,
+    ) -> Result<Self> {
+        Ok(Self {
+            name: static_str(table.name).ok_or_else(|| anyhow!("plugin has no name"))?,
+            version: static_str(table.version).unwrap_or_default(),
+            path,
+            table,
+            _library: library,
+        })
+    }
+
+    /// Names of the capabilities the plugin provides
+    pub fn capabilities(&self) -> Vec<&'static str> {
+        let t = self.table;
+        [
+            ("simulate", t.simulate.is_some()),
+            ("schedule", t.schedule.is_some()),
+            ("validate_plan", t.validate_plan.is_some()),
+            ("compute_gradients", t.compute_gradients.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, present)| *present)
+        .map(|(name, _)| name)
+        .collect()
+    }
+
+    /// Call a capability with a JSON-encoded request and decode its response
+    fn call<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        capability: &str,
+        entry: Option<PluginCall>,
+        request: &Req,
+    ) -> Result<Resp> {
+        let entry =
+            entry.ok_or_else(|| anyhow!("plugin {} does not provide {}", self.name, capability))?;
+        let request = CString::new(serde_json::to_string(request)?)?;
+        let mut response: *mut c_char = std::ptr::null_mut();
+        let status = entry(request.as_ptr(), &mut response);
+        let body = if response.is_null() {
+            String::new()
+        } else {
+            // SAFETY: the plugin wrote a NUL-terminated string it owns until `free_string`.
+            let body = unsafe { CStr::from_ptr(response) }
+                .to_string_lossy()
+                .into_owned();
+            (self.table.free_string)(response);
+            body
+        };
+        if status != PLUGIN_OK {
+            return Err(anyhow!("plugin {} {}: {}", self.name, capability, body));
+        }
+        serde_json::from_str(&body).map_err(|e| {
+            anyhow!(
+                "plugin {} {}: invalid response: {}",
+                self.name,
+                capability,
+                e
+            )
+        })
+    }
+}
+
+fn static_str(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    // SAFETY: the ABI requires table strings to be static and NUL-terminated.
+    Some(
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+/// A plugin's `simulate` capability as a [`Simulator`]
+pub struct PluginSimulator(pub Arc<LoadedPlugin>);
+
+impl Simulator for PluginSimulator {
+    fn simulate(&self, graph: &Graph, seed: Option<u64>) -> Result<serde_json::Value> {
+        let request = SimulateRequest {
+            graph: graph.clone(),
+            seed,
+        };
+        self.0.call("simulate", self.0.table.simulate, &request)
+    }
+}
+
+/// A plugin's `schedule` and `validate_plan` capabilities as a [`Scheduler`]. Plans are
+/// accepted as valid when the plugin has no `validate_plan`.
+pub struct PluginScheduler(pub Arc<LoadedPlugin>);
+
+impl Scheduler for PluginScheduler {
+    fn schedule(
+        &self,
+        graph: &Graph,
+        constraints: &SchedulingConstraints,
+        seed: u64,
+    ) -> Result<ExecutionPlan> {
+        let request = ScheduleRequest {
+            graph: graph.clone(),
+            constraints: constraints.clone(),
+            seed,
+        };
+        self.0.call("schedule", self.0.table.schedule, &request)
+    }
+
+    fn validate_plan(&self, plan: &ExecutionPlan, current_state: &ResourceState) -> Result<()> {
+        if self.0.table.validate_plan.is_none() {
+            return Ok(());
+        }
+        let request = ValidatePlanRequest {
+            plan: plan.clone(),
+            state: current_state.clone(),
+        };
+        self.0
+            .call("validate_plan", self.0.table.validate_plan, &request)
+    }
+}
+
+/// A plugin's `compute_gradients` capability as a [`GradientProvider`]
+pub struct PluginGradientProvider(pub Arc<LoadedPlugin>);
+
+impl GradientProvider for PluginGradientProvider {
+    fn compute_gradients(
+        &self,
+        ir_json: &str,
+        params: &[String],
+        noise: &NoiseModel,
+        opts: &GradientOptions,
+    ) -> Result<GradientResult> {
+        let request = GradientRequest {
+            ir_json: ir_json.to_string(),
+            params: params.to_vec(),
+            noise: noise.clone(),
+            opts: opts.clone(),
+        };
+        self.0.call(
+            "compute_gradients",
+            self.0.table.compute_gradients,
+            &request,
+        )
+    }
+}
+
+/// Registries that loaded plugins are registered into
+#[derive(Clone)]
+pub struct PluginTargets {
+    pub simulators: Arc<SimulatorRegistry>,
+    pub schedulers: Arc<SchedulerRegistry>,
+    pub gradients: Arc<GradientRegistry>,
+}
+
+impl PluginTargets {
+    /// The global simulator, scheduler and gradient registries
+    pub fn global() -> Self {
+        Self {
+            simulators: GLOBAL_SIMULATOR_REGISTRY.clone(),
+            schedulers: GLOBAL_SCHEDULER_REGISTRY.clone(),
+            gradients: GLOBAL_GRADIENT_REGISTRY.clone(),
+        }
+    }
+}
+
 impl PluginLoader {
     /// Invoke a plugin executable at `path`, sending `input_json` to its stdin.
     /// Returns the stdout of the process as a string if the plugin ran and
@@ -41,4 +260,232 @@ impl PluginLoader {
             Ok(None)
         }
     }
+
+    /// Load a plugin library. The ABI version is checked before the entry table is read.
+    pub fn load_library(path: &Path) -> Result<Arc<LoadedPlugin>> {
+        // SAFETY: loading a library runs its initializers; plugin libraries are trusted code
+        // installed by the operator. The entry table is only read after the ABI version matches.
+        let library = unsafe { libloading::Library::new(path) }
+            .map_err(|e| anyhow!("failed to load plugin {}: {}", path.display(), e))?;
+
+        let abi = unsafe { library.get::<extern "C" fn() -> u32>(ABI_VERSION_SYMBOL) }
+            .map_err(|e| anyhow!("{} is not an AWEN plugin: {}", path.display(), e))?;
+        check_abi_version(abi())?;
+
+        let entry = unsafe { library.get::<extern "C" fn() -> *const AwenPlugin>(ENTRY_SYMBOL) }
+            .map_err(|e| anyhow!("{} has no plugin entry: {}", path.display(), e))?;
+        let table = entry();
+        if table.is_null() {
+            return Err(anyhow!("{} returned a null plugin entry", path.display()));
+        }
+        // SAFETY: ABI version matched, so the table has the layout of our `AwenPlugin`. It is
+        // static in the library, which the `LoadedPlugin` keeps loaded.
+        let table: &'static AwenPlugin = unsafe { &*table };
+        Ok(Arc::new(LoadedPlugin::new(
+            table,
+            Some(path.to_path_buf()),
+            Some(library),
+        )?))
+    }
+
+    /// Register each capability of `plugin` under the plugin's name: `simulate` as a
+    /// simulator, `schedule` as a scheduler and `compute_gradients` as a gradient provider.
+    pub fn register(plugin: &Arc<LoadedPlugin>, targets: &PluginTargets) {
+        let table = plugin.table;
+        if table.simulate.is_some() {
+            targets
+                .simulators
+                .register(&plugin.name, Arc::new(PluginSimulator(plugin.clone())));
+        }
+        if table.schedule.is_some() {
+            targets
+                .schedulers
+                .register(&plugin.name, Arc::new(PluginScheduler(plugin.clone())));
+        }
+        if table.compute_gradients.is_some() {
+            targets.gradients.register(
+                &plugin.name,
+                Arc::new(PluginGradientProvider(plugin.clone())),
+            );
+        }
+    }
+
+    /// Load and register every shared library in `dir`. Libraries that fail to load or are not
+    /// AWEN plugins are reported in the returned error list rather than aborting discovery.
+    pub fn discover(
+        dir: &Path,
+        targets: &PluginTargets,
+    ) -> (Vec<Arc<LoadedPlugin>>, Vec<(PathBuf, String)>) {
+        let mut loaded = Vec::new();
+        let mut failed = Vec::new();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return (loaded, failed),
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| is_shared_library(p))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match Self::load_library(&path) {
+                Ok(plugin) => {
+                    Self::register(&plugin, targets);
+                    loaded.push(plugin);
+                }
+                Err(e) => failed.push((path, e.to_string())),
+            }
+        }
+        (loaded, failed)
+    }
+}
+
+fn check_abi_version(found: u32) -> Result<()> {
+    if found != PLUGIN_ABI_VERSION {
+        return Err(anyhow!(
+            "plugin ABI version {} does not match runtime ABI version {}",
+            found,
+            PLUGIN_ABI_VERSION
+        ));
+    }
+    Ok(())
+}
+
+fn is_shared_library(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("so") | Some("dylib") | Some("dll")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::Node;
+    use crate::scheduler::{ResourceLimits, StaticScheduler};
+    use std::collections::HashMap;
+
+    fn simulate(req: SimulateRequest) -> Result<serde_json::Value> {
+        if req.graph.nodes.is_empty() {
+            return Err(anyhow!("empty graph"));
+        }
+        Ok(serde_json::json!({ "nodes": req.graph.nodes.len(), "seed": req.seed }))
+    }
+
+    fn schedule(req: ScheduleRequest) -> Result<ExecutionPlan> {
+        StaticScheduler::new().schedule(&req.graph, &req.constraints, req.seed)
+    }
+
+    fn gradients(req: GradientRequest) -> Result<GradientResult> {
+        Ok(GradientResult {
+            gradients: req.params.iter().map(|p| (p.clone(), 0.5)).collect(),
+            gradient_std: None,
+            provenance: HashMap::from([("strategy".to_string(), req.opts.strategy)]),
+        })
+    }
+
+    crate::declare_plugin!("test_plugin", "0.1.0", {
+        simulate: simulate,
+        schedule: schedule,
+        compute_gradients: gradients,
+    });
+
+    fn graph(nodes: usize) -> Graph {
+        Graph {
+            nodes: (0..nodes)
+                .map(|i| Node {
+                    id: format!("node_{}", i),
+                    node_type: "MZI".to_string(),
+                    params: HashMap::new(),
+                    measure_mode: None,
+                    measurement: None,
+                    conditional_branches: None,
+                    feed_forward: None,
+                })
+                .collect(),
+            edges: Vec::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn targets() -> PluginTargets {
+        PluginTargets {
+            simulators: Arc::new(SimulatorRegistry::new()),
+            schedulers: Arc::new(SchedulerRegistry::new()),
+            gradients: Arc::new(GradientRegistry::new()),
+        }
+    }
+
+    #[test]
+    fn test_declared_plugin_registers_capabilities() {
+        assert_eq!(awen_plugin_abi_version(), PLUGIN_ABI_VERSION);
+        // SAFETY: the generated entry returns a pointer to a static table.
+        let plugin = Arc::new(LoadedPlugin::from_static(unsafe { &*awen_plugin_entry() }).unwrap());
+        assert_eq!(plugin.name, "test_plugin");
+        assert_eq!(plugin.version, "0.1.0");
+        assert_eq!(
+            plugin.capabilities(),
+            ["simulate", "schedule", "compute_gradients"]
+        );
+
+        let targets = targets();
+        PluginLoader::register(&plugin, &targets);
+
+        let sim = targets.simulators.get("test_plugin").unwrap();
+        let out = sim.simulate(&graph(3), Some(7)).unwrap();
+        assert_eq!(out, serde_json::json!({ "nodes": 3, "seed": 7 }));
+        let err = sim.simulate(&graph(0), None).unwrap_err();
+        assert!(err.to_string().contains("empty graph"));
+
+        let constraints = SchedulingConstraints::unconstrained(ResourceLimits {
+            max_wavelengths: 4,
+            max_memory_slots: 4,
+            max_concurrent_operations: 10,
+        });
+        let scheduler = targets.schedulers.get("test_plugin").unwrap();
+        let plan = scheduler.schedule(&graph(2), &constraints, 42).unwrap();
+        assert_eq!(plan.id, "exec-plan-42");
+        assert_eq!(plan.schedule.len(), 2);
+
+        let provider = targets.gradients.get("test_plugin").unwrap();
+        let noise = NoiseModel {
+            shot_noise_std: None,
+            thermal_noise_std: None,
+            phase_noise_std: None,
+            loss_variation: None,
+            metadata: None,
+        };
+        let opts = GradientOptions {
+            strategy: "adjoint".to_string(),
+            seed: None,
+            samples: None,
+        };
+        let result = provider
+            .compute_gradients("{}", &["phi".to_string()], &noise, &opts)
+            .unwrap();
+        assert_eq!(result.gradients["phi"], 0.5);
+        assert_eq!(result.provenance["strategy"], "adjoint");
+    }
+
+    #[test]
+    fn test_abi_version_mismatch_rejected() {
+        assert!(check_abi_version(PLUGIN_ABI_VERSION).is_ok());
+        assert!(check_abi_version(PLUGIN_ABI_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_discover_reports_invalid_libraries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("not_a_plugin.so"), b"garbage").unwrap();
+        std::fs::write(dir.path().join("readme.txt"), b"ignored").unwrap();
+
+        let targets = targets();
+        let (loaded, failed) = PluginLoader::discover(dir.path(), &targets);
+        assert!(loaded.is_empty());
+        assert_eq!(failed.len(), 1);
+        assert!(targets.simulators.names().is_empty());
+
+        let (loaded, failed) = PluginLoader::discover(&dir.path().join("missing"), &targets);
+        assert!(loaded.is_empty() && failed.is_empty());
+    }
 }
@@ -1,7 +1,35 @@
+#[cfg(feature = "native")]
+pub mod abi;
+#[cfg(feature = "native")]
+pub mod discovery;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "native")]
 pub mod loader;
+#[cfg(feature = "native")]
+pub mod permissions;
 pub mod reference_sim;
+#[cfg(feature = "native")]
 pub mod registry;
+#[cfg(feature = "native")]
+pub mod simulator;
 
-pub use loader::PluginLoader;
+#[cfg(feature = "native")]
+pub use discovery::{DiscoveredPlugin, PluginDescriptor, PluginHandle, PluginKind};
+#[cfg(feature = "grpc")]
+pub use grpc::{PluginServer, RemoteBackend, RemotePlugin, RemoteScheduler};
+#[cfg(feature = "native")]
+pub use loader::{
+    LoadedPlugin, PluginGradientProvider, PluginLoader, PluginScheduler, PluginSimulator,
+    PluginTargets,
+};
+#[cfg(feature = "native")]
+pub use permissions::{ExecutionTarget, PluginPermission};
 pub use reference_sim::run_reference_simulator;
+#[cfg(feature = "native")]
 pub use registry::PluginRegistry;
+#[cfg(feature = "native")]
+pub use simulator::{
+    register_default_simulators, ReferenceSimulator, Simulator, SimulatorRegistry,
+    GLOBAL_SIMULATOR_REGISTRY,
+};
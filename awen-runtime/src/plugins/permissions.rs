@@ -0,0 +1,158 @@
+//! Capability-based permissions for plugins.
+//!
+//! A manifest lists the permissions its plugin needs. The execution chokepoint checks them
+//! before it runs or routes an operation for a plugin. On a simulation target no operation
+//! touches hardware, so none are needed. On a hardware target, measurement ops need
+//! `read-sensors` and every other op needs `write-params`. A simulation plugin declares neither,
+//! so it can never drive real hardware.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::registry::PluginManifest;
+use crate::chokepoint::PhotonicOp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PluginPermission {
+    /// Read detector and monitor values from real hardware
+    ReadSensors,
+    /// Set actuator parameters (phases, couplings, drive powers) on real hardware
+    WriteParams,
+    /// Access the run's artifact directory
+    Filesystem,
+    /// Open network connections
+    Network,
+}
+
+impl PluginPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginPermission::ReadSensors => "read-sensors",
+            PluginPermission::WriteParams => "write-params",
+            PluginPermission::Filesystem => "filesystem",
+            PluginPermission::Network => "network",
+        }
+    }
+}
+
+impl fmt::Display for PluginPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What the chokepoint executes against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionTarget {
+    #[default]
+    Simulation,
+    Hardware,
+}
+
+/// Whether `op` reads out detectors rather than setting parameters
+pub fn is_measurement_op(op: &PhotonicOp) -> bool {
+    let op_type = op.op_type.to_ascii_lowercase();
+    op_type.contains("measure") || op_type.contains("detect")
+}
+
+/// Permissions a plugin needs to run `op` against `target`
+pub fn required_permissions(op: &PhotonicOp, target: ExecutionTarget) -> Vec<PluginPermission> {
+    match target {
+        ExecutionTarget::Simulation => Vec::new(),
+        ExecutionTarget::Hardware if is_measurement_op(op) => vec![PluginPermission::ReadSensors],
+        ExecutionTarget::Hardware => vec![PluginPermission::WriteParams],
+    }
+}
+
+impl PluginManifest {
+    pub fn has_permission(&self, permission: PluginPermission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// Fail unless the manifest declares `permission`
+    pub fn require_permission(&self, permission: PluginPermission) -> Result<()> {
+        if self.has_permission(permission) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "plugin {} lacks permission {}",
+                self.id,
+                permission
+            ))
+        }
+    }
+
+    /// Fail unless the manifest declares every permission needed to run `op` against `target`
+    pub fn authorize(&self, op: &PhotonicOp, target: ExecutionTarget) -> Result<()> {
+        for permission in required_permissions(op, target) {
+            self.require_permission(permission)
+                .map_err(|e| anyhow!("{} for op {}", e, op.op_id))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(op_type: &str) -> PhotonicOp {
+        PhotonicOp {
+            op_id: "op1".into(),
+            op_type: op_type.into(),
+            targets: vec!["wg0".into()],
+            params: None,
+            calibration_handle: None,
+        }
+    }
+
+    #[test]
+    fn test_simulation_plugin_cannot_drive_hardware() {
+        let manifest: PluginManifest = serde_json::from_str(
+            r#"{"id": "sim", "version": "0.1", "capabilities": ["execute"],
+                "signature": null, "public_key": null, "path": null,
+                "permissions": ["filesystem"]}"#,
+        )
+        .unwrap();
+        assert!(manifest.has_permission(PluginPermission::Filesystem));
+
+        let phase = op("classical:phase_shifter");
+        let measure = op("measurement");
+        assert!(manifest
+            .authorize(&phase, ExecutionTarget::Simulation)
+            .is_ok());
+        assert!(manifest
+            .authorize(&measure, ExecutionTarget::Simulation)
+            .is_ok());
+        let err = manifest
+            .authorize(&phase, ExecutionTarget::Hardware)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "plugin sim lacks permission write-params for op op1"
+        );
+        assert!(manifest
+            .authorize(&measure, ExecutionTarget::Hardware)
+            .is_err());
+
+        let mut driver = manifest.clone();
+        driver.permissions = vec![PluginPermission::ReadSensors];
+        assert!(driver
+            .authorize(&measure, ExecutionTarget::Hardware)
+            .is_ok());
+        assert!(driver.authorize(&phase, ExecutionTarget::Hardware).is_err());
+    }
+
+    #[test]
+    fn test_unknown_permission_rejected() {
+        let parsed = serde_json::from_str::<PluginManifest>(
+            r#"{"id": "x", "version": "0.1", "capabilities": [],
+                "signature": null, "public_key": null, "path": null,
+                "permissions": ["root"]}"#,
+        );
+        assert!(parsed.is_err());
+    }
+}
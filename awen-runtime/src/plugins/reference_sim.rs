@@ -31,6 +31,8 @@ pub struct SimulationResult {
 /// - RING: applies frequency-dependent transfer approximation via `coupling` and `loss`
 /// - DETECTOR: produces measurement outcomes (analog & optional digital probabilistic outcome)
 /// - LOSS: multiply amplitude by (1 - loss)
+///
+/// Nodes run in `ir::execution_order`, after their edge inputs and the detectors they depend on.
 #[allow(unused_assignments)]
 pub fn run_reference_simulator(graph: &Graph, seed: Option<u64>) -> Result<SimulationResult> {
     let seed = seed.unwrap_or(0xDEADBEEF_u64);
@@ -46,7 +48,7 @@ pub fn run_reference_simulator(graph: &Graph, seed: Option<u64>) -> Result<Simul
     let mut current = (input_amp, 0.0_f64); // real, imag
     let mut _accumulated_loss = 0.0_f64;
 
-    for node in &graph.nodes {
+    for node in crate::ir::execution_order(graph)? {
         let node_type = node.node_type.to_lowercase();
         let mut phase_noise = 0.0_f64;
         let mut power_loss = 0.0_f64;
@@ -131,3 +133,27 @@ pub fn run_reference_simulator(graph: &Graph, seed: Option<u64>) -> Result<Simul
         node_results: results,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detector_runs_before_its_dependents() {
+        let graph: Graph = serde_json::from_str(
+            r#"{"nodes": [
+                {"id": "mzi_0", "type": "MZI", "params": {"phase": 0.2},
+                 "feed_forward": [{"param": "phase", "source_node": "det_0"}]},
+                {"id": "det_0", "type": "DETECTOR", "params": {"quantum": 1.0}}
+            ]}"#,
+        )
+        .unwrap();
+        let results = run_reference_simulator(&graph, Some(1)).unwrap();
+        let order: Vec<&str> = results
+            .node_results
+            .iter()
+            .map(|r| r.node_id.as_str())
+            .collect();
+        assert_eq!(order, ["det_0", "mzi_0"]);
+    }
+}
@@ -1,11 +1,16 @@
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
-use ed25519_dalek::{PublicKey, Signature, Verifier};
+use ed25519_dalek::{Signature, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 
+use super::discovery::DiscoveredPlugin;
+use super::permissions::PluginPermission;
+#[cfg(feature = "grpc")]
+use {super::grpc::RemotePlugin, std::collections::HashMap, std::sync::Arc};
+
 /// Basic plugin manifest describing capability and a signing handle.
 /// Implementations must provide a `public_key` and `signature` (both Base64).
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,19 +24,29 @@ pub struct PluginManifest {
     pub public_key: Option<String>,
     /// Optional path to plugin binary / adapter
     pub path: Option<PathBuf>,
+    /// gRPC endpoint of an out-of-process plugin. Omitted from the signed JSON when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Permissions the plugin needs; the chokepoint denies anything not listed. Omitted from
+    /// the signed JSON when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<PluginPermission>,
 }
 
 /// Registry that holds discovered plugins and performs manifest enforcement.
 #[derive(Debug, Default)]
 pub struct PluginRegistry {
     pub plugins: Vec<PluginManifest>,
+    /// Plugins found in `awen-plugin.toml` manifests, loaded on first use
+    pub(super) discovered: Vec<DiscoveredPlugin>,
+    /// Connections to out-of-process plugins, keyed by plugin id
+    #[cfg(feature = "grpc")]
+    remote: HashMap<String, Arc<RemotePlugin>>,
 }
 
 impl PluginRegistry {
     pub fn new() -> Self {
-        Self {
-            plugins: Vec::new(),
-        }
+        Self::default()
     }
 
     /// Register a plugin manifest into the registry (discovery step)
@@ -39,6 +54,22 @@ impl PluginRegistry {
         self.plugins.push(manifest);
     }
 
+    /// Connect to the out-of-process plugin at `endpoint` and register its manifest, with the
+    /// capabilities the plugin reports.
+    #[cfg(feature = "grpc")]
+    pub fn register_remote(&mut self, endpoint: &str) -> Result<Arc<RemotePlugin>> {
+        let plugin = RemotePlugin::connect(endpoint)?;
+        self.register(plugin.manifest());
+        self.remote.insert(plugin.name.clone(), plugin.clone());
+        Ok(plugin)
+    }
+
+    /// Connection to a plugin registered with [`register_remote`](Self::register_remote)
+    #[cfg(feature = "grpc")]
+    pub fn remote(&self, id: &str) -> Option<Arc<RemotePlugin>> {
+        self.remote.get(id).cloned()
+    }
+
     /// Verify manifest signing and policy. Real implementation should verify
     /// signature against an organizational trust root and check manifest contents.
     pub fn verify_manifest(&self, manifest: &PluginManifest) -> Result<bool> {
@@ -51,9 +82,9 @@ impl PluginRegistry {
                     .decode(pk_b64)
                     .map_err(|e| anyhow::anyhow!("invalid public_key base64: {}", e))?;
 
-                let pk = PublicKey::from_bytes(&pk_bytes)
+                let pk = VerifyingKey::try_from(pk_bytes.as_slice())
                     .map_err(|e| anyhow::anyhow!("invalid public key: {}", e))?;
-                let sig = Signature::from_bytes(&sig_bytes)
+                let sig = Signature::from_slice(&sig_bytes)
                     .map_err(|e| anyhow::anyhow!("invalid signature bytes: {}", e))?;
 
                 // Serialize manifest to canonical JSON excluding signature and public_key fields
@@ -62,7 +93,7 @@ impl PluginRegistry {
                 clone.public_key = None;
                 let data = serde_json::to_vec(&clone)?;
 
-                pk.verify(&data, &sig)
+                pk.verify_strict(&data, &sig)
                     .map(|_| true)
                     .map_err(|e| anyhow::anyhow!("signature verification failed: {}", e))
             }
@@ -181,6 +212,8 @@ mod tests {
             signature: None,
             public_key: None,
             path: None,
+            endpoint: None,
+            permissions: Vec::new(),
         };
 
         // No signature/public_key present — verify_manifest should return false
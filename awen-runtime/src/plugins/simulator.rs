@@ -0,0 +1,71 @@
+//! Simulator trait and registry.
+//!
+//! Simulators take an IR graph and a seed and return their results as JSON. The built-in
+//! reference simulator registers as `reference`; plugin libraries add more through
+//! [`PluginLoader`](super::PluginLoader).
+
+use crate::ir::Graph;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::run_reference_simulator;
+
+pub trait Simulator: Send + Sync {
+    /// Simulate `graph`; the same seed must give the same result
+    fn simulate(&self, graph: &Graph, seed: Option<u64>) -> Result<serde_json::Value>;
+}
+
+/// [`run_reference_simulator`] as a [`Simulator`]
+pub struct ReferenceSimulator;
+
+impl Simulator for ReferenceSimulator {
+    fn simulate(&self, graph: &Graph, seed: Option<u64>) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(run_reference_simulator(graph, seed)?)?)
+    }
+}
+
+/// Simulators keyed by name
+pub struct SimulatorRegistry {
+    simulators: RwLock<HashMap<String, Arc<dyn Simulator>>>,
+}
+
+impl SimulatorRegistry {
+    pub fn new() -> Self {
+        Self {
+            simulators: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&self, name: &str, simulator: Arc<dyn Simulator>) {
+        let mut w = self.simulators.write().unwrap();
+        w.insert(name.to_string(), simulator);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Simulator>> {
+        let r = self.simulators.read().unwrap();
+        r.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.simulators.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+impl Default for SimulatorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global registry accessible to runtime and CLI. Initialized lazily.
+pub static GLOBAL_SIMULATOR_REGISTRY: Lazy<Arc<SimulatorRegistry>> =
+    Lazy::new(|| Arc::new(SimulatorRegistry::new()));
+
+/// Register the built-in simulators (`reference`)
+pub fn register_default_simulators(registry: &SimulatorRegistry) {
+    registry.register("reference", Arc::new(ReferenceSimulator));
+}
@@ -6,10 +6,31 @@ use chrono::{DateTime, Utc};
 /// Defines quantum state, evolution, measurement, and backend interfaces
 /// supporting CV/DV quantum photonics with measurement-conditioned feedback.
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use uuid::Uuid;
 
+pub mod density;
+pub mod gaussian;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub(crate) mod linalg;
+pub mod mps;
+pub mod pauli;
+pub mod statevector;
+pub mod tomography;
+pub mod wigner;
+
+use density::DensityMatrix;
+use gaussian::{db_to_squeezing, GaussianState};
+use mps::MatrixProductState;
+use num_complex::Complex64;
+use pauli::{Pauli, PauliString};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use statevector::{GateMatrix, StateVector};
+use wigner::{fock_wigner, gaussian_wigner, PhaseSpaceGrid, WignerGrid};
+
 // ============================================================================
 // Core Quantum State Abstractions
 // ============================================================================
@@ -25,12 +46,42 @@ pub enum StateType {
 pub struct StateSnapshot {
     pub mode_labels: Vec<String>,
     pub global_purity: f64, // Tr(ρ²) ∈ [0,1]
+    /// Von Neumann entropy (bits) of each requested subsystem, keyed by
+    /// `(subsystem labels, complement labels)` joined with `,`.
+    #[serde(with = "partition_map")]
     pub entanglement_entropy: HashMap<(String, String), f64>,
     pub noise_floor: f64,
     pub timestamp: DateTime<Utc>,
     pub provenance: QuantumStateProvenance,
 }
 
+/// JSON object keys must be strings: partition-keyed maps are stored as `"A|B"` keys.
+mod partition_map {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        map: &HashMap<(String, String), f64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(map.iter().map(|((a, b), v)| (format!("{}|{}", a, b), v)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(String, String), f64>, D::Error> {
+        HashMap::<String, f64>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(key, v)| {
+                key.split_once('|')
+                    .map(|(a, b)| ((a.to_string(), b.to_string()), v))
+                    .ok_or_else(|| D::Error::custom(format!("partition key '{}' has no '|'", key)))
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuantumStateProvenance {
     pub state_id: String,
@@ -82,8 +133,12 @@ pub struct QuantumState {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CVStateData {
     pub modes: HashMap<String, CVMode>,
-    pub covariance: Vec<Vec<f64>>, // simplified
+    /// Quadrature covariance over `(q_0, p_0, q_1, p_1, ...)` in `mode_labels` order (ħ = 1).
+    pub covariance: Vec<Vec<f64>>,
     pub is_gaussian: bool,
+    /// Quadrature means in the same ordering as `covariance`.
+    #[serde(default)]
+    pub means: Vec<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,6 +155,17 @@ pub struct CVMode {
 pub struct DVStateData {
     pub qudits: HashMap<String, Qudit>,
     pub entanglement_graph: HashMap<String, Vec<String>>,
+    /// Joint amplitudes in `mode_labels` order (first label most significant). Empty means the
+    /// state is the product of the per-qudit `amplitudes`.
+    #[serde(default)]
+    pub statevector: Vec<Complex64>,
+    /// Joint density matrix, same ordering as `statevector`. When present it supersedes the
+    /// statevector and describes a (possibly) mixed state.
+    #[serde(default)]
+    pub density_matrix: Option<Vec<Vec<Complex64>>>,
+    /// Matrix-product form used by tensor-network backends; supersedes `statevector`.
+    #[serde(default)]
+    pub mps: Option<MatrixProductState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -141,8 +207,9 @@ impl QuantumState {
                         )
                     })
                     .collect(),
-                covariance: vec![],
+                covariance: GaussianState::vacuum(mode_labels.len()).covariance,
                 is_gaussian: true,
+                means: vec![0.0; 2 * mode_labels.len()],
             }),
             dv_data: None,
         }
@@ -190,16 +257,25 @@ impl QuantumState {
             dv_data: Some(DVStateData {
                 qudits,
                 entanglement_graph: HashMap::new(),
+                statevector: Vec::new(),
+                density_matrix: None,
+                mps: None,
             }),
         }
     }
 
+    /// Snapshot with the entanglement entropy of every single-mode cut.
     pub fn snapshot(&self) -> Result<StateSnapshot> {
+        self.snapshot_with_partitions(&self.single_mode_partitions())
+    }
+
+    /// Snapshot with the entanglement entropy of each requested subsystem against the rest.
+    pub fn snapshot_with_partitions(&self, partitions: &[Vec<String>]) -> Result<StateSnapshot> {
         Ok(StateSnapshot {
             mode_labels: self.mode_labels.clone(),
             global_purity: self.compute_purity(),
-            entanglement_entropy: HashMap::new(), // simplified
-            noise_floor: 0.01,                    // mock
+            entanglement_entropy: self.entanglement_entropies(partitions)?,
+            noise_floor: 0.01, // mock
             timestamp: self.timestamp,
             provenance: QuantumStateProvenance {
                 state_id: self.state_id.clone(),
@@ -226,13 +302,415 @@ impl QuantumState {
 
     fn compute_purity(&self) -> f64 {
         // Simplified: return nominal purity
-        if let Some(dv) = &self.dv_data {
+        if let Some(Ok(rho)) = self.stored_density_matrix() {
+            rho.purity()
+        } else if let Some(dv) = &self.dv_data {
             dv.qudits.values().map(|q| q.purity).sum::<f64>() / dv.qudits.len() as f64
+        } else if let Some(gaussian) = self.gaussian_state() {
+            gaussian.purity()
         } else {
             0.99 // mock
         }
     }
 
+    /// Gaussian view of CV data, in `mode_labels` order. States without a covariance matrix
+    /// (e.g. deserialized from older artifacts) are rebuilt as coherent states from their
+    /// per-mode displacements. Returns `None` for DV states.
+    pub fn gaussian_state(&self) -> Option<GaussianState> {
+        let cv = self.cv_data.as_ref()?;
+        let n = self.mode_labels.len();
+        let stored = GaussianState {
+            means: cv.means.clone(),
+            covariance: cv.covariance.clone(),
+        };
+        if stored.num_modes() == n && stored.validate().is_ok() {
+            return Some(stored);
+        }
+        let mut state = GaussianState::vacuum(n);
+        for (i, label) in self.mode_labels.iter().enumerate() {
+            if let Some(mode) = cv.modes.get(label) {
+                state.means[2 * i] = mode.displacement_q;
+                state.means[2 * i + 1] = mode.displacement_p;
+            }
+        }
+        Some(state)
+    }
+
+    /// Store a Gaussian state back into the CV data, keeping per-mode displacements in sync.
+    pub fn set_gaussian_state(&mut self, gaussian: GaussianState) -> Result<()> {
+        gaussian.validate()?;
+        if gaussian.num_modes() != self.mode_labels.len() {
+            return Err(anyhow!(
+                "Gaussian state has {} modes, state {} has {}",
+                gaussian.num_modes(),
+                self.state_id,
+                self.mode_labels.len()
+            ));
+        }
+        let cv = self
+            .cv_data
+            .as_mut()
+            .ok_or_else(|| anyhow!("state {} is not a CV state", self.state_id))?;
+        for (i, label) in self.mode_labels.iter().enumerate() {
+            if let Some(mode) = cv.modes.get_mut(label) {
+                mode.displacement_q = gaussian.means[2 * i];
+                mode.displacement_p = gaussian.means[2 * i + 1];
+            }
+        }
+        cv.means = gaussian.means;
+        cv.covariance = gaussian.covariance;
+        cv.is_gaussian = true;
+        Ok(())
+    }
+
+    /// Joint statevector of DV data, in `mode_labels` order. Returns `None` for CV states.
+    pub fn statevector(&self) -> Option<Result<StateVector>> {
+        let dv = self.dv_data.as_ref()?;
+        let qudits: Result<Vec<&Qudit>> = self
+            .mode_labels
+            .iter()
+            .map(|l| {
+                dv.qudits
+                    .get(l)
+                    .ok_or_else(|| anyhow!("missing qudit '{}' in state {}", l, self.state_id))
+            })
+            .collect();
+        let qudits = match qudits {
+            Ok(q) => q,
+            Err(e) => return Some(Err(e)),
+        };
+        let dims: Vec<usize> = qudits.iter().map(|q| q.dimension).collect();
+        if let Some(mps) = &dv.mps {
+            return Some(mps.to_statevector());
+        }
+        if !dv.statevector.is_empty() {
+            return Some(StateVector::from_amplitudes(dims, dv.statevector.clone()));
+        }
+        let factors: Vec<Vec<Complex64>> = qudits
+            .iter()
+            .map(|q| {
+                q.amplitudes
+                    .iter()
+                    .map(|a| Complex64::new(*a, 0.0))
+                    .collect()
+            })
+            .collect();
+        Some(StateVector::product(&factors))
+    }
+
+    /// Store a joint statevector, refreshing each qudit's marginal amplitudes and purity and
+    /// dropping entanglement-graph edges of qudits that are no longer entangled. Clears any
+    /// stored density matrix.
+    pub fn set_statevector(&mut self, sv: StateVector) -> Result<()> {
+        self.check_qudit_count(sv.num_qudits())?;
+        let marginals = (0..sv.num_qudits())
+            .map(|i| Ok((sv.marginal(i)?, sv.reduced_purity(i)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let dv = self.refresh_qudits(marginals)?;
+        dv.statevector = sv.amplitudes;
+        dv.density_matrix = None;
+        dv.mps = None;
+        Ok(())
+    }
+
+    /// Matrix-product form of DV data: the stored MPS if there is one, otherwise a decomposition
+    /// of the statevector truncated to `max_bond`. Returns `None` for CV states.
+    pub fn mps(&self, max_bond: usize) -> Option<Result<MatrixProductState>> {
+        let dv = self.dv_data.as_ref()?;
+        if let Some(mps) = &dv.mps {
+            return Some(Ok(mps.clone()));
+        }
+        if dv.statevector.is_empty() && dv.density_matrix.is_none() {
+            // Product state: no need to expand it densely.
+            let factors: Result<Vec<Vec<Complex64>>> = self
+                .mode_labels
+                .iter()
+                .map(|l| {
+                    dv.qudits
+                        .get(l)
+                        .map(|q| {
+                            q.amplitudes
+                                .iter()
+                                .map(|a| Complex64::new(*a, 0.0))
+                                .collect()
+                        })
+                        .ok_or_else(|| anyhow!("missing qudit '{}' in state {}", l, self.state_id))
+                })
+                .collect();
+            return Some(factors.and_then(|f| MatrixProductState::product(&f)));
+        }
+        if dv.density_matrix.is_some() {
+            return Some(Err(anyhow!(
+                "state {} is mixed and has no MPS form",
+                self.state_id
+            )));
+        }
+        Some(
+            self.statevector()?
+                .and_then(|sv| MatrixProductState::from_statevector(&sv, max_bond)),
+        )
+    }
+
+    /// Store an MPS, refreshing per-qudit marginals and reduced purities. Clears the stored
+    /// statevector and density matrix.
+    pub fn set_mps(&mut self, mps: MatrixProductState) -> Result<()> {
+        self.check_qudit_count(mps.num_qudits())?;
+        let marginals = mps
+            .reduced_densities()?
+            .into_iter()
+            .map(|rho| {
+                let probs = (0..rho.len()).map(|i| rho[i][i].re).collect();
+                let purity = rho
+                    .iter()
+                    .flat_map(|r| r.iter())
+                    .map(|v| v.norm_sqr())
+                    .sum();
+                (probs, purity)
+            })
+            .collect();
+        let dv = self.refresh_qudits(marginals)?;
+        dv.statevector = Vec::new();
+        dv.density_matrix = None;
+        dv.mps = Some(mps);
+        Ok(())
+    }
+
+    /// Joint density matrix of DV data: the stored mixed state if there is one, otherwise
+    /// `|ψ⟩⟨ψ|` of the statevector. Returns `None` for CV states.
+    pub fn density_matrix(&self) -> Option<Result<DensityMatrix>> {
+        if let Some(stored) = self.stored_density_matrix() {
+            return Some(stored);
+        }
+        Some(
+            self.statevector()?
+                .map(|sv| DensityMatrix::from_statevector(&sv)),
+        )
+    }
+
+    /// Whether the DV data is held as a density matrix rather than a statevector.
+    pub fn is_mixed_representation(&self) -> bool {
+        self.dv_data
+            .as_ref()
+            .is_some_and(|dv| dv.density_matrix.is_some())
+    }
+
+    fn stored_density_matrix(&self) -> Option<Result<DensityMatrix>> {
+        let rho = self.dv_data.as_ref()?.density_matrix.clone()?;
+        let dims = self
+            .mode_labels
+            .iter()
+            .map(|l| {
+                self.dv_data
+                    .as_ref()
+                    .and_then(|dv| dv.qudits.get(l))
+                    .map(|q| q.dimension)
+                    .ok_or_else(|| anyhow!("missing qudit '{}' in state {}", l, self.state_id))
+            })
+            .collect::<Result<Vec<usize>>>();
+        Some(dims.and_then(|dims| DensityMatrix::from_matrix(dims, rho)))
+    }
+
+    /// Store a joint density matrix, refreshing per-qudit marginals and reduced purities the same
+    /// way as [`QuantumState::set_statevector`]. Clears the stored statevector.
+    pub fn set_density_matrix(&mut self, rho: DensityMatrix) -> Result<()> {
+        self.check_qudit_count(rho.num_qudits())?;
+        let marginals = (0..rho.num_qudits())
+            .map(|i| Ok((rho.marginal(i)?, rho.partial_trace(&[i])?.purity())))
+            .collect::<Result<Vec<_>>>()?;
+        let dv = self.refresh_qudits(marginals)?;
+        dv.statevector = Vec::new();
+        dv.density_matrix = Some(rho.rho);
+        dv.mps = None;
+        Ok(())
+    }
+
+    fn check_qudit_count(&self, n: usize) -> Result<()> {
+        if n != self.mode_labels.len() {
+            return Err(anyhow!(
+                "register has {} qudits, state {} has {}",
+                n,
+                self.state_id,
+                self.mode_labels.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Update each qudit from its `(marginal probabilities, reduced purity)` and prune
+    /// entanglement-graph edges of pure qudits.
+    fn refresh_qudits(&mut self, marginals: Vec<(Vec<f64>, f64)>) -> Result<&mut DVStateData> {
+        let dv = self
+            .dv_data
+            .as_mut()
+            .ok_or_else(|| anyhow!("state {} is not a DV state", self.state_id))?;
+        let mut pure = Vec::new();
+        for (label, (probs, purity)) in self.mode_labels.iter().zip(marginals) {
+            if let Some(q) = dv.qudits.get_mut(label) {
+                q.amplitudes = probs.into_iter().map(f64::sqrt).collect();
+                q.purity = purity;
+            }
+            if purity > 1.0 - 1e-9 {
+                pure.push(label.clone());
+            }
+        }
+        dv.entanglement_graph.retain(|k, _| !pure.contains(k));
+        for partners in dv.entanglement_graph.values_mut() {
+            partners.retain(|p| !pure.contains(p));
+        }
+        dv.entanglement_graph.retain(|_, v| !v.is_empty());
+        Ok(dv)
+    }
+
+    /// Uhlmann fidelity with another state over the same modes: the Gaussian closed form for CV
+    /// states, `|⟨ψ|φ⟩|²` for pure DV states, and the density-matrix form when either DV state
+    /// is mixed.
+    pub fn fidelity(&self, other: &QuantumState) -> Result<f64> {
+        if self.mode_labels != other.mode_labels {
+            return Err(anyhow!(
+                "cannot compare states over different modes: {:?} vs {:?}",
+                self.mode_labels,
+                other.mode_labels
+            ));
+        }
+        match (self.gaussian_state(), other.gaussian_state()) {
+            (Some(a), Some(b)) => return a.fidelity(&b),
+            (None, None) => {}
+            _ => return Err(anyhow!("cannot compare a CV state with a DV state")),
+        }
+        let stored_mps = |s: &QuantumState| s.dv_data.as_ref().and_then(|dv| dv.mps.clone());
+        if let (Some(a), Some(b)) = (stored_mps(self), stored_mps(other)) {
+            return Ok(a.inner(&b)?.norm_sqr() / (a.norm_sqr() * b.norm_sqr()));
+        }
+        let missing = |s: &QuantumState| anyhow!("state {} has no DV data", s.state_id);
+        if self.is_mixed_representation() || other.is_mixed_representation() {
+            let a = self.density_matrix().ok_or_else(|| missing(self))??;
+            let b = other.density_matrix().ok_or_else(|| missing(other))??;
+            return a.fidelity(&b);
+        }
+        let a = self.statevector().ok_or_else(|| missing(self))??;
+        let b = other.statevector().ok_or_else(|| missing(other))??;
+        Ok(a.inner(&b)?.norm_sqr())
+    }
+
+    /// Each mode on its own, for states with at least two modes.
+    pub fn single_mode_partitions(&self) -> Vec<Vec<String>> {
+        if self.mode_labels.len() < 2 {
+            return Vec::new();
+        }
+        self.mode_labels.iter().map(|l| vec![l.clone()]).collect()
+    }
+
+    /// Von Neumann entropy (bits) of the reduced state of each subsystem in `partitions`,
+    /// keyed by `(subsystem, complement)` labels joined with `,`. For a globally pure state
+    /// this is the bipartite entanglement entropy. CV states use the symplectic eigenvalues of
+    /// the reduced covariance matrix; DV states the spectrum of the reduced density matrix.
+    pub fn entanglement_entropies(
+        &self,
+        partitions: &[Vec<String>],
+    ) -> Result<HashMap<(String, String), f64>> {
+        let indexed = partitions
+            .iter()
+            .map(|p| {
+                if p.is_empty() {
+                    return Err(anyhow!("entropy partition must name at least one mode"));
+                }
+                p.iter().map(|l| self.mode_index(l)).collect()
+            })
+            .collect::<Result<Vec<Vec<usize>>>>()?;
+        let entropies = match self.gaussian_state() {
+            Some(gaussian) => indexed
+                .iter()
+                .map(|modes| gaussian.reduced(modes)?.entropy())
+                .collect::<Result<Vec<f64>>>()?,
+            None => self
+                .reduced_dv_densities(&indexed)?
+                .iter()
+                .map(DensityMatrix::entropy)
+                .collect(),
+        };
+        let mut out = HashMap::new();
+        for (labels, entropy) in partitions.iter().zip(entropies) {
+            let complement: Vec<&str> = self
+                .mode_labels
+                .iter()
+                .filter(|l| !labels.contains(l))
+                .map(String::as_str)
+                .collect();
+            out.insert((labels.join(","), complement.join(",")), entropy);
+        }
+        Ok(out)
+    }
+
+    /// Reduced density matrices of DV `subsystems` (mode indices). MPS and product states serve
+    /// single sites from one sweep and larger subsystems from the contracted statevector.
+    /// Subsystems are traced out in parallel.
+    pub(crate) fn reduced_dv_densities(
+        &self,
+        subsystems: &[Vec<usize>],
+    ) -> Result<Vec<DensityMatrix>> {
+        let dv = self
+            .dv_data
+            .as_ref()
+            .ok_or_else(|| anyhow!("state {} is not a DV state", self.state_id))?;
+        if let Some(rho) = self.stored_density_matrix() {
+            let rho = rho?;
+            return subsystems
+                .par_iter()
+                .map(|m| rho.partial_trace(m))
+                .collect();
+        }
+        if !dv.statevector.is_empty() {
+            let sv = self
+                .statevector()
+                .ok_or_else(|| anyhow!("state {} has no DV data", self.state_id))??;
+            return subsystems
+                .par_iter()
+                .map(|m| DensityMatrix::reduced_from_statevector(&sv, m))
+                .collect();
+        }
+        let mps = self
+            .mps(usize::MAX)
+            .ok_or_else(|| anyhow!("state {} has no DV data", self.state_id))??;
+        let sites = if subsystems.iter().any(|m| m.len() == 1) {
+            mps.reduced_densities()?
+        } else {
+            Vec::new()
+        };
+        subsystems
+            .par_iter()
+            .map(|modes| match modes[..] {
+                [q] => Ok(DensityMatrix {
+                    dims: vec![sites[q].len()],
+                    rho: sites[q].clone(),
+                }),
+                _ => DensityMatrix::reduced_from_statevector(&mps.to_statevector()?, modes),
+            })
+            .collect()
+    }
+
+    /// Wigner function of one mode over `grid`. CV modes use the reduced Gaussian state; DV
+    /// qudits are read as Fock-truncated modes (level `k` = `k` photons).
+    pub fn wigner(&self, mode: &str, grid: &PhaseSpaceGrid) -> Result<WignerGrid> {
+        let index = self.mode_index(mode)?;
+        if let Some(gaussian) = self.gaussian_state() {
+            let w = gaussian_wigner(&gaussian.reduced(&[index])?)?;
+            return WignerGrid::sample(mode, &self.state_id, grid, w);
+        }
+        let rho = self
+            .reduced_dv_densities(&[vec![index]])?
+            .pop()
+            .ok_or_else(|| anyhow!("no reduced state for mode '{}'", mode))?;
+        WignerGrid::sample(mode, &self.state_id, grid, fock_wigner(&rho.rho))
+    }
+
+    /// Index of a mode label in `mode_labels`.
+    pub fn mode_index(&self, label: &str) -> Result<usize> {
+        self.mode_labels
+            .iter()
+            .position(|l| l == label)
+            .ok_or_else(|| anyhow!("unknown mode '{}' in state {}", label, self.state_id))
+    }
+
     pub fn is_coherent_at(&self, timestamp: DateTime<Utc>) -> bool {
         timestamp < self.coherence_deadline
     }
@@ -305,11 +783,149 @@ pub struct PauliTerm {
     pub operators: HashMap<String, String>, // "X", "Y", "Z", "I"
 }
 
+impl Hamiltonian {
+    /// Resolve the terms against a state's mode labels. Coefficients are angular frequencies
+    /// in rad/ns (ħ = 1), so evolving for `duration_ns` applies `exp(−iH·duration_ns)`.
+    pub fn pauli_strings(&self, state: &QuantumState) -> Result<Vec<PauliString>> {
+        self.terms
+            .iter()
+            .map(|term| {
+                let mut ops = term
+                    .operators
+                    .iter()
+                    .map(|(label, op)| Ok((state.mode_index(label)?, Pauli::parse(op)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                ops.sort_by_key(|(q, _)| *q);
+                Ok(PauliString::new(term.coefficient, ops))
+            })
+            .collect()
+    }
+}
+
+/// Noise applied during `QuantumBackend::evolve`. Rates are per nanosecond of evolution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NoiseChannel {
-    Depolarizing { error_rate: f64 },
-    PhaseDamping { error_rate: f64 },
-    ThermalNoise { bath_temp_k: f64, mode_loss: f64 },
+    Depolarizing {
+        error_rate: f64,
+    },
+    PhaseDamping {
+        error_rate: f64,
+    },
+    /// Loss of `mode_loss` of the mode's energy per nanosecond into a bath at `bath_temp_k`.
+    ThermalNoise {
+        bath_temp_k: f64,
+        /// Fraction of the energy lost per nanosecond, compounding: after `t` ns the
+        /// transmission is `(1 − mode_loss)^t`. Not a per-component loss; convert one with
+        /// [`NoiseChannel::thermal_loss`].
+        mode_loss: f64,
+    },
+}
+
+impl NoiseChannel {
+    /// `ThermalNoise` losing `loss` of the energy over `duration_ns`, e.g. a component's
+    /// insertion loss over its transit time
+    pub fn thermal_loss(bath_temp_k: f64, loss: f64, duration_ns: f64) -> Self {
+        let transmission = 1.0 - loss.clamp(0.0, 1.0);
+        NoiseChannel::ThermalNoise {
+            bath_temp_k,
+            mode_loss: 1.0 - transmission.powf(1.0 / duration_ns.max(f64::MIN_POSITIVE)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseChannel::Depolarizing { .. } => "Depolarizing",
+            NoiseChannel::PhaseDamping { .. } => "PhaseDamping",
+            NoiseChannel::ThermalNoise { .. } => "ThermalNoise",
+        }
+    }
+}
+
+/// Optical carrier used to convert bath temperatures into photon occupations (1550 nm).
+const OPTICAL_CARRIER_HZ: f64 = 193.4e12;
+
+/// Gaussian unitary gates applied by CV backends (`GaussianSimulator::apply_gate`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GaussianGate {
+    Displace {
+        mode: String,
+        q: f64,
+        p: f64,
+    },
+    PhaseShift {
+        mode: String,
+        phi: f64,
+    },
+    Squeeze {
+        mode: String,
+        r: f64,
+        angle: f64,
+    },
+    BeamSplitter {
+        mode_a: String,
+        mode_b: String,
+        theta: f64,
+        phi: f64,
+    },
+}
+
+impl GaussianGate {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GaussianGate::Displace { .. } => "displace",
+            GaussianGate::PhaseShift { .. } => "phase_shift",
+            GaussianGate::Squeeze { .. } => "squeeze",
+            GaussianGate::BeamSplitter { .. } => "beam_splitter",
+        }
+    }
+}
+
+/// Discrete-variable gates applied by DV backends (`StatevectorSimulator::apply_gate`). Names
+/// match the `QuantumGate` node names used by `engine_v2`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DVGate {
+    Hadamard { qudit: String },
+    RX { qudit: String, theta: f64 },
+    CNOT { control: String, target: String },
+}
+
+impl DVGate {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DVGate::Hadamard { .. } => "Hadamard",
+            DVGate::RX { .. } => "RX",
+            DVGate::CNOT { .. } => "CNOT",
+        }
+    }
+
+    /// Build a gate from an `engine_v2` gate name, the qudits it acts on and its parameters
+    /// (`theta` for RX).
+    pub fn from_name(
+        gate_name: &str,
+        qudits: &[String],
+        parameters: &HashMap<String, f64>,
+    ) -> Result<Self> {
+        let qudit = |i: usize| {
+            qudits
+                .get(i)
+                .cloned()
+                .ok_or_else(|| anyhow!("gate {} needs {} qudit(s)", gate_name, i + 1))
+        };
+        match gate_name {
+            "Hadamard" | "H" => Ok(DVGate::Hadamard { qudit: qudit(0)? }),
+            "RX" => Ok(DVGate::RX {
+                qudit: qudit(0)?,
+                theta: *parameters
+                    .get("theta")
+                    .ok_or_else(|| anyhow!("RX gate requires a 'theta' parameter"))?,
+            }),
+            "CNOT" | "CX" => Ok(DVGate::CNOT {
+                control: qudit(0)?,
+                target: qudit(1)?,
+            }),
+            other => Err(anyhow!("unsupported DV gate '{}'", other)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -405,6 +1021,124 @@ impl MeasurementOutcome {
     }
 }
 
+/// Repeated measurements of one state: `shots[k][c]` is shot `k`'s result for `labels[c]`
+/// (the keys `measure` would put in `classical_results`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShotRecord {
+    pub record_id: String,
+    pub state_id: String,
+    pub measurement_type: BasisType,
+    pub labels: Vec<String>,
+    pub shots: Vec<Vec<MeasurementResult>>,
+    pub seed: u64,
+}
+
+impl ShotRecord {
+    pub fn new(
+        state_id: String,
+        basis_type: BasisType,
+        labels: Vec<String>,
+        shots: Vec<Vec<MeasurementResult>>,
+        seed: u64,
+    ) -> Self {
+        ShotRecord {
+            record_id: Uuid::new_v4().to_string(),
+            state_id,
+            measurement_type: basis_type,
+            labels,
+            shots,
+            seed,
+        }
+    }
+
+    pub fn num_shots(&self) -> usize {
+        self.shots.len()
+    }
+
+    fn label_index(&self, label: &str) -> Result<usize> {
+        self.labels
+            .iter()
+            .position(|l| l == label)
+            .ok_or_else(|| anyhow!("shot record has no column '{}'", label))
+    }
+
+    /// Discrete outcomes of `label`, one per shot.
+    pub fn outcomes(&self, label: &str) -> Result<Vec<usize>> {
+        let c = self.label_index(label)?;
+        self.shots
+            .iter()
+            .map(|shot| match shot[c] {
+                MeasurementResult::DiscreteOutcome(k) => Ok(k),
+                ref other => Err(anyhow!("'{}' is not discrete: {:?}", label, other)),
+            })
+            .collect()
+    }
+
+    /// Continuous values of `label`, one per shot.
+    pub fn values(&self, label: &str) -> Result<Vec<f64>> {
+        let c = self.label_index(label)?;
+        self.shots
+            .iter()
+            .map(|shot| match shot[c] {
+                MeasurementResult::ContinuousValue(x) => Ok(x),
+                ref other => Err(anyhow!("'{}' is not continuous: {:?}", label, other)),
+            })
+            .collect()
+    }
+
+    /// Histogram of discrete shots keyed by the outcomes in `labels` order, joined with ','.
+    pub fn counts(&self) -> Result<BTreeMap<String, usize>> {
+        let mut counts = BTreeMap::new();
+        for shot in &self.shots {
+            let key = shot
+                .iter()
+                .map(|r| match r {
+                    MeasurementResult::DiscreteOutcome(k) => Ok(k.to_string()),
+                    other => Err(anyhow!("cannot count continuous result {:?}", other)),
+                })
+                .collect::<Result<Vec<_>>>()?
+                .join(",");
+            *counts.entry(key).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+}
+
+/// One RNG seed per parallel work item (shot, setting, ...), drawn up front from `seed` so the
+/// results do not depend on how rayon schedules the items.
+pub(crate) fn split_seeds(seed: u64, items: usize) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..items).map(|_| rng.gen()).collect()
+}
+
+/// Cumulative distribution of `probs` and one inverse-CDF target `u·total` per shot. Shared by
+/// the CPU lookup and the `gpu` kernel so both draw identical shots for a seed.
+pub(crate) fn sampling_targets(
+    probs: &[f64],
+    shots: usize,
+    rng: &mut StdRng,
+) -> (Vec<f64>, Vec<f64>) {
+    let mut total = 0.0;
+    let cdf: Vec<f64> = probs
+        .iter()
+        .map(|p| {
+            total += p.max(0.0);
+            total
+        })
+        .collect();
+    let targets = (0..shots).map(|_| rng.gen::<f64>() * total).collect();
+    (cdf, targets)
+}
+
+/// Draw `shots` indices from `probs` by inverse-CDF lookup, one uniform per shot.
+fn sample_distribution(probs: &[f64], shots: usize, rng: &mut StdRng) -> Vec<usize> {
+    let (cdf, targets) = sampling_targets(probs, shots, rng);
+    targets
+        .par_iter()
+        .map(|t| cdf.partition_point(|c| c <= t).min(probs.len() - 1))
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MeasurementLatency {
     pub detection_latency_ns: u64,
@@ -590,7 +1324,14 @@ pub struct QuantumMetrics {
     pub coherence_remaining_ns: u64,
     pub decoherence_rate: f64,
     pub measurement_confidence: f64,
+    #[serde(with = "partition_map")]
     pub entanglement_entropy: HashMap<(String, String), f64>,
+    /// Largest bond dimension of a tensor-network state (0 for dense backends).
+    #[serde(default)]
+    pub max_bond_dimension: usize,
+    /// Accumulated discarded weight from bond truncation (0 for exact backends).
+    #[serde(default)]
+    pub truncation_error: f64,
 }
 
 // ============================================================================
@@ -654,11 +1395,33 @@ impl QuantumArtifact {
                 coherence_remaining_ns: 100,
                 decoherence_rate: 0.001,
                 measurement_confidence: 0.95,
-                entanglement_entropy: HashMap::new(),
+                entanglement_entropy: initial_state
+                    .entanglement_entropies(&initial_state.single_mode_partitions())
+                    .unwrap_or_default(),
+                max_bond_dimension: 0,
+                truncation_error: 0.0,
             },
         }
     }
 
+    /// Record an intermediate snapshot of `state` with the entropies of `partitions`, which also
+    /// become the artifact's current entanglement metrics.
+    pub fn record_snapshot(
+        &mut self,
+        state: &QuantumState,
+        partitions: &[Vec<String>],
+    ) -> Result<()> {
+        let snapshot = state.snapshot_with_partitions(partitions)?;
+        self.metrics.entanglement_entropy = snapshot.entanglement_entropy.clone();
+        self.intermediate_states.push(snapshot);
+        Ok(())
+    }
+
+    /// Record the noise profile the execution ran under, so replays resolve the same model.
+    pub fn set_noise_profile(&mut self, profile: &crate::simulator::NoiseProfile) {
+        self.noise_model_id = Some(profile.id.clone());
+    }
+
     pub fn can_deterministic_replay(&self) -> bool {
         !self.backend_name.is_empty() && self.noise_model_id.is_some()
     }
@@ -701,8 +1464,23 @@ pub trait QuantumBackend: Send + Sync {
 
     fn snapshot(&self, state: &QuantumState) -> Result<StateSnapshot>;
 
+    /// Wigner function of `mode` sampled over `grid`.
+    fn wigner(&self, state: &QuantumState, mode: &str, grid: &PhaseSpaceGrid)
+        -> Result<WignerGrid>;
+
     fn fidelity(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64>;
 
+    /// `shots` repetitions of measuring `basis` on `state`, seeded by `seed`. Unlike `measure`,
+    /// `state` is left untouched and shots are not drawn by collapsing a copy per shot where the
+    /// representation allows sampling the joint outcome distribution directly.
+    fn sample(
+        &self,
+        state: &QuantumState,
+        basis: &MeasurementBasis,
+        shots: usize,
+        seed: u64,
+    ) -> Result<ShotRecord>;
+
     fn release_state(&mut self, state_id: &str) -> Result<()>;
 }
 
@@ -728,6 +1506,82 @@ impl GaussianSimulator {
             coherence_time_ns: 500,
         }
     }
+
+    /// Apply one noise channel to every mode for `duration_ns`, returning a description of the
+    /// effective channel for the evolution trace.
+    ///
+    /// - `Depolarizing`: additive Gaussian noise of `p` vacuum units, `p = 1 − e^{−rt}`.
+    /// - `PhaseDamping`: phase diffusion with variance `2rt`, so coherences decay as `e^{−rt}`.
+    /// - `ThermalNoise`: thermal loss with `η = (1 − mode_loss)^t` into `n̄(bath_temp_k)`,
+    ///   `mode_loss` being per nanosecond.
+    fn apply_noise(
+        gaussian: &mut GaussianState,
+        channel: &NoiseChannel,
+        duration_ns: u64,
+    ) -> Result<String> {
+        let t = duration_ns as f64;
+        let modes = 0..gaussian.num_modes();
+        let description = match channel {
+            NoiseChannel::Depolarizing { error_rate } => {
+                let p = -(-error_rate.max(0.0) * t).exp_m1();
+                for mode in modes {
+                    gaussian.add_noise(mode, p * gaussian::VACUUM_VARIANCE)?;
+                }
+                format!("p={:.6}", p)
+            }
+            NoiseChannel::PhaseDamping { error_rate } => {
+                let variance = 2.0 * error_rate.max(0.0) * t;
+                for mode in modes {
+                    gaussian.phase_diffusion(mode, variance)?;
+                }
+                format!("phase_variance={:.6}", variance)
+            }
+            NoiseChannel::ThermalNoise {
+                bath_temp_k,
+                mode_loss,
+            } => {
+                let eta = (1.0 - mode_loss.clamp(0.0, 1.0)).powf(t);
+                let bath_photons = gaussian::thermal_occupation(OPTICAL_CARRIER_HZ, *bath_temp_k);
+                for mode in modes {
+                    gaussian.attenuate(mode, eta, bath_photons)?;
+                }
+                format!("eta={:.6},bath_photons={:.6}", eta, bath_photons)
+            }
+        };
+        Ok(format!("{}({})", channel.name(), description))
+    }
+}
+
+impl GaussianSimulator {
+    /// Apply a Gaussian unitary to a CV state's covariance and displacement.
+    pub fn apply_gate(&self, state: &mut QuantumState, gate: &GaussianGate) -> Result<()> {
+        let mut gaussian = state
+            .gaussian_state()
+            .ok_or_else(|| anyhow!("gate {} requires a CV state", gate.name()))?;
+        match gate {
+            GaussianGate::Displace { mode, q, p } => {
+                gaussian.displace(state.mode_index(mode)?, *q, *p)?
+            }
+            GaussianGate::PhaseShift { mode, phi } => {
+                gaussian.phase_shift(state.mode_index(mode)?, *phi)?
+            }
+            GaussianGate::Squeeze { mode, r, angle } => {
+                gaussian.squeeze(state.mode_index(mode)?, *r, *angle)?
+            }
+            GaussianGate::BeamSplitter {
+                mode_a,
+                mode_b,
+                theta,
+                phi,
+            } => gaussian.beam_splitter(
+                state.mode_index(mode_a)?,
+                state.mode_index(mode_b)?,
+                *theta,
+                *phi,
+            )?,
+        }
+        state.set_gaussian_state(gaussian)
+    }
 }
 
 impl Default for GaussianSimulator {
@@ -774,7 +1628,7 @@ impl QuantumBackend for GaussianSimulator {
     fn prepare(
         &mut self,
         modes: Vec<String>,
-        _preparation: &PreparationKind,
+        preparation: &PreparationKind,
         seed: u64,
     ) -> Result<QuantumState> {
         if modes.len() > self.max_modes {
@@ -785,33 +1639,106 @@ impl QuantumBackend for GaussianSimulator {
             ));
         }
 
-        Ok(QuantumState::new_cv(modes, seed, self.coherence_time_ns))
+        let n = modes.len();
+        let gaussian = match preparation {
+            PreparationKind::DisplacedSqueezed {
+                displacement_q,
+                displacement_p,
+                squeezing_db,
+                squeezing_angle,
+            } => {
+                let mut g = GaussianState::vacuum(n);
+                for mode in 0..n {
+                    g.squeeze(mode, db_to_squeezing(*squeezing_db), *squeezing_angle)?;
+                    g.displace(mode, *displacement_q, *displacement_p)?;
+                }
+                g
+            }
+            PreparationKind::ThermalState { mean_photons } => {
+                if *mean_photons < 0.0 {
+                    return Err(anyhow!("mean photon number must be non-negative"));
+                }
+                GaussianState::thermal(n, *mean_photons)
+            }
+            other => {
+                return Err(anyhow!(
+                    "{:?} is not a Gaussian state; use a DV backend",
+                    other
+                ))
+            }
+        };
+
+        let mut state = QuantumState::new_cv(modes, seed, self.coherence_time_ns);
+        if let (
+            PreparationKind::DisplacedSqueezed {
+                squeezing_db,
+                squeezing_angle,
+                ..
+            },
+            Some(cv),
+        ) = (preparation, state.cv_data.as_mut())
+        {
+            for mode in cv.modes.values_mut() {
+                mode.squeezing_db = *squeezing_db;
+                mode.squeezing_angle = *squeezing_angle;
+            }
+        }
+        if let (PreparationKind::ThermalState { mean_photons }, Some(cv)) =
+            (preparation, state.cv_data.as_mut())
+        {
+            for mode in cv.modes.values_mut() {
+                mode.thermal_photons = *mean_photons;
+            }
+        }
+        state.set_gaussian_state(gaussian)?;
+        Ok(state)
     }
 
     fn evolve(
         &mut self,
         state: &mut QuantumState,
         _hamiltonian: &Hamiltonian,
-        _noise_channels: &[NoiseChannel],
+        noise_channels: &[NoiseChannel],
         duration_ns: u64,
         seed: u64,
     ) -> Result<EvolutionTrace> {
-        // Mock evolution: just update timestamp and add to provenance
-        let new_state_id = Uuid::new_v4().to_string();
-        let old_state_id = state.state_id.clone();
+        let mut gaussian = state
+            .gaussian_state()
+            .ok_or_else(|| anyhow!("state {} is not a CV state", state.state_id))?;
+        let purity_before = gaussian.purity();
+        let mut operations = Vec::new();
+        for channel in noise_channels {
+            operations.push(QuantumOperation::Evolution {
+                duration_ns,
+                noise_channel: Self::apply_noise(&mut gaussian, channel, duration_ns)?,
+            });
+        }
+        if operations.is_empty() {
+            operations.push(QuantumOperation::Evolution {
+                duration_ns,
+                noise_channel: "none".to_string(),
+            });
+        }
+        let purity_after = gaussian.purity();
+        state.set_gaussian_state(gaussian)?;
 
-        state.state_id = new_state_id.clone();
+        let new_state_id = Uuid::new_v4().to_string();
+        let old_state_id = std::mem::replace(&mut state.state_id, new_state_id.clone());
         state.timestamp = Utc::now();
         state.seed = seed;
 
+        // Purity lost to the explicit channels, compounded with intrinsic T2 dephasing.
+        let retained = if purity_before > 0.0 {
+            (purity_after / purity_before).min(1.0)
+        } else {
+            1.0
+        };
+        let intrinsic = (-(duration_ns as f64) / self.t2_ns.max(1) as f64).exp();
         Ok(EvolutionTrace {
             initial_state_id: old_state_id,
             final_state_id: new_state_id,
-            operations: vec![QuantumOperation::Evolution {
-                duration_ns,
-                noise_channel: "none".to_string(),
-            }],
-            decoherence_estimated: 0.01, // 1% decoherence per operation
+            operations,
+            decoherence_estimated: 1.0 - retained * intrinsic,
             seed,
         })
     }
@@ -830,13 +1757,39 @@ impl QuantumBackend for GaussianSimulator {
             ));
         }
 
-        // Mock homodyne outcome: sample from Gaussian
+        // Sample each mode in turn from its marginal, conditioning the remaining modes on the
+        // outcome. Heterodyne results are reported as `<mode>:q` and `<mode>:p`.
+        let mut gaussian = state
+            .gaussian_state()
+            .ok_or_else(|| anyhow!("state {} has no CV data", state.state_id))?;
+        let mut rng = StdRng::seed_from_u64(seed);
         let mut results = HashMap::new();
-        let mock_value = (seed % 1000) as f64 / 1000.0; // Deterministic from seed
-
         for mode in &basis.mode_labels {
-            results.insert(mode.clone(), MeasurementResult::ContinuousValue(mock_value));
+            let idx = state.mode_index(mode)?;
+            match &basis.basis_type {
+                BasisType::Homodyne { axis } => {
+                    let angle = match axis {
+                        HomodyneAxis::Q => 0.0,
+                        HomodyneAxis::P => std::f64::consts::FRAC_PI_2,
+                    };
+                    let x = gaussian.measure_homodyne(idx, angle, &mut rng)?;
+                    results.insert(mode.clone(), MeasurementResult::ContinuousValue(x));
+                }
+                BasisType::Heterodyne => {
+                    let (q, p) = gaussian.measure_heterodyne(idx, &mut rng)?;
+                    results.insert(format!("{}:q", mode), MeasurementResult::ContinuousValue(q));
+                    results.insert(format!("{}:p", mode), MeasurementResult::ContinuousValue(p));
+                }
+                other => {
+                    return Err(anyhow!(
+                        "{:?} measurement not supported by {}",
+                        other,
+                        self.name
+                    ))
+                }
+            }
         }
+        state.set_gaussian_state(gaussian)?;
 
         Ok(MeasurementOutcome::new(
             Uuid::new_v4().to_string(),
@@ -851,28 +1804,1050 @@ impl QuantumBackend for GaussianSimulator {
         state.snapshot()
     }
 
-    fn fidelity(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64> {
-        // Mock: compare state IDs, return high fidelity if same, low if different
-        let fidelity = if state1.state_id == state2.state_id {
-            1.0
-        } else {
-            0.0
-        };
-        Ok(fidelity)
+    fn wigner(
+        &self,
+        state: &QuantumState,
+        mode: &str,
+        grid: &PhaseSpaceGrid,
+    ) -> Result<WignerGrid> {
+        state.wigner(mode, grid)
     }
 
-    fn release_state(&mut self, _state_id: &str) -> Result<()> {
-        Ok(())
+    /// Uhlmann fidelity from the covariance matrices and displacements.
+    fn fidelity(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64> {
+        state1.fidelity(state2)
     }
-}
 
-// ============================================================================
-// Drift Detector for Quantum States
-// ============================================================================
+    /// Outcomes are jointly Gaussian, so every shot is one draw through a single Cholesky
+    /// factor of the outcome covariance (heterodyne adds ½ of vacuum noise per quadrature).
+    /// Shots are drawn in parallel, each from its own `split_seeds` stream.
+    fn sample(
+        &self,
+        state: &QuantumState,
+        basis: &MeasurementBasis,
+        shots: usize,
+        seed: u64,
+    ) -> Result<ShotRecord> {
+        if !state.can_measure_basis(basis) {
+            return Err(anyhow!(
+                "Measurement basis {:?} incompatible with state type {:?}",
+                basis.basis_type,
+                state.state_type
+            ));
+        }
 
-pub trait QuantumDriftDetector: Send + Sync {
-    fn detect_drift(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64>;
-}
+        let mut gaussian = state
+            .gaussian_state()
+            .ok_or_else(|| anyhow!("state {} has no CV data", state.state_id))?;
+        let mut labels = Vec::new();
+        let mut indices = Vec::new();
+        let mut added_variance = 0.0;
+        for mode in &basis.mode_labels {
+            let idx = state.mode_index(mode)?;
+            match &basis.basis_type {
+                BasisType::Homodyne { axis } => {
+                    if let HomodyneAxis::P = axis {
+                        gaussian.phase_shift(idx, -std::f64::consts::FRAC_PI_2)?;
+                    }
+                    labels.push(mode.clone());
+                    indices.push(2 * idx);
+                }
+                BasisType::Heterodyne => {
+                    labels.push(format!("{}:q", mode));
+                    labels.push(format!("{}:p", mode));
+                    indices.extend([2 * idx, 2 * idx + 1]);
+                    added_variance = 0.5;
+                }
+                other => {
+                    return Err(anyhow!(
+                        "{:?} measurement not supported by {}",
+                        other,
+                        self.name
+                    ))
+                }
+            }
+        }
+
+        let means: Vec<f64> = indices.iter().map(|&i| gaussian.means[i]).collect();
+        let covariance: Vec<Vec<f64>> = indices
+            .iter()
+            .enumerate()
+            .map(|(a, &i)| {
+                indices
+                    .iter()
+                    .enumerate()
+                    .map(|(b, &j)| {
+                        gaussian.covariance[i][j] + if a == b { added_variance } else { 0.0 }
+                    })
+                    .collect()
+            })
+            .collect();
+        let factor = linalg::cholesky(&covariance)
+            .ok_or_else(|| anyhow!("outcome covariance of {} is not PSD", state.state_id))?;
+
+        let rows = split_seeds(seed, shots)
+            .into_par_iter()
+            .map(|shot_seed| {
+                let mut rng = StdRng::seed_from_u64(shot_seed);
+                let z: Vec<f64> = (0..means.len())
+                    .map(|_| gaussian::standard_normal(&mut rng))
+                    .collect();
+                factor
+                    .iter()
+                    .zip(&means)
+                    .map(|(row, mean)| {
+                        let x = mean + row.iter().zip(&z).map(|(l, z)| l * z).sum::<f64>();
+                        MeasurementResult::ContinuousValue(x)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(ShotRecord::new(
+            state.state_id.clone(),
+            basis.basis_type.clone(),
+            labels,
+            rows,
+            seed,
+        ))
+    }
+
+    fn release_state(&mut self, _state_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Reference: DV Statevector Simulator Backend
+// ============================================================================
+
+/// How a DV backend holds joint states: pure amplitude vectors, or density matrices so that
+/// noise channels can produce mixed states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DVRepresentation {
+    #[default]
+    StateVector,
+    DensityMatrix,
+}
+
+/// Joint DV register in whichever representation the state currently uses.
+enum DVRegister {
+    Pure(StateVector),
+    Mixed(DensityMatrix),
+}
+
+impl DVRegister {
+    fn load(state: &QuantumState) -> Result<Self> {
+        if state.is_mixed_representation() {
+            Ok(DVRegister::Mixed(state.density_matrix().ok_or_else(
+                || anyhow!("state {} has no DV data", state.state_id),
+            )??))
+        } else {
+            Ok(DVRegister::Pure(StatevectorSimulator::load(state)?))
+        }
+    }
+
+    fn store(self, state: &mut QuantumState) -> Result<()> {
+        match self {
+            DVRegister::Pure(sv) => state.set_statevector(sv),
+            DVRegister::Mixed(rho) => state.set_density_matrix(rho),
+        }
+    }
+
+    fn apply(&mut self, control: Option<usize>, target: usize, gate: &GateMatrix) -> Result<()> {
+        match self {
+            DVRegister::Pure(sv) => sv.apply_controlled(control, target, gate),
+            DVRegister::Mixed(rho) => rho.apply_unitary(control, target, gate),
+        }
+    }
+
+    fn dimension(&self, q: usize) -> Result<usize> {
+        let dims = match self {
+            DVRegister::Pure(sv) => &sv.dims,
+            DVRegister::Mixed(rho) => &rho.dims,
+        };
+        dims.get(q)
+            .copied()
+            .ok_or_else(|| anyhow!("qudit {} out of range", q))
+    }
+
+    fn rotate_pauli(&mut self, term: &PauliString, theta: f64) -> Result<()> {
+        match self {
+            DVRegister::Pure(sv) => term.rotate(sv, theta),
+            DVRegister::Mixed(rho) => rho.conjugate_by(|col| term.rotate(col, theta)),
+        }
+    }
+
+    fn measure(&mut self, q: usize, rng: &mut StdRng) -> Result<usize> {
+        match self {
+            DVRegister::Pure(sv) => sv.measure(q, rng),
+            DVRegister::Mixed(rho) => rho.measure(q, rng),
+        }
+    }
+
+    /// Joint probabilities of the computational values of `qudits`, indexed in mixed radix
+    /// with the first qudit most significant.
+    fn joint_distribution(&self, qudits: &[usize]) -> Result<Vec<f64>> {
+        let (dims, weights): (&[usize], Vec<f64>) = match self {
+            DVRegister::Pure(sv) => (
+                &sv.dims,
+                sv.amplitudes.iter().map(|a| a.norm_sqr()).collect(),
+            ),
+            DVRegister::Mixed(rho) => (
+                &rho.dims,
+                (0..rho.size()).map(|i| rho.rho[i][i].re).collect(),
+            ),
+        };
+        for &q in qudits {
+            self.dimension(q)?;
+        }
+        let strides: Vec<usize> = qudits
+            .iter()
+            .map(|&q| dims[q + 1..].iter().product())
+            .collect();
+        let size: usize = qudits.iter().map(|&q| dims[q]).product();
+        let mut probs = vec![0.0; size];
+        for (i, w) in weights.iter().enumerate() {
+            let key = qudits.iter().zip(&strides).fold(0, |key, (&q, stride)| {
+                key * dims[q] + (i / stride) % dims[q]
+            });
+            probs[key] += w;
+        }
+        Ok(probs)
+    }
+
+    fn measure_bell(&mut self, a: usize, b: usize, rng: &mut StdRng) -> Result<usize> {
+        match self {
+            DVRegister::Pure(sv) => sv.measure_bell(a, b, rng),
+            DVRegister::Mixed(_) => {
+                // Same decomposition as `StateVector::measure_bell`.
+                let d = self.dimension(b)?;
+                self.apply(Some(a), b, &statevector::pauli_x(d))?;
+                self.apply(None, a, &statevector::hadamard())?;
+                let sign = self.measure(a, rng)?;
+                let parity = self.measure(b, rng)?;
+                Ok(parity * 2 + sign)
+            }
+        }
+    }
+}
+
+/// Add a symmetric entanglement-graph edge; `set_*` prunes it again once either side is pure.
+fn record_entanglement(state: &mut QuantumState, a: &str, b: &str) {
+    if let Some(dv) = state.dv_data.as_mut() {
+        for (x, y) in [(a, b), (b, a)] {
+            let partners = dv.entanglement_graph.entry(x.to_string()).or_default();
+            if !partners.iter().any(|p| p == y) {
+                partners.push(y.to_string());
+            }
+        }
+    }
+}
+
+pub struct StatevectorSimulator {
+    pub name: String,
+    pub max_qudits: usize,
+    pub coherence_time_ns: u64,
+    pub representation: DVRepresentation,
+    #[cfg(feature = "gpu")]
+    gpu: Option<std::sync::Arc<gpu::GpuContext>>,
+}
+
+impl StatevectorSimulator {
+    pub fn new() -> Self {
+        StatevectorSimulator {
+            name: "statevector_simulator".to_string(),
+            max_qudits: 20,
+            coherence_time_ns: 1_000,
+            representation: DVRepresentation::StateVector,
+            #[cfg(feature = "gpu")]
+            gpu: None,
+        }
+    }
+
+    /// Offload pure-state gates and shot lookups to `gpu`. Density matrices stay on the CPU.
+    #[cfg(feature = "gpu")]
+    pub fn with_gpu(mut self, gpu: std::sync::Arc<gpu::GpuContext>) -> Self {
+        self.gpu = Some(gpu);
+        self
+    }
+
+    fn apply(
+        &self,
+        register: &mut DVRegister,
+        control: Option<usize>,
+        target: usize,
+        gate: &GateMatrix,
+    ) -> Result<()> {
+        #[cfg(feature = "gpu")]
+        if let (Some(gpu), DVRegister::Pure(sv)) = (&self.gpu, &mut *register) {
+            if sv.dims[target] <= gpu::MAX_GPU_QUDIT_DIM {
+                return gpu.apply_controlled(sv, control, target, gate);
+            }
+        }
+        register.apply(control, target, gate)
+    }
+
+    fn sample_indices(&self, probs: &[f64], shots: usize, rng: &mut StdRng) -> Result<Vec<usize>> {
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = &self.gpu {
+            let (cdf, targets) = sampling_targets(probs, shots, rng);
+            return gpu.sample_indices(&cdf, &targets);
+        }
+        Ok(sample_distribution(probs, shots, rng))
+    }
+
+    /// Select how prepared states are held. Density matrices cost `O(D²)` memory but support
+    /// [`StatevectorSimulator::apply_kraus`] without losing information.
+    pub fn with_representation(mut self, representation: DVRepresentation) -> Self {
+        self.representation = representation;
+        self
+    }
+
+    fn load(state: &QuantumState) -> Result<StateVector> {
+        state
+            .statevector()
+            .ok_or_else(|| anyhow!("state {} has no DV data", state.state_id))?
+    }
+
+    /// Apply a gate to a DV state. Two-qudit gates record an entanglement-graph edge while the
+    /// qudits remain entangled.
+    pub fn apply_gate(&self, state: &mut QuantumState, gate: &DVGate) -> Result<()> {
+        let mut register = DVRegister::load(state)?;
+        match gate {
+            DVGate::Hadamard { qudit } => {
+                let idx = state.mode_index(qudit)?;
+                self.apply(&mut register, None, idx, &statevector::hadamard())?
+            }
+            DVGate::RX { qudit, theta } => {
+                let idx = state.mode_index(qudit)?;
+                self.apply(&mut register, None, idx, &statevector::rx(*theta))?
+            }
+            DVGate::CNOT { control, target } => {
+                let target_idx = state.mode_index(target)?;
+                let shift = statevector::pauli_x(register.dimension(target_idx)?);
+                let control_idx = state.mode_index(control)?;
+                self.apply(&mut register, Some(control_idx), target_idx, &shift)?;
+                record_entanglement(state, control, target);
+            }
+        }
+        register.store(state)
+    }
+
+    /// Apply a single-qudit Kraus channel. Pure states are promoted to a density matrix.
+    pub fn apply_kraus(
+        &self,
+        state: &mut QuantumState,
+        qudit: &str,
+        kraus: &[GateMatrix],
+    ) -> Result<()> {
+        let mut rho = state
+            .density_matrix()
+            .ok_or_else(|| anyhow!("state {} has no DV data", state.state_id))??;
+        rho.apply_kraus(state.mode_index(qudit)?, kraus)?;
+        state.set_density_matrix(rho)
+    }
+}
+
+impl Default for StatevectorSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuantumBackend for StatevectorSimulator {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn state_type(&self) -> StateType {
+        StateType::DV
+    }
+
+    fn supported_bases(&self) -> Vec<BasisType> {
+        vec![
+            BasisType::Computational,
+            BasisType::Hadamard,
+            BasisType::BellMeasurement {
+                qudit_pairs: vec![],
+            },
+        ]
+    }
+
+    fn max_modes(&self) -> usize {
+        self.max_qudits
+    }
+
+    fn coherence_time_ns(&self) -> u64 {
+        self.coherence_time_ns
+    }
+
+    fn measurement_latency(&self) -> MeasurementLatency {
+        MeasurementLatency {
+            detection_latency_ns: 50,
+            electronics_latency_ns: 10,
+            transport_latency_ns: 0,
+            certainty: 0.99,
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        modes: Vec<String>,
+        preparation: &PreparationKind,
+        seed: u64,
+    ) -> Result<QuantumState> {
+        if modes.len() > self.max_qudits {
+            return Err(anyhow!(
+                "Too many qudits: {} > {}",
+                modes.len(),
+                self.max_qudits
+            ));
+        }
+        let n = modes.len();
+        let mut state = QuantumState::new_dv(
+            modes.into_iter().map(|m| (m, 2)).collect(),
+            seed,
+            self.coherence_time_ns,
+        );
+        let sv = match preparation {
+            PreparationKind::BasisState { amplitudes } => {
+                let amps: Vec<Complex64> =
+                    amplitudes.iter().map(|a| Complex64::new(*a, 0.0)).collect();
+                if amps.len() == 1usize << n {
+                    StateVector::from_amplitudes(vec![2; n], amps)?
+                } else if amps.len() == 2 {
+                    StateVector::product(&vec![amps; n])?
+                } else {
+                    return Err(anyhow!(
+                        "BasisState needs 2 (per qubit) or {} (joint) amplitudes, got {}",
+                        1usize << n,
+                        amps.len()
+                    ));
+                }
+            }
+            PreparationKind::BellState { entanglement_type } => {
+                if n != 2 {
+                    return Err(anyhow!("Bell state needs exactly 2 qubits, got {}", n));
+                }
+                let h = std::f64::consts::FRAC_1_SQRT_2;
+                // Amplitudes over |00⟩, |01⟩, |10⟩, |11⟩.
+                let amps = match entanglement_type {
+                    BellType::PhiPlus => [h, 0.0, 0.0, h],
+                    BellType::PhiMinus => [h, 0.0, 0.0, -h],
+                    BellType::PsiPlus => [0.0, h, h, 0.0],
+                    BellType::PsiMinus => [0.0, h, -h, 0.0],
+                };
+                StateVector::from_amplitudes(
+                    vec![2; 2],
+                    amps.iter().map(|a| Complex64::new(*a, 0.0)).collect(),
+                )?
+            }
+            PreparationKind::GHZState { num_qudits } => {
+                if *num_qudits != n || n < 2 {
+                    return Err(anyhow!(
+                        "GHZ state over {} qubits needs at least 2 matching modes, got {}",
+                        num_qudits,
+                        n
+                    ));
+                }
+                // (|0…0⟩ + |1…1⟩)/√2
+                let mut amps = vec![Complex64::new(0.0, 0.0); 1usize << n];
+                amps[0] = Complex64::new(1.0, 0.0);
+                amps[(1usize << n) - 1] = Complex64::new(1.0, 0.0);
+                StateVector::from_amplitudes(vec![2; n], amps)?
+            }
+            other => {
+                return Err(anyhow!(
+                    "{:?} preparation not supported by {}",
+                    other,
+                    self.name
+                ))
+            }
+        };
+        match self.representation {
+            DVRepresentation::StateVector => state.set_statevector(sv)?,
+            DVRepresentation::DensityMatrix => {
+                state.set_density_matrix(DensityMatrix::from_statevector(&sv))?
+            }
+        }
+        if matches!(
+            preparation,
+            PreparationKind::BellState { .. } | PreparationKind::GHZState { .. }
+        ) {
+            let labels = state.mode_labels.clone();
+            for (i, a) in labels.iter().enumerate() {
+                for b in &labels[i + 1..] {
+                    record_entanglement(&mut state, a, b);
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    /// Unitary evolution under the Pauli Hamiltonian (exact for commuting terms, second-order
+    /// Trotter otherwise). Noise channels are not applied by this backend.
+    fn evolve(
+        &mut self,
+        state: &mut QuantumState,
+        hamiltonian: &Hamiltonian,
+        _noise_channels: &[NoiseChannel],
+        duration_ns: u64,
+        seed: u64,
+    ) -> Result<EvolutionTrace> {
+        let terms = hamiltonian.pauli_strings(state)?;
+        let mut operations = Vec::new();
+        if !terms.is_empty() {
+            let schedule = pauli::evolution_schedule(&terms, duration_ns as f64);
+            let mut register = DVRegister::load(state)?;
+            for (k, theta) in &schedule.rotations {
+                register.rotate_pauli(&terms[*k], *theta)?;
+            }
+            for term in terms.iter().filter(|t| t.coefficient != 0.0) {
+                for (i, (a, _)) in term.ops.iter().enumerate() {
+                    for (b, _) in &term.ops[i + 1..] {
+                        let (a, b) = (state.mode_labels[*a].clone(), state.mode_labels[*b].clone());
+                        record_entanglement(state, &a, &b);
+                    }
+                }
+            }
+            register.store(state)?;
+            operations.push(QuantumOperation::Unitary {
+                gate_name: "PauliEvolution".to_string(),
+                parameters: HashMap::from([
+                    ("terms".to_string(), terms.len() as f64),
+                    ("trotter_steps".to_string(), schedule.steps as f64),
+                    ("duration_ns".to_string(), duration_ns as f64),
+                ]),
+            });
+        }
+        operations.push(QuantumOperation::Evolution {
+            duration_ns,
+            noise_channel: "none".to_string(),
+        });
+
+        let new_state_id = Uuid::new_v4().to_string();
+        let old_state_id = std::mem::replace(&mut state.state_id, new_state_id.clone());
+        state.timestamp = Utc::now();
+        state.seed = seed;
+
+        Ok(EvolutionTrace {
+            initial_state_id: old_state_id,
+            final_state_id: new_state_id,
+            operations,
+            decoherence_estimated: 0.0,
+            seed,
+        })
+    }
+
+    fn measure(
+        &mut self,
+        state: &mut QuantumState,
+        basis: &MeasurementBasis,
+        seed: u64,
+    ) -> Result<MeasurementOutcome> {
+        if !state.can_measure_basis(basis) {
+            return Err(anyhow!(
+                "Measurement basis {:?} incompatible with state type {:?}",
+                basis.basis_type,
+                state.state_type
+            ));
+        }
+
+        let mut register = DVRegister::load(state)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut results = HashMap::new();
+        match &basis.basis_type {
+            BasisType::Computational | BasisType::Hadamard => {
+                for label in &basis.mode_labels {
+                    let idx = state.mode_index(label)?;
+                    if matches!(basis.basis_type, BasisType::Hadamard) {
+                        self.apply(&mut register, None, idx, &statevector::hadamard())?;
+                    }
+                    let outcome = register.measure(idx, &mut rng)?;
+                    results.insert(label.clone(), MeasurementResult::DiscreteOutcome(outcome));
+                }
+            }
+            BasisType::BellMeasurement { qudit_pairs } => {
+                // Outcome index follows `BellType` order: Φ+, Φ−, Ψ+, Ψ−.
+                for (a, b) in qudit_pairs {
+                    let outcome = register.measure_bell(
+                        state.mode_index(a)?,
+                        state.mode_index(b)?,
+                        &mut rng,
+                    )?;
+                    results.insert(
+                        format!("{},{}", a, b),
+                        MeasurementResult::DiscreteOutcome(outcome),
+                    );
+                }
+            }
+            other => {
+                return Err(anyhow!(
+                    "{:?} measurement not supported by {}",
+                    other,
+                    self.name
+                ))
+            }
+        }
+        register.store(state)?;
+
+        Ok(MeasurementOutcome::new(
+            Uuid::new_v4().to_string(),
+            basis.mode_labels.clone(),
+            basis.basis_type.clone(),
+            results,
+            seed,
+        ))
+    }
+
+    fn snapshot(&self, state: &QuantumState) -> Result<StateSnapshot> {
+        state.snapshot()
+    }
+
+    fn wigner(
+        &self,
+        state: &QuantumState,
+        mode: &str,
+        grid: &PhaseSpaceGrid,
+    ) -> Result<WignerGrid> {
+        state.wigner(mode, grid)
+    }
+
+    /// `|⟨ψ|φ⟩|²` for pure states, Uhlmann fidelity when either side is mixed.
+    fn fidelity(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64> {
+        state1.fidelity(state2)
+    }
+
+    /// The joint outcome distribution is computed once from the (rotated) register and every
+    /// shot is a single inverse-CDF lookup.
+    fn sample(
+        &self,
+        state: &QuantumState,
+        basis: &MeasurementBasis,
+        shots: usize,
+        seed: u64,
+    ) -> Result<ShotRecord> {
+        if !state.can_measure_basis(basis) {
+            return Err(anyhow!(
+                "Measurement basis {:?} incompatible with state type {:?}",
+                basis.basis_type,
+                state.state_type
+            ));
+        }
+
+        let mut register = DVRegister::load(state)?;
+        let mut labels = Vec::new();
+        let mut qudits = Vec::new();
+        let bell = matches!(basis.basis_type, BasisType::BellMeasurement { .. });
+        match &basis.basis_type {
+            BasisType::Computational | BasisType::Hadamard => {
+                for label in &basis.mode_labels {
+                    let idx = state.mode_index(label)?;
+                    if matches!(basis.basis_type, BasisType::Hadamard) {
+                        self.apply(&mut register, None, idx, &statevector::hadamard())?;
+                    }
+                    labels.push(label.clone());
+                    qudits.push(idx);
+                }
+            }
+            BasisType::BellMeasurement { qudit_pairs } => {
+                // Same decomposition as `measure_bell`: outcome = parity·2 + sign.
+                for (a, b) in qudit_pairs {
+                    let (ia, ib) = (state.mode_index(a)?, state.mode_index(b)?);
+                    if register.dimension(ia)? != 2 || register.dimension(ib)? != 2 {
+                        return Err(anyhow!("Bell measurement requires two qubits"));
+                    }
+                    self.apply(&mut register, Some(ia), ib, &statevector::pauli_x(2))?;
+                    self.apply(&mut register, None, ia, &statevector::hadamard())?;
+                    labels.push(format!("{},{}", a, b));
+                    qudits.extend([ia, ib]);
+                }
+            }
+            other => {
+                return Err(anyhow!(
+                    "{:?} measurement not supported by {}",
+                    other,
+                    self.name
+                ))
+            }
+        }
+
+        let probs = register.joint_distribution(&qudits)?;
+        let radices = qudits
+            .iter()
+            .map(|&q| register.dimension(q))
+            .collect::<Result<Vec<_>>>()?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let rows = self
+            .sample_indices(&probs, shots, &mut rng)?
+            .into_iter()
+            .map(|joint| {
+                let mut digits = vec![0; radices.len()];
+                let mut rest = joint;
+                for (digit, radix) in digits.iter_mut().zip(&radices).rev() {
+                    *digit = rest % radix;
+                    rest /= radix;
+                }
+                if bell {
+                    digits
+                        .chunks(2)
+                        .map(|ab| MeasurementResult::DiscreteOutcome(ab[1] * 2 + ab[0]))
+                        .collect()
+                } else {
+                    digits
+                        .into_iter()
+                        .map(MeasurementResult::DiscreteOutcome)
+                        .collect()
+                }
+            })
+            .collect();
+
+        Ok(ShotRecord::new(
+            state.state_id.clone(),
+            basis.basis_type.clone(),
+            labels,
+            rows,
+            seed,
+        ))
+    }
+
+    fn release_state(&mut self, _state_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Reference: Tensor-Network (MPS) Simulator Backend
+// ============================================================================
+
+/// Matrix-product-state backend for wide, low-depth DV circuits. Memory scales with
+/// `n·χ²` instead of `2ⁿ`; entanglement beyond the bond dimension `χ` is truncated and the
+/// discarded weight is reported through [`MpsSimulator::metrics`].
+pub struct MpsSimulator {
+    pub name: String,
+    pub max_qudits: usize,
+    pub coherence_time_ns: u64,
+    pub max_bond_dimension: usize,
+}
+
+impl MpsSimulator {
+    pub fn new() -> Self {
+        MpsSimulator {
+            name: "mps_simulator".to_string(),
+            max_qudits: 1_000,
+            coherence_time_ns: 1_000,
+            max_bond_dimension: 64,
+        }
+    }
+
+    pub fn with_bond_dimension(mut self, max_bond_dimension: usize) -> Self {
+        self.max_bond_dimension = max_bond_dimension.max(1);
+        self
+    }
+
+    fn load(&self, state: &QuantumState) -> Result<MatrixProductState> {
+        state
+            .mps(self.max_bond_dimension)
+            .ok_or_else(|| anyhow!("state {} has no DV data", state.state_id))?
+    }
+
+    /// Apply a gate; two-qudit gates are truncated to the configured bond dimension.
+    pub fn apply_gate(&self, state: &mut QuantumState, gate: &DVGate) -> Result<()> {
+        let mut mps = self.load(state)?;
+        match gate {
+            DVGate::Hadamard { qudit } => {
+                mps.apply_single(state.mode_index(qudit)?, &statevector::hadamard())?
+            }
+            DVGate::RX { qudit, theta } => {
+                mps.apply_single(state.mode_index(qudit)?, &statevector::rx(*theta))?
+            }
+            DVGate::CNOT { control, target } => {
+                let target_idx = state.mode_index(target)?;
+                let shift = statevector::pauli_x(mps.dims[target_idx]);
+                mps.apply_controlled(
+                    Some(state.mode_index(control)?),
+                    target_idx,
+                    &shift,
+                    self.max_bond_dimension,
+                )?;
+                record_entanglement(state, control, target);
+            }
+        }
+        state.set_mps(mps)
+    }
+
+    /// Metrics for an MPS state, including bond dimension and accumulated truncation error.
+    /// `fidelity_estimate` is `1 − truncation_error`, a first-order estimate of the overlap
+    /// with the untruncated state.
+    pub fn metrics(&self, state: &QuantumState) -> Result<QuantumMetrics> {
+        let mps = self.load(state)?;
+        Ok(QuantumMetrics {
+            fidelity_estimate: (1.0 - mps.truncation_error).max(0.0),
+            coherence_remaining_ns: state.time_to_coherence_deadline().max(0) as u64,
+            decoherence_rate: 0.0,
+            measurement_confidence: self.measurement_latency().certainty,
+            entanglement_entropy: state.entanglement_entropies(&state.single_mode_partitions())?,
+            max_bond_dimension: mps.max_bond_dimension(),
+            truncation_error: mps.truncation_error,
+        })
+    }
+}
+
+impl Default for MpsSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuantumBackend for MpsSimulator {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn state_type(&self) -> StateType {
+        StateType::DV
+    }
+
+    fn supported_bases(&self) -> Vec<BasisType> {
+        vec![BasisType::Computational, BasisType::Hadamard]
+    }
+
+    fn max_modes(&self) -> usize {
+        self.max_qudits
+    }
+
+    fn coherence_time_ns(&self) -> u64 {
+        self.coherence_time_ns
+    }
+
+    fn measurement_latency(&self) -> MeasurementLatency {
+        MeasurementLatency {
+            detection_latency_ns: 50,
+            electronics_latency_ns: 10,
+            transport_latency_ns: 0,
+            certainty: 0.99,
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        modes: Vec<String>,
+        preparation: &PreparationKind,
+        seed: u64,
+    ) -> Result<QuantumState> {
+        if modes.len() > self.max_qudits {
+            return Err(anyhow!(
+                "Too many qudits: {} > {}",
+                modes.len(),
+                self.max_qudits
+            ));
+        }
+        let n = modes.len();
+        let mut state = QuantumState::new_dv(
+            modes.into_iter().map(|m| (m, 2)).collect(),
+            seed,
+            self.coherence_time_ns,
+        );
+        let mps = match preparation {
+            PreparationKind::BasisState { amplitudes } if amplitudes.len() == 2 => {
+                let amps: Vec<Complex64> =
+                    amplitudes.iter().map(|a| Complex64::new(*a, 0.0)).collect();
+                MatrixProductState::product(&vec![amps; n])?
+            }
+            PreparationKind::BasisState { amplitudes } => {
+                return Err(anyhow!(
+                    "{} prepares product states only (2 amplitudes per qubit), got {}",
+                    self.name,
+                    amplitudes.len()
+                ))
+            }
+            other => {
+                return Err(anyhow!(
+                    "{:?} preparation not supported by {}",
+                    other,
+                    self.name
+                ))
+            }
+        };
+        state.set_mps(mps)?;
+        Ok(state)
+    }
+
+    fn evolve(
+        &mut self,
+        state: &mut QuantumState,
+        hamiltonian: &Hamiltonian,
+        _noise_channels: &[NoiseChannel],
+        duration_ns: u64,
+        seed: u64,
+    ) -> Result<EvolutionTrace> {
+        if !hamiltonian.terms.is_empty() {
+            return Err(anyhow!(
+                "Hamiltonian evolution is not supported by {}; apply gates instead",
+                self.name
+            ));
+        }
+        let new_state_id = Uuid::new_v4().to_string();
+        let old_state_id = std::mem::replace(&mut state.state_id, new_state_id.clone());
+        state.timestamp = Utc::now();
+        state.seed = seed;
+
+        Ok(EvolutionTrace {
+            initial_state_id: old_state_id,
+            final_state_id: new_state_id,
+            operations: vec![QuantumOperation::Evolution {
+                duration_ns,
+                noise_channel: "none".to_string(),
+            }],
+            decoherence_estimated: 0.0,
+            seed,
+        })
+    }
+
+    fn measure(
+        &mut self,
+        state: &mut QuantumState,
+        basis: &MeasurementBasis,
+        seed: u64,
+    ) -> Result<MeasurementOutcome> {
+        if !state.can_measure_basis(basis) {
+            return Err(anyhow!(
+                "Measurement basis {:?} incompatible with state type {:?}",
+                basis.basis_type,
+                state.state_type
+            ));
+        }
+
+        let mut mps = self.load(state)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut results = HashMap::new();
+        match &basis.basis_type {
+            BasisType::Computational | BasisType::Hadamard => {
+                for label in &basis.mode_labels {
+                    let idx = state.mode_index(label)?;
+                    if matches!(basis.basis_type, BasisType::Hadamard) {
+                        mps.apply_single(idx, &statevector::hadamard())?;
+                    }
+                    let outcome = mps.measure(idx, &mut rng)?;
+                    results.insert(label.clone(), MeasurementResult::DiscreteOutcome(outcome));
+                }
+            }
+            other => {
+                return Err(anyhow!(
+                    "{:?} measurement not supported by {}",
+                    other,
+                    self.name
+                ))
+            }
+        }
+        state.set_mps(mps)?;
+
+        Ok(MeasurementOutcome::new(
+            Uuid::new_v4().to_string(),
+            basis.mode_labels.clone(),
+            basis.basis_type.clone(),
+            results,
+            seed,
+        ))
+    }
+
+    fn snapshot(&self, state: &QuantumState) -> Result<StateSnapshot> {
+        state.snapshot()
+    }
+
+    fn wigner(
+        &self,
+        state: &QuantumState,
+        mode: &str,
+        grid: &PhaseSpaceGrid,
+    ) -> Result<WignerGrid> {
+        state.wigner(mode, grid)
+    }
+
+    /// `|⟨ψ|φ⟩|²`, contracted without expanding either state.
+    fn fidelity(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64> {
+        let (a, b) = (self.load(state1)?, self.load(state2)?);
+        Ok(a.inner(&b)?.norm_sqr() / (a.norm_sqr() * b.norm_sqr()))
+    }
+
+    /// Sequential (perfect) sampling: each shot measures the qudits one by one on a copy of the
+    /// rotated MPS, costing `O(n·χ²)` per shot instead of the dense `2ⁿ` distribution. Shots
+    /// run in parallel, each from its own `split_seeds` stream.
+    fn sample(
+        &self,
+        state: &QuantumState,
+        basis: &MeasurementBasis,
+        shots: usize,
+        seed: u64,
+    ) -> Result<ShotRecord> {
+        if !state.can_measure_basis(basis) {
+            return Err(anyhow!(
+                "Measurement basis {:?} incompatible with state type {:?}",
+                basis.basis_type,
+                state.state_type
+            ));
+        }
+
+        let mut mps = self.load(state)?;
+        let qudits = basis
+            .mode_labels
+            .iter()
+            .map(|label| state.mode_index(label))
+            .collect::<Result<Vec<_>>>()?;
+        match &basis.basis_type {
+            BasisType::Computational => {}
+            BasisType::Hadamard => {
+                for &q in &qudits {
+                    mps.apply_single(q, &statevector::hadamard())?;
+                }
+            }
+            other => {
+                return Err(anyhow!(
+                    "{:?} measurement not supported by {}",
+                    other,
+                    self.name
+                ))
+            }
+        }
+
+        let rows = split_seeds(seed, shots)
+            .into_par_iter()
+            .map(|shot_seed| {
+                let mut rng = StdRng::seed_from_u64(shot_seed);
+                let mut shot = mps.clone();
+                qudits
+                    .iter()
+                    .map(|&q| {
+                        Ok(MeasurementResult::DiscreteOutcome(
+                            shot.measure(q, &mut rng)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ShotRecord::new(
+            state.state_id.clone(),
+            basis.basis_type.clone(),
+            basis.mode_labels.clone(),
+            rows,
+            seed,
+        ))
+    }
+
+    fn release_state(&mut self, _state_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Drift Detector for Quantum States
+// ============================================================================
+
+pub trait QuantumDriftDetector: Send + Sync {
+    fn detect_drift(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64>;
+}
 
 pub struct SimpleFidelityDriftDetector {
     pub threshold: f64,
@@ -885,19 +2860,16 @@ impl SimpleFidelityDriftDetector {
 }
 
 impl QuantumDriftDetector for SimpleFidelityDriftDetector {
+    /// Drift as infidelity `1 − F(state1, state2)`.
     fn detect_drift(&self, state1: &QuantumState, state2: &QuantumState) -> Result<f64> {
-        // Mock: measure drift as difference in purity
-        let purity1 = state1.compute_purity();
-        let purity2 = state2.compute_purity();
-
-        let drift = (purity1 - purity2).abs();
-        Ok(drift)
+        Ok(1.0 - state1.fidelity(state2)?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn test_cv_state_creation() {
@@ -997,10 +2969,761 @@ mod tests {
         assert!(!outcome.classical_results.is_empty());
     }
 
+    #[test]
+    fn test_gaussian_simulator_tracks_covariance() {
+        let mut backend = GaussianSimulator::new();
+        let modes = vec!["a".to_string(), "b".to_string()];
+        let mut state = backend
+            .prepare(
+                modes,
+                &PreparationKind::ThermalState { mean_photons: 0.0 },
+                1,
+            )
+            .unwrap();
+        backend
+            .apply_gate(
+                &mut state,
+                &GaussianGate::Displace {
+                    mode: "a".to_string(),
+                    q: 1.0,
+                    p: 0.0,
+                },
+            )
+            .unwrap();
+        backend
+            .apply_gate(
+                &mut state,
+                &GaussianGate::BeamSplitter {
+                    mode_a: "a".to_string(),
+                    mode_b: "b".to_string(),
+                    theta: std::f64::consts::FRAC_PI_2,
+                    phi: 0.0,
+                },
+            )
+            .unwrap();
+        let cv = state.cv_data.as_ref().unwrap();
+        assert!(cv.modes["a"].displacement_q.abs() < 1e-12);
+        assert!((cv.modes["a"].displacement_q - cv.means[0]).abs() < 1e-12);
+        assert!((state.compute_purity() - 1.0).abs() < 1e-9);
+
+        let basis = MeasurementBasis {
+            basis_type: BasisType::Heterodyne,
+            mode_labels: vec!["b".to_string()],
+        };
+        let first = backend.measure(&mut state.clone(), &basis, 9).unwrap();
+        let second = backend.measure(&mut state, &basis, 9).unwrap();
+        assert_eq!(first.classical_results.len(), 2);
+        assert_eq!(
+            format!("{:?}", first.classical_results["b:q"]),
+            format!("{:?}", second.classical_results["b:q"])
+        );
+        // Measured mode is left in vacuum.
+        assert!(state.gaussian_state().unwrap().means[2].abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_gaussian_simulator_rejects_non_gaussian_preparation() {
+        let mut backend = GaussianSimulator::new();
+        let prep = PreparationKind::GHZState { num_qudits: 3 };
+        assert!(backend.prepare(vec!["m".to_string()], &prep, 1).is_err());
+    }
+
+    #[test]
+    fn test_gaussian_evolve_applies_noise_channels() {
+        let mut backend = GaussianSimulator::new();
+        let prep = PreparationKind::DisplacedSqueezed {
+            displacement_q: 2.0,
+            displacement_p: 0.0,
+            squeezing_db: 3.0,
+            squeezing_angle: 0.0,
+        };
+        let state = backend.prepare(vec!["m".to_string()], &prep, 1).unwrap();
+        let hamiltonian = Hamiltonian {
+            terms: vec![],
+            static_field: false,
+        };
+
+        let mut noisy = state.clone();
+        let channels = [
+            NoiseChannel::Depolarizing { error_rate: 0.001 },
+            NoiseChannel::PhaseDamping { error_rate: 0.001 },
+            NoiseChannel::ThermalNoise {
+                bath_temp_k: 300.0,
+                mode_loss: 0.001,
+            },
+        ];
+        let trace = backend
+            .evolve(&mut noisy, &hamiltonian, &channels, 100, 2)
+            .unwrap();
+        assert_eq!(trace.operations.len(), 3);
+        match &trace.operations[2] {
+            QuantumOperation::Evolution { noise_channel, .. } => {
+                assert!(noise_channel.starts_with("ThermalNoise(eta=0.904"))
+            }
+            other => panic!("unexpected operation {:?}", other),
+        }
+        let before = state.snapshot().unwrap().global_purity;
+        let after = noisy.snapshot().unwrap().global_purity;
+        assert!(after < before);
+        assert!(trace.decoherence_estimated > 1.0 - after / before);
+        let displaced = noisy.cv_data.as_ref().unwrap().modes["m"].displacement_q;
+        assert!(displaced < 2.0 && displaced > 1.5);
+
+        // A hot bath adds photons even to vacuum.
+        let mut vacuum = backend
+            .prepare(
+                vec!["m".to_string()],
+                &PreparationKind::ThermalState { mean_photons: 0.0 },
+                1,
+            )
+            .unwrap();
+        let hot = NoiseChannel::ThermalNoise {
+            bath_temp_k: 1e5,
+            mode_loss: 1.0,
+        };
+        backend
+            .evolve(&mut vacuum, &hamiltonian, &[hot], 1, 2)
+            .unwrap();
+        let photons = vacuum
+            .gaussian_state()
+            .unwrap()
+            .mean_photon_number(0)
+            .unwrap();
+        assert!(photons > 9.0 && photons < 12.0);
+    }
+
+    #[test]
+    fn test_thermal_noise_loss_is_per_nanosecond() {
+        let mut backend = GaussianSimulator::new();
+        let hamiltonian = Hamiltonian {
+            terms: vec![],
+            static_field: false,
+        };
+        let eta_after = |backend: &mut GaussianSimulator, channel: NoiseChannel, ns: u64| {
+            let mut state = backend
+                .prepare(
+                    vec!["m".to_string()],
+                    &PreparationKind::ThermalState { mean_photons: 0.0 },
+                    1,
+                )
+                .unwrap();
+            let trace = backend
+                .evolve(&mut state, &hamiltonian, &[channel], ns, 2)
+                .unwrap();
+            match &trace.operations[0] {
+                QuantumOperation::Evolution { noise_channel, .. } => noise_channel
+                    .trim_start_matches("ThermalNoise(eta=")
+                    .split(',')
+                    .next()
+                    .unwrap()
+                    .parse::<f64>()
+                    .unwrap(),
+                other => panic!("unexpected operation {:?}", other),
+            }
+        };
+
+        // 1e-4 per ns compounds to 0.9999^1000 over 1 µs
+        let per_ns = NoiseChannel::ThermalNoise {
+            bath_temp_k: 4.0,
+            mode_loss: 1e-4,
+        };
+        let eta = eta_after(&mut backend, per_ns, 1_000);
+        assert!((eta - 0.9999f64.powi(1000)).abs() < 1e-6, "eta {}", eta);
+
+        // A 10% component loss over its 1 µs transit leaves η = 0.9, not ~0
+        let component = NoiseChannel::thermal_loss(4.0, 0.1, 1_000.0);
+        let eta = eta_after(&mut backend, component.clone(), 1_000);
+        assert!((eta - 0.9).abs() < 1e-6, "eta {}", eta);
+        let eta = eta_after(&mut backend, component, 500);
+        assert!((eta - 0.9f64.sqrt()).abs() < 1e-6, "eta {}", eta);
+    }
+
+    #[test]
+    fn test_statevector_simulator_bell_pair_correlations() {
+        let mut backend = StatevectorSimulator::new();
+        let labels = vec!["q0".to_string(), "q1".to_string()];
+        let mut state = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                3,
+            )
+            .unwrap();
+        let gate_params = HashMap::new();
+        for (name, qudits) in [("Hadamard", &labels[..1]), ("CNOT", &labels[..])] {
+            let gate = DVGate::from_name(name, qudits, &gate_params).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+        }
+        let dv = state.dv_data.as_ref().unwrap();
+        assert_eq!(dv.entanglement_graph["q0"], vec!["q1".to_string()]);
+        assert!((dv.qudits["q0"].purity - 0.5).abs() < 1e-9);
+
+        let bell = MeasurementBasis {
+            basis_type: BasisType::BellMeasurement {
+                qudit_pairs: vec![("q0".to_string(), "q1".to_string())],
+            },
+            mode_labels: labels.clone(),
+        };
+        let outcome = backend.measure(&mut state.clone(), &bell, 1).unwrap();
+        assert!(matches!(
+            outcome.classical_results["q0,q1"],
+            MeasurementResult::DiscreteOutcome(0)
+        ));
+
+        let computational = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: labels,
+        };
+        for seed in 0..10 {
+            let mut copy = state.clone();
+            let out = backend.measure(&mut copy, &computational, seed).unwrap();
+            assert_eq!(
+                format!("{:?}", out.classical_results["q0"]),
+                format!("{:?}", out.classical_results["q1"])
+            );
+            assert!(copy.dv_data.unwrap().entanglement_graph.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_statevector_simulator_prepares_bell_states() {
+        let labels = vec!["a".to_string(), "b".to_string()];
+        let computational = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: labels.clone(),
+        };
+        let bell = MeasurementBasis {
+            basis_type: BasisType::BellMeasurement {
+                qudit_pairs: vec![("a".to_string(), "b".to_string())],
+            },
+            mode_labels: labels.clone(),
+        };
+        for representation in [
+            DVRepresentation::StateVector,
+            DVRepresentation::DensityMatrix,
+        ] {
+            let mut backend = StatevectorSimulator::new().with_representation(representation);
+            for (index, (kind, correlated)) in [
+                (BellType::PhiPlus, true),
+                (BellType::PhiMinus, true),
+                (BellType::PsiPlus, false),
+                (BellType::PsiMinus, false),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let prep = PreparationKind::BellState {
+                    entanglement_type: kind,
+                };
+                let state = backend.prepare(labels.clone(), &prep, 1).unwrap();
+                let dv = state.dv_data.as_ref().unwrap();
+                assert_eq!(dv.entanglement_graph["a"], vec!["b".to_string()]);
+                assert!((dv.qudits["b"].purity - 0.5).abs() < 1e-9);
+
+                let out = backend.measure(&mut state.clone(), &bell, 1).unwrap();
+                assert_eq!(
+                    format!("{:?}", out.classical_results["a,b"]),
+                    format!("{:?}", MeasurementResult::DiscreteOutcome(index))
+                );
+                let mut seen = HashSet::new();
+                for seed in 0..20 {
+                    let out = backend
+                        .measure(&mut state.clone(), &computational, seed)
+                        .unwrap();
+                    let (a, b) = (&out.classical_results["a"], &out.classical_results["b"]);
+                    assert_eq!(format!("{:?}", a) == format!("{:?}", b), correlated);
+                    seen.insert(format!("{:?}", a));
+                }
+                assert_eq!(seen.len(), 2);
+            }
+        }
+        let prep = PreparationKind::BellState {
+            entanglement_type: BellType::PhiPlus,
+        };
+        let mut backend = StatevectorSimulator::new();
+        assert!(backend.prepare(vec!["a".to_string()], &prep, 1).is_err());
+    }
+
+    #[test]
+    fn test_statevector_simulator_prepares_ghz_state() {
+        let mut backend = StatevectorSimulator::new();
+        let labels: Vec<String> = (0..4).map(|i| format!("q{}", i)).collect();
+        let prep = PreparationKind::GHZState { num_qudits: 4 };
+        let state = backend.prepare(labels.clone(), &prep, 1).unwrap();
+        let dv = state.dv_data.as_ref().unwrap();
+        assert_eq!(dv.entanglement_graph["q2"].len(), 3);
+        let sv = state.statevector().unwrap().unwrap();
+        assert!((sv.amplitudes[0].re - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+        assert!((sv.amplitudes[15].re - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+
+        let computational = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: labels.clone(),
+        };
+        let mut seen = HashSet::new();
+        for seed in 0..20 {
+            let out = backend
+                .measure(&mut state.clone(), &computational, seed)
+                .unwrap();
+            let values: HashSet<String> = labels
+                .iter()
+                .map(|l| format!("{:?}", out.classical_results[l]))
+                .collect();
+            assert_eq!(values.len(), 1);
+            seen.extend(values);
+        }
+        assert_eq!(seen.len(), 2);
+        assert!(backend
+            .prepare(labels, &PreparationKind::GHZState { num_qudits: 3 }, 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_statevector_simulator_hadamard_basis_and_fidelity() {
+        let mut backend = StatevectorSimulator::new();
+        let labels = vec!["q".to_string()];
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        let plus = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![h, h],
+                },
+                1,
+            )
+            .unwrap();
+        let basis = MeasurementBasis {
+            basis_type: BasisType::Hadamard,
+            mode_labels: labels.clone(),
+        };
+        let out = backend.measure(&mut plus.clone(), &basis, 8).unwrap();
+        assert!(matches!(
+            out.classical_results["q"],
+            MeasurementResult::DiscreteOutcome(0)
+        ));
+
+        let zero = QuantumState::new_dv(vec![("q".to_string(), 2)], 1, 500);
+        assert!((backend.fidelity(&plus, &zero).unwrap() - 0.5).abs() < 1e-9);
+        assert!(DVGate::from_name("RX", &labels, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_mps_simulator_wide_register() {
+        let mut backend = MpsSimulator::new().with_bond_dimension(4);
+        let labels: Vec<String> = (0..200).map(|i| format!("q{}", i)).collect();
+        let mut state = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                5,
+            )
+            .unwrap();
+        let reference = state.clone();
+        let gate_params = HashMap::new();
+        backend
+            .apply_gate(
+                &mut state,
+                &DVGate::from_name("H", &labels[..1], &gate_params).unwrap(),
+            )
+            .unwrap();
+        for pair in labels.windows(2) {
+            let gate = DVGate::from_name("CNOT", pair, &gate_params).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+        }
+        let metrics = backend.metrics(&state).unwrap();
+        assert_eq!(metrics.max_bond_dimension, 2);
+        assert!(metrics.truncation_error < 1e-12);
+        // GHZ state: every single-qubit cut carries one ebit.
+        assert_eq!(metrics.entanglement_entropy.len(), 200);
+        assert!(metrics
+            .entanglement_entropy
+            .values()
+            .all(|s| (s - 1.0).abs() < 1e-9));
+        assert!((state.dv_data.as_ref().unwrap().qudits["q150"].purity - 0.5).abs() < 1e-9);
+        assert!((backend.fidelity(&reference, &state).unwrap() - 0.5).abs() < 1e-9);
+
+        let basis = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: vec!["q0".to_string(), "q199".to_string()],
+        };
+        let out = backend.measure(&mut state, &basis, 2).unwrap();
+        assert_eq!(
+            format!("{:?}", out.classical_results["q0"]),
+            format!("{:?}", out.classical_results["q199"])
+        );
+        assert!(state.dv_data.unwrap().entanglement_graph.is_empty());
+    }
+
+    #[test]
+    fn test_mps_simulator_reports_truncation() {
+        let mut backend = MpsSimulator::new().with_bond_dimension(1);
+        let labels: Vec<String> = (0..4).map(|i| format!("q{}", i)).collect();
+        let mut state = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                5,
+            )
+            .unwrap();
+        let gate_params = HashMap::new();
+        for (name, qudits) in [
+            ("H", vec![labels[0].clone()]),
+            ("CNOT", vec![labels[0].clone(), labels[3].clone()]),
+        ] {
+            let gate = DVGate::from_name(name, &qudits, &gate_params).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+        }
+        let metrics = backend.metrics(&state).unwrap();
+        assert_eq!(metrics.max_bond_dimension, 1);
+        assert!((metrics.truncation_error - 0.5).abs() < 1e-9);
+        assert!((metrics.fidelity_estimate - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_statevector_simulator_samples_shots() {
+        let labels = vec!["a".to_string(), "b".to_string()];
+        let computational = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: labels.clone(),
+        };
+        let bell = MeasurementBasis {
+            basis_type: BasisType::BellMeasurement {
+                qudit_pairs: vec![("a".to_string(), "b".to_string())],
+            },
+            mode_labels: labels.clone(),
+        };
+        for representation in [
+            DVRepresentation::StateVector,
+            DVRepresentation::DensityMatrix,
+        ] {
+            let mut backend = StatevectorSimulator::new().with_representation(representation);
+            let prep = PreparationKind::BellState {
+                entanglement_type: BellType::PhiPlus,
+            };
+            let state = backend.prepare(labels.clone(), &prep, 1).unwrap();
+
+            let record = backend.sample(&state, &computational, 2000, 9).unwrap();
+            assert_eq!(record.num_shots(), 2000);
+            assert_eq!(record.labels, labels);
+            let counts = record.counts().unwrap();
+            assert_eq!(counts.len(), 2);
+            assert!((900..=1100).contains(&counts["0,0"]), "{:?}", counts);
+            assert_eq!(counts["0,0"] + counts["1,1"], 2000);
+            let again = backend.sample(&state, &computational, 2000, 9).unwrap();
+            assert_eq!(again.counts().unwrap(), counts);
+            // Sampling leaves the state entangled.
+            assert_eq!(
+                state.dv_data.as_ref().unwrap().entanglement_graph["a"],
+                vec!["b".to_string()]
+            );
+
+            let record = backend.sample(&state, &bell, 50, 3).unwrap();
+            assert!(record.outcomes("a,b").unwrap().iter().all(|&k| k == 0));
+        }
+
+        let mut backend = StatevectorSimulator::new();
+        let psi_minus = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BellState {
+                    entanglement_type: BellType::PsiMinus,
+                },
+                1,
+            )
+            .unwrap();
+        let record = backend.sample(&psi_minus, &bell, 20, 3).unwrap();
+        assert!(record.outcomes("a,b").unwrap().iter().all(|&k| k == 3));
+        let homodyne = MeasurementBasis {
+            basis_type: BasisType::Homodyne {
+                axis: HomodyneAxis::Q,
+            },
+            mode_labels: labels,
+        };
+        assert!(backend.sample(&psi_minus, &homodyne, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_gaussian_simulator_samples_shots() {
+        let mut backend = GaussianSimulator::new();
+        let squeezed = backend
+            .prepare(
+                vec!["mode_0".to_string()],
+                &PreparationKind::DisplacedSqueezed {
+                    displacement_q: 1.0,
+                    displacement_p: 0.0,
+                    squeezing_db: 6.0,
+                    squeezing_angle: 0.0,
+                },
+                1,
+            )
+            .unwrap();
+        let moments = |values: &[f64]| {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let var = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            (mean, var)
+        };
+        let gaussian = squeezed.gaussian_state().unwrap();
+        for axis in [HomodyneAxis::Q, HomodyneAxis::P] {
+            let i = match axis {
+                HomodyneAxis::Q => 0,
+                HomodyneAxis::P => 1,
+            };
+            let basis = MeasurementBasis {
+                basis_type: BasisType::Homodyne { axis },
+                mode_labels: vec!["mode_0".to_string()],
+            };
+            let record = backend.sample(&squeezed, &basis, 4000, 5).unwrap();
+            let (mean, var) = moments(&record.values("mode_0").unwrap());
+            assert!((mean - gaussian.means[i]).abs() < 0.05);
+            assert!((var / gaussian.covariance[i][i] - 1.0).abs() < 0.08);
+        }
+
+        let vacuum = backend
+            .prepare(
+                vec!["v".to_string()],
+                &PreparationKind::ThermalState { mean_photons: 0.0 },
+                1,
+            )
+            .unwrap();
+        let basis = MeasurementBasis {
+            basis_type: BasisType::Heterodyne,
+            mode_labels: vec!["v".to_string()],
+        };
+        let record = backend.sample(&vacuum, &basis, 4000, 6).unwrap();
+        assert_eq!(record.labels, vec!["v:q".to_string(), "v:p".to_string()]);
+        for label in ["v:q", "v:p"] {
+            let (mean, var) = moments(&record.values(label).unwrap());
+            assert!(mean.abs() < 0.05);
+            assert!((var - 1.0).abs() < 0.08);
+        }
+        assert!(record.counts().is_err());
+    }
+
+    #[test]
+    fn test_mps_simulator_samples_shots() {
+        let mut backend = MpsSimulator::new().with_bond_dimension(4);
+        let labels: Vec<String> = (0..40).map(|i| format!("q{}", i)).collect();
+        let mut state = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                5,
+            )
+            .unwrap();
+        let gate_params = HashMap::new();
+        backend
+            .apply_gate(
+                &mut state,
+                &DVGate::from_name("H", &labels[..1], &gate_params).unwrap(),
+            )
+            .unwrap();
+        for pair in labels.windows(2) {
+            let gate = DVGate::from_name("CNOT", pair, &gate_params).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+        }
+        let basis = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: vec!["q0".to_string(), "q39".to_string()],
+        };
+        let record = backend.sample(&state, &basis, 200, 4).unwrap();
+        let counts = record.counts().unwrap();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["0,0"] + counts["1,1"], 200);
+        assert!(!state
+            .dv_data
+            .as_ref()
+            .unwrap()
+            .entanglement_graph
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parallel_sampling_independent_of_thread_count() {
+        let labels = vec!["a".to_string(), "b".to_string()];
+        let mut gaussian = GaussianSimulator::new();
+        let squeezed = gaussian
+            .prepare(
+                labels.clone(),
+                &PreparationKind::DisplacedSqueezed {
+                    displacement_q: 0.3,
+                    displacement_p: -0.2,
+                    squeezing_db: 4.0,
+                    squeezing_angle: 0.0,
+                },
+                1,
+            )
+            .unwrap();
+        let heterodyne = MeasurementBasis {
+            basis_type: BasisType::Heterodyne,
+            mode_labels: labels.clone(),
+        };
+        let mut mps = MpsSimulator::new();
+        let mut plus = mps
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                2,
+            )
+            .unwrap();
+        mps.apply_gate(
+            &mut plus,
+            &DVGate::Hadamard {
+                qudit: "a".to_string(),
+            },
+        )
+        .unwrap();
+        let computational = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: labels,
+        };
+
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let cv = gaussian.sample(&squeezed, &heterodyne, 500, 7).unwrap();
+                let dv = mps.sample(&plus, &computational, 500, 7).unwrap();
+                (
+                    serde_json::to_string(&cv.shots).unwrap(),
+                    serde_json::to_string(&dv.shots).unwrap(),
+                )
+            })
+        };
+        assert_eq!(run(1), run(4));
+    }
+
+    #[test]
+    fn test_statevector_simulator_hamiltonian_evolution() {
+        let term = |coefficient: f64, ops: &[(&str, &str)]| PauliTerm {
+            coefficient,
+            operators: ops
+                .iter()
+                .map(|(l, o)| (l.to_string(), o.to_string()))
+                .collect(),
+        };
+        let labels = vec!["q0".to_string(), "q1".to_string()];
+        let zero = PreparationKind::BasisState {
+            amplitudes: vec![1.0, 0.0],
+        };
+        // XX coupling at 2π·1 MHz for a quarter period swaps |00⟩ ↔ |11⟩ halfway.
+        let g = 2.0 * std::f64::consts::PI * 1e-3;
+        let hamiltonian = Hamiltonian {
+            terms: vec![term(g, &[("q0", "X"), ("q1", "X")])],
+            static_field: true,
+        };
+        for representation in [
+            DVRepresentation::StateVector,
+            DVRepresentation::DensityMatrix,
+        ] {
+            let mut backend = StatevectorSimulator::new().with_representation(representation);
+            let mut state = backend.prepare(labels.clone(), &zero, 1).unwrap();
+            let trace = backend
+                .evolve(&mut state, &hamiltonian, &[], 125, 2)
+                .unwrap();
+            assert!(matches!(
+                &trace.operations[0],
+                QuantumOperation::Unitary { gate_name, .. } if gate_name == "PauliEvolution"
+            ));
+            let dv = state.dv_data.as_ref().unwrap();
+            assert!((dv.qudits["q0"].amplitudes[1].powi(2) - 0.5).abs() < 1e-9);
+            assert_eq!(dv.entanglement_graph["q1"], vec!["q0".to_string()]);
+        }
+
+        // Non-commuting drive + detuning is Trotterized and stays normalized.
+        let mut backend = StatevectorSimulator::new();
+        let mut state = backend.prepare(labels[..1].to_vec(), &zero, 1).unwrap();
+        let rabi = Hamiltonian {
+            terms: vec![term(0.01, &[("q0", "X")]), term(0.006, &[("q0", "Z")])],
+            static_field: true,
+        };
+        let trace = backend.evolve(&mut state, &rabi, &[], 135, 3).unwrap();
+        match &trace.operations[0] {
+            QuantumOperation::Unitary { parameters, .. } => {
+                assert!(parameters["trotter_steps"] > 1.0)
+            }
+            other => panic!("unexpected operation {:?}", other),
+        }
+        let sv = state.statevector().unwrap().unwrap();
+        assert!((sv.norm_sqr() - 1.0).abs() < 1e-9);
+        // P(|1⟩) = Ω²/(Ω²+Δ²)·sin²(√(Ω²+Δ²)·t/2) with Ω = 0.02, Δ = 0.012 rad/ns.
+        let (omega, delta) = (0.02f64, 0.012f64);
+        let rabi_rate = omega.hypot(delta);
+        let expected = (omega / rabi_rate).powi(2) * (rabi_rate * 135.0 / 2.0).sin().powi(2);
+        assert!((sv.marginal(0).unwrap()[1] - expected).abs() < 1e-3);
+
+        let bad = Hamiltonian {
+            terms: vec![term(1.0, &[("q9", "X")])],
+            static_field: true,
+        };
+        assert!(backend.evolve(&mut state, &bad, &[], 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_density_matrix_representation_tracks_noise() {
+        let mut backend =
+            StatevectorSimulator::new().with_representation(DVRepresentation::DensityMatrix);
+        let labels = vec!["q0".to_string(), "q1".to_string()];
+        let mut state = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                3,
+            )
+            .unwrap();
+        assert!(state.is_mixed_representation());
+        let gate_params = HashMap::new();
+        for (name, qudits) in [("Hadamard", &labels[..1]), ("CNOT", &labels[..])] {
+            let gate = DVGate::from_name(name, qudits, &gate_params).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+        }
+        let pure_bell = state.clone();
+        assert!((state.snapshot().unwrap().global_purity - 1.0).abs() < 1e-9);
+
+        backend
+            .apply_kraus(&mut state, "q0", &density::phase_damping_kraus(1.0))
+            .unwrap();
+        let purity = state.snapshot().unwrap().global_purity;
+        assert!((purity - 0.5).abs() < 1e-9);
+        assert!((backend.fidelity(&pure_bell, &state).unwrap() - 0.5).abs() < 1e-9);
+
+        // Dephasing kills the coherence but not the classical correlation.
+        let computational = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: labels,
+        };
+        for seed in 0..5 {
+            let out = backend
+                .measure(&mut state.clone(), &computational, seed)
+                .unwrap();
+            assert_eq!(
+                format!("{:?}", out.classical_results["q0"]),
+                format!("{:?}", out.classical_results["q1"])
+            );
+        }
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: QuantumState = serde_json::from_str(&json).unwrap();
+        assert!((restored.snapshot().unwrap().global_purity - purity).abs() < 1e-9);
+    }
+
     #[test]
     fn test_quantum_artifact() {
         let state = QuantumState::new_cv(vec!["mode_0".to_string()], 12345, 500);
-        let artifact = QuantumArtifact::new(
+        let mut artifact = QuantumArtifact::new(
             "kernel_0".to_string(),
             state.clone(),
             "gaussian_simulator".to_string(),
@@ -1009,6 +3732,134 @@ mod tests {
         assert_eq!(artifact.kernel_id, "kernel_0");
         assert_eq!(artifact.backend_name, "gaussian_simulator");
         assert!(!artifact.can_deterministic_replay()); // needs noise_model_id
+
+        let profile = crate::simulator::NoiseProfileRegistry::new()
+            .get("ideal")
+            .unwrap();
+        artifact.set_noise_profile(&profile);
+        assert_eq!(artifact.noise_model_id.as_deref(), Some("ideal"));
+        assert!(artifact.can_deterministic_replay());
+    }
+
+    #[test]
+    fn test_backend_wigner_functions() {
+        let grid = PhaseSpaceGrid::square(5.0, 61);
+        let mut gaussian = GaussianSimulator::new();
+        let modes = vec!["a".to_string(), "b".to_string()];
+        let vacuum = PreparationKind::ThermalState { mean_photons: 0.0 };
+        let mut cv = gaussian.prepare(modes, &vacuum, 1).unwrap();
+        gaussian
+            .apply_gate(
+                &mut cv,
+                &GaussianGate::Displace {
+                    mode: "b".to_string(),
+                    q: 1.0,
+                    p: 0.0,
+                },
+            )
+            .unwrap();
+        let w = gaussian.wigner(&cv, "b", &grid).unwrap();
+        let (i, j) = (36, 30); // (q, p) = (1, 0): peak of the displaced vacuum
+        assert!((w.q[i] - 1.0).abs() < 1e-12 && w.p[j].abs() < 1e-12);
+        assert!((w.values[i][j] - 1.0 / std::f64::consts::PI).abs() < 1e-12);
+        assert!((w.integral() - 1.0).abs() < 1e-6);
+        assert!(gaussian.wigner(&cv, "c", &grid).is_err());
+
+        // A qubit flipped to |1⟩ reads as a single photon: negative at the origin.
+        let mut dv = StatevectorSimulator::new();
+        let labels = vec!["q0".to_string(), "q1".to_string()];
+        let basis = PreparationKind::BasisState {
+            amplitudes: vec![0.0, 1.0],
+        };
+        let state = dv.prepare(labels, &basis, 1).unwrap();
+        let w = dv.wigner(&state, "q1", &grid).unwrap();
+        assert!((w.min_value() + 1.0 / std::f64::consts::PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_artifact_records_entanglement_entropy() {
+        let mut backend = StatevectorSimulator::new();
+        let labels = vec!["q0".to_string(), "q1".to_string(), "q2".to_string()];
+        let mut state = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                3,
+            )
+            .unwrap();
+        let mut artifact = QuantumArtifact::new(
+            "kernel_0".to_string(),
+            state.clone(),
+            "statevector_simulator".to_string(),
+        );
+        assert!(artifact
+            .metrics
+            .entanglement_entropy
+            .values()
+            .all(|s| s.abs() < 1e-9));
+
+        let gate_params = HashMap::new();
+        for (name, qudits) in [("Hadamard", &labels[..1]), ("CNOT", &labels[..2])] {
+            let gate = DVGate::from_name(name, qudits, &gate_params).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+        }
+        let partitions = vec![
+            vec!["q0".to_string()],
+            vec!["q1".to_string(), "q0".to_string()],
+            vec!["q2".to_string()],
+        ];
+        artifact.record_snapshot(&state, &partitions).unwrap();
+        let entropy = &artifact.metrics.entanglement_entropy;
+        let key = |a: &str, b: &str| (a.to_string(), b.to_string());
+        assert!((entropy[&key("q0", "q1,q2")] - 1.0).abs() < 1e-9);
+        assert!(entropy[&key("q1,q0", "q2")].abs() < 1e-9);
+        assert!(entropy[&key("q2", "q0,q1")].abs() < 1e-9);
+        assert!(state
+            .entanglement_entropies(&[vec!["q3".to_string()]])
+            .is_err());
+
+        let json = serde_json::to_string(&artifact).unwrap();
+        let restored: QuantumArtifact = serde_json::from_str(&json).unwrap();
+        let restored = &restored.intermediate_states[0].entanglement_entropy;
+        assert_eq!(restored.len(), 3);
+        assert!((restored[&key("q0", "q1,q2")] - 1.0).abs() < 1e-9);
+
+        let cv = QuantumState::new_cv(vec!["a".to_string(), "b".to_string()], 1, 500);
+        let snapshot = cv.snapshot().unwrap();
+        assert_eq!(snapshot.entanglement_entropy.len(), 2);
+        assert!(snapshot
+            .entanglement_entropy
+            .values()
+            .all(|s| s.abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_fidelity_drift_detector_tracks_displacement() {
+        let mut backend = GaussianSimulator::new();
+        let modes = vec!["m".to_string()];
+        let vacuum = PreparationKind::ThermalState { mean_photons: 0.0 };
+        let reference = backend.prepare(modes.clone(), &vacuum, 1).unwrap();
+        let mut drifted = reference.clone();
+        backend
+            .apply_gate(
+                &mut drifted,
+                &GaussianGate::Displace {
+                    mode: "m".to_string(),
+                    q: 0.2,
+                    p: 0.0,
+                },
+            )
+            .unwrap();
+        let detector = SimpleFidelityDriftDetector::new(0.05);
+        // Coherent-state infidelity 1 − exp(−|α|²) with |α|² = q²/2.
+        let drift = detector.detect_drift(&reference, &drifted).unwrap();
+        assert!((drift - (1.0 - (-0.02f64).exp())).abs() < 1e-9);
+        assert_eq!(detector.detect_drift(&reference, &reference).unwrap(), 0.0);
+
+        let dv = QuantumState::new_dv(vec![("m".to_string(), 2)], 1, 500);
+        assert!(detector.detect_drift(&reference, &dv).is_err());
     }
 
     #[test]
@@ -0,0 +1,547 @@
+//! Mixed-state (density-matrix) representation for discrete-variable registers.
+//!
+//! [`DensityMatrix`] uses the same mixed-radix qudit ordering as [`StateVector`]. Unitaries act
+//! as `ρ → UρU†` and noise as Kraus maps `ρ → Σ KρK†`; both reuse the statevector kernels by
+//! applying the operator to the columns of `ρ` and then to the columns of its adjoint.
+
+use super::linalg::hermitian_eigen;
+use super::statevector::{GateMatrix, StateVector};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+const TRACE_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DensityMatrix {
+    pub dims: Vec<usize>,
+    pub rho: Vec<Vec<Complex64>>,
+}
+
+impl DensityMatrix {
+    /// `|ψ⟩⟨ψ|`.
+    pub fn from_statevector(sv: &StateVector) -> Self {
+        let rho = sv
+            .amplitudes
+            .iter()
+            .map(|a| sv.amplitudes.iter().map(|b| a * b.conj()).collect())
+            .collect();
+        Self {
+            dims: sv.dims.clone(),
+            rho,
+        }
+    }
+
+    pub fn from_matrix(dims: Vec<usize>, rho: Vec<Vec<Complex64>>) -> Result<Self> {
+        let size: usize = dims.iter().product();
+        if rho.len() != size || rho.iter().any(|r| r.len() != size) {
+            return Err(anyhow!(
+                "density matrix must be {}x{} for dimensions {:?}",
+                size,
+                size,
+                dims
+            ));
+        }
+        let dm = Self { dims, rho };
+        let trace = dm.trace();
+        if (trace - 1.0).abs() > 1e-6 {
+            return Err(anyhow!("density matrix trace is {}, expected 1", trace));
+        }
+        Ok(dm)
+    }
+
+    pub fn num_qudits(&self) -> usize {
+        self.dims.len()
+    }
+
+    pub fn size(&self) -> usize {
+        self.rho.len()
+    }
+
+    pub fn trace(&self) -> f64 {
+        (0..self.size()).map(|i| self.rho[i][i].re).sum()
+    }
+
+    /// `Tr(ρ²)`: 1 for pure states, `1/D` for the maximally mixed state.
+    pub fn purity(&self) -> f64 {
+        // Tr(ρ²) = Σ_ij ρ_ij ρ_ji = Σ_ij |ρ_ij|² for Hermitian ρ.
+        self.rho
+            .iter()
+            .flat_map(|row| row.iter())
+            .map(|v| v.norm_sqr())
+            .sum()
+    }
+
+    /// Reduced state of the qudits in `keep` (in the given order) of a pure state, without
+    /// expanding `|ψ⟩⟨ψ|` over the whole register.
+    pub fn reduced_from_statevector(sv: &StateVector, keep: &[usize]) -> Result<DensityMatrix> {
+        if let Some(q) = keep.iter().find(|&&q| q >= sv.num_qudits()) {
+            return Err(anyhow!("qudit {} out of range", q));
+        }
+        let kept_dims: Vec<usize> = keep.iter().map(|&q| sv.dims[q]).collect();
+        let size: usize = kept_dims.iter().product();
+        // Group amplitudes by the digits of the traced qudits: ρ_ab = Σ_e ψ_(a,e) ψ*_(b,e).
+        let traced: Vec<usize> = (0..sv.num_qudits()).filter(|q| !keep.contains(q)).collect();
+        let mut columns = vec![vec![ZERO; size]; sv.amplitudes.len() / size];
+        for (index, amp) in sv.amplitudes.iter().enumerate() {
+            let index_of = |qs: &[usize]| {
+                qs.iter()
+                    .fold(0, |acc, &q| acc * sv.dims[q] + sv.digit(index, q))
+            };
+            columns[index_of(&traced)][index_of(keep)] = *amp;
+        }
+        let mut rho = vec![vec![ZERO; size]; size];
+        for column in &columns {
+            for (row, a) in rho.iter_mut().zip(column) {
+                for (v, b) in row.iter_mut().zip(column) {
+                    *v += a * b.conj();
+                }
+            }
+        }
+        Ok(DensityMatrix {
+            dims: kept_dims,
+            rho,
+        })
+    }
+
+    /// Reduced state of the qudits in `keep` (in the given order), tracing out the rest.
+    pub fn partial_trace(&self, keep: &[usize]) -> Result<DensityMatrix> {
+        for &q in keep {
+            if q >= self.num_qudits() {
+                return Err(anyhow!("qudit {} out of range", q));
+            }
+        }
+        let digits = |index: usize| -> Vec<usize> {
+            let mut out = vec![0; self.dims.len()];
+            let mut rem = index;
+            for (q, d) in self.dims.iter().enumerate().rev() {
+                out[q] = rem % d;
+                rem /= d;
+            }
+            out
+        };
+        let kept_dims: Vec<usize> = keep.iter().map(|&q| self.dims[q]).collect();
+        let reduced_index = |ds: &[usize]| -> usize {
+            keep.iter()
+                .zip(&kept_dims)
+                .fold(0, |acc, (&q, &d)| acc * d + ds[q])
+        };
+        let size: usize = kept_dims.iter().product();
+        let mut rho = vec![vec![ZERO; size]; size];
+        let all_digits: Vec<Vec<usize>> = (0..self.size()).map(digits).collect();
+        for (i, di) in all_digits.iter().enumerate() {
+            for (j, dj) in all_digits.iter().enumerate() {
+                let traced_equal = (0..self.num_qudits())
+                    .filter(|q| !keep.contains(q))
+                    .all(|q| di[q] == dj[q]);
+                if traced_equal {
+                    rho[reduced_index(di)][reduced_index(dj)] += self.rho[i][j];
+                }
+            }
+        }
+        Ok(DensityMatrix {
+            dims: kept_dims,
+            rho,
+        })
+    }
+
+    /// `ρ → MρN†`, with `M` and `N` applied to `target` (optionally controlled).
+    fn sandwich(
+        &self,
+        control: Option<usize>,
+        target: usize,
+        left: &GateMatrix,
+        right: &GateMatrix,
+    ) -> Result<Vec<Vec<Complex64>>> {
+        self.sandwich_with(
+            |col| col.apply_controlled(control, target, left),
+            |col| col.apply_controlled(control, target, right),
+        )
+    }
+
+    /// `ρ → MρN†` with `M` and `N` given by their action on statevectors.
+    fn sandwich_with(
+        &self,
+        left: impl Fn(&mut StateVector) -> Result<()>,
+        right: impl Fn(&mut StateVector) -> Result<()>,
+    ) -> Result<Vec<Vec<Complex64>>> {
+        // Columns of Mρ.
+        let mut m_rho = self.columns();
+        for col in m_rho.iter_mut() {
+            left(col)?;
+        }
+        // (Mρ)N† = (N (Mρ)†)†.
+        let mut adjoint: Vec<StateVector> = (0..self.size())
+            .map(|row| StateVector {
+                dims: self.dims.clone(),
+                amplitudes: m_rho.iter().map(|col| col.amplitudes[row].conj()).collect(),
+            })
+            .collect();
+        for col in adjoint.iter_mut() {
+            right(col)?;
+        }
+        Ok((0..self.size())
+            .map(|i| adjoint.iter().map(|col| col.amplitudes[i].conj()).collect())
+            .collect())
+    }
+
+    fn columns(&self) -> Vec<StateVector> {
+        (0..self.size())
+            .map(|j| StateVector {
+                dims: self.dims.clone(),
+                amplitudes: self.rho.iter().map(|row| row[j]).collect(),
+            })
+            .collect()
+    }
+
+    /// `ρ → UρU†` for a (controlled) single-qudit unitary.
+    pub fn apply_unitary(
+        &mut self,
+        control: Option<usize>,
+        target: usize,
+        unitary: &GateMatrix,
+    ) -> Result<()> {
+        self.rho = self.sandwich(control, target, unitary, unitary)?;
+        Ok(())
+    }
+
+    /// `ρ → UρU†` for a unitary given by its action on statevectors (e.g. a multi-qudit
+    /// rotation that has no single-qudit matrix form).
+    pub fn conjugate_by(&mut self, unitary: impl Fn(&mut StateVector) -> Result<()>) -> Result<()> {
+        self.rho = self.sandwich_with(&unitary, &unitary)?;
+        Ok(())
+    }
+
+    /// Apply the channel `ρ → Σ_k K_k ρ K_k†` to qudit `target`. The Kraus operators must satisfy
+    /// `Σ K_k†K_k = I`.
+    pub fn apply_kraus(&mut self, target: usize, kraus: &[GateMatrix]) -> Result<()> {
+        if target >= self.num_qudits() {
+            return Err(anyhow!("qudit {} out of range", target));
+        }
+        check_completeness(kraus, self.dims[target])?;
+        let mut out = vec![vec![ZERO; self.size()]; self.size()];
+        for k in kraus {
+            let term = self.sandwich(None, target, k, k)?;
+            for (row, trow) in out.iter_mut().zip(term) {
+                for (v, t) in row.iter_mut().zip(trow) {
+                    *v += t;
+                }
+            }
+        }
+        self.rho = out;
+        Ok(())
+    }
+
+    /// Probability of each basis value of qudit `q`.
+    pub fn marginal(&self, q: usize) -> Result<Vec<f64>> {
+        let reduced = self.partial_trace(&[q])?;
+        Ok((0..reduced.size()).map(|i| reduced.rho[i][i].re).collect())
+    }
+
+    /// Projective measurement of qudit `q` in the computational basis; collapses the state.
+    pub fn measure<R: Rng>(&mut self, q: usize, rng: &mut R) -> Result<usize> {
+        let probs = self.marginal(q)?;
+        let total: f64 = probs.iter().sum();
+        let mut u = rng.gen::<f64>() * total;
+        let mut outcome = probs.len() - 1;
+        for (i, p) in probs.iter().enumerate() {
+            if u < *p {
+                outcome = i;
+                break;
+            }
+            u -= p;
+        }
+        let probe = StateVector::zero(self.dims.clone());
+        let keep: Vec<bool> = (0..self.size())
+            .map(|i| probe.digit(i, q) == outcome)
+            .collect();
+        for (i, row) in self.rho.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                if !(keep[i] && keep[j]) {
+                    *v = ZERO;
+                }
+            }
+        }
+        let p = self.trace();
+        if p < TRACE_TOLERANCE {
+            return Err(anyhow!(
+                "measurement outcome {} has zero probability",
+                outcome
+            ));
+        }
+        self.rho
+            .iter_mut()
+            .flat_map(|row| row.iter_mut())
+            .for_each(|v| *v /= p);
+        Ok(outcome)
+    }
+
+    /// `⟨ψ|ρ|ψ⟩`, the fidelity with a pure state.
+    pub fn expectation(&self, sv: &StateVector) -> Result<f64> {
+        if sv.dims != self.dims {
+            return Err(anyhow!(
+                "dimension mismatch: {:?} vs {:?}",
+                sv.dims,
+                self.dims
+            ));
+        }
+        let mut total = ZERO;
+        for (i, row) in self.rho.iter().enumerate() {
+            for (j, v) in row.iter().enumerate() {
+                total += sv.amplitudes[i].conj() * v * sv.amplitudes[j];
+            }
+        }
+        Ok(total.re)
+    }
+
+    /// `Tr(ρσ)`; equals the fidelity when either state is pure.
+    pub fn overlap(&self, other: &DensityMatrix) -> Result<f64> {
+        if other.dims != self.dims {
+            return Err(anyhow!(
+                "dimension mismatch: {:?} vs {:?}",
+                other.dims,
+                self.dims
+            ));
+        }
+        let mut total = ZERO;
+        for (i, row) in self.rho.iter().enumerate() {
+            for (j, v) in row.iter().enumerate() {
+                total += v * other.rho[j][i];
+            }
+        }
+        Ok(total.re)
+    }
+
+    /// Von Neumann entropy `−Tr(ρ log₂ρ)` in bits.
+    pub fn entropy(&self) -> f64 {
+        let (values, _) = hermitian_eigen(self.rho.clone());
+        values
+            .iter()
+            .filter(|&&l| l > 1e-12)
+            .map(|l| -l * l.log2())
+            .sum::<f64>()
+            .max(0.0)
+    }
+
+    /// Uhlmann fidelity `F = (Tr√(√ρ σ √ρ))²`.
+    pub fn fidelity(&self, other: &DensityMatrix) -> Result<f64> {
+        if other.dims != self.dims {
+            return Err(anyhow!(
+                "dimension mismatch: {:?} vs {:?}",
+                other.dims,
+                self.dims
+            ));
+        }
+        let n = self.size();
+        let (values, vectors) = hermitian_eigen(self.rho.clone());
+        // √ρ = U diag(√λ) U†
+        let sqrt_rho: Vec<Vec<Complex64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        (0..n)
+                            .map(|k| {
+                                vectors[i][k] * values[k].max(0.0).sqrt() * vectors[j][k].conj()
+                            })
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        let product = |a: &[Vec<Complex64>], b: &[Vec<Complex64>]| -> Vec<Vec<Complex64>> {
+            (0..n)
+                .map(|i| {
+                    (0..n)
+                        .map(|j| (0..n).map(|k| a[i][k] * b[k][j]).sum())
+                        .collect()
+                })
+                .collect()
+        };
+        let inner = product(&product(&sqrt_rho, &other.rho), &sqrt_rho);
+        let (spectrum, _) = hermitian_eigen(inner);
+        let root: f64 = spectrum.iter().map(|l| l.max(0.0).sqrt()).sum();
+        Ok((root * root).clamp(0.0, 1.0))
+    }
+}
+
+fn check_completeness(kraus: &[GateMatrix], d: usize) -> Result<()> {
+    if kraus.is_empty() {
+        return Err(anyhow!("at least one Kraus operator is required"));
+    }
+    for k in kraus {
+        if k.len() != d || k.iter().any(|r| r.len() != d) {
+            return Err(anyhow!("Kraus operators must be {}x{}", d, d));
+        }
+    }
+    for i in 0..d {
+        for j in 0..d {
+            let sum: Complex64 = kraus
+                .iter()
+                .map(|k| (0..d).map(|m| k[m][i].conj() * k[m][j]).sum::<Complex64>())
+                .sum();
+            let expected = if i == j { 1.0 } else { 0.0 };
+            if (sum - Complex64::new(expected, 0.0)).norm() > 1e-6 {
+                return Err(anyhow!("Kraus operators are not trace preserving"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn real_matrix(rows: &[&[f64]]) -> GateMatrix {
+    rows.iter()
+        .map(|r| r.iter().map(|v| Complex64::new(*v, 0.0)).collect())
+        .collect()
+}
+
+/// Single-qubit depolarizing channel with error probability `p` (`ρ → (1−p)ρ + p·I/2`).
+pub fn depolarizing_kraus(p: f64) -> Vec<GateMatrix> {
+    let p = p.clamp(0.0, 1.0);
+    let a = (1.0 - 3.0 * p / 4.0).sqrt();
+    let b = (p / 4.0).sqrt();
+    let i = Complex64::new(0.0, 1.0);
+    vec![
+        real_matrix(&[&[a, 0.0], &[0.0, a]]),
+        real_matrix(&[&[0.0, b], &[b, 0.0]]),
+        vec![vec![ZERO, -i * b], vec![i * b, ZERO]],
+        real_matrix(&[&[b, 0.0], &[0.0, -b]]),
+    ]
+}
+
+/// Single-qubit phase damping: off-diagonal coherences scale by `√(1−λ)`.
+pub fn phase_damping_kraus(lambda: f64) -> Vec<GateMatrix> {
+    let lambda = lambda.clamp(0.0, 1.0);
+    vec![
+        real_matrix(&[&[1.0, 0.0], &[0.0, (1.0 - lambda).sqrt()]]),
+        real_matrix(&[&[0.0, 0.0], &[0.0, lambda.sqrt()]]),
+    ]
+}
+
+/// Single-qubit amplitude damping (energy relaxation `|1⟩ → |0⟩` with probability `γ`).
+pub fn amplitude_damping_kraus(gamma: f64) -> Vec<GateMatrix> {
+    let gamma = gamma.clamp(0.0, 1.0);
+    vec![
+        real_matrix(&[&[1.0, 0.0], &[0.0, (1.0 - gamma).sqrt()]]),
+        real_matrix(&[&[0.0, gamma.sqrt()], &[0.0, 0.0]]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quantum::statevector::{hadamard, pauli_x};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn bell() -> DensityMatrix {
+        let mut sv = StateVector::zero(vec![2, 2]);
+        sv.hadamard(0).unwrap();
+        sv.cnot(0, 1).unwrap();
+        DensityMatrix::from_statevector(&sv)
+    }
+
+    #[test]
+    fn test_partial_trace_of_bell_pair_is_maximally_mixed() {
+        let rho = bell();
+        assert!((rho.purity() - 1.0).abs() < 1e-12);
+        let reduced = rho.partial_trace(&[1]).unwrap();
+        assert!((reduced.purity() - 0.5).abs() < 1e-12);
+        assert!((reduced.rho[0][0].re - 0.5).abs() < 1e-12);
+        assert!(reduced.rho[0][1].norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_unitaries_match_statevector() {
+        let mut rho = DensityMatrix::from_statevector(&StateVector::zero(vec![2, 2]));
+        rho.apply_unitary(None, 0, &hadamard()).unwrap();
+        rho.apply_unitary(Some(0), 1, &pauli_x(2)).unwrap();
+        assert!((rho.rho[0][3].re - 0.5).abs() < 1e-12);
+        assert!((rho.trace() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_kraus_channels_reduce_purity() {
+        let mut plus = DensityMatrix::from_statevector(
+            &StateVector::from_amplitudes(
+                vec![2],
+                vec![Complex64::new(1.0, 0.0), Complex64::new(1.0, 0.0)],
+            )
+            .unwrap(),
+        );
+        plus.apply_kraus(0, &phase_damping_kraus(1.0)).unwrap();
+        assert!((plus.purity() - 0.5).abs() < 1e-12);
+        assert!((plus.trace() - 1.0).abs() < 1e-12);
+
+        let mut rho = bell();
+        rho.apply_kraus(0, &depolarizing_kraus(1.0)).unwrap();
+        assert!((rho.purity() - 0.25).abs() < 1e-12);
+
+        let mut excited = DensityMatrix::from_statevector(&{
+            let mut sv = StateVector::zero(vec![2]);
+            sv.apply_single(0, &pauli_x(2)).unwrap();
+            sv
+        });
+        excited
+            .apply_kraus(0, &amplitude_damping_kraus(1.0))
+            .unwrap();
+        assert!((excited.rho[0][0].re - 1.0).abs() < 1e-12);
+
+        let bad = vec![real_matrix(&[&[0.5, 0.0], &[0.0, 0.5]])];
+        assert!(rho.apply_kraus(0, &bad).is_err());
+    }
+
+    #[test]
+    fn test_entropy_of_reduced_states() {
+        let mut sv = StateVector::zero(vec![2, 2, 3]);
+        sv.hadamard(0).unwrap();
+        sv.cnot(0, 1).unwrap();
+        let reduced = DensityMatrix::reduced_from_statevector(&sv, &[1]).unwrap();
+        assert_eq!(
+            reduced,
+            DensityMatrix::from_statevector(&sv)
+                .partial_trace(&[1])
+                .unwrap()
+        );
+        assert!((reduced.entropy() - 1.0).abs() < 1e-9);
+        let pair = DensityMatrix::reduced_from_statevector(&sv, &[1, 0]).unwrap();
+        assert!(pair.entropy().abs() < 1e-9);
+        assert!(DensityMatrix::reduced_from_statevector(&sv, &[3]).is_err());
+    }
+
+    #[test]
+    fn test_uhlmann_fidelity() {
+        let rho = bell();
+        assert!((rho.fidelity(&rho).unwrap() - 1.0).abs() < 1e-9);
+
+        // Diagonal states: F = (Σ √(p_i q_i))².
+        let diag = |p: f64| {
+            DensityMatrix::from_matrix(
+                vec![2],
+                vec![
+                    vec![Complex64::new(p, 0.0), ZERO],
+                    vec![ZERO, Complex64::new(1.0 - p, 0.0)],
+                ],
+            )
+            .unwrap()
+        };
+        let expected = ((0.3f64 * 0.8).sqrt() + (0.7f64 * 0.2).sqrt()).powi(2);
+        assert!((diag(0.3).fidelity(&diag(0.8)).unwrap() - expected).abs() < 1e-9);
+
+        // Reduces to ⟨ψ|σ|ψ⟩ when one side is pure.
+        let mut noisy = bell();
+        noisy.apply_kraus(1, &depolarizing_kraus(0.4)).unwrap();
+        let pure = bell();
+        assert!((pure.fidelity(&noisy).unwrap() - pure.overlap(&noisy).unwrap()).abs() < 1e-9);
+        assert!((noisy.fidelity(&pure).unwrap() - pure.overlap(&noisy).unwrap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_measurement_collapses_correlated_partner() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut rho = bell();
+        let a = rho.measure(0, &mut rng).unwrap();
+        assert_eq!(rho.marginal(1).unwrap()[a], 1.0);
+    }
+}
@@ -0,0 +1,846 @@
+//! Gaussian (covariance-matrix) continuous-variable state simulation.
+//!
+//! States are described by a mean vector and covariance matrix over the quadratures
+//! `(q_0, p_0, q_1, p_1, ...)` with ħ = 1, so the vacuum has covariance `½·I`. Gaussian unitaries
+//! act as symplectic matrices `S`: `μ → Sμ`, `V → S V Sᵀ`. Homodyne and heterodyne outcomes are
+//! sampled from the exact marginal distributions and the unmeasured modes are conditioned on the
+//! outcome; measured modes are left in vacuum.
+
+use super::density::DensityMatrix;
+use super::linalg::{determinant, inverse, matmul, symplectic_eigenvalues};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Quadrature variance of the vacuum state (ħ = 1).
+pub const VACUUM_VARIANCE: f64 = 0.5;
+
+const PLANCK_J_S: f64 = 6.626_070_15e-34;
+const BOLTZMANN_J_PER_K: f64 = 1.380_649e-23;
+
+/// Bose–Einstein occupation `n̄ = 1/(e^{hν/kT} − 1)` of a mode at `frequency_hz` in a bath at
+/// `temp_k`.
+pub fn thermal_occupation(frequency_hz: f64, temp_k: f64) -> f64 {
+    if temp_k <= 0.0 {
+        return 0.0;
+    }
+    1.0 / (PLANCK_J_S * frequency_hz / (BOLTZMANN_J_PER_K * temp_k)).exp_m1()
+}
+
+/// Squeezing parameter `r` for a squeezing level in dB (`dB = 10·log10(e^{2r})`).
+pub fn db_to_squeezing(squeezing_db: f64) -> f64 {
+    squeezing_db * std::f64::consts::LN_10 / 20.0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GaussianState {
+    pub means: Vec<f64>,
+    pub covariance: Vec<Vec<f64>>,
+}
+
+impl GaussianState {
+    pub fn vacuum(num_modes: usize) -> Self {
+        Self::thermal(num_modes, 0.0)
+    }
+
+    /// Every mode in a thermal state with `mean_photons` average occupation.
+    pub fn thermal(num_modes: usize, mean_photons: f64) -> Self {
+        let dim = 2 * num_modes;
+        let mut covariance = vec![vec![0.0; dim]; dim];
+        for (i, row) in covariance.iter_mut().enumerate() {
+            row[i] = mean_photons + VACUUM_VARIANCE;
+        }
+        Self {
+            means: vec![0.0; dim],
+            covariance,
+        }
+    }
+
+    pub fn num_modes(&self) -> usize {
+        self.means.len() / 2
+    }
+
+    /// Check that means and covariance have consistent, even dimensions.
+    pub fn validate(&self) -> Result<()> {
+        let dim = self.means.len();
+        if !dim.is_multiple_of(2) {
+            return Err(anyhow!("Gaussian state has odd quadrature count {}", dim));
+        }
+        if self.covariance.len() != dim || self.covariance.iter().any(|r| r.len() != dim) {
+            return Err(anyhow!(
+                "covariance matrix must be {}x{} for {} modes",
+                dim,
+                dim,
+                dim / 2
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_mode(&self, mode: usize) -> Result<()> {
+        if mode >= self.num_modes() {
+            return Err(anyhow!(
+                "mode {} out of range ({} modes)",
+                mode,
+                self.num_modes()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Displacement `D(α)` with `α = (q + i·p)/√2`.
+    pub fn displace(&mut self, mode: usize, q: f64, p: f64) -> Result<()> {
+        self.check_mode(mode)?;
+        self.means[2 * mode] += q;
+        self.means[2 * mode + 1] += p;
+        Ok(())
+    }
+
+    /// Apply a local symplectic matrix `s` (2k x 2k) acting on `modes` (k modes, in order).
+    pub fn apply_symplectic(&mut self, modes: &[usize], s: &[Vec<f64>]) -> Result<()> {
+        for &m in modes {
+            self.check_mode(m)?;
+        }
+        let idx: Vec<usize> = modes.iter().flat_map(|&m| [2 * m, 2 * m + 1]).collect();
+        if s.len() != idx.len() || s.iter().any(|r| r.len() != idx.len()) {
+            return Err(anyhow!(
+                "symplectic matrix must be {}x{}",
+                idx.len(),
+                idx.len()
+            ));
+        }
+
+        let local_means: Vec<f64> = idx.iter().map(|&i| self.means[i]).collect();
+        for (a, &i) in idx.iter().enumerate() {
+            self.means[i] = (0..idx.len()).map(|b| s[a][b] * local_means[b]).sum();
+        }
+
+        // Rows: V' = S V on the touched rows, then columns: V'' = V' Sᵀ.
+        let rows: Vec<Vec<f64>> = idx.iter().map(|&i| self.covariance[i].clone()).collect();
+        for (a, &i) in idx.iter().enumerate() {
+            for (col, v) in self.covariance[i].iter_mut().enumerate() {
+                *v = (0..idx.len()).map(|b| s[a][b] * rows[b][col]).sum();
+            }
+        }
+        for row in self.covariance.iter_mut() {
+            let local: Vec<f64> = idx.iter().map(|&i| row[i]).collect();
+            for (a, &i) in idx.iter().enumerate() {
+                row[i] = (0..idx.len()).map(|b| s[a][b] * local[b]).sum();
+            }
+        }
+        Ok(())
+    }
+
+    /// Phase rotation `a → e^{iφ} a`.
+    pub fn phase_shift(&mut self, mode: usize, phi: f64) -> Result<()> {
+        self.apply_symplectic(&[mode], &rotation(phi))
+    }
+
+    /// Single-mode squeezing `S(r·e^{iθ})`; `r > 0` with `θ = 0` squeezes the q quadrature.
+    pub fn squeeze(&mut self, mode: usize, r: f64, angle: f64) -> Result<()> {
+        let s = matmul(
+            &matmul(
+                &rotation(angle / 2.0),
+                &[vec![(-r).exp(), 0.0], vec![0.0, r.exp()]],
+            ),
+            &rotation(-angle / 2.0),
+        );
+        self.apply_symplectic(&[mode], &s)
+    }
+
+    /// Beam splitter `a → cos θ·a − e^{−iφ} sin θ·b`, `b → e^{iφ} sin θ·a + cos θ·b`.
+    /// `θ = π/4` is a balanced (50:50) splitter.
+    pub fn beam_splitter(
+        &mut self,
+        mode_a: usize,
+        mode_b: usize,
+        theta: f64,
+        phi: f64,
+    ) -> Result<()> {
+        if mode_a == mode_b {
+            return Err(anyhow!("beam splitter needs two distinct modes"));
+        }
+        let (c, s) = (theta.cos(), theta.sin());
+        let bs = vec![
+            vec![c, 0.0, -s, 0.0],
+            vec![0.0, c, 0.0, -s],
+            vec![s, 0.0, c, 0.0],
+            vec![0.0, s, 0.0, c],
+        ];
+        self.phase_shift(mode_b, -phi)?;
+        self.apply_symplectic(&[mode_a, mode_b], &bs)?;
+        self.phase_shift(mode_b, phi)
+    }
+
+    /// Mean photon number `⟨a†a⟩` of one mode.
+    pub fn mean_photon_number(&self, mode: usize) -> Result<f64> {
+        self.check_mode(mode)?;
+        let (q, p) = (2 * mode, 2 * mode + 1);
+        let second_moments = self.covariance[q][q]
+            + self.covariance[p][p]
+            + self.means[q].powi(2)
+            + self.means[p].powi(2);
+        Ok(second_moments / 2.0 - VACUUM_VARIANCE)
+    }
+
+    /// Purity `Tr(ρ²) = 1/√det(2V)`.
+    pub fn purity(&self) -> f64 {
+        let scaled: Vec<Vec<f64>> = self
+            .covariance
+            .iter()
+            .map(|row| row.iter().map(|v| 2.0 * v).collect())
+            .collect();
+        let det = determinant(&scaled);
+        if det <= 0.0 {
+            return 0.0;
+        }
+        1.0 / det.sqrt()
+    }
+
+    /// Reduced state of `modes` (in the given order), tracing out the rest.
+    pub fn reduced(&self, modes: &[usize]) -> Result<GaussianState> {
+        if let Some(m) = modes.iter().find(|&&m| m >= self.num_modes()) {
+            return Err(anyhow!("mode {} out of range", m));
+        }
+        let rows: Vec<usize> = modes.iter().flat_map(|&m| [2 * m, 2 * m + 1]).collect();
+        Ok(GaussianState {
+            means: rows.iter().map(|&r| self.means[r]).collect(),
+            covariance: rows
+                .iter()
+                .map(|&r| rows.iter().map(|&c| self.covariance[r][c]).collect())
+                .collect(),
+        })
+    }
+
+    /// Von Neumann entropy in bits, `Σ_k g(ν_k)` over the symplectic eigenvalues with
+    /// `g(ν) = (ν+½)log₂(ν+½) − (ν−½)log₂(ν−½)`.
+    pub fn entropy(&self) -> Result<f64> {
+        let nu = symplectic_eigenvalues(&self.covariance)
+            .ok_or_else(|| anyhow!("covariance matrix is not positive definite"))?;
+        let xlog = |x: f64| if x > 1e-12 { x * x.log2() } else { 0.0 };
+        Ok(nu
+            .iter()
+            .map(|n| xlog(n + VACUUM_VARIANCE) - xlog(n - VACUUM_VARIANCE))
+            .sum::<f64>()
+            .max(0.0))
+    }
+
+    /// Fock-basis density matrix `⟨m|ρ|n⟩` (`m, n < cutoff`) of a single-mode state, renormalized
+    /// to unit trace inside the cutoff. Computed from the position kernel
+    /// `⟨x+y/2|ρ|x−y/2⟩ = ∫W(x,p)e^{ipy}dp` projected onto Hermite functions by quadrature.
+    pub fn fock_density(&self, cutoff: usize) -> Result<DensityMatrix> {
+        if self.num_modes() != 1 {
+            return Err(anyhow!(
+                "Fock density needs a single-mode state, got {} modes",
+                self.num_modes()
+            ));
+        }
+        if cutoff == 0 {
+            return Err(anyhow!("Fock cutoff must be at least 1"));
+        }
+        let (a, b, c) = (
+            self.covariance[0][0],
+            self.covariance[0][1],
+            self.covariance[1][1],
+        );
+        let (mu_q, mu_p) = (self.means[0], self.means[1]);
+        // Conditional spread of p given x.
+        let s = c - b * b / a;
+        let extent = (mu_q.abs() + 8.0 * a.sqrt()).max((2.0 * cutoff as f64 + 1.0).sqrt() + 5.0);
+        let points = (2.0 * extent / 0.04).ceil() as usize + 1;
+        let dx = 2.0 * extent / (points - 1) as f64;
+        let xs: Vec<f64> = (0..points).map(|i| -extent + dx * i as f64).collect();
+        let psi: Vec<Vec<f64>> = xs.iter().map(|&x| hermite_functions(x, cutoff)).collect();
+        // Kernel K[i][j] = ⟨x_i|ρ|x_j⟩.
+        let kernel: Vec<Vec<Complex64>> = xs
+            .iter()
+            .map(|&x1| {
+                xs.iter()
+                    .map(|&x2| {
+                        let (x, y) = ((x1 + x2) / 2.0, x1 - x2);
+                        let marginal = (-(x - mu_q).powi(2) / (2.0 * a)).exp()
+                            / (2.0 * std::f64::consts::PI * a).sqrt();
+                        let mean_p = mu_p + b / a * (x - mu_q);
+                        Complex64::from_polar(marginal * (-0.5 * s * y * y).exp(), mean_p * y)
+                    })
+                    .collect()
+            })
+            .collect();
+        // (K Ψ)[i][n], then ρ_mn = Σ_i ψ_m(x_i) (K Ψ)[i][n] · dx².
+        let k_psi: Vec<Vec<Complex64>> = kernel
+            .iter()
+            .map(|row| {
+                (0..cutoff)
+                    .map(|n| row.iter().zip(&psi).map(|(k, p)| k * p[n]).sum())
+                    .collect()
+            })
+            .collect();
+        let mut rho: Vec<Vec<Complex64>> = (0..cutoff)
+            .map(|m| {
+                (0..cutoff)
+                    .map(|n| {
+                        psi.iter()
+                            .zip(&k_psi)
+                            .map(|(p, kp)| kp[n] * p[m])
+                            .sum::<Complex64>()
+                            * dx
+                            * dx
+                    })
+                    .collect()
+            })
+            .collect();
+        let trace: f64 = (0..cutoff).map(|i| rho[i][i].re).sum();
+        if trace <= f64::EPSILON {
+            return Err(anyhow!("state has no weight below Fock cutoff {}", cutoff));
+        }
+        rho.iter_mut().flatten().for_each(|v| *v /= trace);
+        Ok(DensityMatrix {
+            dims: vec![cutoff],
+            rho,
+        })
+    }
+
+    /// Classical additive noise: adds `variance` to both quadratures of `mode`.
+    pub fn add_noise(&mut self, mode: usize, variance: f64) -> Result<()> {
+        self.check_mode(mode)?;
+        if variance < 0.0 {
+            return Err(anyhow!(
+                "noise variance must be non-negative, got {}",
+                variance
+            ));
+        }
+        for i in [2 * mode, 2 * mode + 1] {
+            self.covariance[i][i] += variance;
+        }
+        Ok(())
+    }
+
+    /// Loss into a thermal bath with transmissivity `eta`:
+    /// `V → ηV + (1−η)(n̄+½)I` on `mode`, means scaled by `√η`.
+    pub fn attenuate(&mut self, mode: usize, eta: f64, bath_photons: f64) -> Result<()> {
+        self.check_mode(mode)?;
+        if !(0.0..=1.0).contains(&eta) {
+            return Err(anyhow!("transmissivity must be in [0, 1], got {}", eta));
+        }
+        let amplitude = eta.sqrt();
+        for i in [2 * mode, 2 * mode + 1] {
+            self.means[i] *= amplitude;
+            for row in self.covariance.iter_mut() {
+                row[i] *= amplitude;
+            }
+            self.covariance[i].iter_mut().for_each(|v| *v *= amplitude);
+            self.covariance[i][i] += (1.0 - eta) * (bath_photons + VACUUM_VARIANCE);
+        }
+        Ok(())
+    }
+
+    /// Phase diffusion: a random rotation `φ ~ N(0, variance)` on `mode`, moment-matched back
+    /// to a Gaussian state. Coherent amplitudes shrink by `e^{−σ²/2}`, the anisotropic
+    /// (squeezed) part of the covariance by `e^{−2σ²}`, and the lost displacement reappears as
+    /// phase-insensitive noise.
+    pub fn phase_diffusion(&mut self, mode: usize, variance: f64) -> Result<()> {
+        self.check_mode(mode)?;
+        if variance < 0.0 {
+            return Err(anyhow!(
+                "phase variance must be non-negative, got {}",
+                variance
+            ));
+        }
+        let (q, p) = (2 * mode, 2 * mode + 1);
+        let first = (-variance / 2.0).exp();
+        let second = (-2.0 * variance).exp();
+        let d = [self.means[q], self.means[p]];
+        let block = [
+            [self.covariance[q][q], self.covariance[q][p]],
+            [self.covariance[p][q], self.covariance[p][p]],
+        ];
+
+        // Cross-correlations with other modes only see E[R(φ)] = e^{−σ²/2}·I.
+        for i in [q, p] {
+            for row in self.covariance.iter_mut() {
+                row[i] *= first;
+            }
+            self.covariance[i].iter_mut().for_each(|v| *v *= first);
+        }
+
+        let isotropic = (block[0][0] + block[1][1]) / 2.0;
+        let displacement = (d[0] * d[0] + d[1] * d[1]) / 2.0;
+        for (a, row) in [q, p].into_iter().enumerate() {
+            for (b, col) in [q, p].into_iter().enumerate() {
+                let identity = if a == b { 1.0 } else { 0.0 };
+                let anisotropic = block[a][b] - isotropic * identity;
+                let outer = d[a] * d[b];
+                self.covariance[row][col] = isotropic * identity
+                    + second * anisotropic
+                    + displacement * identity
+                    + second * (outer - displacement * identity)
+                    - first * first * outer;
+            }
+        }
+        self.means[q] *= first;
+        self.means[p] *= first;
+        Ok(())
+    }
+
+    /// Uhlmann fidelity `F = (Tr√(√ρ σ √ρ))²`, using the closed form of Banchi, Braunstein and
+    /// Pirandola (PRL 115, 260501, 2015) for arbitrary multimode Gaussian states:
+    /// `√F = F_tot·exp(−¼ δᵀ(V₁+V₂)⁻¹δ)` with `F_tot` determined by the symplectic spectrum of
+    /// `V_aux = Ωᵀ(V₁+V₂)⁻¹(Ω/4 + V₂ΩV₁)`.
+    pub fn fidelity(&self, other: &GaussianState) -> Result<f64> {
+        self.validate()?;
+        other.validate()?;
+        if self.num_modes() != other.num_modes() {
+            return Err(anyhow!(
+                "fidelity needs equal mode counts, got {} and {}",
+                self.num_modes(),
+                other.num_modes()
+            ));
+        }
+        if self == other {
+            return Ok(1.0);
+        }
+        let dim = self.means.len();
+        let mut omega = vec![vec![0.0; dim]; dim];
+        for k in (0..dim).step_by(2) {
+            omega[k][k + 1] = 1.0;
+            omega[k + 1][k] = -1.0;
+        }
+        let sum: Vec<Vec<f64>> = self
+            .covariance
+            .iter()
+            .zip(&other.covariance)
+            .map(|(a, b)| a.iter().zip(b).map(|(x, y)| x + y).collect())
+            .collect();
+        let sum_inv =
+            inverse(&sum).ok_or_else(|| anyhow!("singular covariance sum in fidelity"))?;
+        let mut inner = matmul(&matmul(&other.covariance, &omega), &self.covariance);
+        for (row, omega_row) in inner.iter_mut().zip(&omega) {
+            for (v, o) in row.iter_mut().zip(omega_row) {
+                *v += o / 4.0;
+            }
+        }
+        let omega_t: Vec<Vec<f64>> = omega
+            .iter()
+            .map(|r| r.iter().map(|v| -v).collect())
+            .collect();
+        let raw = matmul(&matmul(&omega_t, &sum_inv), &inner);
+        // Symmetric in exact arithmetic; remove rounding asymmetry before the eigensolve.
+        let v_aux: Vec<Vec<f64>> = (0..dim)
+            .map(|i| (0..dim).map(|j| (raw[i][j] + raw[j][i]) / 2.0).collect())
+            .collect();
+        let nu = symplectic_eigenvalues(&v_aux)
+            .ok_or_else(|| anyhow!("auxiliary covariance is not positive definite"))?;
+        // F_tot⁴ = det[2(√(I + (V_aux Ω)⁻²/4) + I)V_aux] / det(V₁+V₂), evaluated on the spectrum.
+        // Pure inputs give ν = ½ exactly; snap rounding noise there before the square root
+        // amplifies it.
+        let spectral: f64 = nu
+            .iter()
+            .map(|n| {
+                let radicand = 1.0 - 1.0 / (4.0 * n * n);
+                let root = if radicand < 1e-12 {
+                    0.0
+                } else {
+                    radicand.sqrt()
+                };
+                (2.0 * (1.0 + root)).powi(2)
+            })
+            .product();
+        let f_tot4 = spectral * determinant(&v_aux) / determinant(&sum);
+        let delta: Vec<f64> = other
+            .means
+            .iter()
+            .zip(&self.means)
+            .map(|(b, a)| b - a)
+            .collect();
+        let exponent: f64 = (0..dim)
+            .map(|i| {
+                (0..dim)
+                    .map(|j| delta[i] * sum_inv[i][j] * delta[j])
+                    .sum::<f64>()
+            })
+            .sum();
+        Ok((f_tot4.max(0.0).sqrt() * (-exponent / 2.0).exp()).clamp(0.0, 1.0))
+    }
+
+    /// Homodyne measurement of `x_θ = q·cos θ + p·sin θ` on `mode` (`θ = 0` → q, `θ = π/2` → p).
+    /// Returns the sampled quadrature value; the other modes are conditioned on it.
+    pub fn measure_homodyne<R: Rng>(
+        &mut self,
+        mode: usize,
+        angle: f64,
+        rng: &mut R,
+    ) -> Result<f64> {
+        self.phase_shift(mode, -angle)?;
+        let i = 2 * mode;
+        let variance = self.covariance[i][i];
+        let mean = self.means[i];
+        let outcome = mean + variance.max(0.0).sqrt() * standard_normal(rng);
+
+        if variance > f64::EPSILON {
+            let column: Vec<f64> = self.covariance.iter().map(|row| row[i]).collect();
+            let shift = (outcome - mean) / variance;
+            for (j, row) in self.covariance.iter_mut().enumerate() {
+                self.means[j] += column[j] * shift;
+                for (l, v) in row.iter_mut().enumerate() {
+                    *v -= column[j] * column[l] / variance;
+                }
+            }
+        }
+        self.reset_mode(mode);
+        Ok(outcome)
+    }
+
+    /// Heterodyne (double-homodyne) measurement of `mode`, returning `(q, p)` sampled from
+    /// `N(μ, V + ½·I)`; the other modes are conditioned on the outcome.
+    pub fn measure_heterodyne<R: Rng>(&mut self, mode: usize, rng: &mut R) -> Result<(f64, f64)> {
+        self.check_mode(mode)?;
+        let (iq, ip) = (2 * mode, 2 * mode + 1);
+        let m = [
+            [
+                self.covariance[iq][iq] + VACUUM_VARIANCE,
+                self.covariance[iq][ip],
+            ],
+            [
+                self.covariance[ip][iq],
+                self.covariance[ip][ip] + VACUUM_VARIANCE,
+            ],
+        ];
+
+        // Cholesky factor of the 2x2 outcome covariance.
+        let l00 = m[0][0].sqrt();
+        let l10 = m[1][0] / l00;
+        let l11 = (m[1][1] - l10 * l10).max(0.0).sqrt();
+        let (z0, z1) = (standard_normal(rng), standard_normal(rng));
+        let outcome = (
+            self.means[iq] + l00 * z0,
+            self.means[ip] + l10 * z0 + l11 * z1,
+        );
+
+        let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+        let inv = [
+            [m[1][1] / det, -m[0][1] / det],
+            [-m[1][0] / det, m[0][0] / det],
+        ];
+        let residual = [outcome.0 - self.means[iq], outcome.1 - self.means[ip]];
+        let cross: Vec<[f64; 2]> = self
+            .covariance
+            .iter()
+            .map(|row| [row[iq], row[ip]])
+            .collect();
+        // gain = V_BA · M⁻¹ for every row.
+        let gain: Vec<[f64; 2]> = cross
+            .iter()
+            .map(|c| {
+                [
+                    c[0] * inv[0][0] + c[1] * inv[1][0],
+                    c[0] * inv[0][1] + c[1] * inv[1][1],
+                ]
+            })
+            .collect();
+        for (j, row) in self.covariance.iter_mut().enumerate() {
+            self.means[j] += gain[j][0] * residual[0] + gain[j][1] * residual[1];
+            for (l, v) in row.iter_mut().enumerate() {
+                *v -= gain[j][0] * cross[l][0] + gain[j][1] * cross[l][1];
+            }
+        }
+        self.reset_mode(mode);
+        Ok(outcome)
+    }
+
+    /// Replace `mode` with vacuum, removing any correlations with the other modes.
+    fn reset_mode(&mut self, mode: usize) {
+        for i in [2 * mode, 2 * mode + 1] {
+            self.means[i] = 0.0;
+            for row in self.covariance.iter_mut() {
+                row[i] = 0.0;
+            }
+            self.covariance[i].iter_mut().for_each(|v| *v = 0.0);
+            self.covariance[i][i] = VACUUM_VARIANCE;
+        }
+    }
+}
+
+/// Harmonic-oscillator eigenfunctions `ψ_n(x)`, `n < count`, in the ħ = 1 convention
+/// (`|ψ_0|² = e^{−x²}/√π`).
+pub(crate) fn hermite_functions(x: f64, count: usize) -> Vec<f64> {
+    let mut out = Vec::with_capacity(count);
+    let mut prev = 0.0;
+    let mut cur = std::f64::consts::PI.powf(-0.25) * (-x * x / 2.0).exp();
+    for n in 0..count {
+        out.push(cur);
+        let nf = n as f64;
+        let next = (2.0 / (nf + 1.0)).sqrt() * x * cur - (nf / (nf + 1.0)).sqrt() * prev;
+        prev = cur;
+        cur = next;
+    }
+    out
+}
+
+fn rotation(phi: f64) -> Vec<Vec<f64>> {
+    let (c, s) = (phi.cos(), phi.sin());
+    vec![vec![c, -s], vec![s, c]]
+}
+
+/// Standard normal sample (Box–Muller).
+pub(crate) fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    let u1: f64 = rng.gen_range(f64::MIN_POSITIVE..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::f64::consts::PI;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_entropy_of_two_mode_squeezed_vacuum() {
+        // Thermal state: S = (n+1)log₂(n+1) − n·log₂n.
+        let n: f64 = 0.7;
+        let expected = (n + 1.0) * (n + 1.0).log2() - n * n.log2();
+        assert!(close(
+            GaussianState::thermal(1, n).entropy().unwrap(),
+            expected
+        ));
+
+        // Opposite squeezing on a balanced splitter gives a two-mode squeezed vacuum: globally
+        // pure, each mode thermal with n = sinh²r.
+        let r: f64 = 0.5;
+        let mut tmsv = GaussianState::vacuum(2);
+        tmsv.squeeze(0, r, 0.0).unwrap();
+        tmsv.squeeze(1, -r, 0.0).unwrap();
+        tmsv.beam_splitter(0, 1, PI / 4.0, 0.0).unwrap();
+        assert!(tmsv.entropy().unwrap() < 1e-6);
+        let n = r.sinh().powi(2);
+        let expected = (n + 1.0) * (n + 1.0).log2() - n * n.log2();
+        let reduced = tmsv.reduced(&[1]).unwrap();
+        assert!((reduced.entropy().unwrap() - expected).abs() < 1e-6);
+        assert!(tmsv.reduced(&[2]).is_err());
+    }
+
+    #[test]
+    fn test_fock_density_of_thermal_and_coherent_states() {
+        let n: f64 = 0.4;
+        let thermal = GaussianState::thermal(1, n).fock_density(25).unwrap();
+        for k in 0..4 {
+            let expected = n.powi(k as i32) / (1.0 + n).powi(k as i32 + 1);
+            assert!((thermal.rho[k][k].re - expected).abs() < 1e-6);
+        }
+        assert!(thermal.rho[0][1].norm() < 1e-9);
+
+        // |α⟩ with α = (q + ip)/√2: ⟨m|ρ|n⟩ = e^{−|α|²} α^m ᾱ^n / √(m!n!).
+        let mut coherent = GaussianState::vacuum(1);
+        coherent.displace(0, 0.6, -0.4).unwrap();
+        let rho = coherent.fock_density(20).unwrap();
+        let alpha = num_complex::Complex64::new(0.6, -0.4) / 2f64.sqrt();
+        let expected = (-alpha.norm_sqr()).exp() * alpha * alpha.conj().powi(2) / 2f64.sqrt();
+        assert!((rho.rho[1][2] - expected).norm() < 1e-6);
+        assert!((rho.purity() - 1.0).abs() < 1e-6);
+        assert!(GaussianState::vacuum(2).fock_density(4).is_err());
+    }
+
+    #[test]
+    fn test_vacuum_and_thermal_photon_numbers() {
+        let vac = GaussianState::vacuum(2);
+        assert!(close(vac.mean_photon_number(1).unwrap(), 0.0));
+        assert!(close(vac.purity(), 1.0));
+
+        let thermal = GaussianState::thermal(1, 2.0);
+        assert!(close(thermal.mean_photon_number(0).unwrap(), 2.0));
+        assert!(close(thermal.purity(), 1.0 / 5.0));
+    }
+
+    #[test]
+    fn test_squeezing_preserves_purity() {
+        let mut state = GaussianState::vacuum(1);
+        let r = db_to_squeezing(10.0);
+        state.squeeze(0, r, 0.0).unwrap();
+        assert!(close(state.covariance[0][0], 0.05));
+        assert!(close(state.covariance[1][1], 5.0));
+        assert!(close(state.purity(), 1.0));
+        assert!(close(
+            state.mean_photon_number(0).unwrap(),
+            r.sinh().powi(2)
+        ));
+    }
+
+    #[test]
+    fn test_beam_splitter_moves_displacement() {
+        let mut state = GaussianState::vacuum(2);
+        state.displace(0, 2.0, 0.0).unwrap();
+        state.beam_splitter(0, 1, PI / 2.0, 0.0).unwrap();
+        assert!(close(state.means[0], 0.0));
+        assert!(close(state.means[2], 2.0));
+
+        let mut split = GaussianState::vacuum(2);
+        split.displace(0, 2.0, 0.0).unwrap();
+        split.beam_splitter(0, 1, PI / 4.0, 0.3).unwrap();
+        let n0 = split.mean_photon_number(0).unwrap();
+        let n1 = split.mean_photon_number(1).unwrap();
+        assert!(close(n0, 1.0) && close(n1, 1.0));
+    }
+
+    #[test]
+    fn test_phase_shift_rotates_quadratures() {
+        let mut state = GaussianState::vacuum(1);
+        state.displace(0, 1.0, 0.0).unwrap();
+        state.phase_shift(0, PI / 2.0).unwrap();
+        assert!(close(state.means[0], 0.0) && close(state.means[1], 1.0));
+    }
+
+    #[test]
+    fn test_homodyne_statistics_match_marginal() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let shots = 20_000;
+        let samples: Vec<f64> = (0..shots)
+            .map(|_| {
+                let mut state = GaussianState::vacuum(1);
+                state.displace(0, 1.5, 0.0).unwrap();
+                state.squeeze(0, db_to_squeezing(6.0), 0.0).unwrap();
+                state.measure_homodyne(0, 0.0, &mut rng).unwrap()
+            })
+            .collect();
+        let mean = samples.iter().sum::<f64>() / shots as f64;
+        let var = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / shots as f64;
+        let expected_var = VACUUM_VARIANCE * (-2.0 * db_to_squeezing(6.0)).exp();
+        assert!((mean - 1.5 * (-db_to_squeezing(6.0)).exp()).abs() < 0.02);
+        assert!((var - expected_var).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_homodyne_conditions_correlated_mode() {
+        // Two-mode squeezed vacuum from a squeezed pair on a balanced beam splitter:
+        // the q quadratures become correlated, so measuring one shifts the other.
+        let mut state = GaussianState::vacuum(2);
+        state.squeeze(0, 1.0, 0.0).unwrap();
+        state.squeeze(1, -1.0, 0.0).unwrap();
+        state.beam_splitter(0, 1, PI / 4.0, 0.0).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let x = state.measure_homodyne(0, 0.0, &mut rng).unwrap();
+        assert!(state.means[2].abs() > 0.0);
+        assert!(state.means[2].signum() == -x.signum());
+        // Measured mode is reset to vacuum and decorrelated.
+        assert!(close(state.covariance[0][0], VACUUM_VARIANCE));
+        assert!(close(state.covariance[0][2], 0.0));
+    }
+
+    #[test]
+    fn test_heterodyne_statistics() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let shots = 20_000;
+        let outcomes: Vec<(f64, f64)> = (0..shots)
+            .map(|_| {
+                let mut state = GaussianState::vacuum(1);
+                state.displace(0, 0.5, -1.0).unwrap();
+                state.measure_heterodyne(0, &mut rng).unwrap()
+            })
+            .collect();
+        let mq = outcomes.iter().map(|o| o.0).sum::<f64>() / shots as f64;
+        let mp = outcomes.iter().map(|o| o.1).sum::<f64>() / shots as f64;
+        let vq = outcomes.iter().map(|o| (o.0 - mq).powi(2)).sum::<f64>() / shots as f64;
+        assert!((mq - 0.5).abs() < 0.03 && (mp + 1.0).abs() < 0.03);
+        // Vacuum noise plus one unit of heterodyne added noise.
+        assert!((vq - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_noise_channels_reduce_purity() {
+        let mut state = GaussianState::vacuum(2);
+        state.squeeze(0, 0.5, 0.0).unwrap();
+        state
+            .beam_splitter(0, 1, std::f64::consts::FRAC_PI_4, 0.0)
+            .unwrap();
+        let mut noisy = state.clone();
+        noisy.add_noise(0, 0.1).unwrap();
+        assert!(noisy.purity() < state.purity());
+        assert!(noisy.validate().is_ok());
+
+        // Full loss into a hot bath leaves a thermal mode.
+        let mut lossy = GaussianState::vacuum(1);
+        lossy.displace(0, 2.0, 0.0).unwrap();
+        lossy.attenuate(0, 0.0, 3.0).unwrap();
+        assert!(close(lossy.mean_photon_number(0).unwrap(), 3.0));
+        assert!(close(lossy.means[0], 0.0));
+
+        let mut half = GaussianState::vacuum(1);
+        half.displace(0, 2.0, 0.0).unwrap();
+        half.attenuate(0, 0.25, 0.0).unwrap();
+        assert!(close(half.means[0], 1.0));
+        assert!(close(half.purity(), 1.0));
+    }
+
+    #[test]
+    fn test_phase_diffusion_preserves_energy() {
+        let mut state = GaussianState::vacuum(1);
+        state.squeeze(0, 0.4, 0.0).unwrap();
+        state.displace(0, 1.5, 0.5).unwrap();
+        let before = state.mean_photon_number(0).unwrap();
+        state.phase_diffusion(0, 0.3).unwrap();
+        assert!(close(state.mean_photon_number(0).unwrap(), before));
+        assert!(state.purity() < 1.0);
+        assert!(close(state.means[0], 1.5 * (-0.15f64).exp()));
+
+        // Complete dephasing leaves a phase-insensitive state.
+        state.phase_diffusion(0, 50.0).unwrap();
+        assert!(close(state.covariance[0][0], state.covariance[1][1]));
+        assert!(state.covariance[0][1].abs() < 1e-9);
+        assert!(close(thermal_occupation(193.4e12, 0.0), 0.0));
+        assert!(thermal_occupation(193.4e12, 300.0) < 1e-12);
+    }
+
+    #[test]
+    fn test_fidelity_closed_forms() {
+        // Coherent states: F = exp(−|α−β|²) with α = (q + ip)/√2.
+        let mut a = GaussianState::vacuum(1);
+        a.displace(0, 1.0, 0.5).unwrap();
+        let mut b = GaussianState::vacuum(1);
+        b.displace(0, -0.2, 0.1).unwrap();
+        let dist = ((1.2f64).powi(2) + (0.4f64).powi(2)) / 2.0;
+        assert!(close(a.fidelity(&b).unwrap(), (-dist).exp()));
+
+        // Squeezed vacuum vs vacuum: F = 1/cosh r.
+        let mut squeezed = GaussianState::vacuum(1);
+        squeezed.squeeze(0, 0.7, 0.0).unwrap();
+        let vacuum = GaussianState::vacuum(1);
+        assert!(close(
+            squeezed.fidelity(&vacuum).unwrap(),
+            1.0 / 0.7f64.cosh()
+        ));
+
+        // Thermal states: F = 1/(√((n+1)(m+1)) − √(nm))².
+        let (n, m) = (0.5f64, 2.0f64);
+        let expected = 1.0 / (((n + 1.0) * (m + 1.0)).sqrt() - (n * m).sqrt()).powi(2);
+        let f = GaussianState::thermal(1, n)
+            .fidelity(&GaussianState::thermal(1, m))
+            .unwrap();
+        assert!(close(f, expected));
+
+        // Symmetric, and invariant under a joint two-mode unitary.
+        let mut x = GaussianState::thermal(2, 0.3);
+        x.squeeze(0, 0.4, 0.2).unwrap();
+        let mut y = GaussianState::thermal(2, 0.1);
+        y.displace(1, 0.3, -0.2).unwrap();
+        let before = x.fidelity(&y).unwrap();
+        assert!(close(before, y.fidelity(&x).unwrap()));
+        for s in [&mut x, &mut y] {
+            s.beam_splitter(0, 1, 0.6, 0.3).unwrap();
+        }
+        assert!(close(x.fidelity(&y).unwrap(), before));
+        assert!(before > 0.0 && before < 1.0);
+    }
+
+    #[test]
+    fn test_invalid_modes_rejected() {
+        let mut state = GaussianState::vacuum(1);
+        assert!(state.phase_shift(3, 0.1).is_err());
+        assert!(state.beam_splitter(0, 0, 0.1, 0.0).is_err());
+        assert!(state.validate().is_ok());
+    }
+}
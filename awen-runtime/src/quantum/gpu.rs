@@ -0,0 +1,498 @@
+//! GPU offload for statevector gates and batched shot sampling (`gpu` feature).
+//!
+//! Kernels are WGSL compute shaders in `f64` (`wgpu::Features::SHADER_F64`, Vulkan), so gate
+//! application matches the CPU path to rounding. Shot sampling searches the CPU-built CDF for
+//! the CPU-drawn targets of `sampling_targets`, so a seed yields identical shots on either path.
+
+use super::statevector::{GateMatrix, StateVector};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use std::borrow::Cow;
+use wgpu::util::DeviceExt;
+
+/// Largest qudit dimension the gate kernel handles (its per-thread scratch size).
+pub const MAX_GPU_QUDIT_DIM: usize = 16;
+const WORKGROUP_SIZE: usize = 64;
+
+/// One thread per group of `dim` amplitudes sharing every digit but the target's.
+const GATE_SHADER: &str = r#"
+struct Params {
+    groups: u32,
+    dim: u32,
+    stride: u32,
+    has_control: u32,
+    control_stride: u32,
+    control_dim: u32,
+    pad0: u32,
+    pad1: u32,
+}
+
+@group(0) @binding(0) var<storage, read_write> amps: array<f64>;
+@group(0) @binding(1) var<storage, read> gate: array<f64>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let g = id.x;
+    if (g >= params.groups) {
+        return;
+    }
+    // Index of the group's target-digit-0 member.
+    let base = (g / params.stride) * params.stride * params.dim + g % params.stride;
+    if (params.has_control == 1u && (base / params.control_stride) % params.control_dim != 1u) {
+        return;
+    }
+    var local: array<vec2<f64>, 16>;
+    for (var k = 0u; k < params.dim; k++) {
+        let i = 2u * (base + k * params.stride);
+        local[k] = vec2<f64>(amps[i], amps[i + 1u]);
+    }
+    for (var row = 0u; row < params.dim; row++) {
+        var re = f64(0.0);
+        var im = f64(0.0);
+        for (var k = 0u; k < params.dim; k++) {
+            let m = 2u * (row * params.dim + k);
+            let a = local[k];
+            re = re + (gate[m] * a.x - gate[m + 1u] * a.y);
+            im = im + (gate[m] * a.y + gate[m + 1u] * a.x);
+        }
+        let i = 2u * (base + row * params.stride);
+        amps[i] = re;
+        amps[i + 1u] = im;
+    }
+}
+"#;
+
+/// One thread per shot: the first CDF index exceeding the shot's target.
+const SAMPLE_SHADER: &str = r#"
+struct Params {
+    len: u32,
+    shots: u32,
+    pad0: u32,
+    pad1: u32,
+}
+
+@group(0) @binding(0) var<storage, read> cdf: array<f64>;
+@group(0) @binding(1) var<storage, read> targets: array<f64>;
+@group(0) @binding(2) var<storage, read_write> outcomes: array<u32>;
+@group(0) @binding(3) var<uniform> params: Params;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let s = id.x;
+    if (s >= params.shots) {
+        return;
+    }
+    let t = targets[s];
+    var lo = 0u;
+    var hi = params.len;
+    while (lo < hi) {
+        let mid = (lo + hi) / 2u;
+        if (cdf[mid] <= t) {
+            lo = mid + 1u;
+        } else {
+            hi = mid;
+        }
+    }
+    outcomes[s] = min(lo, params.len - 1u);
+}
+"#;
+
+/// A GPU device with the statevector kernels compiled. Share it between backends with `Arc`.
+pub struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    gate_pipeline: wgpu::ComputePipeline,
+    sample_pipeline: wgpu::ComputePipeline,
+    adapter_name: String,
+}
+
+impl GpuContext {
+    /// Open the first adapter with 64-bit float shaders; fails when there is none.
+    pub fn new() -> Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .map_err(|e| anyhow!("no GPU adapter: {}", e))?;
+        let adapter_name = adapter.get_info().name;
+        if !adapter.features().contains(wgpu::Features::SHADER_F64) {
+            return Err(anyhow!(
+                "GPU adapter '{}' does not support 64-bit float shaders",
+                adapter_name
+            ));
+        }
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("awen-gpu"),
+            required_features: wgpu::Features::SHADER_F64,
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .map_err(|e| anyhow!("GPU device request failed: {}", e))?;
+
+        let pipeline = |label: &str, source: &str| {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(Cow::Owned(source.to_string())),
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: None,
+                module: &module,
+                entry_point: Some("main"),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let gate_pipeline = pipeline("awen-gate", GATE_SHADER);
+        let sample_pipeline = pipeline("awen-sample", SAMPLE_SHADER);
+        Ok(GpuContext {
+            device,
+            queue,
+            gate_pipeline,
+            sample_pipeline,
+            adapter_name,
+        })
+    }
+
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// GPU counterpart of `StateVector::apply_controlled`.
+    pub fn apply_controlled(
+        &self,
+        sv: &mut StateVector,
+        control: Option<usize>,
+        target: usize,
+        matrix: &GateMatrix,
+    ) -> Result<()> {
+        let n = sv.num_qudits();
+        if target >= n {
+            return Err(anyhow!("qudit {} out of range ({} qudits)", target, n));
+        }
+        if let Some(c) = control {
+            if c >= n {
+                return Err(anyhow!("qudit {} out of range ({} qudits)", c, n));
+            }
+            if c == target {
+                return Err(anyhow!("control and target must differ"));
+            }
+        }
+        let d = sv.dims[target];
+        if matrix.len() != d || matrix.iter().any(|r| r.len() != d) {
+            return Err(anyhow!(
+                "gate matrix must be {}x{} for qudit {}",
+                d,
+                d,
+                target
+            ));
+        }
+        if d > MAX_GPU_QUDIT_DIM {
+            return Err(anyhow!(
+                "GPU gates support qudits up to d={}, got {}",
+                MAX_GPU_QUDIT_DIM,
+                d
+            ));
+        }
+
+        let stride: usize = sv.dims[target + 1..].iter().product();
+        let groups = sv.amplitudes.len() / d;
+        let (has_control, control_stride, control_dim) = match control {
+            Some(c) => (1, sv.dims[c + 1..].iter().product(), sv.dims[c]),
+            None => (0, 1, 1),
+        };
+        let params = [
+            groups,
+            d,
+            stride,
+            has_control,
+            control_stride,
+            control_dim,
+            0,
+            0,
+        ]
+        .iter()
+        .map(|&v| u32::try_from(v).map_err(|_| anyhow!("statevector too large for the GPU")))
+        .collect::<Result<Vec<u32>>>()?;
+
+        let amps = self.buffer(
+            "awen-amplitudes",
+            &complex_bytes(&sv.amplitudes),
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        );
+        let flat: Vec<Complex64> = matrix.iter().flatten().copied().collect();
+        let gate = self.buffer(
+            "awen-gate-matrix",
+            &complex_bytes(&flat),
+            wgpu::BufferUsages::STORAGE,
+        );
+        let uniform = self.buffer(
+            "awen-gate-params",
+            &u32_bytes(&params),
+            wgpu::BufferUsages::UNIFORM,
+        );
+        let bytes = self.run(
+            &self.gate_pipeline,
+            &[&amps, &gate, &uniform],
+            groups,
+            &amps,
+        )?;
+        for (amp, chunk) in sv.amplitudes.iter_mut().zip(bytes.chunks_exact(16)) {
+            *amp = Complex64::new(f64_at(chunk, 0), f64_at(chunk, 8));
+        }
+        Ok(())
+    }
+
+    /// Inverse-CDF lookup of every target (`partition_point(c <= t)`, clamped to the last index).
+    pub fn sample_indices(&self, cdf: &[f64], targets: &[f64]) -> Result<Vec<usize>> {
+        if cdf.is_empty() {
+            return Err(anyhow!("cannot sample an empty distribution"));
+        }
+        if targets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let params = [cdf.len(), targets.len(), 0, 0]
+            .iter()
+            .map(|&v| u32::try_from(v).map_err(|_| anyhow!("distribution too large for the GPU")))
+            .collect::<Result<Vec<u32>>>()?;
+        let cdf_buffer = self.buffer("awen-cdf", &f64_bytes(cdf), wgpu::BufferUsages::STORAGE);
+        let target_buffer = self.buffer(
+            "awen-targets",
+            &f64_bytes(targets),
+            wgpu::BufferUsages::STORAGE,
+        );
+        let outcomes = self.buffer(
+            "awen-outcomes",
+            &vec![0u8; 4 * targets.len()],
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        );
+        let uniform = self.buffer(
+            "awen-sample-params",
+            &u32_bytes(&params),
+            wgpu::BufferUsages::UNIFORM,
+        );
+        let bytes = self.run(
+            &self.sample_pipeline,
+            &[&cdf_buffer, &target_buffer, &outcomes, &uniform],
+            targets.len(),
+            &outcomes,
+        )?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as usize)
+            .collect())
+    }
+
+    fn buffer(&self, label: &str, contents: &[u8], usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage,
+            })
+    }
+
+    /// Dispatch `pipeline` over `invocations` threads with `bindings` as group 0, then read
+    /// `output` back.
+    fn run(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        bindings: &[&wgpu::Buffer],
+        invocations: usize,
+        output: &wgpu::Buffer,
+    ) -> Result<Vec<u8>> {
+        let workgroups = invocations.div_ceil(WORKGROUP_SIZE);
+        let max = self.device.limits().max_compute_workgroups_per_dimension as usize;
+        if workgroups > max {
+            return Err(anyhow!(
+                "{} workgroups exceed the device limit of {}",
+                workgroups,
+                max
+            ));
+        }
+        let entries: Vec<wgpu::BindGroupEntry> = bindings
+            .iter()
+            .enumerate()
+            .map(|(i, buffer)| wgpu::BindGroupEntry {
+                binding: i as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("awen-readback"),
+            size: output.size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(workgroups as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(output, 0, &staging, 0, output.size());
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        staging.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = sender.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| anyhow!("GPU poll failed: {}", e))?;
+        receiver
+            .recv()
+            .map_err(|_| anyhow!("GPU readback was dropped"))?
+            .map_err(|e| anyhow!("GPU readback failed: {}", e))?;
+        let bytes = staging
+            .get_mapped_range(..)
+            .map_err(|e| anyhow!("GPU readback failed: {}", e))?
+            .to_vec();
+        staging.unmap();
+        Ok(bytes)
+    }
+}
+
+fn f64_bytes(values: &[f64]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn complex_bytes(values: &[Complex64]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|c| [c.re, c.im])
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn f64_at(bytes: &[u8], offset: usize) -> f64 {
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(&bytes[offset..offset + 8]);
+    f64::from_le_bytes(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quantum::statevector;
+    use crate::quantum::{
+        sample_distribution, sampling_targets, BasisType, BellType, DVGate, MeasurementBasis,
+        MeasurementResult, PreparationKind, QuantumBackend, StatevectorSimulator,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    fn context() -> Option<GpuContext> {
+        match GpuContext::new() {
+            Ok(gpu) => Some(gpu),
+            Err(e) => {
+                eprintln!("skipping GPU comparison: {}", e);
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_shaders_validate_with_f64() {
+        use wgpu::naga;
+        for source in [GATE_SHADER, SAMPLE_SHADER] {
+            let module = naga::front::wgsl::parse_str(source).expect("WGSL parses");
+            naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::FLOAT64,
+            )
+            .validate(&module)
+            .expect("WGSL validates");
+        }
+    }
+
+    #[test]
+    fn test_gates_match_cpu() {
+        let Some(gpu) = context() else { return };
+        let dims = vec![2, 3, 2, 2];
+        let size: usize = dims.iter().product();
+        let amplitudes = (0..size)
+            .map(|i| Complex64::new((i as f64 * 0.37).sin(), (i as f64 * 0.11).cos()))
+            .collect();
+        let mut cpu = StateVector::from_amplitudes(dims, amplitudes).unwrap();
+        let mut gpu_sv = cpu.clone();
+        for (control, target, gate) in [
+            (None, 0, statevector::hadamard()),
+            (Some(0), 1, statevector::pauli_x(3)),
+            (None, 3, statevector::rx(0.4)),
+            (Some(2), 3, statevector::hadamard()),
+        ] {
+            cpu.apply_controlled(control, target, &gate).unwrap();
+            gpu.apply_controlled(&mut gpu_sv, control, target, &gate)
+                .unwrap();
+        }
+        for (a, b) in cpu.amplitudes.iter().zip(&gpu_sv.amplitudes) {
+            assert!((a - b).norm() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sampling_matches_cpu() {
+        let Some(gpu) = context() else { return };
+        let probs = [0.1, 0.0, 0.25, 0.4, 0.0, 0.25];
+        let cpu = sample_distribution(&probs, 5000, &mut StdRng::seed_from_u64(11));
+        let (cdf, targets) = sampling_targets(&probs, 5000, &mut StdRng::seed_from_u64(11));
+        assert_eq!(gpu.sample_indices(&cdf, &targets).unwrap(), cpu);
+    }
+
+    #[test]
+    fn test_backend_shots_match_cpu() {
+        let Some(gpu) = context() else { return };
+        let labels = vec!["a".to_string(), "b".to_string()];
+        let prep = PreparationKind::BellState {
+            entanglement_type: BellType::PsiPlus,
+        };
+        let basis = MeasurementBasis {
+            basis_type: BasisType::Computational,
+            mode_labels: labels.clone(),
+        };
+        let gate = DVGate::RX {
+            qudit: "a".to_string(),
+            theta: 0.7,
+        };
+        let mut records = Vec::new();
+        for mut backend in [
+            StatevectorSimulator::new(),
+            StatevectorSimulator::new().with_gpu(Arc::new(gpu)),
+        ] {
+            let mut state = backend.prepare(labels.clone(), &prep, 1).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+            records.push(backend.sample(&state, &basis, 3000, 21).unwrap());
+        }
+        let outcomes = |shots: &[Vec<MeasurementResult>]| {
+            shots
+                .iter()
+                .flatten()
+                .map(|r| match r {
+                    MeasurementResult::DiscreteOutcome(k) => *k,
+                    MeasurementResult::ContinuousValue(_) => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(outcomes(&records[0].shots), outcomes(&records[1].shots));
+    }
+}
@@ -0,0 +1,215 @@
+//! Small dense linear-algebra kernels shared by the simulator backends.
+
+use num_complex::Complex64;
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+const ONE: Complex64 = Complex64::new(1.0, 0.0);
+
+/// Real matrix product.
+pub(crate) fn matmul(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|j| row.iter().zip(b).map(|(x, brow)| x * brow[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// Determinant by Gaussian elimination with partial pivoting.
+pub(crate) fn determinant(matrix: &[Vec<f64>]) -> f64 {
+    let mut m = matrix.to_vec();
+    let n = m.len();
+    let mut det = 1.0;
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))
+            .unwrap_or(col);
+        if m[pivot][col].abs() < f64::EPSILON {
+            return 0.0;
+        }
+        if pivot != col {
+            m.swap(pivot, col);
+            det = -det;
+        }
+        det *= m[col][col];
+        let pivot_row = m[col].clone();
+        for row in m.iter_mut().skip(col + 1) {
+            let factor = row[col] / pivot_row[col];
+            for (v, p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                *v -= factor * p;
+            }
+        }
+    }
+    det
+}
+
+/// Lower-triangular `L` with `L·Lᵀ = a` for a symmetric positive semi-definite `a`. Columns
+/// with a vanishing pivot stay zero; `None` if `a` is not PSD.
+pub(crate) fn cholesky(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for j in 0..n {
+        let scale = a[j][j].abs().max(1.0);
+        let pivot = a[j][j] - l[j][..j].iter().map(|x| x * x).sum::<f64>();
+        if pivot < -1e-10 * scale {
+            return None;
+        }
+        if pivot <= 1e-14 * scale {
+            continue;
+        }
+        let pivot = pivot.sqrt();
+        l[j][j] = pivot;
+        for i in j + 1..n {
+            let dot: f64 = l[i][..j].iter().zip(&l[j][..j]).map(|(x, y)| x * y).sum();
+            l[i][j] = (a[i][j] - dot) / pivot;
+        }
+    }
+    Some(l)
+}
+
+/// Inverse by Gauss–Jordan elimination with partial pivoting; `None` if singular.
+pub(crate) fn inverse(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut m: Vec<Vec<f64>> = matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut r = row.clone();
+            r.extend((0..n).map(|j| if i == j { 1.0 } else { 0.0 }));
+            r
+        })
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+        if m[pivot][col].abs() < f64::EPSILON {
+            return None;
+        }
+        m.swap(pivot, col);
+        let p = m[col][col];
+        m[col].iter_mut().for_each(|v| *v /= p);
+        let pivot_row = m[col].clone();
+        for (r, row) in m.iter_mut().enumerate() {
+            if r == col {
+                continue;
+            }
+            let factor = row[col];
+            for (v, pv) in row.iter_mut().zip(&pivot_row) {
+                *v -= factor * pv;
+            }
+        }
+    }
+    Some(m.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+/// Symplectic eigenvalues `ν_k` (ascending, one per mode) of a real symmetric positive-definite
+/// `2n × 2n` matrix in `(q_0, p_0, q_1, p_1, ...)` ordering: the moduli of the eigenvalues of
+/// `iΩV`. `None` if the matrix is not positive definite.
+pub(crate) fn symplectic_eigenvalues(v: &[Vec<f64>]) -> Option<Vec<f64>> {
+    let dim = v.len();
+    let complex: Vec<Vec<Complex64>> = v
+        .iter()
+        .map(|r| r.iter().map(|x| Complex64::new(*x, 0.0)).collect())
+        .collect();
+    let (values, vectors) = hermitian_eigen(complex);
+    if values.iter().any(|l| *l <= 0.0) {
+        return None;
+    }
+    // √V = U diag(√λ) U†; √V (iΩ) √V is Hermitian with eigenvalues ±ν_k.
+    let sqrt_v: Vec<Vec<Complex64>> = (0..dim)
+        .map(|i| {
+            (0..dim)
+                .map(|j| {
+                    (0..dim)
+                        .map(|k| vectors[i][k] * values[k].sqrt() * vectors[j][k].conj())
+                        .sum()
+                })
+                .collect()
+        })
+        .collect();
+    let i_omega = |r: usize, c: usize| -> Complex64 {
+        match (r.is_multiple_of(2), c) {
+            (true, c) if c == r + 1 => Complex64::new(0.0, 1.0),
+            (false, c) if c + 1 == r => Complex64::new(0.0, -1.0),
+            _ => ZERO,
+        }
+    };
+    let left: Vec<Vec<Complex64>> = (0..dim)
+        .map(|i| {
+            (0..dim)
+                .map(|j| (0..dim).map(|k| sqrt_v[i][k] * i_omega(k, j)).sum())
+                .collect()
+        })
+        .collect();
+    let h: Vec<Vec<Complex64>> = (0..dim)
+        .map(|i| {
+            (0..dim)
+                .map(|j| (0..dim).map(|k| left[i][k] * sqrt_v[k][j]).sum())
+                .collect()
+        })
+        .collect();
+    let (spectrum, _) = hermitian_eigen(h);
+    let mut nu: Vec<f64> = spectrum.into_iter().take(dim / 2).collect();
+    nu.sort_by(f64::total_cmp);
+    Some(nu)
+}
+
+/// Cyclic Jacobi eigendecomposition of a Hermitian matrix. Returns eigenvalues in descending
+/// order and the matching eigenvectors as columns.
+pub(crate) fn hermitian_eigen(mut a: Vec<Vec<Complex64>>) -> (Vec<f64>, Vec<Vec<Complex64>>) {
+    let n = a.len();
+    let mut v: Vec<Vec<Complex64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { ONE } else { ZERO }).collect())
+        .collect();
+    let scale: f64 = a
+        .iter()
+        .flat_map(|r| r.iter())
+        .map(|x| x.norm_sqr())
+        .sum::<f64>()
+        .max(f64::MIN_POSITIVE);
+    for _ in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j].norm_sqr())
+            .sum();
+        if off <= 1e-30 * scale {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let mag = a[p][q].norm();
+                if mag <= 1e-300 {
+                    continue;
+                }
+                // Rotate the phase out of a[p][q], then apply a real Jacobi rotation.
+                let phase = (a[p][q] / mag).conj();
+                let theta = 0.5 * (2.0 * mag).atan2(a[q][q].re - a[p][p].re);
+                let (c, s) = (theta.cos(), theta.sin());
+                let w = [
+                    [Complex64::new(c, 0.0), Complex64::new(s, 0.0)],
+                    [phase * -s, phase * c],
+                ];
+                for row in a.iter_mut().chain(v.iter_mut()) {
+                    let (xp, xq) = (row[p], row[q]);
+                    row[p] = xp * w[0][0] + xq * w[1][0];
+                    row[q] = xp * w[0][1] + xq * w[1][1];
+                }
+                let (head, tail) = a.split_at_mut(q);
+                for (xp, xq) in head[p].iter_mut().zip(tail[0].iter_mut()) {
+                    let (vp, vq) = (*xp, *xq);
+                    *xp = w[0][0].conj() * vp + w[1][0].conj() * vq;
+                    *xq = w[0][1].conj() * vp + w[1][1].conj() * vq;
+                }
+                a[p][q] = ZERO;
+                a[q][p] = ZERO;
+            }
+        }
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|x, y| a[*y][*y].re.total_cmp(&a[*x][*x].re));
+    let eigenvalues = order.iter().map(|&i| a[i][i].re).collect();
+    let vectors = (0..n)
+        .map(|row| order.iter().map(|&col| v[row][col]).collect())
+        .collect();
+    (eigenvalues, vectors)
+}
@@ -0,0 +1,725 @@
+//! Matrix-product-state (MPS) representation for wide, shallow DV circuits.
+//!
+//! Each site tensor `A[l, s, r]` carries a left bond, a physical index and a right bond. The
+//! state is kept in mixed-canonical form around `center`, so the singular values discarded
+//! when a two-site gate is truncated to the bond-dimension limit are exactly the squared-norm
+//! error of that step; they accumulate in `truncation_error`.
+
+use super::linalg::hermitian_eigen;
+use super::statevector::{GateMatrix, StateVector};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+const ONE: Complex64 = Complex64::new(1.0, 0.0);
+/// Singular values with `σ² / Σσ²` below this are dropped even without a bond limit.
+const RANK_TOLERANCE: f64 = 1e-14;
+/// Largest register `to_statevector` will expand.
+const MAX_DENSE_AMPLITUDES: usize = 1 << 24;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiteTensor {
+    pub left: usize,
+    pub phys: usize,
+    pub right: usize,
+    /// Row-major `[left][phys][right]`.
+    pub data: Vec<Complex64>,
+}
+
+impl SiteTensor {
+    fn index(&self, l: usize, s: usize, r: usize) -> usize {
+        (l * self.phys + s) * self.right + r
+    }
+
+    fn get(&self, l: usize, s: usize, r: usize) -> Complex64 {
+        self.data[self.index(l, s, r)]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatrixProductState {
+    pub dims: Vec<usize>,
+    pub tensors: Vec<SiteTensor>,
+    /// Orthogonality center: sites left of it are left-canonical, sites right of it are
+    /// right-canonical.
+    pub center: usize,
+    /// Accumulated discarded weight `Σ σ²_dropped` from bond truncations.
+    pub truncation_error: f64,
+}
+
+impl MatrixProductState {
+    /// Product state from per-qudit amplitudes (each normalized independently).
+    pub fn product(qudits: &[Vec<Complex64>]) -> Result<Self> {
+        if qudits.is_empty() {
+            return Err(anyhow!("MPS needs at least one qudit"));
+        }
+        let mut tensors = Vec::with_capacity(qudits.len());
+        for (q, amps) in qudits.iter().enumerate() {
+            let norm = amps.iter().map(|a| a.norm_sqr()).sum::<f64>().sqrt();
+            if amps.is_empty() || norm < 1e-12 {
+                return Err(anyhow!("qudit {} has zero amplitude vector", q));
+            }
+            tensors.push(SiteTensor {
+                left: 1,
+                phys: amps.len(),
+                right: 1,
+                data: amps.iter().map(|a| a / norm).collect(),
+            });
+        }
+        Ok(Self {
+            dims: qudits.iter().map(Vec::len).collect(),
+            tensors,
+            center: 0,
+            truncation_error: 0.0,
+        })
+    }
+
+    /// Decompose a dense statevector by successive SVDs, truncating each bond to `max_bond`.
+    pub fn from_statevector(sv: &StateVector, max_bond: usize) -> Result<Self> {
+        let n = sv.num_qudits();
+        if n == 0 {
+            return Err(anyhow!("MPS needs at least one qudit"));
+        }
+        let mut tensors = Vec::with_capacity(n);
+        let mut truncation_error = 0.0;
+        // `rest` holds the not-yet-decomposed tensor as a (bond·d_q) × remaining matrix.
+        let mut rest = sv.amplitudes.clone();
+        let mut left = 1;
+        let mut remaining: usize = sv.dims.iter().product();
+        for &d in sv.dims.iter().take(n - 1) {
+            remaining /= d;
+            let split = svd(&rest, left * d, remaining, max_bond);
+            truncation_error += split.discarded;
+            let k = split.singular_values.len();
+            tensors.push(SiteTensor {
+                left,
+                phys: d,
+                right: k,
+                data: split.u,
+            });
+            rest = scale_rows(&split.vh, &split.singular_values, remaining);
+            left = k;
+        }
+        let d = sv.dims[n - 1];
+        tensors.push(SiteTensor {
+            left,
+            phys: d,
+            right: 1,
+            data: rest,
+        });
+        Ok(Self {
+            dims: sv.dims.clone(),
+            tensors,
+            center: n - 1,
+            truncation_error,
+        })
+    }
+
+    pub fn num_qudits(&self) -> usize {
+        self.dims.len()
+    }
+
+    pub fn max_bond_dimension(&self) -> usize {
+        self.tensors.iter().map(|t| t.right).max().unwrap_or(1)
+    }
+
+    fn check_site(&self, q: usize) -> Result<()> {
+        if q >= self.num_qudits() {
+            return Err(anyhow!("qudit {} out of range", q));
+        }
+        Ok(())
+    }
+
+    /// Shift the orthogonality center to `site` without truncation.
+    pub fn move_center(&mut self, site: usize) -> Result<()> {
+        self.check_site(site)?;
+        while self.center < site {
+            let i = self.center;
+            let a = &self.tensors[i];
+            let split = svd(&a.data, a.left * a.phys, a.right, usize::MAX);
+            let k = split.singular_values.len();
+            let carry = scale_rows(&split.vh, &split.singular_values, a.right);
+            let (left, phys) = (a.left, a.phys);
+            self.tensors[i] = SiteTensor {
+                left,
+                phys,
+                right: k,
+                data: split.u,
+            };
+            let next = &self.tensors[i + 1];
+            let mut data = vec![ZERO; k * next.phys * next.right];
+            for kk in 0..k {
+                for m in 0..next.left {
+                    let c = carry[kk * next.left + m];
+                    if c == ZERO {
+                        continue;
+                    }
+                    for s in 0..next.phys {
+                        for r in 0..next.right {
+                            data[(kk * next.phys + s) * next.right + r] += c * next.get(m, s, r);
+                        }
+                    }
+                }
+            }
+            self.tensors[i + 1] = SiteTensor {
+                left: k,
+                phys: next.phys,
+                right: next.right,
+                data,
+            };
+            self.center += 1;
+        }
+        while self.center > site {
+            let i = self.center;
+            let a = &self.tensors[i];
+            let split = svd(&a.data, a.left, a.phys * a.right, usize::MAX);
+            let k = split.singular_values.len();
+            // carry = U·diag(σ), a left × k matrix.
+            let carry: Vec<Complex64> = (0..a.left)
+                .flat_map(|m| {
+                    let u = &split.u;
+                    let s = &split.singular_values;
+                    (0..k).map(move |kk| u[m * k + kk] * s[kk])
+                })
+                .collect();
+            let (phys, right, old_left) = (a.phys, a.right, a.left);
+            self.tensors[i] = SiteTensor {
+                left: k,
+                phys,
+                right,
+                data: split.vh,
+            };
+            let prev = &self.tensors[i - 1];
+            let mut data = vec![ZERO; prev.left * prev.phys * k];
+            for l in 0..prev.left {
+                for s in 0..prev.phys {
+                    for m in 0..old_left {
+                        let p = prev.get(l, s, m);
+                        if p == ZERO {
+                            continue;
+                        }
+                        for kk in 0..k {
+                            data[(l * prev.phys + s) * k + kk] += p * carry[m * k + kk];
+                        }
+                    }
+                }
+            }
+            self.tensors[i - 1] = SiteTensor {
+                left: prev.left,
+                phys: prev.phys,
+                right: k,
+                data,
+            };
+            self.center -= 1;
+        }
+        Ok(())
+    }
+
+    /// Apply a single-qudit operator. Does not change bond dimensions.
+    pub fn apply_single(&mut self, q: usize, matrix: &GateMatrix) -> Result<()> {
+        self.check_site(q)?;
+        let t = &mut self.tensors[q];
+        if matrix.len() != t.phys || matrix.iter().any(|r| r.len() != t.phys) {
+            return Err(anyhow!(
+                "gate must be {}x{} for qudit {}",
+                t.phys,
+                t.phys,
+                q
+            ));
+        }
+        let mut data = vec![ZERO; t.data.len()];
+        for l in 0..t.left {
+            for (s_out, row) in matrix.iter().enumerate() {
+                for (s_in, g) in row.iter().enumerate() {
+                    if *g == ZERO {
+                        continue;
+                    }
+                    for r in 0..t.right {
+                        data[t.index(l, s_out, r)] += g * t.get(l, s_in, r);
+                    }
+                }
+            }
+        }
+        t.data = data;
+        Ok(())
+    }
+
+    /// Apply a two-qudit gate (a `(d_q·d_{q+1})`-square matrix, first qudit most significant) to
+    /// adjacent sites `q, q+1`, keeping at most `max_bond` singular values. Returns the weight
+    /// discarded by this step.
+    pub fn apply_adjacent(&mut self, q: usize, gate: &GateMatrix, max_bond: usize) -> Result<f64> {
+        self.check_site(q + 1)?;
+        let (d1, d2) = (self.dims[q], self.dims[q + 1]);
+        let dd = d1 * d2;
+        if gate.len() != dd || gate.iter().any(|r| r.len() != dd) {
+            return Err(anyhow!("two-site gate must be {}x{}", dd, dd));
+        }
+        self.move_center(q)?;
+        let (a, b) = (&self.tensors[q], &self.tensors[q + 1]);
+        let (l, r) = (a.left, b.right);
+        // θ[l, s1, s2, r] = Σ_m A[l,s1,m] B[m,s2,r], stored as (l·d1) × (d2·r).
+        let mut theta = vec![ZERO; l * dd * r];
+        for ll in 0..l {
+            for s1 in 0..d1 {
+                for m in 0..a.right {
+                    let av = a.get(ll, s1, m);
+                    if av == ZERO {
+                        continue;
+                    }
+                    for s2 in 0..d2 {
+                        for rr in 0..r {
+                            theta[((ll * d1 + s1) * d2 + s2) * r + rr] += av * b.get(m, s2, rr);
+                        }
+                    }
+                }
+            }
+        }
+        let mut gated = vec![ZERO; theta.len()];
+        for ll in 0..l {
+            for (out, row) in gate.iter().enumerate() {
+                for (inp, g) in row.iter().enumerate() {
+                    if *g == ZERO {
+                        continue;
+                    }
+                    for rr in 0..r {
+                        gated[(ll * dd + out) * r + rr] += g * theta[(ll * dd + inp) * r + rr];
+                    }
+                }
+            }
+        }
+        let split = svd(&gated, l * d1, d2 * r, max_bond.max(1));
+        let k = split.singular_values.len();
+        self.tensors[q] = SiteTensor {
+            left: l,
+            phys: d1,
+            right: k,
+            data: split.u,
+        };
+        self.tensors[q + 1] = SiteTensor {
+            left: k,
+            phys: d2,
+            right: r,
+            data: scale_rows(&split.vh, &split.singular_values, d2 * r),
+        };
+        self.center = q + 1;
+        self.truncation_error += split.discarded;
+        Ok(split.discarded)
+    }
+
+    /// Apply `matrix` to `target`, conditioned on `control` (when given) having basis
+    /// value 1, matching `StateVector::apply_controlled`. Distant sites are brought together
+    /// with a SWAP network. Returns the discarded weight.
+    pub fn apply_controlled(
+        &mut self,
+        control: Option<usize>,
+        target: usize,
+        matrix: &GateMatrix,
+        max_bond: usize,
+    ) -> Result<f64> {
+        let control = match control {
+            None => {
+                self.apply_single(target, matrix)?;
+                return Ok(0.0);
+            }
+            Some(c) => c,
+        };
+        self.check_site(control)?;
+        self.check_site(target)?;
+        if control == target {
+            return Err(anyhow!("control and target must differ"));
+        }
+        let (lo, hi) = (control.min(target), control.max(target));
+        let mut discarded = 0.0;
+        // Move `hi` down to `lo + 1`.
+        for site in (lo + 1..hi).rev() {
+            discarded += self.swap(site, max_bond)?;
+        }
+        let gate = controlled_gate(
+            self.dims[control],
+            self.dims[target],
+            matrix,
+            control < target,
+        )?;
+        discarded += self.apply_adjacent(lo, &gate, max_bond)?;
+        for site in lo + 1..hi {
+            discarded += self.swap(site, max_bond)?;
+        }
+        Ok(discarded)
+    }
+
+    /// Exchange sites `q` and `q+1`, which must have the same dimension.
+    fn swap(&mut self, q: usize, max_bond: usize) -> Result<f64> {
+        let (d1, d2) = (self.dims[q], self.dims[q + 1]);
+        if d1 != d2 {
+            return Err(anyhow!(
+                "SWAP between qudits of different dimension ({} and {}) is not supported",
+                d1,
+                d2
+            ));
+        }
+        let mut gate = vec![vec![ZERO; d1 * d1]; d1 * d1];
+        for a in 0..d1 {
+            for b in 0..d1 {
+                gate[b * d1 + a][a * d1 + b] = ONE;
+            }
+        }
+        self.apply_adjacent(q, &gate, max_bond)
+    }
+
+    /// Reduced density matrix of every site, in order.
+    pub fn reduced_densities(&self) -> Result<Vec<GateMatrix>> {
+        let mut work = self.clone();
+        work.move_center(0)?;
+        let mut out = Vec::with_capacity(self.num_qudits());
+        for q in 0..self.num_qudits() {
+            work.move_center(q)?;
+            let t = &work.tensors[q];
+            let mut rho = vec![vec![ZERO; t.phys]; t.phys];
+            for l in 0..t.left {
+                for r in 0..t.right {
+                    for (s, row) in rho.iter_mut().enumerate() {
+                        for (sp, v) in row.iter_mut().enumerate() {
+                            *v += t.get(l, s, r) * t.get(l, sp, r).conj();
+                        }
+                    }
+                }
+            }
+            out.push(rho);
+        }
+        Ok(out)
+    }
+
+    /// Projective measurement of qudit `q` in the computational basis; collapses the state.
+    pub fn measure<R: Rng>(&mut self, q: usize, rng: &mut R) -> Result<usize> {
+        self.move_center(q)?;
+        let t = &self.tensors[q];
+        let mut probs = vec![0.0; t.phys];
+        for l in 0..t.left {
+            for (s, p) in probs.iter_mut().enumerate() {
+                for r in 0..t.right {
+                    *p += t.get(l, s, r).norm_sqr();
+                }
+            }
+        }
+        let total: f64 = probs.iter().sum();
+        let mut u = rng.gen::<f64>() * total;
+        let mut outcome = probs.len() - 1;
+        for (i, p) in probs.iter().enumerate() {
+            if u < *p {
+                outcome = i;
+                break;
+            }
+            u -= p;
+        }
+        let p = probs[outcome];
+        if p < 1e-15 {
+            return Err(anyhow!(
+                "measurement outcome {} has zero probability",
+                outcome
+            ));
+        }
+        let scale = 1.0 / p.sqrt();
+        let t = &mut self.tensors[q];
+        for l in 0..t.left {
+            for s in 0..t.phys {
+                for r in 0..t.right {
+                    let idx = t.index(l, s, r);
+                    t.data[idx] = if s == outcome {
+                        t.data[idx] * scale
+                    } else {
+                        ZERO
+                    };
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// `⟨self|other⟩`, contracted site by site.
+    pub fn inner(&self, other: &MatrixProductState) -> Result<Complex64> {
+        if self.dims != other.dims {
+            return Err(anyhow!(
+                "dimension mismatch: {:?} vs {:?}",
+                self.dims,
+                other.dims
+            ));
+        }
+        // env[a][b]: a = bond of `self` (conjugated), b = bond of `other`.
+        let mut env = vec![vec![ONE]];
+        for (a, b) in self.tensors.iter().zip(&other.tensors) {
+            let mut next = vec![vec![ZERO; b.right]; a.right];
+            for (l1, row) in env.iter().enumerate() {
+                for (l2, e) in row.iter().enumerate() {
+                    if *e == ZERO {
+                        continue;
+                    }
+                    for s in 0..a.phys {
+                        for (r1, next_row) in next.iter_mut().enumerate() {
+                            let av = a.get(l1, s, r1).conj() * e;
+                            if av == ZERO {
+                                continue;
+                            }
+                            for (r2, v) in next_row.iter_mut().enumerate() {
+                                *v += av * b.get(l2, s, r2);
+                            }
+                        }
+                    }
+                }
+            }
+            env = next;
+        }
+        Ok(env[0][0])
+    }
+
+    pub fn norm_sqr(&self) -> f64 {
+        self.inner(self).map(|v| v.re).unwrap_or(0.0)
+    }
+
+    /// Dense amplitudes, first qudit most significant.
+    pub fn to_statevector(&self) -> Result<StateVector> {
+        let size: usize = self.dims.iter().product();
+        if size > MAX_DENSE_AMPLITUDES {
+            return Err(anyhow!(
+                "register of {} amplitudes is too large to expand densely",
+                size
+            ));
+        }
+        // psi[prefix][bond]
+        let mut psi = vec![ONE];
+        let mut prefix = 1;
+        for t in &self.tensors {
+            let mut next = vec![ZERO; prefix * t.phys * t.right];
+            for p in 0..prefix {
+                for m in 0..t.left {
+                    let v = psi[p * t.left + m];
+                    if v == ZERO {
+                        continue;
+                    }
+                    for s in 0..t.phys {
+                        for r in 0..t.right {
+                            next[(p * t.phys + s) * t.right + r] += v * t.get(m, s, r);
+                        }
+                    }
+                }
+            }
+            psi = next;
+            prefix *= t.phys;
+        }
+        StateVector::from_amplitudes(self.dims.clone(), psi)
+    }
+}
+
+/// Two-site matrix for a controlled operation on adjacent sites, ordered (control, target) when
+/// `control_first`, otherwise (target, control).
+fn controlled_gate(
+    dc: usize,
+    dt: usize,
+    matrix: &GateMatrix,
+    control_first: bool,
+) -> Result<GateMatrix> {
+    if matrix.len() != dt || matrix.iter().any(|r| r.len() != dt) {
+        return Err(anyhow!("gate must be {}x{}", dt, dt));
+    }
+    let dd = dc * dt;
+    let index = |c: usize, t: usize| {
+        if control_first {
+            c * dt + t
+        } else {
+            t * dc + c
+        }
+    };
+    let mut gate = vec![vec![ZERO; dd]; dd];
+    for c in 0..dc {
+        for t_out in 0..dt {
+            for t_in in 0..dt {
+                gate[index(c, t_out)][index(c, t_in)] = if c == 1 {
+                    matrix[t_out][t_in]
+                } else if t_out == t_in {
+                    ONE
+                } else {
+                    ZERO
+                };
+            }
+        }
+    }
+    Ok(gate)
+}
+
+/// `diag(σ)·Vh` for a `k × cols` matrix `vh`.
+fn scale_rows(vh: &[Complex64], sigma: &[f64], cols: usize) -> Vec<Complex64> {
+    vh.chunks(cols)
+        .zip(sigma)
+        .flat_map(|(row, s)| row.iter().map(move |v| v * *s))
+        .collect()
+}
+
+struct Svd {
+    /// rows × k
+    u: Vec<Complex64>,
+    singular_values: Vec<f64>,
+    /// k × cols
+    vh: Vec<Complex64>,
+    /// Fraction of the squared norm dropped by truncation.
+    discarded: f64,
+}
+
+/// Truncated SVD of a row-major `rows × cols` matrix via the Hermitian eigenproblem of the
+/// smaller Gram matrix. Kept singular values are rescaled so the norm is preserved.
+fn svd(m: &[Complex64], rows: usize, cols: usize, max_rank: usize) -> Svd {
+    let at = |i: usize, j: usize| m[i * cols + j];
+    let left_gram = rows <= cols;
+    let n = rows.min(cols);
+    let mut gram = vec![vec![ZERO; n]; n];
+    for (i, row) in gram.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = if left_gram {
+                (0..cols).map(|c| at(i, c) * at(j, c).conj()).sum()
+            } else {
+                (0..rows).map(|r| at(r, i).conj() * at(r, j)).sum()
+            };
+        }
+    }
+    let (eigenvalues, vectors) = hermitian_eigen(gram);
+    let total: f64 = eigenvalues.iter().map(|l| l.max(0.0)).sum();
+    let cutoff = RANK_TOLERANCE * total.max(f64::MIN_POSITIVE);
+    let k = eigenvalues
+        .iter()
+        .take_while(|l| **l > cutoff)
+        .count()
+        .clamp(1, max_rank.max(1).min(n));
+    let kept: f64 = eigenvalues[..k].iter().map(|l| l.max(0.0)).sum();
+    let discarded = if total > 0.0 {
+        ((total - kept) / total).max(0.0)
+    } else {
+        0.0
+    };
+    let renorm = if kept > 0.0 {
+        (total / kept).sqrt()
+    } else {
+        1.0
+    };
+    let sigma: Vec<f64> = eigenvalues[..k].iter().map(|l| l.max(0.0).sqrt()).collect();
+    let mut u = vec![ZERO; rows * k];
+    let mut vh = vec![ZERO; k * cols];
+    for kk in 0..k {
+        let s = sigma[kk];
+        if left_gram {
+            for i in 0..rows {
+                u[i * k + kk] = vectors[i][kk];
+            }
+            if s > 0.0 {
+                for j in 0..cols {
+                    let v: Complex64 = (0..rows).map(|i| vectors[i][kk].conj() * at(i, j)).sum();
+                    vh[kk * cols + j] = v / s;
+                }
+            }
+        } else {
+            for j in 0..cols {
+                vh[kk * cols + j] = vectors[j][kk].conj();
+            }
+            if s > 0.0 {
+                for i in 0..rows {
+                    let v: Complex64 = (0..cols).map(|j| at(i, j) * vectors[j][kk]).sum();
+                    u[i * k + kk] = v / s;
+                }
+            }
+        }
+    }
+    Svd {
+        u,
+        singular_values: sigma.into_iter().map(|s| s * renorm).collect(),
+        vh,
+        discarded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quantum::statevector::{hadamard, pauli_x};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn zeros(n: usize) -> MatrixProductState {
+        MatrixProductState::product(&vec![vec![ONE, ZERO]; n]).unwrap()
+    }
+
+    fn ghz(n: usize, max_bond: usize) -> MatrixProductState {
+        let mut mps = zeros(n);
+        mps.apply_single(0, &hadamard()).unwrap();
+        for q in 1..n {
+            mps.apply_controlled(Some(0), q, &pauli_x(2), max_bond)
+                .unwrap();
+        }
+        mps
+    }
+
+    #[test]
+    fn test_svd_reconstructs_matrix() {
+        let m: Vec<Complex64> = (0..12)
+            .map(|i| Complex64::new((i as f64).sin(), (i as f64 * 0.7).cos()))
+            .collect();
+        for (rows, cols) in [(3, 4), (4, 3)] {
+            let split = svd(&m, rows, cols, usize::MAX);
+            let k = split.singular_values.len();
+            let sv = scale_rows(&split.vh, &split.singular_values, cols);
+            for i in 0..rows {
+                for j in 0..cols {
+                    let v: Complex64 = (0..k)
+                        .map(|kk| split.u[i * k + kk] * sv[kk * cols + j])
+                        .sum();
+                    assert!((v - m[i * cols + j]).norm() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_dense_simulation() {
+        let mut mps = zeros(4);
+        let mut sv = StateVector::zero(vec![2; 4]);
+        mps.apply_single(1, &hadamard()).unwrap();
+        sv.hadamard(1).unwrap();
+        mps.apply_controlled(Some(1), 3, &pauli_x(2), 8).unwrap();
+        sv.cnot(1, 3).unwrap();
+        mps.apply_controlled(Some(3), 0, &pauli_x(2), 8).unwrap();
+        sv.cnot(3, 0).unwrap();
+        let dense = mps.to_statevector().unwrap();
+        assert!((dense.inner(&sv).unwrap().norm_sqr() - 1.0).abs() < 1e-9);
+        assert!(mps.truncation_error < 1e-12);
+        assert_eq!(mps.max_bond_dimension(), 2);
+
+        let roundtrip = MatrixProductState::from_statevector(&sv, 8).unwrap();
+        assert!((roundtrip.inner(&mps).unwrap().norm_sqr() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_wide_ghz_stays_bond_two() {
+        let mut mps = ghz(120, 4);
+        assert_eq!(mps.max_bond_dimension(), 2);
+        assert!((mps.norm_sqr() - 1.0).abs() < 1e-9);
+        let rho = mps.reduced_densities().unwrap();
+        assert!((rho[60][0][0].re - 0.5).abs() < 1e-9);
+
+        let mut rng = StdRng::seed_from_u64(9);
+        let first = mps.measure(0, &mut rng).unwrap();
+        for q in [1, 59, 119] {
+            assert_eq!(mps.measure(q, &mut rng).unwrap(), first);
+        }
+    }
+
+    #[test]
+    fn test_truncation_reports_discarded_weight() {
+        // Two Bell pairs crossing the middle bond need bond dimension 4 there.
+        let mut mps = zeros(4);
+        mps.apply_single(0, &hadamard()).unwrap();
+        mps.apply_single(1, &hadamard()).unwrap();
+        mps.apply_controlled(Some(0), 3, &pauli_x(2), 1).unwrap();
+        mps.apply_controlled(Some(1), 2, &pauli_x(2), 1).unwrap();
+        assert_eq!(mps.max_bond_dimension(), 1);
+        assert!(mps.truncation_error > 0.4);
+        assert!((mps.norm_sqr() - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,363 @@
+//! Discrete-variable statevector simulation.
+//!
+//! A [`StateVector`] holds the joint complex amplitudes of a register of qudits in mixed radix,
+//! with the first qudit most significant (`|q0 q1 ... q_{n-1}⟩`). Gates are applied in place;
+//! measurements sample from the Born-rule marginal and collapse the state.
+
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Square gate matrix acting on one qudit (`matrix[row][col]`).
+pub type GateMatrix = Vec<Vec<Complex64>>;
+
+const NORM_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateVector {
+    pub dims: Vec<usize>,
+    pub amplitudes: Vec<Complex64>,
+}
+
+impl StateVector {
+    /// `|0...0⟩` on qudits of the given dimensions.
+    pub fn zero(dims: Vec<usize>) -> Self {
+        let size = dims.iter().product();
+        let mut amplitudes = vec![Complex64::new(0.0, 0.0); size];
+        amplitudes[0] = Complex64::new(1.0, 0.0);
+        Self { dims, amplitudes }
+    }
+
+    /// Product state from per-qudit amplitude vectors (normalised individually).
+    pub fn product(qudits: &[Vec<Complex64>]) -> Result<Self> {
+        let mut amplitudes = vec![Complex64::new(1.0, 0.0)];
+        for q in qudits {
+            let norm = q.iter().map(|a| a.norm_sqr()).sum::<f64>().sqrt();
+            if norm < NORM_TOLERANCE {
+                return Err(anyhow!("qudit amplitudes must not all be zero"));
+            }
+            amplitudes = amplitudes
+                .iter()
+                .flat_map(|a| q.iter().map(move |b| a * b / norm))
+                .collect();
+        }
+        Ok(Self {
+            dims: qudits.iter().map(|q| q.len()).collect(),
+            amplitudes,
+        })
+    }
+
+    /// Joint state from a full amplitude vector, normalised.
+    pub fn from_amplitudes(dims: Vec<usize>, amplitudes: Vec<Complex64>) -> Result<Self> {
+        let size: usize = dims.iter().product();
+        if amplitudes.len() != size {
+            return Err(anyhow!(
+                "expected {} amplitudes for dimensions {:?}, got {}",
+                size,
+                dims,
+                amplitudes.len()
+            ));
+        }
+        let mut state = Self { dims, amplitudes };
+        state.normalize()?;
+        Ok(state)
+    }
+
+    pub fn num_qudits(&self) -> usize {
+        self.dims.len()
+    }
+
+    pub fn norm_sqr(&self) -> f64 {
+        self.amplitudes.iter().map(|a| a.norm_sqr()).sum()
+    }
+
+    pub fn normalize(&mut self) -> Result<()> {
+        let norm = self.norm_sqr().sqrt();
+        if norm < NORM_TOLERANCE {
+            return Err(anyhow!("state vector has zero norm"));
+        }
+        self.amplitudes.iter_mut().for_each(|a| *a /= norm);
+        Ok(())
+    }
+
+    /// Stride of qudit `q` in the flattened index.
+    fn stride(&self, q: usize) -> usize {
+        self.dims[q + 1..].iter().product()
+    }
+
+    /// Digit of qudit `q` in basis index `index`.
+    pub fn digit(&self, index: usize, q: usize) -> usize {
+        (index / self.stride(q)) % self.dims[q]
+    }
+
+    fn check_qudit(&self, q: usize) -> Result<()> {
+        if q >= self.num_qudits() {
+            return Err(anyhow!(
+                "qudit {} out of range ({} qudits)",
+                q,
+                self.num_qudits()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply a `d x d` unitary to qudit `target`.
+    pub fn apply_single(&mut self, target: usize, matrix: &GateMatrix) -> Result<()> {
+        self.apply_controlled(None, target, matrix)
+    }
+
+    /// Apply `matrix` to `target` on the branches where qudit `control` is `|1⟩` (or always when
+    /// `control` is `None`).
+    pub fn apply_controlled(
+        &mut self,
+        control: Option<usize>,
+        target: usize,
+        matrix: &GateMatrix,
+    ) -> Result<()> {
+        self.check_qudit(target)?;
+        if let Some(c) = control {
+            self.check_qudit(c)?;
+            if c == target {
+                return Err(anyhow!("control and target must differ"));
+            }
+        }
+        let d = self.dims[target];
+        if matrix.len() != d || matrix.iter().any(|r| r.len() != d) {
+            return Err(anyhow!(
+                "gate matrix must be {}x{} for qudit {}",
+                d,
+                d,
+                target
+            ));
+        }
+        let stride = self.stride(target);
+        let mut local = vec![Complex64::new(0.0, 0.0); d];
+        for base in 0..self.amplitudes.len() {
+            // Visit each group of `d` amplitudes once, from the index where the target digit is 0.
+            if self.digit(base, target) != 0 {
+                continue;
+            }
+            if let Some(c) = control {
+                if self.digit(base, c) != 1 {
+                    continue;
+                }
+            }
+            for (k, slot) in local.iter_mut().enumerate() {
+                *slot = self.amplitudes[base + k * stride];
+            }
+            for (row, coeffs) in matrix.iter().enumerate() {
+                self.amplitudes[base + row * stride] =
+                    coeffs.iter().zip(&local).map(|(m, a)| m * a).sum();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn hadamard(&mut self, q: usize) -> Result<()> {
+        self.apply_single(q, &hadamard())
+    }
+
+    pub fn rx(&mut self, q: usize, theta: f64) -> Result<()> {
+        self.apply_single(q, &rx(theta))
+    }
+
+    /// CNOT (generalised to qudits as a controlled cyclic shift `X`).
+    pub fn cnot(&mut self, control: usize, target: usize) -> Result<()> {
+        self.check_qudit(target)?;
+        let shift = pauli_x(self.dims[target]);
+        self.apply_controlled(Some(control), target, &shift)
+    }
+
+    /// Probability of each basis value of qudit `q`.
+    pub fn marginal(&self, q: usize) -> Result<Vec<f64>> {
+        self.check_qudit(q)?;
+        let mut probs = vec![0.0; self.dims[q]];
+        for (i, a) in self.amplitudes.iter().enumerate() {
+            probs[self.digit(i, q)] += a.norm_sqr();
+        }
+        Ok(probs)
+    }
+
+    /// Projective computational-basis measurement of qudit `q`; collapses the state.
+    pub fn measure<R: Rng>(&mut self, q: usize, rng: &mut R) -> Result<usize> {
+        let probs = self.marginal(q)?;
+        let outcome = sample_index(&probs, rng);
+        for i in 0..self.amplitudes.len() {
+            if self.digit(i, q) != outcome {
+                self.amplitudes[i] = Complex64::new(0.0, 0.0);
+            }
+        }
+        self.normalize()?;
+        Ok(outcome)
+    }
+
+    /// Bell-basis measurement of qubits `a` and `b`, returning 0..=3 for Φ+, Φ−, Ψ+, Ψ−
+    /// (the order of `BellType`). Leaves the pair in the corresponding computational state.
+    pub fn measure_bell<R: Rng>(&mut self, a: usize, b: usize, rng: &mut R) -> Result<usize> {
+        if self.dims.get(a) != Some(&2) || self.dims.get(b) != Some(&2) {
+            return Err(anyhow!("Bell measurement requires two qubits"));
+        }
+        self.cnot(a, b)?;
+        self.hadamard(a)?;
+        let sign = self.measure(a, rng)?;
+        let parity = self.measure(b, rng)?;
+        Ok(parity * 2 + sign)
+    }
+
+    /// `⟨self|other⟩`.
+    pub fn inner(&self, other: &StateVector) -> Result<Complex64> {
+        if self.dims != other.dims {
+            return Err(anyhow!(
+                "dimension mismatch: {:?} vs {:?}",
+                self.dims,
+                other.dims
+            ));
+        }
+        Ok(self
+            .amplitudes
+            .iter()
+            .zip(&other.amplitudes)
+            .map(|(a, b)| a.conj() * b)
+            .sum())
+    }
+
+    /// Reduced density matrix of qudit `q` (`rho[row][col]`).
+    pub fn reduced_density(&self, q: usize) -> Result<GateMatrix> {
+        self.check_qudit(q)?;
+        let d = self.dims[q];
+        let stride = self.stride(q);
+        let mut rho = vec![vec![Complex64::new(0.0, 0.0); d]; d];
+        for base in (0..self.amplitudes.len()).filter(|&i| self.digit(i, q) == 0) {
+            for (r, row) in rho.iter_mut().enumerate() {
+                for (c, v) in row.iter_mut().enumerate() {
+                    *v += self.amplitudes[base + r * stride]
+                        * self.amplitudes[base + c * stride].conj();
+                }
+            }
+        }
+        Ok(rho)
+    }
+
+    /// Purity `Tr(ρ_q²)` of the reduced state of qudit `q`; 1 for an unentangled qudit.
+    pub fn reduced_purity(&self, q: usize) -> Result<f64> {
+        let rho = self.reduced_density(q)?;
+        Ok(rho
+            .iter()
+            .flat_map(|row| row.iter())
+            .map(|v| v.norm_sqr())
+            .sum())
+    }
+}
+
+pub fn hadamard() -> GateMatrix {
+    let h = std::f64::consts::FRAC_1_SQRT_2;
+    vec![
+        vec![Complex64::new(h, 0.0), Complex64::new(h, 0.0)],
+        vec![Complex64::new(h, 0.0), Complex64::new(-h, 0.0)],
+    ]
+}
+
+/// `RX(θ) = exp(−iθX/2)`.
+pub fn rx(theta: f64) -> GateMatrix {
+    let (c, s) = ((theta / 2.0).cos(), (theta / 2.0).sin());
+    vec![
+        vec![Complex64::new(c, 0.0), Complex64::new(0.0, -s)],
+        vec![Complex64::new(0.0, -s), Complex64::new(c, 0.0)],
+    ]
+}
+
+/// Cyclic shift `|k⟩ → |k+1 mod d⟩` (Pauli X for qubits).
+pub fn pauli_x(d: usize) -> GateMatrix {
+    (0..d)
+        .map(|row| {
+            (0..d)
+                .map(|col| {
+                    if (col + 1) % d == row {
+                        Complex64::new(1.0, 0.0)
+                    } else {
+                        Complex64::new(0.0, 0.0)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn sample_index<R: Rng>(probs: &[f64], rng: &mut R) -> usize {
+    let total: f64 = probs.iter().sum();
+    let mut u = rng.gen::<f64>() * total;
+    for (i, p) in probs.iter().enumerate() {
+        if u < *p {
+            return i;
+        }
+        u -= p;
+    }
+    probs.iter().rposition(|p| *p > 0.0).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_hadamard_cnot_makes_bell_pair() {
+        let mut sv = StateVector::zero(vec![2, 2]);
+        sv.hadamard(0).unwrap();
+        sv.cnot(0, 1).unwrap();
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((sv.amplitudes[0].re - h).abs() < 1e-12);
+        assert!((sv.amplitudes[3].re - h).abs() < 1e-12);
+        assert!(sv.amplitudes[1].norm() < 1e-12 && sv.amplitudes[2].norm() < 1e-12);
+        assert!((sv.reduced_purity(0).unwrap() - 0.5).abs() < 1e-12);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            let mut copy = sv.clone();
+            let a = copy.measure(0, &mut rng).unwrap();
+            let b = copy.measure(1, &mut rng).unwrap();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_rx_pi_flips_qubit() {
+        let mut sv = StateVector::zero(vec![2]);
+        sv.rx(0, std::f64::consts::PI).unwrap();
+        assert!(sv.marginal(0).unwrap()[1] > 1.0 - 1e-12);
+        assert!((sv.amplitudes[1] - Complex64::new(0.0, -1.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_bell_measurement_identifies_states() {
+        let mut rng = StdRng::seed_from_u64(5);
+        // Ψ− = (|01⟩ − |10⟩)/√2 → outcome 3.
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        let amps = vec![
+            Complex64::new(0.0, 0.0),
+            Complex64::new(h, 0.0),
+            Complex64::new(-h, 0.0),
+            Complex64::new(0.0, 0.0),
+        ];
+        let mut sv = StateVector::from_amplitudes(vec![2, 2], amps).unwrap();
+        assert_eq!(sv.measure_bell(0, 1, &mut rng).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_qutrit_shift_and_product_state() {
+        let mut sv = StateVector::zero(vec![3, 2]);
+        sv.apply_single(0, &pauli_x(3)).unwrap();
+        sv.apply_single(0, &pauli_x(3)).unwrap();
+        assert!((sv.marginal(0).unwrap()[2] - 1.0).abs() < 1e-12);
+
+        let plus = vec![Complex64::new(1.0, 0.0), Complex64::new(1.0, 0.0)];
+        let product = StateVector::product(&[plus.clone(), plus]).unwrap();
+        assert!(product
+            .amplitudes
+            .iter()
+            .all(|a| (a.re - 0.5).abs() < 1e-12));
+        assert!((product.inner(&product).unwrap().re - 1.0).abs() < 1e-12);
+    }
+}
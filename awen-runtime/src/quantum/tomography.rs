@@ -0,0 +1,533 @@
+//! Quantum state tomography.
+//!
+//! A [`TomographyPlan`] lists measurement settings: Pauli bases for qubit registers or homodyne
+//! angles for a single CV mode. [`collect`] rotates the target into each setting's measurement
+//! basis and samples shots through a [`QuantumBackend`]; [`reconstruct`] runs the iterative
+//! maximum-likelihood `RρR` algorithm (Hradil; Lvovsky for homodyne data in a truncated Fock
+//! basis) over the rank-one projectors of the observed outcomes.
+
+use super::density::DensityMatrix;
+use super::gaussian::hermite_functions;
+use super::pauli::Pauli;
+use super::statevector::{hadamard, GateMatrix};
+use super::{
+    BasisType, DVRegister, HomodyneAxis, MeasurementBasis, QuantumBackend, QuantumState, StateType,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use num_complex::Complex64;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+/// Largest qubit register the Pauli plan will schedule (3ⁿ settings, 2ⁿ-dimensional MLE).
+pub const MAX_TOMOGRAPHY_QUBITS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TomographySetting {
+    /// One Pauli basis (`X`, `Y` or `Z`) per qubit, in plan mode order.
+    Pauli(Vec<Pauli>),
+    /// Homodyne quadrature `x_θ = q·cos θ + p·sin θ`.
+    Homodyne { angle: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TomographyPlan {
+    /// Qubits of the register (Pauli settings) or the single CV mode (homodyne settings).
+    pub modes: Vec<String>,
+    pub settings: Vec<TomographySetting>,
+    pub shots_per_setting: usize,
+    /// Fock truncation of the homodyne reconstruction.
+    pub fock_cutoff: usize,
+    pub max_iterations: usize,
+    /// Stop once no density-matrix element changes by more than this between iterations.
+    pub tolerance: f64,
+}
+
+impl TomographyPlan {
+    /// All `3ⁿ` Pauli-basis settings of the qubits `modes`.
+    pub fn pauli(modes: Vec<String>, shots_per_setting: usize) -> Result<Self> {
+        if modes.is_empty() || modes.len() > MAX_TOMOGRAPHY_QUBITS {
+            return Err(anyhow!(
+                "Pauli tomography needs 1..={} qubits, got {}",
+                MAX_TOMOGRAPHY_QUBITS,
+                modes.len()
+            ));
+        }
+        let bases = [Pauli::X, Pauli::Y, Pauli::Z];
+        let settings = (0..3usize.pow(modes.len() as u32))
+            .map(|mut k| {
+                let mut setting = vec![Pauli::Z; modes.len()];
+                for slot in setting.iter_mut().rev() {
+                    *slot = bases[k % 3];
+                    k /= 3;
+                }
+                TomographySetting::Pauli(setting)
+            })
+            .collect();
+        Ok(Self::with_settings(modes, settings, shots_per_setting))
+    }
+
+    /// `angles` homodyne angles evenly spaced over `[0, π)` on one mode, reconstructed below
+    /// `fock_cutoff` photons.
+    pub fn homodyne(
+        mode: String,
+        angles: usize,
+        shots_per_setting: usize,
+        fock_cutoff: usize,
+    ) -> Self {
+        let settings = (0..angles)
+            .map(|k| TomographySetting::Homodyne {
+                angle: std::f64::consts::PI * k as f64 / angles as f64,
+            })
+            .collect();
+        let mut plan = Self::with_settings(vec![mode], settings, shots_per_setting);
+        plan.fock_cutoff = fock_cutoff;
+        plan
+    }
+
+    fn with_settings(modes: Vec<String>, settings: Vec<TomographySetting>, shots: usize) -> Self {
+        Self {
+            modes,
+            settings,
+            shots_per_setting: shots,
+            fock_cutoff: 10,
+            max_iterations: 2000,
+            tolerance: 1e-7,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.settings.is_empty() || self.shots_per_setting == 0 {
+            return Err(anyhow!("tomography plan needs settings and shots"));
+        }
+        for setting in &self.settings {
+            match setting {
+                TomographySetting::Pauli(bases) if bases.len() != self.modes.len() => {
+                    return Err(anyhow!(
+                        "Pauli setting has {} bases for {} qubits",
+                        bases.len(),
+                        self.modes.len()
+                    ))
+                }
+                TomographySetting::Pauli(bases) if bases.contains(&Pauli::I) => {
+                    return Err(anyhow!("Pauli settings must use X, Y or Z on every qubit"))
+                }
+                TomographySetting::Homodyne { .. } if self.modes.len() != 1 => {
+                    return Err(anyhow!("homodyne tomography reconstructs a single mode"))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SettingOutcomes {
+    /// Shot counts per outcome bitstring (plan mode order, `0` = +1 eigenvalue).
+    Counts(BTreeMap<String, usize>),
+    /// Sampled quadrature values.
+    Quadratures(Vec<f64>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingRecord {
+    pub setting: TomographySetting,
+    pub outcomes: SettingOutcomes,
+}
+
+/// Measure every setting of `plan` on `backend`: `shots_per_setting` shots of the rotated
+/// `target` through `QuantumBackend::sample`, seeded with `seed + k·shots` for setting `k`.
+/// Settings are independent and sampled in parallel.
+pub fn collect(
+    backend: &mut dyn QuantumBackend,
+    target: &QuantumState,
+    plan: &TomographyPlan,
+    seed: u64,
+) -> Result<Vec<SettingRecord>> {
+    plan.validate()?;
+    let backend: &dyn QuantumBackend = backend;
+    plan.settings
+        .par_iter()
+        .enumerate()
+        .map(|(k, setting)| {
+            let mut rotated = target.clone();
+            let basis = rotate_into(&mut rotated, plan, setting)?;
+            let shots = backend.sample(
+                &rotated,
+                &basis,
+                plan.shots_per_setting,
+                seed.wrapping_add((k * plan.shots_per_setting) as u64),
+            )?;
+            let outcomes = match setting {
+                TomographySetting::Pauli(_) => {
+                    let columns = plan
+                        .modes
+                        .iter()
+                        .map(|m| shots.outcomes(m))
+                        .collect::<Result<Vec<_>>>()?;
+                    let mut counts = BTreeMap::new();
+                    for s in 0..shots.num_shots() {
+                        let bits = columns
+                            .iter()
+                            .map(|column| match column[s] {
+                                b if b < 2 => Ok(b.to_string()),
+                                other => Err(anyhow!("expected a qubit outcome, got {}", other)),
+                            })
+                            .collect::<Result<String>>()?;
+                        *counts.entry(bits).or_insert(0) += 1;
+                    }
+                    SettingOutcomes::Counts(counts)
+                }
+                TomographySetting::Homodyne { .. } => {
+                    SettingOutcomes::Quadratures(shots.values(&plan.modes[0])?)
+                }
+            };
+            Ok(SettingRecord {
+                setting: setting.clone(),
+                outcomes,
+            })
+        })
+        .collect()
+}
+
+/// Rotate `state` so that a computational (DV) or q-homodyne (CV) measurement realizes
+/// `setting`, returning that measurement basis.
+fn rotate_into(
+    state: &mut QuantumState,
+    plan: &TomographyPlan,
+    setting: &TomographySetting,
+) -> Result<MeasurementBasis> {
+    match setting {
+        TomographySetting::Pauli(bases) => {
+            if state.state_type != StateType::DV {
+                return Err(anyhow!("Pauli tomography needs a DV state"));
+            }
+            let mut register = DVRegister::load(state)?;
+            for (mode, pauli) in plan.modes.iter().zip(bases) {
+                let q = state.mode_index(mode)?;
+                if register.dimension(q)? != 2 {
+                    return Err(anyhow!("Pauli tomography needs qubits, '{}' is not", mode));
+                }
+                if let Some(rotation) = basis_rotation(*pauli) {
+                    register.apply(None, q, &rotation)?;
+                }
+            }
+            register.store(state)?;
+            Ok(MeasurementBasis {
+                basis_type: BasisType::Computational,
+                mode_labels: plan.modes.clone(),
+            })
+        }
+        TomographySetting::Homodyne { angle } => {
+            let mut gaussian = state
+                .gaussian_state()
+                .ok_or_else(|| anyhow!("homodyne tomography needs a CV state"))?;
+            gaussian.phase_shift(state.mode_index(&plan.modes[0])?, -angle)?;
+            state.set_gaussian_state(gaussian)?;
+            Ok(MeasurementBasis {
+                basis_type: BasisType::Homodyne {
+                    axis: HomodyneAxis::Q,
+                },
+                mode_labels: plan.modes.clone(),
+            })
+        }
+    }
+}
+
+/// Unitary taking the `pauli` eigenbasis to the computational basis (`+1` eigenvector → `|0⟩`).
+fn basis_rotation(pauli: Pauli) -> Option<GateMatrix> {
+    match pauli {
+        Pauli::X => Some(hadamard()),
+        // H·S†
+        Pauli::Y => {
+            let h = std::f64::consts::FRAC_1_SQRT_2;
+            Some(vec![
+                vec![Complex64::new(h, 0.0), Complex64::new(0.0, -h)],
+                vec![Complex64::new(h, 0.0), Complex64::new(0.0, h)],
+            ])
+        }
+        Pauli::Z | Pauli::I => None,
+    }
+}
+
+/// Eigenvector (ket) of `pauli` for outcome `bit` (`0` = +1 eigenvalue).
+fn eigenvector(pauli: Pauli, bit: usize) -> [Complex64; 2] {
+    let h = std::f64::consts::FRAC_1_SQRT_2;
+    let sign = if bit == 0 { 1.0 } else { -1.0 };
+    match pauli {
+        Pauli::X => [Complex64::new(h, 0.0), Complex64::new(sign * h, 0.0)],
+        Pauli::Y => [Complex64::new(h, 0.0), Complex64::new(0.0, sign * h)],
+        Pauli::Z | Pauli::I => {
+            let mut v = [ZERO; 2];
+            v[bit] = Complex64::new(1.0, 0.0);
+            v
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reconstruction {
+    pub density_matrix: DensityMatrix,
+    pub iterations: usize,
+    pub converged: bool,
+    /// `Σ_k f_k ln p_k` over the observed outcomes.
+    pub log_likelihood: f64,
+}
+
+/// Maximum-likelihood density matrix for `records`: Pauli data over the `2ⁿ`-dimensional register
+/// of `plan.modes`, homodyne data over Fock states below `plan.fock_cutoff`.
+pub fn reconstruct(plan: &TomographyPlan, records: &[SettingRecord]) -> Result<Reconstruction> {
+    plan.validate()?;
+    let mut observations: Vec<(Vec<Complex64>, f64)> = Vec::new();
+    let mut dims = vec![2; plan.modes.len()];
+    for record in records {
+        match (&record.setting, &record.outcomes) {
+            (TomographySetting::Pauli(bases), SettingOutcomes::Counts(counts)) => {
+                for (bits, count) in counts {
+                    let mut v = vec![Complex64::new(1.0, 0.0)];
+                    for (pauli, bit) in bases.iter().zip(bits.chars()) {
+                        let e = eigenvector(*pauli, if bit == '1' { 1 } else { 0 });
+                        v = v
+                            .iter()
+                            .flat_map(|a| e.iter().map(move |b| a * b))
+                            .collect();
+                    }
+                    observations.push((v, *count as f64));
+                }
+            }
+            (TomographySetting::Homodyne { angle }, SettingOutcomes::Quadratures(samples)) => {
+                dims = vec![plan.fock_cutoff];
+                for &x in samples {
+                    // ⟨n|x_θ⟩ = e^{inθ} ψ_n(x)
+                    let v = hermite_functions(x, plan.fock_cutoff)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(n, psi)| Complex64::from_polar(psi, n as f64 * angle))
+                        .collect();
+                    observations.push((v, 1.0));
+                }
+            }
+            _ => return Err(anyhow!("tomography record does not match its setting")),
+        }
+    }
+    let total: f64 = observations.iter().map(|(_, w)| w).sum();
+    if total <= 0.0 {
+        return Err(anyhow!("no tomography outcomes to reconstruct from"));
+    }
+    let size: usize = dims.iter().product();
+    let mut rho: Vec<Vec<Complex64>> = (0..size)
+        .map(|i| {
+            (0..size)
+                .map(|j| {
+                    if i == j {
+                        Complex64::new(1.0 / size as f64, 0.0)
+                    } else {
+                        ZERO
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < plan.max_iterations {
+        iterations += 1;
+        // R = Σ_k (f_k / p_k) |v_k⟩⟨v_k|
+        let mut r = vec![vec![ZERO; size]; size];
+        for (v, w) in &observations {
+            let p = expectation(&rho, v).max(1e-300);
+            let scale = w / total / p;
+            for (row, a) in r.iter_mut().zip(v) {
+                for (x, b) in row.iter_mut().zip(v) {
+                    *x += a * b.conj() * scale;
+                }
+            }
+        }
+        let mut next = matmul(&matmul(&r, &rho), &r);
+        let trace: f64 = (0..size).map(|i| next[i][i].re).sum();
+        next.iter_mut().flatten().for_each(|x| *x /= trace);
+        let change = next
+            .iter()
+            .flatten()
+            .zip(rho.iter().flatten())
+            .map(|(a, b)| (a - b).norm())
+            .fold(0.0, f64::max);
+        rho = next;
+        if change < plan.tolerance {
+            converged = true;
+            break;
+        }
+    }
+    let log_likelihood = observations
+        .iter()
+        .map(|(v, w)| w / total * expectation(&rho, v).max(1e-300).ln())
+        .sum();
+    Ok(Reconstruction {
+        density_matrix: DensityMatrix { dims, rho },
+        iterations,
+        converged,
+        log_likelihood,
+    })
+}
+
+/// `⟨v|ρ|v⟩`.
+fn expectation(rho: &[Vec<Complex64>], v: &[Complex64]) -> f64 {
+    rho.iter()
+        .zip(v)
+        .map(|(row, a)| a.conj() * row.iter().zip(v).map(|(x, b)| x * b).sum::<Complex64>())
+        .sum::<Complex64>()
+        .re
+}
+
+fn matmul(a: &[Vec<Complex64>], b: &[Vec<Complex64>]) -> Vec<Vec<Complex64>> {
+    a.iter()
+        .map(|row| {
+            (0..b.len())
+                .map(|j| row.iter().zip(b).map(|(x, brow)| x * brow[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// Reference state of the plan's modes for fidelity: the reduced DV density matrix, or the
+/// Gaussian mode's Fock density below the cutoff.
+pub fn target_density(target: &QuantumState, plan: &TomographyPlan) -> Result<DensityMatrix> {
+    let modes = plan
+        .modes
+        .iter()
+        .map(|m| target.mode_index(m))
+        .collect::<Result<Vec<usize>>>()?;
+    match target.gaussian_state() {
+        Some(gaussian) => gaussian.reduced(&modes)?.fock_density(plan.fock_cutoff),
+        None => target
+            .reduced_dv_densities(&[modes])?
+            .pop()
+            .ok_or_else(|| anyhow!("no reduced state for tomography modes")),
+    }
+}
+
+/// Reconstruction artifact: raw shot data, the maximum-likelihood state and its fidelity with
+/// the simulated target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TomographyArtifact {
+    pub artifact_id: String,
+    pub target_state_id: String,
+    pub backend_name: String,
+    pub seed: u64,
+    pub plan: TomographyPlan,
+    pub records: Vec<SettingRecord>,
+    pub reconstruction: Reconstruction,
+    pub fidelity: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Collect shots for `plan`, reconstruct and compare with `target`.
+pub fn run(
+    backend: &mut dyn QuantumBackend,
+    target: &QuantumState,
+    plan: &TomographyPlan,
+    seed: u64,
+) -> Result<TomographyArtifact> {
+    let records = collect(backend, target, plan, seed)?;
+    let reconstruction = reconstruct(plan, &records)?;
+    let fidelity = target_density(target, plan)?.fidelity(&reconstruction.density_matrix)?;
+    Ok(TomographyArtifact {
+        artifact_id: Uuid::new_v4().to_string(),
+        target_state_id: target.state_id.clone(),
+        backend_name: backend.name().to_string(),
+        seed,
+        plan: plan.clone(),
+        records,
+        reconstruction,
+        fidelity,
+        timestamp: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quantum::{
+        DVGate, GaussianGate, GaussianSimulator, PreparationKind, StatevectorSimulator,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_pauli_plan_enumerates_bases() {
+        let plan = TomographyPlan::pauli(vec!["a".to_string(), "b".to_string()], 10).unwrap();
+        assert_eq!(plan.settings.len(), 9);
+        assert_eq!(
+            plan.settings[5],
+            TomographySetting::Pauli(vec![Pauli::Y, Pauli::Z])
+        );
+        assert!(TomographyPlan::pauli(Vec::new(), 10).is_err());
+    }
+
+    #[test]
+    fn test_bell_state_reconstruction() {
+        let mut backend = StatevectorSimulator::new();
+        let labels = vec!["q0".to_string(), "q1".to_string()];
+        let mut state = backend
+            .prepare(
+                labels.clone(),
+                &PreparationKind::BasisState {
+                    amplitudes: vec![1.0, 0.0],
+                },
+                1,
+            )
+            .unwrap();
+        let params = HashMap::new();
+        for (name, qudits) in [("Hadamard", &labels[..1]), ("CNOT", &labels[..])] {
+            let gate = DVGate::from_name(name, qudits, &params).unwrap();
+            backend.apply_gate(&mut state, &gate).unwrap();
+        }
+        let plan = TomographyPlan::pauli(labels, 400).unwrap();
+        let artifact = run(&mut backend, &state, &plan, 7).unwrap();
+        assert!(artifact.fidelity > 0.97, "fidelity {}", artifact.fidelity);
+        // Perfectly correlated ZZ outcomes.
+        match &artifact.records[8].outcomes {
+            SettingOutcomes::Counts(counts) => {
+                assert_eq!(counts.get("01"), None);
+                assert_eq!(counts.get("10"), None);
+            }
+            other => panic!("unexpected outcomes {:?}", other),
+        }
+        let rho = &artifact.reconstruction.density_matrix;
+        assert!((rho.trace() - 1.0).abs() < 1e-9);
+        assert!(rho.purity() > 0.9);
+    }
+
+    #[test]
+    fn test_homodyne_reconstruction_of_coherent_state() {
+        let mut backend = GaussianSimulator::new();
+        let mut state = backend
+            .prepare(
+                vec!["m".to_string()],
+                &PreparationKind::ThermalState { mean_photons: 0.0 },
+                1,
+            )
+            .unwrap();
+        backend
+            .apply_gate(
+                &mut state,
+                &GaussianGate::Displace {
+                    mode: "m".to_string(),
+                    q: 0.8,
+                    p: 0.4,
+                },
+            )
+            .unwrap();
+        let plan = TomographyPlan::homodyne("m".to_string(), 6, 300, 8);
+        let artifact = run(&mut backend, &state, &plan, 11).unwrap();
+        assert!(artifact.fidelity > 0.95, "fidelity {}", artifact.fidelity);
+        assert_eq!(artifact.reconstruction.density_matrix.dims, vec![8]);
+
+        // Pauli settings cannot be measured on a CV state.
+        let pauli = TomographyPlan::pauli(vec!["m".to_string()], 10).unwrap();
+        assert!(collect(&mut backend, &state, &pauli, 1).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+//! Phase-space (Wigner function) views of single modes.
+//!
+//! Quadratures follow the Gaussian backend's `ħ = 1` convention (vacuum variance ½), so the
+//! vacuum is `W(q, p) = exp(−q² − p²)/π`. DV qudits are read as Fock-truncated modes: level `k`
+//! holds `k` photons.
+
+use super::gaussian::GaussianState;
+use super::linalg::{determinant, inverse};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Rectangular sampling grid over `(q, p)`, `points` samples per axis (endpoints included).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseSpaceGrid {
+    pub q_min: f64,
+    pub q_max: f64,
+    pub p_min: f64,
+    pub p_max: f64,
+    pub points: usize,
+}
+
+impl PhaseSpaceGrid {
+    /// `[−extent, extent]²`.
+    pub fn square(extent: f64, points: usize) -> Self {
+        Self {
+            q_min: -extent,
+            q_max: extent,
+            p_min: -extent,
+            p_max: extent,
+            points,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.points < 2 {
+            return Err(anyhow!("phase-space grid needs at least 2 points per axis"));
+        }
+        if !(self.q_min < self.q_max && self.p_min < self.p_max) {
+            return Err(anyhow!("phase-space grid bounds must be increasing"));
+        }
+        Ok(())
+    }
+
+    pub fn q_axis(&self) -> Vec<f64> {
+        axis(self.q_min, self.q_max, self.points)
+    }
+
+    pub fn p_axis(&self) -> Vec<f64> {
+        axis(self.p_min, self.p_max, self.points)
+    }
+}
+
+impl Default for PhaseSpaceGrid {
+    fn default() -> Self {
+        Self::square(4.0, 81)
+    }
+}
+
+fn axis(min: f64, max: f64, points: usize) -> Vec<f64> {
+    let step = (max - min) / (points - 1) as f64;
+    (0..points).map(|i| min + step * i as f64).collect()
+}
+
+/// Wigner function of one mode; `values[i][j]` is `W(q[i], p[j])`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WignerGrid {
+    pub mode: String,
+    pub state_id: String,
+    pub q: Vec<f64>,
+    pub p: Vec<f64>,
+    pub values: Vec<Vec<f64>>,
+}
+
+impl WignerGrid {
+    /// Sample `w` over `grid`.
+    pub fn sample(
+        mode: &str,
+        state_id: &str,
+        grid: &PhaseSpaceGrid,
+        w: impl Fn(f64, f64) -> f64,
+    ) -> Result<Self> {
+        grid.validate()?;
+        let (q, p) = (grid.q_axis(), grid.p_axis());
+        let values = q
+            .iter()
+            .map(|&qi| p.iter().map(|&pj| w(qi, pj)).collect())
+            .collect();
+        Ok(Self {
+            mode: mode.to_string(),
+            state_id: state_id.to_string(),
+            q,
+            p,
+            values,
+        })
+    }
+
+    /// Riemann-sum estimate of `∫W dq dp`; close to 1 when the grid covers the state.
+    pub fn integral(&self) -> f64 {
+        let dq = (self.q[self.q.len() - 1] - self.q[0]) / (self.q.len() - 1) as f64;
+        let dp = (self.p[self.p.len() - 1] - self.p[0]) / (self.p.len() - 1) as f64;
+        self.values.iter().flatten().sum::<f64>() * dq * dp
+    }
+
+    /// Most negative sample (0 if none): a witness of non-classicality.
+    pub fn min_value(&self) -> f64 {
+        self.values.iter().flatten().fold(0.0, |m, v| m.min(*v))
+    }
+}
+
+/// `W(q, p) = exp(−½ dᵀV⁻¹d) / (2π√det V)` of a single-mode Gaussian state, `d = (q, p) − μ`.
+pub fn gaussian_wigner(state: &GaussianState) -> Result<impl Fn(f64, f64) -> f64> {
+    if state.num_modes() != 1 {
+        return Err(anyhow!(
+            "Wigner function needs a single-mode state, got {} modes",
+            state.num_modes()
+        ));
+    }
+    let v_inv = inverse(&state.covariance)
+        .ok_or_else(|| anyhow!("singular covariance matrix in Wigner function"))?;
+    let norm = 1.0 / (2.0 * PI * determinant(&state.covariance).sqrt());
+    let means = state.means.clone();
+    Ok(move |q: f64, p: f64| {
+        let d = [q - means[0], p - means[1]];
+        let quad = d[0] * (v_inv[0][0] * d[0] + v_inv[0][1] * d[1])
+            + d[1] * (v_inv[1][0] * d[0] + v_inv[1][1] * d[1]);
+        norm * (-0.5 * quad).exp()
+    })
+}
+
+/// Wigner function of a Fock-basis density matrix `rho[m][n] = ⟨m|ρ|n⟩`:
+/// `W = e^{−B/2}/π · Σ_{m≤n} c_mn (−1)^m (2α)^{n−m} √(m!/n!) L_m^{(n−m)}(B)` with
+/// `α = (q + ip)/√2`, `B = |2α|²`, `c_mm = ρ_mm` and `c_mn = 2ρ_mn` (real part taken).
+pub fn fock_wigner(rho: &[Vec<Complex64>]) -> impl Fn(f64, f64) -> f64 + '_ {
+    move |q: f64, p: f64| {
+        let two_alpha = Complex64::new(q, p) * std::f64::consts::SQRT_2;
+        let b = two_alpha.norm_sqr();
+        let mut total = 0.0;
+        for (m, row) in rho.iter().enumerate() {
+            let sign = if m.is_multiple_of(2) { 1.0 } else { -1.0 };
+            let mut power = Complex64::new(1.0, 0.0);
+            let mut ratio = 1.0; // √(m!/n!)
+            for (n, value) in row.iter().enumerate().skip(m) {
+                if n > m {
+                    power *= two_alpha;
+                    ratio /= (n as f64).sqrt();
+                }
+                if value.norm_sqr() == 0.0 {
+                    continue;
+                }
+                let weight = if n == m { 1.0 } else { 2.0 };
+                let term = value * power * (ratio * laguerre(m, (n - m) as f64, b));
+                total += weight * sign * term.re;
+            }
+        }
+        total * (-b / 2.0).exp() / PI
+    }
+}
+
+/// Generalized Laguerre polynomial `L_n^{(a)}(x)` by the three-term recurrence.
+pub(crate) fn laguerre(n: usize, a: f64, x: f64) -> f64 {
+    let (mut prev, mut cur) = (1.0, 1.0 + a - x);
+    if n == 0 {
+        return prev;
+    }
+    for k in 1..n {
+        let k = k as f64;
+        let next = ((2.0 * k + 1.0 + a - x) * cur - (k + a) * prev) / (k + 1.0);
+        prev = cur;
+        cur = next;
+    }
+    cur
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+
+    fn fock(n: usize, dim: usize) -> Vec<Vec<Complex64>> {
+        let mut rho = vec![vec![ZERO; dim]; dim];
+        rho[n][n] = Complex64::new(1.0, 0.0);
+        rho
+    }
+
+    #[test]
+    fn test_vacuum_matches_gaussian() {
+        let gaussian = gaussian_wigner(&GaussianState::vacuum(1)).unwrap();
+        let rho = fock(0, 3);
+        let fock_w = fock_wigner(&rho);
+        for (q, p) in [(0.0f64, 0.0f64), (0.7, -0.3), (-1.2, 1.5)] {
+            let expected = (-q * q - p * p).exp() / PI;
+            assert!((gaussian(q, p) - expected).abs() < 1e-12);
+            assert!((fock_w(q, p) - expected).abs() < 1e-12);
+        }
+        assert!(gaussian_wigner(&GaussianState::vacuum(2)).is_err());
+    }
+
+    #[test]
+    fn test_single_photon_is_negative_at_origin() {
+        let rho = fock(1, 2);
+        let grid = WignerGrid::sample(
+            "m",
+            "s",
+            &PhaseSpaceGrid::square(5.0, 101),
+            fock_wigner(&rho),
+        )
+        .unwrap();
+        assert!((grid.min_value() + 1.0 / PI).abs() < 1e-12);
+        assert!((grid.integral() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_coherences_match_displaced_gaussian() {
+        // Truncated coherent state |α⟩ with α = 0.3 against the displaced vacuum.
+        let alpha: f64 = 0.3;
+        let dim = 12;
+        let mut amps = Vec::with_capacity(dim);
+        let mut c = (-alpha * alpha / 2.0).exp();
+        for n in 0..dim {
+            if n > 0 {
+                c *= alpha / (n as f64).sqrt();
+            }
+            amps.push(c);
+        }
+        let rho: Vec<Vec<Complex64>> = amps
+            .iter()
+            .map(|a| amps.iter().map(|b| Complex64::new(a * b, 0.0)).collect())
+            .collect();
+        let mut displaced = GaussianState::vacuum(1);
+        displaced
+            .displace(0, alpha * std::f64::consts::SQRT_2, 0.0)
+            .unwrap();
+        let gaussian = gaussian_wigner(&displaced).unwrap();
+        let fock_w = fock_wigner(&rho);
+        for (q, p) in [(0.0, 0.0), (0.4, 0.2), (-0.5, 0.6)] {
+            assert!((gaussian(q, p) - fock_w(q, p)).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,740 @@
+//! Persistent job queue with priorities, per-user fairness, device affinity and retries.
+//!
+//! [`JobQueue`] keeps every job and its history in one JSON file, rewritten after each change,
+//! so a restarted process resumes where the last one stopped. Jobs that were running when it
+//! stopped go back to pending and keep their attempt count.
+//!
+//! A device session claims one job at a time ([`JobQueue::claim`]). Among the pending jobs it
+//! may run, the highest [`JobPriority`] wins; within a priority, the user with the fewest
+//! running jobs goes first, then the one served least recently, then the oldest job. A job pinned to a device with [`JobRequest::on_device`]
+//! only runs there. A failed attempt is retried until the job has used
+//! [`JobRequest::max_attempts`].
+//!
+//! [`QueueRunner`] drives a queue with one thread per device session, handing each claimed job
+//! to a [`JobExecutor`]; [`EngineJobExecutor`] runs it on the reference engine.
+
+use crate::ir::{self, Graph};
+use crate::namespace::Namespace;
+use crate::observability::EventSink;
+use crate::storage;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Attempts a job gets unless its request says otherwise
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+const SOURCE: &str = "queue";
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl JobPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobPriority::Low => "low",
+            JobPriority::Normal => "normal",
+            JobPriority::High => "high",
+            JobPriority::Urgent => "urgent",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    /// Whether the job will not change any more
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed)
+    }
+}
+
+/// What to run, for whom, and where
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRequest {
+    pub graph: Graph,
+    pub seed: Option<u64>,
+    /// Who submitted the job; fairness is shared out per user
+    pub user: String,
+    /// Namespace the job and its bundle belong to
+    #[serde(default)]
+    pub namespace: Namespace,
+    pub priority: JobPriority,
+    /// Device session the job must run on; any when `None`
+    pub device: Option<String>,
+    /// Runs before the job fails for good
+    pub max_attempts: u32,
+}
+
+impl JobRequest {
+    pub fn new(graph: Graph, user: &str) -> Self {
+        Self {
+            graph,
+            seed: None,
+            user: user.to_string(),
+            namespace: Namespace::default(),
+            priority: JobPriority::Normal,
+            device: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn in_namespace(mut self, namespace: &Namespace) -> Self {
+        self.namespace = namespace.clone();
+        self
+    }
+
+    pub fn with_priority(mut self, priority: JobPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Only run on the device session named `device`.
+    pub fn on_device(mut self, device: &str) -> Self {
+        self.device = Some(device.to_string());
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+/// A status change of a job, numbered from 0 in the order it happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobEvent {
+    pub seq: usize,
+    pub job_id: String,
+    pub status: JobStatus,
+    pub at: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub job_id: String,
+    pub request: JobRequest,
+    pub status: JobStatus,
+    /// Attempts started so far
+    pub attempts: u32,
+    /// Device session of the current or last attempt
+    pub device: Option<String>,
+    pub submitted_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Error of each failed attempt, oldest first
+    pub errors: Vec<String>,
+    /// Artifact bundle written by the successful attempt
+    pub artifact: Option<PathBuf>,
+    pub events: Vec<JobEvent>,
+}
+
+impl QueuedJob {
+    /// Whether `device` may run this job
+    pub fn runs_on(&self, device: &str) -> bool {
+        self.request.device.as_deref().is_none_or(|d| d == device)
+    }
+
+    fn transition(&mut self, status: JobStatus, message: String) {
+        self.status = status;
+        self.events.push(JobEvent {
+            seq: self.events.len(),
+            job_id: self.job_id.clone(),
+            status,
+            at: now(),
+            message,
+        });
+    }
+}
+
+/// Contents of the queue file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueState {
+    /// Jobs in submission order
+    jobs: Vec<QueuedJob>,
+    /// Claims made so far
+    claims: u64,
+    /// Claim number at which each user was last served
+    last_served: HashMap<String, u64>,
+}
+
+impl QueueState {
+    fn job_mut(&mut self, job_id: &str) -> Result<&mut QueuedJob> {
+        self.jobs
+            .iter_mut()
+            .find(|j| j.job_id == job_id)
+            .ok_or_else(|| anyhow!("no job {}", job_id))
+    }
+
+    /// Index of the job `device` should run next, if it is free and one is eligible
+    fn next_for(&self, device: &str) -> Option<usize> {
+        let busy = self
+            .jobs
+            .iter()
+            .any(|j| j.status == JobStatus::Running && j.device.as_deref() == Some(device));
+        if busy {
+            return None;
+        }
+        let running = |user: &str| {
+            self.jobs
+                .iter()
+                .filter(|j| j.status == JobStatus::Running && j.request.user == user)
+                .count()
+        };
+        self.jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, j)| j.status == JobStatus::Pending && j.runs_on(device))
+            .min_by_key(|(index, j)| {
+                (
+                    std::cmp::Reverse(j.request.priority),
+                    running(&j.request.user),
+                    self.last_served.get(&j.request.user).copied().unwrap_or(0),
+                    *index,
+                )
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+/// Persistent job queue, safe to share between threads
+pub struct JobQueue {
+    path: PathBuf,
+    state: Mutex<QueueState>,
+    /// Signalled on every job event
+    changed: Condvar,
+}
+
+impl JobQueue {
+    /// Open the queue stored at `path`, creating it if missing. Jobs left running by an earlier
+    /// process are put back in the queue.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut state: QueueState = if path.exists() {
+            serde_json::from_slice(&std::fs::read(path)?)
+                .map_err(|e| anyhow!("invalid queue file {}: {}", path.display(), e))?
+        } else {
+            QueueState::default()
+        };
+        for job in state
+            .jobs
+            .iter_mut()
+            .filter(|j| j.status == JobStatus::Running)
+        {
+            job.transition(
+                JobStatus::Pending,
+                "requeued after the queue was reopened".to_string(),
+            );
+        }
+        let queue = Self {
+            path: path.to_path_buf(),
+            state: Mutex::new(state),
+            changed: Condvar::new(),
+        };
+        queue.save(&queue.state.lock().unwrap())?;
+        Ok(queue)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Validate the request's graph and queue it.
+    pub fn submit(&self, request: JobRequest) -> Result<QueuedJob> {
+        ir::validate_graph(&request.graph).map_err(|e| anyhow!("invalid IR: {}", e))?;
+        if request.max_attempts == 0 {
+            return Err(anyhow!("max_attempts must be at least 1"));
+        }
+        let mut job = QueuedJob {
+            job_id: format!("job-{}", uuid::Uuid::new_v4()),
+            status: JobStatus::Pending,
+            attempts: 0,
+            device: None,
+            submitted_at: now(),
+            started_at: None,
+            finished_at: None,
+            errors: Vec::new(),
+            artifact: None,
+            events: Vec::new(),
+            request,
+        };
+        job.transition(
+            JobStatus::Pending,
+            format!(
+                "submitted by {} at {} priority",
+                job.request.user,
+                job.request.priority.as_str()
+            ),
+        );
+        let mut state = self.state.lock().unwrap();
+        state.jobs.push(job.clone());
+        self.commit(&state)?;
+        Ok(job)
+    }
+
+    /// Start the next job `device` should run, or `None` while it is running one or nothing is
+    /// eligible.
+    pub fn claim(&self, device: &str) -> Result<Option<QueuedJob>> {
+        let mut state = self.state.lock().unwrap();
+        self.claim_locked(&mut state, device)
+    }
+
+    /// Like [`Self::claim`], waiting up to `timeout` for a job to become eligible.
+    pub fn claim_wait(&self, device: &str, timeout: Duration) -> Result<Option<QueuedJob>> {
+        let state = self.state.lock().unwrap();
+        let (mut state, _) = self
+            .changed
+            .wait_timeout_while(state, timeout, |state| state.next_for(device).is_none())
+            .unwrap();
+        self.claim_locked(&mut state, device)
+    }
+
+    fn claim_locked(&self, state: &mut QueueState, device: &str) -> Result<Option<QueuedJob>> {
+        let Some(index) = state.next_for(device) else {
+            return Ok(None);
+        };
+        state.claims += 1;
+        let claim = state.claims;
+        let job = &mut state.jobs[index];
+        job.attempts += 1;
+        job.device = Some(device.to_string());
+        job.started_at = Some(now());
+        let message = format!("attempt {} on {}", job.attempts, device);
+        job.transition(JobStatus::Running, message);
+        let job = job.clone();
+        state.last_served.insert(job.request.user.clone(), claim);
+        self.commit(state)?;
+        Ok(Some(job))
+    }
+
+    /// Record the outcome of the running attempt of `job_id`. A failed attempt is queued again
+    /// while the job has attempts left.
+    pub fn complete(&self, job_id: &str, outcome: Result<PathBuf, String>) -> Result<QueuedJob> {
+        let mut state = self.state.lock().unwrap();
+        let job = state.job_mut(job_id)?;
+        if job.status != JobStatus::Running {
+            return Err(anyhow!(
+                "job {} is {}, not running",
+                job_id,
+                job.status.as_str()
+            ));
+        }
+        match outcome {
+            Ok(artifact) => {
+                job.finished_at = Some(now());
+                let message = format!("wrote {}", artifact.display());
+                job.artifact = Some(artifact);
+                job.transition(JobStatus::Succeeded, message);
+            }
+            Err(error) if job.attempts < job.request.max_attempts => {
+                let message = format!(
+                    "attempt {} of {} failed, retrying: {}",
+                    job.attempts, job.request.max_attempts, error
+                );
+                job.errors.push(error);
+                job.transition(JobStatus::Pending, message);
+            }
+            Err(error) => {
+                job.finished_at = Some(now());
+                let message = format!("failed after {} attempts: {}", job.attempts, error);
+                job.errors.push(error);
+                job.transition(JobStatus::Failed, message);
+            }
+        }
+        let job = job.clone();
+        self.commit(&state)?;
+        Ok(job)
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<QueuedJob> {
+        let state = self.state.lock().unwrap();
+        state.jobs.iter().find(|j| j.job_id == job_id).cloned()
+    }
+
+    /// Every job, in submission order
+    pub fn list(&self) -> Vec<QueuedJob> {
+        self.state.lock().unwrap().jobs.clone()
+    }
+
+    /// Pending jobs, highest priority first, then by when their user was last served
+    pub fn pending(&self) -> Vec<QueuedJob> {
+        let state = self.state.lock().unwrap();
+        let mut pending: Vec<&QueuedJob> = state
+            .jobs
+            .iter()
+            .filter(|j| j.status == JobStatus::Pending)
+            .collect();
+        pending.sort_by_key(|j| {
+            (
+                std::cmp::Reverse(j.request.priority),
+                state.last_served.get(&j.request.user).copied().unwrap_or(0),
+            )
+        });
+        pending.into_iter().cloned().collect()
+    }
+
+    /// Events of `job_id` numbered `from` onwards, waiting up to `timeout` for one to arrive.
+    /// Returns `None` for an unknown job.
+    pub fn events(&self, job_id: &str, from: usize, timeout: Duration) -> Option<Vec<JobEvent>> {
+        let state = self.state.lock().unwrap();
+        let (state, _) = self
+            .changed
+            .wait_timeout_while(state, timeout, |state| {
+                state
+                    .jobs
+                    .iter()
+                    .find(|j| j.job_id == job_id)
+                    .is_some_and(|j| j.events.len() <= from && !j.status.is_finished())
+            })
+            .unwrap();
+        let job = state.jobs.iter().find(|j| j.job_id == job_id)?;
+        Some(job.events.iter().skip(from).cloned().collect())
+    }
+
+    /// Persist `state` and wake everyone waiting on a change.
+    fn commit(&self, state: &QueueState) -> Result<()> {
+        self.save(state)?;
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    /// Write to a temporary file, then rename over the queue file, so a crash never leaves
+    /// it half written.
+    fn save(&self, state: &QueueState) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Runner
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Runs one claimed job on a device session
+pub trait JobExecutor: Send + Sync {
+    /// Run `job` on `device`, returning the artifact bundle it wrote.
+    fn execute(&self, job: &QueuedJob, device: &str) -> Result<PathBuf>;
+}
+
+/// Runs jobs on the reference engine, writing each bundle under `<job_id>/` in its namespace's
+/// directory of `artifacts_dir`
+pub struct EngineJobExecutor {
+    artifacts_dir: PathBuf,
+    catalog: Option<PathBuf>,
+}
+
+impl EngineJobExecutor {
+    pub fn new(artifacts_dir: &Path) -> Self {
+        Self {
+            artifacts_dir: artifacts_dir.to_path_buf(),
+            catalog: None,
+        }
+    }
+
+    /// Index every bundle into the run catalog at `catalog`; needs the `catalog` feature
+    pub fn with_catalog(mut self, catalog: &Path) -> Self {
+        self.catalog = Some(catalog.to_path_buf());
+        self
+    }
+}
+
+impl JobExecutor for EngineJobExecutor {
+    fn execute(&self, job: &QueuedJob, _device: &str) -> Result<PathBuf> {
+        let namespace = &job.request.namespace;
+        let bundle = storage::run_artifact(
+            &job.request.graph,
+            job.request.seed,
+            namespace,
+            &namespace.dir(&self.artifacts_dir).join(&job.job_id),
+        )?;
+        if let Some(catalog) = &self.catalog {
+            storage::index_in_catalog(catalog, &bundle)?;
+        }
+        Ok(bundle)
+    }
+}
+
+/// One thread per device session, each claiming and running one job at a time until stopped.
+/// A job already running when the runner stops finishes first.
+pub struct QueueRunner {
+    completed: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl QueueRunner {
+    pub fn start(
+        queue: Arc<JobQueue>,
+        devices: &[String],
+        executor: Arc<dyn JobExecutor>,
+        events: EventSink,
+    ) -> Self {
+        let completed = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let handles = devices
+            .iter()
+            .map(|device| {
+                let (queue, executor, events) =
+                    (Arc::clone(&queue), Arc::clone(&executor), events.clone());
+                let (completed, stop) = (Arc::clone(&completed), Arc::clone(&stop));
+                let device = device.clone();
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::SeqCst) {
+                        // Wake up in short slices so `stop` returns promptly while idle.
+                        let job = match queue.claim_wait(&device, Duration::from_millis(10)) {
+                            Ok(Some(job)) => job,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                events.error(
+                                    SOURCE,
+                                    &format!("claim failed: {}", e),
+                                    HashMap::from([("device".to_string(), device.clone())]),
+                                );
+                                std::thread::sleep(Duration::from_millis(10));
+                                continue;
+                            }
+                        };
+                        let outcome = executor.execute(&job, &device).map_err(|e| e.to_string());
+                        let attrs = HashMap::from([
+                            ("job_id".to_string(), job.job_id.clone()),
+                            ("device".to_string(), device.clone()),
+                            ("user".to_string(), job.request.user.clone()),
+                            ("attempt".to_string(), job.attempts.to_string()),
+                        ]);
+                        match queue.complete(&job.job_id, outcome) {
+                            Ok(done) => {
+                                let message = &done.events.last().expect("job has events").message;
+                                match done.status {
+                                    JobStatus::Succeeded => {
+                                        completed.fetch_add(1, Ordering::SeqCst);
+                                        events.info(SOURCE, message, attrs)
+                                    }
+                                    JobStatus::Failed => {
+                                        completed.fetch_add(1, Ordering::SeqCst);
+                                        events.error(SOURCE, message, attrs)
+                                    }
+                                    _ => events.warning(SOURCE, message, attrs),
+                                }
+                            }
+                            Err(e) => events.error(
+                                SOURCE,
+                                &format!("cannot record outcome: {}", e),
+                                attrs,
+                            ),
+                        }
+                    }
+                })
+            })
+            .collect();
+        Self {
+            completed,
+            stop,
+            handles,
+        }
+    }
+
+    /// Jobs that succeeded or failed for good on this runner
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::SeqCst)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handles.iter().any(|h| !h.is_finished())
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for QueueRunner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::LogFilter;
+
+    fn graph() -> Graph {
+        serde_json::from_value(serde_json::json!({
+            "nodes": [{"id": "mzi_0", "type": "MZI", "params": {"phase": 0.1}}]
+        }))
+        .unwrap()
+    }
+
+    fn submit(queue: &JobQueue, request: JobRequest) -> String {
+        queue.submit(request).unwrap().job_id
+    }
+
+    #[test]
+    fn test_claims_by_priority_then_user_then_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = JobQueue::open(&dir.path().join("queue.json")).unwrap();
+        let a1 = submit(&queue, JobRequest::new(graph(), "alice"));
+        let a2 = submit(&queue, JobRequest::new(graph(), "alice"));
+        let b1 = submit(&queue, JobRequest::new(graph(), "bob"));
+        let low = submit(
+            &queue,
+            JobRequest::new(graph(), "carol").with_priority(JobPriority::Low),
+        );
+        let urgent = submit(
+            &queue,
+            JobRequest::new(graph(), "carol").with_priority(JobPriority::Urgent),
+        );
+
+        let mut order = Vec::new();
+        while let Some(job) = queue.claim("sim").unwrap() {
+            // One job at a time per device session
+            assert!(queue.claim("sim").unwrap().is_none());
+            queue.complete(&job.job_id, Ok(PathBuf::from("x"))).unwrap();
+            order.push(job.job_id);
+        }
+        // Alice was served first, so Bob goes before her second job
+        assert_eq!(order, [urgent, a1, b1, a2, low]);
+    }
+
+    #[test]
+    fn test_affinity_retries_and_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.json");
+        let queue = JobQueue::open(&path).unwrap();
+        let pinned = submit(
+            &queue,
+            JobRequest::new(graph(), "alice").on_device("chip-1"),
+        );
+        let retried = submit(&queue, JobRequest::new(graph(), "bob").with_max_attempts(2));
+        assert!(queue
+            .submit(JobRequest::new(graph(), "bob").with_max_attempts(0))
+            .is_err());
+
+        let job = queue.claim("chip-0").unwrap().unwrap();
+        assert_eq!(job.job_id, retried);
+        let job = queue.complete(&retried, Err("laser fault".into())).unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        // chip-0 may not take the pinned job, only the retry
+        assert_eq!(queue.claim("chip-0").unwrap().unwrap().job_id, retried);
+        let job = queue.complete(&retried, Err("laser fault".into())).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.attempts, 2);
+        assert_eq!(job.errors.len(), 2);
+        assert!(queue.claim("chip-0").unwrap().is_none());
+
+        assert_eq!(queue.claim("chip-1").unwrap().unwrap().job_id, pinned);
+        drop(queue);
+
+        // The running job is requeued when the queue is reopened
+        let queue = JobQueue::open(&path).unwrap();
+        let job = queue.get(&pinned).unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert_eq!(job.attempts, 1);
+        assert_eq!(queue.get(&retried).unwrap().status, JobStatus::Failed);
+        assert_eq!(queue.pending().len(), 1);
+    }
+
+    #[test]
+    fn test_runner_runs_jobs_on_the_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = Arc::new(JobQueue::open(&dir.path().join("queue.json")).unwrap());
+        let job_id = submit(&queue, JobRequest::new(graph(), "alice").with_seed(7));
+        let events = EventSink::with_filter(LogFilter::default());
+        let mut runner = QueueRunner::start(
+            Arc::clone(&queue),
+            &["sim-0".to_string(), "sim-1".to_string()],
+            Arc::new(EngineJobExecutor::new(&dir.path().join("artifacts"))),
+            events.clone(),
+        );
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(60);
+        while runner.completed() < 1 {
+            assert!(std::time::Instant::now() < deadline, "job never finished");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        runner.stop();
+        assert!(!runner.is_running());
+
+        let job = queue.get(&job_id).unwrap();
+        assert_eq!(job.status, JobStatus::Succeeded, "{:?}", job.errors);
+        let bundle = storage::import_bundle(job.artifact.as_ref().unwrap()).unwrap();
+        assert_eq!(bundle.seed, Some(7));
+        let statuses: Vec<JobStatus> = job.events.iter().map(|e| e.status).collect();
+        assert_eq!(
+            statuses,
+            [JobStatus::Pending, JobStatus::Running, JobStatus::Succeeded]
+        );
+        assert!(events.events().iter().any(|e| e.source == SOURCE));
+    }
+
+    #[cfg(feature = "catalog")]
+    #[test]
+    fn test_engine_executor_indexes_into_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = JobQueue::open(&dir.path().join("queue.json")).unwrap();
+        let job_id = submit(&queue, JobRequest::new(graph(), "alice").with_seed(7));
+        let job = queue.claim("sim-0").unwrap().unwrap();
+        let catalog = dir.path().join("runs.sqlite");
+        let bundle = EngineJobExecutor::new(&dir.path().join("artifacts"))
+            .with_catalog(&catalog)
+            .execute(&job, "sim-0")
+            .unwrap();
+
+        let run_id = storage::import_bundle(&bundle).unwrap().artifact_id;
+        let entry = storage::RunCatalog::open(&catalog)
+            .unwrap()
+            .get(&run_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.location, bundle);
+        assert_eq!(entry.seed, Some(7));
+        assert_eq!(queue.get(&job_id).unwrap().status, JobStatus::Running);
+    }
+}
@@ -2,10 +2,13 @@
 // Timing, resource allocation, and coherence-aware execution planning
 
 use crate::ir::Graph;
+use crate::observability::{timeline::lanes, SpanContext, TimelineEvent};
 use crate::state::CoherenceWindow;
 use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Core Scheduler Trait
@@ -23,6 +26,70 @@ pub trait Scheduler: Send + Sync {
 
     /// Validate existing plan against current resource state
     fn validate_plan(&self, plan: &ExecutionPlan, current_state: &ResourceState) -> Result<()>;
+
+    /// `schedule` inside a `schedule` span nested under `span`, annotated with the plan's size
+    /// and makespan (or the error).
+    fn schedule_traced(
+        &self,
+        graph: &Graph,
+        constraints: &SchedulingConstraints,
+        seed: u64,
+        span: &SpanContext,
+    ) -> Result<ExecutionPlan> {
+        let mut handle = span.start_span("schedule");
+        handle.set_attribute("nodes", &graph.nodes.len().to_string());
+        let plan = self.schedule(graph, constraints, seed);
+        match &plan {
+            Ok(plan) => handle.set_attribute("makespan_ns", &plan.makespan_ns.to_string()),
+            Err(e) => handle.set_attribute("error", &e.to_string()),
+        }
+        handle.end();
+        plan
+    }
+}
+
+/// Schedulers keyed by name, e.g. `static` or one loaded from a plugin library
+pub struct SchedulerRegistry {
+    schedulers: RwLock<HashMap<String, Arc<dyn Scheduler>>>,
+}
+
+impl SchedulerRegistry {
+    pub fn new() -> Self {
+        Self {
+            schedulers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&self, name: &str, scheduler: Arc<dyn Scheduler>) {
+        let mut w = self.schedulers.write().unwrap();
+        w.insert(name.to_string(), scheduler);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Scheduler>> {
+        let r = self.schedulers.read().unwrap();
+        r.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.schedulers.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+impl Default for SchedulerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global registry accessible to runtime and CLI. Initialized lazily.
+pub static GLOBAL_SCHEDULER_REGISTRY: Lazy<Arc<SchedulerRegistry>> =
+    Lazy::new(|| Arc::new(SchedulerRegistry::new()));
+
+/// Register the built-in schedulers (`static`)
+pub fn register_default_schedulers(registry: &SchedulerRegistry) {
+    registry.register("static", Arc::new(StaticScheduler::new()));
 }
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -44,6 +111,13 @@ pub struct FeedbackLoop {
     pub control_node: String,
     pub deadline_ns: u64,
     pub priority: Priority,
+    /// What a runtime `FeedbackExecutor` does when an iteration misses `deadline_ns`
+    #[serde(default = "default_feedback_violation_action")]
+    pub violation_action: ViolationAction,
+}
+
+fn default_feedback_violation_action() -> ViolationAction {
+    ViolationAction::Abort
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -69,7 +143,7 @@ pub enum ConstraintType {
     MaximumLatency { src_node: String, dst_node: String },
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ViolationAction {
     Abort,
     Alert,
@@ -83,6 +157,18 @@ pub struct ResourceLimits {
     pub max_concurrent_operations: usize,
 }
 
+impl SchedulingConstraints {
+    /// No coherence windows, feedback deadlines or timing constraints.
+    pub fn unconstrained(resource_limits: ResourceLimits) -> Self {
+        Self {
+            coherence_windows: Vec::new(),
+            feedback_loops: Vec::new(),
+            timing_constraints: Vec::new(),
+            resource_limits,
+        }
+    }
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Execution Plan
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -99,6 +185,41 @@ pub struct ExecutionPlan {
     pub provenance: HashMap<String, String>,
 }
 
+impl ExecutionPlan {
+    /// One timeline event per resource allocation, on the `HAL.Device.<device>.<type>.<id>`
+    /// lane of the allocated resource. Plan times are offsets from `origin_ms`.
+    pub fn timeline_events(&self, device_id: &str, origin_ms: u128) -> Vec<TimelineEvent> {
+        let mut nodes: Vec<&ScheduledNode> = self.schedule.values().collect();
+        nodes.sort_by(|a, b| (a.start_time_ns, &a.node_id).cmp(&(b.start_time_ns, &b.node_id)));
+        let at = |ns: u64| origin_ms + (ns / 1_000_000) as u128;
+        nodes
+            .into_iter()
+            .flat_map(|node| {
+                node.allocated_resources.iter().map(move |alloc| {
+                    let mut attributes = HashMap::new();
+                    attributes.insert("node_id".to_string(), node.node_id.clone());
+                    attributes.insert("device_id".to_string(), device_id.to_string());
+                    attributes.insert("resource_type".to_string(), alloc.resource_type.clone());
+                    attributes.insert("resource_id".to_string(), alloc.resource_id.clone());
+                    attributes.insert("start_ns".to_string(), alloc.start_ns.to_string());
+                    attributes.insert("end_ns".to_string(), alloc.end_ns.to_string());
+                    TimelineEvent {
+                        lane: lanes::hal_resource(
+                            device_id,
+                            &alloc.resource_type,
+                            &alloc.resource_id,
+                        ),
+                        name: format!("alloc:{}", node.node_id),
+                        start_ms: at(alloc.start_ns),
+                        end_ms: at(alloc.end_ns),
+                        attributes,
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ScheduledNode {
     pub node_id: String,
@@ -167,9 +288,15 @@ impl StaticScheduler {
             node_latencies.insert(node.id.clone(), 100); // 100ns default
         }
 
-        // Compute depths via topological traversal
+        // Compute depths via topological traversal; depths still growing after one pass per
+        // node mean a dependency cycle
         let mut changed = true;
+        let mut passes = 0usize;
         while changed {
+            if passes > graph.nodes.len() {
+                return Err(anyhow!("Graph has a dependency cycle"));
+            }
+            passes += 1;
             changed = false;
             for edge in &graph.edges {
                 let src_depth = node_depths.get(&edge.src_node).copied().unwrap_or(0);
@@ -259,9 +386,9 @@ impl StaticScheduler {
                 ));
             }
 
-            // Check fidelity threshold (exponential decay model)
+            // Check fidelity threshold under the window's decoherence model
             let duration = scheduled_node.end_time_ns - scheduled_node.start_time_ns;
-            let fidelity = (-(duration as f64) / (window.duration_ns as f64)).exp();
+            let fidelity = window.fidelity_after(duration as f64)?;
             if fidelity < window.fidelity_threshold {
                 return Err(anyhow!(
                     "Node {} fidelity {} below threshold {}",
@@ -347,7 +474,8 @@ impl Scheduler for StaticScheduler {
         };
 
         // Phase 3: Schedule nodes in topological order
-        for node in &graph.nodes {
+        let detectors = crate::ir::detector_dependencies(graph);
+        for node in crate::ir::execution_order(graph)? {
             // Compute earliest start time based on dependencies
             let mut earliest_start = 0u64;
             for edge in &graph.edges {
@@ -357,6 +485,16 @@ impl Scheduler for StaticScheduler {
                     earliest_start = earliest_start.max(src_end + edge_delay);
                 }
             }
+            for detector in detectors.get(node.id.as_str()).into_iter().flatten() {
+                let detector_end = node_end_times.get(*detector).copied().ok_or_else(|| {
+                    anyhow!(
+                        "Node {} depends on detector {}, which is not scheduled",
+                        node.id,
+                        detector
+                    )
+                })?;
+                earliest_start = earliest_start.max(detector_end);
+            }
 
             // Default node latency
             let node_latency = 100u64; // 100ns
@@ -463,14 +601,18 @@ mod tests {
                     node_type: "Source".to_string(),
                     params: HashMap::new(),
                     measure_mode: None,
+                    measurement: None,
                     conditional_branches: None,
+                    feed_forward: None,
                 },
                 Node {
                     id: "node_1".to_string(),
                     node_type: "MZI".to_string(),
                     params: HashMap::new(),
                     measure_mode: None,
+                    measurement: None,
                     conditional_branches: None,
+                    feed_forward: None,
                 },
             ],
             edges: vec![Edge {
@@ -507,6 +649,153 @@ mod tests {
         assert_eq!(plan1.schedule.len(), plan2.schedule.len());
     }
 
+    #[test]
+    fn test_schedule_span_nests_under_context() {
+        let graph = Graph {
+            nodes: vec![Node {
+                id: "node_0".to_string(),
+                node_type: "MZI".to_string(),
+                params: HashMap::new(),
+                measure_mode: None,
+                measurement: None,
+                conditional_branches: None,
+                feed_forward: None,
+            }],
+            edges: vec![],
+            metadata: HashMap::new(),
+        };
+        let constraints = SchedulingConstraints {
+            coherence_windows: vec![],
+            feedback_loops: vec![],
+            timing_constraints: vec![],
+            resource_limits: ResourceLimits {
+                max_wavelengths: 4,
+                max_memory_slots: 4,
+                max_concurrent_operations: 10,
+            },
+        };
+        let tracer = crate::observability::TracerHandle::new();
+        let run = tracer.start_span("run");
+        let plan = StaticScheduler::new()
+            .schedule_traced(&graph, &constraints, 7, &run.context())
+            .unwrap();
+
+        let spans = tracer.spans();
+        assert_eq!(spans[1].name, "schedule");
+        assert_eq!(spans[1].parent.as_deref(), Some(spans[0].id.as_str()));
+        assert_eq!(
+            spans[1].attributes["makespan_ns"],
+            plan.makespan_ns.to_string()
+        );
+    }
+
+    #[test]
+    fn test_plan_timeline_events_use_resource_lanes() {
+        let node = |id: &str| Node {
+            id: id.to_string(),
+            node_type: "MZI".to_string(),
+            params: HashMap::new(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let edge = |src: &str, dst: &str| Edge {
+            src_node: src.to_string(),
+            src_port: None,
+            dst_node: dst.to_string(),
+            dst_port: None,
+            delay: None,
+        };
+        let mut graph = Graph {
+            nodes: vec![node("a"), node("b")],
+            edges: vec![edge("a", "b")],
+            metadata: HashMap::new(),
+        };
+        let constraints = SchedulingConstraints::unconstrained(ResourceLimits {
+            max_wavelengths: 4,
+            max_memory_slots: 4,
+            max_concurrent_operations: 10,
+        });
+        let plan = StaticScheduler::new()
+            .schedule(&graph, &constraints, 1)
+            .unwrap();
+
+        let events = plan.timeline_events("dev", 1_000);
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].name, "alloc:a");
+        assert_eq!(events[0].lane, "HAL.Device.dev.wavelength.1550nm");
+        assert_eq!(events[1].lane, "HAL.Device.dev.memory.mem_0");
+        assert_eq!(events[2].attributes["start_ns"], "100");
+        assert!(events.iter().all(|e| e.start_ms == 1_000));
+
+        // A feedback edge without a break never settles; it is reported, not looped on.
+        graph.edges.push(edge("b", "a"));
+        let err = StaticScheduler::new()
+            .schedule(&graph, &constraints, 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("cycle"));
+    }
+
+    #[test]
+    fn test_branch_and_feed_forward_wait_for_detector() {
+        let node = |id: &str, node_type: &str| Node {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            params: HashMap::new(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        };
+        let mut detector = node("det", "DETECTOR");
+        detector.conditional_branches = Some(vec![crate::ir::ConditionalBranch {
+            outcome_index: 1,
+            then_nodes: vec!["fix".to_string()],
+            else_nodes: None,
+        }]);
+        let mut tuned = node("tuned", "MZI");
+        tuned.feed_forward = Some(vec![crate::ir::FeedForward {
+            param: "phase".to_string(),
+            source_node: "det".to_string(),
+            quadrature: 0,
+            gain: 1.0,
+            offset: 0.0,
+        }]);
+        let graph = Graph {
+            // Listed after the nodes waiting for it
+            nodes: vec![node("fix", "MZI"), tuned, detector],
+            edges: vec![],
+            metadata: HashMap::new(),
+        };
+        let constraints = SchedulingConstraints::unconstrained(ResourceLimits {
+            max_wavelengths: 1,
+            max_memory_slots: 1,
+            max_concurrent_operations: 3,
+        });
+
+        let plan = StaticScheduler::new()
+            .schedule(&graph, &constraints, 1)
+            .unwrap();
+        let detector_end = plan.schedule["det"].end_time_ns;
+        assert_eq!(plan.schedule["fix"].start_time_ns, detector_end);
+        assert_eq!(plan.schedule["tuned"].start_time_ns, detector_end);
+
+        // A branch target feeding its own detector can never run
+        let mut looped = graph.clone();
+        looped.edges.push(Edge {
+            src_node: "fix".to_string(),
+            src_port: None,
+            dst_node: "det".to_string(),
+            dst_port: None,
+            delay: None,
+        });
+        let err = StaticScheduler::new()
+            .schedule(&looped, &constraints, 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("cycle"));
+    }
+
     #[test]
     fn test_critical_path_computation() {
         let graph = Graph {
@@ -516,21 +805,27 @@ mod tests {
                     node_type: "Source".to_string(),
                     params: HashMap::new(),
                     measure_mode: None,
+                    measurement: None,
                     conditional_branches: None,
+                    feed_forward: None,
                 },
                 Node {
                     id: "b".to_string(),
                     node_type: "MZI".to_string(),
                     params: HashMap::new(),
                     measure_mode: None,
+                    measurement: None,
                     conditional_branches: None,
+                    feed_forward: None,
                 },
                 Node {
                     id: "c".to_string(),
                     node_type: "Detector".to_string(),
                     params: HashMap::new(),
                     measure_mode: None,
+                    measurement: None,
                     conditional_branches: None,
+                    feed_forward: None,
                 },
             ],
             edges: vec![
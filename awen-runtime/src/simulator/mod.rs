@@ -1,18 +1,35 @@
-/// Reference Simulator v0.1 - Realistic Photonic Quantum Simulation
-///
-/// This module extends the SimulatorBackend from Phase 2.3 HAL v0.2 with
-/// realistic noise models, Kerr effects, and measurement-conditioned dynamics.
-///
-/// CONSTITUTIONAL DIRECTIVE ALIGNMENT:
-/// - Full-scope: All noise models (loss, dark counts, phase, Kerr, thermal)
-/// - Non-bypassable: SimulatorBackend impl of PhotonicBackend trait only
-/// - Frontier-first: Measurement-conditioned feedback, coherence limits enforced
-
+//! Reference Simulator v0.1 - Realistic Photonic Quantum Simulation
+//!
+//! This module extends the SimulatorBackend from Phase 2.3 HAL v0.2 with
+//! realistic noise models, Kerr effects, and measurement-conditioned dynamics.
+//!
+//! CONSTITUTIONAL DIRECTIVE ALIGNMENT:
+//! - Full-scope: All noise models (loss, dark counts, phase, Kerr, thermal)
+//! - Non-bypassable: SimulatorBackend impl of PhotonicBackend trait only
+//! - Frontier-first: Measurement-conditioned feedback, coherence limits enforced
+//!
+//! Every noise sampler draws from a caller-supplied `StdRng`, so seeding it from the run seed
+//! replays a simulation exactly.
+
+use crate::state::{QuantumState, ReferenceStateEvolver, StateEvolver};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::f64::consts::PI;
 
+mod profiles;
+pub use profiles::{
+    NoiseProfile, NoiseProfileRegistry, DEFAULT_PROFILE, IDEAL_PROFILE, NOISE_PROFILE_METADATA_KEY,
+};
+mod trajectory;
+pub use trajectory::{TrajectoryShot, TrajectorySimulator};
+
 /// Noise model configuration for reference simulator
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulatorNoiseConfig {
     /// Photon loss rate (per cm of propagation)
     pub loss_rate_per_cm: f64,
@@ -28,18 +45,34 @@ pub struct SimulatorNoiseConfig {
     pub temperature: f64,
     /// Maximum photon number cutoff
     pub max_photons: usize,
+    /// PNR detector: probability that each incident photon is detected
+    pub pnr_efficiency: f64,
+    /// PNR detector: probability that each detected photon triggers one afterpulse count
+    pub pnr_afterpulse_probability: f64,
+    /// PNR detector: largest resolvable count (higher counts read as this value)
+    pub pnr_saturation: u32,
+    /// PNR detector: counting window for dark counts (s)
+    pub pnr_integration_time: f64,
+    /// Optical crosstalk: `crosstalk[i][j]` is the fraction of mode `i`'s power leaking into
+    /// mode `j` per gate step. Reciprocal (symmetric); empty disables crosstalk
+    pub crosstalk: Vec<Vec<f64>>,
 }
 
 impl Default for SimulatorNoiseConfig {
     fn default() -> Self {
         Self {
-            loss_rate_per_cm: 0.01,           // 1% per cm
-            dark_count_rate: 1000.0,          // 1 kHz
-            lo_linewidth: 1000.0,             // 1 kHz
-            kerr_coefficient: 0.1,            // rad/(photon·cm)
-            relative_intensity_noise: 0.001,  // -30 dB
-            temperature: 300.0,               // Room temp
+            loss_rate_per_cm: 0.01,          // 1% per cm
+            dark_count_rate: 1000.0,         // 1 kHz
+            lo_linewidth: 1000.0,            // 1 kHz
+            kerr_coefficient: 0.1,           // rad/(photon·cm)
+            relative_intensity_noise: 0.001, // -30 dB
+            temperature: 300.0,              // Room temp
             max_photons: 3,
+            pnr_efficiency: 0.9,              // 90% per photon
+            pnr_afterpulse_probability: 0.01, // 1% per detection
+            pnr_saturation: 8,
+            pnr_integration_time: 1e-6, // 1 µs
+            crosstalk: Vec::new(),
         }
     }
 }
@@ -61,13 +94,13 @@ pub struct NoiseInjectionParams {
 
 impl NoiseInjectionParams {
     /// Sample noise parameters from configuration and physics
-    pub fn sample(config: &SimulatorNoiseConfig) -> Self {
+    pub fn sample(config: &SimulatorNoiseConfig, rng: &mut StdRng) -> Self {
         Self {
-            lo_phase_noise: sample_gaussian() * (config.lo_linewidth * PI).sqrt(),
-            lo_frequency_noise: sample_gaussian() * config.lo_linewidth,
-            shot_noise_variance: 0.5,  // Vacuum shot noise limit
+            lo_phase_noise: sample_gaussian(rng) * (config.lo_linewidth * PI).sqrt(),
+            lo_frequency_noise: sample_gaussian(rng) * config.lo_linewidth,
+            shot_noise_variance: 0.5, // Vacuum shot noise limit
             thermal_noise_variance: config.relative_intensity_noise * 0.1,
-            kerr_phase_shift: sample_gaussian() * config.kerr_coefficient * 0.01,
+            kerr_phase_shift: sample_gaussian(rng) * config.kerr_coefficient * 0.01,
         }
     }
 }
@@ -84,19 +117,19 @@ impl PhotonLossChannel {
     pub fn from_distance(distance: f64, loss_rate_per_cm: f64) -> Self {
         let loss_prob = 1.0 - (-loss_rate_per_cm * distance).exp();
         Self {
-            loss_probability: loss_prob.max(0.0).min(1.0),
+            loss_probability: loss_prob.clamp(0.0, 1.0),
         }
     }
 
     /// Apply loss to measured photon number
-    pub fn apply(&self, photon_count: u32) -> u32 {
+    pub fn apply(&self, photon_count: u32, rng: &mut StdRng) -> u32 {
         if photon_count == 0 {
             return 0;
         }
-        
+
         let mut remaining = photon_count;
         for _ in 0..photon_count {
-            if sample_uniform() < self.loss_probability {
+            if sample_uniform(rng) < self.loss_probability {
                 remaining -= 1;
             }
         }
@@ -120,9 +153,9 @@ pub struct DarkCountNoise {
 
 impl DarkCountNoise {
     /// Sample dark counts from Poisson distribution
-    pub fn sample(&self) -> u32 {
+    pub fn sample(&self, rng: &mut StdRng) -> u32 {
         let lambda = self.rate * self.integration_time;
-        poisson_sample(lambda)
+        poisson_sample(lambda, rng)
     }
 
     /// Average dark count over measurement
@@ -150,9 +183,9 @@ impl PhaseNoise {
     }
 
     /// Evolve phase noise for given time step
-    pub fn evolve(&mut self, time_step: f64) {
+    pub fn evolve(&mut self, time_step: f64, rng: &mut StdRng) {
         let diffusion = (self.linewidth * PI * time_step).sqrt();
-        self.current_phase += sample_gaussian() * diffusion;
+        self.current_phase += sample_gaussian(rng) * diffusion;
     }
 
     /// SNR degradation from phase noise during measurement
@@ -188,6 +221,64 @@ impl KarrEffect {
     }
 }
 
+/// Noise a node is exposed to under a `SimulatorNoiseConfig`, for attributing results to
+/// noise sources.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoiseDiagnostics {
+    /// Probability that a photon is lost over the node's propagation distance
+    pub loss_probability: f64,
+    /// Fraction of SNR lost to LO phase noise over the detection window
+    pub snr_degradation: f64,
+    /// Expected dark counts added per detection window (detectors only)
+    pub dark_counts: f64,
+    /// Kerr self-phase shift at the mode's mean photon number (rad)
+    pub kerr_phase_shift: f64,
+}
+
+impl NoiseDiagnostics {
+    /// `(name, value)` pairs, for span attributes and metrics.
+    pub fn entries(&self) -> [(&'static str, f64); 4] {
+        [
+            ("loss_probability", self.loss_probability),
+            ("snr_degradation", self.snr_degradation),
+            ("dark_counts", self.dark_counts),
+            ("kerr_phase_shift", self.kerr_phase_shift),
+        ]
+    }
+}
+
+impl SimulatorNoiseConfig {
+    /// Diagnostics of a node propagating `distance_cm` with `mean_photons` in its mode. The
+    /// detection window is `pnr_integration_time`; dark counts apply to detectors only.
+    pub fn diagnostics(
+        &self,
+        distance_cm: f64,
+        mean_photons: f64,
+        detector: bool,
+    ) -> NoiseDiagnostics {
+        let kerr = KarrEffect {
+            chi: self.kerr_coefficient,
+            distance: distance_cm,
+        };
+        NoiseDiagnostics {
+            loss_probability: PhotonLossChannel::from_distance(distance_cm, self.loss_rate_per_cm)
+                .loss_probability,
+            snr_degradation: PhaseNoise::new(self.lo_linewidth)
+                .snr_degradation(self.pnr_integration_time),
+            dark_counts: if detector {
+                DarkCountNoise {
+                    rate: self.dark_count_rate,
+                    integration_time: self.pnr_integration_time,
+                }
+                .expected_count()
+            } else {
+                0.0
+            },
+            kerr_phase_shift: kerr.chi * mean_photons * mean_photons * kerr.distance,
+        }
+    }
+}
+
 /// Homodyne measurement with noise
 #[derive(Clone, Debug)]
 pub struct HomodyneSimulator {
@@ -196,21 +287,15 @@ pub struct HomodyneSimulator {
 }
 
 impl HomodyneSimulator {
-    /// Simulate homodyne measurement with noise
-    pub fn measure(
-        &self,
-        ideal_i: f64,
-        ideal_q: f64,
-        lo_power: f64,
-    ) -> (f64, f64, f64) {
+    /// Simulate homodyne measurement with noise of the complex field `ideal` (I + iQ)
+    pub fn measure(&self, ideal: Complex64, lo_power: f64, rng: &mut StdRng) -> (f64, f64, f64) {
         // Apply phase noise to local oscillator
-        let lo_angle = self.noise_params.lo_phase_noise;
-        let rotated_i = ideal_i * lo_angle.cos() + ideal_q * lo_angle.sin();
-        let rotated_q = -ideal_i * lo_angle.sin() + ideal_q * lo_angle.cos();
+        let rotated = ideal * Complex64::from_polar(1.0, -self.noise_params.lo_phase_noise);
 
         // Add shot noise (proportional to LO power)
-        let shot_i = rotated_i + sample_gaussian() * self.noise_params.shot_noise_variance.sqrt();
-        let shot_q = rotated_q + sample_gaussian() * self.noise_params.shot_noise_variance.sqrt();
+        let shot_std = self.noise_params.shot_noise_variance.sqrt();
+        let shot_i = rotated.re + sample_gaussian(rng) * shot_std;
+        let shot_q = rotated.im + sample_gaussian(rng) * shot_std;
 
         // Add thermal/RIN noise
         let rin_factor = (1.0 + self.config.relative_intensity_noise * lo_power).sqrt();
@@ -232,25 +317,19 @@ pub struct HeterodyneSimulator {
 }
 
 impl HeterodyneSimulator {
-    /// Simulate heterodyne measurement with frequency jitter
-    pub fn measure(
-        &self,
-        ideal_i: f64,
-        ideal_q: f64,
-        measurement_time: f64,
-    ) -> (f64, f64, f64) {
+    /// Simulate heterodyne measurement of the complex field `ideal` (I + iQ) with frequency
+    /// jitter
+    pub fn measure(&self, ideal: Complex64, measurement_time: f64) -> (f64, f64, f64) {
         // Frequency jitter effect on SNR
         let frequency_jitter = self.noise_params.lo_frequency_noise;
         let snr_factor = 1.0 / (1.0 + (frequency_jitter * measurement_time).powi(2));
 
         // Apply frequency-dependent phase jitter
-        let phase_jitter = self.noise_params.lo_phase_noise;
-        let rotated_i = ideal_i * phase_jitter.cos() + ideal_q * phase_jitter.sin();
-        let rotated_q = -ideal_i * phase_jitter.sin() + ideal_q * phase_jitter.cos();
+        let rotated = ideal * Complex64::from_polar(1.0, -self.noise_params.lo_phase_noise);
 
         // Degrade magnitude due to frequency uncertainty
-        let magnitude = (rotated_i.powi(2) + rotated_q.powi(2)).sqrt() * snr_factor;
-        let phase = rotated_q.atan2(rotated_i);
+        let magnitude = rotated.norm() * snr_factor;
+        let phase = rotated.arg();
 
         let snr = magnitude / (1.0 - snr_factor);
 
@@ -267,36 +346,97 @@ pub struct DirectDetectionSimulator {
 
 impl DirectDetectionSimulator {
     /// Simulate photon counting measurement
-    pub fn measure(
-        &self,
-        photon_count: u32,
-        quantum_efficiency: f64,
-    ) -> u32 {
+    pub fn measure(&self, photon_count: u32, quantum_efficiency: f64, rng: &mut StdRng) -> u32 {
         // Apply quantum efficiency
-        let detected = if sample_uniform() < quantum_efficiency {
+        let detected = if sample_uniform(rng) < quantum_efficiency {
             photon_count
         } else {
             0
         };
 
         // Add dark counts
-        let dark = self.dark_count_noise.sample();
+        let dark = self.dark_count_noise.sample(rng);
 
         detected + dark
     }
 
+    /// Simulate a photon-number-resolving measurement of `photon_count` incident photons:
+    /// binomial detection with `pnr_efficiency`, one afterpulse per detected photon with
+    /// `pnr_afterpulse_probability`, Poisson dark counts, and saturation at `pnr_saturation`.
+    pub fn measure_pnr(&self, photon_count: u32, rng: &mut StdRng) -> PnrOutcome {
+        let distribution = self.pnr_distribution(photon_count);
+        let u = sample_uniform(rng);
+        let mut cumulative = 0.0;
+        let mut count = distribution.len() as u32 - 1;
+        for (k, p) in distribution.iter().enumerate() {
+            cumulative += p;
+            if u < cumulative {
+                count = k as u32;
+                break;
+            }
+        }
+        PnrOutcome {
+            incident_photons: photon_count,
+            count,
+            saturated: count == self.config.pnr_saturation,
+            distribution,
+        }
+    }
+
+    /// Probability of each reported PNR count `0..=pnr_saturation` for `photon_count` incident
+    /// photons; the last entry holds all counts at or above saturation.
+    pub fn pnr_distribution(&self, photon_count: u32) -> Vec<f64> {
+        let saturation = self.config.pnr_saturation as usize;
+        let detected = binomial_pmf(photon_count, self.config.pnr_efficiency);
+        // Detected photons plus their afterpulses.
+        let mut signal = vec![0.0; 2 * photon_count as usize + 1];
+        for (d, p_d) in detected.iter().enumerate() {
+            for (extra, p_a) in binomial_pmf(d as u32, self.config.pnr_afterpulse_probability)
+                .iter()
+                .enumerate()
+            {
+                signal[d + extra] += p_d * p_a;
+            }
+        }
+        let lambda = self.config.dark_count_rate * self.config.pnr_integration_time;
+        let mut distribution = vec![0.0; saturation + 1];
+        for (k, p_s) in signal.iter().enumerate() {
+            let mut p_dark = (-lambda).exp();
+            for dark in 0..=saturation {
+                if dark > 0 {
+                    p_dark *= lambda / dark as f64;
+                }
+                let total = (k + dark).min(saturation);
+                distribution[total] += p_s * p_dark;
+            }
+        }
+        // Dark-count tail beyond the saturation window.
+        let covered: f64 = distribution.iter().sum();
+        distribution[saturation] += (1.0 - covered).max(0.0);
+        distribution
+    }
+
     /// Calibrate detected count to true photon number
-    pub fn calibrate(
-        &self,
-        measured: u32,
-        quantum_efficiency: f64,
-    ) -> u32 {
+    pub fn calibrate(&self, measured: u32, quantum_efficiency: f64) -> u32 {
         let dark_baseline = self.dark_count_noise.expected_count() as u32;
         let signal = (measured as i32 - dark_baseline as i32).max(0) as u32;
         ((signal as f64) / quantum_efficiency).round() as u32
     }
 }
 
+/// Photon-number-resolving detection result
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PnrOutcome {
+    /// Photons incident on the detector
+    pub incident_photons: u32,
+    /// Sampled count reported by the detector
+    pub count: u32,
+    /// Whether the count hit the detector's saturation limit
+    pub saturated: bool,
+    /// Probability of every reportable count `0..=pnr_saturation`
+    pub distribution: Vec<f64>,
+}
+
 /// Calibration state for simulator (tracks drift and errors)
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SimulatorCalibrationState {
@@ -317,8 +457,8 @@ impl Default for SimulatorCalibrationState {
         Self {
             phase_calib_time: 0.0,
             dark_calib_time: 0.0,
-            phase_drift_rate: 1e-5,      // rad/s
-            dark_count_drift: 0.0001,    // per second
+            phase_drift_rate: 1e-5,   // rad/s
+            dark_count_drift: 0.0001, // per second
             accumulated_phase_drift: 0.0,
         }
     }
@@ -341,41 +481,143 @@ impl SimulatorCalibrationState {
     }
 }
 
+// ============================================================================
+// CROSSTALK
+// ============================================================================
+
+impl SimulatorNoiseConfig {
+    /// Crosstalk matrix for `modes` waveguides in a row, each leaking `leakage` of its power
+    /// into each neighbour
+    pub fn adjacent_crosstalk(modes: usize, leakage: f64) -> Vec<Vec<f64>> {
+        (0..modes)
+            .map(|i| {
+                (0..modes)
+                    .map(|j| if i.abs_diff(j) == 1 { leakage } else { 0.0 })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Crosstalk matrix for `modes` waveguides from fitted `crosstalk_<i>_<j>` parameters (as
+    /// produced by `CalibrationKernel::crosstalk_characterization`); missing pairs are 0
+    pub fn crosstalk_from_parameters(modes: usize, params: &HashMap<String, f64>) -> Vec<Vec<f64>> {
+        let leakage = |i: usize, j: usize| {
+            let (low, high) = (i.min(j), i.max(j));
+            params
+                .get(&format!("crosstalk_{}_{}", low, high))
+                .or_else(|| params.get(&format!("crosstalk_{}_{}", high, low)))
+                .copied()
+                .unwrap_or(0.0)
+        };
+        (0..modes)
+            .map(|i| {
+                (0..modes)
+                    .map(|j| if i == j { 0.0 } else { leakage(i, j) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Check the crosstalk matrix is square and reciprocal, with every mode keeping a
+    /// non-negative share of its power
+    pub fn validate_crosstalk(&self) -> Result<()> {
+        let n = self.crosstalk.len();
+        for (i, row) in self.crosstalk.iter().enumerate() {
+            if row.len() != n {
+                return Err(anyhow!(
+                    "crosstalk row {} has {} entries for {} modes",
+                    i,
+                    row.len(),
+                    n
+                ));
+            }
+            for (j, &leakage) in row.iter().enumerate() {
+                if !(0.0..=1.0).contains(&leakage) || (i == j && leakage != 0.0) {
+                    return Err(anyhow!(
+                        "crosstalk[{}][{}] = {} out of range",
+                        i,
+                        j,
+                        leakage
+                    ));
+                }
+                if (leakage - self.crosstalk[j][i]).abs() > 1e-12 {
+                    return Err(anyhow!(
+                        "crosstalk is not reciprocal between modes {} and {}",
+                        i,
+                        j
+                    ));
+                }
+            }
+            if row.iter().sum::<f64>() > 1.0 {
+                return Err(anyhow!("mode {} leaks more than all of its power", i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply one step of crosstalk: each coupled pair `(i, j)` mixes on a weak beam splitter
+    /// with `sin²θ = crosstalk[i][j]`, so a photon hops with exactly the leakage probability.
+    /// Matrix indices address modes as the gates do (`i` or `mode_<i>`)
+    pub fn apply_crosstalk(&self, state: &QuantumState) -> Result<QuantumState> {
+        self.validate_crosstalk()?;
+        let evolver = ReferenceStateEvolver;
+        let mut out = state.clone();
+        let last_gate = state.provenance.get("last_gate").cloned();
+        for (i, row) in self.crosstalk.iter().enumerate() {
+            for (j, &leakage) in row.iter().enumerate().skip(i + 1) {
+                if leakage == 0.0 {
+                    continue;
+                }
+                let params = HashMap::from([
+                    ("mode1".to_string(), i as f64),
+                    ("mode2".to_string(), j as f64),
+                    ("theta".to_string(), leakage.sqrt().asin()),
+                ]);
+                out = evolver.evolve_state(&out, "BS", &params)?;
+            }
+        }
+        // Crosstalk is not a gate of the program; keep the provenance pointing at the last one.
+        match last_gate {
+            Some(gate) => out.provenance.insert("last_gate".to_string(), gate),
+            None => out.provenance.remove("last_gate"),
+        };
+        Ok(out)
+    }
+}
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
 
 /// Sample from standard Gaussian distribution
-fn sample_gaussian() -> f64 {
-    // Box-Muller transform
-    let u1 = sample_uniform();
-    let u2 = sample_uniform();
+fn sample_gaussian(rng: &mut StdRng) -> f64 {
+    // Box-Muller transform; 1 − u keeps the logarithm finite
+    let u1 = 1.0 - sample_uniform(rng);
+    let u2 = sample_uniform(rng);
     (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
 }
 
-/// Sample uniform random between 0 and 1
-fn sample_uniform() -> f64 {
-    use std::cell::RefCell;
-    thread_local! {
-        static RNG: RefCell<u64> = RefCell::new(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos() as u64
-        );
-    }
-
-    RNG.with(|rng| {
-        let mut x = rng.borrow_mut();
-        *x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
-        let mantissa = (*x >> 11) as f64;
-        let exponent = -53.0_f64;
-        mantissa * 2.0_f64.powf(exponent)
-    })
+/// Sample uniform random in [0, 1)
+fn sample_uniform(rng: &mut StdRng) -> f64 {
+    rng.gen::<f64>()
+}
+
+/// Binomial probabilities P(k successes of n trials), k = 0..=n
+fn binomial_pmf(n: u32, p: f64) -> Vec<f64> {
+    let p = p.clamp(0.0, 1.0);
+    let mut pmf = vec![0.0; n as usize + 1];
+    let mut coefficient = 1.0;
+    for (k, slot) in pmf.iter_mut().enumerate() {
+        if k > 0 {
+            coefficient *= (n as usize + 1 - k) as f64 / k as f64;
+        }
+        *slot = coefficient * p.powi(k as i32) * (1.0 - p).powi((n as usize - k) as i32);
+    }
+    pmf
 }
 
 /// Sample from Poisson distribution
-fn poisson_sample(lambda: f64) -> u32 {
+fn poisson_sample(lambda: f64, rng: &mut StdRng) -> u32 {
     if lambda < 30.0 {
         // Knuth algorithm for small lambda
         let mut k = 0;
@@ -383,13 +625,13 @@ fn poisson_sample(lambda: f64) -> u32 {
         let l = (-lambda).exp();
         while p > l {
             k += 1;
-            p *= sample_uniform();
+            p *= sample_uniform(rng);
         }
         k - 1
     } else {
         // "Ratio of uniforms" for large lambda
         let g = lambda;
-        let em = g + (2.0 * g).sqrt() * sample_gaussian();
+        let em = g + (2.0 * g).sqrt() * sample_gaussian(rng);
         em.max(0.0) as u32
     }
 }
@@ -401,16 +643,17 @@ fn poisson_sample(lambda: f64) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
 
     #[test]
     fn test_photon_loss_channel() {
         let loss = PhotonLossChannel::from_distance(1.0, 0.01);
         assert!(loss.loss_probability > 0.0);
-        assert!(loss.loss_probability < 0.02);  // ~1% for 1 cm at 0.01 loss rate
+        assert!(loss.loss_probability < 0.02); // ~1% for 1 cm at 0.01 loss rate
 
-        let remaining = loss.apply(10);
+        let remaining = loss.apply(10, &mut StdRng::seed_from_u64(1));
         assert!(remaining <= 10);
-        assert!(remaining >= 8);  // Expect ~90% survival
+        assert!(remaining >= 8); // Expect ~90% survival
     }
 
     #[test]
@@ -420,18 +663,20 @@ mod tests {
             integration_time: 1e-6,
         };
         let expected = dark.expected_count();
-        assert_eq!(expected, 0.001);  // 1000 Hz * 1 µs = 0.001 counts
+        assert_eq!(expected, 0.001); // 1000 Hz * 1 µs = 0.001 counts
 
-        let samples: u32 = (0..100).map(|_| dark.sample()).sum();
-        assert!(samples > 0);  // Poisson sampling should give some dark counts
+        // 100k windows at λ = 0.001: ~100 dark counts expected
+        let mut rng = StdRng::seed_from_u64(2);
+        let samples: u32 = (0..100_000).map(|_| dark.sample(&mut rng)).sum();
+        assert!(samples > 0); // Poisson sampling should give some dark counts
     }
 
     #[test]
     fn test_phase_noise_evolution() {
         let mut phase_noise = PhaseNoise::new(1000.0);
         let initial = phase_noise.current_phase;
-        phase_noise.evolve(1e-6);
-        assert_ne!(phase_noise.current_phase, initial);  // Phase changed
+        phase_noise.evolve(1e-6, &mut StdRng::seed_from_u64(3));
+        assert_ne!(phase_noise.current_phase, initial); // Phase changed
     }
 
     #[test]
@@ -443,29 +688,176 @@ mod tests {
         let phase_0 = kerr.phase_shift(0);
         let phase_1 = kerr.phase_shift(1);
         let phase_2 = kerr.phase_shift(2);
-        
+
         assert_eq!(phase_0, 0.0);
         assert_eq!(phase_1, 0.1);
-        assert_eq!(phase_2, 0.4);  // n² scaling
+        assert_eq!(phase_2, 0.4); // n² scaling
+    }
+
+    #[test]
+    fn test_noise_diagnostics() {
+        let config = SimulatorNoiseConfig::default();
+        let gate = config.diagnostics(2.0, 2.0, false);
+        assert!((gate.loss_probability - (1.0 - (-0.02_f64).exp())).abs() < 1e-12);
+        assert!((gate.kerr_phase_shift - 0.8).abs() < 1e-12);
+        assert_eq!(gate.dark_counts, 0.0);
+
+        let detector = config.diagnostics(0.0, 0.0, true);
+        assert_eq!(detector.loss_probability, 0.0);
+        assert!((detector.dark_counts - 1e-3).abs() < 1e-12);
+        assert!(detector.snr_degradation > 0.0 && detector.snr_degradation < 1.0);
     }
 
     #[test]
     fn test_homodyne_measurement() {
         let config = SimulatorNoiseConfig::default();
-        let noise_params = NoiseInjectionParams::sample(&config);
-        let simulator = HomodyneSimulator { config, noise_params };
-        
-        let (i, q, var) = simulator.measure(1.0, 0.0, 1.0);
-        assert!(var >= 0.5);  // Shot noise limit
+        let mut rng = StdRng::seed_from_u64(4);
+        let noise_params = NoiseInjectionParams::sample(&config, &mut rng);
+        let simulator = HomodyneSimulator {
+            config,
+            noise_params,
+        };
+
+        let (i, q, var) = simulator.measure(Complex64::new(1.0, 0.0), 1.0, &mut rng);
+        assert!(var >= 0.5); // Shot noise limit
         assert!(i.is_finite() && q.is_finite());
     }
 
+    #[test]
+    fn test_pnr_count_distribution() {
+        let config = SimulatorNoiseConfig {
+            dark_count_rate: 0.0,
+            pnr_afterpulse_probability: 0.0,
+            pnr_efficiency: 0.5,
+            pnr_saturation: 3,
+            ..Default::default()
+        };
+        let detector = DirectDetectionSimulator {
+            config,
+            dark_count_noise: DarkCountNoise {
+                rate: 0.0,
+                integration_time: 1e-6,
+            },
+        };
+
+        // Two photons at 50%: 1/4, 1/2, 1/4
+        let dist = detector.pnr_distribution(2);
+        assert_eq!(dist.len(), 4);
+        assert!((dist[0] - 0.25).abs() < 1e-12);
+        assert!((dist[1] - 0.5).abs() < 1e-12);
+        assert!((dist[2] - 0.25).abs() < 1e-12);
+
+        // Five photons saturate at 3
+        let dist = detector.pnr_distribution(5);
+        assert!((dist.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        let outcome = detector.measure_pnr(5, &mut StdRng::seed_from_u64(5));
+        assert!(outcome.count <= 3);
+        assert_eq!(outcome.saturated, outcome.count == 3);
+    }
+
+    #[test]
+    fn test_pnr_afterpulsing_adds_counts() {
+        let config = SimulatorNoiseConfig {
+            dark_count_rate: 0.0,
+            pnr_efficiency: 1.0,
+            pnr_afterpulse_probability: 0.2,
+            ..Default::default()
+        };
+        let detector = DirectDetectionSimulator {
+            config,
+            dark_count_noise: DarkCountNoise {
+                rate: 0.0,
+                integration_time: 1e-6,
+            },
+        };
+        let dist = detector.pnr_distribution(1);
+        assert!((dist[1] - 0.8).abs() < 1e-12);
+        assert!((dist[2] - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_seeded_noise_replays() {
+        let config = SimulatorNoiseConfig::default();
+        let detector = DirectDetectionSimulator {
+            config: config.clone(),
+            dark_count_noise: DarkCountNoise {
+                rate: 1e6,
+                integration_time: 1e-6,
+            },
+        };
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let homodyne = HomodyneSimulator {
+                config: config.clone(),
+                noise_params: NoiseInjectionParams::sample(&config, &mut rng),
+            };
+            let quadratures: Vec<(f64, f64, f64)> = (0..20)
+                .map(|_| homodyne.measure(Complex64::new(0.3, -0.2), 1.0, &mut rng))
+                .collect();
+            let counts: Vec<u32> = (0..20)
+                .map(|_| detector.measure(2, 0.8, &mut rng))
+                .collect();
+            let pnr: Vec<u32> = (0..20)
+                .map(|_| detector.measure_pnr(3, &mut rng).count)
+                .collect();
+            (quadratures, counts, pnr)
+        };
+        assert_eq!(run(11), run(11));
+        assert_ne!(run(11).0, run(12).0);
+    }
+
     #[test]
     fn test_calibration_state_drift() {
         let mut calib = SimulatorCalibrationState::default();
-        calib.update(1000.0);  // 1000 seconds
-        
+        calib.update(1000.0); // 1000 seconds
+
         assert!(calib.accumulated_phase_drift > 0.0);
-        assert!(calib.phase_calib_expired());  // Should be expired
+        assert!(calib.phase_calib_expired()); // Should be expired
+    }
+
+    #[test]
+    fn test_crosstalk_leaks_adjacent_modes() {
+        use crate::state::{CoherenceWindow, QuantumMode};
+        let mode = |id: usize, photons: usize| QuantumMode {
+            mode_id: format!("mode_{}", id),
+            mode_type: "quantum_fock".to_string(),
+            photon_numbers: Some(vec![0, 1]),
+            amplitudes: Some(
+                (0..2)
+                    .map(|n| Complex64::new((n == photons) as u8 as f64, 0.0))
+                    .collect(),
+            ),
+        };
+        let state = QuantumState {
+            id: "crosstalk".to_string(),
+            modes: vec![mode(0, 1), mode(1, 0), mode(2, 0)],
+            coherence_window: CoherenceWindow::new("cw".to_string(), 1_000),
+            seed: Some(1),
+            provenance: HashMap::new(),
+        };
+        let config = SimulatorNoiseConfig {
+            crosstalk: SimulatorNoiseConfig::adjacent_crosstalk(3, 0.01),
+            ..Default::default()
+        };
+
+        // The photon hops 0 → 1 with probability 1%, then 1% of that moves on to mode 2.
+        let leaked = config.apply_crosstalk(&state).unwrap();
+        let occupation = |i: usize| leaked.modes[i].amplitudes.as_ref().unwrap()[1].norm_sqr();
+        assert!((occupation(0) - 0.99).abs() < 1e-12);
+        assert!((occupation(1) - 0.0099).abs() < 1e-12);
+        assert!((occupation(2) - 0.0001).abs() < 1e-12);
+        assert!(!leaked.provenance.contains_key("last_gate"));
+
+        let mut asymmetric = config.clone();
+        asymmetric.crosstalk[0][1] = 0.02;
+        assert!(asymmetric.apply_crosstalk(&state).is_err());
+        let mut ragged = config;
+        ragged.crosstalk[2].pop();
+        assert!(ragged.validate_crosstalk().is_err());
+
+        let fitted = HashMap::from([("crosstalk_1_2".to_string(), 0.03)]);
+        let matrix = SimulatorNoiseConfig::crosstalk_from_parameters(3, &fitted);
+        assert_eq!(matrix[2][1], 0.03);
+        assert_eq!(matrix[0][1], 0.0);
     }
 }
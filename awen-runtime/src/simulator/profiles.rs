@@ -0,0 +1,176 @@
+//! Named noise profiles.
+//!
+//! A profile is a `SimulatorNoiseConfig` under a stable id ("ideal", "lab_chip_A_2026Q1", ...).
+//! Graphs select one through the `noise_profile` metadata key and engines through
+//! `Engine::with_noise_profile`; the active id is recorded with the run (state provenance,
+//! `noise_model.json`, `QuantumArtifact::noise_model_id`) so a replay resolves the same model.
+
+use super::SimulatorNoiseConfig;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// IR metadata key naming the graph's noise profile.
+pub const NOISE_PROFILE_METADATA_KEY: &str = "noise_profile";
+
+/// Built-in profile without any noise.
+pub const IDEAL_PROFILE: &str = "ideal";
+
+/// Built-in profile with `SimulatorNoiseConfig::default()`.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A noise configuration with the id it was selected by.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoiseProfile {
+    pub id: String,
+    pub config: SimulatorNoiseConfig,
+}
+
+impl SimulatorNoiseConfig {
+    /// Noise-free configuration: lossless, no dark counts, ideal LO and detectors
+    pub fn ideal() -> Self {
+        Self {
+            loss_rate_per_cm: 0.0,
+            dark_count_rate: 0.0,
+            lo_linewidth: 0.0,
+            kerr_coefficient: 0.0,
+            relative_intensity_noise: 0.0,
+            temperature: 0.0,
+            pnr_efficiency: 1.0,
+            pnr_afterpulse_probability: 0.0,
+            crosstalk: Vec::new(),
+            ..Self::default()
+        }
+    }
+
+    /// Load a configuration from a JSON file; missing fields take their defaults
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read noise config {}: {}", path.display(), e))?;
+        let config: Self = serde_json::from_str(&text)
+            .map_err(|e| anyhow!("invalid noise config {}: {}", path.display(), e))?;
+        config.validate_crosstalk()?;
+        Ok(config)
+    }
+}
+
+/// Registry of noise profiles, keyed by id. Starts with the built-in `ideal` and `default`
+/// profiles.
+#[derive(Clone, Debug)]
+pub struct NoiseProfileRegistry {
+    profiles: BTreeMap<String, SimulatorNoiseConfig>,
+}
+
+impl Default for NoiseProfileRegistry {
+    fn default() -> Self {
+        Self {
+            profiles: BTreeMap::from([
+                (IDEAL_PROFILE.to_string(), SimulatorNoiseConfig::ideal()),
+                (DEFAULT_PROFILE.to_string(), SimulatorNoiseConfig::default()),
+            ]),
+        }
+    }
+}
+
+impl NoiseProfileRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `config` under `id`.
+    pub fn register(&mut self, id: &str, config: SimulatorNoiseConfig) -> Result<()> {
+        if self.profiles.contains_key(id) {
+            return Err(anyhow!("noise profile {} already registered", id));
+        }
+        config.validate_crosstalk()?;
+        self.profiles.insert(id.to_string(), config);
+        Ok(())
+    }
+
+    /// Register a profile file under its file stem (`lab_chip_A_2026Q1.json` →
+    /// `lab_chip_A_2026Q1`), returning the id.
+    pub fn load_file(&mut self, path: &Path) -> Result<String> {
+        let id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("noise profile path {} has no name", path.display()))?
+            .to_string();
+        self.register(&id, SimulatorNoiseConfig::from_file(path)?)?;
+        Ok(id)
+    }
+
+    /// Register every `*.json` file in `dir`, returning the ids in name order.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<Vec<String>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| anyhow!("failed to list noise profiles in {}: {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        paths.iter().map(|path| self.load_file(path)).collect()
+    }
+
+    /// The profile registered under `id`.
+    pub fn get(&self, id: &str) -> Result<NoiseProfile> {
+        self.profiles
+            .get(id)
+            .map(|config| NoiseProfile {
+                id: id.to_string(),
+                config: config.clone(),
+            })
+            .ok_or_else(|| anyhow!("unknown noise profile {}", id))
+    }
+
+    /// Registered ids in name order.
+    pub fn ids(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_load_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lab_chip_A_2026Q1.json"),
+            r#"{"loss_rate_per_cm": 0.2, "crosstalk": [[0.0, 0.02], [0.02, 0.0]]}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a profile").unwrap();
+
+        let mut registry = NoiseProfileRegistry::new();
+        assert_eq!(
+            registry.load_dir(dir.path()).unwrap(),
+            vec!["lab_chip_A_2026Q1"]
+        );
+        assert_eq!(
+            registry.ids(),
+            vec!["default", "ideal", "lab_chip_A_2026Q1"]
+        );
+
+        let chip = registry.get("lab_chip_A_2026Q1").unwrap();
+        assert_eq!(chip.config.loss_rate_per_cm, 0.2);
+        assert_eq!(chip.config.crosstalk[0][1], 0.02);
+        // Unspecified fields keep their defaults.
+        assert_eq!(chip.config.pnr_saturation, 8);
+        assert_eq!(
+            registry.get(IDEAL_PROFILE).unwrap().config.dark_count_rate,
+            0.0
+        );
+
+        assert!(registry.get("lab_chip_B").is_err());
+        assert!(registry
+            .register("ideal", SimulatorNoiseConfig::default())
+            .is_err());
+        std::fs::write(
+            dir.path().join("bad.json"),
+            r#"{"crosstalk": [[0.0, 0.1], [0.3, 0.0]]}"#,
+        )
+        .unwrap();
+        assert!(registry.load_file(&dir.path().join("bad.json")).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! Quantum trajectory (Monte Carlo unravelling) simulation of loss and dephasing.
+//!
+//! Instead of averaging the noise channels into the state, each shot draws one Kraus branch
+//! per step from a seeded RNG: loss removes `k` photons with probability `‖A_k ψ‖²`
+//! (`A_k ∝ √((1−η)^k/k!) η^{n/2} a^k`, `η` the transmissivity), and dephasing applies a random
+//! phase `e^{iφn}` with `φ ~ N(0, σ²)`. Averaged over shots the trajectories reproduce the
+//! channels; individually they carry the shot-to-shot variation a deterministic run cannot.
+//! `"mixed"` modes only hold populations, so they take the loss channel on those populations
+//! directly (dephasing leaves them unchanged).
+//!
+//! Shots, and the modes within a step, evolve in parallel. Each work item has its own RNG
+//! stream, fixed before any work starts, so results do not depend on thread scheduling.
+
+use super::{
+    binomial_pmf, sample_gaussian, sample_uniform, PhotonLossChannel, SimulatorNoiseConfig,
+};
+use crate::state::{
+    fock_coefficients, unpair, QuantumMode, QuantumState, ReferenceStateEvolver, StateEvolver,
+};
+use anyhow::Result;
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Provenance key counting the photons a trajectory has lost from `mode_id`.
+fn loss_key(mode_id: &str) -> String {
+    format!("trajectory_loss:{}", mode_id)
+}
+
+/// Per-step stochastic loss and dephasing applied to every mode.
+#[derive(Clone, Debug)]
+pub struct TrajectorySimulator {
+    /// Photon loss per step
+    pub loss: PhotonLossChannel,
+    /// Variance σ² of the random phase per step (rad²)
+    pub dephasing_variance: f64,
+}
+
+/// One shot's final state, with `trajectory_loss:<mode>` provenance counting lost photons.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrajectoryShot {
+    pub seed: u64,
+    pub final_state: QuantumState,
+}
+
+impl TrajectoryShot {
+    /// Photons this shot lost from `mode_id`.
+    pub fn photons_lost(&self, mode_id: &str) -> u32 {
+        self.final_state
+            .provenance
+            .get(&loss_key(mode_id))
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Mean photon number `⟨n⟩` of `mode_id` in this shot's final state.
+    pub fn mean_photon_number(&self, mode_id: &str) -> Result<f64> {
+        let mode = self
+            .final_state
+            .modes
+            .iter()
+            .find(|m| m.mode_id == mode_id)
+            .ok_or_else(|| anyhow::anyhow!("mode {} not in trajectory state", mode_id))?;
+        Ok(fock_coefficients(mode)?
+            .iter()
+            .map(|(n, c)| *n as f64 * c.norm_sqr())
+            .sum())
+    }
+}
+
+impl TrajectorySimulator {
+    pub fn new(loss_probability: f64, dephasing_variance: f64) -> Self {
+        Self {
+            loss: PhotonLossChannel {
+                loss_probability: loss_probability.clamp(0.0, 1.0),
+            },
+            dephasing_variance: dephasing_variance.max(0.0),
+        }
+    }
+
+    /// Steps of `distance_cm` propagation and `duration_s` LO phase diffusion under `config`
+    /// (phase variance `π·linewidth·duration`, as `PhaseNoise::evolve`)
+    pub fn from_noise_config(
+        config: &SimulatorNoiseConfig,
+        distance_cm: f64,
+        duration_s: f64,
+    ) -> Self {
+        Self {
+            loss: PhotonLossChannel::from_distance(distance_cm, config.loss_rate_per_cm),
+            dephasing_variance: PI * config.lo_linewidth * duration_s,
+        }
+    }
+
+    /// One trajectory step on every mode with amplitudes. Losing photons from a mode breaks
+    /// its PDC pairing. Modes step in parallel, each seeded by one draw from `rng`.
+    pub fn step(&self, state: &QuantumState, rng: &mut StdRng) -> Result<QuantumState> {
+        let mut out = state.clone();
+        let seeds: Vec<u64> = out.modes.iter().map(|_| rng.gen()).collect();
+        let outcomes = out
+            .modes
+            .par_iter_mut()
+            .zip(seeds)
+            .map(|(mode, mode_seed)| {
+                if mode.amplitudes.is_none() {
+                    return Ok(None);
+                }
+                if mode.mode_type == "mixed" {
+                    self.attenuate_populations(mode)?;
+                    return Ok(Some(None));
+                }
+                let mut rng = StdRng::seed_from_u64(mode_seed);
+                let lost = self.jump(mode, &mut rng)?;
+                self.dephase(mode, &mut rng)?;
+                Ok(Some(Some(lost)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for (mode, outcome) in out.modes.iter().zip(outcomes) {
+            // `None`: no amplitudes to step; `Some(None)`: populations only, nothing counted
+            let Some(lost) = outcome else {
+                continue;
+            };
+            if self.loss.loss_probability > 0.0 {
+                unpair(&mut out.provenance, &mode.mode_id);
+            }
+            if let Some(lost) = lost.filter(|&k| k > 0) {
+                let total = out
+                    .provenance
+                    .get(&loss_key(&mode.mode_id))
+                    .and_then(|n| n.parse::<u32>().ok())
+                    .unwrap_or(0)
+                    + lost;
+                out.provenance
+                    .insert(loss_key(&mode.mode_id), total.to_string());
+            }
+        }
+        Ok(out)
+    }
+
+    /// Run `shots` trajectories of `gates` (`(gate, params)` for `ReferenceStateEvolver`) from
+    /// `initial`, stepping the noise after each gate. Shot `s` is seeded with `seed + s`; shots
+    /// run in parallel and come back in shot order.
+    pub fn run_shots(
+        &self,
+        initial: &QuantumState,
+        gates: &[(String, HashMap<String, f64>)],
+        shots: usize,
+        seed: u64,
+    ) -> Result<Vec<TrajectoryShot>> {
+        let evolver = ReferenceStateEvolver;
+        (0..shots)
+            .into_par_iter()
+            .map(|shot| {
+                let shot_seed = seed.wrapping_add(shot as u64);
+                let mut rng = StdRng::seed_from_u64(shot_seed);
+                let mut state = initial.clone();
+                state.seed = Some(shot_seed);
+                for (gate, params) in gates {
+                    state = evolver.evolve_state(&state, gate, params)?;
+                    state = self.step(&state, &mut rng)?;
+                }
+                Ok(TrajectoryShot {
+                    seed: shot_seed,
+                    final_state: state,
+                })
+            })
+            .collect()
+    }
+
+    /// Draw the number of lost photons `k` and collapse onto `A_k ψ`, returning `k`.
+    fn jump(&self, mode: &mut QuantumMode, rng: &mut StdRng) -> Result<u32> {
+        let coeffs = fock_coefficients(mode)?;
+        let max_n = coeffs.iter().map(|(n, _)| *n).max().unwrap_or(0);
+        // branches[k][m]: amplitude of |m⟩ after losing k photons (unnormalised)
+        let mut branches = vec![vec![Complex64::new(0.0, 0.0); max_n + 1]; max_n + 1];
+        for (n, c) in &coeffs {
+            for (k, p) in binomial_pmf(*n as u32, self.loss.loss_probability)
+                .iter()
+                .enumerate()
+            {
+                branches[k][n - k] += c * p.sqrt();
+            }
+        }
+        let weights: Vec<f64> = branches
+            .iter()
+            .map(|b| b.iter().map(|a| a.norm_sqr()).sum())
+            .collect();
+        let mut u = sample_uniform(rng) * weights.iter().sum::<f64>();
+        let k = weights
+            .iter()
+            .position(|w| {
+                u -= w;
+                u < 0.0 && *w > 0.0
+            })
+            .unwrap_or_else(|| weights.iter().rposition(|w| *w > 0.0).unwrap_or(0));
+        let norm = weights[k].sqrt();
+        mode.photon_numbers = Some((0..=max_n as u32).collect());
+        mode.amplitudes = Some(branches[k].iter().map(|a| a / norm).collect());
+        Ok(k as u32)
+    }
+
+    /// Random phase `e^{iφn}`, `φ ~ N(0, σ²)`.
+    fn dephase(&self, mode: &mut QuantumMode, rng: &mut StdRng) -> Result<()> {
+        if self.dephasing_variance == 0.0 {
+            return Ok(());
+        }
+        let phi = sample_gaussian(rng) * self.dephasing_variance.sqrt();
+        let coeffs = fock_coefficients(mode)?;
+        mode.amplitudes = Some(
+            coeffs
+                .iter()
+                .map(|(n, c)| c * Complex64::from_polar(1.0, phi * *n as f64))
+                .collect(),
+        );
+        Ok(())
+    }
+
+    /// Loss channel on a mixed mode's populations `p_m = Σ_n p_n P(n−m lost | n)`.
+    fn attenuate_populations(&self, mode: &mut QuantumMode) -> Result<()> {
+        let coeffs = fock_coefficients(mode)?;
+        let max_n = coeffs.iter().map(|(n, _)| *n).max().unwrap_or(0);
+        let mut populations = vec![0.0; max_n + 1];
+        for (n, c) in &coeffs {
+            for (k, p) in binomial_pmf(*n as u32, self.loss.loss_probability)
+                .iter()
+                .enumerate()
+            {
+                populations[n - k] += c.norm_sqr() * p;
+            }
+        }
+        mode.photon_numbers = Some((0..=max_n as u32).collect());
+        mode.amplitudes = Some(
+            populations
+                .iter()
+                .map(|p| Complex64::new(p.sqrt(), 0.0))
+                .collect(),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::CoherenceWindow;
+
+    fn fock_state(n: usize) -> QuantumState {
+        let mut amplitudes = vec![Complex64::new(0.0, 0.0); n + 1];
+        amplitudes[n] = Complex64::new(1.0, 0.0);
+        QuantumState {
+            id: "traj".to_string(),
+            modes: vec![QuantumMode {
+                mode_id: "0".to_string(),
+                mode_type: "quantum_fock".to_string(),
+                photon_numbers: Some((0..=n as u32).collect()),
+                amplitudes: Some(amplitudes),
+            }],
+            coherence_window: CoherenceWindow::new("cw".to_string(), 1_000_000),
+            seed: None,
+            provenance: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_loss_trajectories_reproduce_the_channel() {
+        // |4⟩ through three steps of 20% loss: binomial with transmissivity 0.8³.
+        let sim = TrajectorySimulator::new(0.2, 0.0);
+        let ps = (
+            "PS".to_string(),
+            HashMap::from([("mode_id".to_string(), 0.0), ("phase".to_string(), 0.3)]),
+        );
+        let gates = vec![ps.clone(), ps.clone(), ps];
+        let shots = sim.run_shots(&fock_state(4), &gates, 2000, 7).unwrap();
+
+        let eta = 0.8f64.powi(3);
+        let lost: Vec<f64> = shots.iter().map(|s| s.photons_lost("0") as f64).collect();
+        let mean = lost.iter().sum::<f64>() / lost.len() as f64;
+        let variance = lost.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / lost.len() as f64;
+        assert!((mean - 4.0 * (1.0 - eta)).abs() < 0.1, "mean lost {}", mean);
+        assert!(
+            (variance - 4.0 * eta * (1.0 - eta)).abs() < 0.15,
+            "variance {}",
+            variance
+        );
+        // Fock inputs stay Fock states: every trajectory ends with a definite photon number.
+        for shot in &shots {
+            let n = shot.mean_photon_number("0").unwrap();
+            assert!((n - (4 - shot.photons_lost("0")) as f64).abs() < 1e-9);
+        }
+
+        // Seeded: the same seed replays the same trajectories.
+        let again = sim.run_shots(&fock_state(4), &gates, 20, 7).unwrap();
+        for (a, b) in shots.iter().zip(&again) {
+            assert_eq!(a.photons_lost("0"), b.photons_lost("0"));
+        }
+    }
+
+    #[test]
+    fn test_parallel_trajectories_do_not_depend_on_thread_count() {
+        // Three modes, so steps split across threads as well as shots.
+        let mut state = fock_state(3);
+        for id in ["1", "2"] {
+            let mut mode = state.modes[0].clone();
+            mode.mode_id = id.to_string();
+            state.modes.push(mode);
+        }
+        let sim = TrajectorySimulator::new(0.3, 0.2);
+        let ps = (
+            "PS".to_string(),
+            HashMap::from([("mode_id".to_string(), 1.0), ("phase".to_string(), 0.3)]),
+        );
+        let gates = vec![ps.clone(), ps];
+
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let shots = sim.run_shots(&state, &gates, 200, 5).unwrap();
+                serde_json::to_string(&shots).unwrap()
+            })
+        };
+        assert_eq!(run(1), run(4));
+    }
+
+    #[test]
+    fn test_dephasing_trajectories_randomise_phase() {
+        let mut state = fock_state(1);
+        state.modes[0].amplitudes = Some(vec![
+            Complex64::new(0.5f64.sqrt(), 0.0),
+            Complex64::new(0.5f64.sqrt(), 0.0),
+        ]);
+        let sim = TrajectorySimulator::new(0.0, 0.5);
+        let mut rng = StdRng::seed_from_u64(11);
+        // ⟨c₀* c₁⟩ over trajectories decays as e^{−σ²/2}; each trajectory stays pure.
+        let coherence: Complex64 = (0..4000)
+            .map(|_| {
+                let out = sim.step(&state, &mut rng).unwrap();
+                let a = out.modes[0].amplitudes.as_ref().unwrap();
+                assert!((a[0].norm_sqr() + a[1].norm_sqr() - 1.0).abs() < 1e-12);
+                a[0].conj() * a[1]
+            })
+            .sum::<Complex64>()
+            / 4000.0;
+        assert!((coherence.re - 0.5 * (-0.25f64).exp()).abs() < 0.02);
+    }
+}
@@ -0,0 +1,224 @@
+//! Conversion between the engine's mode-list `QuantumState` and the backend
+//! `quantum::QuantumState`.
+//!
+//! Engine modes are independent single-mode Fock states, so a state maps onto a DV product
+//! state with one qudit per mode (level `k` = `k` photons), stored as a bond-1 MPS so wide
+//! graphs never expand densely. The reverse direction keeps each mode's reduced state: pure
+//! modes keep their complex amplitudes, entangled or mixed modes become `"mixed"` modes
+//! holding the square roots of their photon-number populations.
+
+use super::{
+    fock_coefficients, CoherenceWindow, MeasurementKind, MeasurementOutcome, QuantumMode,
+    QuantumState, PURE_TOLERANCE,
+};
+use crate::quantum::{self, mps::MatrixProductState, BasisType, HomodyneAxis, MeasurementResult};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+use std::collections::HashMap;
+
+impl CoherenceWindow {
+    /// Backend window for `state_id` with the same duration, decoherence timescale (as T2) and
+    /// idle budget (as grace period).
+    pub fn to_backend_window(&self, state_id: &str) -> quantum::CoherenceWindow {
+        let mut window = quantum::CoherenceWindow::new(state_id.to_string(), self.duration_ns);
+        window.t2_ns = self.decoherence_timescale_ns.map(|t| t as u64);
+        if let Some(idle) = self.idle_time_budget_ns {
+            window.grace_period_ns = idle;
+        }
+        window
+    }
+}
+
+impl QuantumState {
+    /// The listed modes, in the given order, as a state of their own.
+    pub fn select_modes(&self, mode_ids: &[String]) -> Result<QuantumState> {
+        let modes = mode_ids
+            .iter()
+            .map(|id| {
+                self.modes
+                    .iter()
+                    .find(|m| &m.mode_id == id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("mode '{}' not in state {}", id, self.id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(QuantumState {
+            modes,
+            ..self.clone()
+        })
+    }
+
+    /// Backend form: a DV product state with one qudit per mode (dimension = highest photon
+    /// number + 1). The id, seed and coherence duration carry over, as do `hardware_revision`
+    /// and `calibration_id` provenance entries.
+    pub fn to_backend_state(&self) -> Result<quantum::QuantumState> {
+        let factors = self
+            .modes
+            .iter()
+            .map(|mode| {
+                let coeffs = fock_coefficients(mode)?;
+                let dim = coeffs.iter().map(|(n, _)| n + 1).max().unwrap_or(1);
+                let mut fock = vec![Complex64::new(0.0, 0.0); dim];
+                for (n, c) in coeffs {
+                    fock[n] += c;
+                }
+                Ok(fock)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let labels = self
+            .modes
+            .iter()
+            .zip(&factors)
+            .map(|(mode, fock)| (mode.mode_id.clone(), fock.len()))
+            .collect();
+        let mut state = quantum::QuantumState::new_dv(
+            labels,
+            self.seed.unwrap_or(0),
+            self.coherence_window.duration_ns,
+        );
+        state.state_id = self.id.clone();
+        if let Some(revision) = self.provenance.get("hardware_revision") {
+            state.hardware_revision = revision.clone();
+        }
+        if let Some(calibration) = self.provenance.get("calibration_id") {
+            state.calibration_id = calibration.clone();
+        }
+        state.set_mps(MatrixProductState::product(&factors)?)?;
+        Ok(state)
+    }
+
+    /// Engine form of a DV backend state, one mode per qudit. Gaussian states have no Fock
+    /// amplitudes here and are rejected.
+    pub fn from_backend_state(state: &quantum::QuantumState) -> Result<QuantumState> {
+        if state.state_type != quantum::StateType::DV {
+            return Err(anyhow!(
+                "state {} is Gaussian; only DV states convert to Fock modes",
+                state.state_id
+            ));
+        }
+        let singles: Vec<Vec<usize>> = (0..state.mode_labels.len()).map(|q| vec![q]).collect();
+        let modes = state
+            .reduced_dv_densities(&singles)?
+            .into_iter()
+            .zip(&state.mode_labels)
+            .map(|(rho, label)| {
+                let dim = rho.dims[0];
+                let populations: Vec<f64> = (0..dim).map(|n| rho.rho[n][n].re.max(0.0)).collect();
+                let (mode_type, amplitudes) = if rho.purity() > 1.0 - PURE_TOLERANCE {
+                    // ρ = |ψ⟩⟨ψ|: the column of the largest population is ψ up to a phase.
+                    let k = (0..dim)
+                        .max_by(|&a, &b| populations[a].total_cmp(&populations[b]))
+                        .unwrap_or(0);
+                    let psi = (0..dim)
+                        .map(|n| rho.rho[n][k] / populations[k].sqrt())
+                        .collect();
+                    ("quantum_fock", psi)
+                } else {
+                    (
+                        "mixed",
+                        populations
+                            .iter()
+                            .map(|p| Complex64::new(p.sqrt(), 0.0))
+                            .collect(),
+                    )
+                };
+                QuantumMode {
+                    mode_id: label.clone(),
+                    mode_type: mode_type.to_string(),
+                    photon_numbers: Some((0..dim as u32).collect()),
+                    amplitudes: Some(amplitudes),
+                }
+            })
+            .collect();
+        let duration_ns = (state.coherence_deadline - state.timestamp)
+            .num_nanoseconds()
+            .unwrap_or(0)
+            .max(0) as u64;
+        Ok(QuantumState {
+            id: state.state_id.clone(),
+            modes,
+            coherence_window: CoherenceWindow::new(format!("cw-{}", state.state_id), duration_ns),
+            seed: Some(state.seed),
+            provenance: HashMap::from([
+                ("origin".to_string(), "quantum::QuantumState".to_string()),
+                (
+                    "hardware_revision".to_string(),
+                    state.hardware_revision.clone(),
+                ),
+                ("calibration_id".to_string(), state.calibration_id.clone()),
+            ]),
+        })
+    }
+}
+
+impl MeasurementOutcome {
+    /// Backend form of this outcome on `mode_label`, keyed like `QuantumBackend::measure`:
+    /// photon counts are computational `DiscreteOutcome`s, homodyne values a `ContinuousValue`
+    /// on the Q or P axis and heterodyne values `<mode>:q` / `<mode>:p`.
+    pub fn to_backend_outcome(&self, mode_label: &str) -> Result<quantum::MeasurementOutcome> {
+        let quadratures = || {
+            self.quadratures
+                .as_deref()
+                .ok_or_else(|| anyhow!("{:?} outcome has no quadratures", self.kind))
+        };
+        let (basis, results) = match self.kind {
+            MeasurementKind::PhotonCount => (
+                BasisType::Computational,
+                vec![(
+                    mode_label.to_string(),
+                    MeasurementResult::DiscreteOutcome(self.photon_count as usize),
+                )],
+            ),
+            MeasurementKind::Homodyne { angle } => {
+                let axis = if angle.abs() < 1e-12 {
+                    HomodyneAxis::Q
+                } else if (angle - std::f64::consts::FRAC_PI_2).abs() < 1e-12 {
+                    HomodyneAxis::P
+                } else {
+                    return Err(anyhow!(
+                        "homodyne angle {} has no backend axis (Q or P only)",
+                        angle
+                    ));
+                };
+                let x = *quadratures()?
+                    .first()
+                    .ok_or_else(|| anyhow!("homodyne outcome has no value"))?;
+                (
+                    BasisType::Homodyne { axis },
+                    vec![(
+                        mode_label.to_string(),
+                        MeasurementResult::ContinuousValue(x),
+                    )],
+                )
+            }
+            MeasurementKind::Heterodyne => match quadratures()? {
+                [q, p] => (
+                    BasisType::Heterodyne,
+                    vec![
+                        (
+                            format!("{}:q", mode_label),
+                            MeasurementResult::ContinuousValue(*q),
+                        ),
+                        (
+                            format!("{}:p", mode_label),
+                            MeasurementResult::ContinuousValue(*p),
+                        ),
+                    ],
+                ),
+                other => {
+                    return Err(anyhow!(
+                        "heterodyne outcome needs [q, p], got {} values",
+                        other.len()
+                    ))
+                }
+            },
+        };
+        Ok(quantum::MeasurementOutcome::new(
+            uuid::Uuid::new_v4().to_string(),
+            vec![mode_label.to_string()],
+            basis,
+            results.into_iter().collect(),
+            self.seed_used.unwrap_or(0),
+        ))
+    }
+}
@@ -0,0 +1,119 @@
+//! Decoherence models for coherence windows.
+//!
+//! A model maps elapsed time to the remaining coherence (the fidelity estimate against the
+//! state at the start of the window) for a given timescale `T`. Every model is normalised so
+//! that coherence is `1/e` at `t = T`; they differ in shape: Markovian exponential decay,
+//! Gaussian decay from quasi-static noise, and the log-corrected Gaussian of 1/f dephasing.
+//! Windows select a model by name (`CoherenceWindow::decoherence_model`).
+
+use anyhow::{anyhow, Result};
+
+/// Coherence decay law selectable per coherence window.
+pub trait DecoherenceModel: Send + Sync {
+    /// Name accepted by [`decoherence_model`] and stored on the window.
+    fn name(&self) -> &'static str;
+
+    /// Remaining coherence in `[0, 1]` after `elapsed_ns` for timescale `timescale_ns`.
+    fn coherence(&self, elapsed_ns: f64, timescale_ns: f64) -> f64;
+}
+
+/// Markovian dephasing: `e^{−t/T}`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExponentialDecoherence;
+
+impl DecoherenceModel for ExponentialDecoherence {
+    fn name(&self) -> &'static str {
+        "exponential"
+    }
+
+    fn coherence(&self, elapsed_ns: f64, timescale_ns: f64) -> f64 {
+        (-elapsed_ns.max(0.0) / timescale_ns).exp()
+    }
+}
+
+/// Quasi-static (inhomogeneous) dephasing: `e^{−(t/T)²}`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GaussianDecoherence;
+
+impl DecoherenceModel for GaussianDecoherence {
+    fn name(&self) -> &'static str {
+        "gaussian"
+    }
+
+    fn coherence(&self, elapsed_ns: f64, timescale_ns: f64) -> f64 {
+        (-(elapsed_ns.max(0.0) / timescale_ns).powi(2)).exp()
+    }
+}
+
+/// 1/f dephasing with infrared cutoff `τ_ir`: `e^{−(t/T)² ln(1 + τ_ir/t) / ln(1 + τ_ir/T)}`.
+/// The logarithm makes early decay faster than Gaussian and late decay slower.
+#[derive(Debug, Clone, Copy)]
+pub struct OneOverFDephasing {
+    /// Longest noise correlation time (inverse low-frequency cutoff), ns.
+    pub infrared_cutoff_ns: f64,
+}
+
+impl Default for OneOverFDephasing {
+    fn default() -> Self {
+        Self {
+            infrared_cutoff_ns: 1e9, // 1 Hz
+        }
+    }
+}
+
+impl DecoherenceModel for OneOverFDephasing {
+    fn name(&self) -> &'static str {
+        "1/f"
+    }
+
+    fn coherence(&self, elapsed_ns: f64, timescale_ns: f64) -> f64 {
+        if elapsed_ns <= 0.0 {
+            return 1.0;
+        }
+        let log = |t: f64| (1.0 + self.infrared_cutoff_ns / t).ln();
+        (-(elapsed_ns / timescale_ns).powi(2) * log(elapsed_ns) / log(timescale_ns)).exp()
+    }
+}
+
+/// Model registered under `name` (`"exponential"`, `"gaussian"`, `"1/f"`; case-insensitive,
+/// `"one_over_f"` is accepted for 1/f).
+pub fn decoherence_model(name: &str) -> Result<Box<dyn DecoherenceModel>> {
+    match name.to_ascii_lowercase().as_str() {
+        "exponential" => Ok(Box::new(ExponentialDecoherence)),
+        "gaussian" => Ok(Box::new(GaussianDecoherence)),
+        "1/f" | "one_over_f" => Ok(Box::new(OneOverFDephasing::default())),
+        other => Err(anyhow!("unknown decoherence model '{}'", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_models_share_the_timescale() {
+        for name in ["exponential", "gaussian", "1/f"] {
+            let model = decoherence_model(name).unwrap();
+            assert_eq!(model.name(), name);
+            assert_eq!(model.coherence(0.0, 100.0), 1.0);
+            assert!((model.coherence(100.0, 100.0) - (-1.0f64).exp()).abs() < 1e-12);
+            assert!(model.coherence(50.0, 100.0) > model.coherence(150.0, 100.0));
+        }
+        assert!(decoherence_model("lorentzian").is_err());
+    }
+
+    #[test]
+    fn test_model_shapes() {
+        let (exp, gauss, one_over_f) = (
+            ExponentialDecoherence,
+            GaussianDecoherence,
+            OneOverFDephasing::default(),
+        );
+        // Before T, Gaussian decay is slowest; 1/f's log factor makes it decay faster.
+        assert!(gauss.coherence(10.0, 100.0) > one_over_f.coherence(10.0, 100.0));
+        assert!(gauss.coherence(10.0, 100.0) > exp.coherence(10.0, 100.0));
+        // After T, Gaussian falls fastest and 1/f lags behind it.
+        assert!(one_over_f.coherence(300.0, 100.0) > gauss.coherence(300.0, 100.0));
+        assert!(exp.coherence(300.0, 100.0) > gauss.coherence(300.0, 100.0));
+    }
+}
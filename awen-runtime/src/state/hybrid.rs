@@ -0,0 +1,122 @@
+//! Hybrid CV–DV states.
+//!
+//! Every mode is held in a truncated Fock basis, but modes carry their role in `mode_type`:
+//! Gaussian gates (squeezing, displacement) leave a continuous-variable `"cv"` mode, while
+//! photon-number resources are discrete-variable `"quantum_fock"` modes. A state mixing both
+//! is hybrid. The conversion gates move between the two: `HERALD` photon-counts a mode and
+//! leaves `|n⟩` as a DV resource (in its PDC partner, or in the mode itself), and
+//! `PHOTON_SUBTRACT` taps a photon off a CV mode, keeping it CV but non-Gaussian.
+
+use super::{fock_coefficients, mode_photon_numbers, unpair, QuantumMode, QuantumState};
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
+
+/// `mode_type` of continuous-variable modes.
+pub const CV_MODE: &str = "cv";
+
+/// `mode_type` of discrete-variable (photon-number) modes.
+pub const DV_MODE: &str = "quantum_fock";
+
+impl QuantumState {
+    /// Ids of the continuous-variable modes.
+    pub fn cv_modes(&self) -> Vec<&str> {
+        self.modes_of_type(CV_MODE)
+    }
+
+    /// Ids of the discrete-variable modes.
+    pub fn dv_modes(&self) -> Vec<&str> {
+        self.modes_of_type(DV_MODE)
+    }
+
+    /// Whether the state holds both CV and DV modes.
+    pub fn is_hybrid(&self) -> bool {
+        !self.cv_modes().is_empty() && !self.dv_modes().is_empty()
+    }
+
+    fn modes_of_type(&self, mode_type: &str) -> Vec<&str> {
+        self.modes
+            .iter()
+            .filter(|m| m.mode_type == mode_type)
+            .map(|m| m.mode_id.as_str())
+            .collect()
+    }
+}
+
+/// `|count⟩` over the mode's current photon-number support (extended to reach `count`).
+fn set_number_state(mode: &mut QuantumMode, count: usize) {
+    let mut numbers = mode_photon_numbers(mode);
+    if !numbers.contains(&count) {
+        numbers = (0..=numbers.iter().copied().max().unwrap_or(0).max(count)).collect();
+    }
+    mode.amplitudes = Some(
+        numbers
+            .iter()
+            .map(|n| Complex64::new(if *n == count { 1.0 } else { 0.0 }, 0.0))
+            .collect(),
+    );
+    mode.photon_numbers = Some(numbers.iter().map(|n| *n as u32).collect());
+    mode.mode_type = DV_MODE.to_string();
+}
+
+/// Herald `count` photons on mode `i`, returning the herald probability. A PDC-paired mode is
+/// absorbed by the counter and its partner left in `|count⟩`; an unpaired mode is projected
+/// onto `|count⟩` itself. Either way the heralded mode becomes a DV resource.
+pub(super) fn herald(state: &mut QuantumState, i: usize, count: usize) -> Result<f64> {
+    let probability = fock_coefficients(&state.modes[i])?
+        .iter()
+        .filter(|(n, _)| *n == count)
+        .map(|(_, c)| c.norm_sqr())
+        .sum::<f64>();
+    if probability < f64::EPSILON {
+        return Err(anyhow!(
+            "mode {} never heralds {} photons",
+            state.modes[i].mode_id,
+            count
+        ));
+    }
+    let mode_id = state.modes[i].mode_id.clone();
+    match unpair(&mut state.provenance, &mode_id) {
+        Some(partner) => {
+            set_number_state(&mut state.modes[i], 0);
+            let target = state
+                .modes
+                .iter_mut()
+                .find(|m| m.mode_id == partner)
+                .ok_or_else(|| anyhow!("PDC partner {} of {} not in state", partner, mode_id))?;
+            set_number_state(target, count);
+        }
+        None => set_number_state(&mut state.modes[i], count),
+    }
+    Ok(probability)
+}
+
+/// Subtract one photon by tapping `reflectivity` of the mode onto a single-photon counter and
+/// heralding one click: `c'_{n−1} ∝ √n · t^{n−1} · r · c_n` (`t² = 1 − r²`), which tends to
+/// `a|ψ⟩` for a weak tap. A mixed mode keeps its type (the same map acts on its populations);
+/// pure modes come out CV. Returns the herald probability.
+pub(super) fn subtract_photon(mode: &mut QuantumMode, reflectivity: f64) -> Result<f64> {
+    if !(0.0..=1.0).contains(&reflectivity) || reflectivity == 0.0 {
+        return Err(anyhow!(
+            "photon subtraction reflectivity must be in (0, 1], got {}",
+            reflectivity
+        ));
+    }
+    let coeffs = fock_coefficients(mode)?;
+    let (t, r) = ((1.0 - reflectivity).sqrt(), reflectivity.sqrt());
+    let max_n = coeffs.iter().map(|(n, _)| *n).max().unwrap_or(0);
+    let mut subtracted = vec![Complex64::new(0.0, 0.0); max_n.max(1)];
+    for (n, c) in coeffs.iter().filter(|(n, _)| *n > 0) {
+        subtracted[n - 1] += c * (*n as f64).sqrt() * t.powi(*n as i32 - 1) * r;
+    }
+    let probability: f64 = subtracted.iter().map(|c| c.norm_sqr()).sum();
+    if probability < f64::EPSILON {
+        return Err(anyhow!("no photon to subtract from mode {}", mode.mode_id));
+    }
+    let norm = probability.sqrt();
+    mode.photon_numbers = Some((0..subtracted.len() as u32).collect());
+    mode.amplitudes = Some(subtracted.iter().map(|c| c / norm).collect());
+    if mode.mode_type != "mixed" {
+        mode.mode_type = CV_MODE.to_string();
+    }
+    Ok(probability)
+}
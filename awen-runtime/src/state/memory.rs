@@ -2,6 +2,7 @@
 
 use super::QuantumMode;
 use anyhow::{bail, Result};
+use num_complex::Complex64;
 use serde::{Deserialize, Serialize};
 
 /// Memory primitive trait
@@ -259,7 +260,7 @@ impl MemoryPrimitive for HybridRegister {
         // Photonic → Electronic: measure and store classical result
         let classical_value = if let Some(amps) = &mode.amplitudes {
             // Store first amplitude magnitude as classical value
-            amps.first().map(|a| a.abs()).unwrap_or(0.0)
+            amps.first().map(|a| a.norm()).unwrap_or(0.0)
         } else {
             0.0
         };
@@ -288,8 +289,7 @@ impl MemoryPrimitive for HybridRegister {
             mode_id: format!("{}_output", self.id),
             mode_type: "classical".to_string(),
             photon_numbers: Some(vec![0, 1]),
-            amplitudes: Some(vec![classical_value * self.fidelity]),
-            phases: Some(vec![0.0]),
+            amplitudes: Some(vec![Complex64::new(classical_value * self.fidelity, 0.0)]),
         };
 
         Ok(Some(mode))
@@ -316,8 +316,7 @@ mod tests {
             mode_id: "test_mode".to_string(),
             mode_type: "classical".to_string(),
             photon_numbers: None,
-            amplitudes: Some(vec![1.0]),
-            phases: Some(vec![0.0]),
+            amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
         };
 
         // Write at t=0
@@ -332,7 +331,7 @@ mod tests {
 
         // Check loss applied
         let out_mode = result.unwrap();
-        let out_amp = out_mode.amplitudes.unwrap()[0];
+        let out_amp = out_mode.amplitudes.unwrap()[0].norm();
         let expected_amp = 1.0 * 10.0_f64.powf(-0.5 / 10.0).sqrt();
         assert!((out_amp - expected_amp).abs() < 0.01);
     }
@@ -345,8 +344,7 @@ mod tests {
             mode_id: "test_mode".to_string(),
             mode_type: "quantum_fock".to_string(),
             photon_numbers: Some(vec![0, 1]),
-            amplitudes: Some(vec![1.0]),
-            phases: Some(vec![0.0]),
+            amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
         };
 
         // Write at t=0
@@ -354,7 +352,7 @@ mod tests {
 
         // Read at t=500 (1τ)
         let result = resonator.read(500).unwrap().unwrap();
-        let out_amp = result.amplitudes.unwrap()[0];
+        let out_amp = result.amplitudes.unwrap()[0].norm();
 
         // Expected: write_eff * exp(-500/500) * read_eff
         let expected = 0.95_f64.sqrt() * (-1.0_f64).exp() * 0.9_f64.sqrt();
@@ -386,8 +384,7 @@ mod tests {
             mode_id: "test_mode".to_string(),
             mode_type: "classical".to_string(),
             photon_numbers: None,
-            amplitudes: Some(vec![1.0]),
-            phases: Some(vec![0.0]),
+            amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
         };
 
         // Fill capacity
@@ -1,22 +1,34 @@
 // Quantum State & Coherence Window model (v0.1)
 
+use crate::quantum::gaussian::{hermite_functions, standard_normal};
+use crate::quantum::wigner::laguerre;
+use crate::simulator::HomodyneSimulator;
 use anyhow::Result;
+use num_complex::Complex64;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+mod convert;
+mod decoherence;
+mod hybrid;
 mod memory;
+pub use decoherence::{
+    decoherence_model, DecoherenceModel, ExponentialDecoherence, GaussianDecoherence,
+    OneOverFDephasing,
+};
+pub use hybrid::{CV_MODE, DV_MODE};
 pub use memory::{DelayBuffer, HybridRegister, MemoryPrimitive, ResonatorStore};
 
 /// A photonic quantum state mode: classical or quantum (Fock/mixed).
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct QuantumMode {
     pub mode_id: String,
-    pub mode_type: String, // "classical", "quantum_fock", "mixed"
+    pub mode_type: String, // "classical", "quantum_fock" (DV), "cv", "mixed"
     pub photon_numbers: Option<Vec<u32>>,
-    pub amplitudes: Option<Vec<f64>>,
-    pub phases: Option<Vec<f64>>,
+    /// Fock amplitudes `c_n`, aligned with `photon_numbers` (else indexed by photon number).
+    pub amplitudes: Option<Vec<Complex64>>,
 }
 
 /// Coherence window: temporal validity of quantum state before decoherence.
@@ -31,6 +43,13 @@ pub struct CoherenceWindow {
     pub idle_time_budget_ns: Option<u64>,
     pub fidelity_threshold: f64,
     pub notes: Option<String>,
+    /// Decay law of the fidelity estimate (see [`decoherence_model`]).
+    #[serde(default = "default_decoherence_model")]
+    pub decoherence_model: String,
+}
+
+fn default_decoherence_model() -> String {
+    ExponentialDecoherence.name().to_string()
 }
 
 impl CoherenceWindow {
@@ -46,11 +65,22 @@ impl CoherenceWindow {
             idle_time_budget_ns: None,
             fidelity_threshold: 0.0,
             notes: None,
+            decoherence_model: default_decoherence_model(),
         }
     }
+
+    /// Fidelity estimate after `elapsed_ns` inside the window, from the window's decoherence
+    /// model over `decoherence_timescale_ns` (the window duration when unset).
+    pub fn fidelity_after(&self, elapsed_ns: f64) -> Result<f64> {
+        let timescale = self
+            .decoherence_timescale_ns
+            .unwrap_or(self.duration_ns as f64);
+        Ok(decoherence_model(&self.decoherence_model)?.coherence(elapsed_ns, timescale))
+    }
 }
 
-/// A quantum state snapshot: modes + coherence window + provenance.
+/// A quantum state snapshot: modes + coherence window + provenance. Backends work on
+/// `quantum::QuantumState`; see `to_backend_state` / `from_backend_state` for the mapping.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct QuantumState {
     pub id: String,
@@ -60,6 +90,38 @@ pub struct QuantumState {
     pub provenance: HashMap<String, String>,
 }
 
+/// Observable read out by a DETECTOR node.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MeasurementKind {
+    /// Photon counting: categorical sampling over the Fock amplitudes.
+    #[default]
+    PhotonCount,
+    /// Quadrature `x_θ = q cos θ + p sin θ` (ħ = 1, vacuum variance ½).
+    Homodyne { angle: f64 },
+    /// Joint `(q, p)` readout through a balanced split against vacuum: samples the Husimi Q
+    /// function, so each quadrature carries an extra ½ of variance (vacuum variance 1).
+    Heterodyne,
+}
+
+impl MeasurementKind {
+    /// Kind selected by a DETECTOR node's `measurement` field (`photon_count`, `homodyne`,
+    /// `heterodyne`); homodyne reads its local-oscillator angle from `params["angle"]`.
+    pub fn from_node(measurement: Option<&str>, params: &HashMap<String, f64>) -> Result<Self> {
+        match measurement
+            .map(|m| m.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("photon_count") => Ok(MeasurementKind::PhotonCount),
+            Some("homodyne") => Ok(MeasurementKind::Homodyne {
+                angle: params.get("angle").copied().unwrap_or(0.0),
+            }),
+            Some("heterodyne") => Ok(MeasurementKind::Heterodyne),
+            Some(other) => Err(anyhow::anyhow!("unknown measurement kind: {}", other)),
+        }
+    }
+}
+
 /// Measurement outcome on a mode.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MeasurementOutcome {
@@ -68,6 +130,15 @@ pub struct MeasurementOutcome {
     pub probability: f64,
     pub collapsed_state: Option<QuantumState>,
     pub seed_used: Option<u64>,
+    #[serde(default)]
+    pub kind: MeasurementKind,
+    /// Continuous outcomes: `[x_θ]` for homodyne, `[q, p]` for heterodyne.
+    #[serde(default)]
+    pub quadratures: Option<Vec<f64>>,
+    /// Id linking this outcome to the branch decision it drives and the nodes that decision
+    /// runs; set by the engine when the outcome is recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
 }
 
 /// Trait for quantum state evolution and measurement.
@@ -88,13 +159,27 @@ pub trait StateEvolver: Send + Sync {
         seed: Option<u64>,
     ) -> Result<MeasurementOutcome>;
 
+    /// Homodyne or heterodyne readout of a mode. `noise` adds a `HomodyneSimulator`'s LO phase
+    /// jitter, thermal variance and intensity noise on top of the quantum shot noise. The mode is
+    /// absorbed by the detector and left in vacuum; `outcome_index` is 1 when the (first)
+    /// quadrature is non-negative, so conditional branches can act on its sign.
+    fn measure_quadrature(
+        &self,
+        state: &QuantumState,
+        mode_id: &str,
+        kind: MeasurementKind,
+        noise: Option<&HomodyneSimulator>,
+        seed: Option<u64>,
+    ) -> Result<MeasurementOutcome>;
+
     /// Check if state is still coherent (within time window and budgets).
     fn is_coherent(&self, state: &QuantumState, current_time_ns: u64) -> bool;
 }
 
 /// Trait for coherence window tracking.
 pub trait CoherenceManager: Send + Sync {
-    /// Create a coherence window for a subgraph execution.
+    /// Create a coherence window for a subgraph execution, decaying per the named
+    /// [`DecoherenceModel`].
     fn create_window(
         &self,
         start_ns: u64,
@@ -117,12 +202,12 @@ impl StateEvolver for ReferenceStateEvolver {
         params: &HashMap<String, f64>,
     ) -> Result<QuantumState> {
         // Implement unitary gate evolution on quantum modes.
-        // Each gate applies a unitary transformation to mode amplitudes/phases.
+        // Each gate applies a unitary transformation to the modes' Fock amplitudes.
         let mut out = state.clone();
 
         match gate {
             "PS" => {
-                // Phase Shift: modifies phase of selected mode
+                // Phase Shift e^{iφn̂}: photon-number-dependent phase on the selected mode
                 let mode_id = params
                     .get("mode_id")
                     .map(|v| (*v as usize).to_string())
@@ -133,9 +218,10 @@ impl StateEvolver for ReferenceStateEvolver {
                     .ok_or_else(|| anyhow::anyhow!("PS gate requires phase parameter"))?;
 
                 if let Some(mode) = out.modes.iter_mut().find(|m| m.mode_id == mode_id) {
-                    if let Some(ref mut phases) = mode.phases {
-                        for p in phases.iter_mut() {
-                            *p += phase_shift;
+                    let numbers = mode_photon_numbers(mode);
+                    if let Some(ref mut amps) = mode.amplitudes {
+                        for (a, n) in amps.iter_mut().zip(&numbers) {
+                            *a *= Complex64::from_polar(1.0, phase_shift * *n as f64);
                         }
                     }
                 }
@@ -145,48 +231,55 @@ impl StateEvolver for ReferenceStateEvolver {
                 );
             }
             "BS" => {
-                // Beam Splitter: couples two modes via unitary transformation
+                // Beam Splitter: a₁† → cos θ a₁† + sin θ a₂†, a₂† → −sin θ a₁† + cos θ a₂†
                 let mode1 = params
                     .get("mode1")
-                    .map(|v| (*v as usize).to_string())
+                    .map(|v| *v as usize)
                     .ok_or_else(|| anyhow::anyhow!("BS gate requires mode1 parameter"))?;
                 let mode2 = params
                     .get("mode2")
-                    .map(|v| (*v as usize).to_string())
+                    .map(|v| *v as usize)
                     .ok_or_else(|| anyhow::anyhow!("BS gate requires mode2 parameter"))?;
                 let theta = params
                     .get("theta")
                     .copied()
                     .ok_or_else(|| anyhow::anyhow!("BS gate requires theta parameter"))?;
 
-                // BS unitary: [cos(θ), -sin(θ); sin(θ), cos(θ)] applied to mode amplitudes
-                let idx1 = out.modes.iter().position(|m| m.mode_id == mode1);
-                let idx2 = out.modes.iter().position(|m| m.mode_id == mode2);
+                let idx1 = mode_position(&out.modes, mode1);
+                let idx2 = mode_position(&out.modes, mode2);
+                let has_amplitudes = |i: usize| {
+                    out.modes[i]
+                        .amplitudes
+                        .as_ref()
+                        .is_some_and(|a| !a.is_empty())
+                };
 
                 if let (Some(i1), Some(i2)) = (idx1, idx2) {
-                    if i1 < i2 {
-                        let (first, second) = out.modes.split_at_mut(i2);
-                        if let (Some(amp1), Some(amp2)) =
-                            (first[i1].amplitudes.as_mut(), second[0].amplitudes.as_mut())
+                    if i1 != i2 && has_amplitudes(i1) && has_amplitudes(i2) {
+                        let c1 = fock_coefficients(&out.modes[i1])?;
+                        let c2 = fock_coefficients(&out.modes[i2])?;
+                        let cutoff = params
+                            .get("cutoff")
+                            .map(|v| *v as usize)
+                            .unwrap_or_else(|| beam_splitter_cutoff(&c1, &c2));
+                        let joint = beam_splitter_joint(&c1, &c2, theta, cutoff);
+                        let (mode_type, amps1, amps2) = split_joint(&joint).map_err(|e| {
+                            anyhow::anyhow!("BS on modes {} and {}: {}", mode1, mode2, e)
+                        })?;
+                        // Interfering a CV mode leaves CV modes
+                        let mode_type = if mode_type == DV_MODE
+                            && [i1, i2].iter().any(|&i| out.modes[i].mode_type == CV_MODE)
                         {
-                            if !amp1.is_empty() && !amp2.is_empty() {
-                                let a1 = amp1[0] * theta.cos() - amp2[0] * theta.sin();
-                                let a2 = amp1[0] * theta.sin() + amp2[0] * theta.cos();
-                                amp1[0] = a1;
-                                amp2[0] = a2;
-                            }
-                        }
-                    } else if i2 < i1 {
-                        let (first, second) = out.modes.split_at_mut(i1);
-                        if let (Some(amp1), Some(amp2)) =
-                            (second[0].amplitudes.as_mut(), first[i2].amplitudes.as_mut())
-                        {
-                            if !amp1.is_empty() && !amp2.is_empty() {
-                                let a1 = amp1[0] * theta.cos() - amp2[0] * theta.sin();
-                                let a2 = amp1[0] * theta.sin() + amp2[0] * theta.cos();
-                                amp1[0] = a1;
-                                amp2[0] = a2;
-                            }
+                            CV_MODE
+                        } else {
+                            mode_type
+                        };
+                        for (i, amps) in [(i1, amps1), (i2, amps2)] {
+                            let mode = &mut out.modes[i];
+                            mode.mode_type = mode_type.to_string();
+                            mode.photon_numbers = Some((0..=cutoff as u32).collect());
+                            mode.amplitudes = Some(amps);
+                            unpair(&mut out.provenance, &mode.mode_id);
                         }
                     }
                 }
@@ -194,44 +287,150 @@ impl StateEvolver for ReferenceStateEvolver {
                     .insert("last_gate".to_string(), format!("BS(theta={})", theta));
             }
             "SQUEEZING" => {
-                // Squeezing: modifies variance of mode
-                let mode_id = params
+                // Squeezing S(ξ) = exp((ξ* a² − ξ a†²)/2), ξ = r e^{iθ}: r > 0 at θ = 0
+                // squeezes q by e^{−r}, matching `GaussianState::squeeze`
+                let index = params
                     .get("mode_id")
-                    .map(|v| (*v as usize).to_string())
+                    .map(|v| *v as usize)
                     .ok_or_else(|| anyhow::anyhow!("SQUEEZING gate requires mode_id parameter"))?;
                 let r = params
                     .get("r")
                     .copied()
                     .ok_or_else(|| anyhow::anyhow!("SQUEEZING gate requires r parameter"))?;
+                let angle = params.get("angle").copied().unwrap_or(0.0);
+                let cutoff = params.get("cutoff").map(|v| *v as usize);
 
-                if let Some(mode) = out.modes.iter_mut().find(|m| m.mode_id == mode_id) {
-                    if let Some(ref mut amps) = mode.amplitudes {
-                        for a in amps.iter_mut() {
-                            *a *= r.exp(); // squeeze amplitude
-                        }
+                if let Some(i) = mode_position(&out.modes, index) {
+                    let mode = &mut out.modes[i];
+                    if mode.amplitudes.is_some() {
+                        squeeze_mode(mode, r, angle, cutoff)?;
+                        mark_cv(mode);
+                        unpair(&mut out.provenance, &mode.mode_id);
                     }
                 }
-                out.provenance
-                    .insert("last_gate".to_string(), format!("SQUEEZING(r={})", r));
+                out.provenance.insert(
+                    "last_gate".to_string(),
+                    format!("SQUEEZING(r={}, angle={})", r, angle),
+                );
             }
             "PDC" => {
-                // Parametric Down-Conversion: creates entangled pairs (simplified)
-                let pump_id = params
-                    .get("pump_id")
-                    .map(|v| (*v as usize).to_string())
-                    .ok_or_else(|| anyhow::anyhow!("PDC gate requires pump_id parameter"))?;
-                let nonlinearity = params.get("nonlinearity").copied().unwrap_or(0.1);
+                // Parametric Down-Conversion with an undepleted pump: two-mode squeezing of
+                // vacuum signal/idler modes into Σ_n (−tanh r)^n / cosh r |n, n⟩
+                let signal = params
+                    .get("signal_id")
+                    .map(|v| *v as usize)
+                    .ok_or_else(|| anyhow::anyhow!("PDC gate requires signal_id parameter"))?;
+                let idler = params
+                    .get("idler_id")
+                    .map(|v| *v as usize)
+                    .ok_or_else(|| anyhow::anyhow!("PDC gate requires idler_id parameter"))?;
+                let gain = params
+                    .get("gain")
+                    .or_else(|| params.get("nonlinearity"))
+                    .copied()
+                    .unwrap_or(0.1);
+                let cutoff = params
+                    .get("cutoff")
+                    .map(|v| *v as usize)
+                    .unwrap_or_else(|| squeezed_vacuum_pairs(gain).min(MAX_SQUEEZING_CUTOFF));
 
-                if let Some(mode) = out.modes.iter_mut().find(|m| m.mode_id == pump_id) {
-                    if let Some(ref mut amps) = mode.amplitudes {
-                        for a in amps.iter_mut() {
-                            *a *= 1.0 + nonlinearity; // simplified entanglement generation
+                let positions = (
+                    mode_position(&out.modes, signal),
+                    mode_position(&out.modes, idler),
+                );
+                if let (Some(is), Some(ii)) = positions {
+                    if is != ii {
+                        let marginal = two_mode_squeezed_marginal(gain, cutoff);
+                        for i in [is, ii] {
+                            let mode = &mut out.modes[i];
+                            if !fock_coefficients(mode)?
+                                .iter()
+                                .all(|(n, c)| *n == 0 || c.norm_sqr() < PURE_TOLERANCE)
+                            {
+                                return Err(anyhow::anyhow!(
+                                    "PDC requires vacuum signal and idler modes; {} is occupied",
+                                    mode.mode_id
+                                ));
+                            }
+                            mode.mode_type = "mixed".to_string();
+                            mode.photon_numbers = Some((0..=cutoff as u32).collect());
+                            mode.amplitudes = Some(marginal.clone());
                         }
+                        let (signal_id, idler_id) =
+                            (out.modes[is].mode_id.clone(), out.modes[ii].mode_id.clone());
+                        for (mode, partner) in [(&signal_id, &idler_id), (&idler_id, &signal_id)] {
+                            unpair(&mut out.provenance, mode);
+                            out.provenance.insert(pair_key(mode), partner.clone());
+                        }
+                    }
+                }
+                out.provenance
+                    .insert("last_gate".to_string(), format!("PDC(gain={})", gain));
+            }
+            "DISPLACEMENT" => {
+                // Displacement D(α), α = (q + ip)/√2: shifts the mode's quadratures by (q, p)
+                let index = params.get("mode_id").map(|v| *v as usize).ok_or_else(|| {
+                    anyhow::anyhow!("DISPLACEMENT gate requires mode_id parameter")
+                })?;
+                let q = params.get("q").copied().unwrap_or(0.0);
+                let p = params.get("p").copied().unwrap_or(0.0);
+
+                if let Some(i) = mode_position(&out.modes, index) {
+                    let mode = &mut out.modes[i];
+                    if mode.amplitudes.is_some() {
+                        displace_mode(mode, Complex64::new(q, p) / std::f64::consts::SQRT_2)?;
+                        mark_cv(mode);
+                        unpair(&mut out.provenance, &mode.mode_id);
+                    }
+                }
+                out.provenance.insert(
+                    "last_gate".to_string(),
+                    format!("DISPLACEMENT(q={}, p={})", q, p),
+                );
+            }
+            "HERALD" => {
+                // CV → DV conversion: photon-count mode_id and keep the heralded |count⟩
+                let index = params
+                    .get("mode_id")
+                    .map(|v| *v as usize)
+                    .ok_or_else(|| anyhow::anyhow!("HERALD gate requires mode_id parameter"))?;
+                let count = params.get("count").map(|v| *v as usize).unwrap_or(1);
+
+                if let Some(i) = mode_position(&out.modes, index) {
+                    if out.modes[i].amplitudes.is_some() {
+                        let mode_id = out.modes[i].mode_id.clone();
+                        let probability = hybrid::herald(&mut out, i, count)?;
+                        out.provenance.insert(
+                            format!("herald_probability:{}", mode_id),
+                            probability.to_string(),
+                        );
+                    }
+                }
+                out.provenance
+                    .insert("last_gate".to_string(), format!("HERALD(count={})", count));
+            }
+            "PHOTON_SUBTRACT" => {
+                // Heralded photon subtraction through a weak tap of the given reflectivity
+                let index = params.get("mode_id").map(|v| *v as usize).ok_or_else(|| {
+                    anyhow::anyhow!("PHOTON_SUBTRACT gate requires mode_id parameter")
+                })?;
+                let reflectivity = params.get("reflectivity").copied().unwrap_or(0.01);
+
+                if let Some(i) = mode_position(&out.modes, index) {
+                    let mode = &mut out.modes[i];
+                    if mode.amplitudes.is_some() {
+                        let probability = hybrid::subtract_photon(mode, reflectivity)?;
+                        let mode_id = mode.mode_id.clone();
+                        unpair(&mut out.provenance, &mode_id);
+                        out.provenance.insert(
+                            format!("herald_probability:{}", mode_id),
+                            probability.to_string(),
+                        );
                     }
                 }
                 out.provenance.insert(
                     "last_gate".to_string(),
-                    format!("PDC(nonlinearity={})", nonlinearity),
+                    format!("PHOTON_SUBTRACT(reflectivity={})", reflectivity),
                 );
             }
             _ => {
@@ -266,7 +465,7 @@ impl StateEvolver for ReferenceStateEvolver {
             .ok_or_else(|| anyhow::anyhow!("mode {} has no amplitudes", mode_id))?;
 
         // Compute outcome probabilities (normalized)
-        let probs: Vec<f64> = amplitudes.iter().map(|a| a.abs().powi(2)).collect();
+        let probs: Vec<f64> = amplitudes.iter().map(|a| a.norm_sqr()).collect();
         let total: f64 = probs.iter().sum();
         if total <= 0.0 {
             return Err(anyhow::anyhow!(
@@ -288,25 +487,42 @@ impl StateEvolver for ReferenceStateEvolver {
             }
         }
 
-        // Build collapsed state (projection onto measured outcome)
+        // Build collapsed state (projection onto measured outcome, renormalised)
         let mut collapsed_modes = state.modes.clone();
         if let Some(m) = collapsed_modes.iter_mut().find(|m| m.mode_id == mode_id) {
             if let Some(ref mut amps) = m.amplitudes {
                 amps.iter_mut().enumerate().for_each(|(i, a)| {
                     if i != outcome_idx as usize {
-                        *a = 0.0;
+                        *a = Complex64::new(0.0, 0.0);
+                    } else if a.norm() > 0.0 {
+                        *a /= a.norm();
                     }
                 });
             }
         }
 
+        // A down-converted partner carries the same photon number: herald its Fock state.
+        let mut provenance = state.provenance.clone();
+        if let Some(partner) = unpair(&mut provenance, mode_id) {
+            if let Some(m) = collapsed_modes.iter_mut().find(|m| m.mode_id == partner) {
+                let numbers = mode_photon_numbers(m);
+                if let Some(ref mut amps) = m.amplitudes {
+                    for (a, n) in amps.iter_mut().zip(&numbers) {
+                        *a =
+                            Complex64::new(if *n == outcome_idx as usize { 1.0 } else { 0.0 }, 0.0);
+                    }
+                    m.mode_type = "quantum_fock".to_string();
+                }
+            }
+        }
+
         let collapsed_state = QuantumState {
             id: format!("{}-measured-{}", state.id, outcome_idx),
             modes: collapsed_modes,
             coherence_window: state.coherence_window.clone(),
             seed: Some(seed_val),
             provenance: {
-                let mut p = state.provenance.clone();
+                let mut p = provenance;
                 p.insert(
                     "measurement".to_string(),
                     format!("mode:{} outcome:{}", mode_id, outcome_idx),
@@ -321,12 +537,576 @@ impl StateEvolver for ReferenceStateEvolver {
             probability: normalized[outcome_idx as usize],
             collapsed_state: Some(collapsed_state),
             seed_used: Some(seed_val),
+            kind: MeasurementKind::PhotonCount,
+            quadratures: None,
+            correlation_id: None,
+        })
+    }
+
+    fn measure_quadrature(
+        &self,
+        state: &QuantumState,
+        mode_id: &str,
+        kind: MeasurementKind,
+        noise: Option<&HomodyneSimulator>,
+        seed: Option<u64>,
+    ) -> Result<MeasurementOutcome> {
+        let seed_val = seed.unwrap_or(0xDEADBEEF);
+        let mut rng = StdRng::seed_from_u64(seed_val);
+
+        let mode = state
+            .modes
+            .iter()
+            .find(|m| m.mode_id == mode_id)
+            .ok_or_else(|| anyhow::anyhow!("mode {} not found", mode_id))?;
+        let coeffs = fock_coefficients(mode)?;
+
+        // Technical noise: LO phase jitter, excess (thermal) variance and RIN gain at unit LO power.
+        let (jitter, excess_std, gain) = match noise {
+            Some(sim) => (
+                sim.noise_params.lo_phase_noise,
+                sim.noise_params.thermal_noise_variance.max(0.0).sqrt(),
+                (1.0 + sim.config.relative_intensity_noise).sqrt(),
+            ),
+            None => (0.0, 0.0, 1.0),
+        };
+        let degrade = |x: f64, rng: &mut StdRng| (x + excess_std * standard_normal(rng)) * gain;
+
+        let (quadratures, probability) = match kind {
+            MeasurementKind::PhotonCount => {
+                return Err(anyhow::anyhow!(
+                    "photon counting is not a quadrature measurement; use measure"
+                ))
+            }
+            MeasurementKind::Homodyne { angle } => {
+                let (x, p_non_negative) = sample_homodyne(&coeffs, angle + jitter, &mut rng);
+                let x = degrade(x, &mut rng);
+                let probability = if x >= 0.0 {
+                    p_non_negative
+                } else {
+                    1.0 - p_non_negative
+                };
+                (vec![x], probability)
+            }
+            MeasurementKind::Heterodyne => {
+                let (q, p, p_non_negative) = sample_heterodyne(&coeffs, &mut rng);
+                // The LO jitter rotates the measured phase-space point.
+                let (qr, pr) = (
+                    q * jitter.cos() + p * jitter.sin(),
+                    -q * jitter.sin() + p * jitter.cos(),
+                );
+                let (q, p) = (degrade(qr, &mut rng), degrade(pr, &mut rng));
+                let probability = if q >= 0.0 {
+                    p_non_negative
+                } else {
+                    1.0 - p_non_negative
+                };
+                (vec![q, p], probability)
+            }
+        };
+        let outcome_idx = (quadratures[0] >= 0.0) as u32;
+
+        // The detector absorbs the mode: leave it in vacuum.
+        let mut collapsed_modes = state.modes.clone();
+        if let Some(m) = collapsed_modes.iter_mut().find(|m| m.mode_id == mode_id) {
+            let numbers = mode_photon_numbers(m);
+            if let Some(ref mut amps) = m.amplitudes {
+                for (a, n) in amps.iter_mut().zip(&numbers) {
+                    *a = Complex64::new(if *n == 0 { 1.0 } else { 0.0 }, 0.0);
+                }
+            }
+        }
+
+        let label = match kind {
+            MeasurementKind::Heterodyne => "heterodyne",
+            _ => "homodyne",
+        };
+        let collapsed_state = QuantumState {
+            id: format!("{}-{}-{}", state.id, label, mode_id),
+            modes: collapsed_modes,
+            coherence_window: state.coherence_window.clone(),
+            seed: Some(seed_val),
+            provenance: {
+                let mut p = state.provenance.clone();
+                unpair(&mut p, mode_id);
+                p.insert(
+                    "measurement".to_string(),
+                    format!("mode:{} {}:{:?}", mode_id, label, quadratures),
+                );
+                p
+            },
+        };
+
+        Ok(MeasurementOutcome {
+            outcome_index: outcome_idx,
+            photon_count: 0,
+            probability,
+            collapsed_state: Some(collapsed_state),
+            seed_used: Some(seed_val),
+            kind,
+            quadratures: Some(quadratures),
+            correlation_id: None,
         })
     }
 
     fn is_coherent(&self, state: &QuantumState, current_time_ns: u64) -> bool {
-        current_time_ns < state.coherence_window.end_ns
+        let window = &state.coherence_window;
+        let elapsed = current_time_ns.saturating_sub(window.start_ns) as f64;
+        current_time_ns < window.end_ns
+            && window
+                .fidelity_after(elapsed)
+                .is_ok_and(|f| f >= window.fidelity_threshold)
+    }
+}
+
+// ============================================================================
+// Mode lookup and PDC pairing
+// ============================================================================
+
+/// Gaussian gates leave a pure mode as a CV resource.
+fn mark_cv(mode: &mut QuantumMode) {
+    if mode.mode_type != "mixed" {
+        mode.mode_type = CV_MODE.to_string();
+    }
+}
+
+/// Position of mode `index`, accepting both bare indices and the engine's `mode_<i>` ids.
+fn mode_position(modes: &[QuantumMode], index: usize) -> Option<usize> {
+    let (bare, prefixed) = (index.to_string(), format!("mode_{}", index));
+    modes
+        .iter()
+        .position(|m| m.mode_id == bare || m.mode_id == prefixed)
+}
+
+/// Provenance key naming the down-converted partner of `mode_id`. Modes stay independent, so
+/// PDC records the pair's photon-number correlation here for photon counting to herald.
+fn pair_key(mode_id: &str) -> String {
+    format!("pdc_partner:{}", mode_id)
+}
+
+/// Drop `mode_id`'s PDC pairing, returning its partner. Gates that change photon numbers and
+/// quadrature measurements break the correlation.
+pub(crate) fn unpair(provenance: &mut HashMap<String, String>, mode_id: &str) -> Option<String> {
+    let partner = provenance.remove(&pair_key(mode_id))?;
+    provenance.remove(&pair_key(&partner));
+    Some(partner)
+}
+
+// ============================================================================
+// Quadrature sampling
+// ============================================================================
+
+/// Grid points per axis for inverse-CDF quadrature sampling.
+const HOMODYNE_GRID_POINTS: usize = 2001;
+const HETERODYNE_GRID_POINTS: usize = 161;
+
+/// Photon number of each amplitude slot (`photon_numbers`, else the slot index).
+fn mode_photon_numbers(mode: &QuantumMode) -> Vec<usize> {
+    let len = mode.amplitudes.as_ref().map_or(0, |a| a.len());
+    match &mode.photon_numbers {
+        Some(numbers) => numbers.iter().map(|n| *n as usize).collect(),
+        None => (0..len).collect(),
+    }
+}
+
+/// Normalised Fock coefficients `c_n` of a mode.
+pub(crate) fn fock_coefficients(mode: &QuantumMode) -> Result<Vec<(usize, Complex64)>> {
+    let amplitudes = mode
+        .amplitudes
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("mode {} has no amplitudes", mode.mode_id))?;
+    let numbers = mode_photon_numbers(mode);
+    if numbers.len() != amplitudes.len() {
+        return Err(anyhow::anyhow!(
+            "mode {} has {} photon numbers for {} amplitudes",
+            mode.mode_id,
+            numbers.len(),
+            amplitudes.len()
+        ));
+    }
+    let norm = amplitudes.iter().map(|a| a.norm_sqr()).sum::<f64>().sqrt();
+    if norm <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "invalid probability distribution for measurement"
+        ));
+    }
+    Ok(numbers
+        .into_iter()
+        .zip(amplitudes)
+        .map(|(n, a)| (n, a / norm))
+        .collect())
+}
+
+/// `⟨m|D(α)|n⟩ = √(n!/m!) α^{m−n} e^{−|α|²/2} L_n^{(m−n)}(|α|²)` for `m ≥ n`, and the
+/// `(−α*)`-conjugate form for `m < n`.
+fn displacement_element(m: usize, n: usize, alpha: Complex64) -> Complex64 {
+    let x = alpha.norm_sqr();
+    let (low, high, base) = if m >= n {
+        (n, m, alpha)
+    } else {
+        (m, n, -alpha.conj())
+    };
+    let ratio: f64 = (low + 1..=high).map(|k| 1.0 / (k as f64).sqrt()).product();
+    base.powu((high - low) as u32)
+        * ratio
+        * (-x / 2.0).exp()
+        * laguerre(low, (high - low) as f64, x)
+}
+
+/// Apply `D(α)` within the mode's photon-number support, renormalising for truncation.
+fn displace_mode(mode: &mut QuantumMode, alpha: Complex64) -> Result<()> {
+    let coeffs = fock_coefficients(mode)?;
+    let displaced: Vec<Complex64> = coeffs
+        .iter()
+        .map(|(m, _)| {
+            coeffs
+                .iter()
+                .map(|(n, c)| displacement_element(*m, *n, alpha) * c)
+                .sum()
+        })
+        .collect();
+    let norm = displaced.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt();
+    if norm <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "displacement leaves no weight on mode {}'s Fock support",
+            mode.mode_id
+        ));
+    }
+    mode.amplitudes = Some(displaced.iter().map(|c| c / norm).collect());
+    Ok(())
+}
+
+// ============================================================================
+// Beam splitter
+// ============================================================================
+
+/// Reduced purity above which a mode is treated as pure.
+const PURE_TOLERANCE: f64 = 1e-9;
+
+fn ln_factorial(n: usize) -> f64 {
+    (2..=n).map(|k| (k as f64).ln()).sum()
+}
+
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    ln_factorial(n) - ln_factorial(k) - ln_factorial(n - k)
+}
+
+/// Default per-mode cutoff: the wider input support, or the highest occupied photon number
+/// the two inputs can pile into one output mode if that is larger.
+fn beam_splitter_cutoff(c1: &[(usize, Complex64)], c2: &[(usize, Complex64)]) -> usize {
+    let support = |c: &[(usize, Complex64)]| c.iter().map(|(n, _)| *n).max().unwrap_or(0);
+    let occupied = |c: &[(usize, Complex64)]| {
+        c.iter()
+            .filter(|(_, a)| a.norm_sqr() > 0.0)
+            .map(|(n, _)| *n)
+            .max()
+            .unwrap_or(0)
+    };
+    support(c1)
+        .max(support(c2))
+        .max(occupied(c1) + occupied(c2))
+}
+
+/// Joint output amplitudes `ψ[m₁][m₂]` of the beam splitter on `|c₁⟩ ⊗ |c₂⟩`, truncated to
+/// `m₁, m₂ ≤ cutoff`. Expanding `(t a₁† + r a₂†)^{n₁} (−r a₁† + t a₂†)^{n₂}` spreads each
+/// `|n₁, n₂⟩` binomially over `|m, n₁ + n₂ − m⟩` with `t = cos θ`, `r = sin θ`.
+fn beam_splitter_joint(
+    c1: &[(usize, Complex64)],
+    c2: &[(usize, Complex64)],
+    theta: f64,
+    cutoff: usize,
+) -> Vec<Vec<Complex64>> {
+    let (t, r) = (theta.cos(), theta.sin());
+    let mut joint = vec![vec![Complex64::new(0.0, 0.0); cutoff + 1]; cutoff + 1];
+    for &(n1, a1) in c1 {
+        for &(n2, a2) in c2 {
+            let input = a1 * a2;
+            if input.norm_sqr() == 0.0 {
+                continue;
+            }
+            let total = n1 + n2;
+            let input_norm = -0.5 * (ln_factorial(n1) + ln_factorial(n2));
+            for j in 0..=n1 {
+                for k in 0..=n2 {
+                    // j of mode 1's photons and k of mode 2's leave through output 1.
+                    let m = j + k;
+                    if m > cutoff || total - m > cutoff {
+                        continue;
+                    }
+                    let log_weight = ln_binomial(n1, j)
+                        + ln_binomial(n2, k)
+                        + 0.5 * (ln_factorial(m) + ln_factorial(total - m))
+                        + input_norm;
+                    let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                    joint[m][total - m] += input
+                        * sign
+                        * log_weight.exp()
+                        * t.powi((j + n2 - k) as i32)
+                        * r.powi((n1 - j + k) as i32);
+                }
+            }
+        }
+    }
+    joint
+}
+
+/// Per-mode states of a two-mode amplitude matrix, renormalised after truncation. A product state
+/// splits into pure `"quantum_fock"` factors; an entangled one leaves both modes `"mixed"`,
+/// holding the square roots of their photon-number populations.
+fn split_joint(joint: &[Vec<Complex64>]) -> Result<(&'static str, Vec<Complex64>, Vec<Complex64>)> {
+    let dim = joint.len();
+    let norm_sqr: f64 = joint.iter().flatten().map(|a| a.norm_sqr()).sum();
+    // Weight below machine precision is rounding residue from a cancelled amplitude.
+    if norm_sqr < f64::EPSILON {
+        return Err(anyhow::anyhow!("no amplitude left within the Fock cutoff"));
+    }
+    let psi: Vec<Vec<Complex64>> = joint
+        .iter()
+        .map(|row| row.iter().map(|a| a / norm_sqr.sqrt()).collect())
+        .collect();
+
+    // Reduced state of mode 1: ρ[a][b] = Σ_n ψ[a][n] ψ[b][n]*.
+    let rho: Vec<Vec<Complex64>> = (0..dim)
+        .map(|a| {
+            (0..dim)
+                .map(|b| (0..dim).map(|n| psi[a][n] * psi[b][n].conj()).sum())
+                .collect()
+        })
+        .collect();
+    let purity: f64 = rho.iter().flatten().map(|x| x.norm_sqr()).sum();
+
+    if purity > 1.0 - PURE_TOLERANCE {
+        // ψ = u ⊗ v: the column and row through the largest entry are u and v up to phases.
+        let (k1, k2) = (0..dim)
+            .flat_map(|m| (0..dim).map(move |n| (m, n)))
+            .max_by(|&(a, b), &(c, d)| psi[a][b].norm_sqr().total_cmp(&psi[c][d].norm_sqr()))
+            .unwrap_or((0, 0));
+        // Normalising each and removing the pivot's phase from v gives u ⊗ v = ψ exactly.
+        let pivot = psi[k1][k2];
+        let column_norm = (0..dim).map(|m| psi[m][k2].norm_sqr()).sum::<f64>().sqrt();
+        let row_norm = psi[k1].iter().map(|a| a.norm_sqr()).sum::<f64>().sqrt();
+        let u: Vec<Complex64> = (0..dim).map(|m| psi[m][k2] / column_norm).collect();
+        let v: Vec<Complex64> = psi[k1]
+            .iter()
+            .map(|a| a * pivot.conj() / (pivot.norm() * row_norm))
+            .collect();
+        Ok(("quantum_fock", u, v))
+    } else {
+        let marginal = |p: Vec<f64>| {
+            p.into_iter()
+                .map(|x| Complex64::new(x.sqrt(), 0.0))
+                .collect()
+        };
+        let p1 = (0..dim)
+            .map(|m| psi[m].iter().map(|a| a.norm_sqr()).sum())
+            .collect();
+        let p2 = (0..dim)
+            .map(|n| psi.iter().map(|row| row[n].norm_sqr()).sum())
+            .collect();
+        Ok(("mixed", marginal(p1), marginal(p2)))
+    }
+}
+
+// ============================================================================
+// Squeezing
+// ============================================================================
+
+/// Largest photon number a squeezed mode is expanded to without an explicit cutoff.
+const MAX_SQUEEZING_CUTOFF: usize = 200;
+
+/// Default cutoff after squeezing: the input support, widened until the squeezed-vacuum tail
+/// `tanh^{2k} r` beyond the highest occupied level drops below 1e-16.
+fn squeezing_cutoff(coeffs: &[(usize, Complex64)], r: f64) -> usize {
+    let support = coeffs.iter().map(|(n, _)| *n).max().unwrap_or(0);
+    let occupied = coeffs
+        .iter()
+        .filter(|(_, c)| c.norm_sqr() > 0.0)
+        .map(|(n, _)| *n)
+        .max()
+        .unwrap_or(0);
+    let tail = 2 * squeezed_vacuum_pairs(r);
+    support.max((occupied + tail).min(MAX_SQUEEZING_CUTOFF))
+}
+
+/// Smallest `k` with `tanh^{2k} r < 1e-16`: the photon pairs a squeezed vacuum needs.
+fn squeezed_vacuum_pairs(r: f64) -> usize {
+    let tanh = r.abs().tanh();
+    if tanh > 0.0 {
+        (16.0 * std::f64::consts::LN_10 / (-2.0 * tanh.ln())).ceil() as usize
+    } else {
+        0
+    }
+}
+
+/// Signal (or idler) amplitudes of a two-mode squeezed vacuum truncated to `cutoff`: the
+/// square roots of the thermal populations `tanh^{2n} r / cosh² r`, renormalised.
+fn two_mode_squeezed_marginal(r: f64, cutoff: usize) -> Vec<Complex64> {
+    let populations: Vec<f64> = (0..=cutoff)
+        .map(|n| r.tanh().powi(2 * n as i32) / r.cosh().powi(2))
+        .collect();
+    let total: f64 = populations.iter().sum();
+    populations
+        .iter()
+        .map(|p| Complex64::new((p / total).sqrt(), 0.0))
+        .collect()
+}
+
+/// Apply `S(r e^{iθ})` and truncate to `cutoff` (default [`squeezing_cutoff`]), renormalising.
+///
+/// The columns `S|n⟩` follow from the squeezed vacuum
+/// `⟨2k|S|0⟩ = (−e^{iθ} tanh r)^k √((2k)!) / (2^k k! √cosh r)` and
+/// `S|n+1⟩ = (cosh r a† + e^{−iθ} sinh r a) S|n⟩ / √(n+1)`, since `S a† S† = cosh r a† +
+/// e^{−iθ} sinh r a`. Each step reads one level higher, so the vacuum column is built
+/// `n_max` levels past the cutoff and every kept element is exact.
+fn squeeze_mode(mode: &mut QuantumMode, r: f64, angle: f64, cutoff: Option<usize>) -> Result<()> {
+    let coeffs = fock_coefficients(mode)?;
+    let cutoff = cutoff.unwrap_or_else(|| squeezing_cutoff(&coeffs, r));
+    let n_max = coeffs.iter().map(|(n, _)| *n).max().unwrap_or(0);
+    let width = cutoff + n_max + 1;
+
+    let ratio = -Complex64::from_polar(r.tanh(), angle);
+    let mut column = vec![Complex64::new(0.0, 0.0); width];
+    for k in 0..=(width - 1) / 2 {
+        let log_weight = 0.5 * ln_factorial(2 * k)
+            - k as f64 * std::f64::consts::LN_2
+            - ln_factorial(k)
+            - 0.5 * r.cosh().ln();
+        column[2 * k] = ratio.powu(k as u32) * log_weight.exp();
+    }
+
+    let lowering = Complex64::from_polar(r.sinh(), -angle);
+    let mut squeezed = vec![Complex64::new(0.0, 0.0); cutoff + 1];
+    for n in 0..=n_max {
+        for &(_, c) in coeffs.iter().filter(|(m, _)| *m == n) {
+            for (out, s) in squeezed.iter_mut().zip(&column) {
+                *out += c * s;
+            }
+        }
+        if n < n_max {
+            let next = (0..width)
+                .map(|m| {
+                    let raise = if m > 0 {
+                        column[m - 1] * (m as f64).sqrt() * r.cosh()
+                    } else {
+                        Complex64::new(0.0, 0.0)
+                    };
+                    let lower = match column.get(m + 1) {
+                        Some(c) => c * lowering * ((m + 1) as f64).sqrt(),
+                        None => Complex64::new(0.0, 0.0),
+                    };
+                    (raise + lower) / ((n + 1) as f64).sqrt()
+                })
+                .collect();
+            column = next;
+        }
+    }
+
+    let norm = squeezed.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt();
+    if norm <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "squeezing leaves no weight on mode {} below cutoff {}",
+            mode.mode_id,
+            cutoff
+        ));
+    }
+    mode.photon_numbers = Some((0..=cutoff as u32).collect());
+    mode.amplitudes = Some(squeezed.iter().map(|c| c / norm).collect());
+    Ok(())
+}
+
+/// Sampling half-width covering the Fock support with ~6σ of vacuum margin.
+fn phase_space_extent(coeffs: &[(usize, Complex64)]) -> f64 {
+    let n_max = coeffs.iter().map(|(n, _)| *n).max().unwrap_or(0);
+    (2.0 * n_max as f64 + 1.0).sqrt() + 6.0
+}
+
+/// Draw `x_θ` from `|Σ_n c_n e^{−inθ} ψ_n(x)|²`. Also returns `P(x_θ ≥ 0)`.
+fn sample_homodyne(coeffs: &[(usize, Complex64)], angle: f64, rng: &mut StdRng) -> (f64, f64) {
+    let extent = phase_space_extent(coeffs);
+    let count = coeffs.iter().map(|(n, _)| n + 1).max().unwrap_or(1);
+    let dx = 2.0 * extent / (HOMODYNE_GRID_POINTS - 1) as f64;
+    let xs: Vec<f64> = (0..HOMODYNE_GRID_POINTS)
+        .map(|i| -extent + i as f64 * dx)
+        .collect();
+    let density: Vec<f64> = xs
+        .iter()
+        .map(|&x| {
+            let psi = hermite_functions(x, count);
+            coeffs
+                .iter()
+                .map(|(n, c)| c * Complex64::from_polar(psi[*n], -(*n as f64) * angle))
+                .sum::<Complex64>()
+                .norm_sqr()
+        })
+        .collect();
+    let total: f64 = density.iter().sum();
+    let non_negative: f64 = xs
+        .iter()
+        .zip(&density)
+        .filter(|(x, _)| **x >= 0.0)
+        .map(|(_, d)| d)
+        .sum();
+    let target = rng.gen::<f64>() * total;
+    let mut cumulative = 0.0;
+    let mut cell = HOMODYNE_GRID_POINTS - 1;
+    for (i, d) in density.iter().enumerate() {
+        cumulative += d;
+        if target < cumulative {
+            cell = i;
+            break;
+        }
+    }
+    let x = xs[cell] + (rng.gen::<f64>() - 0.5) * dx;
+    (x, non_negative / total)
+}
+
+/// Draw `(q, p)` from the Husimi function `|⟨α|ψ⟩|²/2π`, `α = (q + ip)/√2`. Also returns
+/// `P(q ≥ 0)`.
+fn sample_heterodyne(coeffs: &[(usize, Complex64)], rng: &mut StdRng) -> (f64, f64, f64) {
+    let extent = phase_space_extent(coeffs);
+    let d = 2.0 * extent / (HETERODYNE_GRID_POINTS - 1) as f64;
+    let axis: Vec<f64> = (0..HETERODYNE_GRID_POINTS)
+        .map(|i| -extent + i as f64 * d)
+        .collect();
+    // ⟨α|n⟩ = e^{−|α|²/2} (α*)ⁿ/√n!
+    let n_max = coeffs.iter().map(|(n, _)| *n).max().unwrap_or(0);
+    let mut factorial_sqrt = vec![1.0; n_max + 1];
+    for n in 1..=n_max {
+        factorial_sqrt[n] = factorial_sqrt[n - 1] * (n as f64).sqrt();
+    }
+    let husimi = |q: f64, p: f64| {
+        let alpha_conj = Complex64::new(q, -p) / std::f64::consts::SQRT_2;
+        let overlap: Complex64 = coeffs
+            .iter()
+            .map(|(n, c)| c * alpha_conj.powu(*n as u32) / factorial_sqrt[*n])
+            .sum();
+        (-alpha_conj.norm_sqr()).exp() * overlap.norm_sqr()
+    };
+    let density: Vec<f64> = axis
+        .iter()
+        .flat_map(|&q| axis.iter().map(move |&p| (q, p)))
+        .map(|(q, p)| husimi(q, p))
+        .collect();
+    let total: f64 = density.iter().sum();
+    let non_negative: f64 = density
+        .chunks(HETERODYNE_GRID_POINTS)
+        .zip(&axis)
+        .filter(|(_, q)| **q >= 0.0)
+        .map(|(row, _)| row.iter().sum::<f64>())
+        .sum();
+    let target = rng.gen::<f64>() * total;
+    let mut cumulative = 0.0;
+    let mut cell = density.len() - 1;
+    for (i, w) in density.iter().enumerate() {
+        cumulative += w;
+        if target < cumulative {
+            cell = i;
+            break;
+        }
     }
+    let q = axis[cell / HETERODYNE_GRID_POINTS] + (rng.gen::<f64>() - 0.5) * d;
+    let p = axis[cell % HETERODYNE_GRID_POINTS] + (rng.gen::<f64>() - 0.5) * d;
+    (q, p, non_negative / total)
 }
 
 /// Reference coherence manager.
@@ -339,6 +1119,7 @@ impl CoherenceManager for ReferenceCoherenceManager {
         duration_ns: u64,
         decoherence_model: &str,
     ) -> Result<CoherenceWindow> {
+        let model = decoherence::decoherence_model(decoherence_model)?;
         Ok(CoherenceWindow {
             id: format!("coh-{}-{}", start_ns, decoherence_model),
             start_ns,
@@ -349,6 +1130,7 @@ impl CoherenceManager for ReferenceCoherenceManager {
             idle_time_budget_ns: Some(200),
             fidelity_threshold: 0.95,
             notes: Some(format!("model:{}", decoherence_model)),
+            decoherence_model: model.name().to_string(),
         })
     }
 
@@ -56,6 +56,13 @@ pub struct ArtifactBundle {
     pub provenance: ProvenanceData,
 }
 
+impl ArtifactBundle {
+    /// Namespace the bundle belongs to, from its `namespace:<name>` tag
+    pub fn namespace(&self) -> crate::namespace::Namespace {
+        crate::namespace::Namespace::from_tags(&self.provenance.tags)
+    }
+}
+
 /// Bundle type
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -74,6 +81,12 @@ pub struct ObservabilityData {
     pub timeline: Option<PathBuf>,
     pub metrics: Option<PathBuf>,
     pub events: Option<PathBuf>,
+    /// Per-node state history (`quantum_states.json`)
+    #[serde(default)]
+    pub states: Option<PathBuf>,
+    /// Measurement outcomes (`measurements.json`)
+    #[serde(default)]
+    pub measurements: Option<PathBuf>,
 }
 
 /// Environment snapshot
@@ -92,6 +105,22 @@ pub struct ProvenanceData {
     pub tags: Vec<String>,
     pub notes: Option<String>,
     pub citation: Option<String>,
+    /// Plugins discovered for the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<PluginRecord>,
+}
+
+/// A plugin discovered from an `awen-plugin.toml` manifest
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PluginRecord {
+    pub name: String,
+    pub version: String,
+    /// `cdylib`, `executable` or `grpc`
+    pub kind: String,
+    pub entry: String,
+    pub capabilities: Vec<String>,
+    /// Whether the run loaded the plugin (plugins load lazily, on first use)
+    pub loaded: bool,
 }
 
 /// Creator information
@@ -120,6 +149,9 @@ pub struct BundleBuilder {
     title: Option<String>,
     authors: Option<String>,
     organization: Option<String>,
+    device: Option<crate::hal::DeviceProvenance>,
+    device_health: Option<(String, HashMap<String, String>)>,
+    plugins: Vec<PluginRecord>,
 }
 
 impl BundleBuilder {
@@ -142,6 +174,9 @@ impl BundleBuilder {
             title: None,
             authors: None,
             organization: None,
+            device: None,
+            device_health: None,
+            plugins: Vec::new(),
         }
     }
 
@@ -204,6 +239,37 @@ impl BundleBuilder {
         self
     }
 
+    /// Mark the artifact as a member of sweep group `group` (a `sweep:<group>` tag)
+    pub fn with_sweep_group(mut self, group: &str) -> Self {
+        self.tags
+            .push(format!("{}{}", super::lineage::SWEEP_TAG_PREFIX, group));
+        self
+    }
+
+    /// Record the namespace the artifact belongs to (a `namespace:<name>` tag)
+    pub fn with_namespace(mut self, namespace: &crate::namespace::Namespace) -> Self {
+        self.tags.push(namespace.tag());
+        self
+    }
+
+    /// Record the identity of the device that produced the results
+    pub fn with_device(mut self, device: crate::hal::DeviceProvenance) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// Record the device identity and firmware versions from a HAL health report
+    pub fn with_device_health(mut self, device_id: &str, report: HashMap<String, String>) -> Self {
+        self.device_health = Some((device_id.to_string(), report));
+        self
+    }
+
+    /// Record the plugins available to the run, e.g. from `PluginRegistry::provenance`
+    pub fn with_plugins(mut self, plugins: Vec<PluginRecord>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
     /// Set notes
     pub fn with_notes(mut self, notes: String) -> Self {
         self.notes = Some(notes);
@@ -230,7 +296,13 @@ impl BundleBuilder {
             .ok_or_else(|| anyhow::anyhow!("Results not set"))?;
 
         // Capture environment
-        let environment = capture_environment();
+        let mut environment = capture_environment();
+        if let Some(device) = &self.device {
+            environment.device.apply_provenance(device);
+        }
+        if let Some((device_id, report)) = &self.device_health {
+            environment.device.apply_health_report(device_id, report);
+        }
 
         // Compute deterministic ID
         let artifact_id = compute_deterministic_id(
@@ -250,6 +322,8 @@ impl BundleBuilder {
                 timeline: Some(dir.join("timeline.json")),
                 metrics: Some(dir.join("metrics.json")),
                 events: Some(dir.join("events.jsonl")),
+                states: Some(dir.join("quantum_states.json")),
+                measurements: Some(dir.join("measurements.json")),
             });
 
         // Create provenance
@@ -286,6 +360,7 @@ impl BundleBuilder {
             tags: self.tags,
             notes: self.notes,
             citation,
+            plugins: self.plugins,
         };
 
         // Create manifest
@@ -397,6 +472,31 @@ mod tests {
         assert_eq!(bundle.provenance.tags.len(), 1);
     }
 
+    #[test]
+    fn test_bundle_records_device_provenance() {
+        let ir = Graph {
+            nodes: vec![],
+            edges: vec![],
+            metadata: std::collections::HashMap::new(),
+        };
+        let bundle = BundleBuilder::new(ir, ArtifactType::Run)
+            .with_results(serde_json::json!({}))
+            .with_device(crate::hal::DeviceProvenance {
+                device_id: "chip-a".to_string(),
+                firmware_version: Some("2.1.0".to_string()),
+                serial_number: Some("SN123".to_string()),
+                driver_version: None,
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(bundle.environment.device.device_id, "chip-a");
+        assert_eq!(
+            bundle.environment.device.firmware_version.as_deref(),
+            Some("2.1.0")
+        );
+    }
+
     #[test]
     fn test_citation_generation() {
         let citation = generate_citation(
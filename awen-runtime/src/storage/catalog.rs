@@ -0,0 +1,589 @@
+//! SQLite catalog of exported runs (`catalog` feature)
+//!
+//! `RunCatalog` indexes bundles as they are exported (or later, by scanning an artifacts
+//! directory) so old runs can be found by query instead of by walking directories: run id,
+//! IR hash, seed, backend, status, creation time, location, and every numeric top-level
+//! field of the results as a key metric.
+//!
+//! Runs are keyed by namespace and run id, so two groups running the same graph keep separate
+//! entries. A catalog opened with [`RunCatalog::for_namespace`] only sees and indexes the runs
+//! of that namespace. Catalogs written before namespaces existed are migrated on open, their
+//! runs placed in the `default` namespace.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{export_bundle, import_bundle, ir_hash, ArtifactBundle, ExportFormat};
+use crate::namespace::Namespace;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    namespace TEXT NOT NULL DEFAULT 'default',
+    run_id TEXT NOT NULL,
+    artifact_type TEXT NOT NULL,
+    ir_hash TEXT NOT NULL,
+    seed INTEGER,
+    backend TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    location TEXT NOT NULL,
+    PRIMARY KEY (namespace, run_id)
+);
+CREATE INDEX IF NOT EXISTS runs_ir_hash ON runs (ir_hash);
+CREATE INDEX IF NOT EXISTS runs_created_at ON runs (created_at);
+CREATE TABLE IF NOT EXISTS run_metrics (
+    namespace TEXT NOT NULL DEFAULT 'default',
+    run_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (namespace, run_id, key),
+    FOREIGN KEY (namespace, run_id) REFERENCES runs (namespace, run_id) ON DELETE CASCADE
+);
+";
+
+/// Move a catalog without namespaces into the current schema, in the `default` namespace
+const MIGRATE_NAMESPACES: &str = "
+DROP INDEX IF EXISTS runs_ir_hash;
+DROP INDEX IF EXISTS runs_created_at;
+ALTER TABLE run_metrics RENAME TO run_metrics_unscoped;
+ALTER TABLE runs RENAME TO runs_unscoped;
+";
+
+const COPY_UNSCOPED: &str = "
+INSERT INTO runs
+    (run_id, artifact_type, ir_hash, seed, backend, status, created_at, location)
+    SELECT run_id, artifact_type, ir_hash, seed, backend, status, created_at, location
+    FROM runs_unscoped;
+INSERT INTO run_metrics (run_id, key, value)
+    SELECT run_id, key, value FROM run_metrics_unscoped;
+DROP TABLE run_metrics_unscoped;
+DROP TABLE runs_unscoped;
+";
+
+const COLUMNS: &str =
+    "namespace, run_id, artifact_type, ir_hash, seed, backend, status, created_at, location";
+
+/// One indexed run
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    pub namespace: Namespace,
+    pub run_id: String,
+    pub artifact_type: String,
+    pub ir_hash: String,
+    pub seed: Option<u64>,
+    /// Device type the run executed on, e.g. `simulator`
+    pub backend: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    /// Exported bundle directory or archive
+    pub location: PathBuf,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+impl CatalogEntry {
+    /// Catalog fields of a bundle exported to `location`
+    pub fn from_bundle(bundle: &ArtifactBundle, location: &Path) -> Result<Self> {
+        let created_at = DateTime::parse_from_rfc3339(&bundle.manifest.created_at)
+            .map_err(|e| anyhow!("invalid created_at in manifest: {}", e))?
+            .with_timezone(&Utc);
+        let status = super::search::run_status(&bundle.results, &bundle.manifest);
+        let metrics = bundle
+            .results
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|(key, value)| value.as_f64().map(|v| (key.clone(), v)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Self {
+            namespace: bundle.namespace(),
+            run_id: bundle.artifact_id.clone(),
+            artifact_type: bundle.manifest.artifact_type.clone(),
+            ir_hash: ir_hash(&bundle.ir_original)?,
+            seed: bundle.seed,
+            backend: bundle.environment.device.device_type.clone(),
+            status,
+            created_at,
+            location: location.to_path_buf(),
+            metrics,
+        })
+    }
+}
+
+/// Filters for [`RunCatalog::query`]; unset fields match every run
+#[derive(Debug, Clone, Default)]
+pub struct RunQuery {
+    pub namespace: Option<Namespace>,
+    pub ir_hash: Option<String>,
+    pub seed: Option<u64>,
+    pub backend: Option<String>,
+    pub status: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Runs whose metric `.0` is at least `.1`
+    pub metric_at_least: Option<(String, f64)>,
+    pub limit: Option<usize>,
+}
+
+impl RunQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn namespace(mut self, namespace: &Namespace) -> Self {
+        self.namespace = Some(namespace.clone());
+        self
+    }
+
+    /// Runs of `graph`
+    pub fn graph(mut self, graph: &crate::ir::Graph) -> Result<Self> {
+        self.ir_hash = Some(ir_hash(graph)?);
+        Ok(self)
+    }
+
+    pub fn ir_hash(mut self, hash: &str) -> Self {
+        self.ir_hash = Some(hash.to_string());
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn backend(mut self, backend: &str) -> Self {
+        self.backend = Some(backend.to_string());
+        self
+    }
+
+    pub fn status(mut self, status: &str) -> Self {
+        self.status = Some(status.to_string());
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn metric_at_least(mut self, key: &str, min: f64) -> Self {
+        self.metric_at_least = Some((key.to_string(), min));
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// Run index backed by a SQLite database
+pub struct RunCatalog {
+    conn: Connection,
+    /// Only namespace this catalog sees, if scoped
+    namespace: Option<Namespace>,
+}
+
+impl std::fmt::Debug for RunCatalog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunCatalog")
+            .field("path", &self.conn.path())
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+/// Timestamps are stored as fixed-width RFC 3339 UTC so text order is time order
+fn timestamp(t: &DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+impl RunCatalog {
+    /// Open (creating if needed) the catalog database at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        let unscoped: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'runs')
+             AND NOT EXISTS (SELECT 1 FROM pragma_table_info('runs') WHERE name = 'namespace')",
+            [],
+            |row| row.get(0),
+        )?;
+        if unscoped {
+            let tx = conn.transaction()?;
+            tx.execute_batch(MIGRATE_NAMESPACES)?;
+            tx.execute_batch(SCHEMA)?;
+            tx.execute_batch(COPY_UNSCOPED)?;
+            tx.commit()?;
+        }
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn,
+            namespace: None,
+        })
+    }
+
+    /// Only see, and only index, runs of `namespace`.
+    pub fn for_namespace(mut self, namespace: &Namespace) -> Self {
+        self.namespace = Some(namespace.clone());
+        self
+    }
+
+    pub fn namespace(&self) -> Option<&Namespace> {
+        self.namespace.as_ref()
+    }
+
+    fn sees(&self, namespace: &Namespace) -> bool {
+        self.namespace
+            .as_ref()
+            .is_none_or(|scope| scope == namespace)
+    }
+
+    /// Add or replace a run. A scoped catalog refuses runs of other namespaces.
+    pub fn index(&mut self, entry: &CatalogEntry) -> Result<()> {
+        if !self.sees(&entry.namespace) {
+            return Err(anyhow!(
+                "run {} belongs to namespace {}, not {}",
+                entry.run_id,
+                entry.namespace,
+                self.namespace
+                    .as_ref()
+                    .map(|n| n.as_str())
+                    .unwrap_or_default()
+            ));
+        }
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO runs
+             (namespace, run_id, artifact_type, ir_hash, seed, backend, status, created_at,
+              location)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.namespace.as_str(),
+                entry.run_id,
+                entry.artifact_type,
+                entry.ir_hash,
+                entry.seed.map(|s| s as i64),
+                entry.backend,
+                entry.status,
+                timestamp(&entry.created_at),
+                entry.location.to_string_lossy(),
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM run_metrics WHERE namespace = ?1 AND run_id = ?2",
+            params![entry.namespace.as_str(), entry.run_id],
+        )?;
+        for (key, value) in &entry.metrics {
+            tx.execute(
+                "INSERT INTO run_metrics (namespace, run_id, key, value) VALUES (?1, ?2, ?3, ?4)",
+                params![entry.namespace.as_str(), entry.run_id, key, value],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Export a bundle and index it
+    pub fn export(
+        &mut self,
+        bundle: &ArtifactBundle,
+        output_dir: &Path,
+        format: ExportFormat,
+    ) -> Result<PathBuf> {
+        let location = export_bundle(bundle, output_dir, format)?;
+        self.index(&CatalogEntry::from_bundle(bundle, &location)?)?;
+        Ok(location)
+    }
+
+    /// Index every bundle directory and `.tar.zst` archive directly under `artifacts_dir`;
+    /// returns how many were indexed. A scoped catalog skips bundles of other namespaces.
+    pub fn index_dir(&mut self, artifacts_dir: &Path) -> Result<usize> {
+        let mut indexed = 0;
+        for entry in std::fs::read_dir(artifacts_dir)? {
+            let path = entry?.path();
+            let is_bundle = if path.is_dir() {
+                path.join("manifest.json").is_file()
+            } else {
+                path.to_string_lossy().ends_with(".tar.zst")
+            };
+            if !is_bundle {
+                continue;
+            }
+            let bundle = import_bundle(&path)?;
+            if !self.sees(&bundle.namespace()) {
+                continue;
+            }
+            self.index(&CatalogEntry::from_bundle(&bundle, &path)?)?;
+            indexed += 1;
+        }
+        Ok(indexed)
+    }
+
+    /// Run `run_id` of this catalog's namespace; unscoped, of whichever namespace sorts first
+    pub fn get(&self, run_id: &str) -> Result<Option<CatalogEntry>> {
+        let row = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM runs WHERE run_id = ?1 AND (?2 IS NULL OR namespace = ?2)
+                     ORDER BY namespace LIMIT 1",
+                    COLUMNS
+                ),
+                params![run_id, self.namespace.as_ref().map(|n| n.as_str())],
+                Self::row,
+            )
+            .optional()?;
+        row.map(|entry| self.with_metrics(entry?)).transpose()
+    }
+
+    /// Matching runs, newest first. A scoped catalog only returns runs of its namespace.
+    pub fn query(&self, query: &RunQuery) -> Result<Vec<CatalogEntry>> {
+        let namespace = match (&self.namespace, &query.namespace) {
+            (Some(scope), Some(wanted)) if scope != wanted => return Ok(Vec::new()),
+            (scope, wanted) => scope.as_ref().or(wanted.as_ref()),
+        };
+        let mut sql = format!("SELECT {} FROM runs WHERE 1 = 1", COLUMNS);
+        let mut args: Vec<Box<dyn ToSql>> = Vec::new();
+        let mut filter = |clause: &str, arg: Box<dyn ToSql>| {
+            args.push(arg);
+            sql.push_str(&format!(" AND {} ?{}", clause, args.len()));
+        };
+        if let Some(namespace) = namespace {
+            filter("namespace =", Box::new(namespace.as_str().to_string()));
+        }
+        if let Some(hash) = &query.ir_hash {
+            filter("ir_hash =", Box::new(hash.clone()));
+        }
+        if let Some(seed) = query.seed {
+            filter("seed =", Box::new(seed as i64));
+        }
+        if let Some(backend) = &query.backend {
+            filter("backend =", Box::new(backend.clone()));
+        }
+        if let Some(status) = &query.status {
+            filter("status =", Box::new(status.clone()));
+        }
+        if let Some(since) = &query.since {
+            filter("created_at >=", Box::new(timestamp(since)));
+        }
+        if let Some(until) = &query.until {
+            filter("created_at <", Box::new(timestamp(until)));
+        }
+        if let Some((key, min)) = &query.metric_at_least {
+            filter(
+                "(namespace, run_id) IN (SELECT namespace, run_id FROM run_metrics WHERE key =",
+                Box::new(key.clone()),
+            );
+            args.push(Box::new(*min));
+            sql.push_str(&format!(" AND value >= ?{})", args.len()));
+        }
+        sql.push_str(" ORDER BY created_at DESC, run_id, namespace");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params: Vec<&dyn ToSql> = args.iter().map(|a| a.as_ref()).collect();
+        let rows = stmt.query_map(params.as_slice(), Self::row)?;
+        rows.map(|row| self.with_metrics(row??)).collect()
+    }
+
+    fn row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Result<CatalogEntry>> {
+        let namespace: String = row.get(0)?;
+        let created_at: String = row.get(7)?;
+        let location: String = row.get(8)?;
+        let seed: Option<i64> = row.get(4)?;
+        let namespace = match Namespace::new(&namespace) {
+            Ok(namespace) => namespace,
+            Err(e) => return Ok(Err(e)),
+        };
+        Ok(DateTime::parse_from_rfc3339(&created_at)
+            .map_err(|e| anyhow!("invalid created_at in catalog: {}", e))
+            .map(|created_at| CatalogEntry {
+                namespace,
+                run_id: row.get(1).unwrap_or_default(),
+                artifact_type: row.get(2).unwrap_or_default(),
+                ir_hash: row.get(3).unwrap_or_default(),
+                seed: seed.map(|s| s as u64),
+                backend: row.get(5).unwrap_or_default(),
+                status: row.get(6).unwrap_or_default(),
+                created_at: created_at.with_timezone(&Utc),
+                location: PathBuf::from(location),
+                metrics: BTreeMap::new(),
+            }))
+    }
+
+    fn with_metrics(&self, mut entry: CatalogEntry) -> Result<CatalogEntry> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM run_metrics WHERE namespace = ?1 AND run_id = ?2")?;
+        let rows = stmt.query_map(params![entry.namespace.as_str(), entry.run_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })?;
+        for row in rows {
+            let (key, value) = row?;
+            entry.metrics.insert(key, value);
+        }
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Graph, Node};
+    use crate::storage::{ArtifactType, BundleBuilder};
+    use std::collections::HashMap;
+
+    fn graph(id: &str) -> Graph {
+        Graph {
+            nodes: vec![Node {
+                id: id.to_string(),
+                node_type: "MZI".to_string(),
+                params: HashMap::new(),
+                measure_mode: None,
+                measurement: None,
+                conditional_branches: None,
+                feed_forward: None,
+            }],
+            edges: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn bundle(graph: Graph, seed: u64, fidelity: f64) -> ArtifactBundle {
+        BundleBuilder::new(graph, ArtifactType::Run)
+            .with_initial_parameters(HashMap::new())
+            .with_seed(seed)
+            .with_results(serde_json::json!({"fidelity": fidelity, "counts": [1, 2]}))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_catalog_queries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut catalog = RunCatalog::open(dir.path().join("catalog.db")).unwrap();
+        let start = Utc::now() - chrono::Duration::seconds(1);
+        let runs = [
+            bundle(graph("x"), 42, 0.9),
+            bundle(graph("x"), 7, 0.95),
+            bundle(graph("y"), 42, 0.99),
+        ];
+        for run in &runs {
+            catalog
+                .export(run, &dir.path().join("artifacts"), ExportFormat::Directory)
+                .unwrap();
+        }
+
+        let hits = catalog
+            .query(
+                &RunQuery::new()
+                    .graph(&graph("x"))
+                    .unwrap()
+                    .seed(42)
+                    .since(start),
+            )
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].run_id, runs[0].artifact_id);
+        assert_eq!(hits[0].status, "completed");
+        assert_eq!(hits[0].metrics["fidelity"], 0.9);
+        assert!(!hits[0].metrics.contains_key("counts"));
+        assert!(hits[0].location.join("manifest.json").exists());
+
+        let good = catalog
+            .query(&RunQuery::new().metric_at_least("fidelity", 0.95))
+            .unwrap();
+        assert_eq!(good.len(), 2);
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(catalog
+            .query(&RunQuery::new().since(future))
+            .unwrap()
+            .is_empty());
+
+        // A fresh catalog rebuilt from the directory sees the same runs
+        let mut rebuilt = RunCatalog::in_memory().unwrap();
+        assert_eq!(rebuilt.index_dir(&dir.path().join("artifacts")).unwrap(), 3);
+        let entry = rebuilt.get(&runs[2].artifact_id).unwrap().unwrap();
+        assert_eq!(entry.seed, Some(42));
+        assert_eq!(entry.backend, runs[2].environment.device.device_type);
+        assert!(rebuilt.get("awen_missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_catalog_namespaces() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("catalog.db");
+        // A catalog from before namespaces
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE runs (run_id TEXT PRIMARY KEY, artifact_type TEXT NOT NULL,
+                     ir_hash TEXT NOT NULL, seed INTEGER, backend TEXT NOT NULL,
+                     status TEXT NOT NULL, created_at TEXT NOT NULL, location TEXT NOT NULL);
+                 CREATE INDEX runs_ir_hash ON runs (ir_hash);
+                 CREATE TABLE run_metrics (
+                     run_id TEXT NOT NULL REFERENCES runs (run_id) ON DELETE CASCADE,
+                     key TEXT NOT NULL, value REAL NOT NULL, PRIMARY KEY (run_id, key));
+                 INSERT INTO runs VALUES ('awen_old', 'run', 'h', 1, 'simulator', 'completed',
+                     '2026-01-01T00:00:00.000000Z', '/old');
+                 INSERT INTO run_metrics VALUES ('awen_old', 'fidelity', 0.5);",
+            )
+            .unwrap();
+        }
+        let mut catalog = RunCatalog::open(&path).unwrap();
+        let old = catalog.get("awen_old").unwrap().unwrap();
+        assert!(old.namespace.is_default());
+        assert_eq!(old.metrics["fidelity"], 0.5);
+
+        // The same run in two namespaces gets two entries
+        let optics = Namespace::new("optics").unwrap();
+        let mut entry = CatalogEntry::from_bundle(&bundle(graph("x"), 1, 0.9), &path).unwrap();
+        catalog.index(&entry).unwrap();
+        entry.namespace = optics.clone();
+        entry.metrics.insert("fidelity".to_string(), 0.8);
+        catalog.index(&entry).unwrap();
+        assert_eq!(catalog.query(&RunQuery::new()).unwrap().len(), 3);
+        let hits = catalog.query(&RunQuery::new().namespace(&optics)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].metrics["fidelity"], 0.8);
+
+        let mut scoped = RunCatalog::open(&path).unwrap().for_namespace(&optics);
+        assert!(scoped.get("awen_old").unwrap().is_none());
+        assert_eq!(
+            scoped.get(&entry.run_id).unwrap().unwrap().namespace,
+            optics
+        );
+        assert_eq!(
+            scoped
+                .query(&RunQuery::new().metric_at_least("fidelity", 0.0))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(scoped
+            .query(&RunQuery::new().namespace(&Namespace::default()))
+            .unwrap()
+            .is_empty());
+        assert!(scoped.index(&old).is_err());
+    }
+}
@@ -0,0 +1,610 @@
+//! Publication metadata for exported bundles
+//!
+//! [`DataCiteMetadata`] describes one or more exported bundles in the DataCite JSON layout
+//! used by the DataCite REST API and accepted by Zenodo. It covers creators, related
+//! identifiers, a run description, dates and the SHA-256 checksums of every bundle file.
+//! [`DataCiteMetadata::to_zenodo`] converts it to the `metadata` object of a Zenodo
+//! deposition. Artifact ids have no DataCite identifier type of their own, so they are
+//! written as `urn:awen:artifact:<id>` URNs.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use super::{import_bundle, ArtifactBundle};
+
+/// URN of an artifact, used wherever DataCite expects an identifier
+pub fn artifact_urn(artifact_id: &str) -> String {
+    format!("urn:awen:artifact:{}", artifact_id)
+}
+
+/// A creator of the deposited data
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Creator {
+    /// `Family, Given` for people, or an organisation name
+    pub name: String,
+    pub affiliation: Option<String>,
+    /// Bare ORCID iD, e.g. `0000-0002-1825-0097`
+    pub orcid: Option<String>,
+}
+
+impl Creator {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            affiliation: None,
+            orcid: None,
+        }
+    }
+
+    pub fn affiliation(mut self, affiliation: &str) -> Self {
+        self.affiliation = Some(affiliation.to_string());
+        self
+    }
+
+    pub fn orcid(mut self, orcid: &str) -> Self {
+        self.orcid = Some(orcid.to_string());
+        self
+    }
+}
+
+/// Caller-supplied publication details; anything unset is derived from the bundles
+#[derive(Clone, Debug, Default)]
+pub struct PublicationInfo {
+    pub title: String,
+    /// Defaults to the bundles' recorded creators
+    pub creators: Vec<Creator>,
+    /// Defaults to a summary of the runs
+    pub description: Option<String>,
+    pub publisher: Option<String>,
+    /// Defaults to the year of the newest bundle
+    pub publication_year: Option<String>,
+    /// DOI of the paper the data supports
+    pub paper_doi: Option<String>,
+    pub keywords: Vec<String>,
+    /// SPDX license id, e.g. `CC-BY-4.0`
+    pub license: Option<String>,
+}
+
+impl PublicationInfo {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn creator(mut self, creator: Creator) -> Self {
+        self.creators.push(creator);
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn publisher(mut self, publisher: &str) -> Self {
+        self.publisher = Some(publisher.to_string());
+        self
+    }
+
+    pub fn publication_year(mut self, year: &str) -> Self {
+        self.publication_year = Some(year.to_string());
+        self
+    }
+
+    pub fn paper_doi(mut self, doi: &str) -> Self {
+        self.paper_doi = Some(doi.to_string());
+        self
+    }
+
+    pub fn keyword(mut self, keyword: &str) -> Self {
+        self.keywords.push(keyword.to_string());
+        self
+    }
+
+    pub fn license(mut self, spdx_id: &str) -> Self {
+        self.license = Some(spdx_id.to_string());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteCreator {
+    pub name: String,
+    pub name_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affiliation: Vec<DataCiteAffiliation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub name_identifiers: Vec<NameIdentifier>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DataCiteAffiliation {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameIdentifier {
+    pub name_identifier: String,
+    pub name_identifier_scheme: String,
+    pub scheme_uri: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Title {
+    pub title: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceType {
+    pub resource_type_general: String,
+    pub resource_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Description {
+    pub description: String,
+    /// `Abstract` or `TechnicalInfo`
+    pub description_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedIdentifier {
+    pub related_identifier: String,
+    /// `DOI`, `URN`, `URL`, ...
+    pub related_identifier_type: String,
+    /// `IsSupplementTo`, `IsDerivedFrom`, `HasPart`, ...
+    pub relation_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteDate {
+    pub date: String,
+    pub date_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rights {
+    pub rights_identifier: String,
+    pub rights_identifier_scheme: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Subject {
+    pub subject: String,
+}
+
+/// DataCite metadata for a set of exported bundles
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCiteMetadata {
+    pub types: ResourceType,
+    pub titles: Vec<Title>,
+    pub creators: Vec<DataCiteCreator>,
+    pub publisher: String,
+    pub publication_year: String,
+    pub subjects: Vec<Subject>,
+    pub dates: Vec<DataCiteDate>,
+    pub related_identifiers: Vec<RelatedIdentifier>,
+    pub sizes: Vec<String>,
+    pub formats: Vec<String>,
+    pub version: String,
+    pub rights_list: Vec<Rights>,
+    pub descriptions: Vec<Description>,
+}
+
+impl DataCiteMetadata {
+    /// Metadata for the bundles exported at `paths` (directories or archives)
+    pub fn from_bundle_paths<P: AsRef<Path>>(paths: &[P], info: &PublicationInfo) -> Result<Self> {
+        let bundles = paths
+            .iter()
+            .map(|p| {
+                import_bundle(p.as_ref())
+                    .map_err(|e| anyhow!("cannot read bundle {}: {}", p.as_ref().display(), e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let sizes = paths
+            .iter()
+            .map(|p| stored_size(p.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_bundles(&bundles, &sizes, info)
+    }
+
+    /// Metadata for `bundles`, with `sizes[i]` the stored size of bundle `i` in bytes.
+    /// Checksums come from each manifest, so bundles must have been exported.
+    pub fn from_bundles(
+        bundles: &[ArtifactBundle],
+        sizes: &[u64],
+        info: &PublicationInfo,
+    ) -> Result<Self> {
+        if bundles.is_empty() {
+            return Err(anyhow!("publication metadata needs at least one bundle"));
+        }
+        if info.title.trim().is_empty() {
+            return Err(anyhow!("publication metadata needs a title"));
+        }
+        let created: BTreeSet<&str> = bundles
+            .iter()
+            .map(|b| b.manifest.created_at.as_str())
+            .collect();
+        let newest = created.iter().next_back().copied().unwrap_or_default();
+
+        let creators = if info.creators.is_empty() {
+            recorded_creators(bundles)
+        } else {
+            info.creators.clone()
+        };
+        if creators.is_empty() {
+            return Err(anyhow!(
+                "no creators given and none recorded in the bundles"
+            ));
+        }
+
+        let ids: BTreeSet<&str> = bundles.iter().map(|b| b.artifact_id.as_str()).collect();
+        let mut related = BTreeSet::new();
+        if let Some(doi) = &info.paper_doi {
+            related.insert(RelatedIdentifier {
+                related_identifier: doi.clone(),
+                related_identifier_type: "DOI".to_string(),
+                relation_type: "IsSupplementTo".to_string(),
+            });
+        }
+        for bundle in bundles {
+            related.insert(RelatedIdentifier {
+                related_identifier: artifact_urn(&bundle.artifact_id),
+                related_identifier_type: "URN".to_string(),
+                relation_type: "HasPart".to_string(),
+            });
+            // Parents deposited alongside are already parts
+            for parent in &bundle.provenance.parent_artifacts {
+                if !ids.contains(parent.as_str()) {
+                    related.insert(RelatedIdentifier {
+                        related_identifier: artifact_urn(parent),
+                        related_identifier_type: "URN".to_string(),
+                        relation_type: "IsDerivedFrom".to_string(),
+                    });
+                }
+            }
+        }
+
+        let mut descriptions = vec![Description {
+            description: info
+                .description
+                .clone()
+                .unwrap_or_else(|| run_summary(bundles)),
+            description_type: "Abstract".to_string(),
+        }];
+        descriptions.push(Description {
+            description: checksum_listing(bundles),
+            description_type: "TechnicalInfo".to_string(),
+        });
+
+        let mut subjects = vec!["photonic computing".to_string()];
+        subjects.extend(info.keywords.iter().cloned());
+        let versions: BTreeSet<&str> = bundles
+            .iter()
+            .map(|b| b.manifest.awen_runtime_version.as_str())
+            .collect();
+
+        Ok(Self {
+            types: ResourceType {
+                resource_type_general: "Dataset".to_string(),
+                resource_type: "awen artifact bundle".to_string(),
+            },
+            titles: vec![Title {
+                title: info.title.clone(),
+            }],
+            creators: creators.iter().map(datacite_creator).collect(),
+            publisher: info
+                .publisher
+                .clone()
+                .unwrap_or_else(|| "Zenodo".to_string()),
+            publication_year: info
+                .publication_year
+                .clone()
+                .unwrap_or_else(|| newest.chars().take(4).collect()),
+            subjects: subjects
+                .into_iter()
+                .map(|subject| Subject { subject })
+                .collect(),
+            dates: created
+                .iter()
+                .map(|date| DataCiteDate {
+                    date: date.to_string(),
+                    date_type: "Created".to_string(),
+                })
+                .collect(),
+            related_identifiers: related.into_iter().collect(),
+            sizes: vec![format!("{} bytes", sizes.iter().sum::<u64>())],
+            formats: vec![
+                "application/json".to_string(),
+                "application/zstd".to_string(),
+            ],
+            version: versions.into_iter().collect::<Vec<_>>().join(", "),
+            rights_list: info
+                .license
+                .iter()
+                .map(|spdx| Rights {
+                    rights_identifier: spdx.clone(),
+                    rights_identifier_scheme: "SPDX".to_string(),
+                })
+                .collect(),
+            descriptions,
+        })
+    }
+
+    /// The same metadata as a Zenodo deposition `metadata` object
+    pub fn to_zenodo(&self) -> serde_json::Value {
+        let creators: Vec<_> = self
+            .creators
+            .iter()
+            .map(|c| {
+                let mut creator = json!({"name": c.name});
+                if let Some(a) = c.affiliation.first() {
+                    creator["affiliation"] = json!(a.name);
+                }
+                if let Some(id) = c.name_identifiers.first() {
+                    creator["orcid"] = json!(id.name_identifier);
+                }
+                creator
+            })
+            .collect();
+        let related: Vec<_> = self
+            .related_identifiers
+            .iter()
+            .map(|r| {
+                json!({
+                    "identifier": r.related_identifier,
+                    "relation": lower_camel(&r.relation_type),
+                    "resource_type": if r.relation_type == "IsSupplementTo" {
+                        "publication-article"
+                    } else {
+                        "dataset"
+                    },
+                })
+            })
+            .collect();
+        // Zenodo renders the description as HTML
+        let description = self
+            .descriptions
+            .iter()
+            .map(|d| {
+                format!(
+                    "<p>{}</p>",
+                    html_escape(&d.description).replace('\n', "<br>")
+                )
+            })
+            .collect::<String>();
+        let mut metadata = json!({
+            "upload_type": "dataset",
+            "title": self.titles.first().map(|t| t.title.as_str()).unwrap_or_default(),
+            "creators": creators,
+            "description": description,
+            "publication_date": self.dates.last().map(|d| &d.date[..10.min(d.date.len())]),
+            "keywords": self.subjects.iter().map(|s| s.subject.as_str()).collect::<Vec<_>>(),
+            "related_identifiers": related,
+            "version": self.version,
+        });
+        if let Some(rights) = self.rights_list.first() {
+            metadata["license"] = json!(rights.rights_identifier.to_lowercase());
+        }
+        metadata
+    }
+
+    /// Write the DataCite JSON to `path`
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Total bytes stored under a bundle directory, or the size of an archive
+fn stored_size(path: &Path) -> Result<u64> {
+    if path.is_file() {
+        return Ok(path.metadata()?.len());
+    }
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| Ok(e.metadata()?.len()))
+        .sum()
+}
+
+/// Unique `user` / `organization` creators recorded in the bundles' provenance
+fn recorded_creators(bundles: &[ArtifactBundle]) -> Vec<Creator> {
+    let mut seen = BTreeSet::new();
+    bundles
+        .iter()
+        .filter_map(|b| {
+            let creator = &b.provenance.creator;
+            let name = creator.user.clone().or(creator.organization.clone())?;
+            seen.insert((name.clone(), creator.organization.clone()))
+                .then(|| Creator {
+                    name,
+                    affiliation: creator.organization.clone(),
+                    orcid: None,
+                })
+        })
+        .collect()
+}
+
+fn datacite_creator(creator: &Creator) -> DataCiteCreator {
+    DataCiteCreator {
+        name: creator.name.clone(),
+        // `Family, Given` is the DataCite convention for people
+        name_type: if creator.name.contains(',') || creator.orcid.is_some() {
+            "Personal"
+        } else {
+            "Organizational"
+        }
+        .to_string(),
+        affiliation: creator
+            .affiliation
+            .iter()
+            .map(|name| DataCiteAffiliation { name: name.clone() })
+            .collect(),
+        name_identifiers: creator
+            .orcid
+            .iter()
+            .map(|orcid| NameIdentifier {
+                name_identifier: format!("https://orcid.org/{}", orcid),
+                name_identifier_scheme: "ORCID".to_string(),
+                scheme_uri: "https://orcid.org".to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Default abstract: what the bundles hold and what produced them
+fn run_summary(bundles: &[ArtifactBundle]) -> String {
+    let types: BTreeSet<&str> = bundles
+        .iter()
+        .map(|b| b.manifest.artifact_type.as_str())
+        .collect();
+    let backends: BTreeSet<&str> = bundles
+        .iter()
+        .map(|b| b.environment.device.device_type.as_str())
+        .collect();
+    let seeds: Vec<String> = bundles
+        .iter()
+        .filter_map(|b| b.seed.map(|s| s.to_string()))
+        .collect();
+    let mut summary = format!(
+        "{} awen artifact bundle(s) ({}) produced on {}.",
+        bundles.len(),
+        types.into_iter().collect::<Vec<_>>().join(", "),
+        backends.into_iter().collect::<Vec<_>>().join(", ")
+    );
+    if !seeds.is_empty() {
+        summary.push_str(&format!(" Seeds: {}.", seeds.join(", ")));
+    }
+    for bundle in bundles {
+        if let Some(notes) = &bundle.provenance.notes {
+            summary.push_str(&format!("\n{}: {}", bundle.artifact_id, notes));
+        }
+    }
+    summary
+}
+
+/// `sha256  <artifact>/<file>` lines for every checksummed bundle file
+fn checksum_listing(bundles: &[ArtifactBundle]) -> String {
+    let mut lines = vec!["SHA-256 checksums of the bundle files:".to_string()];
+    for bundle in bundles {
+        for (file, sha256) in &bundle.manifest.checksums {
+            lines.push(format!("{}  {}/{}", sha256, bundle.artifact_id, file));
+        }
+    }
+    lines.join("\n")
+}
+
+fn lower_camel(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|c| c.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::Graph;
+    use crate::storage::{export_bundle, ArtifactType, BundleBuilder, ExportFormat};
+    use std::collections::HashMap;
+
+    fn bundle(seed: u64, parent: Option<&str>) -> ArtifactBundle {
+        let mut builder = BundleBuilder::new(
+            Graph {
+                nodes: vec![],
+                edges: vec![],
+                metadata: HashMap::new(),
+            },
+            ArtifactType::Run,
+        )
+        .with_seed(seed)
+        .with_results(serde_json::json!({"fidelity": 0.9}));
+        if let Some(parent) = parent {
+            builder = builder.add_parent_artifact(parent.to_string());
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_datacite_metadata_for_bundles() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = bundle(1, None);
+        let second = bundle(2, Some(&first.artifact_id));
+        let third = bundle(3, Some("awen_feedfacefeedface"));
+        let paths: Vec<_> = [&first, &second, &third]
+            .into_iter()
+            .map(|b| export_bundle(b, dir.path(), ExportFormat::Directory).unwrap())
+            .collect();
+
+        let info = PublicationInfo::new("MZI mesh characterisation")
+            .creator(
+                Creator::new("Smith, Jane")
+                    .affiliation("Photonics Lab")
+                    .orcid("0000-0002-1825-0097"),
+            )
+            .paper_doi("10.1234/example.5678")
+            .keyword("MZI")
+            .license("CC-BY-4.0");
+        let metadata = DataCiteMetadata::from_bundle_paths(&paths, &info).unwrap();
+
+        assert_eq!(metadata.types.resource_type_general, "Dataset");
+        assert_eq!(metadata.creators[0].name_type, "Personal");
+        let relations: Vec<(&str, &str)> = metadata
+            .related_identifiers
+            .iter()
+            .map(|r| (r.relation_type.as_str(), r.related_identifier.as_str()))
+            .collect();
+        assert!(relations.contains(&("IsSupplementTo", "10.1234/example.5678")));
+        assert_eq!(relations.iter().filter(|r| r.0 == "HasPart").count(), 3);
+        // Only parents outside the deposit are listed as sources
+        assert_eq!(
+            relations
+                .iter()
+                .filter(|r| r.0 == "IsDerivedFrom")
+                .map(|r| r.1)
+                .collect::<Vec<_>>(),
+            [artifact_urn("awen_feedfacefeedface")]
+        );
+        let technical = &metadata.descriptions[1].description;
+        assert!(technical.contains(&format!("{}/ir/original.json", first.artifact_id)));
+        assert!(metadata.descriptions[0]
+            .description
+            .contains("Seeds: 1, 2, 3"));
+
+        let zenodo = metadata.to_zenodo();
+        assert_eq!(zenodo["upload_type"], "dataset");
+        assert_eq!(zenodo["creators"][0]["affiliation"], "Photonics Lab");
+        assert_eq!(zenodo["license"], "cc-by-4.0");
+
+        // DataCite JSON round-trips
+        let path = dir.path().join("datacite.json");
+        metadata.write(&path).unwrap();
+        let parsed: DataCiteMetadata =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(parsed, metadata);
+
+        assert!(DataCiteMetadata::from_bundles(&[], &[], &info).is_err());
+    }
+}
@@ -49,6 +49,11 @@ pub fn compute_deterministic_id(
     Ok(format!("awen_{}", hex))
 }
 
+/// SHA-256 of the canonical IR JSON, identifying a graph across runs
+pub fn ir_hash(ir: &Graph) -> Result<String> {
+    Ok(hex::encode(Sha256::digest(canonical_json(ir)?.as_bytes())))
+}
+
 /// Serialize to canonical JSON (sorted keys, no whitespace)
 fn canonical_json<T: serde::Serialize>(value: &T) -> Result<String> {
     // Serialize to serde_json::Value first to ensure key ordering
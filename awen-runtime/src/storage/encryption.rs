@@ -0,0 +1,512 @@
+//! At-rest encryption of bundle archives
+//!
+//! An encrypted bundle is a `.tar.zst` archive (see [`ExportFormat::Archive`]) sealed with
+//! AES-256-GCM into `awen_<type>_<hash>.tar.zst.enc`. The file starts with [`MAGIC`], a
+//! length-prefixed JSON [`EncryptionHeader`], and then the archive in length-prefixed chunks of
+//! at most [`CHUNK_SIZE`] plaintext bytes. Each chunk has its own nonce: a random per-file
+//! prefix, the chunk counter, and a flag set only on the last chunk. The header is bound to
+//! every chunk as associated data. Reordering, truncating or extending the chunks therefore
+//! fails to decrypt, as does editing the header.
+//!
+//! Keys are never stored in the bundle. The header names a key id, and a [`KeyProvider`]
+//! resolves it on export and import.
+//!
+//! [`ExportFormat::Archive`]: super::ExportFormat::Archive
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Extension added to encrypted bundle archives
+pub const ENCRYPTED_EXTENSION: &str = "enc";
+
+/// Leading bytes of an encrypted bundle
+pub const MAGIC: &[u8; 8] = b"AWENENC1";
+
+/// Plaintext bytes per encrypted chunk
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Environment variable holding a hex-encoded 32-byte key for [`EnvKeyProvider`]
+pub const KEY_ENV_VAR: &str = "AWEN_BUNDLE_KEY";
+
+/// Environment variable naming the key held in [`KEY_ENV_VAR`]; defaults to `env`
+pub const KEY_ID_ENV_VAR: &str = "AWEN_BUNDLE_KEY_ID";
+
+const ALGORITHM: &str = "aes-256-gcm";
+const NONCE_PREFIX_LEN: usize = 7;
+const TAG_LEN: usize = 16;
+/// Upper bound on the JSON header, checked before it is read and authenticated
+const MAX_HEADER_LEN: usize = 4 * 1024;
+
+/// 256-bit bundle encryption key
+pub type BundleKey = [u8; 32];
+
+/// Resolves bundle encryption keys
+///
+/// Export seals with the key named by [`KeyProvider::current_key_id`]. Import looks up the
+/// key id recorded in the bundle, so keys can be rotated without re-encrypting older bundles.
+pub trait KeyProvider: std::fmt::Debug + Send + Sync {
+    /// Key id that new bundles are encrypted with
+    fn current_key_id(&self) -> Result<String>;
+
+    /// Key for `key_id`
+    fn key(&self, key_id: &str) -> Result<BundleKey>;
+}
+
+/// Keys held in memory, e.g. fetched from a secrets manager
+#[derive(Clone)]
+pub struct StaticKeyProvider {
+    current: String,
+    keys: BTreeMap<String, BundleKey>,
+}
+
+impl std::fmt::Debug for StaticKeyProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StaticKeyProvider")
+            .field("current", &self.current)
+            .field("key_ids", &self.keys.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl StaticKeyProvider {
+    pub fn new(key_id: &str, key: BundleKey) -> Self {
+        Self {
+            current: key_id.to_string(),
+            keys: BTreeMap::from([(key_id.to_string(), key)]),
+        }
+    }
+
+    /// Also accept `key_id` on import, e.g. a key rotated out of use for new bundles
+    pub fn with_key(mut self, key_id: &str, key: BundleKey) -> Self {
+        self.keys.insert(key_id.to_string(), key);
+        self
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key_id(&self) -> Result<String> {
+        Ok(self.current.clone())
+    }
+
+    fn key(&self, key_id: &str) -> Result<BundleKey> {
+        self.keys
+            .get(key_id)
+            .copied()
+            .ok_or_else(|| anyhow!("no key for key id {}", key_id))
+    }
+}
+
+/// Key read from [`KEY_ENV_VAR`], named by [`KEY_ID_ENV_VAR`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnvKeyProvider;
+
+impl KeyProvider for EnvKeyProvider {
+    fn current_key_id(&self) -> Result<String> {
+        Ok(std::env::var(KEY_ID_ENV_VAR).unwrap_or_else(|_| "env".to_string()))
+    }
+
+    fn key(&self, key_id: &str) -> Result<BundleKey> {
+        let current = self.current_key_id()?;
+        if key_id != current {
+            return Err(anyhow!(
+                "bundle needs key {}, but {} holds key {}",
+                key_id,
+                KEY_ENV_VAR,
+                current
+            ));
+        }
+        let hex_key = std::env::var(KEY_ENV_VAR)
+            .map_err(|_| anyhow!("{} is not set; cannot decrypt bundle", KEY_ENV_VAR))?;
+        parse_key(&hex_key)
+    }
+}
+
+/// Directory of hex-encoded key files named `<key_id>.key`
+#[derive(Clone, Debug)]
+pub struct KeyDirProvider {
+    dir: PathBuf,
+    current: String,
+}
+
+impl KeyDirProvider {
+    /// Encrypt with `<dir>/<current>.key`; decrypt with whichever key a bundle names
+    pub fn new(dir: &Path, current: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            current: current.to_string(),
+        }
+    }
+}
+
+impl KeyProvider for KeyDirProvider {
+    fn current_key_id(&self) -> Result<String> {
+        Ok(self.current.clone())
+    }
+
+    fn key(&self, key_id: &str) -> Result<BundleKey> {
+        if key_id.is_empty() || key_id.contains(['/', '\\']) || key_id.starts_with('.') {
+            return Err(anyhow!("invalid key id {:?}", key_id));
+        }
+        let path = self.dir.join(format!("{}.key", key_id));
+        let hex_key = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("cannot read key file {}: {}", path.display(), e))?;
+        parse_key(&hex_key)
+    }
+}
+
+/// New random key
+pub fn generate_key() -> BundleKey {
+    rand::random()
+}
+
+/// Parse a hex-encoded 32-byte key
+pub fn parse_key(hex_key: &str) -> Result<BundleKey> {
+    hex::decode(hex_key.trim())
+        .ok()
+        .and_then(|bytes| BundleKey::try_from(bytes.as_slice()).ok())
+        .ok_or_else(|| anyhow!("bundle key must be 64 hex characters (32 bytes)"))
+}
+
+/// Whether `path` names an encrypted bundle
+pub fn is_encrypted_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == ENCRYPTED_EXTENSION)
+}
+
+/// Unencrypted header at the start of an encrypted bundle
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionHeader {
+    pub algorithm: String,
+    pub key_id: String,
+    /// Hex-encoded per-file nonce prefix
+    pub nonce_prefix: String,
+    pub chunk_size: usize,
+}
+
+/// Why the header of an encrypted bundle could not be read
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EncryptionHeaderError {
+    #[error("not an encrypted awen bundle")]
+    NotEncrypted,
+    #[error("encrypted bundle header is truncated")]
+    Truncated,
+    #[error("encrypted bundle header is {len} bytes; at most {max} are allowed")]
+    TooLarge { len: usize, max: usize },
+}
+
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+/// Streams plaintext into an encrypted bundle; call [`EncryptWriter::finish`] to seal it
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    cipher: Aes256Gcm,
+    header: Vec<u8>,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    /// Write the header to `inner` and start encrypting with the provider's current key
+    pub fn new(mut inner: W, keys: &dyn KeyProvider) -> Result<Self> {
+        let key_id = keys.current_key_id()?;
+        let key = keys.key(&key_id)?;
+        let nonce_prefix: [u8; NONCE_PREFIX_LEN] = rand::random();
+        let header = serde_json::to_vec(&EncryptionHeader {
+            algorithm: ALGORITHM.to_string(),
+            key_id,
+            nonce_prefix: hex::encode(nonce_prefix),
+            chunk_size: CHUNK_SIZE,
+        })?;
+        inner.write_all(MAGIC)?;
+        inner.write_all(&(header.len() as u32).to_le_bytes())?;
+        inner.write_all(&header)?;
+        Ok(Self {
+            inner,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+            header,
+            nonce_prefix,
+            counter: 0,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        })
+    }
+
+    fn write_chunk(&mut self, last: bool) -> std::io::Result<()> {
+        let nonce = chunk_nonce(&self.nonce_prefix, self.counter, last);
+        let sealed = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &self.buffer,
+                    aad: &self.header,
+                },
+            )
+            .map_err(|_| std::io::Error::other("bundle encryption failed"))?;
+        self.inner.write_all(&(sealed.len() as u32).to_le_bytes())?;
+        self.inner.write_all(&sealed)?;
+        self.buffer.clear();
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| std::io::Error::other("bundle too large to encrypt"))?;
+        Ok(())
+    }
+
+    /// Seal the final chunk and return the inner writer
+    pub fn finish(mut self) -> Result<W> {
+        self.write_chunk(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // A full buffer is only sealed once more data arrives, so the last chunk is never
+        // sealed without the last flag
+        if self.buffer.len() == CHUNK_SIZE && !buf.is_empty() {
+            self.write_chunk(false)?;
+        }
+        let n = buf.len().min(CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Streams the plaintext of an encrypted bundle, authenticating each chunk as it is read
+pub struct DecryptReader<R: Read> {
+    inner: R,
+    cipher: Aes256Gcm,
+    header: Vec<u8>,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    chunk: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+/// Read the magic and header of an encrypted bundle
+pub fn read_encryption_header(mut reader: impl Read) -> Result<EncryptionHeader> {
+    Ok(read_header_bytes(&mut reader)?.0)
+}
+
+fn read_header_bytes(reader: &mut impl Read) -> Result<(EncryptionHeader, Vec<u8>)> {
+    let mut magic = [0u8; 8];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| EncryptionHeaderError::NotEncrypted)?;
+    if &magic != MAGIC {
+        return Err(EncryptionHeaderError::NotEncrypted.into());
+    }
+    let mut len = [0u8; 4];
+    reader
+        .read_exact(&mut len)
+        .map_err(|_| EncryptionHeaderError::Truncated)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_HEADER_LEN {
+        return Err(EncryptionHeaderError::TooLarge {
+            len,
+            max: MAX_HEADER_LEN,
+        }
+        .into());
+    }
+    let mut bytes = vec![0u8; len];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|_| EncryptionHeaderError::Truncated)?;
+    let header: EncryptionHeader = serde_json::from_slice(&bytes)?;
+    if header.algorithm != ALGORITHM {
+        return Err(anyhow!(
+            "unsupported bundle encryption algorithm {}",
+            header.algorithm
+        ));
+    }
+    Ok((header, bytes))
+}
+
+impl<R: Read> DecryptReader<R> {
+    /// Read the header from `inner` and resolve its key id through `keys`
+    pub fn new(mut inner: R, keys: &dyn KeyProvider) -> Result<Self> {
+        let (header, header_bytes) = read_header_bytes(&mut inner)?;
+        let nonce_prefix = hex::decode(&header.nonce_prefix)
+            .ok()
+            .and_then(|b| <[u8; NONCE_PREFIX_LEN]>::try_from(b.as_slice()).ok())
+            .ok_or_else(|| anyhow!("malformed nonce prefix in bundle header"))?;
+        let key = keys.key(&header.key_id)?;
+        Ok(Self {
+            inner,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+            header: header_bytes,
+            nonce_prefix,
+            counter: 0,
+            chunk: Vec::new(),
+            pos: 0,
+            done: false,
+        })
+    }
+
+    fn next_chunk(&mut self) -> std::io::Result<()> {
+        let mut len = [0u8; 4];
+        self.inner.read_exact(&mut len).map_err(|_| truncated())?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > CHUNK_SIZE + TAG_LEN {
+            return Err(std::io::Error::other("oversized chunk in encrypted bundle"));
+        }
+        let mut sealed = vec![0u8; len];
+        self.inner
+            .read_exact(&mut sealed)
+            .map_err(|_| truncated())?;
+
+        // Only the chunk sealed as last authenticates with the last flag set
+        let payload = |msg| Payload {
+            msg,
+            aad: &self.header[..],
+        };
+        for last in [false, true] {
+            let nonce = chunk_nonce(&self.nonce_prefix, self.counter, last);
+            if let Ok(plain) = self
+                .cipher
+                .decrypt(Nonce::from_slice(&nonce), payload(&sealed[..]))
+            {
+                self.chunk = plain;
+                self.pos = 0;
+                self.done = last;
+                self.counter = self.counter.wrapping_add(1);
+                return Ok(());
+            }
+        }
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "bundle decryption failed: wrong key or corrupted data",
+        ))
+    }
+}
+
+fn truncated() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        "encrypted bundle is truncated",
+    )
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.next_chunk()?;
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seal(plain: &[u8], keys: &dyn KeyProvider) -> Vec<u8> {
+        let mut writer = EncryptWriter::new(Vec::new(), keys).unwrap();
+        writer.write_all(plain).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn open(sealed: &[u8], keys: &dyn KeyProvider) -> std::io::Result<Vec<u8>> {
+        let mut plain = Vec::new();
+        DecryptReader::new(sealed, keys)
+            .map_err(std::io::Error::other)?
+            .read_to_end(&mut plain)?;
+        Ok(plain)
+    }
+
+    #[test]
+    fn test_encrypt_round_trip_and_tamper_detection() {
+        let keys = StaticKeyProvider::new("lab-2026", generate_key());
+        for len in [0, 10, CHUNK_SIZE, 2 * CHUNK_SIZE + 5] {
+            let plain: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let sealed = seal(&plain, &keys);
+            assert_eq!(open(&sealed, &keys).unwrap(), plain);
+        }
+
+        let plain = vec![7u8; 2 * CHUNK_SIZE + 5];
+        let sealed = seal(&plain, &keys);
+        let header = read_encryption_header(&sealed[..]).unwrap();
+        assert_eq!(header.key_id, "lab-2026");
+
+        // Wrong key, flipped ciphertext bit, and a dropped final chunk all fail
+        let other = StaticKeyProvider::new("lab-2026", generate_key());
+        assert!(open(&sealed, &other).is_err());
+        let mut flipped = sealed.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(open(&flipped, &keys).is_err());
+        let final_chunk = 4 + 5 + TAG_LEN;
+        assert!(open(&sealed[..sealed.len() - final_chunk], &keys).is_err());
+        assert!(open(&sealed, &StaticKeyProvider::new("other", generate_key())).is_err());
+    }
+
+    #[test]
+    fn test_malformed_headers_are_rejected() {
+        let keys = StaticKeyProvider::new("lab-2026", generate_key());
+        let sealed = seal(b"payload", &keys);
+        let header_err = |bytes: &[u8]| {
+            read_encryption_header(bytes)
+                .unwrap_err()
+                .downcast::<EncryptionHeaderError>()
+                .unwrap()
+        };
+
+        assert_eq!(header_err(b"AWENZIP1"), EncryptionHeaderError::NotEncrypted);
+        assert_eq!(header_err(&sealed[..10]), EncryptionHeaderError::Truncated);
+        assert_eq!(header_err(&sealed[..20]), EncryptionHeaderError::Truncated);
+
+        // A huge declared length fails before anything is allocated or read
+        let mut oversized = MAGIC.to_vec();
+        oversized.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            header_err(&oversized),
+            EncryptionHeaderError::TooLarge {
+                len: u32::MAX as usize,
+                max: MAX_HEADER_LEN
+            }
+        );
+        assert!(DecryptReader::new(&oversized[..], &keys).is_err());
+    }
+
+    #[test]
+    fn test_key_providers() {
+        let key = generate_key();
+        assert_eq!(parse_key(&format!("{}\n", hex::encode(key))).unwrap(), key);
+        assert!(parse_key("abcd").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("2026-q1.key"), hex::encode(key)).unwrap();
+        let provider = KeyDirProvider::new(dir.path(), "2026-q1");
+        assert_eq!(provider.key("2026-q1").unwrap(), key);
+        assert!(provider.key("../2026-q1").is_err());
+        assert!(provider.key("missing").is_err());
+
+        let rotated = StaticKeyProvider::new("new", generate_key()).with_key("old", key);
+        assert_eq!(rotated.current_key_id().unwrap(), "new");
+        assert_eq!(rotated.key("old").unwrap(), key);
+        assert!(!format!("{:?}", rotated).contains(&hex::encode(key)));
+    }
+}
@@ -1,6 +1,7 @@
 //! Environment capture for reproducibility
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// Runtime environment information
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -10,6 +11,9 @@ pub struct RuntimeInfo {
     pub build_timestamp: String,
     pub build_profile: String,
     pub rust_version: String,
+    /// SHA-256 of the Cargo.lock the runtime was built against
+    #[serde(default)]
+    pub cargo_lock_sha256: Option<String>,
     pub features: Vec<String>,
     pub plugins: Vec<PluginInfo>,
 }
@@ -31,6 +35,23 @@ pub struct SystemInfo {
     pub cpu_cores: usize,
     pub memory_gb: u64,
     pub hostname: String,
+    #[serde(default)]
+    pub kernel_version: Option<String>,
+    /// CPU feature flags (`flags` on x86, `Features` on ARM), sorted
+    #[serde(default)]
+    pub cpu_flags: Vec<String>,
+    #[serde(default)]
+    pub gpus: Vec<GpuInfo>,
+}
+
+/// GPU visible to the host
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub name: String,
+    pub vendor: String,
+    /// Kernel driver bound to the device (`nvidia`, `amdgpu`, `i915`, ...)
+    pub driver: Option<String>,
+    pub driver_version: Option<String>,
 }
 
 /// Device information
@@ -40,7 +61,38 @@ pub struct DeviceInfo {
     pub device_id: String,
     pub capabilities: DeviceCapabilities,
     pub firmware_version: Option<String>,
+    #[serde(default)]
+    pub serial_number: Option<String>,
+    #[serde(default)]
+    pub driver_version: Option<String>,
     pub calibration_date: Option<String>,
+    /// Firmware versions from the device health report, keyed by component
+    #[serde(default)]
+    pub firmware_versions: BTreeMap<String, String>,
+}
+
+impl DeviceInfo {
+    /// Overwrite the hardware identity fields from a HAL device's provenance.
+    pub fn apply_provenance(&mut self, provenance: &crate::hal::DeviceProvenance) {
+        self.device_id = provenance.device_id.clone();
+        self.firmware_version = provenance.firmware_version.clone();
+        self.serial_number = provenance.serial_number.clone();
+        self.driver_version = provenance.driver_version.clone();
+    }
+
+    /// Apply the identity fields and every firmware version a HAL health report carries. Any
+    /// key naming firmware counts, so multi-component devices can report `laser_firmware`,
+    /// `dac_firmware_version` and so on alongside `firmware_version`.
+    pub fn apply_health_report(&mut self, device_id: &str, report: &HashMap<String, String>) {
+        self.apply_provenance(&crate::hal::DeviceProvenance::from_metadata(
+            device_id, report,
+        ));
+        self.firmware_versions = report
+            .iter()
+            .filter(|(key, _)| key.contains("firmware"))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+    }
 }
 
 /// Device capabilities
@@ -78,6 +130,7 @@ fn capture_runtime() -> RuntimeInfo {
         rust_version: option_env!("RUSTC_VERSION")
             .unwrap_or("unknown")
             .to_string(),
+        cargo_lock_sha256: option_env!("AWEN_CARGO_LOCK_SHA256").map(str::to_string),
         features: vec!["runtime".to_string(), "observability".to_string()],
         plugins: vec![PluginInfo {
             name: "reference_sim".to_string(),
@@ -97,6 +150,9 @@ fn capture_system() -> SystemInfo {
         hostname: std::env::var("HOSTNAME")
             .or_else(|_| std::env::var("COMPUTERNAME"))
             .unwrap_or_else(|_| "unknown".to_string()),
+        kernel_version: get_kernel_version(),
+        cpu_flags: get_cpu_flags(),
+        gpus: get_gpus(),
     }
 }
 
@@ -114,7 +170,10 @@ fn capture_device() -> DeviceInfo {
             power_range_dbm: [-30.0, 10.0],
         },
         firmware_version: None,
+        serial_number: None,
+        driver_version: None,
         calibration_date: None,
+        firmware_versions: BTreeMap::new(),
     }
 }
 
@@ -182,6 +241,132 @@ fn get_memory_gb() -> u64 {
     }
 }
 
+fn get_kernel_version() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+fn get_cpu_flags() -> Vec<String> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/cpuinfo")
+            .map(|s| parse_cpu_flags(&s))
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Flags of the first CPU listed in `/proc/cpuinfo`
+fn parse_cpu_flags(cpuinfo: &str) -> Vec<String> {
+    let mut flags: Vec<String> = cpuinfo
+        .lines()
+        .find(|line| line.starts_with("flags") || line.starts_with("Features"))
+        .and_then(|line| line.split(':').nth(1))
+        .map(|s| s.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default();
+    flags.sort();
+    flags.dedup();
+    flags
+}
+
+fn get_gpus() -> Vec<GpuInfo> {
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(entries) = std::fs::read_dir("/sys/class/drm") else {
+            return Vec::new();
+        };
+        let mut cards: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                // cardN only; cardN-HDMI-A-1 and friends are connectors of the same device
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix("card"))
+                    .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .collect();
+        cards.sort();
+        cards
+            .into_iter()
+            .filter_map(|card| {
+                let uevent = std::fs::read_to_string(card.join("device/uevent")).ok()?;
+                let mut gpu = parse_drm_uevent(&uevent)?;
+                if let Some(driver) = &gpu.driver {
+                    gpu.driver_version =
+                        std::fs::read_to_string(format!("/sys/module/{}/version", driver))
+                            .ok()
+                            .map(|v| v.trim().to_string());
+                }
+                // The proprietary NVIDIA driver reports the marketing name
+                if let Some(slot) = uevent_value(&uevent, "PCI_SLOT_NAME") {
+                    let info = format!("/proc/driver/nvidia/gpus/{}/information", slot);
+                    if let Some(model) = std::fs::read_to_string(info)
+                        .ok()
+                        .and_then(|s| parse_nvidia_model(&s))
+                    {
+                        gpu.name = model;
+                    }
+                }
+                Some(gpu)
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+fn uevent_value<'a>(uevent: &'a str, key: &str) -> Option<&'a str> {
+    uevent.lines().find_map(|line| {
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix('='))
+            .map(str::trim)
+    })
+}
+
+/// GPU identity from a DRM card's `device/uevent`; `None` for non-PCI devices
+fn parse_drm_uevent(uevent: &str) -> Option<GpuInfo> {
+    let pci_id = uevent_value(uevent, "PCI_ID")?;
+    let vendor_id = pci_id.split(':').next().unwrap_or_default().to_uppercase();
+    let vendor = match vendor_id.as_str() {
+        "10DE" => "NVIDIA",
+        "1002" => "AMD",
+        "8086" => "Intel",
+        other => other,
+    }
+    .to_string();
+    Some(GpuInfo {
+        name: format!("{} [{}]", vendor, pci_id),
+        vendor,
+        driver: uevent_value(uevent, "DRIVER").map(str::to_string),
+        driver_version: None,
+    })
+}
+
+fn parse_nvidia_model(information: &str) -> Option<String> {
+    information
+        .lines()
+        .find(|line| line.starts_with("Model:"))
+        .and_then(|line| line.split(':').nth(1))
+        .map(|s| s.trim().to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,4 +381,72 @@ mod tests {
         assert!(env.system.cpu_cores > 0);
         assert_eq!(env.device.device_type, "simulated");
     }
+
+    #[test]
+    fn test_apply_device_provenance() {
+        let mut env = capture_environment();
+        env.device.apply_provenance(&crate::hal::DeviceProvenance {
+            device_id: "chip-a".to_string(),
+            firmware_version: Some("2.1.0".to_string()),
+            serial_number: Some("SN123".to_string()),
+            driver_version: Some("0.4.0".to_string()),
+        });
+        assert_eq!(env.device.device_id, "chip-a");
+        assert_eq!(env.device.serial_number.as_deref(), Some("SN123"));
+
+        // Snapshots written before these fields existed must still parse.
+        let mut json = serde_json::to_value(&env.device).unwrap();
+        json.as_object_mut().unwrap().remove("serial_number");
+        json.as_object_mut().unwrap().remove("driver_version");
+        let parsed: DeviceInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.serial_number, None);
+    }
+
+    #[test]
+    fn test_apply_health_report_firmware_versions() {
+        let mut env = capture_environment();
+        let report: HashMap<String, String> = [
+            ("status", "ok"),
+            ("firmware_version", "2.1.0"),
+            ("laser_firmware", "1.4"),
+            ("serial_number", "SN123"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        env.device.apply_health_report("chip-a", &report);
+
+        assert_eq!(env.device.firmware_version.as_deref(), Some("2.1.0"));
+        assert_eq!(env.device.serial_number.as_deref(), Some("SN123"));
+        assert_eq!(
+            env.device.firmware_versions.keys().collect::<Vec<_>>(),
+            ["firmware_version", "laser_firmware"]
+        );
+    }
+
+    #[test]
+    fn test_parse_system_sources() {
+        let cpuinfo =
+            "processor\t: 0\nflags\t\t: sse2 avx2 fpu avx2\n\nprocessor\t: 1\nflags\t\t: fpu\n";
+        assert_eq!(parse_cpu_flags(cpuinfo), ["avx2", "fpu", "sse2"]);
+        assert_eq!(parse_cpu_flags("Features\t: fp asimd\n"), ["asimd", "fp"]);
+
+        let gpu = parse_drm_uevent("DRIVER=amdgpu\nPCI_ID=1002:73BF\nPCI_SLOT_NAME=0000:03:00.0\n")
+            .unwrap();
+        assert_eq!(gpu.vendor, "AMD");
+        assert_eq!(gpu.driver.as_deref(), Some("amdgpu"));
+        assert_eq!(parse_drm_uevent("DRIVER=vc4-drm\n"), None);
+        assert_eq!(
+            parse_nvidia_model("Model: \t\t NVIDIA A100-SXM4-40GB\nIRQ: 34\n").as_deref(),
+            Some("NVIDIA A100-SXM4-40GB")
+        );
+
+        // Snapshots written before these fields existed must still parse
+        let mut json = serde_json::to_value(capture_environment().system).unwrap();
+        for key in ["kernel_version", "cpu_flags", "gpus"] {
+            json.as_object_mut().unwrap().remove(key);
+        }
+        let parsed: SystemInfo = serde_json::from_value(json).unwrap();
+        assert!(parsed.gpus.is_empty());
+    }
 }
@@ -1,35 +1,236 @@
 //! Export bundles to various formats
+//!
+//! `CompressedDirectory` keeps the directory layout but stores the bulky files (results,
+//! traces, timeline, events, state history and measurements) zstd-compressed with a `.zst`
+//! suffix; checksums cover the stored bytes. `Archive` packs the whole directory into one
+//! `awen_<type>_<hash>.tar.zst` (see [`archive_file_name`]) with `manifest.json` as its first
+//! entry, so the manifest can be read without unpacking.
+//! [`import_bundle`](super::import_bundle) reads all three transparently. An archive can also
+//! be encrypted at rest; see [`super::encryption`].
+//!
+//! Sweep members can leave out the IR and environment snapshot when a shared parent bundle
+//! already holds identical files (see [`ExportOptions::shared_parent`] and [`export_sweep`]).
+//! The manifest then lists them under `references`, and import reads them from the parent.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::fs;
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 
+use super::encryption::{EncryptWriter, KeyProvider, ENCRYPTED_EXTENSION};
+use super::import::{open_bundle_file, read_manifest};
+use super::manifest::ComponentRef;
+use super::signing::{BundleSigner, SIGNATURE_FILE};
+use super::state_history::{
+    json_array_to_jsonl, StateHistoryFormat, STATE_HISTORY_JSON, STATE_HISTORY_JSONL,
+};
 use super::ArtifactBundle;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use walkdir::WalkDir;
 
 #[derive(Clone, Debug)]
 pub enum ExportFormat {
     Directory,
     TarGz,
+    /// Directory with large files zstd-compressed in place.
+    CompressedDirectory,
+    /// Single zstd-compressed tar archive.
+    Archive,
 }
 
+/// Extension added to files stored zstd-compressed.
+pub const COMPRESSED_EXTENSION: &str = "zst";
+
+/// zstd level used for bundles; favours speed over ratio.
+pub const ZSTD_LEVEL: i32 = 3;
+
+/// Options for [`export_bundle_with`]
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions<'a> {
+    /// Sign the bundle, writing a detached `signature.json`
+    pub signer: Option<&'a BundleSigner>,
+    /// How to store a `quantum_states.json` history; `Jsonl` allows incremental reads
+    pub state_history: StateHistoryFormat,
+    /// Encrypt the bundle with the provider's current key; requires [`ExportFormat::Archive`]
+    /// and writes `awen_<type>_<hash>.tar.zst.enc`
+    pub encryption: Option<&'a dyn KeyProvider>,
+    /// Exported bundle directory to share unchanged IR and environment files with. Files
+    /// identical to the parent's are recorded as references instead of being written; the
+    /// parent must stay next to this bundle for it to import.
+    pub shared_parent: Option<&'a Path>,
+}
+
+/// Bundle files that sweep members may share with their parent
+pub const SHAREABLE_FILES: [&str; 3] = [
+    "ir/original.json",
+    "ir/lowered.json",
+    "environment/snapshot.json",
+];
+
 /// Export artifact bundle to filesystem
 pub fn export_bundle(
     bundle: &ArtifactBundle,
     output_dir: &Path,
     format: ExportFormat,
 ) -> Result<PathBuf> {
+    export_bundle_with(bundle, output_dir, format, &ExportOptions::default())
+}
+
+/// Export artifact bundle and sign it, writing a detached `signature.json`
+pub fn export_signed_bundle(
+    bundle: &ArtifactBundle,
+    output_dir: &Path,
+    format: ExportFormat,
+    signer: &BundleSigner,
+) -> Result<PathBuf> {
+    let options = ExportOptions {
+        signer: Some(signer),
+        ..Default::default()
+    };
+    export_bundle_with(bundle, output_dir, format, &options)
+}
+
+/// Export artifact bundle with explicit [`ExportOptions`]
+///
+/// Large files are streamed from the run directory to the bundle, so export memory does not
+/// grow with the size of the traces or state history.
+pub fn export_bundle_with(
+    bundle: &ArtifactBundle,
+    output_dir: &Path,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> Result<PathBuf> {
+    if options.encryption.is_some() && !matches!(format, ExportFormat::Archive) {
+        return Err(anyhow!(
+            "encrypted export requires ExportFormat::Archive, got {:?}",
+            format
+        ));
+    }
     match format {
-        ExportFormat::Directory => export_to_directory(bundle, output_dir),
-        ExportFormat::TarGz => export_to_directory(bundle, output_dir),
+        ExportFormat::Directory => export_to_directory(bundle, output_dir, false, options),
+        ExportFormat::TarGz => export_to_directory(bundle, output_dir, false, options),
+        ExportFormat::CompressedDirectory => export_to_directory(bundle, output_dir, true, options),
+        ExportFormat::Archive => export_to_archive(bundle, output_dir, options),
+    }
+}
+
+/// Export the members of a sweep into `output_dir`, storing the IR and environment once
+///
+/// The first bundle is the shared parent. Every later bundle references the parent's files
+/// where its own are identical. An `Archive` sweep keeps the parent as a plain directory, so
+/// unpacked members can reach it. Encryption is not supported, since the parent would be
+/// left unencrypted.
+pub fn export_sweep(
+    bundles: &[ArtifactBundle],
+    output_dir: &Path,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> Result<Vec<PathBuf>> {
+    if options.encryption.is_some() {
+        return Err(anyhow!("sweep export does not support encryption"));
+    }
+    let Some((parent, members)) = bundles.split_first() else {
+        return Ok(Vec::new());
+    };
+    let parent_format = match format {
+        ExportFormat::Archive => ExportFormat::Directory,
+        ref other => other.clone(),
+    };
+    let parent_dir = export_bundle_with(parent, output_dir, parent_format, options)?;
+    let member_options = ExportOptions {
+        shared_parent: Some(&parent_dir),
+        ..options.clone()
+    };
+    let mut paths = vec![parent_dir.clone()];
+    for member in members {
+        paths.push(export_bundle_with(
+            member,
+            output_dir,
+            format.clone(),
+            &member_options,
+        )?);
     }
+    Ok(paths)
 }
 
-/// Export to directory structure
-fn export_to_directory(bundle: &ArtifactBundle, output_dir: &Path) -> Result<PathBuf> {
+/// Path of the manifest inside an archive; always the first entry.
+pub const ARCHIVE_MANIFEST_PATH: &str = "manifest.json";
+
+/// File name of a bundle archive, e.g. `awen_run_0123456789abcdef.tar.zst`.
+pub fn archive_file_name(bundle: &ArtifactBundle) -> String {
+    format!(
+        "awen_{}_{}.tar.{}",
+        bundle.manifest.artifact_type,
+        bundle
+            .artifact_id
+            .strip_prefix("awen_")
+            .unwrap_or(&bundle.artifact_id),
+        COMPRESSED_EXTENSION
+    )
+}
+
+/// Export to a staging directory, then pack it into a single `.tar.zst` archive.
+fn export_to_archive(
+    bundle: &ArtifactBundle,
+    output_dir: &Path,
+    options: &ExportOptions,
+) -> Result<PathBuf> {
+    let staging = output_dir.join(format!(".{}.staging", bundle.artifact_id));
+    let bundle_dir = export_to_directory(bundle, &staging, false, options)?;
+    let mut archive_path = output_dir.join(archive_file_name(bundle));
+    if options.encryption.is_some() {
+        archive_path
+            .as_mut_os_string()
+            .push(format!(".{}", ENCRYPTED_EXTENSION));
+    }
+
+    let packed = fs::File::create(&archive_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|file| match options.encryption {
+            Some(keys) => {
+                let writer = EncryptWriter::new(BufWriter::new(file), keys)?;
+                pack_archive(&bundle_dir, writer)?.finish()?.flush()?;
+                Ok(())
+            }
+            None => Ok(pack_archive(&bundle_dir, BufWriter::new(file))?.flush()?),
+        });
+    fs::remove_dir_all(&staging)?;
+    packed.map_err(|e| anyhow!("failed to write {}: {}", archive_path.display(), e))?;
+    Ok(archive_path)
+}
+
+/// Tar `bundle_dir` zstd-compressed into `out`: the manifest first, then every other file in
+/// path order, so the same bundle always packs to the same entry order.
+fn pack_archive<W: Write>(bundle_dir: &Path, out: W) -> Result<W> {
+    let mut tar = tar::Builder::new(zstd::Encoder::new(out, ZSTD_LEVEL)?);
+    tar.append_path_with_name(
+        bundle_dir.join(ARCHIVE_MANIFEST_PATH),
+        ARCHIVE_MANIFEST_PATH,
+    )?;
+    for entry in WalkDir::new(bundle_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let rel = entry.path().strip_prefix(bundle_dir)?;
+        if entry.file_type().is_file() && rel != Path::new(ARCHIVE_MANIFEST_PATH) {
+            tar.append_path_with_name(entry.path(), rel)?;
+        }
+    }
+    Ok(tar.into_inner()?.finish()?)
+}
+
+/// Export to directory structure, zstd-compressing results, observability and state history
+/// when `compress` is set
+fn export_to_directory(
+    bundle: &ArtifactBundle,
+    output_dir: &Path,
+    compress: bool,
+    options: &ExportOptions,
+) -> Result<PathBuf> {
     let bundle_dir = output_dir.join(&bundle.artifact_id);
+    let mut shared = SharedFiles::new(options.shared_parent, &bundle.artifact_id)?;
 
     // Create directory structure
     fs::create_dir_all(&bundle_dir)?;
@@ -41,9 +242,9 @@ fn export_to_directory(bundle: &ArtifactBundle, output_dir: &Path) -> Result<Pat
     fs::create_dir_all(bundle_dir.join("provenance"))?;
 
     // Write IR
-    write_json(&bundle_dir.join("ir/original.json"), &bundle.ir_original)?;
+    shared.write_json(&bundle_dir, "ir/original.json", &bundle.ir_original)?;
     if let Some(ref lowered) = bundle.ir_lowered {
-        write_json(&bundle_dir.join("ir/lowered.json"), lowered)?;
+        shared.write_json(&bundle_dir, "ir/lowered.json", lowered)?;
     }
 
     // Write parameters
@@ -64,8 +265,9 @@ fn export_to_directory(bundle: &ArtifactBundle, output_dir: &Path) -> Result<Pat
     }
 
     // Write environment
-    write_json(
-        &bundle_dir.join("environment/snapshot.json"),
+    shared.write_json(
+        &bundle_dir,
+        "environment/snapshot.json",
         &bundle.environment,
     )?;
     if let Some(seed) = bundle.seed {
@@ -73,7 +275,9 @@ fn export_to_directory(bundle: &ArtifactBundle, output_dir: &Path) -> Result<Pat
     }
 
     // Write results
-    write_json(&bundle_dir.join("results/outputs.json"), &bundle.results)?;
+    write_stream(&bundle_dir.join("results/outputs.json"), compress, |out| {
+        Ok(serde_json::to_writer_pretty(out, &bundle.results)?)
+    })?;
 
     // Write provenance
     write_json(
@@ -81,37 +285,179 @@ fn export_to_directory(bundle: &ArtifactBundle, output_dir: &Path) -> Result<Pat
         &bundle.provenance,
     )?;
 
-    // Write manifest
-    write_json(&bundle_dir.join("manifest.json"), &bundle.manifest)?;
+    // Copy observability artifacts and state history from the run directory
+    if let Some(ref obs) = bundle.observability {
+        let files = [
+            (&obs.traces, "traces.jsonl"),
+            (&obs.timeline, "timeline.json"),
+            (&obs.metrics, "metrics.json"),
+            (&obs.events, "events.jsonl"),
+            (&obs.measurements, "measurements.json"),
+        ];
+        for (source, name) in files {
+            if let Some(source) = source.as_ref().filter(|p| p.is_file()) {
+                // metrics.json is small and read by tooling directly
+                let compress = compress && name != "metrics.json";
+                write_stream(&bundle_dir.join("provenance").join(name), compress, |out| {
+                    std::io::copy(&mut open_bundle_file(source)?, out)?;
+                    Ok(())
+                })?;
+            }
+        }
+
+        if let Some(source) = obs.states.as_ref().filter(|p| p.is_file()) {
+            // A history already stored as JSONL stays JSONL
+            let source_is_jsonl = source
+                .to_string_lossy()
+                .trim_end_matches(&format!(".{}", COMPRESSED_EXTENSION))
+                .ends_with(".jsonl");
+            let convert = options.state_history == StateHistoryFormat::Jsonl && !source_is_jsonl;
+            let name = if convert || source_is_jsonl {
+                STATE_HISTORY_JSONL
+            } else {
+                STATE_HISTORY_JSON
+            };
+            write_stream(&bundle_dir.join("provenance").join(name), compress, |out| {
+                let mut input = open_bundle_file(source)?;
+                if convert {
+                    json_array_to_jsonl(input, out)?;
+                } else {
+                    std::io::copy(&mut input, out)?;
+                }
+                Ok(())
+            })?;
+        }
+    }
 
     // Write citation if present
     if let Some(ref citation) = bundle.provenance.citation {
         std::fs::write(bundle_dir.join("provenance/citation.txt"), citation)?;
     }
 
-    // Compute checksums for all files (exclude checksums.json itself)
-    let mut checksums: HashMap<String, String> = HashMap::new();
-    for entry in WalkDir::new(&bundle_dir).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path.is_file() {
-            if let Ok(rel) = path.strip_prefix(&bundle_dir) {
-                if rel == std::path::Path::new("checksums.json") {
-                    continue;
+    // A signature from an earlier export no longer matches
+    let signature_path = bundle_dir.join(SIGNATURE_FILE);
+    if signature_path.exists() {
+        fs::remove_file(&signature_path)?;
+    }
+
+    // Write manifest with checksums of every payload file
+    let mut manifest = bundle.manifest.clone();
+    manifest.checksums = file_checksums(&bundle_dir, &["manifest.json", "checksums.json"])?;
+    manifest.references = shared.references;
+    write_json(&bundle_dir.join("manifest.json"), &manifest)?;
+
+    // Write checksums.json, which additionally covers the manifest
+    write_json(
+        &bundle_dir.join("checksums.json"),
+        &file_checksums(&bundle_dir, &["checksums.json"])?,
+    )?;
+
+    // Sign manifest and checksums
+    if let Some(signer) = options.signer {
+        signer.sign_bundle(&bundle_dir)?;
+    }
+
+    Ok(bundle_dir)
+}
+
+/// Writes shareable files, or references to identical files in a shared parent bundle
+struct SharedFiles {
+    parent: Option<(String, BTreeMap<String, String>)>,
+    references: BTreeMap<String, ComponentRef>,
+}
+
+impl SharedFiles {
+    fn new(parent_dir: Option<&Path>, artifact_id: &str) -> Result<Self> {
+        let parent = match parent_dir {
+            Some(dir) => {
+                let manifest = read_manifest(dir)?;
+                // A bundle never references itself, e.g. when the parent is re-exported
+                (manifest.artifact_id != artifact_id)
+                    .then_some((manifest.artifact_id, manifest.checksums))
+            }
+            None => None,
+        };
+        Ok(Self {
+            parent,
+            references: BTreeMap::new(),
+        })
+    }
+
+    /// Write `value` to `bundle_dir/rel`, or reference the parent's copy if it is identical.
+    /// Only files the parent stores itself are referenced, so references never chain.
+    fn write_json<T: serde::Serialize>(
+        &mut self,
+        bundle_dir: &Path,
+        rel: &str,
+        value: &T,
+    ) -> Result<()> {
+        let json = serde_json::to_string_pretty(value)?;
+        let path = bundle_dir.join(rel);
+        if let Some((parent_id, checksums)) = &self.parent {
+            let sha256 = hex::encode(Sha256::digest(json.as_bytes()));
+            if SHAREABLE_FILES.contains(&rel) && checksums.get(rel) == Some(&sha256) {
+                if path.exists() {
+                    fs::remove_file(&path)?;
                 }
-                let mut file = std::fs::File::open(path)?;
-                let mut hasher = Sha256::new();
-                std::io::copy(&mut file, &mut hasher)?;
-                let digest = hasher.finalize();
-                let hex = hex::encode(digest);
-                checksums.insert(rel.to_string_lossy().to_string(), hex);
+                self.references.insert(
+                    rel.to_string(),
+                    ComponentRef {
+                        artifact_id: parent_id.clone(),
+                        sha256,
+                    },
+                );
+                return Ok(());
             }
         }
+        fs::write(path, json)?;
+        Ok(())
     }
+}
 
-    // Write checksums.json
-    write_json(&bundle_dir.join("checksums.json"), &checksums)?;
+/// SHA-256 of every file under `bundle_dir` except the bundle-relative paths in `exclude`
+fn file_checksums(bundle_dir: &Path, exclude: &[&str]) -> Result<BTreeMap<String, String>> {
+    let mut checksums = BTreeMap::new();
+    for entry in WalkDir::new(bundle_dir).into_iter().filter_map(|e| e.ok()) {
+        let rel = entry.path().strip_prefix(bundle_dir)?;
+        if !entry.file_type().is_file() || exclude.iter().any(|name| rel == Path::new(name)) {
+            continue;
+        }
+        checksums.insert(
+            rel.to_string_lossy().to_string(),
+            sha256_file(entry.path())?,
+        );
+    }
+    Ok(checksums)
+}
 
-    Ok(bundle_dir)
+/// Hex SHA-256 of a file's stored bytes
+pub(crate) fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Stream `write` into `path`, or zstd-compressed into `path.zst`
+fn write_stream(
+    path: &Path,
+    compress: bool,
+    write: impl FnOnce(&mut dyn Write) -> Result<()>,
+) -> Result<()> {
+    if compress {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".");
+        name.push(COMPRESSED_EXTENSION);
+        let file = BufWriter::new(fs::File::create(PathBuf::from(name))?);
+        let mut encoder = zstd::Encoder::new(file, ZSTD_LEVEL)?;
+        write(&mut encoder)?;
+        encoder.finish()?.flush()?;
+    } else {
+        let mut file = BufWriter::new(fs::File::create(path)?);
+        write(&mut file)?;
+        file.flush()?;
+    }
+    Ok(())
 }
 
 /// Write JSON to file
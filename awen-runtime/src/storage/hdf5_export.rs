@@ -0,0 +1,437 @@
+//! HDF5 export of dense numeric artifacts (`hdf5` feature)
+//!
+//! Writes state trajectories, Wigner grids and spectra into one HDF5 file (pure Rust, no
+//! libhdf5 needed), laid out for h5py/MATLAB pipelines:
+//!
+//! - `/states/<mode_id>/amplitudes`: `[steps, levels, 2]` real/imaginary Fock amplitudes per
+//!   state-history step, zero-padded to the widest step; `photon_numbers` `[levels]` gives the
+//!   photon number of each level. Attribute `mode_type`.
+//! - `/wigner/<mode>/{q, p, values}`: the sampled axes and `W(q[i], p[j])` as `[len q, len p]`.
+//!   Attribute `state_id`.
+//! - `/spectra/<name>/{frequency, power}` with `frequency_unit` and `power_unit` attributes.
+//!
+//! Every dataset carries `units` and `description` attributes, and the file root records the
+//! run id, seed and runtime version when known. Large datasets are deflate-compressed.
+
+use anyhow::{anyhow, Result};
+use hdf5_pure::{AttrValue, FileBuilder, GroupBuilder};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::read_bundle_file;
+use super::state_history::{StateHistoryReader, STATE_HISTORY_JSON, STATE_HISTORY_JSONL};
+use crate::quantum::wigner::WignerGrid;
+use crate::state::QuantumState;
+
+/// Datasets with at least this many elements are stored chunked and deflate-compressed
+const COMPRESS_THRESHOLD: usize = 4096;
+
+/// One sampled spectrum
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    pub name: String,
+    pub frequency: Vec<f64>,
+    pub power: Vec<f64>,
+    pub frequency_unit: String,
+    pub power_unit: String,
+}
+
+/// Collects numeric artifacts and writes them as one HDF5 file
+#[derive(Default)]
+pub struct Hdf5Export {
+    attributes: BTreeMap<String, AttrValue>,
+    states: Vec<QuantumState>,
+    wigners: Vec<WignerGrid>,
+    spectra: Vec<Spectrum>,
+}
+
+impl Hdf5Export {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything a run directory holds: state history, `wigner_<mode>.json` grids, and the
+    /// run id (directory name) and seed as root attributes
+    pub fn from_run_dir(run_dir: &Path) -> Result<Self> {
+        let mut export = Self::new();
+        if let Some(name) = run_dir.file_name() {
+            export = export.with_attribute("run_id", &name.to_string_lossy());
+        }
+        let history = [STATE_HISTORY_JSONL, STATE_HISTORY_JSON]
+            .iter()
+            .map(|name| run_dir.join(name))
+            .find(|p| p.is_file());
+        if let Some(path) = history {
+            for record in StateHistoryReader::open(&path)? {
+                export.states.push(serde_json::from_value(record?)?);
+            }
+        }
+        if let Some(seed) = export.states.first().and_then(|s| s.seed) {
+            export
+                .attributes
+                .insert("seed".to_string(), AttrValue::U64(seed));
+        }
+
+        let mut wigner_files: Vec<_> = std::fs::read_dir(run_dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("wigner_") && n.ends_with(".json"))
+            })
+            .collect();
+        wigner_files.sort();
+        for path in wigner_files {
+            export
+                .wigners
+                .push(serde_json::from_slice(&read_bundle_file(&path)?)?);
+        }
+        Ok(export)
+    }
+
+    /// String attribute on the file root, e.g. the runtime version
+    pub fn with_attribute(mut self, name: &str, value: &str) -> Self {
+        self.attributes
+            .insert(name.to_string(), AttrValue::String(value.to_string()));
+        self
+    }
+
+    pub fn with_states(mut self, states: Vec<QuantumState>) -> Self {
+        self.states.extend(states);
+        self
+    }
+
+    pub fn with_wigner(mut self, grid: WignerGrid) -> Self {
+        self.wigners.push(grid);
+        self
+    }
+
+    pub fn with_spectrum(mut self, spectrum: Spectrum) -> Self {
+        self.spectra.push(spectrum);
+        self
+    }
+
+    /// Write the HDF5 file to `path`
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut file = FileBuilder::new();
+        for (name, value) in &self.attributes {
+            file.set_attr(name, value.clone());
+        }
+        file.set_attr(
+            "awen_runtime_version",
+            AttrValue::String(env!("CARGO_PKG_VERSION").to_string()),
+        );
+
+        if !self.states.is_empty() {
+            let mut states = file.create_group("states");
+            for mode in self.mode_ids() {
+                let group = self.mode_trajectory(&mut states, &mode)?;
+                states.add_group(group);
+            }
+            file.add_group(states.finish());
+        }
+
+        if !self.wigners.is_empty() {
+            let mut wigner = file.create_group("wigner");
+            for grid in &self.wigners {
+                let mut group = wigner.create_group(&grid.mode);
+                group.set_attr("state_id", AttrValue::String(grid.state_id.clone()));
+                dataset(
+                    &mut group,
+                    "q",
+                    &grid.q,
+                    &[grid.q.len()],
+                    "1",
+                    "q quadrature axis",
+                );
+                dataset(
+                    &mut group,
+                    "p",
+                    &grid.p,
+                    &[grid.p.len()],
+                    "1",
+                    "p quadrature axis",
+                );
+                let values: Vec<f64> = grid.values.iter().flatten().copied().collect();
+                if values.len() != grid.q.len() * grid.p.len() {
+                    return Err(anyhow!("Wigner grid of {} is not rectangular", grid.mode));
+                }
+                dataset(
+                    &mut group,
+                    "values",
+                    &values,
+                    &[grid.q.len(), grid.p.len()],
+                    "1",
+                    "W(q[i], p[j])",
+                );
+                wigner.add_group(group.finish());
+            }
+            file.add_group(wigner.finish());
+        }
+
+        if !self.spectra.is_empty() {
+            let mut spectra = file.create_group("spectra");
+            for spectrum in &self.spectra {
+                if spectrum.frequency.len() != spectrum.power.len() {
+                    return Err(anyhow!(
+                        "spectrum {} has {} frequencies but {} power values",
+                        spectrum.name,
+                        spectrum.frequency.len(),
+                        spectrum.power.len()
+                    ));
+                }
+                let mut group = spectra.create_group(&spectrum.name);
+                let len = [spectrum.frequency.len()];
+                dataset(
+                    &mut group,
+                    "frequency",
+                    &spectrum.frequency,
+                    &len,
+                    &spectrum.frequency_unit,
+                    "sample frequency",
+                );
+                dataset(
+                    &mut group,
+                    "power",
+                    &spectrum.power,
+                    &len,
+                    &spectrum.power_unit,
+                    "power at each frequency",
+                );
+                group.set_attr(
+                    "frequency_unit",
+                    AttrValue::String(spectrum.frequency_unit.clone()),
+                );
+                group.set_attr("power_unit", AttrValue::String(spectrum.power_unit.clone()));
+                spectra.add_group(group.finish());
+            }
+            file.add_group(spectra.finish());
+        }
+
+        file.write(path)
+            .map_err(|e| anyhow!("cannot write {}: {}", path.display(), e))
+    }
+
+    /// Mode ids in order of first appearance in the state history
+    fn mode_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for mode in self.states.iter().flat_map(|s| &s.modes) {
+            if !ids.contains(&mode.mode_id) {
+                ids.push(mode.mode_id.clone());
+            }
+        }
+        ids
+    }
+
+    /// `/states/<mode>`: amplitudes of `mode` at every step, indexed by photon number
+    fn mode_trajectory(
+        &self,
+        parent: &mut GroupBuilder,
+        mode_id: &str,
+    ) -> Result<hdf5_pure::FinishedGroup> {
+        // Each step's amplitudes keyed by photon number
+        let steps: Vec<BTreeMap<u32, (f64, f64)>> = self
+            .states
+            .iter()
+            .map(|state| {
+                state
+                    .modes
+                    .iter()
+                    .find(|m| m.mode_id == mode_id)
+                    .and_then(|m| {
+                        let amplitudes = m.amplitudes.as_ref()?;
+                        Some(
+                            amplitudes
+                                .iter()
+                                .enumerate()
+                                .map(|(i, c)| {
+                                    let n = m
+                                        .photon_numbers
+                                        .as_ref()
+                                        .and_then(|ns| ns.get(i).copied())
+                                        .unwrap_or(i as u32);
+                                    (n, (c.re, c.im))
+                                })
+                                .collect(),
+                        )
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        let levels: Vec<u32> = steps
+            .iter()
+            .flat_map(|s| s.keys().copied())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let mut values = Vec::with_capacity(steps.len() * levels.len() * 2);
+        for step in &steps {
+            for n in &levels {
+                let (re, im) = step.get(n).copied().unwrap_or((0.0, 0.0));
+                values.extend([re, im]);
+            }
+        }
+        let mut group = parent.create_group(mode_id);
+        if let Some(mode) = self
+            .states
+            .iter()
+            .flat_map(|s| &s.modes)
+            .find(|m| m.mode_id == mode_id)
+        {
+            group.set_attr("mode_type", AttrValue::String(mode.mode_type.clone()));
+        }
+        dataset(
+            &mut group,
+            "amplitudes",
+            &values,
+            &[steps.len(), levels.len(), 2],
+            "1",
+            "Fock amplitudes per step as [step, level, (re, im)]; zero where not tracked",
+        );
+        let photon_numbers: Vec<f64> = levels.iter().map(|&n| n as f64).collect();
+        dataset(
+            &mut group,
+            "photon_numbers",
+            &photon_numbers,
+            &[levels.len()],
+            "photons",
+            "photon number of each level",
+        );
+        Ok(group.finish())
+    }
+}
+
+/// Add a float dataset of `shape` with `units` and `description` attributes
+fn dataset(
+    group: &mut GroupBuilder,
+    name: &str,
+    data: &[f64],
+    shape: &[usize],
+    units: &str,
+    description: &str,
+) {
+    let shape: Vec<u64> = shape.iter().map(|&d| d as u64).collect();
+    let builder = group.create_dataset(name);
+    builder.with_f64_data(data).with_shape(&shape);
+    if data.len() >= COMPRESS_THRESHOLD {
+        // Chunk along the first axis so readers can load a slice at a time
+        let mut chunks = shape.clone();
+        chunks[0] = chunks[0].clamp(1, 64);
+        builder.with_chunks(&chunks).with_deflate(4);
+    }
+    builder
+        .set_attr("units", AttrValue::String(units.to_string()))
+        .set_attr("description", AttrValue::String(description.to_string()));
+}
+
+/// Write a run directory's state history and Wigner grids to `path` as HDF5
+pub fn export_run_hdf5(run_dir: &Path, path: &Path) -> Result<()> {
+    Hdf5Export::from_run_dir(run_dir)?.write(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quantum::wigner::PhaseSpaceGrid;
+    use crate::state::QuantumMode;
+    use num_complex::Complex64;
+
+    #[test]
+    fn test_hdf5_export_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let run_dir = dir.path().join("awen_run_h");
+        std::fs::create_dir_all(&run_dir).unwrap();
+        let state = |amplitudes: Vec<Complex64>, numbers: Option<Vec<u32>>| QuantumState {
+            id: "s".to_string(),
+            modes: vec![QuantumMode {
+                mode_id: "m0".to_string(),
+                mode_type: "quantum_fock".to_string(),
+                photon_numbers: numbers,
+                amplitudes: Some(amplitudes),
+            }],
+            coherence_window: serde_json::from_value(serde_json::json!({
+                "id": "w", "start_ns": 0, "end_ns": 1, "duration_ns": 1,
+                "decoherence_timescale_ns": null, "cross_mode_decoherence_ns": null,
+                "idle_time_budget_ns": null, "fidelity_threshold": 0.9, "notes": null
+            }))
+            .unwrap(),
+            seed: Some(11),
+            provenance: Default::default(),
+        };
+        let history = vec![
+            state(vec![Complex64::new(1.0, 0.0)], None),
+            state(
+                vec![Complex64::new(0.6, 0.0), Complex64::new(0.0, 0.8)],
+                Some(vec![0, 2]),
+            ),
+        ];
+        std::fs::write(
+            run_dir.join(STATE_HISTORY_JSON),
+            serde_json::to_string(&history).unwrap(),
+        )
+        .unwrap();
+        let grid = WignerGrid::sample("m0", "s", &PhaseSpaceGrid::square(1.0, 3), |q, p| {
+            q + 10.0 * p
+        })
+        .unwrap();
+        std::fs::write(
+            run_dir.join("wigner_m0.json"),
+            serde_json::to_string(&grid).unwrap(),
+        )
+        .unwrap();
+
+        let path = dir.path().join("run.h5");
+        Hdf5Export::from_run_dir(&run_dir)
+            .unwrap()
+            .with_spectrum(Spectrum {
+                name: "homodyne_noise".to_string(),
+                frequency: vec![1e6, 2e6],
+                power: vec![-80.0, -82.5],
+                frequency_unit: "Hz".to_string(),
+                power_unit: "dBm".to_string(),
+            })
+            .with_spectrum(Spectrum {
+                name: "sweep".to_string(),
+                frequency: (0..5000).map(f64::from).collect(),
+                power: vec![-90.0; 5000],
+                frequency_unit: "Hz".to_string(),
+                power_unit: "dBm".to_string(),
+            })
+            .write(&path)
+            .unwrap();
+
+        let file = hdf5_pure::File::open(&path).unwrap();
+        let amplitudes = file.dataset("states/m0/amplitudes").unwrap();
+        assert_eq!(amplitudes.shape().unwrap(), vec![2, 2, 2]);
+        assert_eq!(
+            amplitudes.read_f64().unwrap(),
+            vec![1.0, 0.0, 0.0, 0.0, 0.6, 0.0, 0.0, 0.8]
+        );
+        assert_eq!(
+            file.dataset("states/m0/photon_numbers")
+                .unwrap()
+                .read_f64()
+                .unwrap(),
+            vec![0.0, 2.0]
+        );
+        let values = file.dataset("wigner/m0/values").unwrap();
+        assert_eq!(values.shape().unwrap(), vec![3, 3]);
+        assert_eq!(values.read_f64().unwrap()[1], -1.0, "W(q[0], p[1])");
+        let power = file.dataset("spectra/homodyne_noise/power").unwrap();
+        assert_eq!(power.read_f64().unwrap(), vec![-80.0, -82.5]);
+        assert!(matches!(
+            power.attrs().unwrap().get("units"),
+            Some(AttrValue::String(unit)) if unit == "dBm"
+        ));
+        let sweep = file.dataset("spectra/sweep/frequency").unwrap();
+        assert_eq!(
+            sweep.read_f64().unwrap()[4999],
+            4999.0,
+            "compressed dataset"
+        );
+        let root = file.root().attrs().unwrap();
+        assert!(matches!(root.get("run_id"), Some(AttrValue::String(id)) if id == "awen_run_h"));
+        assert!(matches!(root.get("seed"), Some(AttrValue::U64(11))));
+    }
+}
@@ -1,54 +1,286 @@
 //! Import bundles from various formats
 
 use anyhow::Result;
-use std::path::Path;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
 
+use super::encryption::{
+    is_encrypted_bundle, DecryptReader, EnvKeyProvider, KeyProvider, ENCRYPTED_EXTENSION,
+};
+use super::export::{sha256_file, ARCHIVE_MANIFEST_PATH, COMPRESSED_EXTENSION};
+use super::migration::{is_legacy_run_dir, migrate_manifest};
+use super::signing::verify_bundle_signature;
+use super::state_history::{STATE_HISTORY_JSON, STATE_HISTORY_JSONL};
 use super::ArtifactBundle;
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-/// Import artifact bundle from filesystem
+/// `path`, or its `.zst` sibling when only the compressed file exists
+pub(crate) fn stored_path(path: &Path) -> Option<PathBuf> {
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(COMPRESSED_EXTENSION);
+    let compressed = PathBuf::from(name);
+    compressed.exists().then_some(compressed)
+}
+
+/// Open a bundle file for streaming reads, decompressing it if stored as `.zst`
+pub fn open_bundle_file(path: &Path) -> Result<Box<dyn Read>> {
+    let file = std::fs::File::open(path)?;
+    if path
+        .extension()
+        .is_some_and(|ext| ext == COMPRESSED_EXTENSION)
+    {
+        Ok(Box::new(zstd::Decoder::new(file)?))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/// Read a bundle file, decompressing it if stored as `.zst`
+pub fn read_bundle_file(path: &Path) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open_bundle_file(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Parse `path` (or `path.zst`) as JSON without buffering the whole file
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let stored = stored_path(path)
+        .ok_or_else(|| anyhow::anyhow!("Missing bundle file: {}", path.display()))?;
+    Ok(serde_json::from_reader(BufReader::new(open_bundle_file(
+        &stored,
+    )?))?)
+}
+
+/// Unpack a `.tar.zst` archive beside itself, into a directory named after it
+///
+/// A `.tar.zst.enc` archive is decrypted with `keys` while it streams into the directory, so
+/// no plaintext archive is written; the unpacked directory itself is not encrypted.
+fn unpack_archive(archive: &Path, keys: &dyn KeyProvider) -> Result<PathBuf> {
+    let name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let encrypted = is_encrypted_bundle(archive);
+    let name = if encrypted {
+        name.strip_suffix(&format!(".{}", ENCRYPTED_EXTENSION))
+            .unwrap_or(&name)
+    } else {
+        &name
+    };
+    let stem = name
+        .strip_suffix(&format!(".tar.{}", COMPRESSED_EXTENSION))
+        .ok_or_else(|| anyhow::anyhow!("Not a bundle archive: {}", archive.display()))?;
+    let dest = archive.with_file_name(stem);
+    let file = BufReader::new(std::fs::File::open(archive)?);
+    let input: Box<dyn Read> = if encrypted {
+        Box::new(DecryptReader::new(file, keys)?)
+    } else {
+        Box::new(file)
+    };
+    tar::Archive::new(zstd::Decoder::new(input)?)
+        .unpack(&dest)
+        .map_err(|e| anyhow::anyhow!("Failed to unpack {}: {}", archive.display(), e))?;
+    Ok(dest)
+}
+
+/// Parse `path` as JSON if the bundle holds it
+fn optional_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    if stored_path(path).is_none() {
+        return Ok(None);
+    }
+    read_json(path).map(Some)
+}
+
+/// Read the manifest of a `.tar.zst` archive without unpacking it
+pub fn read_archive_manifest(archive: &Path) -> Result<super::Manifest> {
+    let decoder = zstd::Decoder::new(std::fs::File::open(archive)?)?;
+    let mut tar = tar::Archive::new(decoder);
+    let mut first = tar
+        .entries()?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Empty bundle archive: {}", archive.display()))??;
+    if first.path()? != Path::new(ARCHIVE_MANIFEST_PATH) {
+        return Err(anyhow::anyhow!(
+            "Bundle archive {} does not start with {}",
+            archive.display(),
+            ARCHIVE_MANIFEST_PATH
+        ));
+    }
+    migrate_manifest(serde_json::from_reader(&mut first)?)
+}
+
+/// Read and migrate the manifest of a bundle directory
+pub(crate) fn read_manifest(dir: &Path) -> Result<super::Manifest> {
+    let manifest_path = dir.join("manifest.json");
+    let content = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", manifest_path.display(), e))?;
+    migrate_manifest(serde_json::from_str(&content)?)
+}
+
+/// Path of `rel` in the bundle at `dir`, or in the shared parent bundle the manifest
+/// references for it; a referenced file must match the recorded SHA-256
+pub(crate) fn resolve_file(dir: &Path, manifest: &super::Manifest, rel: &str) -> Result<PathBuf> {
+    let Some(reference) = manifest.references.get(rel) else {
+        return Ok(dir.join(rel));
+    };
+    let parent_dir = dir
+        .parent()
+        .zip(inside_bundle(&reference.artifact_id).ok())
+        .map(|(p, id)| p.join(id))
+        .filter(|p| p.is_dir())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Bundle {} references {} in {}, which is not next to it",
+                dir.display(),
+                rel,
+                reference.artifact_id
+            )
+        })?;
+    let file = parent_dir.join(rel);
+    if !file.is_file() || sha256_file(&file)? != reference.sha256 {
+        return Err(anyhow::anyhow!(
+            "Shared file {} in {} does not match the reference in {}",
+            rel,
+            parent_dir.display(),
+            dir.display()
+        ));
+    }
+    Ok(file)
+}
+
+/// Import artifact bundle from filesystem: a bundle directory (plain or with compressed
+/// files) or a `.tar.zst` archive, which is unpacked next to itself first
+///
+/// A signed bundle's signature is verified; unsigned bundles are accepted. An encrypted
+/// `.tar.zst.enc` archive is decrypted with the key in `AWEN_BUNDLE_KEY` (see
+/// [`EnvKeyProvider`]); use [`import_encrypted_bundle`] to supply keys directly.
 pub fn import_bundle(path: &Path) -> Result<ArtifactBundle> {
-    // Read manifest
-    let manifest_path = path.join("manifest.json");
-    let manifest_content = std::fs::read_to_string(&manifest_path)?;
-    let manifest: super::Manifest = serde_json::from_str(&manifest_content)?;
+    import_with(path, None, &EnvKeyProvider)
+}
 
-    // If checksums.json exists, validate checksums
-    let checksums_path = path.join("checksums.json");
-    if checksums_path.exists() {
-        let checksums_content = std::fs::read_to_string(&checksums_path)?;
-        let expected: HashMap<String, String> = serde_json::from_str(&checksums_content)?;
-
-        for (rel, expected_hex) in expected.iter() {
-            let file_path = path.join(rel);
-            let mut file = std::fs::File::open(&file_path)?;
-            let mut hasher = Sha256::new();
-            std::io::copy(&mut file, &mut hasher)?;
-            let digest = hasher.finalize();
-            let hex_actual = hex::encode(digest);
-            if &hex_actual != expected_hex {
-                return Err(anyhow::anyhow!("Checksum mismatch for {}", rel));
+/// Import a bundle that must be signed by one of `trusted_keys` (Base64 ed25519 public keys)
+pub fn import_trusted_bundle(path: &Path, trusted_keys: &[String]) -> Result<ArtifactBundle> {
+    import_with(path, Some(trusted_keys), &EnvKeyProvider)
+}
+
+/// Import a bundle, decrypting it with `keys` if it is encrypted
+pub fn import_encrypted_bundle(path: &Path, keys: &dyn KeyProvider) -> Result<ArtifactBundle> {
+    import_with(path, None, keys)
+}
+
+fn import_with(
+    path: &Path,
+    trusted_keys: Option<&[String]>,
+    keys: &dyn KeyProvider,
+) -> Result<ArtifactBundle> {
+    let dir = if path.is_file() {
+        unpack_archive(path, keys)?
+    } else {
+        path.to_path_buf()
+    };
+    let signature = verify_bundle_signature(&dir)?;
+    if let Some(trusted) = trusted_keys {
+        match signature {
+            Some(sig) if trusted.contains(&sig.public_key) => {}
+            Some(sig) => {
+                return Err(anyhow::anyhow!(
+                    "Bundle signed by untrusted key {}",
+                    sig.public_key
+                ))
             }
+            None => return Err(anyhow::anyhow!("Bundle is not signed")),
+        }
+    }
+    import_directory(&dir)
+}
+
+/// Check every file against the manifest's checksums and `checksums.json`, failing with the
+/// full list of missing or corrupted files
+///
+/// Bundles exported before checksums moved into the manifest are verified against
+/// `checksums.json` alone; a bundle with neither is rejected.
+fn verify_checksums(path: &Path, manifest: &super::Manifest) -> Result<()> {
+    let checksums_path = path.join("checksums.json");
+    let listed: BTreeMap<String, String> = if checksums_path.exists() {
+        serde_json::from_str(&std::fs::read_to_string(&checksums_path)?)?
+    } else {
+        BTreeMap::new()
+    };
+    if manifest.checksums.is_empty() && listed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Bundle {} has no checksums; refusing to import unverified files",
+            path.display()
+        ));
+    }
+
+    let mut corrupted = BTreeSet::new();
+    for (rel, expected) in manifest.checksums.iter().chain(&listed) {
+        let file_path = path.join(inside_bundle(rel)?);
+        let actual = if file_path.is_file() {
+            sha256_file(&file_path)?
+        } else {
+            String::new()
+        };
+        if &actual != expected {
+            corrupted.insert(rel.as_str());
         }
     }
+    if !corrupted.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Checksum mismatch for {} file(s): {}",
+            corrupted.len(),
+            corrupted.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// `rel` as a path that stays inside the directory it is joined to: only plain names, no
+/// `..`, root or prefix components
+fn inside_bundle(rel: &str) -> Result<&Path> {
+    let path = Path::new(rel);
+    if rel.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(anyhow::anyhow!(
+            "Bundle path {:?} leaves the bundle directory",
+            rel
+        ));
+    }
+    Ok(path)
+}
+
+fn import_directory(path: &Path) -> Result<ArtifactBundle> {
+    // Phase 2.3 run directories carry no checksums, so they cannot be verified; a bundle
+    // stripped of its manifest looks the same
+    if is_legacy_run_dir(path) {
+        return Err(anyhow::anyhow!(
+            "{} has no manifest or checksums; refusing to import unverified files. Lift a \
+             Phase 2.3 run directory explicitly with `import_legacy_run_dir`",
+            path.display()
+        ));
+    }
+
+    // Read manifest, migrating older schema versions
+    let manifest = read_manifest(path)?;
+
+    verify_checksums(path, &manifest)?;
 
     // Load core files
-    let ir_path = path.join("ir/original.json");
-    let ir_content = std::fs::read_to_string(&ir_path)?;
-    let ir: crate::ir::Graph = serde_json::from_str(&ir_content)?;
+    let ir: crate::ir::Graph = read_json(&resolve_file(path, &manifest, "ir/original.json")?)?;
+    let ir_lowered = optional_json(&resolve_file(path, &manifest, "ir/lowered.json")?)?;
 
     let params_path = path.join("parameters/initial.json");
     let params_content = std::fs::read_to_string(&params_path)?;
     let parameters_initial: std::collections::HashMap<String, f64> =
         serde_json::from_str(&params_content)?;
 
-    let results_path = path.join("results/outputs.json");
-    let results_content = std::fs::read_to_string(&results_path)?;
-    let results: serde_json::Value = serde_json::from_str(&results_content)?;
+    let results: serde_json::Value = read_json(&path.join("results/outputs.json"))?;
 
     // Environment snapshot
-    let environment_path = path.join("environment/snapshot.json");
+    let environment_path = resolve_file(path, &manifest, "environment/snapshot.json")?;
     let environment: super::EnvironmentSnapshot = if environment_path.exists() {
         let env_c = std::fs::read_to_string(&environment_path)?;
         serde_json::from_str(&env_c)?
@@ -73,6 +305,7 @@ pub fn import_bundle(path: &Path) -> Result<ArtifactBundle> {
             tags: vec![],
             notes: None,
             citation: None,
+            plugins: vec![],
         }
     };
 
@@ -85,31 +318,19 @@ pub fn import_bundle(path: &Path) -> Result<ArtifactBundle> {
         None
     };
 
-    // Observability (best-effort)
-    let observability = if path.join("provenance/traces.jsonl").exists()
-        || path.join("provenance/timeline.json").exists()
-    {
+    // Observability (best-effort); compressed files are returned as stored, see `read_bundle_file`
+    let provenance_file = |name: &str| stored_path(&path.join("provenance").join(name));
+    let traces = provenance_file("traces.jsonl");
+    let timeline = provenance_file("timeline.json");
+    let observability = if traces.is_some() || timeline.is_some() {
         Some(super::ObservabilityData {
-            traces: if path.join("provenance/traces.jsonl").exists() {
-                Some(path.join("provenance/traces.jsonl"))
-            } else {
-                None
-            },
-            timeline: if path.join("provenance/timeline.json").exists() {
-                Some(path.join("provenance/timeline.json"))
-            } else {
-                None
-            },
-            metrics: if path.join("provenance/metrics.json").exists() {
-                Some(path.join("provenance/metrics.json"))
-            } else {
-                None
-            },
-            events: if path.join("provenance/events.jsonl").exists() {
-                Some(path.join("provenance/events.jsonl"))
-            } else {
-                None
-            },
+            traces,
+            timeline,
+            metrics: provenance_file("metrics.json"),
+            events: provenance_file("events.jsonl"),
+            states: provenance_file(STATE_HISTORY_JSONL)
+                .or_else(|| provenance_file(STATE_HISTORY_JSON)),
+            measurements: provenance_file("measurements.json"),
         })
     } else {
         None
@@ -130,11 +351,11 @@ pub fn import_bundle(path: &Path) -> Result<ArtifactBundle> {
         artifact_type,
         manifest,
         ir_original: ir,
-        ir_lowered: None,
+        ir_lowered,
         parameters_initial,
-        parameters_final: None,
-        calibration_state_initial: None,
-        calibration_state_final: None,
+        parameters_final: optional_json(&path.join("parameters/final.json"))?,
+        calibration_state_initial: optional_json(&path.join("calibration/initial.json"))?,
+        calibration_state_final: optional_json(&path.join("calibration/final.json"))?,
         results,
         seed,
         observability,
@@ -0,0 +1,340 @@
+//! Cross-run provenance graph
+//!
+//! Links artifacts to what they were derived from, so the lineage of any result can be walked
+//! and exported. Nodes are artifacts, calibration states and sweep groups; an edge points from
+//! the dependent node to what it depends on:
+//!
+//! - `derived_from`: artifact → parent artifact (`provenance.parent_artifacts`)
+//! - `replay_of`: replay artifact → the artifact it replays
+//! - `used_calibration`: artifact → its initial calibration state
+//! - `produced_by`: final calibration state → the artifact that produced it
+//! - `calibration_parent`: calibration → `CalibrationProvenance.parent_calibration_id`
+//! - `member_of`: artifact → sweep group (a `sweep:<group>` tag)
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use super::{import_bundle, ArtifactBundle, ArtifactType};
+
+/// Tag prefix marking an artifact as a member of a sweep group
+pub const SWEEP_TAG_PREFIX: &str = "sweep:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Artifact,
+    Calibration,
+    SweepGroup,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relationship {
+    DerivedFrom,
+    ReplayOf,
+    UsedCalibration,
+    ProducedBy,
+    CalibrationParent,
+    MemberOf,
+}
+
+impl NodeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NodeKind::Artifact => "artifact",
+            NodeKind::Calibration => "calibration",
+            NodeKind::SweepGroup => "sweep_group",
+        }
+    }
+}
+
+impl Relationship {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Relationship::DerivedFrom => "derived_from",
+            Relationship::ReplayOf => "replay_of",
+            Relationship::UsedCalibration => "used_calibration",
+            Relationship::ProducedBy => "produced_by",
+            Relationship::CalibrationParent => "calibration_parent",
+            Relationship::MemberOf => "member_of",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvenanceNode {
+    pub id: String,
+    pub kind: NodeKind,
+    /// `run`, `gradient`, ... for artifacts
+    pub artifact_type: Option<String>,
+    pub created_at: Option<String>,
+    /// Bundle the node was loaded from; `None` for nodes only known by reference
+    pub location: Option<PathBuf>,
+}
+
+impl ProvenanceNode {
+    fn referenced(id: &str, kind: NodeKind) -> Self {
+        Self {
+            id: id.to_string(),
+            kind,
+            artifact_type: None,
+            created_at: None,
+            location: None,
+        }
+    }
+}
+
+/// Edge from a dependent node to what it depends on
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ProvenanceEdge {
+    pub from: String,
+    pub to: String,
+    pub relationship: Relationship,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProvenanceGraph {
+    pub nodes: BTreeMap<String, ProvenanceNode>,
+    pub edges: BTreeSet<ProvenanceEdge>,
+}
+
+impl ProvenanceGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Graph of every bundle directory and `.tar.zst` archive in `artifacts_dir`
+    pub fn from_artifacts_dir(artifacts_dir: &Path) -> Result<Self> {
+        let mut graph = Self::new();
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(artifacts_dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<_>>()?;
+        paths.sort();
+        for path in paths {
+            let is_bundle = if path.is_dir() {
+                path.join("manifest.json").is_file()
+            } else {
+                path.to_string_lossy().ends_with(".tar.zst")
+            };
+            if is_bundle {
+                let bundle = import_bundle(&path)?;
+                graph.add_bundle(&bundle, Some(&path));
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Add a bundle and the edges its provenance and calibration states record
+    pub fn add_bundle(&mut self, bundle: &ArtifactBundle, location: Option<&Path>) {
+        let id = bundle.artifact_id.as_str();
+        self.nodes.insert(
+            id.to_string(),
+            ProvenanceNode {
+                id: id.to_string(),
+                kind: NodeKind::Artifact,
+                artifact_type: Some(bundle.manifest.artifact_type.clone()),
+                created_at: Some(bundle.manifest.created_at.clone()),
+                location: location.map(Path::to_path_buf),
+            },
+        );
+
+        let parent_relationship = if bundle.artifact_type == ArtifactType::Replay {
+            Relationship::ReplayOf
+        } else {
+            Relationship::DerivedFrom
+        };
+        for parent in &bundle.provenance.parent_artifacts {
+            self.link(id, parent, NodeKind::Artifact, parent_relationship);
+        }
+        for tag in &bundle.provenance.tags {
+            if let Some(group) = tag.strip_prefix(SWEEP_TAG_PREFIX) {
+                self.link(id, group, NodeKind::SweepGroup, Relationship::MemberOf);
+            }
+        }
+
+        let initial = bundle
+            .calibration_state_initial
+            .as_ref()
+            .and_then(calibration_ids);
+        if let Some((calibration, parent)) = &initial {
+            self.link(
+                id,
+                calibration,
+                NodeKind::Calibration,
+                Relationship::UsedCalibration,
+            );
+            if let Some(parent) = parent {
+                self.link_calibration_parent(calibration, parent);
+            }
+        }
+        if let Some((calibration, parent)) = bundle
+            .calibration_state_final
+            .as_ref()
+            .and_then(calibration_ids)
+        {
+            // An unchanged calibration was used, not produced
+            if initial.as_ref().map(|(c, _)| c) != Some(&calibration) {
+                self.add_node(&calibration, NodeKind::Calibration);
+                self.edges.insert(ProvenanceEdge {
+                    from: calibration.clone(),
+                    to: id.to_string(),
+                    relationship: Relationship::ProducedBy,
+                });
+                if let Some(parent) = parent {
+                    self.link_calibration_parent(&calibration, &parent);
+                }
+            }
+        }
+    }
+
+    fn add_node(&mut self, id: &str, kind: NodeKind) {
+        self.nodes
+            .entry(id.to_string())
+            .or_insert_with(|| ProvenanceNode::referenced(id, kind));
+    }
+
+    fn link(&mut self, from: &str, to: &str, kind: NodeKind, relationship: Relationship) {
+        self.add_node(to, kind);
+        self.edges.insert(ProvenanceEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            relationship,
+        });
+    }
+
+    fn link_calibration_parent(&mut self, calibration: &str, parent: &str) {
+        self.link(
+            calibration,
+            parent,
+            NodeKind::Calibration,
+            Relationship::CalibrationParent,
+        );
+    }
+
+    pub fn node(&self, id: &str) -> Option<&ProvenanceNode> {
+        self.nodes.get(id)
+    }
+
+    /// Edges from `id` to what it depends on
+    pub fn parents(&self, id: &str) -> impl Iterator<Item = &ProvenanceEdge> + '_ {
+        let id = id.to_string();
+        self.edges.iter().filter(move |e| e.from == id)
+    }
+
+    /// Edges from nodes that depend on `id`
+    pub fn children(&self, id: &str) -> impl Iterator<Item = &ProvenanceEdge> + '_ {
+        let id = id.to_string();
+        self.edges.iter().filter(move |e| e.to == id)
+    }
+
+    /// Everything `id` transitively depends on, nearest first
+    pub fn ancestors(&self, id: &str) -> Vec<&ProvenanceNode> {
+        self.walk(id, |graph, node| {
+            graph.parents(node).map(|e| e.to.clone()).collect()
+        })
+    }
+
+    /// Everything transitively derived from `id`, nearest first
+    pub fn descendants(&self, id: &str) -> Vec<&ProvenanceNode> {
+        self.walk(id, |graph, node| {
+            graph.children(node).map(|e| e.from.clone()).collect()
+        })
+    }
+
+    /// Breadth-first walk from `start`, excluding it; each node is visited once
+    fn walk(&self, start: &str, next: impl Fn(&Self, &str) -> Vec<String>) -> Vec<&ProvenanceNode> {
+        let mut seen = BTreeSet::from([start.to_string()]);
+        let mut queue = VecDeque::from([start.to_string()]);
+        let mut found = Vec::new();
+        while let Some(node) = queue.pop_front() {
+            for neighbour in next(self, &node) {
+                if seen.insert(neighbour.clone()) {
+                    if let Some(n) = self.nodes.get(&neighbour) {
+                        found.push(n);
+                    }
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        found
+    }
+
+    /// Subgraph of `id` and its ancestors: the full lineage of a result
+    pub fn lineage(&self, id: &str) -> Result<ProvenanceGraph> {
+        let root = self
+            .nodes
+            .get(id)
+            .ok_or_else(|| anyhow!("{} is not in the provenance graph", id))?;
+        let mut nodes: BTreeMap<String, ProvenanceNode> = self
+            .ancestors(id)
+            .into_iter()
+            .map(|n| (n.id.clone(), n.clone()))
+            .collect();
+        nodes.insert(id.to_string(), root.clone());
+        let edges = self
+            .edges
+            .iter()
+            .filter(|e| nodes.contains_key(&e.from) && nodes.contains_key(&e.to))
+            .cloned()
+            .collect();
+        Ok(ProvenanceGraph { nodes, edges })
+    }
+
+    /// Graphviz rendering; nodes only known by reference are dashed
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph provenance {\n  rankdir=BT;\n");
+        for node in self.nodes.values() {
+            let shape = match node.kind {
+                NodeKind::Artifact => "box",
+                NodeKind::Calibration => "ellipse",
+                NodeKind::SweepGroup => "folder",
+            };
+            let label = match &node.artifact_type {
+                Some(t) => format!("{}\\n{}", escape(&node.id), escape(t)),
+                None => escape(&node.id),
+            };
+            let style = if node.location.is_none() && node.kind == NodeKind::Artifact {
+                ", style=dashed"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"{}\", shape={}{}];",
+                escape(&node.id),
+                label,
+                shape,
+                style
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape(&edge.from),
+                escape(&edge.to),
+                edge.relationship.as_str()
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// `(calibration_id, parent_calibration_id)` of a serialized `CalibrationState`
+fn calibration_ids(state: &serde_json::Value) -> Option<(String, Option<String>)> {
+    let id = state.get("calibration_id")?.as_str()?.to_string();
+    let parent = state
+        .get("provenance")
+        .and_then(|p| p.get("parent_calibration_id"))
+        .and_then(|p| p.as_str())
+        .map(str::to_string);
+    Some((id, parent))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -1,6 +1,7 @@
 //! Artifact manifest schema
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Artifact bundle manifest
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -16,6 +17,23 @@ pub struct Manifest {
     pub inputs: InputsHash,
     pub outputs: OutputsHash,
     pub provenance: ProvisionInfo,
+    /// SHA-256 of every file in the bundle except `manifest.json`, `checksums.json` and
+    /// `signature.json`, keyed by bundle-relative path; verified by `import_bundle`
+    #[serde(default)]
+    pub checksums: BTreeMap<String, String>,
+    /// Files stored once in a shared parent bundle instead of this one, keyed by
+    /// bundle-relative path; see `ExportOptions::shared_parent`
+    #[serde(default)]
+    pub references: BTreeMap<String, ComponentRef>,
+}
+
+/// A bundle file stored in another bundle exported to the same directory
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentRef {
+    /// Artifact id, and directory name, of the bundle holding the file
+    pub artifact_id: String,
+    /// SHA-256 the referenced file must have
+    pub sha256: String,
 }
 
 impl Manifest {
@@ -25,7 +43,7 @@ impl Manifest {
         runtime_version: String,
     ) -> Self {
         Self {
-            schema_version: "awen_artifact.v0.2".to_string(),
+            schema_version: super::migration::CURRENT_SCHEMA_VERSION.to_string(),
             artifact_id,
             artifact_type: format!("{:?}", artifact_type).to_lowercase(),
             created_at: chrono::Utc::now().to_rfc3339(),
@@ -36,6 +54,8 @@ impl Manifest {
             inputs: InputsHash::default(),
             outputs: OutputsHash::default(),
             provenance: ProvisionInfo::default(),
+            checksums: BTreeMap::new(),
+            references: BTreeMap::new(),
         }
     }
 }
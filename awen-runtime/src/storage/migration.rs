@@ -0,0 +1,225 @@
+//! Schema versions and migration of older artifact bundles
+//!
+//! `import_bundle` reads the manifest as raw JSON and upgrades it one version at a time to
+//! [`CURRENT_SCHEMA_VERSION`] before deserializing, so bundles written by earlier runtimes
+//! still replay. Supported inputs:
+//!
+//! - Phase 2.3 run directories (`awen_run_<id>/` written by the engine before bundles had a
+//!   manifest): `ir.json` and `results.json` are lifted into an in-memory bundle. They have no
+//!   checksums, so `import_bundle` refuses them; [`import_legacy_run_dir`] and
+//!   `load_artifact_for_replay` lift them explicitly, unverified.
+//! - `awen_artifact.v0.1` manifests, which lacked the content index, input/output hashes and
+//!   conformance fields and named the runtime version `runtime_version`.
+//! - `awen_artifact.v0.2`, the current schema.
+//!
+//! Anything else (including bundles from a newer runtime) fails with an explicit error rather
+//! than a deserialization message.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{ArtifactBundle, ArtifactType, Manifest};
+
+/// Schema version written by this runtime
+pub const CURRENT_SCHEMA_VERSION: &str = "awen_artifact.v0.2";
+
+/// Schema versions `import_bundle` can read, oldest first
+pub const SUPPORTED_SCHEMA_VERSIONS: [&str; 2] = ["awen_artifact.v0.1", CURRENT_SCHEMA_VERSION];
+
+/// Version of a manifest; manifests predating `schema_version` are v0.1
+pub fn schema_version(manifest: &Value) -> &str {
+    manifest
+        .get("schema_version")
+        .and_then(Value::as_str)
+        .unwrap_or("awen_artifact.v0.1")
+}
+
+/// Upgrade a raw manifest to the current schema and deserialize it
+pub fn migrate_manifest(mut manifest: Value) -> Result<Manifest> {
+    loop {
+        let version = schema_version(&manifest).to_string();
+        manifest = match version.as_str() {
+            CURRENT_SCHEMA_VERSION => break,
+            "awen_artifact.v0.1" => migrate_v0_1(manifest)?,
+            other => {
+                return Err(anyhow!(
+                    "Unsupported bundle schema version {} (this runtime reads {}); \
+                     bundles from a newer runtime must be imported with that runtime",
+                    other,
+                    SUPPORTED_SCHEMA_VERSIONS.join(", ")
+                ))
+            }
+        };
+    }
+    serde_json::from_value(manifest).map_err(|e| {
+        anyhow!(
+            "Manifest does not match schema {}: {}",
+            CURRENT_SCHEMA_VERSION,
+            e
+        )
+    })
+}
+
+/// v0.1 → v0.2: rename `runtime_version`, default the sections added in v0.2
+fn migrate_v0_1(manifest: Value) -> Result<Value> {
+    let Value::Object(mut fields) = manifest else {
+        return Err(anyhow!("v0.1 manifest is not a JSON object"));
+    };
+    if let Some(version) = fields.remove("runtime_version") {
+        fields.entry("awen_runtime_version").or_insert(version);
+    }
+    for required in [
+        "artifact_id",
+        "artifact_type",
+        "created_at",
+        "awen_runtime_version",
+    ] {
+        if !fields.contains_key(required) {
+            return Err(anyhow!(
+                "v0.1 manifest is missing {} and cannot be migrated",
+                required
+            ));
+        }
+    }
+    let defaults = [
+        ("conformance_level", json!("basic")),
+        ("determinism_guarantee", json!("none")),
+        (
+            "contents",
+            json!({
+                "ir": [], "parameters": [], "calibration": [], "environment": [],
+                "execution": [], "results": [], "provenance": []
+            }),
+        ),
+        ("inputs", json!({})),
+        ("outputs", json!({"success": true})),
+        ("provenance", json!({"parent_artifacts": [], "tags": []})),
+    ];
+    for (key, value) in defaults {
+        fields.entry(key).or_insert(value);
+    }
+    fields.insert("schema_version".to_string(), json!(CURRENT_SCHEMA_VERSION));
+    Ok(Value::Object(fields))
+}
+
+/// Whether `path` is a Phase 2.3 run directory (no manifest, engine outputs at the root)
+pub fn is_legacy_run_dir(path: &Path) -> bool {
+    !path.join("manifest.json").exists()
+        && path.join("ir.json").is_file()
+        && path.join("results.json").is_file()
+}
+
+/// Lift a Phase 2.3 run directory into a bundle
+///
+/// The run seed is recovered from `quantum_states.json` when present. Parameters are not
+/// recorded in these directories; node parameters in the IR carry the values used.
+pub fn import_legacy_run_dir(path: &Path) -> Result<ArtifactBundle> {
+    let read = |name: &str| -> Result<Value> {
+        let content = std::fs::read_to_string(path.join(name))
+            .map_err(|e| anyhow!("Legacy run directory is missing {}: {}", name, e))?;
+        Ok(serde_json::from_str(&content)?)
+    };
+    let ir: crate::ir::Graph = serde_json::from_value(read("ir.json")?)?;
+    let results = read("results.json")?;
+    let seed = read("quantum_states.json")
+        .ok()
+        .and_then(|states| states.get(0)?.get("seed")?.as_u64());
+
+    let run_id = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let created_at = std::fs::metadata(path.join("results.json"))
+        .and_then(|m| m.modified())
+        .map(chrono::DateTime::<chrono::Utc>::from)
+        .unwrap_or_else(|_| chrono::Utc::now());
+
+    let mut fields = Map::new();
+    fields.insert("artifact_id".to_string(), json!(run_id));
+    fields.insert("artifact_type".to_string(), json!("run"));
+    fields.insert("created_at".to_string(), json!(created_at.to_rfc3339()));
+    fields.insert("runtime_version".to_string(), json!("phase-2.3"));
+    let mut manifest = migrate_manifest(Value::Object(fields))?;
+    manifest.inputs.seed = seed;
+
+    let has = |name: &str| path.join(name).is_file();
+    let observability = (has("traces.jsonl") || has("timeline.json")).then(|| {
+        let file = |name: &str| has(name).then(|| path.join(name));
+        super::ObservabilityData {
+            traces: file("traces.jsonl"),
+            timeline: file("timeline.json"),
+            metrics: file("metrics.json"),
+            events: file("events.jsonl"),
+            states: file("quantum_states.json"),
+            measurements: file("measurements.json"),
+        }
+    });
+
+    Ok(ArtifactBundle {
+        artifact_id: run_id,
+        artifact_type: ArtifactType::Run,
+        manifest,
+        ir_original: ir,
+        ir_lowered: None,
+        parameters_initial: HashMap::new(),
+        parameters_final: None,
+        calibration_state_initial: None,
+        calibration_state_final: None,
+        results,
+        seed,
+        observability,
+        // Not recorded by Phase 2.3; best-effort snapshot of the importing machine
+        environment: super::capture_environment(),
+        provenance: super::ProvenanceData {
+            creator: super::CreatorInfo {
+                user: None,
+                organization: None,
+                machine: "unknown".to_string(),
+            },
+            parent_artifacts: vec![],
+            tags: vec!["migrated:phase-2.3".to_string()],
+            notes: None,
+            citation: None,
+            plugins: vec![],
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_migration() {
+        let v0_1 = json!({
+            "artifact_id": "awen_abc",
+            "artifact_type": "run",
+            "created_at": "2025-11-02T09:00:00Z",
+            "runtime_version": "0.3.0"
+        });
+        let manifest = migrate_manifest(v0_1).unwrap();
+        assert_eq!(manifest.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(manifest.awen_runtime_version, "0.3.0");
+        assert!(manifest.outputs.success);
+
+        let current = serde_json::to_value(Manifest::new(
+            "awen_def".to_string(),
+            ArtifactType::Gradient,
+            "0.6.0".to_string(),
+        ))
+        .unwrap();
+        assert_eq!(migrate_manifest(current).unwrap().artifact_id, "awen_def");
+
+        let future = json!({"schema_version": "awen_artifact.v1.0", "artifact_id": "x"});
+        let err = migrate_manifest(future).unwrap_err().to_string();
+        assert!(err.contains("Unsupported bundle schema version awen_artifact.v1.0"));
+
+        let broken = json!({"schema_version": "awen_artifact.v0.1", "artifact_id": "x"});
+        assert!(migrate_manifest(broken)
+            .unwrap_err()
+            .to_string()
+            .contains("cannot be migrated"));
+    }
+}
@@ -5,24 +5,82 @@
 
 // Public module structure
 pub mod bundle;
+#[cfg(feature = "catalog")]
+pub mod catalog;
+pub mod datacite;
 pub mod deterministic_id;
+pub mod encryption;
 pub mod environment;
 pub mod export;
+#[cfg(feature = "hdf5")]
+pub mod hdf5_export;
 pub mod import;
+pub mod lineage;
 pub mod manifest;
+pub mod migration;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
+pub mod remote;
+pub mod replay;
+#[cfg(feature = "storage-s3")]
+pub mod s3;
+pub mod search;
+pub mod signing;
+pub mod state_history;
 
 // Re-export key types for ergonomics
 pub use bundle::{
     validate_bundle, ArtifactBundle, ArtifactType, BundleBuilder, CreatorInfo, EnvironmentSnapshot,
-    ObservabilityData, ProvenanceData,
+    ObservabilityData, PluginRecord, ProvenanceData,
+};
+#[cfg(feature = "catalog")]
+pub use catalog::{CatalogEntry, RunCatalog, RunQuery};
+pub use datacite::{artifact_urn, Creator, DataCiteMetadata, PublicationInfo};
+pub use deterministic_id::{compute_deterministic_id, ir_hash, short_id};
+pub use encryption::{
+    generate_key, parse_key, BundleKey, EncryptionHeaderError, EnvKeyProvider, KeyDirProvider,
+    KeyProvider, StaticKeyProvider,
 };
-pub use deterministic_id::{compute_deterministic_id, short_id};
 pub use environment::{
-    capture_environment, DeviceCapabilities, DeviceInfo, RuntimeInfo, SystemInfo,
+    capture_environment, DeviceCapabilities, DeviceInfo, GpuInfo, RuntimeInfo, SystemInfo,
+};
+pub use export::{
+    archive_file_name, export_bundle, export_bundle_with, export_signed_bundle, export_sweep,
+    ExportFormat, ExportOptions, ARCHIVE_MANIFEST_PATH, COMPRESSED_EXTENSION, SHAREABLE_FILES,
+    ZSTD_LEVEL,
+};
+#[cfg(feature = "hdf5")]
+pub use hdf5_export::{export_run_hdf5, Hdf5Export, Spectrum};
+pub use import::{
+    import_bundle, import_encrypted_bundle, import_trusted_bundle, open_bundle_file,
+    read_archive_manifest, read_bundle_file,
+};
+pub use lineage::{
+    NodeKind, ProvenanceEdge, ProvenanceGraph, ProvenanceNode, Relationship, SWEEP_TAG_PREFIX,
+};
+pub use manifest::{ComponentRef, Manifest};
+pub use migration::{
+    import_legacy_run_dir, is_legacy_run_dir, migrate_manifest, CURRENT_SCHEMA_VERSION,
+    SUPPORTED_SCHEMA_VERSIONS,
+};
+#[cfg(feature = "parquet")]
+pub use parquet_export::{export_measurements_parquet, MeasurementRow, MeasurementTable};
+pub use remote::{
+    artifact_key, fetch_artifact, load_remote_artifact_for_replay, save_artifact_remote,
+    DirectoryStore, RemoteStore,
+};
+pub use replay::{
+    verify_replay, verify_replay_in, CheckStatus, ReplayCheck, ReplayReport,
+    NONDETERMINISTIC_METRIC_PREFIXES,
+};
+#[cfg(feature = "storage-s3")]
+pub use s3::{S3Credentials, S3Store};
+pub use search::{search, SearchHit, SearchQuery};
+pub use signing::{verify_bundle_signature, BundleSignature, BundleSigner, SIGNATURE_FILE};
+pub use state_history::{
+    json_array_to_jsonl, StateHistoryFormat, StateHistoryReader, STATE_HISTORY_JSON,
+    STATE_HISTORY_JSONL,
 };
-pub use export::{export_bundle, ExportFormat};
-pub use import::import_bundle;
-pub use manifest::Manifest;
 
 /// Components needed for deterministic replay
 #[derive(Clone, Debug)]
@@ -53,12 +111,89 @@ pub fn save_artifact(bundle: &ArtifactBundle, artifacts_dir: &Path) -> Result<Pa
     export_bundle(bundle, artifacts_dir, ExportFormat::Directory)
 }
 
+/// Run `graph` on the reference engine and save it as a replayable `Run` bundle of
+/// `namespace` under `artifacts_dir`; callers pick the namespace's directory with
+/// [`Namespace::dir`](crate::namespace::Namespace::dir)
+///
+/// The initial parameters are the graph's node parameters, keyed `<node>:<param>`. The
+/// engine's scratch run directory is removed once its files are in the bundle.
+pub fn run_artifact(
+    graph: &crate::ir::Graph,
+    seed: Option<u64>,
+    namespace: &crate::namespace::Namespace,
+    artifacts_dir: &Path,
+) -> Result<PathBuf> {
+    run_artifact_on(
+        &crate::engine::Engine::new(),
+        graph,
+        seed,
+        namespace,
+        artifacts_dir,
+    )
+}
+
+/// [`run_artifact`] on a configured `engine`.
+pub fn run_artifact_on(
+    engine: &crate::engine::Engine,
+    graph: &crate::ir::Graph,
+    seed: Option<u64>,
+    namespace: &crate::namespace::Namespace,
+    artifacts_dir: &Path,
+) -> Result<PathBuf> {
+    initialize_storage(artifacts_dir)?;
+    let run_dir = engine.run_graph_in(graph, seed, artifacts_dir)?;
+    let parameters = graph
+        .nodes
+        .iter()
+        .flat_map(|n| {
+            n.params
+                .iter()
+                .map(move |(k, v)| (format!("{}:{}", n.id, k), *v))
+        })
+        .collect();
+    let results = serde_json::from_slice(&std::fs::read(run_dir.join("results.json"))?)?;
+    let mut builder = BundleBuilder::new(graph.clone(), ArtifactType::Run)
+        .with_initial_parameters(parameters)
+        .with_results(results)
+        .with_observability_dir(&run_dir)
+        .with_namespace(namespace);
+    if let Some(seed) = seed {
+        builder = builder.with_seed(seed);
+    }
+    let bundle = save_artifact(&builder.build()?, artifacts_dir)?;
+    std::fs::remove_dir_all(&run_dir)?;
+    Ok(bundle)
+}
+
+/// Index the bundle saved at `location` into the run catalog at `catalog`, e.g.
+/// `storage.catalog`; an error when built without the `catalog` feature
+pub fn index_in_catalog(catalog: &Path, location: &Path) -> Result<()> {
+    #[cfg(feature = "catalog")]
+    {
+        let bundle = import_bundle(location)?;
+        RunCatalog::open(catalog)?.index(&CatalogEntry::from_bundle(&bundle, location)?)
+    }
+    #[cfg(not(feature = "catalog"))]
+    {
+        let _ = location;
+        Err(anyhow::anyhow!(
+            "cannot index into run catalog {}: built without the catalog feature",
+            catalog.display()
+        ))
+    }
+}
+
 /// Load artifact bundle for deterministic replay
 ///
 /// Loads a previously saved artifact bundle and returns the components
-/// needed to replay the execution: IR, parameters, seed, and environment.
+/// needed to replay the execution: IR, parameters, seed, and environment. Phase 2.3 run
+/// directories have no checksums and are lifted unverified.
 pub fn load_artifact_for_replay(artifact_path: &Path) -> Result<ReplayComponents> {
-    let bundle = import_bundle(artifact_path)?;
+    let bundle = if is_legacy_run_dir(artifact_path) {
+        import_legacy_run_dir(artifact_path)?
+    } else {
+        import_bundle(artifact_path)?
+    };
     Ok(ReplayComponents {
         ir: bundle.ir_original,
         parameters: bundle.parameters_initial,
@@ -0,0 +1,301 @@
+//! Parquet export of measurement data (`parquet` feature)
+//!
+//! Flattens a run's measurement outcomes (`measurements.json`) and shot records
+//! (`shots.json`) into one row per value, so analysts can load them straight into
+//! pandas/polars:
+//!
+//! | column | type | |
+//! |--------|------|-|
+//! | `run_id` | string | run directory name or artifact id |
+//! | `node_id` | string | detector node, or the sampled state for shot records |
+//! | `shot` | int64 | shot index; 0 for single-shot outcomes |
+//! | `label` | string | `n` (photon count), `x` (homodyne), `q`/`p` (heterodyne), or the mode label of a shot |
+//! | `outcome` | double | measured value |
+//! | `timestamp` | timestamp (ms, UTC), nullable | start of the node's `kernel` timeline event |
+
+use anyhow::{anyhow, Result};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::{read_bundle_file, ArtifactBundle};
+use crate::observability::TimelineEvent;
+use crate::quantum::{MeasurementResult, ShotRecord};
+use crate::state::{MeasurementKind, MeasurementOutcome};
+
+const SCHEMA: &str = "
+message measurement {
+    REQUIRED BYTE_ARRAY run_id (UTF8);
+    REQUIRED BYTE_ARRAY node_id (UTF8);
+    REQUIRED INT64 shot;
+    REQUIRED BYTE_ARRAY label (UTF8);
+    REQUIRED DOUBLE outcome;
+    OPTIONAL INT64 timestamp (TIMESTAMP(MILLIS,true));
+}
+";
+
+/// One measured value
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasurementRow {
+    pub run_id: String,
+    pub node_id: String,
+    pub shot: i64,
+    pub label: String,
+    pub outcome: f64,
+    pub timestamp_ms: Option<i64>,
+}
+
+/// Measurement rows of one or more runs, in node then shot order per run
+#[derive(Debug, Clone, Default)]
+pub struct MeasurementTable {
+    pub rows: Vec<MeasurementRow>,
+}
+
+impl MeasurementTable {
+    /// Rows from a run directory's `measurements.json` and `shots.json`
+    pub fn from_run_dir(run_dir: &Path) -> Result<Self> {
+        let run_id = run_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let file = |name: &str| Some(run_dir.join(name)).filter(|p| p.is_file());
+        let mut table = Self::default();
+        table.add_outcomes(
+            &run_id,
+            file("measurements.json").as_deref(),
+            file("timeline.json").as_deref(),
+        )?;
+        if let Some(shots) = file("shots.json") {
+            let record: ShotRecord = serde_json::from_slice(&std::fs::read(shots)?)?;
+            table.add_shots(&run_id, &record);
+        }
+        Ok(table)
+    }
+
+    /// Rows from the measurement outcomes stored in a bundle
+    pub fn from_bundle(bundle: &ArtifactBundle) -> Result<Self> {
+        let obs = bundle.observability.as_ref();
+        let mut table = Self::default();
+        table.add_outcomes(
+            &bundle.artifact_id,
+            obs.and_then(|o| o.measurements.as_deref()),
+            obs.and_then(|o| o.timeline.as_deref()),
+        )?;
+        Ok(table)
+    }
+
+    fn add_outcomes(
+        &mut self,
+        run_id: &str,
+        measurements: Option<&Path>,
+        timeline: Option<&Path>,
+    ) -> Result<()> {
+        let Some(measurements) = measurements else {
+            return Ok(());
+        };
+        let outcomes: BTreeMap<String, MeasurementOutcome> =
+            serde_json::from_slice(&read_bundle_file(measurements)?)?;
+        let started: HashMap<String, i64> = match timeline {
+            Some(path) => {
+                let events: Vec<TimelineEvent> = serde_json::from_slice(&read_bundle_file(path)?)?;
+                events
+                    .into_iter()
+                    .filter(|e| e.lane == "kernel")
+                    .filter_map(|e| {
+                        let node = e.attributes.get("node_id")?.clone();
+                        Some((node, i64::try_from(e.start_ms).ok()?))
+                    })
+                    .collect()
+            }
+            None => HashMap::new(),
+        };
+
+        for (node_id, outcome) in outcomes {
+            let quadrature = |i: usize| outcome.quadratures.as_ref().and_then(|q| q.get(i));
+            let values: Vec<(&str, f64)> = match outcome.kind {
+                MeasurementKind::PhotonCount => vec![("n", outcome.photon_count as f64)],
+                MeasurementKind::Homodyne { .. } => {
+                    quadrature(0).map(|&x| ("x", x)).into_iter().collect()
+                }
+                MeasurementKind::Heterodyne => [("q", quadrature(0)), ("p", quadrature(1))]
+                    .into_iter()
+                    .filter_map(|(label, v)| Some((label, *v?)))
+                    .collect(),
+            };
+            for (label, value) in values {
+                self.rows.push(MeasurementRow {
+                    run_id: run_id.to_string(),
+                    node_id: node_id.clone(),
+                    shot: 0,
+                    label: label.to_string(),
+                    outcome: value,
+                    timestamp_ms: started.get(&node_id).copied(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn add_shots(&mut self, run_id: &str, record: &ShotRecord) {
+        for (shot, results) in record.shots.iter().enumerate() {
+            for (label, result) in record.labels.iter().zip(results) {
+                self.rows.push(MeasurementRow {
+                    run_id: run_id.to_string(),
+                    node_id: record.state_id.clone(),
+                    shot: shot as i64,
+                    label: label.clone(),
+                    outcome: match result {
+                        MeasurementResult::DiscreteOutcome(n) => *n as f64,
+                        MeasurementResult::ContinuousValue(v) => *v,
+                    },
+                    timestamp_ms: None,
+                });
+            }
+        }
+    }
+
+    /// Append another table's rows, e.g. to collect a sweep into one file
+    pub fn extend(&mut self, other: MeasurementTable) {
+        self.rows.extend(other.rows);
+    }
+
+    /// Write the rows as a zstd-compressed Parquet file with a single row group
+    pub fn write_parquet(&self, path: &Path) -> Result<()> {
+        let schema = Arc::new(parse_message_type(SCHEMA)?);
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::try_new(super::ZSTD_LEVEL)?))
+            .build();
+        let file = std::fs::File::create(path)?;
+        let mut writer = SerializedFileWriter::new(file, schema, Arc::new(props))?;
+        let mut group = writer.next_row_group()?;
+
+        let strings = |f: fn(&MeasurementRow) -> &str| -> Vec<ByteArray> {
+            self.rows.iter().map(|r| ByteArray::from(f(r))).collect()
+        };
+        let mut column = 0;
+        while let Some(mut writer) = group.next_column()? {
+            match column {
+                0 => writer.typed::<ByteArrayType>().write_batch(
+                    &strings(|r| &r.run_id),
+                    None,
+                    None,
+                )?,
+                1 => writer.typed::<ByteArrayType>().write_batch(
+                    &strings(|r| &r.node_id),
+                    None,
+                    None,
+                )?,
+                2 => {
+                    let shots: Vec<i64> = self.rows.iter().map(|r| r.shot).collect();
+                    writer
+                        .typed::<Int64Type>()
+                        .write_batch(&shots, None, None)?
+                }
+                3 => writer.typed::<ByteArrayType>().write_batch(
+                    &strings(|r| &r.label),
+                    None,
+                    None,
+                )?,
+                4 => {
+                    let outcomes: Vec<f64> = self.rows.iter().map(|r| r.outcome).collect();
+                    writer
+                        .typed::<DoubleType>()
+                        .write_batch(&outcomes, None, None)?
+                }
+                5 => {
+                    let present: Vec<i64> =
+                        self.rows.iter().filter_map(|r| r.timestamp_ms).collect();
+                    let levels: Vec<i16> = self
+                        .rows
+                        .iter()
+                        .map(|r| r.timestamp_ms.is_some() as i16)
+                        .collect();
+                    writer
+                        .typed::<Int64Type>()
+                        .write_batch(&present, Some(&levels), None)?
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "unexpected column {} in measurement schema",
+                        column
+                    ))
+                }
+            };
+            writer.close()?;
+            column += 1;
+        }
+        group.close()?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Write a run directory's measurements and shots to `path` as Parquet
+pub fn export_measurements_parquet(run_dir: &Path, path: &Path) -> Result<usize> {
+    let table = MeasurementTable::from_run_dir(run_dir)?;
+    table.write_parquet(path)?;
+    Ok(table.rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    #[test]
+    fn test_measurements_to_parquet() {
+        let dir = tempfile::tempdir().unwrap();
+        let run_dir = dir.path().join("awen_run_p");
+        std::fs::create_dir_all(&run_dir).unwrap();
+        let outcome = |kind: &str, quadratures: serde_json::Value| {
+            serde_json::json!({
+                "outcome_index": 1, "photon_count": 1, "probability": 0.5,
+                "collapsed_state": null, "seed_used": 7, "kind": {"kind": kind},
+                "quadratures": quadratures
+            })
+        };
+        let measurements = serde_json::json!({
+            "d0": outcome("photon_count", serde_json::Value::Null),
+            "d1": outcome("heterodyne", serde_json::json!([0.25, -1.5])),
+        });
+        std::fs::write(run_dir.join("measurements.json"), measurements.to_string()).unwrap();
+        let timeline = serde_json::json!([{
+            "lane": "kernel", "name": "exec:d0", "start_ms": 1_700_000_000_000u64,
+            "end_ms": 1_700_000_000_001u64, "attributes": {"node_id": "d0"}
+        }]);
+        std::fs::write(run_dir.join("timeline.json"), timeline.to_string()).unwrap();
+        let shots = serde_json::json!({
+            "record_id": "r", "state_id": "psi", "measurement_type": "Computational",
+            "labels": ["m0"], "shots": [[{"DiscreteOutcome": 2}], [{"DiscreteOutcome": 0}]],
+            "seed": 7
+        });
+        std::fs::write(run_dir.join("shots.json"), shots.to_string()).unwrap();
+
+        let path = dir.path().join("measurements.parquet");
+        assert_eq!(export_measurements_parquet(&run_dir, &path).unwrap(), 5);
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 5);
+        let rows: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(rows[0].get_string(1).unwrap(), "d0");
+        assert_eq!(rows[0].get_timestamp_millis(5).unwrap(), 1_700_000_000_000);
+        assert_eq!(rows[2].get_string(3).unwrap(), "p");
+        assert_eq!(rows[2].get_double(4).unwrap(), -1.5);
+        assert!(
+            rows[2].get_timestamp_millis(5).is_err(),
+            "no timeline event for d1"
+        );
+        assert_eq!(rows[3].get_string(0).unwrap(), "awen_run_p");
+        assert_eq!(rows[4].get_long(2).unwrap(), 1);
+    }
+}
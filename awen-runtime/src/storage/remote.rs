@@ -0,0 +1,126 @@
+//! Remote object storage for artifact bundles
+//!
+//! Bundles are uploaded as single `.tar.zst` archives keyed by artifact id, so a run can be
+//! replayed on any machine that can reach the store. `DirectoryStore` keeps objects under a
+//! local or network-mounted directory; with the `storage-s3` feature, `S3Store` talks to S3 or
+//! any S3-compatible service.
+
+use anyhow::{anyhow, Result};
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+use super::export::COMPRESSED_EXTENSION;
+use super::{export_bundle, import_bundle, ArtifactBundle, ExportFormat};
+
+/// Object store holding bundle archives by key
+pub trait RemoteStore: Send + Sync + Debug {
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<()>;
+    fn get(&self, key: &str) -> Result<Vec<u8>>;
+    fn exists(&self, key: &str) -> Result<bool>;
+}
+
+/// Object key of a bundle archive, e.g. `awen_0123456789abcdef.tar.zst`
+pub fn artifact_key(artifact_id: &str) -> String {
+    format!("{}.tar.{}", artifact_id, COMPRESSED_EXTENSION)
+}
+
+/// Objects as files under a root directory
+#[derive(Debug, Clone)]
+pub struct DirectoryStore {
+    root: PathBuf,
+}
+
+impl DirectoryStore {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        if key.is_empty() || key.split('/').any(|part| part.is_empty() || part == "..") {
+            return Err(anyhow!("Invalid object key: {}", key));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+impl RemoteStore for DirectoryStore {
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Write then rename, so readers never see a partial object
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let path = self.path(key)?;
+        std::fs::read(&path).map_err(|e| anyhow!("Failed to read object {}: {}", key, e))
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.path(key)?.is_file())
+    }
+}
+
+/// Save the bundle under `artifacts_dir` and upload it to `store` as an archive
+///
+/// Returns the object key.
+pub fn save_artifact_remote(
+    bundle: &ArtifactBundle,
+    artifacts_dir: &Path,
+    store: &dyn RemoteStore,
+) -> Result<String> {
+    super::save_artifact(bundle, artifacts_dir)?;
+    let archive = export_bundle(bundle, artifacts_dir, ExportFormat::Archive)?;
+    let key = artifact_key(&bundle.artifact_id);
+    let uploaded = std::fs::read(&archive)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| store.put(&key, &bytes));
+    std::fs::remove_file(&archive)?;
+    uploaded?;
+    Ok(key)
+}
+
+/// Fetch a bundle by artifact id into `cache_dir` and import it
+///
+/// A bundle already in the cache is not downloaded again.
+pub fn fetch_artifact(
+    artifact_id: &str,
+    store: &dyn RemoteStore,
+    cache_dir: &Path,
+) -> Result<ArtifactBundle> {
+    let key = artifact_key(artifact_id);
+    let archive = cache_dir.join(&key);
+    if !archive.is_file() {
+        if !store.exists(&key)? {
+            return Err(anyhow!(
+                "Artifact {} not found in remote store",
+                artifact_id
+            ));
+        }
+        std::fs::create_dir_all(cache_dir)?;
+        std::fs::write(&archive, store.get(&key)?)?;
+    }
+    import_bundle(&archive)
+}
+
+/// Fetch a bundle by artifact id and return the components needed to replay it
+pub fn load_remote_artifact_for_replay(
+    artifact_id: &str,
+    store: &dyn RemoteStore,
+    cache_dir: &Path,
+) -> Result<super::ReplayComponents> {
+    let bundle = fetch_artifact(artifact_id, store, cache_dir)?;
+    Ok(super::ReplayComponents {
+        ir: bundle.ir_original,
+        parameters: bundle.parameters_initial,
+        seed: bundle.seed,
+        environment: bundle.environment,
+    })
+}
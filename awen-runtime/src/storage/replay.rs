@@ -0,0 +1,246 @@
+//! Replay verification
+//!
+//! [`verify_replay`] re-runs a bundle's IR with its seed through the [`Engine`] and checks the
+//! rerun against what the bundle stored:
+//!
+//! - `deterministic_id`: the id recomputed from IR, parameters, calibration, seed and runtime
+//!   version matches the bundle's artifact id.
+//! - `measurements`: every measurement outcome is identical (bit-exact), ignoring the
+//!   per-run correlation id.
+//! - `metrics`: counters and gauges are identical, except keys under
+//!   [`NONDETERMINISTIC_METRIC_PREFIXES`] (timings and bundle encoding sizes).
+//!
+//! Checks whose stored artifact is absent are reported as skipped. The engine runs with its
+//! default configuration; parameter values are taken from the node parameters in the IR.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::{compute_deterministic_id, read_bundle_file, ArtifactBundle};
+use crate::engine::Engine;
+use crate::observability::Metrics;
+
+/// Metric key prefixes that vary between identical runs and are not compared
+pub const NONDETERMINISTIC_METRIC_PREFIXES: [&str; 4] = [
+    "artifact.",
+    "hal.",
+    "node_latency_ns",
+    "feedback_latency_ns",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl ReplayCheck {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Outcome of [`verify_replay`]; passes when no check failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub artifact_id: String,
+    pub seed: Option<u64>,
+    /// Run directory of the rerun, when kept
+    pub replay_dir: Option<PathBuf>,
+    pub checks: Vec<ReplayCheck>,
+}
+
+impl ReplayReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "Replay of {}: {}\n",
+            self.artifact_id,
+            if self.passed() { "PASS" } else { "FAIL" }
+        );
+        for check in &self.checks {
+            let mark = match check.status {
+                CheckStatus::Pass => "ok",
+                CheckStatus::Fail => "FAIL",
+                CheckStatus::Skipped => "skip",
+            };
+            out.push_str(&format!("  [{}] {}: {}\n", mark, check.name, check.detail));
+        }
+        out
+    }
+}
+
+/// Re-run `bundle` in a temporary directory and compare against its stored artifacts
+pub fn verify_replay(bundle: &ArtifactBundle) -> Result<ReplayReport> {
+    let work_dir = std::env::temp_dir().join(format!("awen_replay_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&work_dir)?;
+    let report = verify_replay_in(bundle, &work_dir);
+    std::fs::remove_dir_all(&work_dir)?;
+    let mut report = report?;
+    report.replay_dir = None;
+    Ok(report)
+}
+
+/// Like [`verify_replay`], keeping the rerun's bundle under `work_dir`
+pub fn verify_replay_in(bundle: &ArtifactBundle, work_dir: &Path) -> Result<ReplayReport> {
+    let mut checks = vec![check_deterministic_id(bundle)?];
+
+    let replay_dir = Engine::new().run_graph_in(&bundle.ir_original, bundle.seed, work_dir)?;
+    let stored = bundle.observability.as_ref();
+
+    checks.push(
+        match stored
+            .and_then(|o| o.measurements.as_deref())
+            .filter(|p| p.is_file())
+        {
+            Some(path) => compare_measurements(path, &replay_dir.join("measurements.json"))?,
+            None => ReplayCheck::new(
+                "measurements",
+                CheckStatus::Skipped,
+                "bundle has no stored measurements",
+            ),
+        },
+    );
+    checks.push(
+        match stored
+            .and_then(|o| o.metrics.as_deref())
+            .filter(|p| p.is_file())
+        {
+            Some(path) => compare_metrics(path, &replay_dir.join("metrics.json"))?,
+            None => ReplayCheck::new(
+                "metrics",
+                CheckStatus::Skipped,
+                "bundle has no stored metrics",
+            ),
+        },
+    );
+
+    Ok(ReplayReport {
+        artifact_id: bundle.artifact_id.clone(),
+        seed: bundle.seed,
+        replay_dir: Some(replay_dir),
+        checks,
+    })
+}
+
+fn check_deterministic_id(bundle: &ArtifactBundle) -> Result<ReplayCheck> {
+    let recomputed = compute_deterministic_id(
+        &bundle.ir_original,
+        &bundle.parameters_initial,
+        bundle.calibration_state_initial.as_ref(),
+        bundle.seed,
+        &bundle.environment.runtime.version,
+    )?;
+    Ok(if recomputed == bundle.artifact_id {
+        ReplayCheck::new("deterministic_id", CheckStatus::Pass, "matches")
+    } else {
+        ReplayCheck::new(
+            "deterministic_id",
+            CheckStatus::Fail,
+            format!("recomputed {}", recomputed),
+        )
+    })
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    serde_json::from_slice(&read_bundle_file(path)?)
+        .map_err(|e| anyhow!("cannot parse {}: {}", path.display(), e))
+}
+
+fn compare_measurements(stored: &Path, replayed: &Path) -> Result<ReplayCheck> {
+    // measurements.json maps detector node id to its outcome
+    let outcomes = |path: &Path| -> Result<serde_json::Map<String, Value>> {
+        let Value::Object(mut outcomes) = read_json(path)? else {
+            return Err(anyhow!("{} is not a map of outcomes", path.display()));
+        };
+        for outcome in outcomes.values_mut() {
+            // Correlation ids embed the run id, which is fresh on every run
+            if let Value::Object(fields) = outcome {
+                fields.remove("correlation_id");
+            }
+        }
+        Ok(outcomes)
+    };
+    let (stored, replayed) = (outcomes(stored)?, outcomes(replayed)?);
+    let nodes: BTreeSet<&String> = stored.keys().chain(replayed.keys()).collect();
+    let differing: Vec<&str> = nodes
+        .into_iter()
+        .filter(|node| stored.get(*node) != replayed.get(*node))
+        .map(String::as_str)
+        .collect();
+    Ok(if differing.is_empty() {
+        ReplayCheck::new(
+            "measurements",
+            CheckStatus::Pass,
+            format!("{} outcomes identical", stored.len()),
+        )
+    } else {
+        ReplayCheck::new(
+            "measurements",
+            CheckStatus::Fail,
+            format!("outcomes differ at {}", differing.join(", ")),
+        )
+    })
+}
+
+fn compare_metrics(stored: &Path, replayed: &Path) -> Result<ReplayCheck> {
+    let values = |path: &Path| -> Result<HashMap<String, f64>> {
+        let metrics: Metrics = serde_json::from_value(read_json(path)?)?;
+        Ok(metrics
+            .counters
+            .into_iter()
+            .map(|(k, v)| (format!("counter:{}", k), v))
+            .chain(
+                metrics
+                    .gauges
+                    .into_iter()
+                    .map(|(k, v)| (format!("gauge:{}", k), v)),
+            )
+            .filter(|(k, _)| {
+                let key = k.split_once(':').map(|(_, key)| key).unwrap_or(k);
+                !NONDETERMINISTIC_METRIC_PREFIXES
+                    .iter()
+                    .any(|prefix| key.starts_with(prefix))
+            })
+            .collect())
+    };
+    let (stored, replayed) = (values(stored)?, values(replayed)?);
+    let keys: BTreeSet<&String> = stored.keys().chain(replayed.keys()).collect();
+    let differing: Vec<String> = keys
+        .into_iter()
+        .filter(|k| stored.get(*k).map(|v| v.to_bits()) != replayed.get(*k).map(|v| v.to_bits()))
+        .map(|k| match (stored.get(k), replayed.get(k)) {
+            (Some(a), Some(b)) => format!("{} ({} -> {})", k, a, b),
+            (Some(_), None) => format!("{} (missing in replay)", k),
+            _ => format!("{} (not stored)", k),
+        })
+        .collect();
+    Ok(if differing.is_empty() {
+        ReplayCheck::new(
+            "metrics",
+            CheckStatus::Pass,
+            format!("{} deterministic metrics identical", stored.len()),
+        )
+    } else {
+        ReplayCheck::new("metrics", CheckStatus::Fail, differing.join("; "))
+    })
+}
@@ -0,0 +1,375 @@
+//! Metadata search over stored artifacts
+//!
+//! [`search`] scans an artifacts directory and filters bundles on manifest fields, tags and
+//! provenance without importing them. It reads only the manifest, IR, environment snapshot,
+//! lineage, results and seed of each bundle. Archives are read in place, never unpacked.
+//! Encrypted archives and legacy run directories are skipped. For repeated queries over many
+//! runs, index them into a `RunCatalog` (`catalog` feature) instead.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::export::COMPRESSED_EXTENSION;
+use super::import::{read_bundle_file, read_manifest, resolve_file, stored_path};
+use super::lineage::SWEEP_TAG_PREFIX;
+use super::{ir_hash, EnvironmentSnapshot, Manifest, ProvenanceData};
+
+/// Files [`search`] reads from each bundle
+const SEARCHED_FILES: [&str; 5] = [
+    "ir/original.json",
+    "environment/snapshot.json",
+    "environment/seed.txt",
+    "provenance/lineage.json",
+    "results/outputs.json",
+];
+
+/// Status of a run: the `status` field of its results, else `failed` or `completed`
+/// depending on whether the manifest records an error
+pub(crate) fn run_status(results: &serde_json::Value, manifest: &Manifest) -> String {
+    match results.get("status").and_then(|s| s.as_str()) {
+        Some(status) => status.to_string(),
+        None if manifest.outputs.error.is_some() => "failed".to_string(),
+        None => "completed".to_string(),
+    }
+}
+
+/// One bundle matching a [`SearchQuery`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub artifact_id: String,
+    pub artifact_type: String,
+    /// Bundle directory or archive
+    pub location: PathBuf,
+    pub created_at: DateTime<Utc>,
+    pub ir_hash: String,
+    pub seed: Option<u64>,
+    /// Device type the run executed on, e.g. `simulated`
+    pub backend: String,
+    pub status: String,
+    pub tags: Vec<String>,
+    pub parent_artifacts: Vec<String>,
+}
+
+/// Filters for [`search`]; unset fields match every bundle
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Directory holding the bundles
+    pub artifacts_dir: PathBuf,
+    pub artifact_type: Option<String>,
+    pub ir_hash: Option<String>,
+    pub seed: Option<u64>,
+    pub backend: Option<String>,
+    pub status: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Every tag must be present
+    pub tags: Vec<String>,
+    /// Bundles derived from this artifact
+    pub parent: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl SearchQuery {
+    /// Match every bundle in `artifacts_dir`
+    pub fn new(artifacts_dir: &Path) -> Self {
+        Self {
+            artifacts_dir: artifacts_dir.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    pub fn artifact_type(mut self, artifact_type: &str) -> Self {
+        self.artifact_type = Some(artifact_type.to_string());
+        self
+    }
+
+    /// Runs of `graph`
+    pub fn graph(mut self, graph: &crate::ir::Graph) -> Result<Self> {
+        self.ir_hash = Some(ir_hash(graph)?);
+        Ok(self)
+    }
+
+    pub fn ir_hash(mut self, hash: &str) -> Self {
+        self.ir_hash = Some(hash.to_string());
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn backend(mut self, backend: &str) -> Self {
+        self.backend = Some(backend.to_string());
+        self
+    }
+
+    pub fn status(mut self, status: &str) -> Self {
+        self.status = Some(status.to_string());
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Members of sweep group `group`
+    pub fn sweep_group(self, group: &str) -> Self {
+        self.tag(&format!("{}{}", SWEEP_TAG_PREFIX, group))
+    }
+
+    pub fn parent(mut self, artifact_id: &str) -> Self {
+        self.parent = Some(artifact_id.to_string());
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn matches(&self, hit: &SearchHit) -> bool {
+        let eq =
+            |filter: &Option<String>, value: &str| filter.as_deref().is_none_or(|f| f == value);
+        eq(&self.artifact_type, &hit.artifact_type)
+            && eq(&self.ir_hash, &hit.ir_hash)
+            && eq(&self.backend, &hit.backend)
+            && eq(&self.status, &hit.status)
+            && self.seed.is_none_or(|s| hit.seed == Some(s))
+            && self.since.is_none_or(|t| hit.created_at >= t)
+            && self.until.is_none_or(|t| hit.created_at < t)
+            && self.tags.iter().all(|t| hit.tags.contains(t))
+            && self
+                .parent
+                .as_ref()
+                .is_none_or(|p| hit.parent_artifacts.contains(p))
+    }
+}
+
+/// Bundles in `query.artifacts_dir` matching `query`, newest first
+pub fn search(query: &SearchQuery) -> Result<Vec<SearchHit>> {
+    let mut hits = Vec::new();
+    for entry in std::fs::read_dir(&query.artifacts_dir)? {
+        let path = entry?.path();
+        let hit = if path.is_dir() && path.join("manifest.json").is_file() {
+            read_hit(&path, BundleFiles::Directory(&path))?
+        } else if path
+            .to_string_lossy()
+            .ends_with(&format!(".tar.{}", COMPRESSED_EXTENSION))
+        {
+            read_hit(&path, BundleFiles::archive(&path)?)?
+        } else {
+            continue;
+        };
+        if query.matches(&hit) {
+            hits.push(hit);
+        }
+    }
+    hits.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.artifact_id.cmp(&b.artifact_id))
+    });
+    if let Some(limit) = query.limit {
+        hits.truncate(limit);
+    }
+    Ok(hits)
+}
+
+/// Where a bundle's files are read from
+enum BundleFiles<'a> {
+    Directory(&'a Path),
+    /// Manifest and [`SEARCHED_FILES`] of an archive, decompressed, keyed by bundle path
+    Archive(BTreeMap<String, Vec<u8>>),
+}
+
+impl BundleFiles<'_> {
+    fn archive(archive: &Path) -> Result<Self> {
+        let decoder = zstd::Decoder::new(std::fs::File::open(archive)?)?;
+        let mut tar = tar::Archive::new(decoder);
+        let suffix = format!(".{}", COMPRESSED_EXTENSION);
+        let mut files = BTreeMap::new();
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let rel = name.strip_suffix(&suffix).unwrap_or(&name).to_string();
+            if rel != "manifest.json" && !SEARCHED_FILES.contains(&rel.as_str()) {
+                continue;
+            }
+            let mut bytes = Vec::new();
+            if name.ends_with(&suffix) {
+                zstd::Decoder::new(entry)?.read_to_end(&mut bytes)?;
+            } else {
+                entry.read_to_end(&mut bytes)?;
+            }
+            files.insert(rel, bytes);
+        }
+        Ok(Self::Archive(files))
+    }
+
+    fn read(&self, rel: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Directory(dir) => stored_path(&dir.join(rel))
+                .map(|p| read_bundle_file(&p))
+                .transpose(),
+            Self::Archive(files) => Ok(files.get(rel).cloned()),
+        }
+    }
+}
+
+fn read_hit(location: &Path, files: BundleFiles) -> Result<SearchHit> {
+    let manifest: Manifest = match &files {
+        BundleFiles::Directory(dir) => read_manifest(dir)?,
+        BundleFiles::Archive(_) => super::migrate_manifest(serde_json::from_slice(
+            &files
+                .read("manifest.json")?
+                .ok_or_else(|| anyhow!("{} has no manifest", location.display()))?,
+        )?)?,
+    };
+    // Files shared with a sweep parent live in its sibling directory
+    let read = |rel: &str| -> Result<Option<Vec<u8>>> {
+        if manifest.references.contains_key(rel) {
+            read_bundle_file(&resolve_file(location, &manifest, rel)?).map(Some)
+        } else {
+            files.read(rel)
+        }
+    };
+    let required =
+        |rel: &str| read(rel)?.ok_or_else(|| anyhow!("{} has no {}", location.display(), rel));
+
+    let ir: crate::ir::Graph = serde_json::from_slice(&required("ir/original.json")?)?;
+    let results: serde_json::Value = serde_json::from_slice(&required("results/outputs.json")?)?;
+    let environment: Option<EnvironmentSnapshot> = read("environment/snapshot.json")?
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()?;
+    let provenance: Option<ProvenanceData> = read("provenance/lineage.json")?
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()?;
+    let seed = read("environment/seed.txt")?
+        .and_then(|bytes| String::from_utf8_lossy(&bytes).trim().parse().ok());
+    let created_at = DateTime::parse_from_rfc3339(&manifest.created_at)
+        .map_err(|e| anyhow!("invalid created_at in {}: {}", location.display(), e))?
+        .with_timezone(&Utc);
+
+    Ok(SearchHit {
+        artifact_id: manifest.artifact_id.clone(),
+        artifact_type: manifest.artifact_type.clone(),
+        location: location.to_path_buf(),
+        created_at,
+        ir_hash: ir_hash(&ir)?,
+        seed,
+        backend: environment
+            .map(|e| e.device.device_type)
+            .unwrap_or_else(|| "unknown".to_string()),
+        status: run_status(&results, &manifest),
+        tags: provenance
+            .as_ref()
+            .map(|p| p.tags.clone())
+            .unwrap_or_default(),
+        parent_artifacts: provenance.map(|p| p.parent_artifacts).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Graph, Node};
+    use crate::storage::{export_bundle, ArtifactType, BundleBuilder, ExportFormat};
+    use std::collections::HashMap;
+
+    fn graph(id: &str) -> Graph {
+        Graph {
+            nodes: vec![Node {
+                id: id.to_string(),
+                node_type: "MZI".to_string(),
+                params: HashMap::new(),
+                measure_mode: None,
+                measurement: None,
+                conditional_branches: None,
+                feed_forward: None,
+            }],
+            edges: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_filters() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let start = Utc::now() - chrono::Duration::seconds(1);
+        let build = |id: &str, seed: u64, status: &str| {
+            BundleBuilder::new(graph(id), ArtifactType::Run)
+                .with_initial_parameters(HashMap::new())
+                .with_seed(seed)
+                .with_results(serde_json::json!({"status": status}))
+                .with_sweep_group("scan")
+                .build()
+                .unwrap()
+        };
+        let a = build("x", 1, "completed");
+        let b = build("x", 2, "failed");
+        let c = BundleBuilder::new(graph("y"), ArtifactType::Run)
+            .with_results(serde_json::json!({}))
+            .add_parent_artifact(a.artifact_id.clone())
+            .build()
+            .unwrap();
+        export_bundle(&a, dir.path(), ExportFormat::Directory).unwrap();
+        export_bundle(&b, dir.path(), ExportFormat::CompressedDirectory).unwrap();
+        let archive = export_bundle(&c, dir.path(), ExportFormat::Archive).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a bundle").unwrap();
+
+        let ids = |query: &SearchQuery| -> Vec<String> {
+            search(query)
+                .unwrap()
+                .into_iter()
+                .map(|hit| hit.artifact_id)
+                .collect()
+        };
+        let all = SearchQuery::new(dir.path());
+        assert_eq!(ids(&all).len(), 3);
+        assert_eq!(
+            ids(&all.clone().graph(&graph("x")).unwrap().status("failed")),
+            [b.artifact_id.as_str()]
+        );
+        assert_eq!(
+            ids(&all.clone().sweep_group("scan").seed(1)),
+            [a.artifact_id.as_str()]
+        );
+        assert_eq!(
+            ids(&all.clone().parent(&a.artifact_id)),
+            [c.artifact_id.as_str()]
+        );
+        assert!(ids(&all.clone().since(Utc::now() + chrono::Duration::hours(1))).is_empty());
+        assert_eq!(ids(&all.clone().since(start).backend("simulated")).len(), 3);
+        assert_eq!(ids(&all.clone().limit(2)).len(), 2);
+
+        // Archives are read in place
+        let hit = &search(&all.clone().artifact_type("run").parent(&a.artifact_id)).unwrap()[0];
+        assert_eq!(hit.location, archive);
+        assert!(!archive.with_file_name(archive_stem(&archive)).exists());
+    }
+
+    fn archive_stem(archive: &Path) -> String {
+        archive
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .trim_end_matches(".tar.zst")
+            .to_string()
+    }
+}
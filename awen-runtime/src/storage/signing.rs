@@ -0,0 +1,163 @@
+//! Detached ed25519 signatures over artifact bundles
+//!
+//! The signature covers the SHA-256 digests of `manifest.json` and `checksums.json`; since
+//! `checksums.json` lists the digest of every other file, a valid signature with matching
+//! checksums proves no file was changed after export. It is stored in `signature.json`,
+//! which is itself excluded from the checksums.
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// File holding the detached signature at the bundle root
+pub const SIGNATURE_FILE: &str = "signature.json";
+
+const SIGNATURE_DOMAIN: &str = "awen-bundle-signature-v1";
+
+/// Contents of `signature.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BundleSignature {
+    pub algorithm: String,
+    /// Base64-encoded ed25519 public key of the signer
+    pub public_key: String,
+    /// Base64-encoded signature over the signed payload
+    pub signature: String,
+}
+
+/// Signs bundles at export with an ed25519 key
+pub struct BundleSigner {
+    key: SigningKey,
+}
+
+impl std::fmt::Debug for BundleSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BundleSigner")
+            .field("public_key", &self.public_key_base64())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BundleSigner {
+    /// New random signing key
+    pub fn generate() -> Self {
+        Self::from_secret_bytes(&rand::random::<[u8; 32]>()).expect("32-byte ed25519 secret")
+    }
+
+    /// Signer from a 32-byte ed25519 secret key
+    pub fn from_secret_bytes(bytes: &[u8]) -> Result<Self> {
+        let secret = <[u8; 32]>::try_from(bytes)
+            .map_err(|_| anyhow!("invalid secret key: expected 32 bytes, got {}", bytes.len()))?;
+        Ok(Self {
+            key: SigningKey::from_bytes(&secret),
+        })
+    }
+
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.key.to_bytes()
+    }
+
+    /// Base64 public key, as recorded in `signature.json` and passed as a trusted key
+    pub fn public_key_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.key.verifying_key().as_bytes())
+    }
+
+    /// Sign the bundle directory, writing `signature.json`
+    pub fn sign_bundle(&self, bundle_dir: &Path) -> Result<BundleSignature> {
+        let payload = signed_payload(bundle_dir)?;
+        let signature = BundleSignature {
+            algorithm: "ed25519".to_string(),
+            public_key: self.public_key_base64(),
+            signature: general_purpose::STANDARD.encode(self.key.sign(&payload).to_bytes()),
+        };
+        std::fs::write(
+            bundle_dir.join(SIGNATURE_FILE),
+            serde_json::to_string_pretty(&signature)?,
+        )?;
+        Ok(signature)
+    }
+}
+
+/// Bytes the signature covers: a domain tag and the digests of the manifest and checksums
+fn signed_payload(bundle_dir: &Path) -> Result<Vec<u8>> {
+    let digest = |name: &str| -> Result<String> {
+        let bytes = std::fs::read(bundle_dir.join(name))
+            .map_err(|e| anyhow!("cannot read {} to sign: {}", name, e))?;
+        Ok(hex::encode(Sha256::digest(&bytes)))
+    };
+    Ok(format!(
+        "{}\nmanifest.json {}\nchecksums.json {}\n",
+        SIGNATURE_DOMAIN,
+        digest("manifest.json")?,
+        digest("checksums.json")?
+    )
+    .into_bytes())
+}
+
+/// Verify the bundle's `signature.json`; returns the signature, or `None` if unsigned
+///
+/// This proves the manifest and checksums are unchanged since signing by the holder of
+/// `public_key`; the checksums themselves are checked by `import_bundle`. Verification is
+/// strict: non-canonical signatures and weak public keys are rejected, so a signature cannot
+/// be altered into another valid one.
+pub fn verify_bundle_signature(bundle_dir: &Path) -> Result<Option<BundleSignature>> {
+    let path = bundle_dir.join(SIGNATURE_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let signature: BundleSignature = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    if signature.algorithm != "ed25519" {
+        return Err(anyhow!(
+            "unsupported signature algorithm: {}",
+            signature.algorithm
+        ));
+    }
+    let pk_bytes = general_purpose::STANDARD
+        .decode(&signature.public_key)
+        .map_err(|e| anyhow!("invalid public_key base64: {}", e))?;
+    let sig_bytes = general_purpose::STANDARD
+        .decode(&signature.signature)
+        .map_err(|e| anyhow!("invalid signature base64: {}", e))?;
+    let pk = VerifyingKey::try_from(pk_bytes.as_slice())
+        .map_err(|e| anyhow!("invalid public key: {}", e))?;
+    let sig =
+        Signature::from_slice(&sig_bytes).map_err(|e| anyhow!("invalid signature bytes: {}", e))?;
+    pk.verify_strict(&signed_payload(bundle_dir)?, &sig)
+        .map_err(|e| anyhow!("bundle signature verification failed: {}", e))?;
+    Ok(Some(signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weak_key_forgeries_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("manifest.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("checksums.json"), "{}").unwrap();
+        let signer = BundleSigner::generate();
+        let signed = signer.sign_bundle(dir.path()).unwrap();
+        assert_eq!(verify_bundle_signature(dir.path()).unwrap(), Some(signed));
+
+        // The identity point is a small-order key: R = identity, S = 0 satisfies the
+        // non-strict equation for every message
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let mut forged_sig = identity.to_vec();
+        forged_sig.extend_from_slice(&[0u8; 32]);
+        let forged = BundleSignature {
+            algorithm: "ed25519".to_string(),
+            public_key: general_purpose::STANDARD.encode(identity),
+            signature: general_purpose::STANDARD.encode(&forged_sig),
+        };
+        std::fs::write(
+            dir.path().join(SIGNATURE_FILE),
+            serde_json::to_string(&forged).unwrap(),
+        )
+        .unwrap();
+        assert!(verify_bundle_signature(dir.path()).is_err());
+    }
+}
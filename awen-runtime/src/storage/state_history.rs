@@ -0,0 +1,133 @@
+//! State history storage and incremental reads
+//!
+//! The engine writes the per-node state history as one JSON array (`quantum_states.json`).
+//! For deep graphs this can exceed memory, so export can instead store it as framed JSONL
+//! (`quantum_states.jsonl`): one state record per line, converted from the array without
+//! holding it in memory. [`StateHistoryReader`] iterates either form record by record;
+//! only the legacy array form is parsed in full.
+
+use anyhow::{anyhow, Result};
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use serde_json::Value;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+use super::import::open_bundle_file;
+
+/// State history as written by the engine: a single JSON array
+pub const STATE_HISTORY_JSON: &str = "quantum_states.json";
+
+/// State history as framed JSONL: one record per line
+pub const STATE_HISTORY_JSONL: &str = "quantum_states.jsonl";
+
+/// How export stores the state history
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StateHistoryFormat {
+    /// Copy `quantum_states.json` unchanged
+    #[default]
+    Json,
+    /// Convert to `quantum_states.jsonl` for incremental reads
+    Jsonl,
+}
+
+/// Stream a JSON array of state records from `reader` to `writer` as JSONL, one element at a
+/// time; returns the number of records written
+pub fn json_array_to_jsonl(reader: impl Read, writer: &mut dyn Write) -> Result<usize> {
+    struct Frames<'w> {
+        writer: &'w mut dyn Write,
+    }
+
+    impl<'de> Visitor<'de> for Frames<'_> {
+        type Value = usize;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of state records")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<usize, A::Error> {
+            let mut count = 0;
+            while let Some(record) = seq.next_element::<Value>()? {
+                serde_json::to_writer(&mut *self.writer, &record)
+                    .and_then(|_| self.writer.write_all(b"\n").map_err(serde_json::Error::io))
+                    .map_err(serde::de::Error::custom)?;
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    let mut de = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let count = de
+        .deserialize_seq(Frames { writer })
+        .map_err(|e| anyhow!("state history is not a JSON array: {}", e))?;
+    de.end()?;
+    Ok(count)
+}
+
+/// Record-by-record reader over a stored state history (`.json`, `.jsonl`, or either `.zst`)
+pub struct StateHistoryReader {
+    records: Box<dyn Iterator<Item = Result<Value>>>,
+}
+
+impl StateHistoryReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let name = path.to_string_lossy();
+        let name = name
+            .strip_suffix(&format!(".{}", super::COMPRESSED_EXTENSION))
+            .unwrap_or(&name);
+        let reader = open_bundle_file(path)?;
+        let records: Box<dyn Iterator<Item = Result<Value>>> = if name.ends_with(".jsonl") {
+            Box::new(
+                BufReader::new(reader)
+                    .lines()
+                    .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+                    .map(|line| Ok(serde_json::from_str(&line?)?)),
+            )
+        } else {
+            let records: Vec<Value> = serde_json::from_reader(BufReader::new(reader))?;
+            Box::new(records.into_iter().map(Ok))
+        };
+        Ok(Self { records })
+    }
+}
+
+impl Iterator for StateHistoryReader {
+    type Item = Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_history_jsonl_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let states = serde_json::json!([
+            {"node_id": "s0", "step": 0, "seed": 7},
+            {"node_id": "d0", "step": 1, "amplitudes": [[0.5, 0.0], [0.5, 0.0]]}
+        ]);
+        let array = dir.path().join(STATE_HISTORY_JSON);
+        std::fs::write(&array, serde_json::to_vec(&states).unwrap()).unwrap();
+
+        let mut framed = Vec::new();
+        let count = json_array_to_jsonl(std::fs::File::open(&array).unwrap(), &mut framed).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(String::from_utf8_lossy(&framed).lines().count(), 2);
+        let jsonl = dir.path().join(STATE_HISTORY_JSONL);
+        std::fs::write(&jsonl, &framed).unwrap();
+
+        for path in [&array, &jsonl] {
+            let records: Vec<Value> = StateHistoryReader::open(path)
+                .unwrap()
+                .collect::<Result<_>>()
+                .unwrap();
+            assert_eq!(Value::Array(records), states);
+        }
+        assert!(json_array_to_jsonl(&b"{\"seed\": 1}"[..], &mut Vec::new()).is_err());
+    }
+}
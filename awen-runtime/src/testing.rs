@@ -0,0 +1,926 @@
+//! Property-testing support for backend authors.
+//!
+//! Generators draw random but valid inputs from any [`rand::Rng`]: IR graphs that pass
+//! [`ir::validate_graph`], simulator noise configs, calibration kernels for a graph, and
+//! normalised Fock states with gates to evolve them. Seeding a `StdRng` from a property-test
+//! framework's `u64` makes every case reproducible and shrinkable by its seed.
+//!
+//! Checkers return the first [`InvariantViolation`] they find:
+//!
+//! - causality: a node runs only after its edge inputs, its feed-forward sources and the
+//!   detector whose branch selected it;
+//! - probability normalization: states, outcome probabilities and distributions sum to 1;
+//! - coherence containment: scheduled nodes and evolved states stay inside their coherence
+//!   window.
+//!
+//! [`check_scheduler`], [`check_simulator`] and [`check_state_evolver`] run them over many
+//! generated cases against a backend:
+//!
+//! ```
+//! use awen_runtime::scheduler::StaticScheduler;
+//! use awen_runtime::testing;
+//!
+//! testing::check_scheduler(&StaticScheduler::new(), 7, 20).unwrap();
+//! ```
+
+#[cfg(feature = "native")]
+use crate::calibration::{
+    CalibrationKernel, CalibrationSchedule, CostFunction, MeasurementAction, MeasurementStep,
+    OptimizerAlgorithm, OptimizerConfig, SafetyConstraints,
+};
+use crate::ir::{self, ConditionalBranch, Edge, FeedForward, Graph, Node};
+#[cfg(feature = "native")]
+use crate::plugins::simulator::Simulator;
+use crate::scheduler::{ExecutionPlan, ResourceLimits, Scheduler, SchedulingConstraints};
+use crate::simulator::SimulatorNoiseConfig;
+use crate::state::{CoherenceWindow, MeasurementOutcome, QuantumMode, QuantumState, StateEvolver};
+use anyhow::{Context, Result};
+use num_complex::Complex64;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+/// Largest deviation from 1 accepted for a total probability
+pub const TOLERANCE: f64 = 1e-9;
+
+/// An invariant a backend broke
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InvariantViolation {
+    #[error("node {node} is missing from the plan")]
+    Unscheduled { node: String },
+    #[error("node {node} ran more than once")]
+    Repeated { node: String },
+    #[error("node {node} ran before its {link} source {source_node} completed")]
+    Acausal {
+        node: String,
+        link: &'static str,
+        source_node: String,
+    },
+    #[error("{what} has total probability {total}, expected 1")]
+    Unnormalized { what: String, total: f64 },
+    #[error("{what} has probability {value} outside [0, 1]")]
+    InvalidProbability { what: String, value: f64 },
+    #[error("coherence window {window} of {what} not found")]
+    UnknownWindow { what: String, window: String },
+    #[error("{what} falls outside coherence window {window}")]
+    Incoherent { what: String, window: String },
+    #[error("{what} differs between two runs with the same seed")]
+    Nondeterministic { what: String },
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Generators
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+const NODE_TYPES: [&str; 5] = ["MZI", "RING", "LOSS", "DELAY", "DETECTOR"];
+
+/// Random acyclic graphs of reference-simulator node types. Edges, branches and feed-forward
+/// follow a hidden execution order; the node list is shuffled, so backends must derive the
+/// order from the dependencies rather than from the list.
+#[derive(Debug, Clone)]
+pub struct GraphGenerator {
+    max_nodes: usize,
+    edge_probability: f64,
+    branch_probability: f64,
+    feed_forward_probability: f64,
+}
+
+impl Default for GraphGenerator {
+    fn default() -> Self {
+        GraphGenerator {
+            max_nodes: 8,
+            edge_probability: 0.3,
+            branch_probability: 0.2,
+            feed_forward_probability: 0.2,
+        }
+    }
+}
+
+impl GraphGenerator {
+    /// Graphs have between 1 and `max_nodes` nodes.
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes.max(1);
+        self
+    }
+
+    /// Chance of an edge between each ordered pair of nodes.
+    pub fn with_edge_probability(mut self, probability: f64) -> Self {
+        self.edge_probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Chance of a detector branching on its outcome.
+    pub fn with_branch_probability(mut self, probability: f64) -> Self {
+        self.branch_probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Chance of a node taking a parameter from an earlier detector.
+    pub fn with_feed_forward_probability(mut self, probability: f64) -> Self {
+        self.feed_forward_probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn generate(&self, rng: &mut impl Rng) -> Graph {
+        let count = rng.gen_range(1..=self.max_nodes);
+        let mut nodes: Vec<Node> = (0..count)
+            .map(|i| {
+                let node_type = NODE_TYPES[rng.gen_range(0..NODE_TYPES.len())];
+                Node {
+                    id: format!("{}_{}", node_type.to_lowercase(), i),
+                    node_type: node_type.to_string(),
+                    params: node_params(node_type, rng),
+                    measure_mode: None,
+                    measurement: None,
+                    conditional_branches: None,
+                    feed_forward: None,
+                }
+            })
+            .collect();
+
+        let mut edges = Vec::new();
+        for src in 0..count {
+            for dst in src + 1..count {
+                if rng.gen_bool(self.edge_probability) {
+                    edges.push(Edge {
+                        src_node: nodes[src].id.clone(),
+                        src_port: None,
+                        dst_node: nodes[dst].id.clone(),
+                        dst_port: None,
+                        delay: Some(rng.gen_range(0..50) as f64),
+                    });
+                }
+            }
+        }
+
+        for i in 0..count {
+            let later: Vec<String> = nodes[i + 1..].iter().map(|n| n.id.clone()).collect();
+            let is_detector = nodes[i].node_type == "DETECTOR";
+            if is_detector && !later.is_empty() && rng.gen_bool(self.branch_probability) {
+                let then_nodes = pick_some(&later, rng);
+                let else_nodes = if rng.gen_bool(0.5) {
+                    Some(pick_some(&later, rng))
+                } else {
+                    None
+                };
+                nodes[i].conditional_branches = Some(vec![ConditionalBranch {
+                    outcome_index: rng.gen_range(0..2),
+                    then_nodes,
+                    else_nodes,
+                }]);
+            }
+
+            let detectors: Vec<String> = nodes[..i]
+                .iter()
+                .filter(|n| n.node_type == "DETECTOR")
+                .map(|n| n.id.clone())
+                .collect();
+            let param = nodes[i].params.keys().min().cloned();
+            if let Some(param) = param.filter(|_| !detectors.is_empty() && !is_detector) {
+                if rng.gen_bool(self.feed_forward_probability) {
+                    nodes[i].feed_forward = Some(vec![FeedForward {
+                        param,
+                        source_node: detectors[rng.gen_range(0..detectors.len())].clone(),
+                        quadrature: 0,
+                        gain: rng.gen_range(-1.0..1.0),
+                        offset: rng.gen_range(-0.5..0.5),
+                    }]);
+                }
+            }
+        }
+
+        nodes.shuffle(rng);
+        let graph = Graph {
+            nodes,
+            edges,
+            metadata: HashMap::new(),
+        };
+        debug_assert!(ir::validate_graph(&graph).is_ok());
+        graph
+    }
+}
+
+/// Parameters inside each node type's physical range; detectors count photons.
+fn node_params(node_type: &str, rng: &mut impl Rng) -> HashMap<String, f64> {
+    let params: Vec<(&str, f64)> = match node_type {
+        "MZI" => vec![
+            ("phase", rng.gen_range(0.0..TAU)),
+            ("loss", rng.gen_range(0.0..0.1)),
+        ],
+        "RING" => vec![
+            ("coupling", rng.gen_range(0.0..1.0)),
+            ("detuning", rng.gen_range(-1.0..1.0)),
+            ("loss", rng.gen_range(0.0..0.1)),
+        ],
+        "LOSS" => vec![("loss", rng.gen_range(0.0..0.5))],
+        "DETECTOR" => vec![("quantum", 1.0)],
+        _ => Vec::new(),
+    };
+    params
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect()
+}
+
+/// A non-empty random subset of `items`, in order.
+fn pick_some<T: Clone>(items: &[T], rng: &mut impl Rng) -> Vec<T> {
+    let first = rng.gen_range(0..items.len());
+    items
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i == first || rng.gen_bool(0.3))
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+/// A noise config with every rate in a plausible range and a reciprocal crosstalk matrix over
+/// `modes` modes (empty for fewer than two).
+pub fn noise_config(rng: &mut impl Rng, modes: usize) -> SimulatorNoiseConfig {
+    // Pair (i, j), i < j, leaks leakage[i * modes + j] both ways
+    let leakage: Vec<f64> = (0..modes * modes)
+        .map(|_| rng.gen_range(0.0..0.05))
+        .collect();
+    let crosstalk = if modes < 2 {
+        Vec::new()
+    } else {
+        (0..modes)
+            .map(|i| {
+                (0..modes)
+                    .map(|j| {
+                        if i == j {
+                            0.0
+                        } else {
+                            leakage[i.min(j) * modes + i.max(j)]
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+
+    SimulatorNoiseConfig {
+        loss_rate_per_cm: rng.gen_range(0.0..0.1),
+        dark_count_rate: rng.gen_range(0.0..1e4),
+        lo_linewidth: rng.gen_range(0.0..1e4),
+        kerr_coefficient: rng.gen_range(0.0..0.2),
+        relative_intensity_noise: rng.gen_range(0.0..0.01),
+        temperature: rng.gen_range(0.0..300.0),
+        max_photons: rng.gen_range(1..=6),
+        pnr_efficiency: rng.gen_range(0.5..=1.0),
+        pnr_afterpulse_probability: rng.gen_range(0.0..0.05),
+        pnr_saturation: rng.gen_range(1..=16),
+        pnr_integration_time: rng.gen_range(1e-7..1e-5),
+        crosstalk,
+    }
+}
+
+#[cfg(feature = "native")]
+/// A kernel tuning a random subset of `graph`'s MZI phases and ring couplings, named
+/// `node:key` so `ReferenceCalibrationExecutor::with_graph` can locate them. Targets nothing
+/// when the graph has no tunable node.
+pub fn calibration_kernel(rng: &mut impl Rng, graph: &Graph) -> CalibrationKernel {
+    let tunable: Vec<(String, String, (f64, f64))> = graph
+        .nodes
+        .iter()
+        .filter_map(|n| match n.node_type.as_str() {
+            "MZI" => Some((n.id.clone(), "phase".to_string(), (0.0, TAU))),
+            "RING" => Some((n.id.clone(), "coupling".to_string(), (0.0, 1.0))),
+            _ => None,
+        })
+        .collect();
+    let chosen = if tunable.is_empty() {
+        Vec::new()
+    } else {
+        pick_some(&tunable, rng)
+    };
+
+    let mut target_nodes = Vec::new();
+    let mut parameters_to_tune = Vec::new();
+    let mut hard_limits = HashMap::new();
+    let mut measurement_sequence = Vec::new();
+    for (node, key, limits) in &chosen {
+        let name = format!("{}:{}", node, key);
+        target_nodes.push(node.clone());
+        measurement_sequence.push(MeasurementStep {
+            step_id: format!("set_{}", name),
+            action: MeasurementAction::SetParameter {
+                node_id: node.clone(),
+                param_name: key.clone(),
+                value: rng.gen_range(limits.0..limits.1),
+            },
+            expected_duration_ns: 1_000,
+        });
+        hard_limits.insert(name.clone(), *limits);
+        parameters_to_tune.push(name);
+    }
+    let sensor = graph
+        .nodes
+        .iter()
+        .rev()
+        .find(|n| n.node_type == "DETECTOR")
+        .map_or("output", |n| n.id.as_str());
+    measurement_sequence.push(MeasurementStep {
+        step_id: "read".to_string(),
+        action: MeasurementAction::ReadSensor {
+            sensor_id: format!("{}:power", sensor),
+            integration_time_ns: rng.gen_range(1_000..100_000),
+        },
+        expected_duration_ns: 100_000,
+    });
+
+    let expression = format!("{}:power", sensor);
+    let cost_function = if rng.gen_bool(0.5) {
+        CostFunction::Minimize {
+            expression,
+            target_value: Some(rng.gen_range(0.0..1.0)),
+        }
+    } else {
+        CostFunction::Maximize { expression }
+    };
+    let algorithm = if rng.gen_bool(0.5) {
+        OptimizerAlgorithm::NelderMead {
+            initial_simplex_size: rng.gen_range(0.01..0.1),
+        }
+    } else {
+        OptimizerAlgorithm::GradientDescent {
+            learning_rate: rng.gen_range(0.01..0.1),
+            momentum: rng.gen_range(0.0..0.9),
+            gradient_provider: "finite_diff".to_string(),
+        }
+    };
+
+    CalibrationKernel {
+        id: format!("kernel_{:08x}", rng.gen::<u32>()),
+        target_nodes,
+        parameters_to_tune,
+        cost_function,
+        measurement_sequence,
+        optimizer_config: OptimizerConfig {
+            algorithm,
+            max_iterations: rng.gen_range(1..=20),
+            convergence_threshold: 1e-6,
+            initial_guess: None,
+        },
+        safety_constraints: SafetyConstraints {
+            hard_limits,
+            ..SafetyConstraints::default()
+        },
+        schedule: CalibrationSchedule::Manual,
+    }
+}
+
+/// A product of `modes` normalised Fock superpositions up to `cutoff` photons, with modes
+/// numbered from `0` and a coherence window of 1 to 100 µs.
+pub fn quantum_state(rng: &mut impl Rng, modes: usize, cutoff: usize) -> QuantumState {
+    let modes = (0..modes)
+        .map(|i| {
+            let amplitudes: Vec<Complex64> = (0..=cutoff)
+                .map(|_| Complex64::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)))
+                .collect();
+            let norm = amplitudes.iter().map(|a| a.norm_sqr()).sum::<f64>().sqrt();
+            let amplitudes = if norm > 0.0 {
+                amplitudes.into_iter().map(|a| a / norm).collect()
+            } else {
+                let mut vacuum = vec![Complex64::new(0.0, 0.0); cutoff + 1];
+                vacuum[0] = Complex64::new(1.0, 0.0);
+                vacuum
+            };
+            QuantumMode {
+                mode_id: i.to_string(),
+                mode_type: "quantum_fock".to_string(),
+                photon_numbers: Some((0..=cutoff as u32).collect()),
+                amplitudes: Some(amplitudes),
+            }
+        })
+        .collect();
+
+    QuantumState {
+        id: format!("state_{:08x}", rng.gen::<u32>()),
+        modes,
+        coherence_window: CoherenceWindow::new("window".to_string(), rng.gen_range(1_000..100_000)),
+        seed: None,
+        provenance: HashMap::new(),
+    }
+}
+
+/// A random `StateEvolver` gate on a state of `modes` modes: PS, DISPLACEMENT and SQUEEZING
+/// on one mode, or BS between two.
+pub fn gate(rng: &mut impl Rng, modes: usize) -> (String, HashMap<String, f64>) {
+    let mode = rng.gen_range(0..modes.max(1)) as f64;
+    let (gate, params) = match rng.gen_range(0..if modes > 1 { 4 } else { 3 }) {
+        0 => (
+            "PS",
+            vec![("mode_id", mode), ("phase", rng.gen_range(0.0..TAU))],
+        ),
+        1 => (
+            "DISPLACEMENT",
+            vec![
+                ("mode_id", mode),
+                ("q", rng.gen_range(-0.5..0.5)),
+                ("p", rng.gen_range(-0.5..0.5)),
+            ],
+        ),
+        2 => (
+            "SQUEEZING",
+            vec![
+                ("mode_id", mode),
+                ("r", rng.gen_range(0.0..0.5)),
+                ("angle", rng.gen_range(0.0..TAU)),
+            ],
+        ),
+        _ => {
+            let mode1 = rng.gen_range(0..modes);
+            let mode2 = (mode1 + rng.gen_range(1..modes)) % modes;
+            (
+                "BS",
+                vec![
+                    ("mode1", mode1 as f64),
+                    ("mode2", mode2 as f64),
+                    ("theta", rng.gen_range(0.0..TAU)),
+                ],
+            )
+        }
+    };
+    (
+        gate.to_string(),
+        params
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Invariant Checkers
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Causal links into each node: `(source, link)` for edges, feed-forward and branches.
+fn causal_links(graph: &Graph) -> Vec<(&str, &str, &'static str)> {
+    let mut links: Vec<(&str, &str, &'static str)> = graph
+        .edges
+        .iter()
+        .map(|e| (e.dst_node.as_str(), e.src_node.as_str(), "edge"))
+        .collect();
+    for node in &graph.nodes {
+        for ff in node.feed_forward.iter().flatten() {
+            links.push((&node.id, &ff.source_node, "feed-forward"));
+        }
+        for branch in node.conditional_branches.iter().flatten() {
+            for target in branch
+                .then_nodes
+                .iter()
+                .chain(branch.else_nodes.iter().flatten())
+            {
+                links.push((target, &node.id, "branch"));
+            }
+        }
+    }
+    links
+}
+
+/// Check that `order`, the node ids a backend executed, runs each node at most once and after
+/// every causal source. Nodes skipped by a branch may be absent.
+pub fn check_causal_order(graph: &Graph, order: &[String]) -> Result<(), InvariantViolation> {
+    let mut position = HashMap::new();
+    for (i, node) in order.iter().enumerate() {
+        if position.insert(node.as_str(), i).is_some() {
+            return Err(InvariantViolation::Repeated { node: node.clone() });
+        }
+    }
+    for (node, source, link) in causal_links(graph) {
+        if let Some(&at) = position.get(node) {
+            if position.get(source).is_none_or(|&from| from > at) {
+                return Err(InvariantViolation::Acausal {
+                    node: node.to_string(),
+                    link,
+                    source_node: source.to_string(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that `plan` schedules every node of `graph`, starting each after its sources end
+/// (plus the edge delay for edges).
+pub fn check_plan_causality(graph: &Graph, plan: &ExecutionPlan) -> Result<(), InvariantViolation> {
+    for node in &graph.nodes {
+        if !plan.schedule.contains_key(&node.id) {
+            return Err(InvariantViolation::Unscheduled {
+                node: node.id.clone(),
+            });
+        }
+    }
+    let delays: HashMap<(&str, &str), u64> = graph
+        .edges
+        .iter()
+        .map(|e| {
+            let delay = e.delay.unwrap_or(0.0) as u64;
+            ((e.src_node.as_str(), e.dst_node.as_str()), delay)
+        })
+        .collect();
+    for (node, source, link) in causal_links(graph) {
+        let (Some(at), Some(from)) = (plan.schedule.get(node), plan.schedule.get(source)) else {
+            continue;
+        };
+        let delay = match link {
+            "edge" => delays.get(&(source, node)).copied().unwrap_or(0),
+            _ => 0,
+        };
+        if at.start_time_ns < from.end_time_ns + delay {
+            return Err(InvariantViolation::Acausal {
+                node: node.to_string(),
+                link,
+                source_node: source.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check that every node `plan` assigns to a coherence window of `constraints` runs inside it.
+pub fn check_plan_coherence(
+    plan: &ExecutionPlan,
+    constraints: &SchedulingConstraints,
+) -> Result<(), InvariantViolation> {
+    for node in plan.schedule.values() {
+        let Some(window_id) = &node.coherence_window_id else {
+            continue;
+        };
+        let what = format!("node {}", node.node_id);
+        let window = constraints
+            .coherence_windows
+            .iter()
+            .find(|w| &w.id == window_id)
+            .ok_or_else(|| InvariantViolation::UnknownWindow {
+                what: what.clone(),
+                window: window_id.clone(),
+            })?;
+        if node.start_time_ns < window.start_ns
+            || node.end_time_ns > window.start_ns + window.duration_ns
+        {
+            return Err(InvariantViolation::Incoherent {
+                what,
+                window: window_id.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Whether `value` is a probability, up to [`TOLERANCE`] of rounding.
+fn is_probability(value: f64) -> bool {
+    (-TOLERANCE..=1.0 + TOLERANCE).contains(&value)
+}
+
+/// Check that `probabilities` are each in [0, 1] and sum to 1.
+pub fn check_distribution(what: &str, probabilities: &[f64]) -> Result<(), InvariantViolation> {
+    for &value in probabilities {
+        if !is_probability(value) {
+            return Err(InvariantViolation::InvalidProbability {
+                what: what.to_string(),
+                value,
+            });
+        }
+    }
+    let total: f64 = probabilities.iter().sum();
+    if (total - 1.0).abs() > TOLERANCE {
+        return Err(InvariantViolation::Unnormalized {
+            what: what.to_string(),
+            total,
+        });
+    }
+    Ok(())
+}
+
+/// Check that every mode of `state` holding amplitudes has unit norm.
+pub fn check_normalization(state: &QuantumState) -> Result<(), InvariantViolation> {
+    for mode in &state.modes {
+        if let Some(amplitudes) = &mode.amplitudes {
+            let populations: Vec<f64> = amplitudes.iter().map(|a| a.norm_sqr()).collect();
+            check_distribution(
+                &format!("mode {} of state {}", mode.mode_id, state.id),
+                &populations,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Check that a measurement outcome's probability is in [0, 1] and its collapsed state is
+/// normalised.
+pub fn check_outcome(outcome: &MeasurementOutcome) -> Result<(), InvariantViolation> {
+    if !is_probability(outcome.probability) {
+        return Err(InvariantViolation::InvalidProbability {
+            what: format!("outcome {}", outcome.outcome_index),
+            value: outcome.probability,
+        });
+    }
+    match &outcome.collapsed_state {
+        Some(state) => check_normalization(state),
+        None => Ok(()),
+    }
+}
+
+/// Check that `after`, evolved from `before`, has not left or widened `before`'s coherence
+/// window.
+pub fn check_coherence_containment(
+    before: &QuantumState,
+    after: &QuantumState,
+) -> Result<(), InvariantViolation> {
+    let (outer, inner) = (&before.coherence_window, &after.coherence_window);
+    if inner.start_ns < outer.start_ns || inner.end_ns > outer.end_ns {
+        return Err(InvariantViolation::Incoherent {
+            what: format!("state {}", after.id),
+            window: outer.id.clone(),
+        });
+    }
+    Ok(())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Backend Harnesses
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Run `check` on `cases` cases, each with its own RNG seeded from `seed`; a failure names the
+/// case seed to reproduce it with.
+fn for_each_case(
+    seed: u64,
+    cases: usize,
+    mut check: impl FnMut(&mut StdRng, u64) -> Result<()>,
+) -> Result<()> {
+    let mut seeds = StdRng::seed_from_u64(seed);
+    for case in 0..cases {
+        let case_seed: u64 = seeds.gen();
+        let mut rng = StdRng::seed_from_u64(case_seed);
+        check(&mut rng, case_seed)
+            .with_context(|| format!("case {} (seed {})", case, case_seed))?;
+    }
+    Ok(())
+}
+
+/// Schedule `cases` generated graphs inside one coherence window, checking plan causality and
+/// coherence containment.
+pub fn check_scheduler(scheduler: &dyn Scheduler, seed: u64, cases: usize) -> Result<()> {
+    let generator = GraphGenerator::default();
+    for_each_case(seed, cases, |rng, case_seed| {
+        let graph = generator.generate(rng);
+        let mut constraints = SchedulingConstraints::unconstrained(ResourceLimits {
+            max_wavelengths: 2,
+            max_memory_slots: 2,
+            max_concurrent_operations: graph.nodes.len(),
+        });
+        constraints
+            .coherence_windows
+            .push(CoherenceWindow::new("window".to_string(), 1_000_000));
+        let plan = scheduler.schedule(&graph, &constraints, case_seed)?;
+        check_plan_causality(&graph, &plan)?;
+        check_plan_coherence(&plan, &constraints)?;
+        Ok(())
+    })
+}
+
+#[cfg(feature = "native")]
+/// Simulate `cases` generated graphs twice with the same seed, checking the results agree and
+/// that `node_results` lists nodes in causal order.
+pub fn check_simulator(simulator: &dyn Simulator, seed: u64, cases: usize) -> Result<()> {
+    let generator = GraphGenerator::default();
+    for_each_case(seed, cases, |rng, case_seed| {
+        let graph = generator.generate(rng);
+        let results = simulator.simulate(&graph, Some(case_seed))?;
+        if simulator.simulate(&graph, Some(case_seed))? != results {
+            return Err(InvariantViolation::Nondeterministic {
+                what: "simulation results".to_string(),
+            }
+            .into());
+        }
+        let order: Vec<String> = results["node_results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r["node_id"].as_str().map(str::to_string))
+            .collect();
+        check_causal_order(&graph, &order)?;
+        Ok(())
+    })
+}
+
+/// Apply a few random gates to `cases` generated states and measure one mode, checking every
+/// state stays normalised and inside its coherence window and the outcome is a probability.
+pub fn check_state_evolver(evolver: &dyn StateEvolver, seed: u64, cases: usize) -> Result<()> {
+    for_each_case(seed, cases, |rng, case_seed| {
+        let modes = rng.gen_range(1..=3);
+        let cutoff = rng.gen_range(1..=4);
+        let mut state = quantum_state(rng, modes, cutoff);
+        check_normalization(&state)?;
+        for _ in 0..rng.gen_range(1..=4) {
+            let (name, params) = gate(rng, modes);
+            let next = evolver
+                .evolve_state(&state, &name, &params)
+                .with_context(|| format!("gate {} {:?}", name, params))?;
+            check_normalization(&next)?;
+            check_coherence_containment(&state, &next)?;
+            state = next;
+        }
+        let mode = rng.gen_range(0..modes).to_string();
+        let outcome = evolver.measure(&state, &mode, Some(case_seed))?;
+        check_outcome(&outcome)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::calibration::{CalibrationExecutor, ReferenceCalibrationExecutor};
+    #[cfg(feature = "native")]
+    use crate::plugins::simulator::ReferenceSimulator;
+    use crate::scheduler::{ResourceState, StaticScheduler};
+    use crate::state::ReferenceStateEvolver;
+
+    /// Times nodes as they come up in list order, so a dependency listed later counts as
+    /// ending at 0
+    struct ListOrderScheduler;
+
+    impl Scheduler for ListOrderScheduler {
+        fn schedule(
+            &self,
+            graph: &Graph,
+            constraints: &SchedulingConstraints,
+            seed: u64,
+        ) -> Result<ExecutionPlan> {
+            let mut plan = StaticScheduler::new().schedule(graph, constraints, seed)?;
+            let detectors = ir::detector_dependencies(graph);
+            let mut end_times: HashMap<&str, u64> = HashMap::new();
+            for node in &graph.nodes {
+                let end = |id: &str| end_times.get(id).copied().unwrap_or(0);
+                let edge_ready = graph
+                    .edges
+                    .iter()
+                    .filter(|e| e.dst_node == node.id)
+                    .map(|e| end(&e.src_node) + e.delay.unwrap_or(0.0) as u64);
+                let detector_ready = detectors
+                    .get(node.id.as_str())
+                    .into_iter()
+                    .flatten()
+                    .map(|d| end(d));
+                let start = edge_ready.chain(detector_ready).max().unwrap_or(0);
+                let scheduled = plan.schedule.get_mut(&node.id).unwrap();
+                scheduled.start_time_ns = start;
+                scheduled.end_time_ns = start + 100;
+                end_times.insert(&node.id, scheduled.end_time_ns);
+            }
+            Ok(plan)
+        }
+
+        fn validate_plan(&self, _plan: &ExecutionPlan, _state: &ResourceState) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_generators_are_valid_and_deterministic() {
+        let generator = GraphGenerator::default()
+            .with_max_nodes(12)
+            .with_branch_probability(0.8)
+            .with_feed_forward_probability(0.8);
+        let mut shuffled = false;
+        for seed in 0..50 {
+            let graph = generator.generate(&mut StdRng::seed_from_u64(seed));
+            ir::validate_graph(&graph).unwrap();
+            let order: Vec<String> = ir::execution_order(&graph)
+                .unwrap()
+                .iter()
+                .map(|n| n.id.clone())
+                .collect();
+            check_causal_order(&graph, &order).unwrap();
+            let listed: Vec<String> = graph.nodes.iter().map(|n| n.id.clone()).collect();
+            shuffled |= check_causal_order(&graph, &listed).is_err();
+
+            let again = generator.generate(&mut StdRng::seed_from_u64(seed));
+            assert_eq!(
+                serde_json::to_value(&graph).unwrap(),
+                serde_json::to_value(&again).unwrap()
+            );
+
+            let noise = noise_config(&mut StdRng::seed_from_u64(seed), 4);
+            noise.validate_crosstalk().unwrap();
+        }
+        assert!(shuffled, "node lists are never out of execution order");
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_calibration_kernels_run_on_their_graph() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let generator = GraphGenerator::default().with_max_nodes(4);
+        for _ in 0..10 {
+            let graph = generator.generate(&mut rng);
+            let kernel = calibration_kernel(&mut rng, &graph);
+            for name in &kernel.parameters_to_tune {
+                assert!(kernel.safety_constraints.hard_limits.contains_key(name));
+            }
+            let executor = ReferenceCalibrationExecutor::new().with_graph(graph);
+            let state = executor.execute_calibration(&kernel, None).unwrap();
+            for node in &kernel.target_nodes {
+                assert!(state.node_calibrations.contains_key(node));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reference_backends_hold_invariants() {
+        check_scheduler(&StaticScheduler::new(), 11, 50).unwrap();
+        #[cfg(feature = "native")]
+        check_simulator(&ReferenceSimulator, 11, 50).unwrap();
+        check_state_evolver(&ReferenceStateEvolver, 11, 50).unwrap();
+    }
+
+    #[test]
+    fn test_check_scheduler_catches_list_order_scheduling() {
+        let err = check_scheduler(&ListOrderScheduler, 11, 50).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<InvariantViolation>(),
+                Some(InvariantViolation::Acausal { .. })
+            ),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_checkers_report_violations() {
+        let graph = GraphGenerator::default()
+            .with_max_nodes(1)
+            .generate(&mut StdRng::seed_from_u64(0));
+        let id = graph.nodes[0].id.clone();
+        assert_eq!(
+            check_causal_order(&graph, &[id.clone(), id.clone()]),
+            Err(InvariantViolation::Repeated { node: id })
+        );
+
+        let mut chain = graph.clone();
+        chain.nodes.push(Node {
+            id: "mzi_1".to_string(),
+            node_type: "MZI".to_string(),
+            params: HashMap::new(),
+            measure_mode: None,
+            measurement: None,
+            conditional_branches: None,
+            feed_forward: None,
+        });
+        chain.edges.push(Edge {
+            src_node: chain.nodes[0].id.clone(),
+            src_port: None,
+            dst_node: "mzi_1".to_string(),
+            dst_port: None,
+            delay: Some(5.0),
+        });
+        let reversed = vec!["mzi_1".to_string(), chain.nodes[0].id.clone()];
+        let err = check_causal_order(&chain, &reversed).unwrap_err();
+        assert!(matches!(
+            err,
+            InvariantViolation::Acausal { link: "edge", .. }
+        ));
+
+        let constraints = SchedulingConstraints::unconstrained(ResourceLimits {
+            max_wavelengths: 1,
+            max_memory_slots: 1,
+            max_concurrent_operations: 1,
+        });
+        let mut plan = StaticScheduler::new()
+            .schedule(&chain, &constraints, 1)
+            .unwrap();
+        check_plan_causality(&chain, &plan).unwrap();
+        plan.schedule.get_mut("mzi_1").unwrap().start_time_ns -= 1;
+        assert!(check_plan_causality(&chain, &plan).is_err());
+        plan.schedule.get_mut("mzi_1").unwrap().coherence_window_id = Some("gone".to_string());
+        assert!(matches!(
+            check_plan_coherence(&plan, &constraints),
+            Err(InvariantViolation::UnknownWindow { .. })
+        ));
+
+        assert!(check_distribution("coin", &[0.5, 0.5]).is_ok());
+        assert!(matches!(
+            check_distribution("coin", &[0.5, 0.4]),
+            Err(InvariantViolation::Unnormalized { .. })
+        ));
+        assert!(matches!(
+            check_distribution("coin", &[1.5, -0.5]),
+            Err(InvariantViolation::InvalidProbability { .. })
+        ));
+
+        let state = quantum_state(&mut StdRng::seed_from_u64(1), 2, 2);
+        check_normalization(&state).unwrap();
+        let mut leaky = state.clone();
+        leaky.modes[1].amplitudes.as_mut().unwrap()[0] *= 0.5;
+        assert!(check_normalization(&leaky).is_err());
+        leaky.coherence_window.end_ns += 1;
+        assert!(matches!(
+            check_coherence_containment(&state, &leaky),
+            Err(InvariantViolation::Incoherent { .. })
+        ));
+    }
+}
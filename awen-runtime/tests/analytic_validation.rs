@@ -0,0 +1,263 @@
+//! Analytic validation of the simulators against closed-form results: beam-splitter output
+//! statistics, squeezed-vacuum quadrature variances, loss-channel means and the Hong–Ou–Mandel
+//! dip. Each check sweeps its parameters so backend changes are held to the physics, not to
+//! previously recorded outputs.
+
+use awen_runtime::quantum::gaussian::GaussianState;
+use awen_runtime::simulator::{PhotonLossChannel, TrajectorySimulator};
+use awen_runtime::state::{
+    CoherenceWindow, QuantumMode, QuantumState, ReferenceStateEvolver, StateEvolver,
+};
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// Exact Fock-basis results (no sampling) are held to this tolerance.
+const EXACT_TOLERANCE: f64 = 1e-9;
+
+/// Modes "0", "1", ... holding the given real Fock amplitudes.
+fn fock_modes(modes: &[Vec<f64>]) -> QuantumState {
+    QuantumState {
+        id: "analytic".to_string(),
+        modes: modes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| QuantumMode {
+                mode_id: i.to_string(),
+                mode_type: "quantum_fock".to_string(),
+                photon_numbers: Some((0..c.len() as u32).collect()),
+                amplitudes: Some(c.iter().map(|a| Complex64::new(*a, 0.0)).collect()),
+            })
+            .collect(),
+        coherence_window: CoherenceWindow::new("cw".to_string(), 1_000_000),
+        seed: Some(1),
+        provenance: HashMap::new(),
+    }
+}
+
+/// `|n⟩` as a real amplitude vector.
+fn number_state(n: usize) -> Vec<f64> {
+    let mut c = vec![0.0; n + 1];
+    c[n] = 1.0;
+    c
+}
+
+fn evolve(state: &QuantumState, gate: &str, params: &[(&str, f64)]) -> QuantumState {
+    let params: HashMap<String, f64> = params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    ReferenceStateEvolver
+        .evolve_state(state, gate, &params)
+        .unwrap()
+}
+
+/// Photon-number populations of a mode, indexed by photon number.
+fn populations(mode: &QuantumMode) -> Vec<f64> {
+    let numbers = mode.photon_numbers.clone().unwrap();
+    let amps = mode.amplitudes.as_ref().unwrap();
+    let mut p = vec![0.0; *numbers.iter().max().unwrap() as usize + 1];
+    for (n, a) in numbers.iter().zip(amps) {
+        p[*n as usize] += a.norm_sqr();
+    }
+    p
+}
+
+fn mean_photons(mode: &QuantumMode) -> f64 {
+    populations(mode)
+        .iter()
+        .enumerate()
+        .map(|(n, p)| n as f64 * p)
+        .sum()
+}
+
+/// `Var x_φ` of a Fock-amplitude vector, `x_φ = (a e^{−iφ} + a† e^{iφ})/√2`.
+fn quadrature_variance(amps: &[Complex64], phi: f64) -> f64 {
+    let level = |n: usize| amps.get(n).copied().unwrap_or_default();
+    let a: Complex64 = (1..amps.len())
+        .map(|n| level(n - 1).conj() * level(n) * (n as f64).sqrt())
+        .sum();
+    let a2: Complex64 = (2..amps.len())
+        .map(|n| level(n - 2).conj() * level(n) * ((n * (n - 1)) as f64).sqrt())
+        .sum();
+    let n: f64 = amps
+        .iter()
+        .enumerate()
+        .map(|(n, c)| n as f64 * c.norm_sqr())
+        .sum();
+    let rotation = Complex64::from_polar(1.0, -phi);
+    let mean = std::f64::consts::SQRT_2 * (a * rotation).re;
+    (a2 * rotation * rotation).re + n + 0.5 - mean * mean
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).map(|i| (n - i) as f64 / (i + 1) as f64).product()
+}
+
+#[test]
+fn test_beam_splitter_output_statistics() {
+    // |n, 0⟩ → each photon independently transmitted with T = cos²θ: both outputs binomial.
+    for n in 1..=4 {
+        for theta in [0.2, FRAC_PI_4, 1.1, FRAC_PI_2] {
+            let input = fock_modes(&[number_state(n), vec![1.0]]);
+            let out = evolve(
+                &input,
+                "BS",
+                &[("mode1", 0.0), ("mode2", 1.0), ("theta", theta)],
+            );
+            let t = theta.cos().powi(2);
+            for (mode, transmitted) in out.modes.iter().zip([t, 1.0 - t]) {
+                let p = populations(mode);
+                for (k, pk) in p.iter().enumerate() {
+                    let expected = binomial(n, k)
+                        * transmitted.powi(k as i32)
+                        * (1.0 - transmitted).powi((n - k) as i32);
+                    assert!(
+                        (pk - expected).abs() < EXACT_TOLERANCE,
+                        "n={} θ={} P({})={} expected {}",
+                        n,
+                        theta,
+                        k,
+                        pk,
+                        expected
+                    );
+                }
+            }
+            let total = mean_photons(&out.modes[0]) + mean_photons(&out.modes[1]);
+            assert!((total - n as f64).abs() < EXACT_TOLERANCE);
+        }
+    }
+}
+
+#[test]
+fn test_squeezed_vacuum_variances() {
+    // S(r e^{iθ})|0⟩: Var x_φ = ½(e^{−2r} cos²(φ − θ/2) + e^{2r} sin²(φ − θ/2)), ⟨n⟩ = sinh² r.
+    for r in [0.1, 0.3, 0.6, 1.0] {
+        for angle in [0.0, FRAC_PI_2, PI] {
+            let squeezed = evolve(
+                &fock_modes(&[vec![1.0]]),
+                "SQUEEZING",
+                &[("mode_id", 0.0), ("r", r), ("angle", angle)],
+            );
+            let mode = &squeezed.modes[0];
+            assert!((mean_photons(mode) - r.sinh().powi(2)).abs() < 1e-6);
+
+            let mut gaussian = GaussianState::vacuum(1);
+            gaussian.squeeze(0, r, angle).unwrap();
+            for phi in [0.0, FRAC_PI_4, FRAC_PI_2] {
+                let delta = phi - angle / 2.0;
+                let expected = 0.5
+                    * ((-2.0 * r).exp() * delta.cos().powi(2)
+                        + (2.0 * r).exp() * delta.sin().powi(2));
+                let fock = quadrature_variance(mode.amplitudes.as_ref().unwrap(), phi);
+                assert!(
+                    (fock - expected).abs() < 1e-6,
+                    "r={} θ={} φ={}: Var {} expected {}",
+                    r,
+                    angle,
+                    phi,
+                    fock,
+                    expected
+                );
+
+                // The Gaussian backend's covariance gives the same variance.
+                let (c, s) = (phi.cos(), phi.sin());
+                let v = &gaussian.covariance;
+                let backend = c * c * v[0][0] + 2.0 * c * s * v[0][1] + s * s * v[1][1];
+                assert!((backend - expected).abs() < EXACT_TOLERANCE);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_loss_channel_means() {
+    // Loss with transmissivity η scales ⟨n⟩ by η and leaves a binomial on number states.
+    let shots = 4000;
+    for (n, loss) in [(1u32, 0.1), (3, 0.3), (5, 0.5)] {
+        let eta = 1.0 - loss;
+        let channel = PhotonLossChannel {
+            loss_probability: loss,
+        };
+        let mut rng = StdRng::seed_from_u64(n as u64);
+        let counts: Vec<f64> = (0..shots)
+            .map(|_| channel.apply(n, &mut rng) as f64)
+            .collect();
+        let mean = counts.iter().sum::<f64>() / shots as f64;
+        let sigma = (n as f64 * eta * loss / shots as f64).sqrt();
+        assert!(
+            (mean - n as f64 * eta).abs() < 4.0 * sigma,
+            "n={} loss={}: mean {}",
+            n,
+            loss,
+            mean
+        );
+
+        // Trajectories of the same channel average to the same mean.
+        let trajectory = TrajectorySimulator::new(loss, 0.0);
+        let identity = (
+            "PS".to_string(),
+            HashMap::from([("mode_id".to_string(), 0.0), ("phase".to_string(), 0.0)]),
+        );
+        let runs = trajectory
+            .run_shots(
+                &fock_modes(&[number_state(n as usize)]),
+                &[identity],
+                shots,
+                11,
+            )
+            .unwrap();
+        let trajectory_mean = runs
+            .iter()
+            .map(|shot| shot.mean_photon_number("0").unwrap())
+            .sum::<f64>()
+            / shots as f64;
+        assert!(
+            (trajectory_mean - n as f64 * eta).abs() < 4.0 * sigma,
+            "n={} loss={}: trajectory mean {}",
+            n,
+            loss,
+            trajectory_mean
+        );
+
+        // The Gaussian backend attenuates a displaced state's ⟨n⟩ = |α|² exactly by η.
+        let mut coherent = GaussianState::vacuum(1);
+        coherent.displace(0, 2.0, 1.0).unwrap();
+        let before = coherent.mean_photon_number(0).unwrap();
+        coherent.attenuate(0, eta, 0.0).unwrap();
+        assert!((coherent.mean_photon_number(0).unwrap() - eta * before).abs() < EXACT_TOLERANCE);
+    }
+}
+
+#[test]
+fn test_hong_ou_mandel_dip() {
+    // |1, 1⟩ on reflectivity R = sin²θ: P(1, 1) = (T − R)² = cos² 2θ, vanishing at 50:50, and
+    // the photons bunch with P(2, 0) = P(0, 2) = sin² 2θ / 2.
+    let pair = fock_modes(&[number_state(1), number_state(1)]);
+    let coincidence = |theta: f64| {
+        let out = evolve(
+            &pair,
+            "BS",
+            &[("mode1", 0.0), ("mode2", 1.0), ("theta", theta)],
+        );
+        let p = populations(&out.modes[0]);
+        let q = populations(&out.modes[1]);
+        assert!((p[2] - (2.0 * theta).sin().powi(2) / 2.0).abs() < EXACT_TOLERANCE);
+        assert!((q[2] - p[0]).abs() < EXACT_TOLERANCE);
+        p[1]
+    };
+
+    let thetas: Vec<f64> = (0..=20).map(|k| k as f64 * FRAC_PI_2 / 20.0).collect();
+    let dip: Vec<f64> = thetas.iter().map(|&theta| coincidence(theta)).collect();
+    for (theta, p) in thetas.iter().zip(&dip) {
+        assert!(
+            (p - (2.0 * theta).cos().powi(2)).abs() < EXACT_TOLERANCE,
+            "θ={}: P(1,1)={}",
+            theta,
+            p
+        );
+    }
+    // Full visibility: the dip reaches zero at θ = π/4 from unity at θ = 0.
+    assert!(dip[10] < EXACT_TOLERANCE);
+    assert!((dip[0] - 1.0).abs() < EXACT_TOLERANCE);
+    assert!(dip[..10].windows(2).all(|w| w[1] < w[0]));
+}
@@ -5,8 +5,13 @@
 
 use awen_runtime::ir::{Graph, Node};
 use awen_runtime::storage::{
-    capture_environment, compute_deterministic_id, export_bundle, short_id, ArtifactType,
-    BundleBuilder, ExportFormat,
+    capture_environment, compute_deterministic_id, export_bundle, export_bundle_with,
+    export_signed_bundle, export_sweep, generate_key, import_bundle, import_encrypted_bundle,
+    import_legacy_run_dir, import_trusted_bundle, load_artifact_for_replay,
+    load_remote_artifact_for_replay, read_archive_manifest, read_bundle_file, save_artifact_remote,
+    short_id, ArtifactType, BundleBuilder, BundleSigner, DirectoryStore, ExportFormat,
+    ExportOptions, ProvenanceEdge, ProvenanceGraph, Relationship, RemoteStore, StateHistoryFormat,
+    StateHistoryReader, StaticKeyProvider,
 };
 use std::collections::HashMap;
 use tempfile::TempDir;
@@ -22,7 +27,9 @@ fn create_test_graph() -> Graph {
                 .cloned()
                 .collect(),
             measure_mode: None,
+            measurement: None,
             conditional_branches: None,
+            feed_forward: None,
         }],
         edges: vec![],
         metadata: HashMap::new(),
@@ -257,3 +264,422 @@ fn test_11_artifact_directory_structure() {
         assert!(export_path.join(dir).exists(), "{} directory missing", dir);
     }
 }
+
+#[test]
+fn test_12_artifact_zstd_compressed_round_trip() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let run_dir = temp_dir.path().join("run");
+    std::fs::create_dir_all(&run_dir).unwrap();
+    let states = serde_json::json!([{"node": "n0", "amplitudes": vec![0.5; 512]}]).to_string();
+    std::fs::write(run_dir.join("quantum_states.json"), &states).unwrap();
+    std::fs::write(run_dir.join("traces.jsonl"), "{\"id\":\"s0\"}\n").unwrap();
+
+    let results = serde_json::json!({"measurements": [0, 1, 0]});
+    let bundle = BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+        .with_initial_parameters(create_test_params())
+        .with_results(results.clone())
+        .with_observability_dir(&run_dir)
+        .build()
+        .expect("Should create bundle");
+
+    let compressed = export_bundle(
+        &bundle,
+        &temp_dir.path().join("compressed"),
+        ExportFormat::CompressedDirectory,
+    )
+    .expect("Should export compressed bundle");
+    assert!(compressed.join("results/outputs.json.zst").exists());
+    assert!(!compressed.join("results/outputs.json").exists());
+    let stored_states = compressed.join("provenance/quantum_states.json.zst");
+    assert!(std::fs::metadata(&stored_states).unwrap().len() < states.len() as u64);
+
+    let imported = import_bundle(&compressed).expect("Should import compressed bundle");
+    assert_eq!(imported.results, results);
+    let obs = imported.observability.expect("observability imported");
+    assert_eq!(obs.states.as_deref(), Some(stored_states.as_path()));
+    assert_eq!(read_bundle_file(&stored_states).unwrap(), states.as_bytes());
+
+    let archive = export_bundle(
+        &bundle,
+        &temp_dir.path().join("archives"),
+        ExportFormat::Archive,
+    )
+    .expect("Should export archive");
+    assert!(archive.to_string_lossy().ends_with(".tar.zst"));
+    assert_eq!(
+        std::fs::read_dir(archive.parent().unwrap())
+            .unwrap()
+            .count(),
+        1
+    );
+    let imported = import_bundle(&archive).expect("Should import archive");
+    assert_eq!(imported.artifact_id, bundle.artifact_id);
+    assert_eq!(imported.results, results);
+    let states_path = imported.observability.unwrap().states.unwrap();
+    assert_eq!(read_bundle_file(&states_path).unwrap(), states.as_bytes());
+}
+
+#[test]
+fn test_13_artifact_single_file_archive() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let bundle = BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+        .with_initial_parameters(create_test_params())
+        .with_seed(7)
+        .with_results(serde_json::json!({"measurements": [1, 1, 0]}))
+        .build()
+        .expect("Should create bundle");
+
+    let archive = export_bundle(&bundle, temp_dir.path(), ExportFormat::Archive)
+        .expect("Should export archive");
+    let hash = bundle.artifact_id.strip_prefix("awen_").unwrap();
+    assert_eq!(
+        archive.file_name().unwrap().to_string_lossy(),
+        format!("awen_run_{}.tar.zst", hash)
+    );
+
+    let manifest = read_archive_manifest(&archive).expect("Should read manifest in place");
+    assert_eq!(manifest.artifact_id, bundle.artifact_id);
+    assert!(!temp_dir.path().join(format!("awen_run_{}", hash)).exists());
+
+    let imported = import_bundle(&archive).expect("Should import archive");
+    assert_eq!(imported.seed, Some(7));
+    assert_eq!(imported.parameters_initial, bundle.parameters_initial);
+}
+
+#[test]
+fn test_14_artifact_remote_store_replay_elsewhere() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let store = DirectoryStore::new(temp_dir.path().join("bucket"));
+    let bundle = BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+        .with_initial_parameters(create_test_params())
+        .with_seed(42)
+        .with_results(serde_json::json!({"measurements": [0, 0, 1]}))
+        .build()
+        .expect("Should create bundle");
+
+    let key = save_artifact_remote(&bundle, &temp_dir.path().join("lab"), &store)
+        .expect("Should upload bundle");
+    assert!(store.exists(&key).unwrap());
+    assert!(temp_dir
+        .path()
+        .join("lab")
+        .join(&bundle.artifact_id)
+        .exists());
+
+    // Another machine: only the store and the artifact id are shared
+    let cache = temp_dir.path().join("replay-cache");
+    let replay = load_remote_artifact_for_replay(&bundle.artifact_id, &store, &cache)
+        .expect("Should fetch bundle for replay");
+    assert_eq!(replay.seed, Some(42));
+    assert_eq!(replay.ir.nodes.len(), bundle.ir_original.nodes.len());
+    assert!(load_remote_artifact_for_replay("awen_missing", &store, &cache).is_err());
+    assert!(store.put("../escape", b"x").is_err());
+}
+
+#[test]
+fn test_15_artifact_signature_verification() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let bundle = BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+        .with_initial_parameters(create_test_params())
+        .with_results(serde_json::json!({"fidelity": 0.97}))
+        .build()
+        .expect("Should create bundle");
+    let signer = BundleSigner::generate();
+    let trusted = vec![signer.public_key_base64()];
+
+    let dir = export_signed_bundle(&bundle, temp_dir.path(), ExportFormat::Directory, &signer)
+        .expect("Should export signed bundle");
+    assert!(dir.join("signature.json").exists());
+    import_trusted_bundle(&dir, &trusted).expect("Signed by a trusted key");
+    let other = vec![BundleSigner::generate().public_key_base64()];
+    assert!(import_trusted_bundle(&dir, &other).is_err());
+
+    let archive = export_signed_bundle(
+        &bundle,
+        &temp_dir.path().join("release"),
+        ExportFormat::Archive,
+        &signer,
+    )
+    .expect("Should export signed archive");
+    import_trusted_bundle(&archive, &trusted).expect("Archive signature verifies");
+
+    // Rewriting a result and its checksum still breaks the signature
+    let outputs = r#"{"fidelity": 0.99}"#;
+    std::fs::write(dir.join("results/outputs.json"), outputs).unwrap();
+    let checksums_path = dir.join("checksums.json");
+    let mut checksums: HashMap<String, String> =
+        serde_json::from_str(&std::fs::read_to_string(&checksums_path).unwrap()).unwrap();
+    use sha2::Digest;
+    checksums.insert(
+        "results/outputs.json".to_string(),
+        hex::encode(sha2::Sha256::digest(outputs.as_bytes())),
+    );
+    std::fs::write(&checksums_path, serde_json::to_string(&checksums).unwrap()).unwrap();
+    let err = import_bundle(&dir).unwrap_err();
+    assert!(err.to_string().contains("signature verification failed"));
+
+    // Re-exporting unsigned drops the stale signature; trusted import then requires one
+    let dir = export_bundle(&bundle, temp_dir.path(), ExportFormat::Directory).unwrap();
+    assert!(!dir.join("signature.json").exists());
+    import_bundle(&dir).expect("Unsigned bundles still import");
+    assert!(import_trusted_bundle(&dir, &trusted).is_err());
+}
+
+#[test]
+fn test_16_artifact_phase_2_3_run_dir_replays() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let run_dir = temp_dir.path().join("awen_run_legacy");
+    std::fs::create_dir_all(&run_dir).unwrap();
+    let ir = create_test_graph();
+    std::fs::write(run_dir.join("ir.json"), serde_json::to_string(&ir).unwrap()).unwrap();
+    std::fs::write(run_dir.join("results.json"), r#"{"outputs": [0.5]}"#).unwrap();
+    std::fs::write(run_dir.join("quantum_states.json"), r#"[{"seed": 1234}]"#).unwrap();
+
+    let replay = load_artifact_for_replay(&run_dir).expect("Phase 2.3 run dir should replay");
+    assert_eq!(replay.seed, Some(1234));
+    assert_eq!(replay.ir.nodes[0].id, "x0");
+    // Without checksums, only an explicit lift imports it
+    assert!(import_bundle(&run_dir).is_err());
+    let bundle = import_legacy_run_dir(&run_dir).unwrap();
+    assert_eq!(bundle.artifact_id, "awen_run_legacy");
+    assert_eq!(bundle.manifest.schema_version, "awen_artifact.v0.2");
+
+    // A manifest from a newer runtime is rejected explicitly
+    let future = temp_dir.path().join("future");
+    std::fs::create_dir_all(&future).unwrap();
+    std::fs::write(
+        future.join("manifest.json"),
+        r#"{"schema_version": "awen_artifact.v2.0"}"#,
+    )
+    .unwrap();
+    let err = import_bundle(&future).unwrap_err().to_string();
+    assert!(err.contains("Unsupported bundle schema version"), "{}", err);
+}
+
+#[test]
+fn test_17_artifact_state_history_streams_as_jsonl() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let run_dir = temp_dir.path().join("run");
+    std::fs::create_dir_all(&run_dir).unwrap();
+    let states: Vec<serde_json::Value> = (0..64)
+        .map(|step| serde_json::json!({"node": format!("n{}", step), "amplitudes": vec![0.5; 64]}))
+        .collect();
+    std::fs::write(
+        run_dir.join("quantum_states.json"),
+        serde_json::to_string(&states).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(run_dir.join("traces.jsonl"), "{\"id\":\"s0\"}\n").unwrap();
+    let bundle = BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+        .with_initial_parameters(create_test_params())
+        .with_results(serde_json::json!({"measurements": [0, 1]}))
+        .with_observability_dir(&run_dir)
+        .build()
+        .expect("Should create bundle");
+
+    let options = ExportOptions {
+        state_history: StateHistoryFormat::Jsonl,
+        ..Default::default()
+    };
+    let dir = export_bundle_with(
+        &bundle,
+        temp_dir.path(),
+        ExportFormat::CompressedDirectory,
+        &options,
+    )
+    .expect("Should export with JSONL state history");
+    assert!(dir.join("provenance/quantum_states.jsonl.zst").exists());
+    assert!(!dir.join("provenance/quantum_states.json.zst").exists());
+
+    let imported = import_bundle(&dir).expect("Should import");
+    let stored = imported.observability.clone().unwrap().states.unwrap();
+    let mut reader = StateHistoryReader::open(&stored).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap(), states[0]);
+    assert_eq!(reader.count(), states.len() - 1);
+
+    // Re-exporting the imported bundle keeps the history as JSONL, decompressed
+    let reexported = export_bundle(
+        &imported,
+        &temp_dir.path().join("again"),
+        ExportFormat::Directory,
+    )
+    .expect("Should re-export");
+    let jsonl =
+        std::fs::read_to_string(reexported.join("provenance/quantum_states.jsonl")).unwrap();
+    assert_eq!(jsonl.lines().count(), states.len());
+}
+
+#[test]
+fn test_18_artifact_provenance_graph_lineage() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let artifacts = temp_dir.path();
+    let calibration = |id: &str, parent: Option<&str>| serde_json::json!({"calibration_id": id, "provenance": {"parent_calibration_id": parent}});
+    let build = |seed: u64| {
+        BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+            .with_initial_parameters(create_test_params())
+            .with_results(serde_json::json!({"fidelity": 0.9}))
+            .with_seed(seed)
+    };
+
+    // A calibration run, then a sweep of two runs using its output, then a replay of one
+    let calib_run = build(1)
+        .with_calibration_state(
+            calibration("calib-001", None),
+            Some(calibration("calib-002", Some("calib-001"))),
+        )
+        .build()
+        .unwrap();
+    let sweep: Vec<_> = (2..4)
+        .map(|seed| {
+            build(seed)
+                .with_calibration_state(calibration("calib-002", Some("calib-001")), None)
+                .add_parent_artifact(calib_run.artifact_id.clone())
+                .with_sweep_group("phase-sweep")
+                .build()
+                .unwrap()
+        })
+        .collect();
+    let replay = BundleBuilder::new(create_test_graph(), ArtifactType::Replay)
+        .with_initial_parameters(create_test_params())
+        .with_results(serde_json::json!({"fidelity": 0.9}))
+        .with_seed(2)
+        .add_parent_artifact(sweep[0].artifact_id.clone())
+        .build()
+        .unwrap();
+    for bundle in sweep.iter().chain([&calib_run, &replay]) {
+        export_bundle(bundle, artifacts, ExportFormat::Directory).unwrap();
+    }
+
+    let graph = ProvenanceGraph::from_artifacts_dir(artifacts).expect("Should build graph");
+    let ancestors: Vec<&str> = graph
+        .ancestors(&replay.artifact_id)
+        .iter()
+        .map(|n| n.id.as_str())
+        .collect();
+    assert_eq!(ancestors[0], sweep[0].artifact_id);
+    for id in [
+        calib_run.artifact_id.as_str(),
+        "calib-001",
+        "calib-002",
+        "phase-sweep",
+    ] {
+        assert!(
+            ancestors.contains(&id),
+            "{} missing from {:?}",
+            id,
+            ancestors
+        );
+    }
+    assert!(graph.edges.contains(&ProvenanceEdge {
+        from: replay.artifact_id.clone(),
+        to: sweep[0].artifact_id.clone(),
+        relationship: Relationship::ReplayOf,
+    }));
+    assert!(graph.edges.contains(&ProvenanceEdge {
+        from: "calib-002".to_string(),
+        to: calib_run.artifact_id.clone(),
+        relationship: Relationship::ProducedBy,
+    }));
+
+    let members: Vec<&str> = graph
+        .children("phase-sweep")
+        .map(|e| e.from.as_str())
+        .collect();
+    assert_eq!(members.len(), 2);
+    assert_eq!(graph.descendants(&calib_run.artifact_id).len(), 4);
+
+    let lineage = graph.lineage(&sweep[1].artifact_id).unwrap();
+    assert!(!lineage.nodes.contains_key(&replay.artifact_id));
+    assert!(lineage.to_dot().contains("[label=\"used_calibration\"]"));
+    assert!(graph.lineage("awen_unknown").is_err());
+}
+
+#[test]
+fn test_19_artifact_encrypted_archive_round_trip() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let bundle = BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+        .with_initial_parameters(create_test_params())
+        .with_results(serde_json::json!({"fidelity": 0.97}))
+        .with_seed(7)
+        .build()
+        .expect("Should create bundle");
+    let keys = StaticKeyProvider::new("nda-2026", generate_key());
+    let options = ExportOptions {
+        encryption: Some(&keys),
+        ..Default::default()
+    };
+
+    // Only archives can be encrypted
+    assert!(
+        export_bundle_with(&bundle, temp_dir.path(), ExportFormat::Directory, &options).is_err()
+    );
+
+    let archive = export_bundle_with(&bundle, temp_dir.path(), ExportFormat::Archive, &options)
+        .expect("Should export encrypted archive");
+    assert!(archive.to_string_lossy().ends_with(".tar.zst.enc"));
+    let stored = std::fs::read(&archive).unwrap();
+    assert!(!String::from_utf8_lossy(&stored).contains(&bundle.artifact_id));
+    assert!(read_archive_manifest(&archive).is_err());
+
+    let wrong = StaticKeyProvider::new("nda-2026", generate_key());
+    assert!(import_encrypted_bundle(&archive, &wrong).is_err());
+    let imported = import_encrypted_bundle(&archive, &keys).expect("Should decrypt and import");
+    assert_eq!(imported.artifact_id, bundle.artifact_id);
+    assert_eq!(imported.results, bundle.results);
+    assert_eq!(imported.seed, Some(7));
+}
+
+#[test]
+fn test_20_artifact_sweep_shares_ir_and_environment() {
+    let temp_dir = TempDir::new().expect("Should create temp dir");
+    let bundles: Vec<_> = (0..3)
+        .map(|point| {
+            let mut params = create_test_params();
+            params.insert("mzi_0:phase".to_string(), point as f64 * 0.1);
+            BundleBuilder::new(create_test_graph(), ArtifactType::Run)
+                .with_initial_parameters(params)
+                .with_results(serde_json::json!({"point": point}))
+                .with_sweep_group("phase-scan")
+                .build()
+                .expect("Should create bundle")
+        })
+        .collect();
+
+    let out = temp_dir.path();
+    let paths = export_sweep(
+        &bundles,
+        out,
+        ExportFormat::Directory,
+        &ExportOptions::default(),
+    )
+    .expect("Should export sweep");
+    assert_eq!(paths.len(), 3);
+    assert!(paths[0].join("ir/original.json").exists());
+    for member in &paths[1..] {
+        assert!(!member.join("ir/original.json").exists());
+        assert!(!member.join("environment/snapshot.json").exists());
+        let imported = import_bundle(member).expect("Should resolve shared files");
+        assert_eq!(imported.manifest.references.len(), 2);
+        assert_eq!(
+            imported.ir_original.nodes.len(),
+            bundles[0].ir_original.nodes.len()
+        );
+    }
+
+    // Members of an archive sweep reach the parent once unpacked beside it
+    let archives = temp_dir.path().join("archives");
+    let paths = export_sweep(
+        &bundles,
+        &archives,
+        ExportFormat::Archive,
+        &ExportOptions::default(),
+    )
+    .expect("Should export archive sweep");
+    assert!(paths[0].is_dir());
+    assert!(import_bundle(&paths[2]).is_ok());
+
+    // A changed parent file no longer matches the member's reference
+    std::fs::write(paths[0].join("ir/original.json"), "{}").unwrap();
+    let err = import_bundle(&paths[2]).unwrap_err().to_string();
+    assert!(err.contains("does not match"), "{}", err);
+}
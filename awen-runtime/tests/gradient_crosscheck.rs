@@ -0,0 +1,111 @@
+//! Every registered gradient provider is checked against central finite differences of its own
+//! cost on a set of reference graphs. A provider may reject a graph or parameter it does not
+//! support, but it must pass on at least one case and never return a wrong gradient.
+
+use awen_runtime::gradients::{
+    check, register_default_providers, CostFiniteDifference, GradientRegistry,
+};
+use awen_runtime::ir::Graph;
+use serde_json::json;
+
+const TOLERANCE: f64 = 1e-4;
+
+fn reference_cases() -> Vec<(&'static str, Graph, Vec<String>)> {
+    let params = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let mzi_chain: Graph =
+        serde_json::from_str(&std::fs::read_to_string("example_ir.json").unwrap()).unwrap();
+    let lossy: Graph = serde_json::from_value(json!({
+        "nodes": [
+            {"id": "mzi_0", "type": "mzi", "params": {"phase": 0.3}},
+            {"id": "loss_0", "type": "loss", "params": {"loss": 0.2}},
+            {"id": "mzi_1", "type": "mzi", "params": {"phase": 0.5}}
+        ],
+        "metadata": {"input_amplitude": "0.8"}
+    }))
+    .unwrap();
+    let gaussian: Graph = serde_json::from_value(json!({
+        "nodes": [
+            {"id": "sq", "type": "SQUEEZING", "params": {"mode_id": 0.0, "r": 0.3, "angle": 0.2}},
+            {"id": "d1", "type": "DISPLACEMENT", "params": {"mode_id": 1.0, "q": 1.2, "p": 0.4}},
+            {"id": "bs", "type": "BS", "params": {"mode1": 0.0, "mode2": 1.0, "theta": 0.7, "phi": 0.3}},
+            {"id": "ps", "type": "PS", "params": {"mode_id": 0.0, "phase": 0.5}}
+        ],
+        "metadata": {"cost_mode": "0"}
+    }))
+    .unwrap();
+
+    vec![
+        (
+            "mzi_chain",
+            mzi_chain,
+            params(&["mzi_0:phase", "mzi_1:phase"]),
+        ),
+        (
+            "lossy_phase",
+            lossy.clone(),
+            params(&["mzi_0:phase", "mzi_1:phase"]),
+        ),
+        ("lossy_loss", lossy, params(&["mzi_0:phase", "loss_0:loss"])),
+        (
+            "gaussian",
+            gaussian,
+            params(&["sq:r", "sq:angle", "d1:q", "bs:theta", "bs:phi", "ps:phase"]),
+        ),
+    ]
+}
+
+#[test]
+fn every_provider_matches_finite_differences_of_its_cost() {
+    let registry = GradientRegistry::new();
+    register_default_providers(&registry);
+    let names = registry.names();
+    assert!(names.len() >= 6, "providers: {:?}", names);
+
+    for name in &names {
+        let provider = registry.get(name).unwrap();
+        let reference = CostFiniteDifference::new(provider.clone());
+        let mut passed = Vec::new();
+        for (case, graph, params) in reference_cases() {
+            let Ok(result) = check(provider.as_ref(), &reference, &graph, &params, TOLERANCE)
+            else {
+                continue;
+            };
+            assert!(
+                result.passed(),
+                "{} on {}: {:?}",
+                name,
+                case,
+                result.discrepancies
+            );
+            passed.push(case);
+        }
+        assert!(!passed.is_empty(), "{} supports no reference case", name);
+    }
+}
+
+#[test]
+fn check_reports_discrepancies() {
+    let registry = GradientRegistry::new();
+    register_default_providers(&registry);
+    let (_, graph, params) = reference_cases().remove(3);
+
+    // The reference simulator ignores CV gates, so its gradients miss the Gaussian ones
+    let result = check(
+        registry.get("reference-fd").unwrap().as_ref(),
+        registry.get("gaussian-adjoint").unwrap().as_ref(),
+        &graph,
+        &params,
+        TOLERANCE,
+    )
+    .unwrap();
+    assert!(!result.passed());
+    let flagged: Vec<&str> = result
+        .discrepancies
+        .iter()
+        .map(|d| d.param.as_str())
+        .collect();
+    assert!(flagged.contains(&"d1:q"), "{:?}", flagged);
+    for d in &result.discrepancies {
+        assert!(d.difference > TOLERANCE);
+    }
+}
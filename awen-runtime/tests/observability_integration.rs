@@ -2,7 +2,9 @@
 //!
 //! Tests end-to-end observability artifact generation from runtime execution.
 
-use awen_runtime::observability::{Level, ObservabilityContext};
+use awen_runtime::observability::{
+    ErrorCode, EventRecord, Level, ObservabilityContext, RetentionPolicy, Span, TracerHandle,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -391,3 +393,512 @@ fn test_timeline_lanes() {
     assert!(lane_names.contains(&"Control"));
     assert!(lane_names.contains(&"Storage"));
 }
+
+#[test]
+fn test_chrome_trace_export_lanes() {
+    use awen_runtime::observability::{
+        export_chrome_trace, write_timeline, write_traces, Span, TimelineEvent,
+    };
+    use std::collections::HashMap;
+
+    let span = |id: &str, parent: Option<&str>, start: &str, end: &str| Span {
+        id: id.to_string(),
+        parent: parent.map(str::to_string),
+        name: id.to_string(),
+        start_iso: start.to_string(),
+        end_iso: end.to_string(),
+        attributes: HashMap::from([("subsystem".to_string(), "engine".to_string())]),
+    };
+    let event = |lane: &str, name: &str, start_ms: u128| TimelineEvent {
+        lane: lane.to_string(),
+        name: name.to_string(),
+        start_ms,
+        end_ms: start_ms + 2,
+        attributes: HashMap::new(),
+    };
+    let temp_dir = TempDir::new().unwrap();
+    write_traces(
+        temp_dir.path(),
+        &[
+            span("run", None, "2026-01-01T00:00:00Z", "2026-01-01T00:00:01Z"),
+            span(
+                "compile",
+                Some("run"),
+                "2026-01-01T00:00:00.100Z",
+                "2026-01-01T00:00:00.300Z",
+            ),
+            span(
+                "calibrate",
+                None,
+                "2026-01-01T00:00:02Z",
+                "2026-01-01T00:00:03Z",
+            ),
+        ],
+    )
+    .unwrap();
+    write_timeline(
+        temp_dir.path(),
+        &[
+            event("kernel", "exec:a", 10),
+            event("HAL.Channel.1", "apply", 11),
+            event("kernel", "exec:b", 12),
+        ],
+    )
+    .unwrap();
+
+    let path = export_chrome_trace(temp_dir.path()).unwrap();
+    let trace: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+    let complete = |name: &str| {
+        events
+            .iter()
+            .find(|e| e["ph"] == "X" && e["name"] == name)
+            .unwrap_or_else(|| panic!("no event {}", name))
+    };
+
+    // Children share their root span's lane; separate roots get their own.
+    let (run, compile, calibrate) = (complete("run"), complete("compile"), complete("calibrate"));
+    assert_eq!(run["pid"], compile["pid"]);
+    assert_eq!(run["tid"], compile["tid"]);
+    assert_ne!(run["tid"], calibrate["tid"]);
+    assert_eq!(compile["dur"], 200_000);
+    assert_eq!(compile["args"]["parent"], "run");
+    assert_eq!(compile["args"]["subsystem"], "engine");
+
+    // Timeline lanes become named threads of their own process, in microseconds.
+    let (a, b, hal) = (complete("exec:a"), complete("exec:b"), complete("apply"));
+    assert_ne!(a["pid"], run["pid"]);
+    assert_eq!(a["tid"], b["tid"]);
+    assert_ne!(a["tid"], hal["tid"]);
+    assert_eq!(a["ts"], 10_000);
+    assert_eq!(a["dur"], 2_000);
+    assert!(events.iter().any(|e| e["ph"] == "M"
+        && e["name"] == "thread_name"
+        && e["tid"] == hal["tid"]
+        && e["args"]["name"] == "HAL.Channel.1"));
+}
+
+#[test]
+fn test_event_stream_pushes_sse_events() {
+    use awen_runtime::observability::{EventStreamer, MetricsSink, StreamMessage};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::time::{Duration, Instant};
+
+    let streamer = EventStreamer::bind("127.0.0.1:0").unwrap();
+    // Publishing before anyone listens is a no-op.
+    streamer.record_gauge("engine.queue_depth", 1.0);
+
+    let mut client = TcpStream::connect(streamer.local_addr()).unwrap();
+    client
+        .write_all(b"GET / HTTP/1.1\r\nAccept: text/event-stream\r\n\r\n")
+        .unwrap();
+    client
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while streamer.client_count() == 0 {
+        assert!(Instant::now() < deadline, "client never registered");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    streamer.record_counter("engine.operations_executed", 3.0);
+    streamer.publish(&StreamMessage::Measurement {
+        run_id: "run-1".to_string(),
+        node_id: "det0".to_string(),
+        outcome: serde_json::json!({ "photon_count": 2 }),
+    });
+
+    let mut reader = BufReader::new(client);
+    let mut lines = Vec::new();
+    while lines
+        .iter()
+        .filter(|l: &&String| l.starts_with("data: "))
+        .count()
+        < 2
+    {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        lines.push(line.trim_end().to_string());
+    }
+    assert!(lines[0].starts_with("HTTP/1.1 200"));
+    assert!(lines.contains(&"Content-Type: text/event-stream".to_string()));
+
+    let events: Vec<&String> = lines.iter().filter(|l| l.starts_with("event: ")).collect();
+    assert_eq!(events, ["event: metric", "event: measurement"]);
+    let data: Vec<serde_json::Value> = lines
+        .iter()
+        .filter_map(|l| l.strip_prefix("data: "))
+        .map(|d| serde_json::from_str(d).unwrap())
+        .collect();
+    assert_eq!(data[0]["type"], "metric");
+    assert_eq!(data[0]["key"], "engine.operations_executed");
+    assert_eq!(data[0]["kind"], "counter");
+    assert_eq!(data[1]["outcome"]["photon_count"], 2);
+}
+
+#[test]
+fn test_html_report_sections() {
+    use awen_runtime::observability::{
+        render_report, write_metrics, write_timeline, Metrics, TimelineEvent,
+    };
+    use std::collections::HashMap;
+
+    let temp_dir = TempDir::new().unwrap();
+    write_timeline(
+        temp_dir.path(),
+        &[
+            TimelineEvent {
+                lane: "kernel".to_string(),
+                name: "exec:<mzi>".to_string(),
+                start_ms: 100,
+                end_ms: 104,
+                attributes: HashMap::new(),
+            },
+            TimelineEvent {
+                lane: "HAL.Channel.1".to_string(),
+                name: "apply_calibration".to_string(),
+                start_ms: 102,
+                end_ms: 103,
+                attributes: HashMap::from([("device_id".to_string(), "sim-0".to_string())]),
+            },
+        ],
+    )
+    .unwrap();
+    write_metrics(
+        temp_dir.path(),
+        &Metrics {
+            counters: HashMap::from([("nodes_executed".to_string(), 3.0)]),
+            gauges: HashMap::from([("hal.apply_calibration.p99".to_string(), 1.5)]),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("measurements.json"),
+        r#"{"det0": {"photon_count": 1, "probability": 0.5, "kind": "PhotonCount"},
+            "det1": {"photon_count": 1, "probability": 0.5, "kind": "PhotonCount"}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("shots.json"),
+        r#"{"labels": ["n"], "shots": [[{"DiscreteOutcome": 0}], [{"DiscreteOutcome": 2}]]}"#,
+    )
+    .unwrap();
+
+    let path = render_report(temp_dir.path()).unwrap();
+    assert_eq!(path, temp_dir.path().join("report.html"));
+    let html = std::fs::read_to_string(path).unwrap();
+
+    // One Gantt row per lane, with escaped event names.
+    assert!(html.contains("data-lane=\"kernel\""));
+    assert!(html.contains("data-lane=\"HAL.Channel.1\""));
+    assert!(html.contains("exec:&lt;mzi&gt;"));
+    assert!(!html.contains("exec:<mzi>"));
+    assert!(html.contains("<td>nodes_executed</td><td>3</td>"));
+    assert!(html.contains("<td>det0</td>"));
+    assert!(html.contains("Shots (2)"));
+    assert!(html.contains("device_id=sim-0"));
+    assert!(html.contains("HAL latency"));
+    // Self-contained: no external scripts or stylesheets.
+    assert!(!html.contains("src=\"http"));
+    assert!(!html.contains("<link"));
+}
+
+#[test]
+fn test_timeline_sampling_records_dropped_events() {
+    use awen_runtime::observability::{SamplingConfig, SamplingPolicy};
+    use std::collections::HashMap;
+    use std::fs;
+
+    let ctx = ObservabilityContext::new().with_sampling(
+        SamplingConfig::new()
+            .with_lane(
+                "HAL.Channel.*",
+                SamplingPolicy::RateCap {
+                    max_events: 10,
+                    window_ms: 1,
+                },
+            )
+            .with_lane("Control", SamplingPolicy::Head { limit: 1 }),
+    );
+    // A 1 MHz loop for 5 ms: 1000 events per millisecond on one channel.
+    for i in 0..5_000u128 {
+        ctx.timeline.add_entry(
+            "HAL.Channel.0",
+            "feedback",
+            i / 1_000,
+            i / 1_000,
+            HashMap::new(),
+        );
+    }
+    for i in 0..3 {
+        ctx.timeline
+            .add_entry("Control", "update", i, i + 1, HashMap::new());
+    }
+    ctx.timeline
+        .add_entry("Engine", "run", 0, 5, HashMap::new());
+
+    let entries = ctx.timeline.build().entries;
+    assert_eq!(entries.len(), 5 * 10 + 1 + 1);
+    assert_eq!(ctx.timeline.dropped()["HAL.Channel.0"], 4_950);
+    assert_eq!(ctx.timeline.dropped()["Control"], 2);
+
+    let temp_dir = TempDir::new().unwrap();
+    let artifacts = ctx.export(temp_dir.path()).unwrap();
+    let metrics: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(artifacts.metrics).unwrap()).unwrap();
+    let dropped = metrics
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["name"] == "timeline.dropped_events" && m["attributes"]["lane"] == "Control")
+        .expect("dropped counter");
+    assert_eq!(dropped["value"], 2.0);
+    let metadata: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(artifacts.metadata).unwrap()).unwrap();
+    assert_eq!(metadata["dropped_events"]["HAL.Channel.0"], 4_950);
+}
+
+#[test]
+fn test_run_comparison_flags_regressions() {
+    use awen_runtime::observability::{compare, write_metrics, write_traces, Metrics, Span};
+    use std::collections::HashMap;
+
+    let span = |name: &str, end: &str| Span {
+        id: format!("id-{}", name),
+        parent: None,
+        name: name.to_string(),
+        start_iso: "2026-01-01T00:00:00Z".to_string(),
+        end_iso: end.to_string(),
+        attributes: HashMap::new(),
+    };
+    let write_run = |spans: &[Span], nodes: f64, photon_counts: &[u64], shots: &str| {
+        let dir = TempDir::new().unwrap();
+        write_traces(dir.path(), spans).unwrap();
+        write_metrics(
+            dir.path(),
+            &Metrics {
+                counters: HashMap::from([("nodes_executed".to_string(), nodes)]),
+                gauges: HashMap::from([("seed_used".to_string(), 42.0)]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let measurements: HashMap<String, serde_json::Value> = photon_counts
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                (
+                    format!("det{}", i),
+                    serde_json::json!({ "photon_count": n }),
+                )
+            })
+            .collect();
+        std::fs::write(
+            dir.path().join("measurements.json"),
+            serde_json::to_string(&measurements).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("shots.json"), shots).unwrap();
+        dir
+    };
+    let shots = |values: &[f64]| {
+        let rows: Vec<serde_json::Value> = values
+            .iter()
+            .map(|x| serde_json::json!([{ "ContinuousValue": x }]))
+            .collect();
+        serde_json::json!({ "labels": ["x"], "shots": rows }).to_string()
+    };
+
+    let a = write_run(
+        &[
+            span("run", "2026-01-01T00:00:00.100Z"),
+            span("compile", "2026-01-01T00:00:00.010Z"),
+        ],
+        3.0,
+        &[0, 1, 1, 0],
+        &shots(&[-1.0, 0.0, 1.0]),
+    );
+    let same = write_run(
+        &[
+            span("run", "2026-01-01T00:00:00.100500Z"),
+            span("compile", "2026-01-01T00:00:00.010Z"),
+        ],
+        3.0,
+        &[1, 0, 0, 1],
+        &shots(&[-1.0, 0.0, 1.0]),
+    );
+    let diff = compare(a.path(), same.path()).unwrap();
+    // Sub-millisecond jitter and a permuted photon-count distribution are not regressions.
+    assert!(!diff.has_regressions(), "{}", diff.to_text());
+    assert!(diff.metric_deltas.is_empty());
+
+    let slower = write_run(
+        &[
+            span("run", "2026-01-01T00:00:00.200Z"),
+            span("calibrate", "2026-01-01T00:00:00.010Z"),
+        ],
+        4.0,
+        &[2, 2, 2, 2],
+        &shots(&[2.0, 3.0, 4.0]),
+    );
+    let diff = compare(a.path(), slower.path()).unwrap();
+    assert!(diff.has_regressions());
+    assert_eq!(diff.timing_regressions.len(), 1);
+    assert_eq!(diff.timing_regressions[0].name, "run");
+    assert!((diff.timing_regressions[0].ratio - 2.0).abs() < 1e-9);
+    assert_eq!(diff.added_spans, ["calibrate"]);
+    assert_eq!(diff.removed_spans, ["compile"]);
+    assert_eq!(diff.metric_deltas.len(), 1);
+    assert_eq!(diff.metric_deltas[0].key, "nodes_executed");
+    assert_eq!(diff.metric_deltas[0].delta, Some(1.0));
+    let photons = &diff.distribution_shifts[0];
+    assert_eq!(photons.distribution, "measurements.photon_count");
+    assert!((photons.value - 1.0).abs() < 1e-9);
+    let x = &diff.distribution_shifts[1];
+    assert_eq!(x.statistic, "mean_shift_sigma");
+    assert!(x.shifted);
+
+    // Text and JSON reports
+    let out = TempDir::new().unwrap();
+    let (json, text) = diff.write_report(out.path()).unwrap();
+    let parsed: awen_runtime::observability::RunDiff =
+        serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+    assert_eq!(parsed, diff);
+    let text = std::fs::read_to_string(text).unwrap();
+    assert!(text.contains("verdict: REGRESSION"));
+    assert!(text.contains("+ calibrate"));
+}
+
+#[test]
+fn test_log_filter_drops_events_below_level() {
+    use awen_runtime::observability::LogFilter;
+
+    let filter = LogFilter::parse("warn, hal=debug, hal.interlock=trace").unwrap();
+    assert_eq!(filter.level_for("engine"), Level::Warning);
+    assert_eq!(filter.level_for("hal.health"), Level::Debug);
+    assert_eq!(filter.level_for("hal.interlock"), Level::Trace);
+    // Prefixes only match whole path segments.
+    assert_eq!(filter.level_for("halo"), Level::Warning);
+    assert!(LogFilter::parse("hal=loud").is_err());
+    assert_eq!(LogFilter::parse("").unwrap(), LogFilter::default());
+
+    let ctx = ObservabilityContext::new().with_log_filter(filter);
+    ctx.events
+        .info("engine", "node executed", Default::default());
+    ctx.events
+        .error("engine", "node failed", Default::default());
+    ctx.events.trace("hal.health", "poll", Default::default());
+    ctx.events
+        .debug("hal.health", "sensor read", Default::default());
+    ctx.events
+        .trace("hal.interlock", "armed", Default::default());
+
+    let kept: Vec<String> = ctx
+        .events
+        .events()
+        .iter()
+        .map(|e| e.message.clone())
+        .collect();
+    assert_eq!(kept, ["node failed", "sensor read", "armed"]);
+}
+
+#[test]
+fn test_error_events_carry_stable_codes() {
+    let ctx = ObservabilityContext::new().with_log_filter(Default::default());
+    ctx.events.error_with_code(
+        ErrorCode::IrValidation,
+        "engine",
+        "graph rejected",
+        Default::default(),
+    );
+    ctx.events.error(
+        "engine",
+        "coherence window exceeded at node n3",
+        Default::default(),
+    );
+    ctx.events
+        .fatal("engine", "unexpected shutdown", Default::default());
+    ctx.events
+        .warning("hal", "safety limit approached", Default::default());
+
+    let temp_dir = TempDir::new().unwrap();
+    let artifacts = ctx.export(temp_dir.path()).unwrap();
+    let records: Vec<EventRecord> = std::fs::read_to_string(&artifacts.events)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    let codes: Vec<Option<ErrorCode>> = records.iter().map(|r| r.code).collect();
+    assert_eq!(
+        codes,
+        [
+            Some(ErrorCode::IrValidation),
+            Some(ErrorCode::CoherenceViolation),
+            None,
+            None
+        ]
+    );
+    assert_eq!(records[0].attributes["error_code"], "AWEN-IR-001");
+    let raw = std::fs::read_to_string(&artifacts.events).unwrap();
+    assert!(raw
+        .lines()
+        .next()
+        .unwrap()
+        .contains("\"code\":\"AWEN-IR-001\""));
+}
+
+#[test]
+fn test_tracer_retention_bounds_memory_and_rotates_segments() {
+    let temp_dir = TempDir::new().unwrap();
+    let tracer = TracerHandle::with_retention(
+        RetentionPolicy::default()
+            .with_max_spans(10)
+            .with_segments(temp_dir.path().join("segments"), 25),
+    );
+    // A daemon loop: one long-lived root span, many short child spans.
+    let mut daemon = tracer.start_span("monitor");
+    let ctx = daemon.context();
+    for i in 0..100 {
+        let mut tick = ctx.start_span("tick");
+        tick.set_attribute("i", &i.to_string());
+        tick.end();
+    }
+
+    let held = tracer.spans();
+    assert!(held.len() <= 10);
+    // Open spans are never evicted.
+    assert_eq!(held[0].name, "monitor");
+    assert_eq!(held.last().unwrap().attributes["i"], "99");
+    assert_eq!(tracer.evicted(), 101 - held.len() as u64);
+
+    daemon.end();
+    tracer.flush().unwrap();
+    assert!(tracer.spans().is_empty());
+    let segments = tracer.segments();
+    assert_eq!(segments.len(), 5);
+    let flushed: Vec<Span> = segments
+        .iter()
+        .flat_map(|path| {
+            std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<Span>(line).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(flushed.len(), 101);
+    assert!(flushed.iter().any(|s| s.name == "monitor"));
+
+    // Without a segment directory evicted spans are discarded, and flush has nowhere to go.
+    let bounded = TracerHandle::with_retention(RetentionPolicy::default().with_max_spans(3));
+    for _ in 0..20 {
+        bounded.start_span("tick").end();
+    }
+    assert_eq!(bounded.spans().len(), 3);
+    assert_eq!(bounded.evicted(), 17);
+    assert!(bounded.flush().is_err());
+}
@@ -1,4 +1,10 @@
-use awen_runtime::chokepoint::{ExecContext, NonBypassableGateway, PhotonicOp};
+use awen_runtime::chokepoint::{
+    AccessControl, AccessError, ExecContext, NonBypassableGateway, PhotonicOp, Policy, PolicyRule,
+    QuotaResource, QuotaRule, QuotaScope, RateLimiter, Role, RuntimeAction,
+};
+use awen_runtime::namespace::Namespace;
+use awen_runtime::plugins::registry::PluginManifest;
+use awen_runtime::plugins::{ExecutionTarget, PluginPermission};
 use awen_runtime::ExecutionChokepoint;
 use serde_json::json;
 
@@ -15,6 +21,9 @@ fn chokepoint_accepts_valid_op() {
     let ctx = ExecContext {
         run_id: "run1".into(),
         timestamp_ns: 12345,
+        caller: None,
+        namespace: None,
+        api_token: None,
     };
 
     let res = gateway.execute(&op, &ctx);
@@ -34,6 +43,9 @@ fn gateway_accepts_valid_op() {
     let ctx = ExecContext {
         run_id: "run-1".into(),
         timestamp_ns: 1_700_000_000_000_000_000,
+        caller: None,
+        namespace: None,
+        api_token: None,
     };
 
     let res = gw.execute(&op, &ctx);
@@ -58,7 +70,248 @@ fn gateway_rejects_missing_op_id() {
     let ctx = ExecContext {
         run_id: "run-2".into(),
         timestamp_ns: 1,
+        caller: None,
+        namespace: None,
+        api_token: None,
     };
     let res = gw.execute(&op, &ctx);
     assert!(!res.ok, "gateway should reject ops missing op_id");
 }
+
+#[test]
+fn hardware_gateway_denies_plugins_without_permissions() {
+    let gw = NonBypassableGateway::new().with_target(ExecutionTarget::Hardware);
+    let op = PhotonicOp {
+        op_id: "op-hw".into(),
+        op_type: "classical:phase_shifter".into(),
+        targets: vec!["wg0".into()],
+        params: Some(json!({"phase": 0.5})),
+        calibration_handle: None,
+    };
+    let ctx = ExecContext {
+        run_id: "run-3".into(),
+        timestamp_ns: 2,
+        caller: None,
+        namespace: None,
+        api_token: None,
+    };
+    let mut plugin = PluginManifest {
+        id: "sim-plugin".into(),
+        version: "0.1".into(),
+        capabilities: vec!["simulate".into()],
+        signature: None,
+        public_key: None,
+        path: None,
+        endpoint: None,
+        permissions: vec![PluginPermission::Filesystem],
+    };
+
+    let res = gw.execute_for_plugin(&plugin, &op, &ctx);
+    assert!(!res.ok);
+    assert!(res.details.unwrap().contains("write-params"));
+
+    // Simulation never needs hardware permissions
+    let sim = NonBypassableGateway::new();
+    assert!(sim.execute_for_plugin(&plugin, &op, &ctx).ok);
+
+    plugin.permissions.push(PluginPermission::WriteParams);
+    let res = gw.execute_for_plugin(&plugin, &op, &ctx);
+    assert!(res.ok, "{:?}", res.details);
+}
+
+#[test]
+fn production_policy_denies_unlisted_hardware_ops() {
+    let policy = Policy::production().with_rule(
+        PolicyRule::allow("phases")
+            .on_devices("wg*")
+            .on_params("phase")
+            .within(Some(0.0), Some(6.3)),
+    );
+    let gw = NonBypassableGateway::new()
+        .with_target(ExecutionTarget::Hardware)
+        .with_policy(policy.clone());
+    let op = |params| PhotonicOp {
+        op_id: "op-policy".into(),
+        op_type: "classical:phase_shifter".into(),
+        targets: vec!["wg0".into()],
+        params: Some(params),
+        calibration_handle: None,
+    };
+    let ctx = ExecContext {
+        run_id: "run-4".into(),
+        timestamp_ns: 3,
+        caller: Some("lab".into()),
+        namespace: None,
+        api_token: None,
+    };
+
+    assert!(gw.execute(&op(json!({"phase": 0.5})), &ctx).ok);
+    let res = gw.execute(&op(json!({"phase": 9.0})), &ctx);
+    assert!(!res.ok);
+    assert!(res.details.unwrap().starts_with("policy denied"));
+    let res = gw.execute(&op(json!({"ratio": 0.5})), &ctx);
+    assert!(!res.ok);
+
+    // Simulation is not hardware-affecting
+    let sim = NonBypassableGateway::new().with_policy(policy);
+    assert!(sim.execute(&op(json!({"phase": 9.0})), &ctx).ok);
+}
+
+#[test]
+fn hardware_gateway_enforces_write_quota() {
+    let limiter = RateLimiter::new().with_quota(QuotaRule::new(
+        "writes-per-second",
+        QuotaResource::ParamWrites,
+        QuotaScope::Caller,
+        2.0,
+        1.0,
+    ));
+    let gw = NonBypassableGateway::new()
+        .with_target(ExecutionTarget::Hardware)
+        .with_rate_limiter(limiter);
+    let op = PhotonicOp {
+        op_id: "op-quota".into(),
+        op_type: "classical:phase_shifter".into(),
+        targets: vec!["wg0".into()],
+        params: Some(json!({"phase": 0.5})),
+        calibration_handle: None,
+    };
+    let ctx = |timestamp_ns| ExecContext {
+        run_id: "run-5".into(),
+        timestamp_ns,
+        caller: Some("lab".into()),
+        namespace: None,
+        api_token: None,
+    };
+
+    assert!(gw.execute(&op, &ctx(10)).ok);
+    assert!(gw.execute(&op, &ctx(10)).ok);
+    let res = gw.execute(&op, &ctx(10));
+    assert!(!res.ok);
+    assert!(res.details.unwrap().starts_with("quota exceeded"));
+    assert_eq!(gw.rate_limiter().events().events().len(), 1);
+    assert!(gw.execute(&op, &ctx(1_000_000_010)).ok);
+}
+
+#[test]
+fn gateway_enforces_roles_from_api_tokens() {
+    let access = AccessControl::new()
+        .with_token("obs", "dashboard", Role::Observer)
+        .with_token("op", "alice", Role::Operator)
+        .with_token("adm", "root", Role::Admin);
+    let hw = NonBypassableGateway::new()
+        .with_target(ExecutionTarget::Hardware)
+        .with_access_control(access.clone())
+        .with_policy(
+            Policy::production().with_rule(PolicyRule::allow("alice-only").for_callers("alice")),
+        );
+    let sim = NonBypassableGateway::new().with_access_control(access);
+    let op = |calibration_handle: Option<&str>| PhotonicOp {
+        op_id: "op-rbac".into(),
+        op_type: "classical:phase_shifter".into(),
+        targets: vec!["wg0".into()],
+        params: Some(json!({"phase": 0.5})),
+        calibration_handle: calibration_handle.map(str::to_string),
+    };
+    let ctx = |token: Option<&str>| ExecContext {
+        run_id: "run-6".into(),
+        timestamp_ns: 4,
+        // The policy sees the token's identity, not this claim
+        caller: Some("alice".into()),
+        namespace: None,
+        api_token: token.map(str::to_string),
+    };
+
+    assert!(!sim.execute(&op(None), &ctx(None)).ok);
+    assert!(sim.execute(&op(None), &ctx(Some("obs"))).ok);
+    let res = hw.execute(&op(None), &ctx(Some("obs")));
+    assert!(!res.ok);
+    assert!(res.details.unwrap().starts_with("access denied"));
+    assert!(hw.execute(&op(None), &ctx(Some("op"))).ok);
+    let res = hw.execute(&op(None), &ctx(Some("adm")));
+    assert!(res.details.unwrap().starts_with("policy denied"));
+
+    // Only admins approve calibrations, and hardware ops need approved ones
+    assert!(!hw.execute(&op(Some("cal-7")), &ctx(Some("op"))).ok);
+    assert!(hw.approve_calibration(&ctx(Some("op")), "cal-7").is_err());
+    hw.approve_calibration(&ctx(Some("adm")), "cal-7").unwrap();
+    assert!(hw.execute(&op(Some("cal-7")), &ctx(Some("op"))).ok);
+
+    // Only admins change safety limits
+    let locked = Policy::production();
+    assert!(hw.set_policy(&ctx(Some("op")), locked.clone()).is_err());
+    hw.set_policy(&ctx(Some("adm")), locked).unwrap();
+    assert!(!hw.execute(&op(None), &ctx(Some("op"))).ok);
+    assert!(hw
+        .authorize(&ctx(Some("obs")), RuntimeAction::ReadArtifacts)
+        .is_ok());
+
+    // The token never leaves the gateway
+    let serialized = serde_json::to_string(&ctx(Some("op"))).unwrap();
+    assert!(!serialized.contains("api_token"));
+}
+
+#[test]
+fn gateway_confines_namespaced_tokens() {
+    let optics = Namespace::new("optics").unwrap();
+    let access = AccessControl::new()
+        .with_namespace_token("bob", "bob", Role::Operator, &optics)
+        .with_namespace_token("carol", "carol", Role::Admin, &optics)
+        .with_token("adm", "root", Role::Admin);
+    let hw = NonBypassableGateway::new()
+        .with_target(ExecutionTarget::Hardware)
+        .with_access_control(access)
+        .with_policy(
+            Policy::production()
+                .with_rule(PolicyRule::allow("optics-only").in_namespaces("optics")),
+        );
+    let op = PhotonicOp {
+        op_id: "op-ns".into(),
+        op_type: "classical:phase_shifter".into(),
+        targets: vec!["wg0".into()],
+        params: Some(json!({"phase": 0.5})),
+        calibration_handle: None,
+    };
+    let ctx = |token: &str, namespace: Option<&Namespace>| ExecContext {
+        run_id: "run-ns".into(),
+        timestamp_ns: 5,
+        caller: None,
+        namespace: namespace.cloned(),
+        api_token: Some(token.into()),
+    };
+
+    // A namespaced token runs in its own namespace, whether or not the context names it
+    assert!(hw.execute(&op, &ctx("bob", Some(&optics))).ok);
+    assert!(hw.execute(&op, &ctx("bob", None)).ok);
+    let res = hw.execute(&op, &ctx("bob", Some(&Namespace::new("chem").unwrap())));
+    assert_eq!(
+        res.details.as_deref(),
+        Some("access denied: bob cannot run in namespace chem")
+    );
+
+    // An unrestricted token reaches every namespace, subject to the policy
+    assert!(hw.execute(&op, &ctx("adm", Some(&optics))).ok);
+    let res = hw.execute(&op, &ctx("adm", None));
+    assert!(res.details.unwrap().starts_with("policy denied"));
+
+    // The policy and calibration approvals are gateway-wide, so a namespaced admin may change
+    // neither, even within its own namespace
+    let confined = |err: anyhow::Error| {
+        matches!(
+            err.downcast_ref::<AccessError>(),
+            Some(AccessError::Confined { name, .. }) if name == "carol"
+        )
+    };
+    let before = hw.policy();
+    assert!(confined(
+        hw.set_policy(&ctx("carol", Some(&optics)), Policy::permissive())
+            .unwrap_err()
+    ));
+    assert_eq!(hw.policy(), before);
+    assert!(confined(
+        hw.approve_calibration(&ctx("carol", Some(&optics)), "cal-9")
+            .unwrap_err()
+    ));
+    assert!(!hw.is_calibration_approved("cal-9"));
+    hw.approve_calibration(&ctx("adm", None), "cal-9").unwrap();
+}
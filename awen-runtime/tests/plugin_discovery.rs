@@ -17,6 +17,8 @@ fn discover_and_register_unverified_manifest() {
         signature: None,
         public_key: None,
         path: None,
+        endpoint: None,
+        permissions: Vec::new(),
     };
 
     // Write manifest file (no signature)
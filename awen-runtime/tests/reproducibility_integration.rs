@@ -1,9 +1,10 @@
 //! Integration tests for reproducibility and artifact system
 
-use awen_runtime::ir::Graph;
+use awen_runtime::engine::Engine;
+use awen_runtime::ir::{Graph, Node};
 use awen_runtime::storage::{
     compute_deterministic_id, export_bundle, import_bundle, short_id, validate_bundle,
-    ArtifactType, BundleBuilder, ExportFormat,
+    verify_replay, ArtifactType, BundleBuilder, CheckStatus, ExportFormat,
 };
 use std::collections::HashMap;
 use tempfile::tempdir;
@@ -257,6 +258,10 @@ fn test_checksum_validation() {
     let result = import_bundle(&exported_path);
     assert!(result.is_ok(), "Checksum validation should pass");
 
+    let manifest = result.unwrap().manifest;
+    assert!(manifest.checksums.contains_key("results/outputs.json"));
+    assert!(!manifest.checksums.contains_key("manifest.json"));
+
     // Corrupt a file
     std::fs::write(exported_path.join("results/outputs.json"), "corrupted").unwrap();
 
@@ -266,4 +271,124 @@ fn test_checksum_validation() {
         result.is_err(),
         "Checksum validation should fail on corrupted file"
     );
+
+    // Every corrupted or missing file is listed, even with checksums.json removed
+    std::fs::remove_file(exported_path.join("parameters/initial.json")).unwrap();
+    std::fs::remove_file(exported_path.join("checksums.json")).unwrap();
+    let err = import_bundle(&exported_path).unwrap_err().to_string();
+    assert!(
+        err.contains(
+            "Checksum mismatch for 2 file(s): parameters/initial.json, results/outputs.json"
+        ),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_import_refuses_unverifiable_bundles() {
+    let temp_dir = tempdir().unwrap();
+    let ir = Graph {
+        nodes: vec![],
+        edges: vec![],
+        metadata: HashMap::new(),
+    };
+    let bundle = BundleBuilder::new(ir.clone(), ArtifactType::Run)
+        .with_results(serde_json::json!({"output": 42}))
+        .build()
+        .unwrap();
+    let exported_path = export_bundle(&bundle, temp_dir.path(), ExportFormat::Directory).unwrap();
+    let checksums_path = exported_path.join("checksums.json");
+    let listed: HashMap<String, String> =
+        serde_json::from_str(&std::fs::read_to_string(&checksums_path).unwrap()).unwrap();
+
+    // checksums.json entries may not reach outside the bundle
+    std::fs::write(temp_dir.path().join("outside.txt"), "secret").unwrap();
+    let outside = temp_dir.path().join("outside.txt");
+    for escaping in ["../outside.txt", outside.to_str().unwrap()] {
+        let mut tampered = listed.clone();
+        tampered.insert(escaping.to_string(), "0".repeat(64));
+        std::fs::write(&checksums_path, serde_json::to_string(&tampered).unwrap()).unwrap();
+        let err = import_bundle(&exported_path).unwrap_err().to_string();
+        assert!(err.contains("leaves the bundle directory"), "{}", err);
+    }
+    std::fs::write(&checksums_path, serde_json::to_string(&listed).unwrap()).unwrap();
+    import_bundle(&exported_path).unwrap();
+
+    // Removing the manifest does not turn a bundle into an unchecked Phase 2.3 run directory
+    std::fs::remove_file(exported_path.join("manifest.json")).unwrap();
+    std::fs::write(
+        exported_path.join("ir.json"),
+        serde_json::to_string(&ir).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(exported_path.join("results.json"), r#"{"output": 7}"#).unwrap();
+    let err = import_bundle(&exported_path).unwrap_err().to_string();
+    assert!(
+        err.contains("refusing to import unverified files"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_verify_replay_against_stored_run() {
+    let temp_dir = tempdir().unwrap();
+    let node = |id: &str, node_type: &str, measurement: Option<&str>| Node {
+        id: id.to_string(),
+        node_type: node_type.to_string(),
+        params: [("angle".to_string(), 0.3)].into_iter().collect(),
+        measure_mode: measurement.map(|_| "mode_0".to_string()),
+        measurement: measurement.map(str::to_string),
+        conditional_branches: None,
+        feed_forward: None,
+    };
+    let ir = Graph {
+        nodes: vec![
+            node("s0", "SQUEEZER", None),
+            node("m0", "DETECTOR", Some("homodyne")),
+        ],
+        edges: vec![],
+        metadata: HashMap::new(),
+    };
+    let run_dir = Engine::new()
+        .run_graph_in(&ir, Some(7), temp_dir.path())
+        .unwrap();
+
+    let bundle = BundleBuilder::new(ir, ArtifactType::Run)
+        .with_initial_parameters(HashMap::new())
+        .with_seed(7)
+        .with_results(serde_json::json!({"run_dir": run_dir}))
+        .with_observability_dir(&run_dir)
+        .build()
+        .unwrap();
+    let exported = export_bundle(
+        &bundle,
+        &temp_dir.path().join("bundles"),
+        ExportFormat::Directory,
+    )
+    .unwrap();
+    let imported = import_bundle(&exported).unwrap();
+
+    let report = verify_replay(&imported).unwrap();
+    assert!(report.passed(), "{}", report.to_text());
+    assert!(
+        report.checks.iter().all(|c| c.status == CheckStatus::Pass),
+        "{}",
+        report.to_text()
+    );
+
+    // A rerun with a different seed no longer matches the stored outcomes
+    let mut reseeded = imported.clone();
+    reseeded.seed = Some(8);
+    let report = verify_replay(&reseeded).unwrap();
+    assert!(!report.passed());
+    let failed: Vec<&str> = report
+        .checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .map(|c| c.name.as_str())
+        .collect();
+    assert!(failed.contains(&"deterministic_id"));
+    assert!(failed.contains(&"measurements"), "{}", report.to_text());
 }
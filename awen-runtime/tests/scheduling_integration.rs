@@ -79,28 +79,36 @@ fn test_critical_path_identification() {
                 node_type: "Source".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "a".to_string(),
                 node_type: "MZI".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "b".to_string(),
                 node_type: "MZI".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "dst".to_string(),
                 node_type: "Detector".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
         ],
         edges: vec![
@@ -195,6 +203,35 @@ fn test_coherence_window_enforcement() {
     println!("✓ Coherence window enforced: all operations within 1000ns window");
 }
 
+#[test]
+fn test_coherence_fidelity_follows_decoherence_model() {
+    // 100ns nodes in a 1000ns window: exponential decay gives e^{-0.1} ≈ 0.905 per node,
+    // Gaussian decay e^{-0.01} ≈ 0.990, so only the Gaussian window clears a 0.95 threshold.
+    let graph = create_mzi_chain_graph(2);
+    let constraints = |model: &str| {
+        let mut window = CoherenceWindow::new("coh_win_0".to_string(), 1_000);
+        window.fidelity_threshold = 0.95;
+        window.decoherence_model = model.to_string();
+        SchedulingConstraints {
+            coherence_windows: vec![window],
+            feedback_loops: vec![],
+            timing_constraints: vec![],
+            resource_limits: create_default_resource_limits(),
+        }
+    };
+
+    let scheduler = StaticScheduler::new();
+    assert!(scheduler
+        .schedule(&graph, &constraints("exponential"), 42)
+        .is_err());
+    assert!(scheduler
+        .schedule(&graph, &constraints("gaussian"), 42)
+        .is_ok());
+    assert!(scheduler
+        .schedule(&graph, &constraints("telegraph"), 42)
+        .is_err());
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Test 4: Feedback Loop Deadline Validation
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -209,21 +246,27 @@ fn test_feedback_loop_deadline_satisfied() {
                 node_type: "Source".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "detector".to_string(),
                 node_type: "Detector".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "control".to_string(),
                 node_type: "MZI".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
         ],
         edges: vec![
@@ -251,6 +294,7 @@ fn test_feedback_loop_deadline_satisfied() {
         control_node: "control".to_string(),
         deadline_ns: 500, // Generous deadline
         priority: Priority::High,
+        violation_action: ViolationAction::Abort,
     };
 
     let constraints = SchedulingConstraints {
@@ -291,14 +335,18 @@ fn test_feedback_loop_deadline_violation() {
                 node_type: "Detector".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "control".to_string(),
                 node_type: "MZI".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
         ],
         edges: vec![Edge {
@@ -318,6 +366,7 @@ fn test_feedback_loop_deadline_violation() {
         control_node: "control".to_string(),
         deadline_ns: 50, // Impossible: edge delay alone is 200ns
         priority: Priority::Critical,
+        violation_action: ViolationAction::Abort,
     };
 
     let constraints = SchedulingConstraints {
@@ -530,42 +579,54 @@ fn test_complex_graph_scheduling() {
                 node_type: "Source".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "laser_1".to_string(),
                 node_type: "Source".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "mzi_0".to_string(),
                 node_type: "MZI".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "mzi_1".to_string(),
                 node_type: "MZI".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "combiner".to_string(),
                 node_type: "Coupler".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
             Node {
                 id: "detector".to_string(),
                 node_type: "Detector".to_string(),
                 params: HashMap::new(),
                 measure_mode: None,
+                measurement: None,
                 conditional_branches: None,
+                feed_forward: None,
             },
         ],
         edges: vec![
@@ -648,7 +709,9 @@ fn create_mzi_chain_graph(num_nodes: usize) -> Graph {
         node_type: "Source".to_string(),
         params: HashMap::new(),
         measure_mode: None,
+        measurement: None,
         conditional_branches: None,
+        feed_forward: None,
     }];
 
     let mut edges = Vec::new();
@@ -659,7 +722,9 @@ fn create_mzi_chain_graph(num_nodes: usize) -> Graph {
             node_type: "MZI".to_string(),
             params: HashMap::new(),
             measure_mode: None,
+            measurement: None,
             conditional_branches: None,
+            feed_forward: None,
         });
 
         let src = if i == 0 {
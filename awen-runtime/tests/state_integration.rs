@@ -1,9 +1,12 @@
 //! Integration tests for memory and state model
 
+use awen_runtime::simulator::{HomodyneSimulator, NoiseInjectionParams, SimulatorNoiseConfig};
 use awen_runtime::state::{
-    CoherenceManager, CoherenceWindow, DelayBuffer, HybridRegister, MemoryPrimitive, QuantumMode,
-    QuantumState, ReferenceCoherenceManager, ReferenceStateEvolver, ResonatorStore, StateEvolver,
+    CoherenceManager, CoherenceWindow, DelayBuffer, HybridRegister, MeasurementKind,
+    MemoryPrimitive, QuantumMode, QuantumState, ReferenceCoherenceManager, ReferenceStateEvolver,
+    ResonatorStore, StateEvolver,
 };
+use num_complex::Complex64;
 use std::collections::HashMap;
 
 #[test]
@@ -18,8 +21,7 @@ fn test_unitary_evolution_deterministic() {
             mode_id: "mode_0".to_string(),
             mode_type: "quantum_fock".to_string(),
             photon_numbers: Some(vec![0, 1]),
-            amplitudes: Some(vec![1.0]),
-            phases: Some(vec![0.0]),
+            amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
         }],
         coherence_window: coherence_window.clone(),
         seed: Some(42),
@@ -35,7 +37,7 @@ fn test_unitary_evolution_deterministic() {
     let state_1b = evolver.evolve_state(&initial_state, "PS", &params).unwrap();
 
     // Should be identical (deterministic)
-    assert_eq!(state_1a.modes[0].phases, state_1b.modes[0].phases);
+    assert_eq!(state_1a.modes[0].amplitudes, state_1b.modes[0].amplitudes);
 }
 
 #[test]
@@ -50,8 +52,7 @@ fn test_measurement_seeded_replay() {
             mode_id: "mode_0".to_string(),
             mode_type: "quantum_fock".to_string(),
             photon_numbers: Some(vec![0, 1]),
-            amplitudes: Some(vec![0.7, 0.3]), // Superposition
-            phases: Some(vec![0.0, 0.0]),
+            amplitudes: Some(vec![Complex64::new(0.7, 0.0), Complex64::new(0.3, 0.0)]), // Superposition
         }],
         coherence_window,
         seed: Some(42),
@@ -67,6 +68,11 @@ fn test_measurement_seeded_replay() {
     assert_eq!(outcome_1.outcome_index, outcome_2.outcome_index);
     assert_eq!(outcome_1.photon_count, outcome_2.photon_count);
     assert_eq!(outcome_1.seed_used, outcome_2.seed_used);
+
+    // The collapsed mode is the measured Fock state, renormalised
+    let collapsed = outcome_1.collapsed_state.unwrap();
+    let amplitudes = collapsed.modes[0].amplitudes.as_ref().unwrap();
+    assert!((amplitudes[outcome_1.outcome_index as usize].norm() - 1.0).abs() < 1e-12);
 }
 
 #[test]
@@ -90,6 +96,32 @@ fn test_coherence_window_enforcement() {
     assert!(manager.validate_coherence(&state, 15_000).is_err());
 }
 
+#[test]
+fn test_coherence_window_decoherence_model() {
+    let manager = ReferenceCoherenceManager;
+    let evolver = ReferenceStateEvolver;
+    let state = |model: &str| QuantumState {
+        id: "state_0".to_string(),
+        modes: vec![],
+        coherence_window: manager.create_window(0, 10_000, model).unwrap(),
+        seed: None,
+        provenance: HashMap::new(),
+    };
+
+    // 500ns timescale, 0.95 threshold: at 100ns the Gaussian window keeps e^{-0.04} ≈ 0.96,
+    // the exponential one only e^{-0.2} ≈ 0.82.
+    let gaussian = state("gaussian");
+    assert_eq!(gaussian.coherence_window.decoherence_model, "gaussian");
+    assert!(evolver.is_coherent(&gaussian, 100));
+    assert!(!evolver.is_coherent(&gaussian, 300));
+    assert!(!evolver.is_coherent(&state("exponential"), 100));
+    assert_eq!(
+        state("one_over_f").coherence_window.decoherence_model,
+        "1/f"
+    );
+    assert!(manager.create_window(0, 10_000, "telegraph").is_err());
+}
+
 #[test]
 fn test_delay_buffer_fifo_semantics() {
     let mut buffer = DelayBuffer::new("delay_0".to_string(), 1000, 0.5, 10_000);
@@ -98,16 +130,14 @@ fn test_delay_buffer_fifo_semantics() {
         mode_id: "mode_1".to_string(),
         mode_type: "classical".to_string(),
         photon_numbers: None,
-        amplitudes: Some(vec![1.0]),
-        phases: Some(vec![0.0]),
+        amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
     };
 
     let mode_2 = QuantumMode {
         mode_id: "mode_2".to_string(),
         mode_type: "classical".to_string(),
         photon_numbers: None,
-        amplitudes: Some(vec![0.8]),
-        phases: Some(vec![0.5]),
+        amplitudes: Some(vec![Complex64::from_polar(0.8, 0.5)]),
     };
 
     // Write mode_1 at t=0, mode_2 at t=100
@@ -136,8 +166,7 @@ fn test_resonator_exponential_decay() {
         mode_id: "mode_0".to_string(),
         mode_type: "quantum_fock".to_string(),
         photon_numbers: Some(vec![0, 1]),
-        amplitudes: Some(vec![1.0]),
-        phases: Some(vec![0.0]),
+        amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
     };
 
     // Write at t=0
@@ -145,13 +174,13 @@ fn test_resonator_exponential_decay() {
 
     // Read at different times and verify exponential decay
     let read_1 = resonator.read(0).unwrap().unwrap();
-    let amp_1 = read_1.amplitudes.unwrap()[0];
+    let amp_1 = read_1.amplitudes.unwrap()[0].norm();
 
     let read_2 = resonator.read(500).unwrap().unwrap();
-    let amp_2 = read_2.amplitudes.unwrap()[0];
+    let amp_2 = read_2.amplitudes.unwrap()[0].norm();
 
     let read_3 = resonator.read(1000).unwrap().unwrap();
-    let amp_3 = read_3.amplitudes.unwrap()[0];
+    let amp_3 = read_3.amplitudes.unwrap()[0].norm();
 
     // Verify exponential decay: amp(t) = amp(0) * exp(-t/τ)
     let _eff = resonator.write_efficiency.sqrt() * resonator.read_efficiency.sqrt();
@@ -202,8 +231,7 @@ fn test_state_provenance_tracking() {
             mode_id: "mode_0".to_string(),
             mode_type: "quantum_fock".to_string(),
             photon_numbers: Some(vec![0, 1]),
-            amplitudes: Some(vec![1.0]),
-            phases: Some(vec![0.0]),
+            amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
         }],
         coherence_window,
         seed: Some(42),
@@ -222,53 +250,99 @@ fn test_state_provenance_tracking() {
     assert_eq!(evolved_state.provenance.get("origin").unwrap(), "test");
 }
 
-#[test]
-fn test_beam_splitter_coupling() {
-    let evolver = ReferenceStateEvolver;
-
-    let coherence_window = CoherenceWindow::new("test_window".to_string(), 10_000);
-
-    let state = QuantumState {
+/// Modes "0" and "1" holding the real Fock amplitudes `c1` and `c2`.
+fn two_mode_state(c1: Vec<f64>, c2: Vec<f64>) -> QuantumState {
+    let mode = |id: &str, c: Vec<f64>| QuantumMode {
+        mode_id: id.to_string(),
+        mode_type: "quantum_fock".to_string(),
+        photon_numbers: Some((0..c.len() as u32).collect()),
+        amplitudes: Some(c.into_iter().map(|a| Complex64::new(a, 0.0)).collect()),
+    };
+    QuantumState {
         id: "state_0".to_string(),
-        modes: vec![
-            QuantumMode {
-                mode_id: "0".to_string(),
-                mode_type: "quantum_fock".to_string(),
-                photon_numbers: Some(vec![0, 1]),
-                amplitudes: Some(vec![1.0]),
-                phases: Some(vec![0.0]),
-            },
-            QuantumMode {
-                mode_id: "1".to_string(),
-                mode_type: "quantum_fock".to_string(),
-                photon_numbers: Some(vec![0, 1]),
-                amplitudes: Some(vec![0.0]),
-                phases: Some(vec![0.0]),
-            },
-        ],
-        coherence_window,
+        modes: vec![mode("0", c1), mode("1", c2)],
+        coherence_window: CoherenceWindow::new("test_window".to_string(), 10_000),
         seed: Some(42),
         provenance: HashMap::new(),
-    };
+    }
+}
 
-    // Apply 50:50 beam splitter (θ = π/4)
+fn beam_splitter(state: &QuantumState, theta: f64) -> QuantumState {
     let mut params = HashMap::new();
     params.insert("mode1".to_string(), 0.0);
     params.insert("mode2".to_string(), 1.0);
-    params.insert("theta".to_string(), std::f64::consts::FRAC_PI_4);
-
-    let evolved_state = evolver.evolve_state(&state, "BS", &params).unwrap();
+    params.insert("theta".to_string(), theta);
+    ReferenceStateEvolver
+        .evolve_state(state, "BS", &params)
+        .unwrap()
+}
 
-    // Both modes should now have non-zero amplitude
-    let amp_0 = evolved_state.modes[0].amplitudes.as_ref().unwrap()[0];
-    let amp_1 = evolved_state.modes[1].amplitudes.as_ref().unwrap()[0];
+fn populations(mode: &QuantumMode) -> Vec<f64> {
+    let amps = mode.amplitudes.as_ref().unwrap();
+    amps.iter().map(|a| a.norm_sqr()).collect()
+}
 
-    assert!(amp_0.abs() > 0.1);
-    assert!(amp_1.abs() > 0.1);
+#[test]
+fn test_beam_splitter_coupling() {
+    // |1, 0⟩ through a 50:50 splitter: the photon leaves either port with probability ½.
+    let single_photon = two_mode_state(vec![0.0, 1.0], vec![1.0, 0.0]);
+    let evolved_state = beam_splitter(&single_photon, std::f64::consts::FRAC_PI_4);
+    for mode in &evolved_state.modes {
+        assert_eq!(mode.mode_type, "mixed");
+        let p = populations(mode);
+        assert!((p[0] - 0.5).abs() < 1e-12 && (p[1] - 0.5).abs() < 1e-12);
+    }
+
+    // Energy conservation: mean photon numbers still sum to 1
+    let total_energy: f64 = evolved_state
+        .modes
+        .iter()
+        .flat_map(|m| populations(m).into_iter().enumerate())
+        .map(|(n, p)| n as f64 * p)
+        .sum();
+    assert!((total_energy - 1.0).abs() < 1e-12);
+
+    // θ = π/2 swaps the modes and keeps them pure.
+    let swapped = beam_splitter(&single_photon, std::f64::consts::FRAC_PI_2);
+    assert_eq!(swapped.modes[0].mode_type, "quantum_fock");
+    assert!((populations(&swapped.modes[0])[0] - 1.0).abs() < 1e-12);
+    assert!((populations(&swapped.modes[1])[1] - 1.0).abs() < 1e-12);
+}
 
-    // Energy conservation: |a0|² + |a1|² ≈ 1
-    let total_energy = amp_0.powi(2) + amp_1.powi(2);
-    assert!((total_energy - 1.0).abs() < 0.1);
+#[test]
+fn test_beam_splitter_hong_ou_mandel() {
+    // |1, 1⟩ → (|2, 0⟩ − |0, 2⟩)/√2: the photons bunch and never leave one per port.
+    let evolver = ReferenceStateEvolver;
+    let pair = two_mode_state(vec![0.0, 1.0], vec![0.0, 1.0]);
+    let bunched = beam_splitter(&pair, std::f64::consts::FRAC_PI_4);
+    for mode in &bunched.modes {
+        assert_eq!(mode.photon_numbers, Some(vec![0, 1, 2]));
+        let p = populations(mode);
+        assert!((p[0] - 0.5).abs() < 1e-12 && p[1] < 1e-12 && (p[2] - 0.5).abs() < 1e-12);
+        for seed in 0..20 {
+            let outcome = evolver
+                .measure(&bunched, &mode.mode_id, Some(seed))
+                .unwrap();
+            assert_ne!(outcome.photon_count, 1);
+        }
+    }
+
+    // Away from 50:50 the coincidence amplitude cos 2θ returns.
+    let unbalanced = beam_splitter(&pair, 0.3);
+    let p = populations(&unbalanced.modes[0]);
+    assert!((p[1] - (0.6f64).cos().powi(2)).abs() < 1e-12);
+
+    // A cutoff below the bunched states keeps only the coincidence term.
+    let mut params = HashMap::new();
+    params.insert("mode1".to_string(), 0.0);
+    params.insert("mode2".to_string(), 1.0);
+    params.insert("theta".to_string(), 0.3);
+    params.insert("cutoff".to_string(), 1.0);
+    let truncated = evolver.evolve_state(&pair, "BS", &params).unwrap();
+    assert_eq!(truncated.modes[0].mode_type, "quantum_fock");
+    assert!((populations(&truncated.modes[0])[1] - 1.0).abs() < 1e-12);
+    params.insert("theta".to_string(), std::f64::consts::FRAC_PI_4);
+    assert!(evolver.evolve_state(&pair, "BS", &params).is_err());
 }
 
 #[test]
@@ -302,9 +376,8 @@ fn test_state_evolution_sequence() {
         modes: vec![QuantumMode {
             mode_id: "0".to_string(),
             mode_type: "quantum_fock".to_string(),
-            photon_numbers: Some(vec![0, 1]),
-            amplitudes: Some(vec![1.0]),
-            phases: Some(vec![0.0]),
+            photon_numbers: Some(vec![0]),
+            amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
         }],
         coherence_window,
         seed: Some(42),
@@ -336,3 +409,491 @@ fn test_state_evolution_sequence() {
         .unwrap()
         .contains("SQUEEZING"));
 }
+
+/// Single-mode state with amplitudes `magnitudes[n] · e^{i phases[n]}` on `|n⟩`.
+fn fock_state(magnitudes: Vec<f64>, phases: Vec<f64>) -> QuantumState {
+    let amplitudes: Vec<Complex64> = magnitudes
+        .iter()
+        .zip(&phases)
+        .map(|(&r, &phi)| Complex64::from_polar(r, phi))
+        .collect();
+    QuantumState {
+        id: "state_0".to_string(),
+        modes: vec![QuantumMode {
+            mode_id: "mode_0".to_string(),
+            mode_type: "quantum_fock".to_string(),
+            photon_numbers: Some((0..amplitudes.len() as u32).collect()),
+            amplitudes: Some(amplitudes),
+        }],
+        coherence_window: CoherenceWindow::new("test_window".to_string(), 10_000),
+        seed: Some(42),
+        provenance: HashMap::new(),
+    }
+}
+
+fn quadrature_moments(
+    state: &QuantumState,
+    kind: MeasurementKind,
+    noise: Option<&HomodyneSimulator>,
+    shots: u64,
+) -> Vec<(f64, f64)> {
+    let evolver = ReferenceStateEvolver;
+    let samples: Vec<Vec<f64>> = (0..shots)
+        .map(|seed| {
+            evolver
+                .measure_quadrature(state, "mode_0", kind, noise, Some(seed))
+                .unwrap()
+                .quadratures
+                .unwrap()
+        })
+        .collect();
+    (0..samples[0].len())
+        .map(|k| {
+            let mean = samples.iter().map(|s| s[k]).sum::<f64>() / shots as f64;
+            let var = samples.iter().map(|s| (s[k] - mean).powi(2)).sum::<f64>() / shots as f64;
+            (mean, var)
+        })
+        .collect()
+}
+
+#[test]
+fn test_homodyne_variances() {
+    // Vacuum: ⟨x⟩ = 0, Var = ½. Single photon: Var = 3/2 at every angle.
+    let vacuum = fock_state(vec![1.0, 0.0], vec![0.0, 0.0]);
+    let (mean, var) = quadrature_moments(
+        &vacuum,
+        MeasurementKind::Homodyne { angle: 0.0 },
+        None,
+        2000,
+    )[0];
+    assert!(mean.abs() < 0.06, "vacuum mean {}", mean);
+    assert!((var - 0.5).abs() < 0.06, "vacuum variance {}", var);
+
+    let one = fock_state(vec![0.0, 1.0], vec![0.0, 0.0]);
+    let (_, var) =
+        quadrature_moments(&one, MeasurementKind::Homodyne { angle: 0.7 }, None, 2000)[0];
+    assert!((var - 1.5).abs() < 0.15, "single-photon variance {}", var);
+
+    // (|0⟩ + |1⟩)/√2 has ⟨q⟩ = 1/√2 and ⟨p⟩ = 0.
+    let plus = fock_state(vec![1.0, 1.0], vec![0.0, 0.0]);
+    let (mean_q, _) =
+        quadrature_moments(&plus, MeasurementKind::Homodyne { angle: 0.0 }, None, 2000)[0];
+    let (mean_p, _) = quadrature_moments(
+        &plus,
+        MeasurementKind::Homodyne {
+            angle: std::f64::consts::FRAC_PI_2,
+        },
+        None,
+        2000,
+    )[0];
+    assert!((mean_q - std::f64::consts::FRAC_1_SQRT_2).abs() < 0.08);
+    assert!(mean_p.abs() < 0.08);
+}
+
+#[test]
+fn test_heterodyne_adds_vacuum_noise() {
+    let vacuum = fock_state(vec![1.0], vec![0.0]);
+    let moments = quadrature_moments(&vacuum, MeasurementKind::Heterodyne, None, 600);
+    assert_eq!(moments.len(), 2);
+    for (mean, var) in moments {
+        assert!(mean.abs() < 0.15, "heterodyne mean {}", mean);
+        assert!((var - 1.0).abs() < 0.15, "heterodyne variance {}", var);
+    }
+}
+
+#[test]
+fn test_quadrature_measurement_replay_and_noise() {
+    let evolver = ReferenceStateEvolver;
+    let state = fock_state(vec![0.8, 0.6], vec![0.0, 0.3]);
+    let kind = MeasurementKind::Homodyne { angle: 0.2 };
+
+    let a = evolver
+        .measure_quadrature(&state, "mode_0", kind, None, Some(7))
+        .unwrap();
+    let b = evolver
+        .measure_quadrature(&state, "mode_0", kind, None, Some(7))
+        .unwrap();
+    assert_eq!(a.quadratures, b.quadratures);
+    assert_eq!(a.kind, kind);
+    assert_eq!(
+        a.outcome_index,
+        (a.quadratures.as_ref().unwrap()[0] >= 0.0) as u32
+    );
+    // The detector absorbs the mode.
+    let collapsed = a.collapsed_state.unwrap();
+    assert_eq!(
+        collapsed.modes[0].amplitudes,
+        Some(vec![Complex64::new(1.0, 0.0), Complex64::new(0.0, 0.0)])
+    );
+
+    // Photon counting is not a quadrature readout.
+    assert!(evolver
+        .measure_quadrature(
+            &state,
+            "mode_0",
+            MeasurementKind::PhotonCount,
+            None,
+            Some(7)
+        )
+        .is_err());
+
+    // Thermal excess noise from the homodyne simulator adds to the shot noise.
+    let noise = HomodyneSimulator {
+        config: SimulatorNoiseConfig {
+            relative_intensity_noise: 0.0,
+            ..Default::default()
+        },
+        noise_params: NoiseInjectionParams {
+            lo_phase_noise: 0.0,
+            lo_frequency_noise: 0.0,
+            shot_noise_variance: 0.5,
+            thermal_noise_variance: 0.5,
+            kerr_phase_shift: 0.0,
+        },
+    };
+    let vacuum = fock_state(vec![1.0], vec![0.0]);
+    let (_, var) = quadrature_moments(
+        &vacuum,
+        MeasurementKind::Homodyne { angle: 0.0 },
+        Some(&noise),
+        2000,
+    )[0];
+    assert!((var - 1.0).abs() < 0.1, "noisy homodyne variance {}", var);
+}
+
+#[test]
+fn test_measurement_kind_from_detector_node() {
+    let mut params = HashMap::new();
+    assert_eq!(
+        MeasurementKind::from_node(None, &params).unwrap(),
+        MeasurementKind::PhotonCount
+    );
+    params.insert("angle".to_string(), 0.5);
+    assert_eq!(
+        MeasurementKind::from_node(Some("homodyne"), &params).unwrap(),
+        MeasurementKind::Homodyne { angle: 0.5 }
+    );
+    assert_eq!(
+        MeasurementKind::from_node(Some("Heterodyne"), &params).unwrap(),
+        MeasurementKind::Heterodyne
+    );
+    assert!(MeasurementKind::from_node(Some("parity"), &params).is_err());
+}
+
+#[test]
+fn test_displacement_gate() {
+    // D(α)|0⟩ is coherent: |c_n|² Poissonian with mean |α|², ⟨q⟩ = √2 Re α.
+    let evolver = ReferenceStateEvolver;
+    let mut amplitudes = vec![0.0; 16];
+    amplitudes[0] = 1.0;
+    let vacuum = fock_state(amplitudes, vec![0.0; 16]);
+    let mut params = HashMap::new();
+    params.insert("mode_id".to_string(), 0.0);
+    params.insert("q".to_string(), 1.0);
+    let displaced = evolver
+        .evolve_state(&vacuum, "DISPLACEMENT", &params)
+        .unwrap();
+    let amps = displaced.modes[0].amplitudes.as_ref().unwrap();
+    let mean_photons: f64 = amps
+        .iter()
+        .enumerate()
+        .map(|(n, a)| n as f64 * a.norm_sqr())
+        .sum();
+    assert!((mean_photons - 0.5).abs() < 1e-9);
+    assert!((amps[0].norm_sqr() - (-0.5f64).exp()).abs() < 1e-9);
+
+    let (mean, var) = quadrature_moments(
+        &displaced,
+        MeasurementKind::Homodyne { angle: 0.0 },
+        None,
+        1000,
+    )[0];
+    assert!((mean - 1.0).abs() < 0.08, "displaced mean {}", mean);
+    assert!((var - 0.5).abs() < 0.08, "displaced variance {}", var);
+}
+
+/// Exact `(⟨x_φ⟩, Var x_φ)` of a Fock-amplitude vector, `x_φ = (a e^{−iφ} + a† e^{iφ})/√2`.
+fn exact_quadrature_moments(amps: &[Complex64], angle: f64) -> (f64, f64) {
+    let level = |n: usize| amps.get(n).copied().unwrap_or_default();
+    let a: Complex64 = (1..amps.len())
+        .map(|n| level(n - 1).conj() * level(n) * (n as f64).sqrt())
+        .sum();
+    let a2: Complex64 = (2..amps.len())
+        .map(|n| level(n - 2).conj() * level(n) * ((n * (n - 1)) as f64).sqrt())
+        .sum();
+    let n: f64 = amps
+        .iter()
+        .enumerate()
+        .map(|(n, c)| n as f64 * c.norm_sqr())
+        .sum();
+    let rotation = Complex64::from_polar(1.0, -angle);
+    let mean = std::f64::consts::SQRT_2 * (a * rotation).re;
+    let second = (a2 * rotation * rotation).re + n + 0.5;
+    (mean, second - mean * mean)
+}
+
+#[test]
+fn test_squeezing_gate() {
+    // S(r)|0⟩: P(0) = 1/cosh r, ⟨n⟩ = sinh² r, Var q = e^{−2r}/2, Var p = e^{2r}/2.
+    let evolver = ReferenceStateEvolver;
+    let r = 0.5;
+    let vacuum = fock_state(vec![1.0], vec![0.0]);
+    let mut params = HashMap::new();
+    params.insert("mode_id".to_string(), 0.0);
+    params.insert("r".to_string(), r);
+    let squeezed = evolver.evolve_state(&vacuum, "SQUEEZING", &params).unwrap();
+    let amps = squeezed.modes[0].amplitudes.as_ref().unwrap();
+    assert!((amps[0].norm_sqr() - 1.0 / r.cosh()).abs() < 1e-12);
+    assert!(amps.iter().skip(1).step_by(2).all(|c| c.norm() < 1e-15));
+    let mean_photons: f64 = amps
+        .iter()
+        .enumerate()
+        .map(|(n, a)| n as f64 * a.norm_sqr())
+        .sum();
+    assert!((mean_photons - r.sinh().powi(2)).abs() < 1e-12);
+
+    let (_, var_q) = exact_quadrature_moments(amps, 0.0);
+    let (_, var_p) = exact_quadrature_moments(amps, std::f64::consts::FRAC_PI_2);
+    assert!((var_q - 0.5 * (-2.0 * r).exp()).abs() < 1e-12);
+    assert!((var_p - 0.5 * (2.0 * r).exp()).abs() < 1e-12);
+
+    let (_, sampled_q) = quadrature_moments(
+        &squeezed,
+        MeasurementKind::Homodyne { angle: 0.0 },
+        None,
+        400,
+    )[0];
+    assert!(
+        (sampled_q - var_q).abs() < 0.05,
+        "squeezed variance {}",
+        sampled_q
+    );
+
+    // θ = π squeezes p instead.
+    params.insert("angle".to_string(), std::f64::consts::PI);
+    let rotated = evolver.evolve_state(&vacuum, "SQUEEZING", &params).unwrap();
+    let amps = rotated.modes[0].amplitudes.as_ref().unwrap();
+    let (_, var_p) = exact_quadrature_moments(amps, std::f64::consts::FRAC_PI_2);
+    assert!((var_p - 0.5 * (-2.0 * r).exp()).abs() < 1e-12);
+
+    // S(−r) S(r) is the identity, including on photon-number states.
+    let one = fock_state(vec![0.0, 1.0], vec![0.0, 0.0]);
+    params.insert("angle".to_string(), 0.3);
+    let forward = evolver.evolve_state(&one, "SQUEEZING", &params).unwrap();
+    params.insert("r".to_string(), -r);
+    let back = evolver
+        .evolve_state(&forward, "SQUEEZING", &params)
+        .unwrap();
+    let amps = back.modes[0].amplitudes.as_ref().unwrap();
+    assert!((amps[1].norm_sqr() - 1.0).abs() < 1e-9);
+    assert!(amps[1].im.abs() < 1e-9);
+}
+
+#[test]
+fn test_pdc_two_mode_squeezed_vacuum() {
+    // Signal and idler are each thermal with P(n) = tanh^{2n} r / cosh² r and ⟨n⟩ = sinh² r;
+    // counting one heralds the same photon number in the other.
+    let evolver = ReferenceStateEvolver;
+    let r = 0.6;
+    let vacuum = two_mode_state(vec![1.0], vec![1.0]);
+    let mut params = HashMap::new();
+    params.insert("signal_id".to_string(), 0.0);
+    params.insert("idler_id".to_string(), 1.0);
+    params.insert("gain".to_string(), r);
+    let pair = evolver.evolve_state(&vacuum, "PDC", &params).unwrap();
+    for mode in &pair.modes {
+        assert_eq!(mode.mode_type, "mixed");
+        let p = populations(mode);
+        for (n, pn) in p.iter().enumerate().take(5) {
+            assert!((pn - r.tanh().powi(2 * n as i32) / r.cosh().powi(2)).abs() < 1e-12);
+        }
+        let mean: f64 = p.iter().enumerate().map(|(n, pn)| n as f64 * pn).sum();
+        assert!((mean - r.sinh().powi(2)).abs() < 1e-12);
+    }
+
+    let mut heralded = 0;
+    for seed in 0..40 {
+        let idler = evolver.measure(&pair, "1", Some(seed)).unwrap();
+        let collapsed = idler.collapsed_state.unwrap();
+        assert_eq!(collapsed.modes[0].mode_type, "quantum_fock");
+        let signal = evolver.measure(&collapsed, "0", Some(seed + 100)).unwrap();
+        assert_eq!(signal.photon_count, idler.photon_count);
+        assert_eq!(signal.probability, 1.0);
+        heralded += (idler.photon_count == 1) as u32;
+    }
+    assert!(heralded > 0);
+
+    // A beam splitter on the signal breaks the pairing; occupied inputs are rejected.
+    let mut bs = HashMap::new();
+    bs.insert("mode1".to_string(), 0.0);
+    bs.insert("mode2".to_string(), 1.0);
+    bs.insert("theta".to_string(), 0.2);
+    let mixed = evolver.evolve_state(&pair, "BS", &bs).unwrap();
+    assert!(!mixed
+        .provenance
+        .keys()
+        .any(|k| k.starts_with("pdc_partner:")));
+    let one = two_mode_state(vec![0.0, 1.0], vec![1.0]);
+    assert!(evolver.evolve_state(&one, "PDC", &params).is_err());
+}
+
+#[test]
+fn test_hybrid_cv_dv_conversion() {
+    let evolver = ReferenceStateEvolver;
+    let r = 0.5;
+    let mut state = two_mode_state(vec![1.0], vec![1.0]);
+    state
+        .modes
+        .push(fock_state(vec![1.0], vec![0.0]).modes.remove(0));
+    state.modes[2].mode_id = "2".to_string();
+    let gate = |state: &QuantumState, gate: &str, params: &[(&str, f64)]| {
+        let params: HashMap<String, f64> =
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        evolver.evolve_state(state, gate, &params)
+    };
+
+    // Squeezing makes mode 2 a CV resource; a PDC pair heralded on one photon gives a DV one.
+    let state = gate(&state, "SQUEEZING", &[("mode_id", 2.0), ("r", r)]).unwrap();
+    assert_eq!(state.cv_modes(), vec!["2"]);
+    let pair = gate(
+        &state,
+        "PDC",
+        &[("signal_id", 0.0), ("idler_id", 1.0), ("gain", 0.6)],
+    )
+    .unwrap();
+    assert!(!pair.is_hybrid());
+    let heralded = gate(&pair, "HERALD", &[("mode_id", 1.0), ("count", 1.0)]).unwrap();
+    assert!(heralded.is_hybrid());
+    assert_eq!(heralded.dv_modes(), vec!["0", "1"]);
+    assert!((populations(&heralded.modes[0])[1] - 1.0).abs() < 1e-12);
+    assert!((populations(&heralded.modes[1])[0] - 1.0).abs() < 1e-12);
+    let p1 = heralded.provenance["herald_probability:1"]
+        .parse::<f64>()
+        .unwrap();
+    assert!((p1 - populations(&pair.modes[1])[1]).abs() < 1e-12);
+    assert!(gate(&pair, "HERALD", &[("mode_id", 1.0), ("count", 400.0)]).is_err());
+
+    // Subtracting a photon from squeezed vacuum: a S(r)|0⟩ ∝ S(r)|1⟩, ⟨n⟩ = 1 + 3 sinh² r,
+    // heralded with probability ≈ R⟨n⟩.
+    let subtracted = gate(
+        &heralded,
+        "PHOTON_SUBTRACT",
+        &[("mode_id", 2.0), ("reflectivity", 1e-6)],
+    )
+    .unwrap();
+    let mode = &subtracted.modes[2];
+    assert_eq!(mode.mode_type, "cv");
+    let p = populations(mode);
+    assert!(p.iter().step_by(2).all(|pn| *pn < 1e-12));
+    let mean: f64 = p.iter().enumerate().map(|(n, pn)| n as f64 * pn).sum();
+    assert!((mean - (1.0 + 3.0 * r.sinh().powi(2))).abs() < 1e-4);
+    let herald = subtracted.provenance["herald_probability:2"]
+        .parse::<f64>()
+        .unwrap();
+    assert!((herald / 1e-6 - r.sinh().powi(2)).abs() < 1e-4);
+    assert!(gate(&heralded, "PHOTON_SUBTRACT", &[("mode_id", 1.0)]).is_err());
+}
+
+#[test]
+fn test_backend_state_round_trip() {
+    let s = std::f64::consts::FRAC_1_SQRT_2;
+    let mut state = fock_state(vec![s, 0.0, s], vec![0.0, 0.0, 0.4]);
+    state.modes.push(QuantumMode {
+        mode_id: "mode_1".to_string(),
+        mode_type: "quantum_fock".to_string(),
+        photon_numbers: Some(vec![1]),
+        amplitudes: Some(vec![Complex64::new(1.0, 0.0)]),
+    });
+
+    let backend = state.to_backend_state().unwrap();
+    assert_eq!(backend.state_id, state.id);
+    assert_eq!(backend.mode_labels, vec!["mode_0", "mode_1"]);
+    assert_eq!(backend.seed, 42);
+    assert_eq!(
+        backend.dv_data.as_ref().unwrap().qudits["mode_1"].dimension,
+        2
+    );
+
+    let back = QuantumState::from_backend_state(&backend).unwrap();
+    assert_eq!(back.id, state.id);
+    assert_eq!(back.coherence_window.duration_ns, 10_000);
+    // Amplitudes come back up to a global phase.
+    let amps = back.modes[0].amplitudes.as_ref().unwrap();
+    assert!((amps[0].norm() - s).abs() < 1e-12 && amps[1].norm() < 1e-12);
+    assert!((amps[2].norm() - s).abs() < 1e-12);
+    assert!(((amps[2] / amps[0]).arg() - 0.4).abs() < 1e-12);
+    assert_eq!(
+        back.modes[1].amplitudes,
+        Some(vec![Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0)])
+    );
+
+    let window = state.coherence_window.to_backend_window(&backend.state_id);
+    assert_eq!(window.coherence_time_ns, 10_000);
+    assert!(state.select_modes(&["missing".to_string()]).is_err());
+}
+
+#[test]
+fn test_backend_state_entangled_modes_become_mixed() {
+    use awen_runtime::quantum::{BellType, PreparationKind, QuantumBackend, StatevectorSimulator};
+    let mut backend = StatevectorSimulator::new();
+    let bell = backend
+        .prepare(
+            vec!["a".to_string(), "b".to_string()],
+            &PreparationKind::BellState {
+                entanglement_type: BellType::PhiPlus,
+            },
+            3,
+        )
+        .unwrap();
+    let state = QuantumState::from_backend_state(&bell).unwrap();
+    for mode in &state.modes {
+        assert_eq!(mode.mode_type, "mixed");
+        let amps = mode.amplitudes.as_ref().unwrap();
+        assert!(amps
+            .iter()
+            .all(|a| (a - std::f64::consts::FRAC_1_SQRT_2).norm() < 1e-12));
+    }
+
+    let cv = awen_runtime::quantum::QuantumState::new_cv(vec!["q".to_string()], 0, 100);
+    assert!(QuantumState::from_backend_state(&cv).is_err());
+}
+
+#[test]
+fn test_outcome_to_backend_outcome() {
+    use awen_runtime::quantum::{BasisType, MeasurementResult};
+    let evolver = ReferenceStateEvolver;
+    let state = fock_state(vec![0.6, 0.8], vec![0.0, 0.0]);
+
+    let count = evolver
+        .measure(&state, "mode_0", Some(7))
+        .unwrap()
+        .to_backend_outcome("mode_0")
+        .unwrap();
+    assert!(matches!(count.measurement_type, BasisType::Computational));
+    assert!(matches!(
+        count.classical_results["mode_0"],
+        MeasurementResult::DiscreteOutcome(_)
+    ));
+    assert!(count.matches_seed(7));
+
+    let heterodyne = evolver
+        .measure_quadrature(&state, "mode_0", MeasurementKind::Heterodyne, None, Some(7))
+        .unwrap();
+    let backend = heterodyne.to_backend_outcome("mode_0").unwrap();
+    let q = heterodyne.quadratures.as_ref().unwrap()[0];
+    assert!(matches!(
+        backend.classical_results["mode_0:q"],
+        MeasurementResult::ContinuousValue(x) if x == q
+    ));
+
+    let tilted = evolver
+        .measure_quadrature(
+            &state,
+            "mode_0",
+            MeasurementKind::Homodyne { angle: 0.3 },
+            None,
+            Some(7),
+        )
+        .unwrap();
+    assert!(tilted.to_backend_outcome("mode_0").is_err());
+}